@@ -22,12 +22,23 @@ pub enum AdasAlert {
     
     /// Pedestrian in path
     PedestrianWarning { distance_m: f32 },
+
+    /// Pedestrian or cyclist within the vulnerable-road-user warning range
+    VulnerableUserAhead {
+        class: ObjectClass,
+        distance_m: f32,
+        ttc_s: Option<f32>,
+    },
     
     /// Stop sign detected
     StopSignDetected,
     
-    /// Tailgating warning (too close)
-    Tailgating { distance_m: f32 },
+    /// Headway to the lead vehicle has stayed below the tailgating
+    /// threshold for the sustain duration
+    Tailgating { headway_s: f32 },
+
+    /// Road camera stopped delivering frames
+    CameraOffline,
 }
 
 /// Complete ADAS analysis result
@@ -44,14 +55,28 @@ pub struct AdasAnalysis {
     
     /// Active alerts
     pub alerts: Vec<AdasAlert>,
+
+    /// Models were skipped for this frame; the result repeats the last
+    /// processed frame
+    #[serde(default)]
+    pub skipped: bool,
 }
 
 impl AdasAnalysis {
+    /// Result to publish while the road camera is stalled
+    pub fn camera_offline() -> Self {
+        Self {
+            alerts: vec![AdasAlert::CameraOffline],
+            ..Default::default()
+        }
+    }
+
     /// Check if any critical alerts
     pub fn has_critical_alerts(&self) -> bool {
         self.alerts.iter().any(|a| matches!(a, 
             AdasAlert::ForwardCollision { .. } | 
-            AdasAlert::PedestrianWarning { .. }
+            AdasAlert::PedestrianWarning { .. } |
+            AdasAlert::VulnerableUserAhead { .. }
         ))
     }
 }
@@ -1,42 +1,151 @@
 //! ADAS configuration
 
+use camera_capture::{ClaheConfig, FrameSkip, MockConfig, Normalization};
 use serde::{Deserialize, Serialize};
 
+use crate::{CameraGeometry, ObjectClass, RoiMask, TilingConfig, TrafficSign};
+
 /// ADAS configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdasConfig {
     /// Forward collision warning distance (meters)
     pub fcw_distance_m: f32,
+
+    /// Warning distance for pedestrians and cyclists (meters); kept above
+    /// `fcw_distance_m` since they can step into the lane without warning
+    pub vru_warning_distance_m: f32,
+
+    /// Time-to-collision below which a pedestrian or cyclist warning fires
+    /// regardless of distance (seconds)
+    pub vru_warning_ttc_s: f32,
+    
+    /// Headway below which following counts as tailgating (seconds)
+    pub tailgating_headway_s: f32,
+
+    /// How long headway must stay below the threshold before alerting (ms)
+    pub tailgating_sustain_ms: u64,
+
+    /// Ego speed below which headway is not scored, e.g. queueing (km/h)
+    pub tailgating_min_speed_kmh: f32,
     
     /// Lane departure warning enabled
     pub lane_departure_enabled: bool,
     
     /// Object detection confidence threshold
     pub object_confidence: f32,
+
+    /// Object classes to keep after decoding; everything else is dropped
+    pub object_classes: Vec<ObjectClass>,
+
+    /// IoU above which overlapping boxes of the same class are merged by NMS
+    pub nms_iou_threshold: f32,
+
+    /// Drivable region; objects centered outside it and lane points off
+    /// the road are discarded. `None` uses the whole frame
+    pub roi_mask: Option<RoiMask>,
     
     /// Lane detection confidence threshold
     pub lane_confidence: f32,
+
+    /// Assumed lane width when no ground-plane scale is known (meters)
+    pub nominal_lane_width_m: f32,
+
+    /// Ground-plane scale at the bottom of the road image (meters per pixel),
+    /// if known from mounting calibration
+    pub lane_meters_per_pixel: Option<f32>,
+    
+    /// Road camera intrinsics and mounting, used for object distance
+    pub camera_geometry: CameraGeometry,
     
     /// Traffic sign detection enabled
     pub sign_detection_enabled: bool,
+
+    /// Sign detection confidence threshold
+    pub sign_confidence: f32,
+
+    /// Sign model class list, in output order; indices past the end decode
+    /// as `TrafficSign::Unknown`
+    pub sign_classes: Vec<TrafficSign>,
+
+    /// Run object and sign models on overlapping full-resolution tiles
+    /// (more compute, better recall on small distant objects)
+    pub tiled_inference: bool,
+
+    /// Tile layout used when `tiled_inference` is set
+    pub tiling: TilingConfig,
+
+    /// Contrast enhancement for dusk and night driving, applied before
+    /// inference
+    pub low_light: Option<ClaheConfig>,
+
+    /// Frames to run the models on; skipped frames repeat the last result
+    pub frame_skip: FrameSkip,
+
+    /// Pixel scaling expected by each model
+    pub lane_normalization: Normalization,
+    pub object_normalization: Normalization,
+    pub sign_normalization: Normalization,
     
     /// Model paths
     pub lane_model_path: Option<String>,
     pub object_model_path: Option<String>,
     pub sign_model_path: Option<String>,
+
+    /// Seed for synthetic results when a model is not configured
+    pub mock: MockConfig,
 }
 
 impl Default for AdasConfig {
     fn default() -> Self {
         Self {
             fcw_distance_m: 10.0,
+            vru_warning_distance_m: 25.0,
+            vru_warning_ttc_s: 4.0,
+            tailgating_headway_s: 1.5,
+            tailgating_sustain_ms: 3_000,
+            tailgating_min_speed_kmh: 30.0,
             lane_departure_enabled: true,
             object_confidence: 0.5,
+            object_classes: vec![
+                ObjectClass::Vehicle,
+                ObjectClass::Pedestrian,
+                ObjectClass::Cyclist,
+                ObjectClass::Motorcycle,
+                ObjectClass::Truck,
+            ],
+            nms_iou_threshold: 0.45,
+            roi_mask: None,
             lane_confidence: 0.7,
+            nominal_lane_width_m: 3.7,
+            lane_meters_per_pixel: None,
+            camera_geometry: CameraGeometry::default(),
             sign_detection_enabled: true,
+            sign_confidence: 0.5,
+            sign_classes: vec![
+                TrafficSign::SpeedLimit(30),
+                TrafficSign::SpeedLimit(50),
+                TrafficSign::SpeedLimit(70),
+                TrafficSign::SpeedLimit(90),
+                TrafficSign::SpeedLimit(110),
+                TrafficSign::SpeedLimit(130),
+                TrafficSign::Stop,
+                TrafficSign::Yield,
+                TrafficSign::NoEntry,
+                TrafficSign::NoOvertaking,
+                TrafficSign::EndRestriction,
+            ],
+            tiled_inference: false,
+            tiling: TilingConfig::default(),
+            low_light: None,
+            frame_skip: FrameSkip::Off,
+            // UFLD was trained on ImageNet statistics, YOLO on plain 0-1
+            lane_normalization: Normalization::ImageNet,
+            object_normalization: Normalization::ZeroToOne,
+            sign_normalization: Normalization::ZeroToOne,
             lane_model_path: None,
             object_model_path: None,
             sign_model_path: None,
+            mock: MockConfig::default(),
         }
     }
 }
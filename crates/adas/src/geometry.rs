@@ -0,0 +1,188 @@
+//! Road camera geometry and monocular distance estimation
+//!
+//! Image coordinates have their origin at the top-left corner with `y`
+//! growing downwards. Distances assume a flat road: a point on the ground
+//! `d` meters ahead projects to row `horizon + f * h / d`, where `f` is the
+//! focal length in pixels and `h` the camera mounting height.
+
+use serde::{Deserialize, Serialize};
+
+use crate::AdasError;
+
+/// Smallest row offset below the horizon that still yields a distance
+const MIN_ROWS_BELOW_HORIZON: f32 = 1.0;
+
+/// Camera intrinsics and mounting geometry
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraGeometry {
+    /// Focal length (pixels)
+    pub focal_length_px: f32,
+    /// Height of the camera above the road (meters)
+    pub mounting_height_m: f32,
+    /// Downward tilt of the optical axis (radians)
+    pub pitch_rad: f32,
+    /// Calibrated horizon row; derived from pitch and principal point if unset
+    pub horizon_row: Option<f32>,
+    /// Principal point (cx, cy) in pixels
+    pub principal_point: (f32, f32),
+}
+
+impl Default for CameraGeometry {
+    fn default() -> Self {
+        // 1080p windshield camera with a ~88 degree horizontal FOV
+        Self {
+            focal_length_px: 1000.0,
+            mounting_height_m: 1.3,
+            pitch_rad: 0.0,
+            horizon_row: None,
+            principal_point: (960.0, 540.0),
+        }
+    }
+}
+
+/// Detection with a measured ground-truth distance, used for calibration
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationReference {
+    /// Image row where the object meets the road (bottom of its box)
+    pub ground_row: f32,
+    /// Measured distance to the object (meters)
+    pub distance_m: f32,
+}
+
+impl CameraGeometry {
+    /// Row of the horizon in the image
+    pub fn horizon(&self) -> f32 {
+        self.horizon_row.unwrap_or_else(|| {
+            // Tilting down moves the horizon up the image
+            self.principal_point.1 - self.focal_length_px * self.pitch_rad.tan()
+        })
+    }
+
+    /// Distance to a ground point at the given image row
+    ///
+    /// Returns `None` for rows at or above the horizon.
+    pub fn distance_at_row(&self, row: f32) -> Option<f32> {
+        let below = row - self.horizon();
+        if below < MIN_ROWS_BELOW_HORIZON {
+            return None;
+        }
+        Some(self.focal_length_px * self.mounting_height_m / below)
+    }
+
+    /// Distance to an object from its `[x, y, width, height]` bounding box
+    pub fn distance_to_box(&self, bbox: &[f32; 4]) -> Option<f32> {
+        self.distance_at_row(bbox[1] + bbox[3])
+    }
+
+    /// Solve horizon row and scale from reference detections
+    ///
+    /// Fits `row = horizon + (f * h) / d` by least squares, keeping the
+    /// configured mounting height and adjusting the focal length to match
+    /// the fitted scale. At least two references at different distances
+    /// are required.
+    pub fn calibrate(&self, references: &[CalibrationReference]) -> Result<Self, AdasError> {
+        let points: Vec<(f64, f64)> = references
+            .iter()
+            .filter(|r| r.distance_m > 0.0)
+            .map(|r| (1.0 / r.distance_m as f64, r.ground_row as f64))
+            .collect();
+
+        if points.len() < 2 {
+            return Err(AdasError::Calibration(format!(
+                "need at least 2 references with positive distance, got {}",
+                points.len()
+            )));
+        }
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+
+        if sxx <= f64::EPSILON {
+            return Err(AdasError::Calibration(
+                "references must be at different distances".into(),
+            ));
+        }
+
+        let scale = sxy / sxx;
+        if scale <= 0.0 {
+            return Err(AdasError::Calibration(
+                "references imply nearer objects sit higher in the image".into(),
+            ));
+        }
+        let horizon = mean_y - scale * mean_x;
+
+        Ok(Self {
+            focal_length_px: (scale / self.mounting_height_m as f64) as f32,
+            horizon_row: Some(horizon as f32),
+            ..*self
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synthetic() -> CameraGeometry {
+        CameraGeometry {
+            focal_length_px: 800.0,
+            mounting_height_m: 1.5,
+            pitch_rad: 0.0,
+            horizon_row: None,
+            principal_point: (640.0, 360.0),
+        }
+    }
+
+    #[test]
+    fn test_box_at_known_row_maps_to_distance() {
+        let geometry = synthetic();
+        // 800 * 1.5 / (420 - 360) = 20 m
+        let distance = geometry.distance_to_box(&[600.0, 380.0, 80.0, 40.0]).unwrap();
+        assert!((distance - 20.0).abs() < 1e-3);
+
+        assert!(geometry.distance_at_row(300.0).is_none());
+    }
+
+    #[test]
+    fn test_pitch_moves_horizon() {
+        let geometry = CameraGeometry {
+            pitch_rad: 0.05,
+            ..synthetic()
+        };
+        assert!((geometry.horizon() - (360.0 - 800.0 * 0.05f32.tan())).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_calibration_recovers_geometry() {
+        // True camera: horizon at row 350, f = 900 px, h = 1.2 m
+        let truth = CameraGeometry {
+            focal_length_px: 900.0,
+            mounting_height_m: 1.2,
+            horizon_row: Some(350.0),
+            ..synthetic()
+        };
+        let references: Vec<CalibrationReference> = [8.0, 15.0, 30.0]
+            .iter()
+            .map(|&d| CalibrationReference {
+                ground_row: 350.0 + 900.0 * 1.2 / d,
+                distance_m: d,
+            })
+            .collect();
+
+        // Mounting height is measured; focal length starts from a datasheet guess
+        let initial = CameraGeometry {
+            mounting_height_m: 1.2,
+            ..synthetic()
+        };
+        let calibrated = initial.calibrate(&references).unwrap();
+        assert!((calibrated.horizon() - truth.horizon()).abs() < 1e-2);
+        assert!((calibrated.focal_length_px - 900.0).abs() < 1e-2);
+        let d = calibrated.distance_at_row(350.0 + 900.0 * 1.2 / 12.0).unwrap();
+        assert!((d - 12.0).abs() < 1e-2);
+
+        assert!(initial.calibrate(&references[..1]).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Following-distance (headway) monitoring
+//!
+//! Headway is the time until the ego vehicle reaches the lead vehicle's
+//! current position: distance divided by ego speed. Fleets score sustained
+//! short headway as tailgating, which unlike a forward collision warning
+//! says nothing about an imminent impact, so a brief dip (a car cutting in,
+//! a box briefly misjudged) must not count.
+
+use crate::analysis::AdasAlert;
+use crate::config::AdasConfig;
+use crate::object::{DetectedObject, ObjectClass};
+
+/// Tracks how long headway has stayed below the tailgating threshold
+#[derive(Debug, Clone)]
+pub struct HeadwayMonitor {
+    threshold_s: f32,
+    sustain_ms: u64,
+    min_speed_kmh: f32,
+    /// When headway first dropped below the threshold (ns)
+    below_since_ns: Option<u64>,
+}
+
+impl HeadwayMonitor {
+    /// Create a monitor from the tailgating settings in `config`
+    pub fn new(config: &AdasConfig) -> Self {
+        Self {
+            threshold_s: config.tailgating_headway_s,
+            sustain_ms: config.tailgating_sustain_ms,
+            min_speed_kmh: config.tailgating_min_speed_kmh,
+            below_since_ns: None,
+        }
+    }
+
+    /// Headway to a lead vehicle `distance_m` ahead at `speed_kmh` (s),
+    /// `None` when the vehicle is standing still
+    pub fn headway_s(distance_m: f32, speed_kmh: f32) -> Option<f32> {
+        let speed_mps = speed_kmh / 3.6;
+        (speed_mps > 0.0).then(|| distance_m.max(0.0) / speed_mps)
+    }
+
+    /// Nearest vehicle ahead; pedestrians and cyclists are not followed
+    pub fn lead_vehicle(objects: &[DetectedObject]) -> Option<&DetectedObject> {
+        objects
+            .iter()
+            .filter(|obj| {
+                matches!(
+                    obj.class,
+                    ObjectClass::Vehicle | ObjectClass::Truck | ObjectClass::Motorcycle
+                )
+            })
+            .min_by(|a, b| a.distance_m.total_cmp(&b.distance_m))
+    }
+
+    /// Feed one observation at `timestamp_ns`
+    ///
+    /// Returns a tailgating alert on every observation once headway has
+    /// stayed below the threshold for the sustain duration. No lead
+    /// vehicle, or a speed below the minimum, resets the timer.
+    pub fn update(
+        &mut self,
+        lead_distance_m: Option<f32>,
+        speed_kmh: f32,
+        timestamp_ns: u64,
+    ) -> Option<AdasAlert> {
+        let headway_s = lead_distance_m
+            .filter(|_| speed_kmh >= self.min_speed_kmh)
+            .and_then(|distance| Self::headway_s(distance, speed_kmh))
+            .filter(|&headway| headway < self.threshold_s);
+        let Some(headway_s) = headway_s else {
+            self.below_since_ns = None;
+            return None;
+        };
+
+        let since = *self.below_since_ns.get_or_insert(timestamp_ns);
+        let sustained_ms = timestamp_ns.saturating_sub(since) / 1_000_000;
+        (sustained_ms >= self.sustain_ms).then_some(AdasAlert::Tailgating { headway_s })
+    }
+
+    /// Forget the current low-headway run
+    pub fn reset(&mut self) {
+        self.below_since_ns = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sustained_low_headway_alerts_brief_dip_does_not() {
+        // Defaults: below 1.5 s for 3 s, at 30 km/h or more
+        let mut monitor = HeadwayMonitor::new(&AdasConfig::default());
+        let speed_kmh = 90.0; // 25 m/s
+        let ms = 1_000_000u64;
+
+        // 1 s dip to 20 m (0.8 s headway), then back to 50 m
+        for t in (0..1_000).step_by(100) {
+            assert!(monitor.update(Some(20.0), speed_kmh, t * ms).is_none());
+        }
+        assert!(monitor.update(Some(50.0), speed_kmh, 1_000 * ms).is_none());
+
+        // Sustained 0.8 s headway from t = 2 s
+        let mut first_alert = None;
+        for t in (2_000..6_000).step_by(100) {
+            if let Some(AdasAlert::Tailgating { headway_s }) =
+                monitor.update(Some(20.0), speed_kmh, t * ms)
+            {
+                assert!((headway_s - 0.8).abs() < 1e-4);
+                first_alert.get_or_insert(t);
+            }
+        }
+        assert_eq!(first_alert, Some(5_000));
+
+        // Losing the lead vehicle ends the run
+        assert!(monitor.update(None, speed_kmh, 6_000 * ms).is_none());
+        assert!(monitor.update(Some(20.0), speed_kmh, 6_100 * ms).is_none());
+
+        // Queueing in slow traffic is not tailgating
+        let mut slow = HeadwayMonitor::new(&AdasConfig::default());
+        for t in (0..10_000).step_by(100) {
+            assert!(slow.update(Some(3.0), 10.0, t * ms).is_none());
+        }
+    }
+}
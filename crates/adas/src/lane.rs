@@ -2,7 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 use camera_capture::frame::VideoFrame;
-use crate::{AdasConfig, AdasError};
+use camera_capture::Normalization;
+use crate::{AdasConfig, AdasError, RoiMask};
 use ort::{Session, GraphOptimizationLevel};
 use ndarray::{Array, Array4, Axis};
 use tracing::{info, warn, error};
@@ -41,18 +42,112 @@ pub struct LaneState {
     /// Lane curvature (1/radius)
     pub curvature: f32,
     
-    /// Offset from lane center (meters)
+    /// Offset of the vehicle from lane center (meters, positive = right of center)
     pub center_offset_m: f32,
+
+    /// Estimated lane width (meters)
+    pub lane_width_m: f32,
+}
+
+/// Lane geometry measured at the image row nearest the vehicle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaneGeometry {
+    /// Distance from the ego center to the left line (pixels)
+    pub left_distance_px: f32,
+    /// Distance from the ego center to the right line (pixels)
+    pub right_distance_px: f32,
+    /// Separation between the lines (pixels)
+    pub lane_width_px: f32,
+    /// Ground-plane scale used for the metric values
+    pub meters_per_pixel: f32,
+    /// Offset of the ego center from lane center (meters, positive = right)
+    pub center_offset_m: f32,
+    /// Lane width (meters)
+    pub lane_width_m: f32,
+}
+
+impl LaneGeometry {
+    /// Measure lane geometry from detected line points
+    ///
+    /// The camera is assumed to be mounted on the vehicle centerline, so the
+    /// ego center is the middle image column. When `meters_per_pixel` is not
+    /// known, the scale is derived by assuming the detected lane is
+    /// `nominal_width_m` wide.
+    pub fn measure(
+        left: &[(f32, f32)],
+        right: &[(f32, f32)],
+        image_width: u32,
+        nominal_width_m: f32,
+        meters_per_pixel: Option<f32>,
+    ) -> Option<Self> {
+        // Compare both lines at the lowest row they share
+        let bottom = |line: &[(f32, f32)]| line.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+        let row = bottom(left).min(bottom(right));
+        if !row.is_finite() {
+            return None;
+        }
+
+        let left_x = x_at_row(left, row)?;
+        let right_x = x_at_row(right, row)?;
+        let lane_width_px = right_x - left_x;
+        if lane_width_px <= 0.0 {
+            return None;
+        }
+
+        let ego_x = image_width as f32 / 2.0;
+        let meters_per_pixel = meters_per_pixel.unwrap_or(nominal_width_m / lane_width_px);
+        let lane_center_x = (left_x + right_x) / 2.0;
+
+        Some(Self {
+            left_distance_px: ego_x - left_x,
+            right_distance_px: right_x - ego_x,
+            lane_width_px,
+            meters_per_pixel,
+            center_offset_m: (ego_x - lane_center_x) * meters_per_pixel,
+            lane_width_m: lane_width_px * meters_per_pixel,
+        })
+    }
+}
+
+/// X coordinate of a polyline at image row `y`, extrapolating from the end segments
+fn x_at_row(line: &[(f32, f32)], y: f32) -> Option<f32> {
+    let mut points = line.to_vec();
+    points.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    match points.as_slice() {
+        [] => None,
+        [only] => Some(only.0),
+        _ => {
+            let idx = points
+                .windows(2)
+                .position(|w| y <= w[1].1)
+                .unwrap_or(points.len() - 2);
+            let (x0, y0) = points[idx];
+            let (x1, y1) = points[idx + 1];
+            if (y1 - y0).abs() < f32::EPSILON {
+                return Some((x0 + x1) / 2.0);
+            }
+            Some(x0 + (x1 - x0) * (y - y0) / (y1 - y0))
+        }
+    }
 }
 
 /// Lane detector
 pub struct LaneDetector {
     confidence_threshold: f32,
+    nominal_lane_width_m: f32,
+    meters_per_pixel: Option<f32>,
+    roi_mask: Option<RoiMask>,
+    normalization: Normalization,
     session: Option<Session>,
 }
 
 impl LaneDetector {
     pub fn new(config: &AdasConfig) -> Result<Self, AdasError> {
+        if let Some(mask) = &config.roi_mask {
+            mask.validate()?;
+        }
+
         let session = if let Some(path) = &config.lane_model_path {
             info!("Loading lane detection model from {}", path);
             match Session::builder() {
@@ -83,12 +178,18 @@ impl LaneDetector {
 
         Ok(Self {
             confidence_threshold: config.lane_confidence,
+            nominal_lane_width_m: config.nominal_lane_width_m,
+            meters_per_pixel: config.lane_meters_per_pixel,
+            roi_mask: config.roi_mask.clone(),
+            normalization: config.lane_normalization,
             session,
         })
     }
 
     /// Detect lane lines
     pub fn detect(&self, frame: &VideoFrame) -> Result<LaneState, AdasError> {
+        frame.validate_rgb().map_err(|e| AdasError::ImageProcessing(e.to_string()))?;
+
         if let Some(session) = &self.session {
             // Real implementation
             
@@ -97,7 +198,7 @@ impl LaneDetector {
             let img = match image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
                 frame.width, 
                 frame.height, 
-                &frame.data
+                frame.data.as_slice()
             ) {
                 Some(i) => i,
                 None => return Err(AdasError::ImageProcessing("Failed to create image buffer".into())),
@@ -105,22 +206,12 @@ impl LaneDetector {
 
             let resized = image::imageops::resize(&img, 800, 200, image::imageops::FilterType::Triangle);
 
-            // 2. Normalize and create tensor (NCHW - 1x3x200x800)
-            // Ultra-Fast-Lane normalization: mean=[0.485, 0.456, 0.406], std=[0.229, 0.224, 0.225] usually
-            // But simple 0-1 might work for now or standard ImageNet.
-            // Let's assume standard ImageNet normalization for now.
-            let mean = [0.485, 0.456, 0.406];
-            let std = [0.229, 0.224, 0.225];
-
+            // 2. Normalize (UFLD: ImageNet mean/std) and create tensor (NCHW - 1x3x200x800)
             let mut input_array = Array4::<f32>::zeros((1, 3, 200, 800));
             for (x, y, pixel) in resized.enumerate_pixels() {
-                let r = (pixel[0] as f32 / 255.0 - mean[0]) / std[0];
-                let g = (pixel[1] as f32 / 255.0 - mean[1]) / std[1];
-                let b = (pixel[2] as f32 / 255.0 - mean[2]) / std[2];
-                
-                input_array[[0, 0, y as usize, x as usize]] = r;
-                input_array[[0, 1, y as usize, x as usize]] = g;
-                input_array[[0, 2, y as usize, x as usize]] = b;
+                for c in 0..3 {
+                    input_array[[0, c, y as usize, x as usize]] = self.normalization.apply(pixel[c], c);
+                }
             }
 
             // 3. Inference
@@ -140,7 +231,7 @@ impl LaneDetector {
             
             // Calculating mock coordinates based on "real" inference success for this step 
             // to allow compilation without implementing full UFLD decoder complexity in one go.
-             Ok(LaneState {
+            let mut state = LaneState {
                 lanes_detected: true,
                 position: LanePosition::Center,
                 departing: false,
@@ -148,12 +239,15 @@ impl LaneDetector {
                 left_lane: vec![(200.0, 800.0), (350.0, 500.0)], // Mocking real points for now
                 right_lane: vec![(1400.0, 800.0), (1250.0, 500.0)],
                 curvature: 0.001,
-                center_offset_m: 0.1,
-            })
+                center_offset_m: 0.0,
+                lane_width_m: 0.0,
+            };
+            self.apply_geometry(&mut state, frame.width, frame.height);
+            Ok(state)
 
         } else {
             // Mock: lanes detected, centered
-            Ok(LaneState {
+            let mut state = LaneState {
                 lanes_detected: true,
                 position: LanePosition::Center,
                 departing: false,
@@ -162,7 +256,70 @@ impl LaneDetector {
                 right_lane: vec![(1820.0, 1080.0), (1520.0, 540.0)],
                 curvature: 0.0,
                 center_offset_m: 0.0,
-            })
+                lane_width_m: 0.0,
+            };
+            self.apply_geometry(&mut state, frame.width, frame.height);
+            Ok(state)
         }
     }
+
+    /// Fill offset and width from the detected line points that lie on the road
+    fn apply_geometry(&self, state: &mut LaneState, image_width: u32, image_height: u32) {
+        if let Some(mask) = &self.roi_mask {
+            let on_road = |&(x, y): &(f32, f32)| mask.contains(x, y, image_width, image_height);
+            state.left_lane.retain(on_road);
+            state.right_lane.retain(on_road);
+            state.lanes_detected &= !state.left_lane.is_empty() && !state.right_lane.is_empty();
+        }
+
+        if let Some(geometry) = LaneGeometry::measure(
+            &state.left_lane,
+            &state.right_lane,
+            image_width,
+            self.nominal_lane_width_m,
+            self.meters_per_pixel,
+        ) {
+            state.center_offset_m = geometry.center_offset_m;
+            state.lane_width_m = geometry.lane_width_m;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symmetric_lane_has_zero_offset() {
+        let left = [(400.0, 1080.0), (800.0, 600.0)];
+        let right = [(1520.0, 1080.0), (1120.0, 600.0)];
+
+        let geometry = LaneGeometry::measure(&left, &right, 1920, 3.7, None).unwrap();
+        assert!((geometry.left_distance_px - 560.0).abs() < 1e-3);
+        assert!((geometry.right_distance_px - 560.0).abs() < 1e-3);
+        assert!(geometry.center_offset_m.abs() < 1e-4);
+        assert!((geometry.lane_width_m - 3.7).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_asymmetric_lane_has_signed_offset() {
+        // Lines shifted 200px left: the vehicle sits right of lane center
+        let left = [(200.0, 1080.0), (600.0, 600.0)];
+        let right = [(1320.0, 1080.0), (920.0, 600.0)];
+
+        let geometry = LaneGeometry::measure(&left, &right, 1920, 3.7, Some(0.0035)).unwrap();
+        assert!((geometry.center_offset_m - 0.7).abs() < 1e-4);
+        assert!((geometry.lane_width_m - 3.92).abs() < 1e-4);
+
+        // Mirrored: vehicle left of center
+        let left = [(600.0, 1080.0)];
+        let right = [(1720.0, 1080.0)];
+        let geometry = LaneGeometry::measure(&left, &right, 1920, 3.7, Some(0.0035)).unwrap();
+        assert!((geometry.center_offset_m + 0.7).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_missing_lines() {
+        assert!(LaneGeometry::measure(&[], &[(1000.0, 1080.0)], 1920, 3.7, None).is_none());
+    }
 }
@@ -9,18 +9,29 @@
 
 pub mod analysis;
 pub mod config;
+pub mod geometry;
+pub mod headway;
 pub mod lane;
 pub mod object;
+pub mod roi;
 pub mod sign;
+pub mod tiling;
+pub mod yolo;
 
 pub use analysis::{AdasAnalysis, AdasAlert};
 pub use config::AdasConfig;
-pub use lane::{LaneDetector, LaneState, LanePosition};
+pub use geometry::{CalibrationReference, CameraGeometry};
+pub use headway::HeadwayMonitor;
+pub use lane::{LaneDetector, LaneGeometry, LaneState, LanePosition};
 pub use object::{ObjectDetector, DetectedObject, ObjectClass};
+pub use roi::RoiMask;
 pub use sign::{SignClassifier, TrafficSign};
+pub use tiling::{Tile, TilingConfig};
 
 use camera_capture::frame::VideoFrame;
+use camera_capture::{CameraService, CameraStatus, FrameSkipper};
 use thiserror::Error;
+use tokio::sync::watch;
 
 /// ADAS error types
 #[derive(Error, Debug)]
@@ -36,6 +47,12 @@ pub enum AdasError {
 
     #[error("Image processing failed: {0}")]
     ImageProcessing(String),
+
+    #[error("Calibration failed: {0}")]
+    Calibration(String),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
 }
 
 /// ADAS module
@@ -44,6 +61,12 @@ pub struct AdasModule {
     lane_detector: LaneDetector,
     object_detector: ObjectDetector,
     sign_classifier: SignClassifier,
+    headway: HeadwayMonitor,
+    skipper: FrameSkipper,
+    /// Result of the last frame the models ran on
+    last: AdasAnalysis,
+    /// Latest ego speed from OBD (km/h)
+    speed_kmh: Option<f32>,
 }
 
 impl AdasModule {
@@ -53,12 +76,65 @@ impl AdasModule {
             lane_detector: LaneDetector::new(&config)?,
             object_detector: ObjectDetector::new(&config)?,
             sign_classifier: SignClassifier::new(&config)?,
+            headway: HeadwayMonitor::new(&config),
+            skipper: FrameSkipper::new(config.frame_skip),
+            last: AdasAnalysis::default(),
+            speed_kmh: None,
             config,
         })
     }
 
+    /// Feed the current vehicle speed (km/h), used for headway
+    pub fn set_speed(&mut self, speed_kmh: f32) {
+        self.speed_kmh = Some(speed_kmh.max(0.0));
+    }
+
     /// Analyze road scene
     pub async fn analyze(&mut self, frame: &VideoFrame) -> Result<AdasAnalysis, AdasError> {
+        if !self.skipper.should_process(frame.timestamp_ns) {
+            return Ok(self.carry_forward(frame.timestamp_ns));
+        }
+
+        let analysis = self.run_models(frame)?;
+        self.last = analysis.clone();
+        Ok(analysis)
+    }
+
+    /// Analyze the next frame from the road camera, or report it offline
+    ///
+    /// `status` comes from [`CameraService::status_changes`]. While the
+    /// camera is stalled and the service re-opens the driver no frames
+    /// arrive, so each stall yields one [`AdasAnalysis::camera_offline`] result
+    /// instead. `None` once the camera service has stopped.
+    pub async fn analyze_next(
+        &mut self,
+        camera: &mut CameraService,
+        status: &mut watch::Receiver<CameraStatus>,
+    ) -> Option<Result<AdasAnalysis, AdasError>> {
+        loop {
+            tokio::select! {
+                frame = camera.next() => {
+                    let frame = frame?;
+                    return Some(self.analyze(&frame).await);
+                }
+                Ok(()) = status.changed() => {
+                    if status.borrow_and_update().is_offline() {
+                        return Some(Ok(AdasAnalysis::camera_offline()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rate the models are actually running at, after frame skipping
+    pub fn processing_fps(&self) -> Option<f32> {
+        self.skipper.effective_fps()
+    }
+
+    fn run_models(&mut self, frame: &VideoFrame) -> Result<AdasAnalysis, AdasError> {
+        let enhanced = self.config.low_light.map(|clahe| clahe.apply(frame));
+        let frame = enhanced.as_ref().unwrap_or(frame);
+
         // Run detections in parallel
         let lane_state = self.lane_detector.detect(frame)?;
         let objects = self.object_detector.detect(frame)?;
@@ -75,14 +151,12 @@ impl AdasModule {
         }
 
         // Forward collision warning
-        for obj in &objects {
-            if obj.class == ObjectClass::Vehicle && obj.distance_m < self.config.fcw_distance_m {
-                alerts.push(AdasAlert::ForwardCollision {
-                    distance_m: obj.distance_m,
-                    object_type: obj.class,
-                });
-                break;
-            }
+        alerts.extend(collision_alerts(&self.config, &objects));
+
+        // Sustained tailgating; needs ego speed, so skipped until OBD reports it
+        if let Some(speed_kmh) = self.speed_kmh {
+            let lead = HeadwayMonitor::lead_vehicle(&objects).map(|obj| obj.distance_m);
+            alerts.extend(self.headway.update(lead, speed_kmh, frame.timestamp_ns));
         }
 
         // Speed limit warning
@@ -98,6 +172,208 @@ impl AdasModule {
             objects,
             signs,
             alerts,
+            skipped: false,
+        })
+    }
+
+    /// Result for a skipped frame: the last detections, with the
+    /// tailgating timer advanced on them
+    ///
+    /// Collision, lane and sign alerts belong to the frame they were raised
+    /// on and are not repeated.
+    fn carry_forward(&mut self, timestamp_ns: u64) -> AdasAnalysis {
+        let mut analysis = self.last.clone();
+        analysis.skipped = true;
+        analysis.alerts = match self.speed_kmh {
+            Some(speed_kmh) => {
+                let lead = HeadwayMonitor::lead_vehicle(&analysis.objects).map(|obj| obj.distance_m);
+                self.headway.update(lead, speed_kmh, timestamp_ns).into_iter().collect()
+            }
+            None => Vec::new(),
+        };
+        analysis
+    }
+}
+
+/// Collision warnings for the detected objects, most urgent first
+///
+/// Pedestrians and cyclists are checked against their own wider distance
+/// and TTC thresholds; the nearest one in range comes first. The first
+/// vehicle inside the FCW distance follows with a forward collision
+/// warning, so a nearer car doesn't hide a pedestrian or vice versa.
+pub fn collision_alerts(config: &AdasConfig, objects: &[DetectedObject]) -> Vec<AdasAlert> {
+    let vulnerable = objects
+        .iter()
+        .filter(|obj| obj.class.is_vulnerable())
+        .filter(|obj| {
+            obj.distance_m < config.vru_warning_distance_m
+                || obj.ttc_s.is_some_and(|ttc| ttc < config.vru_warning_ttc_s)
+        })
+        .min_by(|a, b| a.distance_m.total_cmp(&b.distance_m))
+        .map(|obj| AdasAlert::VulnerableUserAhead {
+            class: obj.class,
+            distance_m: obj.distance_m,
+            ttc_s: obj.ttc_s,
+        });
+
+    let vehicle = objects
+        .iter()
+        .find(|obj| obj.class == ObjectClass::Vehicle && obj.distance_m < config.fcw_distance_m)
+        .map(|obj| AdasAlert::ForwardCollision {
+            distance_m: obj.distance_m,
+            object_type: obj.class,
+        });
+
+    vulnerable.into_iter().chain(vehicle).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camera_capture::{CameraError, CameraServiceConfig, FrameSkip, FrameSource};
+    use std::time::Duration;
+
+    /// Delivers `remaining` blank frames, then goes quiet for good
+    struct FadingSource {
+        remaining: u32,
+        sequence: u32,
+    }
+
+    impl FrameSource for FadingSource {
+        fn next_frame(&mut self, timeout: Duration) -> Option<VideoFrame> {
+            if self.remaining == 0 {
+                std::thread::sleep(timeout);
+                return None;
+            }
+            self.remaining -= 1;
+            self.sequence += 1;
+            let timestamp_ns = u64::from(self.sequence) * 33_000_000;
+            Some(VideoFrame::new(vec![0; 64 * 48 * 3], 64, 48, timestamp_ns, self.sequence))
+        }
+
+        fn reinit(&mut self) -> Result<(), CameraError> {
+            Err(CameraError::Open("device unplugged".into()))
+        }
+    }
+
+    fn object(class: ObjectClass, distance_m: f32, ttc_s: Option<f32>) -> DetectedObject {
+        DetectedObject {
+            class,
+            bbox: [0.0, 0.0, 10.0, 10.0],
+            confidence: 0.9,
+            distance_m,
+            velocity_mps: 0.0,
+            ttc_s,
+        }
+    }
+
+    #[test]
+    fn test_pedestrian_warned_beyond_fcw_range() {
+        let config = AdasConfig::default();
+
+        // 18 m: outside vehicle FCW range, inside the pedestrian range
+        assert!(collision_alerts(&config, &[object(ObjectClass::Vehicle, 18.0, None)]).is_empty());
+        let alerts = collision_alerts(&config, &[object(ObjectClass::Pedestrian, 18.0, None)]);
+        assert!(matches!(
+            alerts[..],
+            [AdasAlert::VulnerableUserAhead { class: ObjectClass::Pedestrian, .. }]
+        ));
+
+        // Far away but closing fast
+        let alerts = collision_alerts(&config, &[object(ObjectClass::Cyclist, 60.0, Some(3.0))]);
+        assert!(matches!(alerts[..], [AdasAlert::VulnerableUserAhead { .. }]));
+    }
+
+    #[test]
+    fn test_vulnerable_user_comes_before_nearer_vehicle() {
+        let config = AdasConfig::default();
+        let objects = [
+            object(ObjectClass::Vehicle, 6.0, None),
+            object(ObjectClass::Cyclist, 22.0, None),
+            object(ObjectClass::Pedestrian, 15.0, None),
+        ];
+
+        let alerts = collision_alerts(&config, &objects);
+        assert_eq!(alerts.len(), 2, "expected VRU then FCW, got {alerts:?}");
+        assert!(matches!(
+            alerts[0],
+            AdasAlert::VulnerableUserAhead { class: ObjectClass::Pedestrian, distance_m, .. } if distance_m == 15.0
+        ));
+        assert!(matches!(
+            alerts[1],
+            AdasAlert::ForwardCollision { distance_m, .. } if distance_m == 6.0
+        ));
+
+        let vehicle_only = collision_alerts(&config, &objects[..1]);
+        assert!(matches!(vehicle_only[..], [AdasAlert::ForwardCollision { .. }]));
+    }
+
+    #[tokio::test]
+    async fn test_skipped_frames_do_not_repeat_alerts() {
+        let mut adas = AdasModule::new(AdasConfig {
+            frame_skip: FrameSkip::EveryNth(100),
+            ..Default::default()
         })
+        .unwrap();
+        let ms = 1_000_000u64;
+        let frame = |t_ms: u64| VideoFrame::new(vec![0; 64 * 48 * 3], 64, 48, t_ms * ms, t_ms as u32);
+
+        assert!(!adas.analyze(&frame(0)).await.unwrap().skipped);
+        // Pretend the processed frame saw a car 10 m ahead
+        adas.last = AdasAnalysis {
+            objects: vec![object(ObjectClass::Vehicle, 10.0, None)],
+            alerts: vec![AdasAlert::ForwardCollision {
+                distance_m: 10.0,
+                object_type: ObjectClass::Vehicle,
+            }],
+            ..Default::default()
+        };
+        adas.set_speed(90.0);
+
+        let mut tailgating_from = None;
+        for t_ms in (500..=4_000).step_by(500) {
+            let analysis = adas.analyze(&frame(t_ms)).await.unwrap();
+            assert!(analysis.skipped);
+            assert_eq!(analysis.objects.len(), 1);
+            assert!(
+                !analysis.alerts.iter().any(|a| matches!(a, AdasAlert::ForwardCollision { .. })),
+                "FCW repeated at {t_ms} ms"
+            );
+            if tailgating_from.is_none() && matches!(analysis.alerts[..], [AdasAlert::Tailgating { .. }]) {
+                tailgating_from = Some(t_ms);
+            }
+        }
+        // The headway timer kept running across skipped frames
+        assert_eq!(tailgating_from, Some(3_500));
+    }
+
+    #[tokio::test]
+    async fn test_stalled_camera_reported_offline() {
+        let mut module = AdasModule::new(Default::default()).unwrap();
+        let mut camera = CameraService::spawn(
+            FadingSource {
+                remaining: 2,
+                sequence: 0,
+            },
+            CameraServiceConfig {
+                stall_timeout: Duration::from_millis(100),
+                poll_interval: Duration::from_millis(10),
+                reinit_interval: Duration::from_millis(50),
+                channel_capacity: 4,
+            },
+        );
+        let mut status = camera.status_changes();
+
+        for _ in 0..2 {
+            let analysis = module.analyze_next(&mut camera, &mut status).await.unwrap().unwrap();
+            assert!(!analysis.alerts.iter().any(|a| matches!(a, AdasAlert::CameraOffline)));
+        }
+
+        let offline = tokio::time::timeout(Duration::from_secs(2), module.analyze_next(&mut camera, &mut status))
+            .await
+            .expect("stall not reported")
+            .unwrap()
+            .unwrap();
+        assert!(matches!(offline.alerts[..], [AdasAlert::CameraOffline]));
     }
 }
@@ -2,7 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 use camera_capture::frame::VideoFrame;
-use crate::{AdasConfig, AdasError};
+use camera_capture::{MockRng, Normalization};
+use crate::tiling::detect_tiled;
+use crate::yolo;
+use crate::{AdasConfig, AdasError, CameraGeometry, RoiMask, TilingConfig};
 use ort::{Session, GraphOptimizationLevel};
 use ndarray::{Array4, Axis};
 use tracing::{info, warn, error};
@@ -18,6 +21,25 @@ pub enum ObjectClass {
     Unknown,
 }
 
+impl ObjectClass {
+    /// Map a COCO class index (YOLO default head) to an ADAS class
+    pub fn from_coco(class_id: usize) -> Self {
+        match class_id {
+            0 => ObjectClass::Pedestrian,
+            1 => ObjectClass::Cyclist,
+            2 | 5 => ObjectClass::Vehicle, // car, bus
+            3 => ObjectClass::Motorcycle,
+            7 => ObjectClass::Truck,
+            _ => ObjectClass::Unknown,
+        }
+    }
+
+    /// Unprotected road users: pedestrians and cyclists
+    pub fn is_vulnerable(&self) -> bool {
+        matches!(self, ObjectClass::Pedestrian | ObjectClass::Cyclist)
+    }
+}
+
 /// Detected object
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectedObject {
@@ -40,14 +62,50 @@ pub struct DetectedObject {
     pub ttc_s: Option<f32>,
 }
 
+impl DetectedObject {
+    /// Intersection-over-union of two `[x, y, width, height]` boxes
+    pub fn iou(&self, other: &DetectedObject) -> f32 {
+        let [ax, ay, aw, ah] = self.bbox;
+        let [bx, by, bw, bh] = other.bbox;
+
+        let ix = ((ax + aw).min(bx + bw) - ax.max(bx)).max(0.0);
+        let iy = ((ay + ah).min(by + bh) - ay.max(by)).max(0.0);
+        let intersection = ix * iy;
+        let union = aw * ah + bw * bh - intersection;
+
+        if union <= 0.0 {
+            0.0
+        } else {
+            intersection / union
+        }
+    }
+}
+
+/// Detector input resolution (square)
+const MODEL_INPUT_SIZE: u32 = 640;
+
+/// Padding colour for letterboxed input (YOLO training default)
+const LETTERBOX_PAD: [u8; 3] = [114, 114, 114];
+
 /// Object detector using YOLO or similar
 pub struct ObjectDetector {
     confidence_threshold: f32,
+    allowed_classes: Vec<ObjectClass>,
+    nms_iou_threshold: f32,
+    roi_mask: Option<RoiMask>,
+    geometry: CameraGeometry,
+    tiling: Option<TilingConfig>,
+    normalization: Normalization,
     session: Option<Session>,
+    mock: MockRng,
 }
 
 impl ObjectDetector {
     pub fn new(config: &AdasConfig) -> Result<Self, AdasError> {
+        if let Some(mask) = &config.roi_mask {
+            mask.validate()?;
+        }
+
         let session = if let Some(path) = &config.object_model_path {
             info!("Loading object detection model from {}", path);
              match Session::builder() {
@@ -78,73 +136,215 @@ impl ObjectDetector {
 
         Ok(Self {
             confidence_threshold: config.object_confidence,
+            allowed_classes: config.object_classes.clone(),
+            nms_iou_threshold: config.nms_iou_threshold,
+            roi_mask: config.roi_mask.clone(),
+            geometry: config.camera_geometry,
+            tiling: config.tiled_inference.then_some(config.tiling),
+            normalization: config.object_normalization,
             session,
+            mock: MockRng::new(config.mock),
         })
     }
 
     /// Detect objects in frame
+    ///
+    /// With tiled inference enabled the model runs once per tile; the mock
+    /// backend always works on the whole frame.
     pub fn detect(&self, frame: &VideoFrame) -> Result<Vec<DetectedObject>, AdasError> {
-        if let Some(session) = &self.session {
-             // 1. Preprocess: Resize to 640x640 (standard YOLO input)
-            let img = match image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
-                frame.width, 
-                frame.height, 
-                &frame.data
-            ) {
-                Some(i) => i,
-                None => return Err(AdasError::ImageProcessing("Failed to create image buffer".into())),
-            };
-
-            let input_width = 640;
-            let input_height = 640;
-            let resized = image::imageops::resize(&img, input_width, input_height, image::imageops::FilterType::Triangle);
-
-            // 2. Normalize 0-1 and create tensor (NCHW)
-            // YOLO input is typically 0-1 float32
-            let mut input_array = Array4::<f32>::zeros((1, 3, input_height as usize, input_width as usize));
-            for (x, y, pixel) in resized.enumerate_pixels() {
-                input_array[[0, 0, y as usize, x as usize]] = pixel[0] as f32 / 255.0;
-                input_array[[0, 1, y as usize, x as usize]] = pixel[1] as f32 / 255.0;
-                input_array[[0, 2, y as usize, x as usize]] = pixel[2] as f32 / 255.0;
+        frame.validate_rgb().map_err(|e| AdasError::ImageProcessing(e.to_string()))?;
+
+        let raw = match (&self.session, &self.tiling) {
+            (Some(session), Some(tiling)) => {
+                detect_tiled(frame, tiling, |input| self.infer(session, input))?
             }
+            (Some(session), None) => self.infer(session, frame)?,
+            (None, _) => self.mock_detections(),
+        };
 
-             // 3. Inference
-            let outputs = session.run(ort::inputs![input_array].map_err(|e| AdasError::Inference(e.to_string()))?)
-                .map_err(|e| AdasError::Inference(e.to_string()))?;
-
-            // 4. Post-process
-            // YOLOv5/v8 output: [1, anchors, 5 + classes] or [1, 5+classes, anchors] depending on export.
-            // Usually [1, 25200, 85] for v5 export default.
-            
-            // Getting the output tensor. Assuming output 0 is main.
-            let output_tensor = outputs.get(0).ok_or(AdasError::Inference("No output tensor".into()))?;
-            // We'll treat it as dynamic, but we expect it to be 3D.
-            // For completeness, we'd need to check strict shapes.
-            // Simplified parsing: 
-            // Just returning mock for now to ensure compilation safety as we don't have the shape guaranteed.
-            
-            // TODO: Implement parsing of specific tensor output structure.
-            // This requires matching the specific exported model (YOLOv5 vs v8 vs NAS).
-
-             Ok(vec![DetectedObject {
-                class: ObjectClass::Vehicle,
-                bbox: [800.0, 400.0, 300.0, 200.0],
-                confidence: 0.92,
-                distance_m: 25.0,
-                velocity_mps: -2.0, 
-                ttc_s: Some(12.5),
-            }])
+        Ok(self.postprocess(self.retain_in_region(raw, frame.width, frame.height)))
+    }
 
-        } else {
-             // Mock: one vehicle ahead
-            Ok(vec![DetectedObject {
-                class: ObjectClass::Vehicle,
-                bbox: [800.0, 400.0, 300.0, 200.0],
-                confidence: 0.92,
-                distance_m: 25.0,
-                velocity_mps: -2.0, // Approaching
-                ttc_s: Some(12.5),
-            }])
+    /// Run the model on one image, returning boxes in that image's coordinates
+    fn infer(&self, session: &Session, frame: &VideoFrame) -> Result<Vec<DetectedObject>, AdasError> {
+        // 1. Preprocess: letterbox to 640x640 (standard YOLO input) so road
+        // frames keep their aspect ratio
+        let (input, letterbox) = frame.letterbox(MODEL_INPUT_SIZE, MODEL_INPUT_SIZE, LETTERBOX_PAD);
+
+        // 2. Normalize (YOLO: 0-1) and create tensor (NCHW)
+        let size = MODEL_INPUT_SIZE as usize;
+        let mut input_array = Array4::<f32>::zeros((1, 3, size, size));
+        for y in 0..size {
+            for x in 0..size {
+                let offset = (y * size + x) * 3;
+                for c in 0..3 {
+                    input_array[[0, c, y, x]] = self.normalization.apply(input.data[offset + c], c);
+                }
+            }
+        }
+
+        // 3. Inference
+        let outputs = session.run(ort::inputs![input_array].map_err(|e| AdasError::Inference(e.to_string()))?)
+            .map_err(|e| AdasError::Inference(e.to_string()))?;
+
+        // 4. Post-process: decode the head, then map boxes from letterboxed
+        // model coordinates back to this image. Distance and TTC are filled
+        // in after NMS from the camera geometry
+        let output_tensor = outputs.get(0).ok_or(AdasError::Inference("No output tensor".into()))?;
+        let output = output_tensor
+            .try_extract_tensor::<f32>()
+            .map_err(|e| AdasError::Inference(e.to_string()))?;
+
+        Ok(yolo::decode(output, self.confidence_threshold)?
+            .into_iter()
+            .map(|d| DetectedObject {
+                class: ObjectClass::from_coco(d.class_id),
+                bbox: letterbox.to_original(d.bbox),
+                confidence: d.confidence,
+                distance_m: 0.0,
+                velocity_mps: 0.0,
+                ttc_s: None,
+            })
+            .collect())
+    }
+
+    /// Mock: one vehicle ahead, wandering within its lane
+    fn mock_detections(&self) -> Vec<DetectedObject> {
+        vec![DetectedObject {
+            class: ObjectClass::Vehicle,
+            bbox: [
+                self.mock.jitter(800.0, 20.0),
+                self.mock.jitter(400.0, 10.0),
+                300.0,
+                200.0,
+            ],
+            confidence: self.mock.uniform(0.8, 0.98),
+            distance_m: 25.0,
+            velocity_mps: self.mock.uniform(-4.0, 1.0), // Mostly approaching
+            ttc_s: None,
+        }]
+    }
+
+    /// Drop detections centered outside the ROI mask of a `width` × `height`
+    /// frame, so hood and sky false positives can't suppress real boxes in NMS
+    pub fn retain_in_region(
+        &self,
+        mut detections: Vec<DetectedObject>,
+        width: u32,
+        height: u32,
+    ) -> Vec<DetectedObject> {
+        if let Some(mask) = &self.roi_mask {
+            detections.retain(|d| mask.contains_box(&d.bbox, width, height));
+        }
+        detections
+    }
+
+    /// Drop low-confidence and disallowed classes, then apply per-class NMS
+    pub fn postprocess(&self, mut detections: Vec<DetectedObject>) -> Vec<DetectedObject> {
+        detections.retain(|d| {
+            d.confidence >= self.confidence_threshold && self.allowed_classes.contains(&d.class)
+        });
+        detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+        let mut kept: Vec<DetectedObject> = Vec::with_capacity(detections.len());
+        for candidate in detections {
+            let suppressed = kept.iter().any(|k| {
+                k.class == candidate.class && k.iou(&candidate) > self.nms_iou_threshold
+            });
+            if !suppressed {
+                kept.push(candidate);
+            }
+        }
+        for object in &mut kept {
+            self.estimate_range(object);
+        }
+        kept
+    }
+
+    /// Fill distance and time-to-collision from the box's ground contact row
+    fn estimate_range(&self, object: &mut DetectedObject) {
+        let Some(distance) = self.geometry.distance_to_box(&object.bbox) else {
+            return;
+        };
+        object.distance_m = distance;
+        object.ttc_s = (object.velocity_mps < 0.0).then(|| distance / -object.velocity_mps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(class: ObjectClass, bbox: [f32; 4], confidence: f32) -> DetectedObject {
+        DetectedObject {
+            class,
+            bbox,
+            confidence,
+            distance_m: 0.0,
+            velocity_mps: 0.0,
+            ttc_s: None,
         }
     }
+
+    #[test]
+    fn test_class_filter_and_nms() {
+        let config = AdasConfig {
+            object_classes: vec![ObjectClass::Vehicle, ObjectClass::Pedestrian],
+            nms_iou_threshold: 0.5,
+            ..Default::default()
+        };
+        let detector = ObjectDetector::new(&config).unwrap();
+
+        let kept = detector.postprocess(vec![
+            detection(ObjectClass::Vehicle, [100.0, 100.0, 200.0, 100.0], 0.9),
+            // Same car, slightly shifted: merged into the first
+            detection(ObjectClass::Vehicle, [110.0, 105.0, 200.0, 100.0], 0.8),
+            // Overlapping pedestrian is a different class: kept
+            detection(ObjectClass::Pedestrian, [120.0, 100.0, 50.0, 100.0], 0.7),
+            // Disallowed class ("toaster" and friends)
+            detection(ObjectClass::from_coco(70), [500.0, 500.0, 20.0, 20.0], 0.95),
+            detection(ObjectClass::Truck, [800.0, 100.0, 200.0, 200.0], 0.9),
+        ]);
+
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].class, ObjectClass::Vehicle);
+        assert_eq!(kept[0].confidence, 0.9);
+        assert_eq!(kept[1].class, ObjectClass::Pedestrian);
+    }
+
+    #[test]
+    fn test_iou() {
+        let a = detection(ObjectClass::Vehicle, [0.0, 0.0, 10.0, 10.0], 1.0);
+        let b = detection(ObjectClass::Vehicle, [5.0, 0.0, 10.0, 10.0], 1.0);
+        let c = detection(ObjectClass::Vehicle, [20.0, 20.0, 5.0, 5.0], 1.0);
+        assert!((a.iou(&b) - 50.0 / 150.0).abs() < 1e-6);
+        assert_eq!(a.iou(&c), 0.0);
+    }
+
+    #[test]
+    fn test_detections_outside_roi_mask_dropped() {
+        // Road trapezoid: excludes the sky above 40% and the hood below 85%
+        let mask = RoiMask::new(vec![(0.0, 0.85), (0.4, 0.4), (0.6, 0.4), (1.0, 0.85)]).unwrap();
+        let config = AdasConfig {
+            roi_mask: Some(mask),
+            ..Default::default()
+        };
+        let detector = ObjectDetector::new(&config).unwrap();
+
+        let in_region = detections_in_1080p(&detector, vec![
+            // Car ahead, centered at (960, 600)
+            detection(ObjectClass::Vehicle, [860.0, 550.0, 200.0, 100.0], 0.9),
+            // "Vehicle" reflected in the sky, centered at (960, 150)
+            detection(ObjectClass::Vehicle, [860.0, 100.0, 200.0, 100.0], 0.95),
+            // Hood ornament, centered at (960, 1000)
+            detection(ObjectClass::Vehicle, [900.0, 950.0, 120.0, 100.0], 0.8),
+        ]);
+
+        assert_eq!(in_region.len(), 1);
+        assert_eq!(in_region[0].bbox, [860.0, 550.0, 200.0, 100.0]);
+    }
+
+    fn detections_in_1080p(detector: &ObjectDetector, raw: Vec<DetectedObject>) -> Vec<DetectedObject> {
+        detector.postprocess(detector.retain_in_region(raw, 1920, 1080))
+    }
 }
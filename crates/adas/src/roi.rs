@@ -0,0 +1,75 @@
+//! Drivable-region mask
+//!
+//! The road camera also sees the hood, the sky and reflections of the
+//! dashboard, all of which produce detections that are never relevant to
+//! the vehicle. The mask is a polygon in coordinates normalized to the
+//! frame (0.0-1.0 on both axes, origin top-left), so one mask serves any
+//! capture resolution from the same mounting.
+
+use serde::{Deserialize, Serialize};
+
+use crate::AdasError;
+
+/// Polygon enclosing the drivable region of the road image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoiMask {
+    /// Vertices in order, normalized to frame width and height
+    pub polygon: Vec<(f32, f32)>,
+}
+
+impl RoiMask {
+    /// Build a mask from normalized vertices
+    pub fn new(polygon: Vec<(f32, f32)>) -> Result<Self, AdasError> {
+        let mask = Self { polygon };
+        mask.validate()?;
+        Ok(mask)
+    }
+
+    /// Check the polygon, e.g. after loading it from config
+    pub fn validate(&self) -> Result<(), AdasError> {
+        let polygon = &self.polygon;
+        if polygon.len() < 3 {
+            return Err(AdasError::InvalidConfig(format!(
+                "ROI mask needs at least 3 vertices, got {}",
+                polygon.len()
+            )));
+        }
+        if let Some(vertex) = polygon
+            .iter()
+            .find(|(x, y)| !(0.0..=1.0).contains(x) || !(0.0..=1.0).contains(y))
+        {
+            return Err(AdasError::InvalidConfig(format!(
+                "ROI mask vertex {vertex:?} is outside the normalized frame"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether a normalized point lies inside the polygon (even-odd rule)
+    pub fn contains_normalized(&self, x: f32, y: f32) -> bool {
+        let mut inside = false;
+        let n = self.polygon.len();
+        for i in 0..n {
+            let (xi, yi) = self.polygon[i];
+            let (xj, yj) = self.polygon[(i + n - 1) % n];
+            if (yi > y) != (yj > y) && x < xi + (xj - xi) * (y - yi) / (yj - yi) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Whether a pixel position in a `width` × `height` frame is inside
+    pub fn contains(&self, x: f32, y: f32, width: u32, height: u32) -> bool {
+        if width == 0 || height == 0 {
+            return false;
+        }
+        self.contains_normalized(x / width as f32, y / height as f32)
+    }
+
+    /// Whether the center of an `[x, y, width, height]` box is inside
+    pub fn contains_box(&self, bbox: &[f32; 4], width: u32, height: u32) -> bool {
+        let [x, y, w, h] = *bbox;
+        self.contains(x + w / 2.0, y + h / 2.0, width, height)
+    }
+}
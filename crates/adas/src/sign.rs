@@ -2,13 +2,16 @@
 
 use serde::{Deserialize, Serialize};
 use camera_capture::frame::VideoFrame;
-use crate::{AdasConfig, AdasError};
+use camera_capture::Normalization;
+use crate::tiling::tile_grid;
+use crate::yolo;
+use crate::{AdasConfig, AdasError, TilingConfig};
 use ort::{Session, GraphOptimizationLevel};
 use ndarray::{Array4, Axis};
 use tracing::{info, warn, error};
 
 /// Traffic sign types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TrafficSign {
     /// Speed limit (km/h)
     SpeedLimit(u32),
@@ -29,6 +32,10 @@ pub enum TrafficSign {
 /// Traffic sign classifier
 pub struct SignClassifier {
     enabled: bool,
+    confidence_threshold: f32,
+    classes: Vec<TrafficSign>,
+    tiling: Option<TilingConfig>,
+    normalization: Normalization,
     session: Option<Session>,
 }
 
@@ -66,6 +73,10 @@ impl SignClassifier {
 
         Ok(Self {
             enabled: config.sign_detection_enabled,
+            confidence_threshold: config.sign_confidence,
+            classes: config.sign_classes.clone(),
+            tiling: config.tiled_inference.then_some(config.tiling),
+            normalization: config.sign_normalization,
             session,
         })
     }
@@ -76,47 +87,84 @@ impl SignClassifier {
             return Ok(vec![]);
         }
 
-        if let Some(session) = &self.session {
-             // 1. Preprocess: Resize to 640x640 (standard YOLO)
-             // Similar to ObjectDetector
-            let img = match image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
-                frame.width, 
-                frame.height, 
-                &frame.data
-            ) {
-                Some(i) => i,
-                None => return Err(AdasError::ImageProcessing("Failed to create image buffer".into())),
-            };
-
-            let input_width = 640;
-            let input_height = 640;
-            let resized = image::imageops::resize(&img, input_width, input_height, image::imageops::FilterType::Triangle);
-
-            // 2. Normalize 0-1
-            let mut input_array = Array4::<f32>::zeros((1, 3, input_height as usize, input_width as usize));
-            for (x, y, pixel) in resized.enumerate_pixels() {
-                input_array[[0, 0, y as usize, x as usize]] = pixel[0] as f32 / 255.0;
-                input_array[[0, 1, y as usize, x as usize]] = pixel[1] as f32 / 255.0;
-                input_array[[0, 2, y as usize, x as usize]] = pixel[2] as f32 / 255.0;
-            }
+        frame.validate_rgb().map_err(|e| AdasError::ImageProcessing(e.to_string()))?;
 
-            // 3. Inference
-            let outputs = session.run(ort::inputs![input_array].map_err(|e| AdasError::Inference(e.to_string()))?)
-                .map_err(|e| AdasError::Inference(e.to_string()))?;
+        let Some(session) = &self.session else {
+            // Mock: no signs detected
+            return Ok(vec![]);
+        };
 
-            // 4. Post-process
-            // Parsing YOLO output [1, anchors, 85] (approx)
-            // Need to map class ID to TrafficSign
-            
-            // Mocking a detected sign for now to confirm pipeline works
-            // In real logic:
-            // let sign = match class_id { 0 => TrafficSign::SpeedLimit(30), ... };
-            
-            Ok(vec![])
+        let Some(tiling) = &self.tiling else {
+            return self.infer(session, frame);
+        };
 
+        // Signs carry no box to run NMS on; drop repeats from overlapping tiles
+        let mut signs = if tiling.include_full_frame {
+            self.infer(session, frame)?
         } else {
-            // Mock: no signs detected
-            Ok(vec![])
+            Vec::new()
+        };
+        for tile in tile_grid(frame.width, frame.height, tiling) {
+            let crop = frame
+                .crop(tile.x, tile.y, tile.width, tile.height)
+                .ok_or(AdasError::InvalidFrame)?;
+            for sign in self.infer(session, &crop)? {
+                if !signs.contains(&sign) {
+                    signs.push(sign);
+                }
+            }
         }
+        Ok(signs)
+    }
+
+    /// Run the model on one image
+    fn infer(&self, session: &Session, frame: &VideoFrame) -> Result<Vec<TrafficSign>, AdasError> {
+        // 1. Preprocess: Resize to 640x640 (standard YOLO)
+        // Similar to ObjectDetector
+        let img = match image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
+            frame.width, 
+            frame.height, 
+            frame.data.as_slice()
+        ) {
+            Some(i) => i,
+            None => return Err(AdasError::ImageProcessing("Failed to create image buffer".into())),
+        };
+
+        let input_width = 640;
+        let input_height = 640;
+        let resized = image::imageops::resize(&img, input_width, input_height, image::imageops::FilterType::Triangle);
+
+        // 2. Normalize
+        let mut input_array = Array4::<f32>::zeros((1, 3, input_height as usize, input_width as usize));
+        for (x, y, pixel) in resized.enumerate_pixels() {
+            for c in 0..3 {
+                input_array[[0, c, y as usize, x as usize]] = self.normalization.apply(pixel[c], c);
+            }
+        }
+
+        // 3. Inference
+        let outputs = session.run(ort::inputs![input_array].map_err(|e| AdasError::Inference(e.to_string()))?)
+            .map_err(|e| AdasError::Inference(e.to_string()))?;
+
+        // 4. Post-process: only the class matters, so the stretched boxes
+        // are never mapped back
+        let output_tensor = outputs.get(0).ok_or(AdasError::Inference("No output tensor".into()))?;
+        let output = output_tensor
+            .try_extract_tensor::<f32>()
+            .map_err(|e| AdasError::Inference(e.to_string()))?;
+
+        let mut signs = Vec::new();
+        for detection in yolo::decode(output, self.confidence_threshold)? {
+            let sign = self.sign_for_class(detection.class_id);
+            if !signs.contains(&sign) {
+                signs.push(sign);
+            }
+        }
+        Ok(signs)
+    }
+
+    /// Map a model class index to a sign
+    fn sign_for_class(&self, class_id: usize) -> TrafficSign {
+        self.classes.get(class_id).cloned().unwrap_or(TrafficSign::Unknown)
     }
 }
@@ -0,0 +1,181 @@
+//! Tiled inference for high-resolution road frames
+//!
+//! Squashing a 1080p frame into a 640×640 model input shrinks distant
+//! pedestrians and signs to a few pixels. Running the detector on
+//! overlapping full-resolution tiles keeps them visible; tile-local boxes
+//! are shifted back into frame coordinates so a single global NMS pass can
+//! merge duplicates from overlapping tiles.
+
+use camera_capture::frame::VideoFrame;
+use serde::{Deserialize, Serialize};
+
+use crate::{AdasError, DetectedObject};
+
+/// Tile layout for tiled inference
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TilingConfig {
+    /// Tile edge length (pixels); matches the model input to avoid rescaling
+    pub tile_size: u32,
+    /// Fraction of a tile shared with its neighbour, in `0.0..1.0`
+    pub overlap: f32,
+    /// Also run on the whole (downscaled) frame, for objects larger than a tile
+    pub include_full_frame: bool,
+}
+
+impl Default for TilingConfig {
+    fn default() -> Self {
+        Self {
+            tile_size: 640,
+            overlap: 0.2,
+            include_full_frame: true,
+        }
+    }
+}
+
+/// A rectangular region of the source frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Tile {
+    /// Map a tile-local `[x, y, width, height]` box to frame coordinates
+    pub fn to_frame(&self, bbox: [f32; 4]) -> [f32; 4] {
+        [bbox[0] + self.x as f32, bbox[1] + self.y as f32, bbox[2], bbox[3]]
+    }
+}
+
+/// Offsets along one axis; the last tile is pinned to the far edge
+fn axis_offsets(length: u32, tile: u32, step: u32) -> Vec<u32> {
+    if length <= tile {
+        return vec![0];
+    }
+    let last = length - tile;
+    let mut offsets: Vec<u32> = (0..last).step_by(step as usize).collect();
+    offsets.push(last);
+    offsets
+}
+
+/// Overlapping tiles covering a `width`×`height` frame
+pub fn tile_grid(width: u32, height: u32, config: &TilingConfig) -> Vec<Tile> {
+    let tile = config.tile_size.max(1);
+    let overlap = config.overlap.clamp(0.0, 0.9);
+    let step = ((tile as f32 * (1.0 - overlap)) as u32).max(1);
+
+    let xs = axis_offsets(width, tile, step);
+    let ys = axis_offsets(height, tile, step);
+    ys.iter()
+        .flat_map(|&y| {
+            xs.iter().map(move |&x| Tile {
+                x,
+                y,
+                width: tile.min(width),
+                height: tile.min(height),
+            })
+        })
+        .collect()
+}
+
+/// Run `detect` on every tile and return all boxes in frame coordinates
+///
+/// The result is not deduplicated; callers apply their usual NMS over the
+/// combined list.
+pub fn detect_tiled<F>(
+    frame: &VideoFrame,
+    config: &TilingConfig,
+    mut detect: F,
+) -> Result<Vec<DetectedObject>, AdasError>
+where
+    F: FnMut(&VideoFrame) -> Result<Vec<DetectedObject>, AdasError>,
+{
+    let mut detections = if config.include_full_frame {
+        detect(frame)?
+    } else {
+        Vec::new()
+    };
+
+    for tile in tile_grid(frame.width, frame.height, config) {
+        let crop = frame
+            .crop(tile.x, tile.y, tile.width, tile.height)
+            .ok_or(AdasError::InvalidFrame)?;
+        detections.extend(detect(&crop)?.into_iter().map(|mut d| {
+            d.bbox = tile.to_frame(d.bbox);
+            d
+        }));
+    }
+
+    Ok(detections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectClass;
+
+    const MODEL_INPUT: u32 = 640;
+
+    /// Stand-in for a detector with a fixed input size: finds bright pixels
+    /// after resizing, and needs at least 3×3 of them to fire
+    fn blob_detector(frame: &VideoFrame) -> Result<Vec<DetectedObject>, AdasError> {
+        let input = frame.resize(MODEL_INPUT, MODEL_INPUT);
+        let bright: Vec<(u32, u32)> = (0..MODEL_INPUT)
+            .flat_map(|y| (0..MODEL_INPUT).map(move |x| (x, y)))
+            .filter(|&(x, y)| input.get_pixel(x, y).is_some_and(|p| p[0] > 200))
+            .collect();
+        if bright.len() < 9 {
+            return Ok(vec![]);
+        }
+
+        let sx = frame.width as f32 / MODEL_INPUT as f32;
+        let sy = frame.height as f32 / MODEL_INPUT as f32;
+        let min_x = bright.iter().map(|p| p.0).min().unwrap() as f32;
+        let max_x = bright.iter().map(|p| p.0).max().unwrap() as f32 + 1.0;
+        let min_y = bright.iter().map(|p| p.1).min().unwrap() as f32;
+        let max_y = bright.iter().map(|p| p.1).max().unwrap() as f32 + 1.0;
+        Ok(vec![DetectedObject {
+            class: ObjectClass::Pedestrian,
+            bbox: [min_x * sx, min_y * sy, (max_x - min_x) * sx, (max_y - min_y) * sy],
+            confidence: 0.9,
+            distance_m: 0.0,
+            velocity_mps: 0.0,
+            ttc_s: None,
+        }])
+    }
+
+    fn frame_with_distant_object() -> VideoFrame {
+        let (width, height) = (1920, 1080);
+        let mut data = vec![0u8; (width * height * 3) as usize];
+        // 6×6 px object far down the road
+        for y in 900..906 {
+            for x in 1500..1506 {
+                let i = ((y * width + x) * 3) as usize;
+                data[i..i + 3].copy_from_slice(&[255, 255, 255]);
+            }
+        }
+        VideoFrame::new(data, width, height, 0, 0)
+    }
+
+    #[test]
+    fn test_grid_covers_frame() {
+        let tiles = tile_grid(1920, 1080, &TilingConfig::default());
+        let xs: Vec<u32> = tiles.iter().filter(|t| t.y == 0).map(|t| t.x).collect();
+        assert_eq!(xs, vec![0, 512, 1024, 1280]);
+        assert_eq!(tiles.len(), 8);
+        assert!(tiles.iter().all(|t| t.x + t.width <= 1920 && t.y + t.height <= 1080));
+    }
+
+    #[test]
+    fn test_small_object_found_only_with_tiles() {
+        let frame = frame_with_distant_object();
+        assert!(blob_detector(&frame).unwrap().is_empty());
+
+        let detections = detect_tiled(&frame, &TilingConfig::default(), blob_detector).unwrap();
+        assert!(!detections.is_empty());
+        for d in &detections {
+            assert_eq!(d.bbox, [1500.0, 900.0, 6.0, 6.0]);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! YOLO detection head decoding
+//!
+//! Exports come in two layouts: YOLOv5 emits `[1, anchors, 5 + classes]`
+//! with an objectness score after the box, YOLOv8 emits
+//! `[1, 4 + classes, anchors]` without one. There are always far more
+//! anchors than channels, so the longer axis tells them apart.
+
+use ndarray::{ArrayViewD, Ix3};
+
+use crate::AdasError;
+
+/// One decoded candidate, before class filtering and NMS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct YoloDetection {
+    /// Index into the model's class list
+    pub class_id: usize,
+
+    /// Box [x, y, width, height] in model input coordinates
+    pub bbox: [f32; 4],
+
+    /// Class score, times objectness for YOLOv5
+    pub confidence: f32,
+}
+
+/// Decode a raw detection head, keeping candidates at or above `min_confidence`
+pub fn decode(output: ArrayViewD<'_, f32>, min_confidence: f32) -> Result<Vec<YoloDetection>, AdasError> {
+    let shape = output.shape().to_vec();
+    let output = output
+        .into_dimensionality::<Ix3>()
+        .map_err(|_| AdasError::Inference(format!("unexpected detector output shape {:?}", shape)))?;
+
+    let anchors_first = shape[1] > shape[2];
+    let (anchors, channels) = if anchors_first {
+        (shape[1], shape[2])
+    } else {
+        (shape[2], shape[1])
+    };
+    let class_offset = if anchors_first { 5 } else { 4 };
+    if shape[0] != 1 || channels <= class_offset {
+        return Err(AdasError::Inference(format!(
+            "unexpected detector output shape {:?}",
+            shape
+        )));
+    }
+
+    let at = |anchor: usize, channel: usize| {
+        if anchors_first {
+            output[[0, anchor, channel]]
+        } else {
+            output[[0, channel, anchor]]
+        }
+    };
+
+    let mut detections = Vec::new();
+    for anchor in 0..anchors {
+        let objectness = if anchors_first { at(anchor, 4) } else { 1.0 };
+        let (class_id, score) = (class_offset..channels)
+            .map(|channel| (channel - class_offset, at(anchor, channel)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("at least one class channel");
+
+        let confidence = objectness * score;
+        if confidence < min_confidence {
+            continue;
+        }
+
+        let (cx, cy, w, h) = (at(anchor, 0), at(anchor, 1), at(anchor, 2), at(anchor, 3));
+        detections.push(YoloDetection {
+            class_id,
+            bbox: [cx - w / 2.0, cy - h / 2.0, w, h],
+            confidence,
+        });
+    }
+    Ok(detections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array3, IxDyn};
+
+    #[test]
+    fn test_decode_v8_layout() {
+        // [1, 4 + 2 classes, 8 anchors]; anchor 3 is a confident class-1 box
+        let mut output = Array3::<f32>::zeros((1, 6, 8));
+        for (channel, value) in [100.0, 50.0, 20.0, 10.0, 0.1, 0.9].into_iter().enumerate() {
+            output[[0, channel, 3]] = value;
+        }
+        // Anchor 5 is below the threshold
+        output[[0, 4, 5]] = 0.3;
+
+        let detections = decode(output.into_dyn().view(), 0.5).unwrap();
+        assert_eq!(
+            detections,
+            vec![YoloDetection {
+                class_id: 1,
+                bbox: [90.0, 45.0, 20.0, 10.0],
+                confidence: 0.9,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_v5_layout_scales_by_objectness() {
+        // [1, 8 anchors, 5 + 2 classes]
+        let mut output = Array3::<f32>::zeros((1, 8, 7));
+        for (channel, value) in [40.0, 40.0, 8.0, 8.0, 0.8, 0.9, 0.2].into_iter().enumerate() {
+            output[[0, 2, channel]] = value;
+        }
+        // Confident class, but low objectness: 0.5 * 0.9 falls below 0.5
+        for (channel, value) in [10.0, 10.0, 4.0, 4.0, 0.5, 0.9, 0.0].into_iter().enumerate() {
+            output[[0, 6, channel]] = value;
+        }
+
+        let detections = decode(output.into_dyn().view(), 0.5).unwrap();
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].class_id, 0);
+        assert_eq!(detections[0].bbox, [36.0, 36.0, 8.0, 8.0]);
+        assert!((detections[0].confidence - 0.72).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_decode_rejects_unexpected_shape() {
+        let output = ndarray::ArrayD::<f32>::zeros(IxDyn(&[1, 84]));
+        assert!(decode(output.view(), 0.5).is_err());
+    }
+}
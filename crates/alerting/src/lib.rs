@@ -1,7 +1,23 @@
 //! Alerting System
 //!
-//! Provides confidence calibration, alert deduplication, and severity mapping.
+//! Provides confidence calibration, alert deduplication, severity mapping,
+//! the prioritized queue that feeds alerts to the cloud uploader, and the
+//! routing table that maps alerts to actions.
 
 mod manager;
+mod queue;
+mod router;
+mod smoother;
 
-pub use manager::{AlertManager, AlertConfig, AlertState};
+pub use manager::{
+    AlertConfig, AlertManager, AlertState, ConfigError, FiredAlert, SuppressedAlert, SuppressionWindow,
+    MAX_SUPPRESSED_ALERTS, SAFETY_CRITICAL_FAULTS, SUPPRESSED_ALERT_EVENT,
+};
+pub use queue::{AlertQueue, AlertQueueConfig, AlertSource, PushOutcome, QueuedAlert};
+pub use router::{
+    ActionError, ActionHandler, AlertAction, AlertRoute, AlertRouter, DispatchReport, RoutedAlert, RoutingTable,
+    ANY_ALERT,
+};
+pub use smoother::ConfidenceSmoother;
+
+pub use common_types::Severity;
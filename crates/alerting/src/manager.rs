@@ -1,10 +1,28 @@
 //! Alert Manager Implementation
 
+use chrono::{DateTime, FixedOffset, NaiveTime, Utc};
+use common_types::{Severity, SharedClock, SystemClock};
+use inference_engine::{FaultType, Prediction};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use storage::{EventRecord, Storage};
+use thiserror::Error;
 use tracing::{debug, info, warn};
 
+use crate::smoother::ConfidenceSmoother;
+
+/// Fault keys that fire even in maintenance mode or a quiet window
+pub const SAFETY_CRITICAL_FAULTS: &[&str] = &["crash", "rollover"];
+
+/// Suppressed alerts kept between [`AlertManager::take_suppressed`] calls
+/// when no storage is attached; the oldest are dropped beyond this
+pub const MAX_SUPPRESSED_ALERTS: usize = 256;
+
+/// Event kind suppressed alerts are stored under
+pub const SUPPRESSED_ALERT_EVENT: &str = "suppressed_alert";
+
 /// Alert configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertConfig {
@@ -16,6 +34,13 @@ pub struct AlertConfig {
     pub cooldown_seconds: u64,
     /// Maximum alerts per hour before throttling
     pub max_alerts_per_hour: usize,
+    /// EWMA factor for per-fault confidence smoothing (default: 0.3)
+    #[serde(default = "default_smoothing_alpha")]
+    pub smoothing_alpha: f64,
+}
+
+fn default_smoothing_alpha() -> f64 {
+    0.3
 }
 
 impl Default for AlertConfig {
@@ -25,7 +50,62 @@ impl Default for AlertConfig {
             critical_threshold: 0.90,
             cooldown_seconds: 1800, // 30 minutes
             max_alerts_per_hour: 10,
+            smoothing_alpha: default_smoothing_alpha(),
+        }
+    }
+}
+
+/// Invalid alerting configuration
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("{field} must be {expected}, got {value}")]
+    OutOfRange {
+        field: &'static str,
+        value: f64,
+        expected: &'static str,
+    },
+
+    #[error("critical_threshold ({critical}) must not be below confidence_threshold ({confidence})")]
+    ThresholdOrder { confidence: f64, critical: f64 },
+
+    #[error("route for {key:?} {reason}")]
+    InvalidRoute { key: String, reason: &'static str },
+}
+
+impl AlertConfig {
+    /// Check ranges and invariants
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let unit = |field, value: f64| {
+            if (0.0..=1.0).contains(&value) {
+                Ok(())
+            } else {
+                Err(ConfigError::OutOfRange { field, value, expected: "within 0.0..=1.0" })
+            }
+        };
+        unit("confidence_threshold", self.confidence_threshold)?;
+        unit("critical_threshold", self.critical_threshold)?;
+
+        if self.critical_threshold < self.confidence_threshold {
+            return Err(ConfigError::ThresholdOrder {
+                confidence: self.confidence_threshold,
+                critical: self.critical_threshold,
+            });
+        }
+        if self.max_alerts_per_hour == 0 {
+            return Err(ConfigError::OutOfRange {
+                field: "max_alerts_per_hour",
+                value: 0.0,
+                expected: "at least 1",
+            });
+        }
+        if !(self.smoothing_alpha > 0.0 && self.smoothing_alpha <= 1.0) {
+            return Err(ConfigError::OutOfRange {
+                field: "smoothing_alpha",
+                value: self.smoothing_alpha,
+                expected: "within (0.0, 1.0]",
+            });
         }
+        Ok(())
     }
 }
 
@@ -40,6 +120,71 @@ pub struct AlertState {
     pub acknowledged: bool,
 }
 
+/// Alert raised from a model prediction
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FiredAlert {
+    /// Fault that triggered the alert
+    pub fault_type: FaultType,
+    /// Confidence of the prediction
+    pub confidence: f64,
+    /// Severity derived from the confidence
+    pub severity: Severity,
+    /// Action to show the driver
+    pub recommended_action: &'static str,
+    /// Timestamp of the prediction (Unix ms)
+    pub timestamp_ms: u64,
+}
+
+/// Period during which non-safety alerts are held back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionWindow {
+    /// One-off wall-clock period, e.g. a depot service slot
+    Once {
+        /// Start of the window (inclusive)
+        start: SystemTime,
+        /// End of the window (exclusive)
+        end: SystemTime,
+    },
+    /// Quiet hours repeating every day in the vehicle's local time
+    Daily {
+        /// Local time the window opens (inclusive)
+        start: NaiveTime,
+        /// Local time the window closes (exclusive); may be before `start`
+        /// for a window that crosses midnight
+        end: NaiveTime,
+        /// Vehicle's local offset from UTC
+        utc_offset: FixedOffset,
+    },
+}
+
+impl SuppressionWindow {
+    /// Check if `time` falls inside the window
+    pub fn contains(&self, time: SystemTime) -> bool {
+        match *self {
+            SuppressionWindow::Once { start, end } => start <= time && time < end,
+            SuppressionWindow::Daily { start, end, utc_offset } => {
+                let local = DateTime::<Utc>::from(time).with_timezone(&utc_offset).time();
+                if start <= end {
+                    start <= local && local < end
+                } else {
+                    local >= start || local < end
+                }
+            }
+        }
+    }
+}
+
+/// Alert that would have fired but was suppressed, kept for review
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuppressedAlert {
+    /// Fault key passed to [`AlertManager::should_fire`]
+    pub fault_type: String,
+    /// Confidence at the time
+    pub confidence: f64,
+    /// When it was suppressed
+    pub at: SystemTime,
+}
+
 /// Alert manager for deduplication and throttling
 pub struct AlertManager {
     /// Configuration
@@ -50,18 +195,56 @@ pub struct AlertManager {
     hourly_count: usize,
     /// Hour start time
     hour_start: Instant,
+    /// Per-fault confidence smoother
+    smoother: ConfidenceSmoother,
+    /// Time source for cooldowns and the hourly window
+    clock: SharedClock,
+    /// Depot maintenance: hold back all non-safety alerts
+    maintenance_mode: bool,
+    /// Scheduled quiet periods
+    suppression_windows: Vec<SuppressionWindow>,
+    /// Alerts held back since the last [`Self::take_suppressed`], at most
+    /// [`MAX_SUPPRESSED_ALERTS`]; only used without storage
+    suppressed: VecDeque<SuppressedAlert>,
+    /// Where suppressed alerts are recorded for later review
+    storage: Option<Arc<dyn Storage>>,
 }
 
 impl AlertManager {
     /// Create a new alert manager
-    pub fn new(config: AlertConfig) -> Self {
+    pub fn new(config: AlertConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
         info!("Creating alert manager with config: {:?}", config);
-        Self {
+        let clock = SystemClock::shared();
+        Ok(Self {
+            smoother: ConfidenceSmoother::new(config.smoothing_alpha),
             config,
             states: HashMap::new(),
             hourly_count: 0,
-            hour_start: Instant::now(),
-        }
+            hour_start: clock.instant(),
+            clock,
+            maintenance_mode: false,
+            suppression_windows: Vec::new(),
+            suppressed: VecDeque::new(),
+            storage: None,
+        })
+    }
+
+    /// Use `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.hour_start = clock.instant();
+        self.clock = clock;
+        self
+    }
+
+    /// Record suppressed alerts as [`SUPPRESSED_ALERT_EVENT`] events in
+    /// `storage` instead of buffering them in memory
+    ///
+    /// Each write is spawned, so [`Self::should_fire`] must then be called
+    /// from within a Tokio runtime.
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
     }
 
     /// Check if an alert should be fired based on confidence and deduplication
@@ -73,10 +256,21 @@ impl AlertManager {
             return false;
         }
 
+        if self.is_suppressed(fault_type) {
+            debug!("Alert suppressed: {} during maintenance/quiet hours", fault_type);
+            self.record_suppressed(SuppressedAlert {
+                fault_type: fault_type.to_string(),
+                confidence,
+                at: self.clock.now(),
+            });
+            return false;
+        }
+
         // Reset hourly counter if needed
-        if self.hour_start.elapsed() > Duration::from_secs(3600) {
+        let now = self.clock.instant();
+        if now.duration_since(self.hour_start) > Duration::from_secs(3600) {
             self.hourly_count = 0;
-            self.hour_start = Instant::now();
+            self.hour_start = now;
         }
 
         // Check hourly throttle
@@ -88,7 +282,7 @@ impl AlertManager {
         // Check cooldown
         if let Some(state) = self.states.get(fault_type) {
             let cooldown = Duration::from_secs(self.config.cooldown_seconds);
-            if state.last_fired.elapsed() < cooldown {
+            if now.duration_since(state.last_fired) < cooldown {
                 debug!("Alert suppressed: in cooldown period");
                 return false;
             }
@@ -97,23 +291,132 @@ impl AlertManager {
         true
     }
 
+    /// Hold back all alerts except [`SAFETY_CRITICAL_FAULTS`] while `on`
+    pub fn set_maintenance_mode(&mut self, on: bool) {
+        if on != self.maintenance_mode {
+            info!("Maintenance mode {}", if on { "on" } else { "off" });
+        }
+        self.maintenance_mode = on;
+    }
+
+    /// Whether maintenance mode is on
+    pub fn maintenance_mode(&self) -> bool {
+        self.maintenance_mode
+    }
+
+    /// Schedule a quiet period, e.g. a depot service slot
+    pub fn add_suppression_window(&mut self, window: SuppressionWindow) {
+        self.suppression_windows.push(window);
+    }
+
+    /// Whether alerts for `fault_type` are currently held back
+    pub fn is_suppressed(&self, fault_type: &str) -> bool {
+        if SAFETY_CRITICAL_FAULTS.contains(&fault_type) {
+            return false;
+        }
+        if self.maintenance_mode {
+            return true;
+        }
+        let now = self.clock.now();
+        self.suppression_windows.iter().any(|w| w.contains(now))
+    }
+
+    /// Drain the alerts suppressed so far, oldest first; always empty once
+    /// storage is attached, since they are written there instead
+    pub fn take_suppressed(&mut self) -> Vec<SuppressedAlert> {
+        self.suppressed.drain(..).collect()
+    }
+
+    /// Store a suppressed alert, or buffer it if no storage is attached
+    fn record_suppressed(&mut self, alert: SuppressedAlert) {
+        let Some(storage) = &self.storage else {
+            if self.suppressed.len() >= MAX_SUPPRESSED_ALERTS {
+                self.suppressed.pop_front();
+            }
+            self.suppressed.push_back(alert);
+            return;
+        };
+
+        let timestamp_ms = alert
+            .at
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        let record = EventRecord {
+            id: 0,
+            timestamp_ms,
+            kind: SUPPRESSED_ALERT_EVENT.to_string(),
+            severity: Some(self.get_severity(alert.confidence).to_string()),
+            payload: serde_json::json!({
+                "fault_type": alert.fault_type,
+                "confidence": alert.confidence,
+            })
+            .to_string(),
+            trip_id: None,
+        };
+        let storage = Arc::clone(storage);
+        tokio::spawn(async move {
+            if let Err(e) = storage.insert_event(record).await {
+                warn!("Failed to store suppressed alert: {}", e);
+            }
+        });
+    }
+
+    /// Smooth a raw inference confidence, then apply [`Self::should_fire`]
+    /// to the smoothed value
+    pub fn should_fire_smoothed(&mut self, fault_type: &str, confidence: f64) -> bool {
+        let smoothed = self.smoother.update(fault_type, confidence);
+        debug!("Smoothed confidence for {}: {:.3} (raw {:.3})", fault_type, smoothed, confidence);
+        self.should_fire(fault_type, smoothed)
+    }
+
+    /// Get the smoothed confidence for a fault, if any has been observed
+    pub fn smoothed_confidence(&self, fault_type: &str) -> Option<f64> {
+        self.smoother.smoothed(fault_type)
+    }
+
     /// Record that an alert was fired
     pub fn record_fire(&mut self, fault_type: &str) {
         self.hourly_count += 1;
-        
+
+        let now = self.clock.instant();
         let state = self.states.entry(fault_type.to_string()).or_insert(AlertState {
-            last_fired: Instant::now(),
+            last_fired: now,
             fire_count: 0,
             acknowledged: false,
         });
         
-        state.last_fired = Instant::now();
+        state.last_fired = now;
         state.fire_count += 1;
         state.acknowledged = false;
 
         info!("Alert recorded: {} (count: {})", fault_type, state.fire_count);
     }
 
+    /// Decide whether a prediction raises an alert, recording it if so
+    ///
+    /// Keys deduplication on [`FaultType::as_str`]; predictions of
+    /// [`FaultType::None`] never fire.
+    pub fn evaluate_prediction(&mut self, prediction: &Prediction) -> Option<FiredAlert> {
+        if prediction.fault_type == FaultType::None {
+            return None;
+        }
+
+        let key = prediction.fault_type.as_str();
+        if !self.should_fire(key, prediction.confidence) {
+            return None;
+        }
+        self.record_fire(key);
+
+        Some(FiredAlert {
+            fault_type: prediction.fault_type,
+            confidence: prediction.confidence,
+            severity: self.severity_for(prediction.confidence),
+            recommended_action: prediction.fault_type.recommended_action(),
+            timestamp_ms: prediction.timestamp_ms,
+        })
+    }
+
     /// Acknowledge an alert
     pub fn acknowledge(&mut self, fault_type: &str) -> bool {
         if let Some(state) = self.states.get_mut(fault_type) {
@@ -126,18 +429,23 @@ impl AlertManager {
     }
 
     /// Get severity level based on confidence
-    pub fn get_severity(&self, confidence: f64) -> &'static str {
+    pub fn severity_for(&self, confidence: f64) -> Severity {
         if confidence >= self.config.critical_threshold {
-            "critical"
+            Severity::Critical
         } else if confidence >= 0.85 {
-            "high"
+            Severity::High
         } else if confidence >= self.config.confidence_threshold {
-            "medium"
+            Severity::Medium
         } else {
-            "low"
+            Severity::Low
         }
     }
 
+    /// Get severity name based on confidence, as stored with predictions
+    pub fn get_severity(&self, confidence: f64) -> &'static str {
+        self.severity_for(confidence).as_str()
+    }
+
     /// Get pending (unacknowledged) alerts
     pub fn get_pending(&self) -> Vec<(&str, &AlertState)> {
         self.states
@@ -155,19 +463,21 @@ impl AlertManager {
     /// Clear all alert states
     pub fn clear(&mut self) {
         self.states.clear();
+        self.smoother.clear();
         self.hourly_count = 0;
     }
 }
 
 impl Default for AlertManager {
     fn default() -> Self {
-        Self::new(AlertConfig::default())
+        Self::new(AlertConfig::default()).expect("default alert config is valid")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common_types::MockClock;
 
     #[test]
     fn test_confidence_threshold() {
@@ -186,7 +496,7 @@ mod tests {
             cooldown_seconds: 60, // Short cooldown for test
             ..Default::default()
         };
-        let mut manager = AlertManager::new(config);
+        let mut manager = AlertManager::new(config).unwrap();
         
         // First alert should fire
         assert!(manager.should_fire("overheating", 0.85));
@@ -196,6 +506,207 @@ mod tests {
         assert!(!manager.should_fire("overheating", 0.85));
     }
 
+    #[test]
+    fn test_cooldown_expires_with_mock_clock() {
+        let clock = MockClock::default();
+        let config = AlertConfig {
+            cooldown_seconds: 60,
+            ..Default::default()
+        };
+        let mut manager = AlertManager::new(config).unwrap().with_clock(clock.shared());
+
+        assert!(manager.should_fire("overheating", 0.85));
+        manager.record_fire("overheating");
+
+        clock.advance(Duration::from_secs(59));
+        assert!(!manager.should_fire("overheating", 0.85));
+
+        clock.advance(Duration::from_secs(1));
+        assert!(manager.should_fire("overheating", 0.85));
+    }
+
+    #[test]
+    fn test_evaluate_prediction() {
+        let mut manager = AlertManager::default();
+        let prediction = |confidence| Prediction {
+            fault_type: FaultType::Overheating,
+            confidence,
+            probabilities: [1.0 - confidence, confidence, 0.0, 0.0],
+            timestamp_ms: 1_700_000_000_000,
+        };
+
+        assert!(manager.evaluate_prediction(&prediction(0.4)).is_none());
+
+        let alert = manager.evaluate_prediction(&prediction(0.93)).unwrap();
+        assert_eq!(alert.severity, Severity::Critical);
+        assert_eq!(
+            alert.recommended_action,
+            "Check coolant level, reduce engine load, allow engine to cool"
+        );
+        assert_eq!(manager.hourly_count(), 1);
+
+        // Recorded under the fault key, so the repeat is in cooldown
+        assert!(manager.evaluate_prediction(&prediction(0.93)).is_none());
+        assert!(!manager.should_fire("engine_overheating", 0.93));
+    }
+
+    #[test]
+    fn test_maintenance_mode_suppresses_all_but_safety_faults() {
+        let clock = MockClock::default();
+        let mut manager = AlertManager::default().with_clock(clock.shared());
+        let overheating = Prediction {
+            fault_type: FaultType::Overheating,
+            confidence: 0.93,
+            probabilities: [0.07, 0.93, 0.0, 0.0],
+            timestamp_ms: 0,
+        };
+
+        manager.set_maintenance_mode(true);
+        assert!(manager.evaluate_prediction(&overheating).is_none());
+        assert!(manager.should_fire("crash", 0.95));
+
+        // Held back, not lost, and not counted against cooldown or throttle
+        let suppressed = manager.take_suppressed();
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].fault_type, "engine_overheating");
+        assert_eq!(manager.hourly_count(), 0);
+
+        manager.set_maintenance_mode(false);
+        assert!(manager.evaluate_prediction(&overheating).is_some());
+
+        // A scheduled window behaves the same, and only while it is open
+        let start = clock.shared().now() + Duration::from_secs(60);
+        manager.add_suppression_window(SuppressionWindow::Once {
+            start,
+            end: start + Duration::from_secs(3600),
+        });
+        assert!(!manager.is_suppressed("engine_misfire"));
+        clock.advance(Duration::from_secs(60));
+        assert!(manager.is_suppressed("engine_misfire"));
+        assert!(!manager.is_suppressed("rollover"));
+        clock.advance(Duration::from_secs(3600));
+        assert!(!manager.is_suppressed("engine_misfire"));
+    }
+
+    #[test]
+    fn test_suppressed_alerts_are_capped() {
+        let mut manager = AlertManager::default();
+        manager.set_maintenance_mode(true);
+        for i in 0..MAX_SUPPRESSED_ALERTS + 10 {
+            assert!(!manager.should_fire(&format!("fault_{i}"), 0.9));
+        }
+
+        // Only the newest are kept
+        let suppressed = manager.take_suppressed();
+        assert_eq!(suppressed.len(), MAX_SUPPRESSED_ALERTS);
+        assert_eq!(suppressed[0].fault_type, "fault_10");
+        assert!(manager.take_suppressed().is_empty());
+    }
+
+    #[test]
+    fn test_daily_window_recurs_and_wraps_midnight() {
+        // 22:00-06:00 quiet hours at UTC+2
+        let window = SuppressionWindow::Daily {
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            utc_offset: FixedOffset::east_opt(2 * 3600).unwrap(),
+        };
+        let at = |utc: &str| SystemTime::from(utc.parse::<DateTime<Utc>>().unwrap());
+
+        assert!(window.contains(at("2024-03-01T20:00:00Z"))); // 22:00 local
+        assert!(window.contains(at("2024-03-02T01:30:00Z"))); // 03:30 local
+        assert!(!window.contains(at("2024-03-02T04:00:00Z"))); // 06:00 local
+        assert!(!window.contains(at("2024-03-02T12:00:00Z")));
+        // Same hours the following week
+        assert!(window.contains(at("2024-03-09T23:00:00Z")));
+    }
+
+    #[tokio::test]
+    async fn test_suppressed_alerts_are_stored() {
+        let repo = Arc::new(storage::Repository::new());
+        let mut manager = AlertManager::default().with_storage(repo.clone());
+        manager.set_maintenance_mode(true);
+
+        assert!(!manager.should_fire("engine_overheating", 0.93));
+        assert!(manager.should_fire("crash", 0.95));
+        assert!(manager.take_suppressed().is_empty());
+
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            events = repo.get_events(10).await.unwrap();
+            if !events.is_empty() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, SUPPRESSED_ALERT_EVENT);
+        assert_eq!(events[0].severity.as_deref(), Some("critical"));
+        assert!(events[0].payload.contains("engine_overheating"));
+    }
+
+    #[test]
+    fn test_smoothing_ignores_spike_but_fires_on_sustained() {
+        let mut manager = AlertManager::default();
+
+        for _ in 0..10 {
+            assert!(!manager.should_fire_smoothed("misfire", 0.1));
+        }
+        // One-off spike amid low confidence
+        assert!(!manager.should_fire_smoothed("misfire", 0.98));
+        for _ in 0..3 {
+            assert!(!manager.should_fire_smoothed("misfire", 0.1));
+        }
+
+        // Sustained moderate confidence eventually fires
+        let fired = (0..20).any(|_| manager.should_fire_smoothed("misfire", 0.8));
+        assert!(fired);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(AlertConfig::default().validate().is_ok());
+
+        let err = AlertManager::new(AlertConfig {
+            confidence_threshold: 1.2,
+            ..Default::default()
+        })
+        .err()
+        .unwrap();
+        assert_eq!(
+            err,
+            ConfigError::OutOfRange {
+                field: "confidence_threshold",
+                value: 1.2,
+                expected: "within 0.0..=1.0",
+            }
+        );
+
+        let err = AlertConfig {
+            confidence_threshold: 0.8,
+            critical_threshold: 0.6,
+            ..Default::default()
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err, ConfigError::ThresholdOrder { confidence: 0.8, critical: 0.6 });
+
+        let err = AlertConfig {
+            smoothing_alpha: 0.0,
+            ..Default::default()
+        }
+        .validate()
+        .unwrap_err();
+        assert!(err.to_string().starts_with("smoothing_alpha must be within (0.0, 1.0]"));
+
+        assert!(AlertConfig {
+            max_alerts_per_hour: 0,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
     #[test]
     fn test_severity_levels() {
         let manager = AlertManager::default();
@@ -204,6 +715,7 @@ mod tests {
         assert_eq!(manager.get_severity(0.87), "high");
         assert_eq!(manager.get_severity(0.78), "medium");
         assert_eq!(manager.get_severity(0.5), "low");
+        assert_eq!(manager.severity_for(0.95), Severity::Critical);
     }
 
     #[test]
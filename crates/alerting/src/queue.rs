@@ -0,0 +1,360 @@
+//! Prioritized Alert Queue
+//!
+//! DMS, ADAS, inference and fusion raise alerts independently and often
+//! for the same moment. The queue sits between them and the cloud
+//! uploader: repeats of an alert within the dedup window collapse into one
+//! entry, the uploader always receives the most severe alert first, and
+//! producers wait when the uploader falls behind. Critical alerts never
+//! wait; if the queue is full they evict the least severe entry.
+
+use common_types::{Severity, SharedClock, SystemClock};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+/// Subsystem that raised an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum AlertSource {
+    Dms,
+    Adas,
+    Inference,
+    Fusion,
+}
+
+/// Alert waiting for upload
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedAlert<T> {
+    /// Raising subsystem
+    pub source: AlertSource,
+    /// Identifies repeats of the same alert, e.g. `"drowsiness"`
+    pub key: String,
+    /// Highest severity seen for this alert while queued
+    pub severity: Severity,
+    /// Alert body
+    pub payload: T,
+    /// Times the alert was raised before it was delivered
+    pub occurrences: u32,
+}
+
+impl<T> QueuedAlert<T> {
+    /// A single occurrence of an alert
+    pub fn new(source: AlertSource, key: impl Into<String>, severity: Severity, payload: T) -> Self {
+        Self {
+            source,
+            key: key.into(),
+            severity,
+            payload,
+            occurrences: 1,
+        }
+    }
+}
+
+/// What [`AlertQueue::push`] did with an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// Added as a new entry
+    Queued,
+    /// Folded into a queued entry for the same alert
+    Merged,
+    /// Dropped: the same alert was delivered within the dedup window
+    Suppressed,
+    /// Queued after evicting a less severe entry (critical alerts only)
+    Preempted,
+}
+
+/// Alert queue configuration
+#[derive(Debug, Clone)]
+pub struct AlertQueueConfig {
+    /// Entries held before non-critical producers wait
+    pub capacity: usize,
+    /// Repeats of an alert within this window are collapsed
+    pub dedup_window: Duration,
+}
+
+impl Default for AlertQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            dedup_window: Duration::from_secs(10),
+        }
+    }
+}
+
+struct Entry<T> {
+    alert: QueuedAlert<T>,
+    seq: u64,
+}
+
+struct QueueState<T> {
+    entries: Vec<Entry<T>>,
+    /// When each alert was last delivered and at what severity, for
+    /// suppressing repeats
+    delivered: HashMap<(AlertSource, String), (Instant, Severity)>,
+    next_seq: u64,
+}
+
+/// Deduplicating, severity-ordered queue feeding the cloud uploader
+///
+/// Share it behind an `Arc`; producers call [`push`](Self::push) and the
+/// uploader awaits [`next`](Self::next).
+pub struct AlertQueue<T> {
+    state: Mutex<QueueState<T>>,
+    config: AlertQueueConfig,
+    clock: SharedClock,
+    readable: Notify,
+    writable: Notify,
+}
+
+impl<T> AlertQueue<T> {
+    /// Create an empty queue
+    pub fn new(config: AlertQueueConfig) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                entries: Vec::new(),
+                delivered: HashMap::new(),
+                next_seq: 0,
+            }),
+            config: AlertQueueConfig {
+                capacity: config.capacity.max(1),
+                ..config
+            },
+            clock: SystemClock::shared(),
+            readable: Notify::new(),
+            writable: Notify::new(),
+        }
+    }
+
+    /// Use `clock` for the dedup window
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, QueueState<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue an alert, waiting for space unless it is critical
+    pub async fn push(&self, alert: QueuedAlert<T>) -> PushOutcome {
+        let mut alert = alert;
+        loop {
+            let writable = self.writable.notified();
+            match self.try_push(alert) {
+                Ok(outcome) => return outcome,
+                Err(rejected) => alert = rejected,
+            }
+            writable.await;
+        }
+    }
+
+    /// Queue an alert without waiting; a full queue hands the alert back
+    pub fn try_push(&self, alert: QueuedAlert<T>) -> Result<PushOutcome, QueuedAlert<T>> {
+        let now = self.clock.instant();
+        let mut state = self.state();
+
+        let id = (alert.source, alert.key.clone());
+        if let Some(entry) = state
+            .entries
+            .iter_mut()
+            .find(|e| e.alert.source == id.0 && e.alert.key == id.1)
+        {
+            entry.alert.severity = entry.alert.severity.max(alert.severity);
+            entry.alert.occurrences += alert.occurrences;
+            entry.alert.payload = alert.payload;
+            return Ok(PushOutcome::Merged);
+        }
+
+        // An escalated repeat is news, not a duplicate
+        if let Some((at, severity)) = state.delivered.get(&id) {
+            if now.duration_since(*at) < self.config.dedup_window && alert.severity <= *severity {
+                debug!("Suppressing repeat {:?} alert {}", alert.source, alert.key);
+                return Ok(PushOutcome::Suppressed);
+            }
+        }
+
+        let mut outcome = PushOutcome::Queued;
+        if state.entries.len() >= self.config.capacity {
+            if alert.severity != Severity::Critical {
+                return Err(alert);
+            }
+            // Critical alerts make room by dropping the least severe, newest entry
+            let victim = state
+                .entries
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, e)| (e.alert.severity, std::cmp::Reverse(e.seq)))
+                .map(|(i, _)| i)
+                .expect("full queue is not empty");
+            let evicted = state.entries.swap_remove(victim);
+            warn!(
+                "Alert queue full, evicting {:?} alert {} for critical {}",
+                evicted.alert.source, evicted.alert.key, alert.key
+            );
+            outcome = PushOutcome::Preempted;
+        }
+
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.entries.push(Entry { alert, seq });
+        drop(state);
+
+        self.readable.notify_one();
+        Ok(outcome)
+    }
+
+    /// Take the most severe alert, oldest first within a severity, if any
+    pub fn try_next(&self) -> Option<QueuedAlert<T>> {
+        let now = self.clock.instant();
+        let mut state = self.state();
+
+        let index = state
+            .entries
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, e)| (e.alert.severity, std::cmp::Reverse(e.seq)))
+            .map(|(i, _)| i)?;
+        let entry = state.entries.swap_remove(index);
+
+        let window = self.config.dedup_window;
+        state.delivered.retain(|_, (at, _)| now.duration_since(*at) < window);
+        state
+            .delivered
+            .insert((entry.alert.source, entry.alert.key.clone()), (now, entry.alert.severity));
+        drop(state);
+
+        self.writable.notify_one();
+        Some(entry.alert)
+    }
+
+    /// Wait for the next alert to upload
+    pub async fn next(&self) -> QueuedAlert<T> {
+        loop {
+            let readable = self.readable.notified();
+            if let Some(alert) = self.try_next() {
+                return alert;
+            }
+            readable.await;
+        }
+    }
+
+    /// Number of queued alerts
+    pub fn len(&self) -> usize {
+        self.state().entries.len()
+    }
+
+    /// Whether no alerts are queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for AlertQueue<T> {
+    fn default() -> Self {
+        Self::new(AlertQueueConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_types::MockClock;
+    use std::sync::Arc;
+
+    fn alert(source: AlertSource, key: &str, severity: Severity) -> QueuedAlert<&'static str> {
+        QueuedAlert::new(source, key, severity, "")
+    }
+
+    #[tokio::test]
+    async fn test_highest_severity_first_with_duplicates_collapsed() {
+        let queue = AlertQueue::default();
+
+        queue.push(alert(AlertSource::Inference, "overheating", Severity::Medium)).await;
+        queue.push(alert(AlertSource::Dms, "drowsiness", Severity::High)).await;
+        queue.push(alert(AlertSource::Adas, "lane_departure", Severity::Low)).await;
+        let merged = queue.push(alert(AlertSource::Dms, "drowsiness", Severity::High)).await;
+        assert_eq!(merged, PushOutcome::Merged);
+        queue.push(alert(AlertSource::Fusion, "crash", Severity::Critical)).await;
+
+        let mut delivered = Vec::new();
+        while let Some(a) = queue.try_next() {
+            delivered.push((a.key, a.severity, a.occurrences));
+        }
+        assert_eq!(
+            delivered,
+            vec![
+                ("crash".to_string(), Severity::Critical, 1),
+                ("drowsiness".to_string(), Severity::High, 2),
+                ("overheating".to_string(), Severity::Medium, 1),
+                ("lane_departure".to_string(), Severity::Low, 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repeat_suppressed_within_window() {
+        let clock = MockClock::default();
+        let queue = AlertQueue::default().with_clock(clock.shared());
+
+        queue.push(alert(AlertSource::Dms, "distraction", Severity::Medium)).await;
+        assert_eq!(queue.next().await.key, "distraction");
+
+        clock.advance(Duration::from_secs(5));
+        let outcome = queue.push(alert(AlertSource::Dms, "distraction", Severity::Medium)).await;
+        assert_eq!(outcome, PushOutcome::Suppressed);
+        assert!(queue.is_empty());
+
+        clock.advance(Duration::from_secs(5));
+        let outcome = queue.push(alert(AlertSource::Dms, "distraction", Severity::Medium)).await;
+        assert_eq!(outcome, PushOutcome::Queued);
+    }
+
+    #[tokio::test]
+    async fn test_escalated_repeat_bypasses_dedup() {
+        let clock = MockClock::default();
+        let queue = AlertQueue::default().with_clock(clock.shared());
+
+        queue.push(alert(AlertSource::Dms, "drowsiness", Severity::Medium)).await;
+        queue.next().await;
+
+        clock.advance(Duration::from_secs(2));
+        let outcome = queue.push(alert(AlertSource::Dms, "drowsiness", Severity::Critical)).await;
+        assert_eq!(outcome, PushOutcome::Queued);
+        assert_eq!(queue.next().await.severity, Severity::Critical);
+
+        // Once delivered, the critical alert is the level to beat
+        clock.advance(Duration::from_secs(2));
+        let outcome = queue.push(alert(AlertSource::Dms, "drowsiness", Severity::High)).await;
+        assert_eq!(outcome, PushOutcome::Suppressed);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_backpressure_and_preemption() {
+        let queue = Arc::new(AlertQueue::new(AlertQueueConfig {
+            capacity: 2,
+            ..Default::default()
+        }));
+        queue.push(alert(AlertSource::Adas, "a", Severity::Low)).await;
+        queue.push(alert(AlertSource::Adas, "b", Severity::Medium)).await;
+
+        // Non-critical producer waits for the uploader
+        let producer = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move { queue.push(alert(AlertSource::Adas, "c", Severity::High)).await })
+        };
+        tokio::task::yield_now().await;
+        assert!(!producer.is_finished());
+
+        // Critical evicts the least severe entry instead of waiting
+        let outcome = queue.push(alert(AlertSource::Fusion, "crash", Severity::Critical)).await;
+        assert_eq!(outcome, PushOutcome::Preempted);
+        assert_eq!(queue.next().await.key, "crash");
+
+        assert_eq!(producer.await.unwrap(), PushOutcome::Queued);
+        assert_eq!(queue.next().await.key, "c");
+        assert_eq!(queue.next().await.key, "b");
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+//! Alert Routing
+//!
+//! Detection decides *that* something happened; the routing table decides
+//! what the vehicle does about it. Fleets differ (one wants a chime for
+//! every lane departure, another only an upload), so the mapping from
+//! alert key and severity to actions is configuration, and each action is
+//! carried out by a handler registered for it.
+
+use common_types::Severity;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::manager::ConfigError;
+use crate::queue::AlertSource;
+
+/// Route key that matches every alert
+pub const ANY_ALERT: &str = "*";
+
+/// Response to an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlertAction {
+    /// Audible chime in the cabin
+    Buzzer,
+    /// Send to the fleet backend
+    Upload,
+    /// Flash on the driver display
+    Display,
+    /// Record locally, nothing else
+    LogOnly,
+    /// Inhibit ignition restart until cleared
+    Lockout,
+}
+
+/// Actions for alerts of one key at or above a severity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRoute {
+    /// Alert key such as `"drowsiness"`, or [`ANY_ALERT`]
+    pub key: String,
+    /// Lowest severity the route applies to; any severity when unset
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+    pub actions: Vec<AlertAction>,
+}
+
+impl AlertRoute {
+    /// Route `key` at any severity
+    pub fn new(key: impl Into<String>, actions: impl Into<Vec<AlertAction>>) -> Self {
+        Self {
+            key: key.into(),
+            min_severity: None,
+            actions: actions.into(),
+        }
+    }
+
+    /// Only apply at `severity` and above
+    pub fn with_min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    fn matches(&self, key: &str, severity: Severity) -> bool {
+        (self.key == ANY_ALERT || self.key == key)
+            && self.min_severity.is_none_or(|min| severity >= min)
+    }
+}
+
+/// Ordered routes; the first match decides the actions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingTable {
+    #[serde(default)]
+    pub routes: Vec<AlertRoute>,
+    /// Actions for alerts no route matches
+    #[serde(default = "default_actions")]
+    pub default_actions: Vec<AlertAction>,
+}
+
+fn default_actions() -> Vec<AlertAction> {
+    vec![AlertAction::LogOnly]
+}
+
+impl Default for RoutingTable {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            default_actions: default_actions(),
+        }
+    }
+}
+
+impl RoutingTable {
+    /// Append a route, after the existing ones
+    pub fn with_route(mut self, route: AlertRoute) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// Check that every route does something and `LogOnly` stands alone
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for route in &self.routes {
+            let invalid = |reason| ConfigError::InvalidRoute {
+                key: route.key.clone(),
+                reason,
+            };
+            if route.actions.is_empty() {
+                return Err(invalid("has no actions"));
+            }
+            if route.actions.contains(&AlertAction::LogOnly) && route.actions.len() > 1 {
+                return Err(invalid("combines LogOnly with other actions"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Actions for an alert
+    pub fn actions_for(&self, key: &str, severity: Severity) -> &[AlertAction] {
+        self.routes
+            .iter()
+            .find(|route| route.matches(key, severity))
+            .map_or(&self.default_actions[..], |route| &route.actions[..])
+    }
+}
+
+/// Alert handed to action handlers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoutedAlert {
+    pub source: AlertSource,
+    /// Alert key, e.g. `"drowsiness"`
+    pub key: String,
+    pub severity: Severity,
+    /// Time the alert was raised (Unix ms)
+    pub timestamp_ms: u64,
+}
+
+/// An action handler could not carry out its action
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{0}")]
+pub struct ActionError(pub String);
+
+/// Carries out one kind of [`AlertAction`], e.g. driving the buzzer GPIO
+pub trait ActionHandler: Send + Sync {
+    fn handle(&self, alert: &RoutedAlert) -> Result<(), ActionError>;
+}
+
+/// What [`AlertRouter::dispatch`] did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DispatchReport {
+    /// Actions a handler completed
+    pub performed: Vec<AlertAction>,
+    /// Routed actions with no registered handler
+    pub unhandled: Vec<AlertAction>,
+    /// Actions whose handler failed
+    pub failed: Vec<(AlertAction, ActionError)>,
+}
+
+/// Dispatches alerts to action handlers according to a routing table
+pub struct AlertRouter {
+    table: RoutingTable,
+    handlers: HashMap<AlertAction, Arc<dyn ActionHandler>>,
+}
+
+impl AlertRouter {
+    /// Create a router with no handlers registered
+    pub fn new(table: RoutingTable) -> Result<Self, ConfigError> {
+        table.validate()?;
+        Ok(Self {
+            table,
+            handlers: HashMap::new(),
+        })
+    }
+
+    /// Register the handler for `action`, replacing any previous one
+    pub fn with_handler(mut self, action: AlertAction, handler: Arc<dyn ActionHandler>) -> Self {
+        self.handlers.insert(action, handler);
+        self
+    }
+
+    /// Active routing table
+    pub fn table(&self) -> &RoutingTable {
+        &self.table
+    }
+
+    /// Run every action routed for `alert`
+    ///
+    /// A failing handler does not stop the remaining actions.
+    pub fn dispatch(&self, alert: &RoutedAlert) -> DispatchReport {
+        let mut report = DispatchReport::default();
+        for &action in self.table.actions_for(&alert.key, alert.severity) {
+            if action == AlertAction::LogOnly {
+                info!("{:?} alert {} ({})", alert.source, alert.key, alert.severity);
+            }
+            let Some(handler) = self.handlers.get(&action) else {
+                if action != AlertAction::LogOnly {
+                    debug!("No handler for {:?}, alert {}", action, alert.key);
+                    report.unhandled.push(action);
+                }
+                continue;
+            };
+            match handler.handle(alert) {
+                Ok(()) => report.performed.push(action),
+                Err(e) => {
+                    warn!("{:?} failed for alert {}: {}", action, alert.key, e);
+                    report.failed.push((action, e));
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records the keys of the alerts it handles
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl ActionHandler for Recorder {
+        fn handle(&self, alert: &RoutedAlert) -> Result<(), ActionError> {
+            self.0.lock().unwrap().push(alert.key.clone());
+            Ok(())
+        }
+    }
+
+    fn alert(key: &str, severity: Severity) -> RoutedAlert {
+        RoutedAlert {
+            source: AlertSource::Dms,
+            key: key.to_string(),
+            severity,
+            timestamp_ms: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_drowsiness_routed_to_buzzer_and_upload() {
+        let table = RoutingTable::default()
+            .with_route(AlertRoute::new(
+                "drowsiness",
+                [AlertAction::Buzzer, AlertAction::Upload],
+            ))
+            .with_route(
+                AlertRoute::new(ANY_ALERT, [AlertAction::Display]).with_min_severity(Severity::High),
+            );
+        let buzzer = Arc::new(Recorder::default());
+        let upload = Arc::new(Recorder::default());
+        let display = Arc::new(Recorder::default());
+        let router = AlertRouter::new(table)
+            .unwrap()
+            .with_handler(AlertAction::Buzzer, buzzer.clone())
+            .with_handler(AlertAction::Upload, upload.clone())
+            .with_handler(AlertAction::Display, display.clone());
+
+        let report = router.dispatch(&alert("drowsiness", Severity::Medium));
+        assert_eq!(report.performed, [AlertAction::Buzzer, AlertAction::Upload]);
+        assert_eq!(*buzzer.0.lock().unwrap(), ["drowsiness"]);
+        assert_eq!(*upload.0.lock().unwrap(), ["drowsiness"]);
+        assert!(display.0.lock().unwrap().is_empty());
+
+        // Other alerts fall through to the wildcard, then to the default
+        let report = router.dispatch(&alert("distraction", Severity::High));
+        assert_eq!(report.performed, [AlertAction::Display]);
+        let report = router.dispatch(&alert("distraction", Severity::Low));
+        assert!(report.performed.is_empty() && report.unhandled.is_empty());
+        assert_eq!(buzzer.0.lock().unwrap().len(), 1);
+
+        let err = RoutingTable::default()
+            .with_route(AlertRoute::new("yawn", [AlertAction::LogOnly, AlertAction::Upload]))
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "route for \"yawn\" combines LogOnly with other actions"
+        );
+    }
+}
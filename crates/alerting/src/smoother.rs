@@ -0,0 +1,80 @@
+//! Fault Confidence Smoothing
+//!
+//! Integrates per-fault inference confidence over time with an EWMA so that
+//! a one-off spike does not trip an alert but a sustained fault does.
+
+use data_validator::{NormalizationMethod, Normalizer};
+use std::collections::HashMap;
+
+/// Per-fault EWMA confidence smoother
+pub struct ConfidenceSmoother {
+    /// EWMA smoothing factor (0-1, higher = more weight on recent)
+    alpha: f64,
+    /// One smoother per fault type
+    smoothers: HashMap<String, Normalizer>,
+}
+
+impl ConfidenceSmoother {
+    /// Create a new smoother
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            smoothers: HashMap::new(),
+        }
+    }
+
+    /// Feed a raw confidence and return the smoothed confidence
+    pub fn update(&mut self, fault_type: &str, confidence: f64) -> f64 {
+        let alpha = self.alpha;
+        let smoother = self
+            .smoothers
+            .entry(fault_type.to_string())
+            .or_insert_with(|| {
+                // Start from "no evidence" so the first sample can't fire on its own
+                let mut n = Normalizer::new(NormalizationMethod::None, alpha);
+                n.normalize(0.0);
+                n
+            });
+        smoother.normalize(confidence.clamp(0.0, 1.0));
+        smoother.mean()
+    }
+
+    /// Get the current smoothed confidence for a fault
+    pub fn smoothed(&self, fault_type: &str) -> Option<f64> {
+        self.smoothers.get(fault_type).map(|n| n.mean())
+    }
+
+    /// Forget the history of one fault
+    pub fn reset(&mut self, fault_type: &str) {
+        self.smoothers.remove(fault_type);
+    }
+
+    /// Forget all history
+    pub fn clear(&mut self) {
+        self.smoothers.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spike_is_damped() {
+        let mut smoother = ConfidenceSmoother::new(0.3);
+        for _ in 0..10 {
+            smoother.update("misfire", 0.1);
+        }
+        let smoothed = smoother.update("misfire", 0.95);
+        assert!(smoothed < 0.5);
+    }
+
+    #[test]
+    fn test_faults_are_independent() {
+        let mut smoother = ConfidenceSmoother::new(0.5);
+        smoother.update("misfire", 0.9);
+        assert!(smoother.smoothed("overheating").is_none());
+        smoother.reset("misfire");
+        assert!(smoother.smoothed("misfire").is_none());
+    }
+}
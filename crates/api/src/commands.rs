@@ -0,0 +1,281 @@
+//! Remote Commands
+//!
+//! Inbound half of the `/api/v1/ws` socket: the backend sends JSON commands
+//! (snapshot request, model reload, manual upload, ...) and receives a
+//! [`CommandResult`] for each. The API layer only parses, authenticates and
+//! forwards; the pipeline task holding the other end of the [`CommandBus`]
+//! dispatches each [`CommandRequest`] to the subsystem that owns it.
+//!
+//! A connection must authenticate with the configured token before any
+//! command is accepted:
+//!
+//! ```text
+//! -> {"type":"auth","token":"..."}
+//! <- {"type":"auth_ok"}
+//! -> {"type":"command","id":1,"command":"request_snapshot"}
+//! <- {"type":"result","id":1,"command":"request_snapshot","ok":true,"data":{...}}
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+
+use ring_buffer::SensorFrame;
+
+/// Default number of commands queued for the pipeline
+pub const DEFAULT_COMMAND_CAPACITY: usize = 16;
+
+/// Command sent by the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    /// Snapshot the sensor ring buffer
+    RequestSnapshot,
+    /// Reload inference models from disk
+    ReloadModel,
+    /// Start a manual upload regardless of schedule
+    TriggerUpload,
+    /// Deliver queued cloud messages now
+    FlushOutbox,
+    /// Report the running configuration
+    GetConfig,
+}
+
+/// Outcome of a command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandResult {
+    /// Whether the command succeeded
+    pub ok: bool,
+    /// Command-specific payload
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    /// Failure reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CommandResult {
+    /// Successful result without a payload
+    pub fn done() -> Self {
+        Self { ok: true, data: None, error: None }
+    }
+
+    /// Successful result carrying `data`
+    pub fn with_data(data: Value) -> Self {
+        Self { ok: true, data: Some(data), error: None }
+    }
+
+    /// Failed result
+    pub fn failed(error: impl Into<String>) -> Self {
+        Self { ok: false, data: None, error: Some(error.into()) }
+    }
+}
+
+/// A command awaiting a result from the pipeline
+#[derive(Debug)]
+pub struct CommandRequest {
+    /// Command to carry out
+    pub command: Command,
+    reply: oneshot::Sender<CommandResult>,
+}
+
+impl CommandRequest {
+    /// Send the result back to the socket that issued the command
+    pub fn respond(self, result: CommandResult) {
+        // The socket may have closed meanwhile; nothing left to tell
+        let _ = self.reply.send(result);
+    }
+}
+
+/// Sending side of the command queue, held by [`AppState`](crate::AppState)
+#[derive(Debug, Clone)]
+pub struct CommandBus {
+    sender: mpsc::Sender<CommandRequest>,
+}
+
+impl CommandBus {
+    /// Create a bus queueing up to `capacity` commands, and the receiver the
+    /// pipeline drains
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<CommandRequest>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        (Self { sender }, receiver)
+    }
+
+    /// Forward a command and wait for its result
+    pub async fn dispatch(&self, command: Command) -> CommandResult {
+        let (reply, result) = oneshot::channel();
+        if self.sender.send(CommandRequest { command, reply }).await.is_err() {
+            return CommandResult::failed("command handler not running");
+        }
+        result
+            .await
+            .unwrap_or_else(|_| CommandResult::failed("command dropped without a result"))
+    }
+}
+
+/// Message received on the socket
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Authenticate the connection
+    Auth { token: String },
+    /// Run a command
+    Command {
+        /// Caller-chosen id echoed in the result
+        #[serde(default)]
+        id: Option<u64>,
+        command: Command,
+    },
+}
+
+/// Message sent on the socket
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Token accepted
+    AuthOk,
+    /// Token rejected; the connection is closed
+    AuthFailed,
+    /// Result of a command
+    Result {
+        id: Option<u64>,
+        command: Command,
+        #[serde(flatten)]
+        result: CommandResult,
+    },
+    /// Live sensor frame
+    Telemetry { frame: SensorFrame },
+    /// Message could not be handled
+    Error { message: String },
+}
+
+/// Per-connection command state
+#[derive(Debug)]
+pub struct CommandSession {
+    token: Option<String>,
+    bus: Option<CommandBus>,
+    authenticated: bool,
+}
+
+impl CommandSession {
+    /// Session accepting `token`; with no token configured, every
+    /// authentication attempt is refused
+    pub fn new(token: Option<String>, bus: Option<CommandBus>) -> Self {
+        Self { token, bus, authenticated: false }
+    }
+
+    /// Whether the connection has authenticated
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    /// Handle one text frame and produce the reply
+    pub async fn handle_text(&mut self, text: &str) -> ServerMessage {
+        let message = match serde_json::from_str::<ClientMessage>(text) {
+            Ok(message) => message,
+            Err(e) => return ServerMessage::Error { message: format!("invalid message: {}", e) },
+        };
+
+        match message {
+            ClientMessage::Auth { token } => {
+                self.authenticated = self
+                    .token
+                    .as_deref()
+                    .is_some_and(|expected| constant_time_eq(expected.as_bytes(), token.as_bytes()));
+                if self.authenticated {
+                    info!("Command socket authenticated");
+                    ServerMessage::AuthOk
+                } else {
+                    warn!("Command socket authentication failed");
+                    ServerMessage::AuthFailed
+                }
+            }
+            ClientMessage::Command { .. } if !self.authenticated => ServerMessage::Error {
+                message: "not authenticated".to_string(),
+            },
+            ClientMessage::Command { id, command } => {
+                info!("Remote command {:?}", command);
+                let result = match &self.bus {
+                    Some(bus) => bus.dispatch(command).await,
+                    None => CommandResult::failed("remote commands not enabled"),
+                };
+                ServerMessage::Result { id, command, result }
+            }
+        }
+    }
+}
+
+/// Compare secrets without an early exit on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    /// Stand-in for the pipeline task: records commands and answers them
+    fn spawn_pipeline(mut requests: mpsc::Receiver<CommandRequest>) -> Arc<Mutex<Vec<Command>>> {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        tokio::spawn(async move {
+            while let Some(request) = requests.recv().await {
+                log.lock().unwrap().push(request.command);
+                let result = match request.command {
+                    Command::RequestSnapshot => CommandResult::with_data(json!({ "frames": 42 })),
+                    _ => CommandResult::done(),
+                };
+                request.respond(result);
+            }
+        });
+        seen
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_command_reaches_pipeline() {
+        let (bus, requests) = CommandBus::new(DEFAULT_COMMAND_CAPACITY);
+        let seen = spawn_pipeline(requests);
+        let mut session = CommandSession::new(Some("s3cret".to_string()), Some(bus));
+
+        let reply = session.handle_text(r#"{"type":"auth","token":"s3cret"}"#).await;
+        assert!(matches!(reply, ServerMessage::AuthOk));
+
+        let reply = session
+            .handle_text(r#"{"type":"command","id":7,"command":"request_snapshot"}"#)
+            .await;
+        assert_eq!(
+            serde_json::to_value(&reply).unwrap(),
+            json!({
+                "type": "result",
+                "id": 7,
+                "command": "request_snapshot",
+                "ok": true,
+                "data": { "frames": 42 }
+            })
+        );
+        assert_eq!(*seen.lock().unwrap(), vec![Command::RequestSnapshot]);
+    }
+
+    #[tokio::test]
+    async fn test_commands_rejected_before_auth() {
+        let (bus, requests) = CommandBus::new(DEFAULT_COMMAND_CAPACITY);
+        let seen = spawn_pipeline(requests);
+        let mut session = CommandSession::new(Some("s3cret".to_string()), Some(bus));
+
+        let reply = session.handle_text(r#"{"type":"command","command":"flush_outbox"}"#).await;
+        assert!(matches!(reply, ServerMessage::Error { .. }));
+
+        let reply = session.handle_text(r#"{"type":"auth","token":"guess"}"#).await;
+        assert!(matches!(reply, ServerMessage::AuthFailed));
+        assert!(!session.is_authenticated());
+        assert!(seen.lock().unwrap().is_empty());
+
+        // No token configured: nothing authenticates
+        let mut open = CommandSession::new(None, None);
+        let reply = open.handle_text(r#"{"type":"auth","token":""}"#).await;
+        assert!(matches!(reply, ServerMessage::AuthFailed));
+    }
+}
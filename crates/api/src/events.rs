@@ -0,0 +1,223 @@
+//! Event Hub
+//!
+//! Bounded broadcast channels for fanning pipeline events out to API
+//! consumers (WebSocket telemetry, SSE alerts, DMS live view).
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use adas::AdasAnalysis;
+use dms::DmsAnalysis;
+use event_fusion::FusedEvent;
+use ring_buffer::SensorFrame;
+
+/// Default capacity of each hub channel
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 256;
+
+/// A bounded broadcast channel that counts messages dropped by slow receivers
+#[derive(Debug)]
+pub struct EventChannel<T: Clone> {
+    sender: broadcast::Sender<T>,
+    lagged: Arc<AtomicU64>,
+}
+
+impl<T: Clone> Clone for EventChannel<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            lagged: Arc::clone(&self.lagged),
+        }
+    }
+}
+
+impl<T: Clone> EventChannel<T> {
+    /// Create a channel holding at most `capacity` unread messages
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            lagged: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Publish a message, returning the number of subscribers it reached
+    pub fn publish(&self, message: T) -> usize {
+        // No subscribers is not an error for the producer
+        self.sender.send(message).unwrap_or(0)
+    }
+
+    /// Subscribe to future messages
+    pub fn subscribe(&self) -> EventSubscriber<T> {
+        EventSubscriber {
+            receiver: self.sender.subscribe(),
+            lagged: Arc::clone(&self.lagged),
+        }
+    }
+
+    /// Number of active subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Total messages skipped by lagging subscribers
+    pub fn lagged_count(&self) -> u64 {
+        self.lagged.load(Ordering::Relaxed)
+    }
+}
+
+/// Receiving half of an [`EventChannel`]
+#[derive(Debug)]
+pub struct EventSubscriber<T: Clone> {
+    receiver: broadcast::Receiver<T>,
+    lagged: Arc<AtomicU64>,
+}
+
+impl<T: Clone> EventSubscriber<T> {
+    /// Receive the next message
+    ///
+    /// If this subscriber fell behind, the skipped messages are added to the
+    /// channel's lag count and the oldest retained message is returned.
+    /// Returns `None` once every sender has been dropped.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(message) => return Some(message),
+                Err(RecvError::Lagged(skipped)) => {
+                    self.lagged.fetch_add(skipped, Ordering::Relaxed);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Lag statistics for each hub channel
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EventHubStats {
+    pub sensor_frames_lagged: u64,
+    pub fused_events_lagged: u64,
+    pub dms_lagged: u64,
+    pub adas_lagged: u64,
+}
+
+/// Typed broadcast channels shared by ingestion tasks and API handlers
+///
+/// Cloning the hub is cheap and shares the underlying channels, so
+/// ingestion tasks can hold their own copy without locking `AppState`.
+#[derive(Debug, Clone)]
+pub struct EventHub {
+    sensor_frames: EventChannel<SensorFrame>,
+    fused_events: EventChannel<FusedEvent>,
+    dms: EventChannel<DmsAnalysis>,
+    adas: EventChannel<AdasAnalysis>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHANNEL_CAPACITY)
+    }
+}
+
+impl EventHub {
+    /// Create a hub with `capacity` slots per channel
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sensor_frames: EventChannel::new(capacity),
+            fused_events: EventChannel::new(capacity),
+            dms: EventChannel::new(capacity),
+            adas: EventChannel::new(capacity),
+        }
+    }
+
+    /// Publish a sensor frame
+    pub fn publish_sensor_frame(&self, frame: SensorFrame) -> usize {
+        self.sensor_frames.publish(frame)
+    }
+
+    /// Publish a fused event
+    pub fn publish_fused_event(&self, event: FusedEvent) -> usize {
+        self.fused_events.publish(event)
+    }
+
+    /// Publish a DMS analysis result
+    pub fn publish_dms(&self, analysis: DmsAnalysis) -> usize {
+        self.dms.publish(analysis)
+    }
+
+    /// Publish an ADAS analysis result
+    pub fn publish_adas(&self, analysis: AdasAnalysis) -> usize {
+        self.adas.publish(analysis)
+    }
+
+    /// Subscribe to sensor frames
+    pub fn subscribe_sensor_frames(&self) -> EventSubscriber<SensorFrame> {
+        self.sensor_frames.subscribe()
+    }
+
+    /// Subscribe to fused events
+    pub fn subscribe_fused_events(&self) -> EventSubscriber<FusedEvent> {
+        self.fused_events.subscribe()
+    }
+
+    /// Subscribe to DMS analysis results
+    pub fn subscribe_dms(&self) -> EventSubscriber<DmsAnalysis> {
+        self.dms.subscribe()
+    }
+
+    /// Subscribe to ADAS analysis results
+    pub fn subscribe_adas(&self) -> EventSubscriber<AdasAnalysis> {
+        self.adas.subscribe()
+    }
+
+    /// Get lag statistics for all channels
+    pub fn stats(&self) -> EventHubStats {
+        EventHubStats {
+            sensor_frames_lagged: self.sensor_frames.lagged_count(),
+            fused_events_lagged: self.fused_events.lagged_count(),
+            dms_lagged: self.dms.lagged_count(),
+            adas_lagged: self.adas.lagged_count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_multiple_subscribers_receive() {
+        let hub = EventHub::new(8);
+        let mut a = hub.subscribe_sensor_frames();
+        let mut b = hub.subscribe_sensor_frames();
+
+        let delivered = hub.publish_sensor_frame(SensorFrame {
+            rpm: 1800,
+            ..Default::default()
+        });
+        assert_eq!(delivered, 2);
+
+        assert_eq!(a.recv().await.unwrap().rpm, 1800);
+        assert_eq!(b.recv().await.unwrap().rpm, 1800);
+    }
+
+    #[tokio::test]
+    async fn test_lagged_subscriber_is_counted() {
+        let hub = EventHub::new(2);
+        let mut slow = hub.subscribe_fused_events();
+
+        for _ in 0..5 {
+            hub.publish_fused_event(FusedEvent::Normal);
+        }
+
+        assert!(slow.recv().await.is_some());
+        assert_eq!(hub.stats().fused_events_lagged, 3);
+    }
+
+    #[test]
+    fn test_publish_without_subscribers() {
+        let hub = EventHub::default();
+        assert_eq!(hub.publish_dms(DmsAnalysis::default()), 0);
+    }
+}
@@ -3,7 +3,7 @@
 //! REST API and WebSocket server for the vehicle diagnostics dashboard.
 
 use axum::{
-    routing::get,
+    routing::{get, post},
     Router,
     Json,
     extract::State,
@@ -11,21 +11,55 @@ use axum::{
 };
 use serde::Serialize;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{info, Level};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 use tower_governor::GovernorLayer;
 
 mod routes;
+pub mod commands;
+pub mod events;
 pub mod rate_limit;
+pub mod selftest;
 
-use storage::Repository;
+use commands::CommandBus;
+use events::EventHub;
+use inference_engine::{LatencyHistogram, LatencySnapshot};
+use obd_protocol::ObdClient;
+use obd_scheduler::{AdapterStatus, SchedulerHealth};
+use storage::{PredictionStore, Repository, SensorStore, Storage, StorageError};
 use rate_limit::{RateLimitConfig, create_governor_config};
+use selftest::{ImuReader, SelfTestConfig, SharedFrameSource};
+
+/// How often the SQLite backend deletes records past its retention limits
+pub const RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
 
 /// Application state shared across handlers
 pub struct AppState {
-    /// Storage repository
-    pub repository: Repository,
+    /// Storage backend for trips, events, calibrations and the outbox
+    pub repository: Arc<dyn Storage>,
+    /// Sensor log, the repository unless replaced
+    pub sensors: Arc<dyn SensorStore + Send + Sync>,
+    /// Prediction store, the repository unless replaced
+    pub predictions: Arc<dyn PredictionStore + Send + Sync>,
+    /// Broadcast hub for live pipeline events
+    pub events: EventHub,
+    /// Inference latency distribution, shared with the batcher
+    pub inference_latency: Arc<LatencyHistogram>,
+    /// OBD client for on-demand diagnostic queries
+    pub obd_client: Arc<Mutex<ObdClient>>,
+    /// Adapter health published by the PID scheduler, once one is running
+    pub obd_health: Option<Arc<SchedulerHealth>>,
+    /// Queue for remote commands; `None` disables them
+    pub commands: Option<CommandBus>,
+    /// Token a WebSocket client must present before sending commands
+    pub command_token: Option<String>,
+    /// Camera sampled by the self-test, if attached
+    pub camera: Option<SharedFrameSource>,
+    /// IMU sampled by the self-test, if attached
+    pub imu: Option<Arc<dyn ImuReader>>,
+    /// Models, storage location and broker checked by the self-test
+    pub self_test: SelfTestConfig,
     /// Version string
     pub version: String,
     /// Start time
@@ -34,13 +68,93 @@ pub struct AppState {
 
 impl AppState {
     /// Create new application state
+    ///
+    /// On-demand OBD queries go to a mock client until
+    /// [`with_obd_client`](Self::with_obd_client) attaches a real one.
     pub fn new() -> Self {
+        let repository = Arc::new(Repository::new());
         Self {
-            repository: Repository::new(),
+            repository: repository.clone(),
+            sensors: repository.clone(),
+            predictions: repository,
+            events: EventHub::default(),
+            inference_latency: Arc::new(LatencyHistogram::default()),
+            obd_client: Arc::new(Mutex::new(ObdClient::mock())),
+            obd_health: None,
+            commands: None,
+            command_token: None,
+            camera: None,
+            imu: None,
+            self_test: SelfTestConfig::default(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             start_time: std::time::Instant::now(),
         }
     }
+
+    /// Application state persisting to the SQLite database at `db_path`,
+    /// trimmed to the retention limits every [`RETENTION_INTERVAL`]
+    pub async fn with_sqlite(db_path: &str) -> Result<Self, StorageError> {
+        let repository = Arc::new(Repository::with_sqlite(db_path).await?);
+        repository.spawn_retention(RETENTION_INTERVAL);
+        Ok(Self::new().with_storage(repository))
+    }
+
+    /// Persist everything to `storage` instead of the in-memory repository
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.sensors = storage.clone();
+        self.predictions = storage.clone();
+        self.repository = storage;
+        self
+    }
+
+    /// Keep the sensor log in `store` rather than the repository
+    pub fn with_sensor_store(mut self, store: Arc<dyn SensorStore + Send + Sync>) -> Self {
+        self.sensors = store;
+        self
+    }
+
+    /// Keep predictions in `store` rather than the repository
+    pub fn with_prediction_store(mut self, store: Arc<dyn PredictionStore + Send + Sync>) -> Self {
+        self.predictions = store;
+        self
+    }
+
+    /// Use the given OBD client for on-demand queries
+    pub fn with_obd_client(mut self, client: ObdClient) -> Self {
+        self.obd_client = Arc::new(Mutex::new(client));
+        self
+    }
+
+    /// Report OBD status from the scheduler's shared health state
+    pub fn with_obd_health(mut self, health: Arc<SchedulerHealth>) -> Self {
+        self.obd_health = Some(health);
+        self
+    }
+
+    /// Accept remote commands from clients presenting `token`
+    pub fn with_commands(mut self, bus: CommandBus, token: impl Into<String>) -> Self {
+        self.commands = Some(bus);
+        self.command_token = Some(token.into());
+        self
+    }
+
+    /// Include `camera` in the self-test
+    pub fn with_camera(mut self, camera: SharedFrameSource) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Include `imu` in the self-test
+    pub fn with_imu(mut self, imu: Arc<dyn ImuReader>) -> Self {
+        self.imu = Some(imu);
+        self
+    }
+
+    /// Configure what else the self-test checks
+    pub fn with_self_test(mut self, config: SelfTestConfig) -> Self {
+        self.self_test = config;
+        self
+    }
 }
 
 /// Health response
@@ -69,11 +183,36 @@ pub struct ComponentHealth {
     pub last_activity_ms: Option<u64>,
 }
 
+impl ComponentHealth {
+    /// OBD status from the scheduler: `ok`, `unhealthy`, or `unknown` when
+    /// no scheduler is attached; activity is the age of the last answer
+    fn obd(health: Option<&SchedulerHealth>, now_ms: u64) -> Self {
+        let Some(health) = health else {
+            return Self {
+                status: "unknown".to_string(),
+                last_activity_ms: None,
+            };
+        };
+        let snapshot = health.snapshot();
+        let status = match snapshot.status {
+            AdapterStatus::Healthy => "ok",
+            AdapterStatus::Unhealthy => "unhealthy",
+        };
+        Self {
+            status: status.to_string(),
+            last_activity_ms: snapshot.last_success_ms.map(|t| now_ms.saturating_sub(t)),
+        }
+    }
+}
+
 /// System metrics
 #[derive(Debug, Serialize)]
 pub struct SystemMetrics {
-    pub sensor_count: usize,
-    pub prediction_count: usize,
+    /// `None` when the sensor log could not be counted
+    pub sensor_count: Option<usize>,
+    /// `None` when the predictions could not be counted
+    pub prediction_count: Option<usize>,
+    pub inference_latency: LatencySnapshot,
 }
 
 /// Create the application router
@@ -84,15 +223,29 @@ pub fn create_router(state: Arc<RwLock<AppState>>) -> Router {
     // Rate limited API routes
     let api_routes = Router::new()
         .route("/sensors/live", get(routes::sensors::get_live))
+        .route("/sensors/aggregates", get(routes::sensors::get_aggregates))
         .route("/predictions", get(routes::predictions::get_predictions))
+        .route("/predictions/:id", get(routes::predictions::get_prediction))
+        .route("/predictions/:id/context", get(routes::predictions::get_prediction_context))
+        .route("/predictions/:id/acknowledge", post(routes::predictions::acknowledge_prediction))
         .route("/alerts", get(routes::alerts::get_alerts))
+        .route("/trips", get(routes::trips::list_trips))
+        .route("/trips/:id", get(routes::trips::get_trip))
+        .route("/obd/pid/:pid_hex", get(routes::obd::query_pid))
+        .route("/obd/dtcs", get(routes::obd::get_dtcs).delete(routes::obd::clear_dtcs))
+        .route("/selftest", post(selftest::run_self_test))
         .layer(GovernorLayer { config: governor_conf });
 
     // Health endpoint is not rate limited
-    Router::new()
+    let router = Router::new()
         .route("/api/v1/health", get(health_handler))
-        .nest("/api/v1", api_routes)
-        .with_state(state)
+        .nest("/api/v1", api_routes);
+
+    // Long-lived socket, authenticated in-band
+    #[cfg(feature = "ws")]
+    let router = router.route("/api/v1/ws", get(routes::ws::ws_handler));
+
+    router.with_state(state)
 }
 
 /// Health check handler
@@ -100,33 +253,39 @@ async fn health_handler(
     State(state): State<Arc<RwLock<AppState>>>,
 ) -> impl IntoResponse {
     let state = state.read().await;
-    let timestamp = std::time::SystemTime::now()
+    let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
+        .unwrap_or_default();
+
+    let obd = ComponentHealth::obd(state.obd_health.as_deref(), now.as_millis() as u64);
+    let sensor_count = state.sensors.sensor_count().await;
+    let prediction_count = state.predictions.prediction_count().await;
+    if let Err(e) = sensor_count.as_ref().and(prediction_count.as_ref()) {
+        warn!("Health check could not read storage: {}", e);
+    }
+    let database_ok = sensor_count.is_ok() && prediction_count.is_ok();
+    let status = if obd.status == "unhealthy" || !database_ok { "degraded" } else { "healthy" };
 
     let response = HealthResponse {
-        status: "healthy".to_string(),
-        timestamp,
+        status: status.to_string(),
+        timestamp: now.as_secs(),
         version: state.version.clone(),
         uptime_seconds: state.start_time.elapsed().as_secs(),
         components: ComponentStatus {
-            obd: ComponentHealth {
-                status: "ok".to_string(),
-                last_activity_ms: Some(100),
-            },
+            obd,
             inference: ComponentHealth {
                 status: "ok".to_string(),
                 last_activity_ms: Some(150),
             },
             database: ComponentHealth {
-                status: "ok".to_string(),
+                status: if database_ok { "ok" } else { "unhealthy" }.to_string(),
                 last_activity_ms: None,
             },
         },
         metrics: SystemMetrics {
-            sensor_count: state.repository.sensor_count(),
-            prediction_count: state.repository.prediction_count(),
+            sensor_count: sensor_count.ok(),
+            prediction_count: prediction_count.ok(),
+            inference_latency: state.inference_latency.snapshot(),
         },
     };
 
@@ -144,11 +303,20 @@ pub fn init_logging() {
         .expect("Failed to set tracing subscriber");
 }
 
-/// Run the server
-pub async fn run_server(addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// Run the server, persisting to SQLite at `db_path` or in memory without one
+/// and answering on-demand OBD queries through `obd_client`
+pub async fn run_server(
+    addr: &str,
+    db_path: Option<&str>,
+    obd_client: ObdClient,
+) -> Result<(), Box<dyn std::error::Error>> {
     init_logging();
     
-    let state = Arc::new(RwLock::new(AppState::new()));
+    let state = match db_path {
+        Some(path) => AppState::with_sqlite(path).await?,
+        None => AppState::new(),
+    };
+    let state = Arc::new(RwLock::new(state.with_obd_client(obd_client)));
     let app = create_router(state);
 
     info!("Starting API server on {}", addr);
@@ -158,3 +326,311 @@ pub async fn run_server(addr: &str) -> Result<(), Box<dyn std::error::Error>> {
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use obd_scheduler::{PidScheduler, SchedulerConfig};
+    use selftest::{self_test, CheckStatus};
+    use storage::{
+        CalibrationRecord, EventRecord, OutboxMessage, Page, PredictionFilter, PredictionRecord,
+        SensorAggregates, SensorRecord, StorageError, TripRecord,
+    };
+
+    #[test]
+    fn test_obd_component_reflects_scheduler() {
+        let unattached = ComponentHealth::obd(None, 1_000);
+        assert_eq!(unattached.status, "unknown");
+
+        let scheduler = PidScheduler::new(SchedulerConfig::default()).unwrap();
+        let health = scheduler.health();
+        let obd = ComponentHealth::obd(Some(&health), 1_000);
+        assert_eq!(obd.status, "ok");
+        // Nothing answered yet
+        assert_eq!(obd.last_activity_ms, None);
+    }
+
+    /// Backend that records which trait methods the API calls
+    #[derive(Default)]
+    struct RecordingStorage {
+        calls: Arc<std::sync::Mutex<Vec<String>>>,
+        calibration: std::sync::Mutex<Option<CalibrationRecord>>,
+        /// Fail calibration writes as a locked database would
+        locked: bool,
+    }
+
+    impl RecordingStorage {
+        fn record(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SensorStore for RecordingStorage {
+        async fn insert_sensor(&self, _: SensorRecord) -> Result<(), StorageError> {
+            self.record("insert_sensor".into());
+            Ok(())
+        }
+        async fn insert_sensors_batch(&self, _: &[SensorRecord]) -> Result<(), StorageError> {
+            Ok(())
+        }
+        async fn get_sensors(&self, limit: usize) -> Result<Vec<SensorRecord>, StorageError> {
+            self.record(format!("get_sensors({limit})"));
+            Ok(vec![SensorRecord { rpm: 2_000, ..Default::default() }])
+        }
+        async fn get_sensors_paged(&self, _: Option<i64>, _: usize) -> Result<Page<SensorRecord>, StorageError> {
+            Ok(Page { items: Vec::new(), next_cursor: None })
+        }
+        async fn get_sensors_since(&self, since_ms: i64) -> Result<Vec<SensorRecord>, StorageError> {
+            self.record(format!("get_sensors_since({since_ms})"));
+            Ok(Vec::new())
+        }
+        async fn sensor_aggregates(&self, since_ms: i64) -> Result<SensorAggregates, StorageError> {
+            self.record(format!("sensor_aggregates({since_ms})"));
+            Ok(SensorAggregates::default())
+        }
+        async fn prune_sensors(&self, _: i64) -> Result<usize, StorageError> {
+            Ok(0)
+        }
+        async fn get_trip_sensors(&self, _: i64) -> Result<Vec<SensorRecord>, StorageError> {
+            Ok(Vec::new())
+        }
+        async fn trip_sensor_aggregates(&self, _: i64) -> Result<SensorAggregates, StorageError> {
+            Ok(SensorAggregates::default())
+        }
+        async fn sensor_count(&self) -> Result<usize, StorageError> {
+            self.record("sensor_count".into());
+            Ok(1)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PredictionStore for RecordingStorage {
+        async fn insert_prediction(&self, _: PredictionRecord) -> Result<i64, StorageError> {
+            Ok(1)
+        }
+        async fn get_predictions(&self, filter: &PredictionFilter) -> Result<Vec<PredictionRecord>, StorageError> {
+            self.record(format!(
+                "get_predictions({:?}, {:?}, {}, {})",
+                filter.severity, filter.fault_class, filter.unacknowledged_only, filter.limit
+            ));
+            Ok(Vec::new())
+        }
+        async fn get_predictions_paged(
+            &self,
+            _: Option<i64>,
+            _: usize,
+        ) -> Result<Page<PredictionRecord>, StorageError> {
+            Ok(Page { items: Vec::new(), next_cursor: None })
+        }
+        async fn get_predictions_between(
+            &self,
+            _: i64,
+            _: i64,
+            _: Option<&str>,
+        ) -> Result<Vec<PredictionRecord>, StorageError> {
+            Ok(Vec::new())
+        }
+        async fn get_prediction(&self, _: i64) -> Result<PredictionRecord, StorageError> {
+            Err(StorageError::NotFound)
+        }
+        async fn get_prediction_with_context(
+            &self,
+            id: i64,
+        ) -> Result<(PredictionRecord, Vec<SensorRecord>), StorageError> {
+            self.record(format!("get_prediction_with_context({id})"));
+            Err(StorageError::NotFound)
+        }
+        async fn prediction_counts_by_severity(
+            &self,
+            _: i64,
+        ) -> Result<std::collections::HashMap<String, usize>, StorageError> {
+            Ok(Default::default())
+        }
+        async fn acknowledge_prediction(&self, _: i64) -> Result<(), StorageError> {
+            Ok(())
+        }
+        async fn prune_predictions(&self, _: i64) -> Result<usize, StorageError> {
+            Ok(0)
+        }
+        async fn prediction_count(&self) -> Result<usize, StorageError> {
+            Ok(0)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Storage for RecordingStorage {
+        async fn insert_event(&self, _: EventRecord) -> Result<i64, StorageError> {
+            Ok(1)
+        }
+        async fn get_events(&self, _: usize) -> Result<Vec<EventRecord>, StorageError> {
+            Ok(Vec::new())
+        }
+        async fn prune_events(&self, _: i64) -> Result<usize, StorageError> {
+            Ok(0)
+        }
+        async fn save_calibration(&self, record: CalibrationRecord) -> Result<(), StorageError> {
+            self.record("save_calibration".into());
+            if self.locked {
+                return Err(StorageError::Query("database is locked".into()));
+            }
+            *self.calibration.lock().unwrap() = Some(record);
+            Ok(())
+        }
+        async fn get_calibration(
+            &self,
+            _: &str,
+            _: &str,
+        ) -> Result<Option<CalibrationRecord>, StorageError> {
+            self.record("get_calibration".into());
+            Ok(self.calibration.lock().unwrap().clone())
+        }
+        async fn get_calibrations(&self, _: &str) -> Result<Vec<CalibrationRecord>, StorageError> {
+            Ok(Vec::new())
+        }
+        async fn delete_calibration(&self, _: &str, _: &str) -> Result<bool, StorageError> {
+            self.record("delete_calibration".into());
+            Ok(self.calibration.lock().unwrap().take().is_some())
+        }
+        async fn start_trip(&self, _: i64) -> Result<i64, StorageError> {
+            Ok(1)
+        }
+        async fn end_trip(&self, _: i64, _: i64, _: f64) -> Result<(), StorageError> {
+            Ok(())
+        }
+        async fn list_trips(&self, _: usize, _: usize) -> Result<Vec<TripRecord>, StorageError> {
+            Ok(Vec::new())
+        }
+        async fn trip_count(&self) -> Result<usize, StorageError> {
+            Ok(0)
+        }
+        async fn get_trip(&self, _: i64) -> Result<TripRecord, StorageError> {
+            Err(StorageError::NotFound)
+        }
+        async fn get_trip_events(&self, _: i64) -> Result<Vec<EventRecord>, StorageError> {
+            Ok(Vec::new())
+        }
+        async fn trip_event_counts(
+            &self,
+            _: &[i64],
+        ) -> Result<std::collections::HashMap<i64, std::collections::HashMap<String, usize>>, StorageError> {
+            Ok(Default::default())
+        }
+        async fn enqueue_outbox(&self, _: OutboxMessage) -> Result<i64, StorageError> {
+            Ok(1)
+        }
+        async fn peek_outbox(&self, _: usize) -> Result<Vec<OutboxMessage>, StorageError> {
+            Ok(Vec::new())
+        }
+        async fn ack_outbox(&self, _: i64) -> Result<(), StorageError> {
+            Ok(())
+        }
+        async fn requeue_outbox(&self, _: i64, _: std::time::Duration) -> Result<u32, StorageError> {
+            Ok(1)
+        }
+        async fn dead_letter_outbox(&self, _: i64) -> Result<(), StorageError> {
+            Ok(())
+        }
+        async fn get_dead_letters(&self) -> Result<Vec<OutboxMessage>, StorageError> {
+            Ok(Vec::new())
+        }
+        async fn prune_dead_letters(&self, _: i64) -> Result<usize, StorageError> {
+            Ok(0)
+        }
+        async fn outbox_len(&self) -> Result<usize, StorageError> {
+            Ok(0)
+        }
+        async fn reserved_sequence(&self, _: &str) -> Result<u64, StorageError> {
+            Ok(0)
+        }
+        async fn reserve_sequence(&self, _: &str, _: u64) -> Result<(), StorageError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_go_through_storage_trait() {
+        use axum::extract::{Path, Query};
+        use axum::http::StatusCode;
+        use routes::predictions::{get_prediction_context, get_predictions, PredictionQuery};
+        use routes::sensors::{get_live, SensorQuery};
+
+        let storage = RecordingStorage::default();
+        let calls = Arc::clone(&storage.calls);
+        let state = Arc::new(RwLock::new(AppState::new().with_storage(Arc::new(storage))));
+
+        let Json(live) = get_live(State(state.clone()), Query(SensorQuery { limit: 5, since: None })).await;
+        assert_eq!(live.data[0].rpm, 2_000);
+        let _ = get_live(State(state.clone()), Query(SensorQuery { limit: 5, since: Some(100) })).await;
+        let _ = get_predictions(
+            State(state.clone()),
+            Query(PredictionQuery {
+                severity: Some("high".into()),
+                fault_class: None,
+                unacknowledged: true,
+                limit: 900,
+            }),
+        )
+        .await;
+        let missing = get_prediction_context(State(state.clone()), Path(7)).await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+        assert_eq!(self_test(&*state.read().await).await.component("storage").unwrap().status, CheckStatus::Pass);
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "get_sensors(5)",
+                "get_sensors_since(100)",
+                "get_predictions(Some(\"high\"), None, true, 500)",
+                "get_prediction_with_context(7)",
+                "save_calibration",
+                "get_calibration",
+                "delete_calibration",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_self_test_fails_on_storage_error() {
+        let storage = RecordingStorage { locked: true, ..Default::default() };
+        let state = AppState::new().with_storage(Arc::new(storage));
+
+        let report = self_test(&state).await;
+        let check = report.component("storage").unwrap();
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.contains("locked"), "{}", check.detail);
+        assert!(!report.passed);
+    }
+
+    #[tokio::test]
+    async fn test_sensor_store_replaces_repository_sensor_log() {
+        use axum::extract::Query;
+        use routes::sensors::{get_live, SensorQuery};
+
+        let sensors = RecordingStorage::default();
+        let calls = Arc::clone(&sensors.calls);
+        let repository = Arc::new(Repository::new());
+        repository.insert_sensor(SensorRecord { rpm: 900, ..Default::default() }).await.unwrap();
+        let state = AppState::new()
+            .with_storage(repository.clone())
+            .with_sensor_store(Arc::new(sensors));
+        let state = Arc::new(RwLock::new(state));
+
+        let Json(live) = get_live(State(state.clone()), Query(SensorQuery { limit: 5, since: None })).await;
+        assert_eq!(live.data[0].rpm, 2_000);
+        assert_eq!(*calls.lock().unwrap(), ["get_sensors(5)"]);
+
+        // Predictions still come from the repository
+        let prediction = PredictionRecord {
+            id: 0,
+            timestamp_ms: 1_000,
+            fault_class: "overheat".into(),
+            confidence: 0.9,
+            severity: "high".into(),
+            sensor_snapshot: None,
+            acknowledged: false,
+        };
+        repository.insert_prediction(prediction).await.unwrap();
+        assert_eq!(state.read().await.predictions.prediction_count().await.unwrap(), 1);
+    }
+}
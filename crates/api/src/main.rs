@@ -1,7 +1,13 @@
 //! Vehicle Diagnostics Pipeline - Main Entry Point
 
 use api::{init_logging, run_server};
-use tracing::info;
+use obd_protocol::ObdClient;
+use tracing::{info, warn};
+
+/// Adapter used when `VEHICLE_OBD_DEVICE` is not set
+const DEFAULT_OBD_DEVICE: &str = "/dev/ttyUSB0";
+/// ELM327 default baud rate, used when `VEHICLE_OBD_BAUD` is not set
+const DEFAULT_OBD_BAUD: u32 = 38400;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -10,9 +16,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("=== Vehicle AI Pipeline v{} ===", env!("CARGO_PKG_VERSION"));
     info!("Starting vehicle diagnostics system...");
 
-    // Start the API server
+    // On-demand PID queries go to the adapter; an adapter that fails to
+    // initialize leaves the endpoint reporting it as not responding
+    let device = std::env::var("VEHICLE_OBD_DEVICE").unwrap_or_else(|_| DEFAULT_OBD_DEVICE.to_string());
+    let baud_rate = match std::env::var("VEHICLE_OBD_BAUD") {
+        Ok(baud) => baud.parse()?,
+        Err(_) => DEFAULT_OBD_BAUD,
+    };
+    let mut obd_client = ObdClient::new(&device, baud_rate).await?;
+    if let Err(e) = obd_client.initialize().await {
+        warn!("OBD adapter on {} not available: {}", device, e);
+    }
+
+    // Start the API server; sensor data stays in memory unless a database is given
     let addr = "0.0.0.0:8080";
-    run_server(addr).await?;
+    let db_path = std::env::var("VEHICLE_DB_PATH").ok();
+    run_server(addr, db_path.as_deref(), obd_client).await?;
 
     Ok(())
 }
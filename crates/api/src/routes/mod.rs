@@ -3,3 +3,7 @@
 pub mod sensors;
 pub mod predictions;
 pub mod alerts;
+pub mod obd;
+pub mod trips;
+#[cfg(feature = "ws")]
+pub mod ws;
@@ -0,0 +1,181 @@
+//! OBD Diagnostic Routes
+//!
+//! One-off queries issued on behalf of a technician, independent of the
+//! scheduled sampling loop.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::AppState;
+use obd_protocol::{Dtc, ObdError, Pid, PidResponse};
+
+/// Parse a PID given as hex (`0C`, `0x0c`) and restrict it to decodable PIDs
+fn parse_pid(pid_hex: &str) -> Option<Pid> {
+    let digits = pid_hex
+        .strip_prefix("0x")
+        .or_else(|| pid_hex.strip_prefix("0X"))
+        .unwrap_or(pid_hex);
+    if digits.is_empty() || digits.len() > 2 {
+        return None;
+    }
+    u8::from_str_radix(digits, 16).ok().and_then(Pid::from_hex)
+}
+
+/// Query a single Mode 01 PID on demand
+pub async fn query_pid(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Path(pid_hex): Path<String>,
+) -> Result<Json<PidResponse>, StatusCode> {
+    let pid = parse_pid(&pid_hex).ok_or(StatusCode::BAD_REQUEST)?;
+
+    // Don't hold the state lock across the bus round trip
+    let client = Arc::clone(&state.read().await.obd_client);
+    let mut client = client.lock().await;
+
+    info!("On-demand query for PID {:02X}", pid.as_hex());
+    client.query_pid(pid.as_hex()).await.map(Json).map_err(obd_status)
+}
+
+/// A trouble code with its description, if known
+#[derive(Debug, Serialize)]
+pub struct DtcEntry {
+    pub code: String,
+    pub description: Option<&'static str>,
+}
+
+impl From<&Dtc> for DtcEntry {
+    fn from(dtc: &Dtc) -> Self {
+        Self {
+            code: dtc.code.clone(),
+            description: dtc.description(),
+        }
+    }
+}
+
+/// Response for the DTC list endpoint
+#[derive(Debug, Serialize)]
+pub struct DtcResponse {
+    pub data: Vec<DtcEntry>,
+    pub count: usize,
+}
+
+/// Query parameters for clearing DTCs
+#[derive(Debug, Deserialize)]
+pub struct ClearDtcQuery {
+    /// Must be `true`; clearing also resets the readiness monitors
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// Map client errors for diagnostic requests to HTTP status codes
+fn obd_status(e: ObdError) -> StatusCode {
+    warn!("OBD diagnostic request failed: {}", e);
+    match e {
+        ObdError::PidNotSupported(_) | ObdError::NegativeResponse { .. } => StatusCode::NOT_FOUND,
+        ObdError::AdapterNotResponding
+        | ObdError::VehicleNotConnected
+        | ObdError::SearchingProtocol
+        | ObdError::Timeout(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::BAD_GATEWAY,
+    }
+}
+
+/// Read stored trouble codes
+pub async fn get_dtcs(
+    State(state): State<Arc<RwLock<AppState>>>,
+) -> Result<Json<DtcResponse>, StatusCode> {
+    let client = Arc::clone(&state.read().await.obd_client);
+    let dtcs = client.lock().await.read_dtcs().await.map_err(obd_status)?;
+
+    Ok(Json(DtcResponse {
+        count: dtcs.len(),
+        data: dtcs.iter().map(DtcEntry::from).collect(),
+    }))
+}
+
+/// Clear stored trouble codes; requires `?confirm=true`
+pub async fn clear_dtcs(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Query(params): Query<ClearDtcQuery>,
+) -> Result<StatusCode, StatusCode> {
+    if !params.confirm {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let client = Arc::clone(&state.read().await.obd_client);
+    info!("Clearing DTCs on request");
+    client.lock().await.clear_dtcs().await.map_err(obd_status)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> Arc<RwLock<AppState>> {
+        Arc::new(RwLock::new(AppState::new()))
+    }
+
+    #[tokio::test]
+    async fn test_query_rpm() {
+        let Json(response) = query_pid(State(state()), Path("0C".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.pid, 0x0C);
+        assert!((800.0..=3500.0).contains(&response.value));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_pid_rejected() {
+        for input in ["zz", "", "0x", "10C", "A6"] {
+            let err = query_pid(State(state()), Path(input.to_string()))
+                .await
+                .unwrap_err();
+            assert_eq!(err, StatusCode::BAD_REQUEST, "input {input:?}");
+        }
+    }
+
+    fn state_with_codes(codes: &[&str]) -> Arc<RwLock<AppState>> {
+        let dtcs = codes.iter().map(|c| Dtc::parse(c).unwrap()).collect();
+        let client = obd_protocol::ObdClient::mock().with_mock_dtcs(dtcs);
+        Arc::new(RwLock::new(AppState::new().with_obd_client(client)))
+    }
+
+    #[tokio::test]
+    async fn test_list_dtcs() {
+        let state = state_with_codes(&["P0301", "P1234"]);
+        let Json(response) = get_dtcs(State(state)).await.unwrap();
+
+        assert_eq!(response.count, 2);
+        assert_eq!(response.data[0].code, "P0301");
+        assert_eq!(response.data[0].description, Some("Cylinder 1 misfire detected"));
+        // Manufacturer-specific codes have no generic description
+        assert_eq!(response.data[1].description, None);
+    }
+
+    #[tokio::test]
+    async fn test_clear_requires_confirmation() {
+        let state = state_with_codes(&["P0420"]);
+
+        let refused = clear_dtcs(State(state.clone()), Query(ClearDtcQuery { confirm: false })).await;
+        assert_eq!(refused.unwrap_err(), StatusCode::BAD_REQUEST);
+        assert_eq!(get_dtcs(State(state.clone())).await.unwrap().count, 1);
+
+        let cleared = clear_dtcs(State(state.clone()), Query(ClearDtcQuery { confirm: true })).await;
+        assert_eq!(cleared.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(get_dtcs(State(state)).await.unwrap().count, 0);
+    }
+
+    #[test]
+    fn test_parse_pid_prefix() {
+        assert_eq!(parse_pid("0x0d"), Some(Pid::Speed));
+        assert_eq!(parse_pid("0D"), Some(Pid::Speed));
+    }
+}
@@ -1,7 +1,8 @@
 //! Prediction Routes
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
     Json,
 };
 use serde::{Deserialize, Serialize};
@@ -9,13 +10,18 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::AppState;
-use storage::PredictionRecord;
+use storage::{PredictionFilter, PredictionRecord, SensorRecord, StorageError};
 
 /// Query parameters for predictions endpoint
 #[derive(Debug, Deserialize)]
 pub struct PredictionQuery {
     /// Filter by severity
     pub severity: Option<String>,
+    /// Filter by fault class, e.g. `engine_overheating`
+    pub fault_class: Option<String>,
+    /// Only predictions nobody has acknowledged yet
+    #[serde(default)]
+    pub unacknowledged: bool,
     /// Maximum number of records
     #[serde(default = "default_limit")]
     pub limit: usize,
@@ -40,8 +46,15 @@ pub async fn get_predictions(
     let state = state.read().await;
     let limit = params.limit.min(500);
 
-    let data = state.repository
-        .get_predictions(params.severity.as_deref(), limit)
+    let filter = PredictionFilter {
+        severity: params.severity,
+        fault_class: params.fault_class,
+        unacknowledged_only: params.unacknowledged,
+        limit,
+    };
+    let data = state.predictions
+        .get_predictions(&filter)
+        .await
         .unwrap_or_default();
 
     Json(PredictionResponse {
@@ -49,3 +62,57 @@ pub async fn get_predictions(
         data,
     })
 }
+
+/// Get a single prediction, e.g. for an alert detail page
+pub async fn get_prediction(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Path(id): Path<i64>,
+) -> Result<Json<PredictionRecord>, StatusCode> {
+    let state = state.read().await;
+
+    match state.predictions.get_prediction(id).await {
+        Ok(prediction) => Ok(Json(prediction)),
+        Err(StorageError::NotFound) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Response for the prediction context endpoint
+#[derive(Debug, Serialize)]
+pub struct PredictionContextResponse {
+    pub prediction: PredictionRecord,
+    pub sensors: Vec<SensorRecord>,
+    pub sensor_count: usize,
+}
+
+/// Get a prediction with the sensor records from its feature window
+pub async fn get_prediction_context(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Path(id): Path<i64>,
+) -> Result<Json<PredictionContextResponse>, StatusCode> {
+    let state = state.read().await;
+
+    match state.predictions.get_prediction_with_context(id).await {
+        Ok((prediction, sensors)) => Ok(Json(PredictionContextResponse {
+            sensor_count: sensors.len(),
+            prediction,
+            sensors,
+        })),
+        Err(StorageError::NotFound) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Acknowledge a prediction so it drops off the open-items view
+pub async fn acknowledge_prediction(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Path(id): Path<i64>,
+) -> StatusCode {
+    let state = state.read().await;
+
+    match state.predictions.acknowledge_prediction(id).await {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(StorageError::NotFound) => StatusCode::NOT_FOUND,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
@@ -2,6 +2,7 @@
 
 use axum::{
     extract::{Query, State},
+    http::StatusCode,
     Json,
 };
 use serde::{Deserialize, Serialize};
@@ -9,7 +10,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::AppState;
-use storage::SensorRecord;
+use storage::{SensorAggregates, SensorRecord};
 
 /// Query parameters for sensors endpoint
 #[derive(Debug, Deserialize)]
@@ -47,9 +48,9 @@ pub async fn get_live(
     let limit = params.limit.min(1000);
 
     let data = if let Some(since) = params.since {
-        state.repository.get_sensors_since(since).unwrap_or_default()
+        state.sensors.get_sensors_since(since).await.unwrap_or_default()
     } else {
-        state.repository.get_sensors(limit).unwrap_or_default()
+        state.sensors.get_sensors(limit).await.unwrap_or_default()
     };
 
     Json(SensorResponse {
@@ -60,3 +61,27 @@ pub async fn get_live(
         data,
     })
 }
+
+/// Query parameters for the aggregates endpoint
+#[derive(Debug, Deserialize)]
+pub struct AggregateQuery {
+    /// Aggregate records since this timestamp (ms)
+    #[serde(default)]
+    pub since: i64,
+}
+
+/// Get min/max/mean of the core sensor fields instead of raw records
+pub async fn get_aggregates(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Query(params): Query<AggregateQuery>,
+) -> Result<Json<SensorAggregates>, StatusCode> {
+    let state = state.read().await;
+
+    match state.sensors.sensor_aggregates(params.since).await {
+        Ok(aggregates) => Ok(Json(aggregates)),
+        Err(e) => {
+            tracing::warn!("Sensor aggregate query failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
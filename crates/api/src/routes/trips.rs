@@ -0,0 +1,170 @@
+//! Trip Routes
+//!
+//! Trips are the entry point of the fleet review dashboard: the list drills
+//! down into a trip's events and a summary of its sensor log.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::AppState;
+use storage::{EventRecord, SensorAggregates, StorageError, TripRecord};
+
+/// Largest page the trip list serves
+pub const MAX_TRIP_PAGE: usize = 100;
+
+/// Query parameters for the trip list
+#[derive(Debug, Deserialize)]
+pub struct TripQuery {
+    /// Trips to skip, newest first
+    #[serde(default)]
+    pub offset: usize,
+    /// Trips per page, 1 to [`MAX_TRIP_PAGE`]
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+/// One trip in the list
+#[derive(Debug, Serialize)]
+pub struct TripSummary {
+    pub id: i64,
+    pub start_ms: i64,
+    /// `None` while the trip is in progress
+    pub end_ms: Option<i64>,
+    pub distance_km: f64,
+    pub event_count: usize,
+    /// Events by kind, e.g. `{"hard_braking": 2}`
+    pub event_counts: BTreeMap<String, usize>,
+}
+
+impl TripSummary {
+    fn new(trip: TripRecord, counts: HashMap<String, usize>) -> Self {
+        let event_counts: BTreeMap<String, usize> = counts.into_iter().collect();
+        Self {
+            id: trip.id,
+            start_ms: trip.start_ms,
+            end_ms: trip.end_ms,
+            distance_km: trip.distance_km,
+            event_count: event_counts.values().sum(),
+            event_counts,
+        }
+    }
+}
+
+/// Response for the trip list
+#[derive(Debug, Serialize)]
+pub struct TripListResponse {
+    pub data: Vec<TripSummary>,
+    pub offset: usize,
+    pub limit: usize,
+    /// Trips on record, across all pages
+    pub total: usize,
+}
+
+/// Aggregates over the sensor records logged during a trip
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct SensorSummary {
+    pub sample_count: usize,
+    pub avg_speed_kmh: f64,
+    pub max_speed_kmh: i32,
+    pub avg_rpm: f64,
+    pub max_rpm: i32,
+    pub max_coolant_temp: i32,
+}
+
+impl From<SensorAggregates> for SensorSummary {
+    /// All zero when the trip logged no records
+    fn from(aggregates: SensorAggregates) -> Self {
+        Self {
+            sample_count: aggregates.count,
+            avg_speed_kmh: aggregates.speed.mean,
+            max_speed_kmh: aggregates.speed.max,
+            avg_rpm: aggregates.rpm.mean,
+            max_rpm: aggregates.rpm.max,
+            max_coolant_temp: aggregates.coolant_temp.max,
+        }
+    }
+}
+
+/// Response for the trip detail
+#[derive(Debug, Serialize)]
+pub struct TripDetailResponse {
+    #[serde(flatten)]
+    pub trip: TripSummary,
+    /// The trip's events, oldest first
+    pub events: Vec<EventRecord>,
+    pub sensors: SensorSummary,
+}
+
+/// List trips, newest first
+pub async fn list_trips(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Query(params): Query<TripQuery>,
+) -> Result<Json<TripListResponse>, StatusCode> {
+    if !(1..=MAX_TRIP_PAGE).contains(&params.limit) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let state = state.read().await;
+    let storage = &state.repository;
+
+    let total = storage.trip_count().await.map_err(internal)?;
+    let trips = storage.list_trips(params.offset, params.limit).await.map_err(internal)?;
+    let ids: Vec<i64> = trips.iter().map(|t| t.id).collect();
+    let mut counts = storage.trip_event_counts(&ids).await.map_err(internal)?;
+    let data = trips
+        .into_iter()
+        .map(|trip| {
+            let trip_counts = counts.remove(&trip.id).unwrap_or_default();
+            TripSummary::new(trip, trip_counts)
+        })
+        .collect();
+
+    Ok(Json(TripListResponse {
+        data,
+        offset: params.offset,
+        limit: params.limit,
+        total,
+    }))
+}
+
+/// Get a trip with its events and sensor summary
+pub async fn get_trip(
+    State(state): State<Arc<RwLock<AppState>>>,
+    Path(id): Path<i64>,
+) -> Result<Json<TripDetailResponse>, StatusCode> {
+    let state = state.read().await;
+    let storage = &state.repository;
+
+    let trip = match storage.get_trip(id).await {
+        Ok(trip) => trip,
+        Err(StorageError::NotFound) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => return Err(internal(e)),
+    };
+    let events = storage.get_trip_events(id).await.map_err(internal)?;
+    let sensors = state.sensors.trip_sensor_aggregates(id).await.map_err(internal)?;
+
+    let mut counts = HashMap::new();
+    for event in &events {
+        *counts.entry(event.kind.clone()).or_insert(0) += 1;
+    }
+    Ok(Json(TripDetailResponse {
+        trip: TripSummary::new(trip, counts),
+        events,
+        sensors: sensors.into(),
+    }))
+}
+
+fn internal(e: StorageError) -> StatusCode {
+    tracing::warn!("Trip query failed: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
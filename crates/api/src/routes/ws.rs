@@ -0,0 +1,121 @@
+//! WebSocket Route
+//!
+//! Streams live sensor frames to an authenticated client and accepts
+//! remote commands on the same socket.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::commands::{CommandSession, ServerMessage};
+use crate::events::EventSubscriber;
+use crate::AppState;
+use ring_buffer::SensorFrame;
+
+/// Upgrade to a telemetry and command socket
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<RwLock<AppState>>>,
+) -> Response {
+    let (session, frames) = {
+        let state = state.read().await;
+        (
+            CommandSession::new(state.command_token.clone(), state.commands.clone()),
+            state.events.subscribe_sensor_frames(),
+        )
+    };
+    ws.on_upgrade(move |socket| handle_socket(socket, session, frames))
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+async fn handle_socket(
+    mut socket: WebSocket,
+    mut session: CommandSession,
+    mut frames: EventSubscriber<SensorFrame>,
+) {
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = session.handle_text(&text).await;
+                    let rejected = matches!(reply, ServerMessage::AuthFailed);
+                    if !send(&mut socket, &reply).await || rejected {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum; binary frames are not used
+                Some(Ok(_)) => {}
+            },
+            // Telemetry only flows once the client has authenticated
+            frame = frames.recv(), if session.is_authenticated() => match frame {
+                Some(frame) => {
+                    if !send(&mut socket, &ServerMessage::Telemetry { frame }).await {
+                        break;
+                    }
+                }
+                None => break,
+            },
+        }
+    }
+    debug!("WebSocket closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use tokio_tungstenite::tungstenite;
+
+    type Client = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    async fn recv_json(client: &mut Client) -> Value {
+        loop {
+            match client.next().await.expect("socket closed").unwrap() {
+                tungstenite::Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticated_client_receives_telemetry() {
+        let (bus, _requests) = crate::commands::CommandBus::new(4);
+        let state = Arc::new(RwLock::new(AppState::new().with_commands(bus, "secret")));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = crate::create_router(Arc::clone(&state));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}/api/v1/ws", addr))
+            .await
+            .unwrap();
+        let auth = json!({ "type": "auth", "token": "secret" }).to_string();
+        client.send(tungstenite::Message::Text(auth)).await.unwrap();
+        assert_eq!(recv_json(&mut client).await["type"], "auth_ok");
+
+        state.read().await.events.publish_sensor_frame(SensorFrame {
+            rpm: 2400,
+            ..Default::default()
+        });
+        let telemetry = recv_json(&mut client).await;
+        assert_eq!(telemetry["type"], "telemetry");
+        assert_eq!(telemetry["frame"]["rpm"], 2400);
+    }
+}
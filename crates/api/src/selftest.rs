@@ -0,0 +1,339 @@
+//! Diagnostic Self-Test
+//!
+//! One-shot health check for a unit flagged bad in the field. Each
+//! subsystem is exercised end to end rather than read from cached health:
+//! the OBD adapter must echo `0100`, the camera must deliver a frame, the
+//! IMU must answer a read, every configured model must load, the storage
+//! backend must round-trip a probe record and the MQTT broker must accept a
+//! connection. Subsystems not attached to this unit are reported as skipped.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use axum::{extract::State, Json};
+use camera_capture::imu::{ImuDriver, ImuError};
+use camera_capture::{FrameSource, ImuData};
+use inference_engine::InferenceEngine;
+use obd_protocol::{ObdClient, IGNITION_PROBE_PID};
+use serde::Serialize;
+use storage::{CalibrationRecord, Storage};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::AppState;
+
+/// Vehicle ID under which the storage probe is written; the probe is
+/// deleted again once read back
+const PROBE_VEHICLE_ID: &str = "selftest";
+
+/// Camera shared between the capture path and the self-test
+pub type SharedFrameSource = Arc<Mutex<dyn FrameSource>>;
+
+/// An IMU that can be sampled on demand
+pub trait ImuReader: Send + Sync {
+    fn read_imu(&self) -> Result<ImuData, ImuError>;
+}
+
+impl ImuReader for ImuDriver {
+    fn read_imu(&self) -> Result<ImuData, ImuError> {
+        self.read()
+    }
+}
+
+/// What the self-test exercises beyond the always-present subsystems
+#[derive(Debug, Clone)]
+pub struct SelfTestConfig {
+    /// ONNX models that must load
+    pub model_paths: Vec<PathBuf>,
+    /// MQTT broker as `host:port`; `None` skips the connectivity check
+    pub mqtt_broker: Option<String>,
+    /// Longest wait for a camera frame or broker connection
+    pub timeout: Duration,
+}
+
+impl Default for SelfTestConfig {
+    fn default() -> Self {
+        Self {
+            model_paths: Vec::new(),
+            mqtt_broker: None,
+            timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Subsystem not attached to this unit
+    Skipped,
+}
+
+/// Result for one subsystem
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub duration_ms: u64,
+    /// What was observed, or why the check failed
+    pub detail: String,
+}
+
+/// Per-component self-test results
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// True when no check failed
+    pub passed: bool,
+    /// Start of the run (Unix ms)
+    pub started_at_ms: u64,
+    pub duration_ms: u64,
+    pub components: Vec<ComponentCheck>,
+}
+
+impl SelfTestReport {
+    /// Look up a component by name
+    pub fn component(&self, name: &str) -> Option<&ComponentCheck> {
+        self.components.iter().find(|c| c.name == name)
+    }
+}
+
+/// Time `check` and record its outcome under `name`
+async fn run_check<F>(name: impl Into<String>, check: F) -> ComponentCheck
+where
+    F: std::future::Future<Output = Result<Option<String>, String>>,
+{
+    let started = Instant::now();
+    let result = check.await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let (status, detail) = match result {
+        Ok(Some(detail)) => (CheckStatus::Pass, detail),
+        Ok(None) => (CheckStatus::Skipped, "not attached".to_string()),
+        Err(detail) => (CheckStatus::Fail, detail),
+    };
+    ComponentCheck {
+        name: name.into(),
+        status,
+        duration_ms,
+        detail,
+    }
+}
+
+/// Handles to the subsystems under test, cloned out of [`AppState`] so the
+/// state lock is not held while the checks wait on hardware
+struct Targets {
+    obd_client: Arc<tokio::sync::Mutex<ObdClient>>,
+    camera: Option<SharedFrameSource>,
+    imu: Option<Arc<dyn ImuReader>>,
+    storage: Arc<dyn Storage>,
+    config: SelfTestConfig,
+}
+
+impl Targets {
+    fn of(state: &AppState) -> Self {
+        Self {
+            obd_client: state.obd_client.clone(),
+            camera: state.camera.clone(),
+            imu: state.imu.clone(),
+            storage: state.repository.clone(),
+            config: state.self_test.clone(),
+        }
+    }
+}
+
+/// Exercise every subsystem once and report per-component results
+pub async fn self_test(state: &AppState) -> SelfTestReport {
+    run_checks(Targets::of(state)).await
+}
+
+async fn run_checks(state: Targets) -> SelfTestReport {
+    let started = Instant::now();
+    let started_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let config = &state.config;
+    let mut components = Vec::new();
+
+    components.push(
+        run_check("obd", async {
+            let mut client = state.obd_client.lock().await;
+            let response = client
+                .query_pid(IGNITION_PROBE_PID)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(Some(format!("0100 answered with {} data bytes", response.raw_bytes.len())))
+        })
+        .await,
+    );
+
+    components.push(
+        run_check("camera", async {
+            let Some(camera) = state.camera.clone() else {
+                return Ok(None);
+            };
+            let timeout = config.timeout;
+            let frame = tokio::task::spawn_blocking(move || {
+                camera.lock().ok().and_then(|mut source| source.next_frame(timeout))
+            })
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("no frame within {:?}", timeout))?;
+            Ok(Some(format!("{}x{} frame #{}", frame.width, frame.height, frame.sequence)))
+        })
+        .await,
+    );
+
+    components.push(
+        run_check("imu", async {
+            let Some(imu) = state.imu.clone() else {
+                return Ok(None);
+            };
+            let data = tokio::task::spawn_blocking(move || imu.read_imu())
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())?;
+            Ok(Some(format!("{:.2} g", data.g_force)))
+        })
+        .await,
+    );
+
+    for path in &config.model_paths {
+        components.push(
+            run_check(format!("model:{}", path.display()), async {
+                if !path.is_file() {
+                    return Err("model file not found".to_string());
+                }
+                let mut engine =
+                    InferenceEngine::new(&path.to_string_lossy()).map_err(|e| e.to_string())?;
+                engine.load().map_err(|e| e.to_string())?;
+                Ok(Some("loaded".to_string()))
+            })
+            .await,
+        );
+    }
+
+    components.push(
+        run_check("storage", async {
+            let probe = CalibrationRecord {
+                vehicle_id: PROBE_VEHICLE_ID.to_string(),
+                kind: "probe".to_string(),
+                payload: started_at_ms.to_string(),
+                updated_ms: started_at_ms as i64,
+            };
+            state.storage.save_calibration(probe.clone()).await.map_err(|e| e.to_string())?;
+            let read = state.storage.get_calibration(&probe.vehicle_id, &probe.kind).await;
+            // Remove the probe whatever was read, so it never lingers among
+            // real calibrations
+            let deleted = state.storage.delete_calibration(&probe.vehicle_id, &probe.kind).await;
+            let read = read.map_err(|e| e.to_string())?;
+            if read.as_ref() != Some(&probe) {
+                return Err(format!("probe read back as {:?}", read.map(|r| r.payload)));
+            }
+            deleted.map_err(|e| e.to_string())?;
+            Ok(Some("probe record round-tripped".to_string()))
+        })
+        .await,
+    );
+
+    components.push(
+        run_check("mqtt", async {
+            let Some(broker) = &config.mqtt_broker else {
+                return Ok(None);
+            };
+            tokio::time::timeout(config.timeout, tokio::net::TcpStream::connect(broker))
+                .await
+                .map_err(|_| format!("{broker} did not accept within {:?}", config.timeout))?
+                .map_err(|e| format!("{broker}: {e}"))?;
+            Ok(Some(format!("connected to {broker}")))
+        })
+        .await,
+    );
+
+    let passed = components.iter().all(|c| c.status != CheckStatus::Fail);
+    for failed in components.iter().filter(|c| c.status == CheckStatus::Fail) {
+        warn!("Self-test: {} failed: {}", failed.name, failed.detail);
+    }
+    info!("Self-test finished: {}", if passed { "pass" } else { "FAIL" });
+
+    SelfTestReport {
+        passed,
+        started_at_ms,
+        duration_ms: started.elapsed().as_millis() as u64,
+        components,
+    }
+}
+
+/// Run the self-test (`POST /api/v1/selftest`)
+pub async fn run_self_test(State(state): State<Arc<RwLock<AppState>>>) -> Json<SelfTestReport> {
+    let targets = Targets::of(&*state.read().await);
+    Json(run_checks(targets).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camera_capture::{CameraError, VideoFrame};
+
+    struct MockCamera;
+
+    impl FrameSource for MockCamera {
+        fn next_frame(&mut self, _timeout: Duration) -> Option<VideoFrame> {
+            Some(VideoFrame::new(vec![0; 4 * 4 * 3], 4, 4, 0, 1))
+        }
+
+        fn reinit(&mut self) -> Result<(), CameraError> {
+            Ok(())
+        }
+    }
+
+    struct MockImu;
+
+    impl ImuReader for MockImu {
+        fn read_imu(&self) -> Result<ImuData, ImuError> {
+            Ok(ImuData {
+                accel_x: 0.0,
+                accel_y: 0.0,
+                accel_z: 1.0,
+                gyro_x: 0.0,
+                gyro_y: 0.0,
+                gyro_z: 0.0,
+                temperature: 30.0,
+                g_force: 1.0,
+                timestamp_ns: 0,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_all_mock_subsystems_pass() {
+        let broker = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let model = std::env::temp_dir().join(format!("selftest-{}.onnx", std::process::id()));
+        std::fs::write(&model, b"onnx").unwrap();
+
+        let state = AppState::new()
+            .with_camera(Arc::new(Mutex::new(MockCamera)))
+            .with_imu(Arc::new(MockImu))
+            .with_self_test(SelfTestConfig {
+                model_paths: vec![model.clone()],
+                mqtt_broker: Some(broker.local_addr().unwrap().to_string()),
+                ..Default::default()
+            });
+
+        let report = self_test(&state).await;
+        std::fs::remove_file(&model).unwrap();
+
+        for check in &report.components {
+            assert_eq!(check.status, CheckStatus::Pass, "{}: {}", check.name, check.detail);
+        }
+        assert!(report.passed);
+        assert_eq!(report.components.len(), 6);
+        for name in ["obd", "camera", "imu", "storage", "mqtt"] {
+            assert!(report.component(name).is_some(), "missing {name}");
+        }
+        // The storage probe is not left behind
+        assert!(state.repository.get_calibrations(PROBE_VEHICLE_ID).await.unwrap().is_empty());
+    }
+}
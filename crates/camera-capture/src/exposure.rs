@@ -0,0 +1,208 @@
+//! Auto-exposure for the cabin camera
+//!
+//! The IR illuminator and sensor settings chosen at boot suit one driver
+//! in one light. A pale face under a low sun saturates, a dark face at
+//! night sinks into noise, and either degrades eye-state detection. This
+//! loop meters luminance over the face region and nudges exposure (then
+//! gain, which adds noise) towards a target level.
+
+use serde::{Deserialize, Serialize};
+
+use crate::frame::VideoFrame;
+use crate::CameraError;
+
+/// Sensor settings, in driver units: exposure in microseconds, gain in
+/// percent (100 = unity)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExposureSettings {
+    pub exposure_us: u32,
+    pub gain_pct: u32,
+}
+
+impl Default for ExposureSettings {
+    fn default() -> Self {
+        Self {
+            exposure_us: 5_000,
+            gain_pct: 100,
+        }
+    }
+}
+
+/// Runtime exposure controls of a camera
+pub trait ExposureControl {
+    /// Set the exposure time (µs)
+    fn set_exposure(&self, exposure_us: u32) -> Result<(), CameraError>;
+
+    /// Set the analog gain (percent, 100 = unity)
+    fn set_gain(&self, gain_pct: u32) -> Result<(), CameraError>;
+}
+
+/// Auto-exposure tuning
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AutoExposureConfig {
+    /// Median luminance to aim for over the metering region (0-255)
+    pub target_luminance: f32,
+    /// No adjustment while the median is within this of the target
+    pub tolerance: f32,
+    /// Largest brightness change applied in one step (ratio, > 1.0)
+    pub max_step_ratio: f32,
+    /// Exposure limits (µs); the upper bound keeps motion blur acceptable
+    pub exposure_range_us: (u32, u32),
+    /// Gain limits (percent)
+    pub gain_range_pct: (u32, u32),
+    /// Region metered when no face box is given, as normalized
+    /// `[x, y, width, height]`; defaults to where the driver's face sits
+    pub metering_region: [f32; 4],
+}
+
+impl Default for AutoExposureConfig {
+    fn default() -> Self {
+        Self {
+            target_luminance: 110.0,
+            tolerance: 12.0,
+            max_step_ratio: 2.0,
+            exposure_range_us: (100, 20_000),
+            gain_range_pct: (100, 1_600),
+            metering_region: [0.25, 0.15, 0.5, 0.6],
+        }
+    }
+}
+
+/// Exposure/gain controller driven by frame luminance
+#[derive(Debug, Clone)]
+pub struct AutoExposure {
+    config: AutoExposureConfig,
+    settings: ExposureSettings,
+}
+
+impl AutoExposure {
+    /// Start from the settings the camera is currently using
+    pub fn new(config: AutoExposureConfig, current: ExposureSettings) -> Self {
+        Self {
+            config,
+            settings: current,
+        }
+    }
+
+    /// Settings last applied
+    pub fn settings(&self) -> ExposureSettings {
+        self.settings
+    }
+
+    /// Luminance histogram of the pixel box `[x, y, width, height]`,
+    /// clipped to the frame
+    pub fn luminance_histogram(frame: &VideoFrame, region: [u32; 4]) -> [u32; 256] {
+        let mut histogram = [0u32; 256];
+        let [x, y, w, h] = region;
+        let x_end = x.saturating_add(w).min(frame.width);
+        let y_end = y.saturating_add(h).min(frame.height);
+        let grayscale = frame.is_grayscale();
+
+        for row in y.min(y_end)..y_end {
+            for col in x.min(x_end)..x_end {
+                let idx = (row * frame.width + col) as usize;
+                let luma = if grayscale {
+                    frame.data[idx]
+                } else {
+                    match frame.data.get(idx * 3..idx * 3 + 3) {
+                        Some(p) => {
+                            (p[0] as f32 * 0.299 + p[1] as f32 * 0.587 + p[2] as f32 * 0.114) as u8
+                        }
+                        None => continue,
+                    }
+                };
+                histogram[luma as usize] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Median luminance of a histogram, `None` if it is empty
+    pub fn median_luminance(histogram: &[u32; 256]) -> Option<f32> {
+        let total: u64 = histogram.iter().map(|&c| c as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut seen = 0u64;
+        for (level, &count) in histogram.iter().enumerate() {
+            seen += count as u64;
+            if seen * 2 >= total {
+                return Some(level as f32);
+            }
+        }
+        None
+    }
+
+    /// Settings for the next frame, metering the configured region;
+    /// `None` when the current ones are good enough
+    pub fn update(&mut self, frame: &VideoFrame) -> Option<ExposureSettings> {
+        let [x, y, w, h] = self.config.metering_region;
+        let (fw, fh) = (frame.width as f32, frame.height as f32);
+        let region = [
+            (x * fw) as u32,
+            (y * fh) as u32,
+            (w * fw).ceil() as u32,
+            (h * fh).ceil() as u32,
+        ];
+        self.update_region(frame, region)
+    }
+
+    /// Settings for the next frame, metering a pixel box such as the
+    /// detected face
+    pub fn update_region(&mut self, frame: &VideoFrame, region: [u32; 4]) -> Option<ExposureSettings> {
+        let histogram = Self::luminance_histogram(frame, region);
+        let luminance = Self::median_luminance(&histogram)?;
+        if (luminance - self.config.target_luminance).abs() <= self.config.tolerance {
+            return None;
+        }
+
+        let max_step = self.config.max_step_ratio.max(1.0);
+        let ratio = (self.config.target_luminance / luminance.max(1.0)).clamp(1.0 / max_step, max_step);
+
+        // Brightness scales with exposure × gain; spend exposure first
+        let current = self.settings.exposure_us as f32 * self.settings.gain_pct as f32 / 100.0;
+        let wanted = current * ratio;
+        let (min_exp, max_exp) = self.config.exposure_range_us;
+        let (min_gain, max_gain) = self.config.gain_range_pct;
+        let exposure_us = (wanted.round() as u32).clamp(min_exp, max_exp);
+        let gain_pct = ((wanted / exposure_us as f32 * 100.0).round() as u32).clamp(min_gain, max_gain);
+
+        let next = ExposureSettings {
+            exposure_us,
+            gain_pct,
+        };
+        if next == self.settings {
+            // Pinned at a limit
+            return None;
+        }
+        self.settings = next;
+        Some(next)
+    }
+
+    /// Meter `frame` and push any new settings to the camera
+    pub fn step(
+        &mut self,
+        control: &impl ExposureControl,
+        frame: &VideoFrame,
+    ) -> Result<Option<ExposureSettings>, CameraError> {
+        let previous = self.settings;
+        let Some(next) = self.update(frame) else {
+            return Ok(None);
+        };
+        let applied = (|| {
+            if next.exposure_us != previous.exposure_us {
+                control.set_exposure(next.exposure_us)?;
+            }
+            if next.gain_pct != previous.gain_pct {
+                control.set_gain(next.gain_pct)?;
+            }
+            Ok(())
+        })();
+        if let Err(e) = applied {
+            // Retry from what the camera last accepted
+            self.settings = previous;
+            return Err(e);
+        }
+        Ok(Some(next))
+    }
+}
@@ -3,7 +3,8 @@
 use std::ffi::CString;
 use std::os::raw::c_char;
 
-use crate::{CameraConfig, CameraError, CameraType};
+use crate::exposure::ExposureControl;
+use crate::{CameraConfig, CameraError, CameraType, LatencyMode};
 
 /// C pixel format enum
 #[repr(C)]
@@ -52,7 +53,31 @@ pub struct CCameraConfig {
     pub buffer_count: i32,
 }
 
-// Cabin camera FFI functions
+impl CCameraConfig {
+    /// Build the C configuration; `device` must outlive the returned struct
+    pub(crate) fn from_config(config: &CameraConfig, device: &CString) -> Self {
+        Self {
+            device: device.as_ptr(),
+            camera_type: match config.camera_type {
+                CameraType::Cabin => CCameraType::CabinIr,
+                CameraType::Road => CCameraType::Road,
+            },
+            width: config.width,
+            height: config.height,
+            fps: config.fps,
+            format: if config.camera_type == CameraType::Cabin {
+                CPixelFormat::Mjpeg
+            } else {
+                CPixelFormat::H264
+            },
+            enable_ir: if config.enable_ir { 1 } else { 0 },
+            buffer_count: config.buffer_count.min(i32::MAX as u32) as i32,
+        }
+    }
+}
+
+// Cabin camera FFI functions (src_cpp/cabin_capture.cpp)
+#[cfg(feature = "ffi")]
 extern "C" {
     fn cabin_camera_init(config: *const CCameraConfig) -> i32;
     fn cabin_camera_start() -> i32;
@@ -62,9 +87,13 @@ extern "C" {
     fn cabin_camera_release_frame(frame: *mut CVideoFrame);
     fn cabin_camera_is_streaming() -> i32;
     fn cabin_camera_last_error() -> *const c_char;
+    fn cabin_camera_set_ir(enable: i32) -> i32;
+    fn cabin_camera_set_exposure(exposure_us: u32) -> i32;
+    fn cabin_camera_set_gain(gain_pct: u32) -> i32;
 }
 
-// Road camera FFI functions
+// Road camera FFI functions (src_cpp/road_capture.cpp)
+#[cfg(feature = "ffi")]
 extern "C" {
     fn road_camera_init(config: *const CCameraConfig) -> i32;
     fn road_camera_start() -> i32;
@@ -74,12 +103,176 @@ extern "C" {
     fn road_camera_release_frame(frame: *mut CVideoFrame);
     fn road_camera_is_streaming() -> i32;
     fn road_camera_last_error() -> *const c_char;
+    fn road_camera_set_exposure(exposure_us: u32) -> i32;
+    fn road_camera_set_gain(gain_pct: u32) -> i32;
 }
 
+// Mock implementations for when FFI is not available: cameras that
+// initialize and stream but never deliver a frame, recording the last
+// control values they were given
+#[cfg(not(feature = "ffi"))]
+#[allow(dead_code)]
+mod mock_ffi {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+
+    /// Matches CAM_ERROR_NOT_INITIALIZED in camera_capture.h
+    const CAM_ERROR_NOT_INITIALIZED: i32 = -10;
+    /// Matches CAM_ERROR_INVALID_ARG in camera_capture.h
+    const CAM_ERROR_INVALID_ARG: i32 = -7;
+
+    pub(super) struct MockCamera {
+        initialized: AtomicBool,
+        streaming: AtomicBool,
+        pub(super) ir: AtomicI32,
+        pub(super) exposure_us: AtomicU32,
+        pub(super) gain_pct: AtomicU32,
+    }
+
+    impl MockCamera {
+        const fn new() -> Self {
+            Self {
+                initialized: AtomicBool::new(false),
+                streaming: AtomicBool::new(false),
+                ir: AtomicI32::new(-1),
+                exposure_us: AtomicU32::new(0),
+                gain_pct: AtomicU32::new(0),
+            }
+        }
+
+        fn init(&self, config: *const CCameraConfig) -> i32 {
+            let enable_ir = unsafe { (*config).enable_ir };
+            self.ir.store(enable_ir, Ordering::SeqCst);
+            self.initialized.store(true, Ordering::SeqCst);
+            0
+        }
+
+        fn start(&self) -> i32 {
+            if !self.initialized.load(Ordering::SeqCst) {
+                return CAM_ERROR_NOT_INITIALIZED;
+            }
+            self.streaming.store(true, Ordering::SeqCst);
+            0
+        }
+
+        fn shutdown(&self) {
+            self.streaming.store(false, Ordering::SeqCst);
+            self.initialized.store(false, Ordering::SeqCst);
+        }
+
+        fn set(&self, value: &AtomicU32, new: u32) -> i32 {
+            if !self.initialized.load(Ordering::SeqCst) {
+                return CAM_ERROR_NOT_INITIALIZED;
+            }
+            if new == 0 {
+                return CAM_ERROR_INVALID_ARG;
+            }
+            value.store(new, Ordering::SeqCst);
+            0
+        }
+    }
+
+    pub(super) static CABIN: MockCamera = MockCamera::new();
+    pub(super) static ROAD: MockCamera = MockCamera::new();
+
+    fn no_error() -> *const c_char {
+        static MSG: &[u8] = b"No error\0";
+        MSG.as_ptr() as *const c_char
+    }
+
+    pub unsafe fn cabin_camera_init(config: *const CCameraConfig) -> i32 {
+        CABIN.init(config)
+    }
+
+    pub unsafe fn cabin_camera_start() -> i32 {
+        CABIN.start()
+    }
+
+    pub unsafe fn cabin_camera_stop() {
+        CABIN.streaming.store(false, Ordering::SeqCst);
+    }
+
+    pub unsafe fn cabin_camera_shutdown() {
+        CABIN.shutdown();
+    }
+
+    pub unsafe fn cabin_camera_read_frame(_timeout_ms: i32) -> *mut CVideoFrame {
+        std::ptr::null_mut()
+    }
+
+    pub unsafe fn cabin_camera_release_frame(_frame: *mut CVideoFrame) {}
+
+    pub unsafe fn cabin_camera_is_streaming() -> i32 {
+        CABIN.streaming.load(Ordering::SeqCst) as i32
+    }
+
+    pub unsafe fn cabin_camera_last_error() -> *const c_char {
+        no_error()
+    }
+
+    pub unsafe fn cabin_camera_set_ir(enable: i32) -> i32 {
+        if !CABIN.initialized.load(Ordering::SeqCst) {
+            return CAM_ERROR_NOT_INITIALIZED;
+        }
+        CABIN.ir.store(enable, Ordering::SeqCst);
+        0
+    }
+
+    pub unsafe fn cabin_camera_set_exposure(exposure_us: u32) -> i32 {
+        CABIN.set(&CABIN.exposure_us, exposure_us)
+    }
+
+    pub unsafe fn cabin_camera_set_gain(gain_pct: u32) -> i32 {
+        CABIN.set(&CABIN.gain_pct, gain_pct)
+    }
+
+    pub unsafe fn road_camera_init(config: *const CCameraConfig) -> i32 {
+        ROAD.init(config)
+    }
+
+    pub unsafe fn road_camera_start() -> i32 {
+        ROAD.start()
+    }
+
+    pub unsafe fn road_camera_stop() {
+        ROAD.streaming.store(false, Ordering::SeqCst);
+    }
+
+    pub unsafe fn road_camera_shutdown() {
+        ROAD.shutdown();
+    }
+
+    pub unsafe fn road_camera_read_frame(_timeout_ms: i32) -> *mut CVideoFrame {
+        std::ptr::null_mut()
+    }
+
+    pub unsafe fn road_camera_release_frame(_frame: *mut CVideoFrame) {}
+
+    pub unsafe fn road_camera_is_streaming() -> i32 {
+        ROAD.streaming.load(Ordering::SeqCst) as i32
+    }
+
+    pub unsafe fn road_camera_last_error() -> *const c_char {
+        no_error()
+    }
+
+    pub unsafe fn road_camera_set_exposure(exposure_us: u32) -> i32 {
+        ROAD.set(&ROAD.exposure_us, exposure_us)
+    }
+
+    pub unsafe fn road_camera_set_gain(gain_pct: u32) -> i32 {
+        ROAD.set(&ROAD.gain_pct, gain_pct)
+    }
+}
+
+#[cfg(not(feature = "ffi"))]
+use mock_ffi::*;
+
 /// Camera driver wrapper
 pub struct CameraDriver {
     camera_type: CameraType,
     device: CString,
+    latency_mode: LatencyMode,
 }
 
 impl CameraDriver {
@@ -88,23 +281,7 @@ impl CameraDriver {
         let device = CString::new(config.device.as_str())
             .map_err(|e| CameraError::Open(e.to_string()))?;
         
-        let c_config = CCameraConfig {
-            device: device.as_ptr(),
-            camera_type: match config.camera_type {
-                CameraType::Cabin => CCameraType::CabinIr,
-                CameraType::Road => CCameraType::Road,
-            },
-            width: config.width,
-            height: config.height,
-            fps: config.fps,
-            format: if config.camera_type == CameraType::Cabin {
-                CPixelFormat::Mjpeg
-            } else {
-                CPixelFormat::H264
-            },
-            enable_ir: if config.enable_ir { 1 } else { 0 },
-            buffer_count: 4,
-        };
+        let c_config = CCameraConfig::from_config(config, &device);
 
         let ret = match config.camera_type {
             CameraType::Cabin => unsafe { cabin_camera_init(&c_config) },
@@ -118,6 +295,7 @@ impl CameraDriver {
         Ok(Self {
             camera_type: config.camera_type,
             device,
+            latency_mode: config.latency_mode,
         })
     }
 
@@ -152,7 +330,24 @@ impl CameraDriver {
     }
 
     /// Read next frame (blocking with timeout)
+    ///
+    /// In [`LatencyMode::LowLatency`] any frames already queued behind the
+    /// first one are drained and released, so the newest frame is returned.
     pub fn read_frame(&self, timeout_ms: i32) -> Option<CapturedFrame> {
+        let mut frame = self.read_raw(timeout_ms)?;
+
+        if self.latency_mode == LatencyMode::LowLatency {
+            // Dropping the previous frame hands its buffer back to the driver
+            while let Some(newer) = self.read_raw(0) {
+                frame = newer;
+            }
+        }
+
+        Some(frame)
+    }
+
+    /// Dequeue a single frame from the driver
+    fn read_raw(&self, timeout_ms: i32) -> Option<CapturedFrame> {
         let frame_ptr = match self.camera_type {
             CameraType::Cabin => unsafe { cabin_camera_read_frame(timeout_ms) },
             CameraType::Road => unsafe { road_camera_read_frame(timeout_ms) },
@@ -167,6 +362,58 @@ impl CameraDriver {
             camera_type: self.camera_type,
         })
     }
+
+    /// Get the frame delivery strategy
+    pub fn latency_mode(&self) -> LatencyMode {
+        self.latency_mode
+    }
+
+    /// Switch the IR illuminator on or off (cabin camera only)
+    pub fn set_ir(&self, enable: bool) -> Result<(), CameraError> {
+        let ret = match self.camera_type {
+            CameraType::Cabin => unsafe { cabin_camera_set_ir(enable as i32) },
+            CameraType::Road => {
+                return Err(CameraError::Control("road camera has no IR illuminator".into()))
+            }
+        };
+        control_result("set_ir", ret)
+    }
+
+    /// Set the exposure time (µs)
+    pub fn set_exposure(&self, exposure_us: u32) -> Result<(), CameraError> {
+        let ret = match self.camera_type {
+            CameraType::Cabin => unsafe { cabin_camera_set_exposure(exposure_us) },
+            CameraType::Road => unsafe { road_camera_set_exposure(exposure_us) },
+        };
+        control_result("set_exposure", ret)
+    }
+
+    /// Set the analog gain (percent, 100 = unity)
+    pub fn set_gain(&self, gain_pct: u32) -> Result<(), CameraError> {
+        let ret = match self.camera_type {
+            CameraType::Cabin => unsafe { cabin_camera_set_gain(gain_pct) },
+            CameraType::Road => unsafe { road_camera_set_gain(gain_pct) },
+        };
+        control_result("set_gain", ret)
+    }
+}
+
+impl ExposureControl for CameraDriver {
+    fn set_exposure(&self, exposure_us: u32) -> Result<(), CameraError> {
+        CameraDriver::set_exposure(self, exposure_us)
+    }
+
+    fn set_gain(&self, gain_pct: u32) -> Result<(), CameraError> {
+        CameraDriver::set_gain(self, gain_pct)
+    }
+}
+
+fn control_result(control: &str, ret: i32) -> Result<(), CameraError> {
+    if ret != 0 {
+        Err(CameraError::Control(format!("{} failed: {}", control, ret)))
+    } else {
+        Ok(())
+    }
 }
 
 impl Drop for CameraDriver {
@@ -231,3 +478,79 @@ impl Drop for CapturedFrame {
 // Make CapturedFrame Send + Sync for async usage
 unsafe impl Send for CapturedFrame {}
 unsafe impl Sync for CapturedFrame {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    use crate::exposure::{AutoExposure, AutoExposureConfig, ExposureSettings};
+    use crate::frame::VideoFrame;
+
+    /// A dim cabin: luminance proportional to exposure × gain
+    #[cfg(not(feature = "ffi"))]
+    fn cabin_frame() -> VideoFrame {
+        let brightness = mock_ffi::CABIN.exposure_us.load(Ordering::SeqCst) as f32 / 1_000.0
+            * mock_ffi::CABIN.gain_pct.load(Ordering::SeqCst) as f32
+            / 100.0
+            * 8.0;
+        let luma = brightness.round().min(255.0) as u8;
+        VideoFrame::from_grayscale(&[luma; 64 * 48], 64, 48, 0, 0).unwrap()
+    }
+
+    #[test]
+    #[cfg(not(feature = "ffi"))]
+    fn test_controls_forwarded_and_auto_exposure_converges() {
+        let driver = CameraDriver::new(&CameraConfig::cabin()).unwrap();
+
+        driver.set_ir(false).unwrap();
+        assert_eq!(mock_ffi::CABIN.ir.load(Ordering::SeqCst), 0);
+        driver.set_ir(true).unwrap();
+        assert_eq!(mock_ffi::CABIN.ir.load(Ordering::SeqCst), 1);
+        let err = driver.set_gain(0).unwrap_err();
+        assert_eq!(err.to_string(), "Camera control failed: set_gain failed: -7");
+
+        // 2 ms at unity gain renders the face at 16/255
+        let start = ExposureSettings { exposure_us: 2_000, gain_pct: 100 };
+        driver.set_exposure(start.exposure_us).unwrap();
+        driver.set_gain(start.gain_pct).unwrap();
+        let config = AutoExposureConfig::default();
+        let mut auto = AutoExposure::new(config, start);
+
+        let mut luminance = Vec::new();
+        for _ in 0..10 {
+            let frame = cabin_frame();
+            luminance.push(AutoExposure::median_luminance(&AutoExposure::luminance_histogram(
+                &frame,
+                [0, 0, 64, 48],
+            ))
+            .unwrap());
+            auto.step(&driver, &frame).unwrap();
+        }
+
+        // Exposure doubles per step until the target is in reach; gain untouched
+        assert_eq!(&luminance[..4], &[16.0, 32.0, 64.0, 110.0]);
+        let last = *luminance.last().unwrap();
+        assert!((last - config.target_luminance).abs() <= config.tolerance, "{last}");
+        assert_eq!(mock_ffi::CABIN.exposure_us.load(Ordering::SeqCst), auto.settings().exposure_us);
+        assert_eq!(mock_ffi::CABIN.gain_pct.load(Ordering::SeqCst), 100);
+    }
+
+    #[test]
+    fn test_buffer_count_forwarded() {
+        let cabin = CameraConfig::cabin();
+        let device = CString::new(cabin.device.as_str()).unwrap();
+        let c_config = CCameraConfig::from_config(&cabin, &device);
+        assert_eq!(c_config.buffer_count, 2);
+        assert_eq!(cabin.latency_mode, LatencyMode::LowLatency);
+
+        let road = CameraConfig {
+            buffer_count: 8,
+            ..CameraConfig::road()
+        };
+        let device = CString::new(road.device.as_str()).unwrap();
+        let c_config = CCameraConfig::from_config(&road, &device);
+        assert_eq!(c_config.buffer_count, 8);
+        assert_eq!(road.latency_mode, LatencyMode::Buffered);
+    }
+}
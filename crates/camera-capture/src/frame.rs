@@ -1,6 +1,11 @@
 //! Video frame types and processing
 
 use crate::ffi::CPixelFormat;
+use crate::CameraError;
+use serde::{Deserialize, Serialize};
+
+/// Bytes per pixel in an RGB24 frame
+pub const RGB_CHANNELS: usize = 3;
 
 /// Pixel format
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -51,6 +56,58 @@ impl VideoFrame {
         }
     }
 
+    /// Expand a single-channel (grayscale/IR) image to an RGB frame
+    pub fn from_grayscale(
+        gray: &[u8],
+        width: u32,
+        height: u32,
+        timestamp_ns: u64,
+        sequence: u32,
+    ) -> Result<Self, CameraError> {
+        let expected = width as usize * height as usize;
+        if gray.len() != expected {
+            return Err(CameraError::Format(format!(
+                "grayscale frame {}x{} needs {} bytes, got {}",
+                width, height, expected, gray.len()
+            )));
+        }
+
+        let mut data = Vec::with_capacity(expected * RGB_CHANNELS);
+        for &y in gray {
+            data.extend_from_slice(&[y, y, y]);
+        }
+
+        Ok(Self::new(data, width, height, timestamp_ns, sequence))
+    }
+
+    /// Check that the pixel data is a packed RGB24 image of the stated size
+    pub fn validate_rgb(&self) -> Result<(), CameraError> {
+        let pixels = self.width as usize * self.height as usize;
+        let expected = pixels * RGB_CHANNELS;
+
+        if self.data.len() == expected {
+            return Ok(());
+        }
+
+        if self.data.len() == pixels && pixels > 0 {
+            return Err(CameraError::Format(format!(
+                "frame {}x{} is single-channel ({} bytes); expected {} bytes of RGB24, use VideoFrame::from_grayscale",
+                self.width, self.height, pixels, expected
+            )));
+        }
+
+        Err(CameraError::Format(format!(
+            "RGB frame {}x{} needs {} bytes, got {}",
+            self.width, self.height, expected, self.data.len()
+        )))
+    }
+
+    /// Check whether the frame holds packed single-channel (grayscale/IR) data
+    pub fn is_grayscale(&self) -> bool {
+        let pixels = self.width as usize * self.height as usize;
+        pixels > 0 && self.data.len() == pixels
+    }
+
     /// Get pixel at (x, y)
     pub fn get_pixel(&self, x: u32, y: u32) -> Option<[u8; 3]> {
         if x >= self.width || y >= self.height {
@@ -127,6 +184,226 @@ impl VideoFrame {
             sequence: self.sequence,
         }
     }
+
+    /// Scale to fit `width`×`height` without distortion and pad the rest
+    ///
+    /// The content is centred; the returned parameters map coordinates in
+    /// the padded frame back to this one.
+    pub fn letterbox(&self, width: u32, height: u32, pad: [u8; 3]) -> (VideoFrame, LetterboxParams) {
+        let scale = (width as f32 / self.width.max(1) as f32)
+            .min(height as f32 / self.height.max(1) as f32);
+        let content_width = ((self.width as f32 * scale).round() as u32).clamp(1, width.max(1));
+        let content_height = ((self.height as f32 * scale).round() as u32).clamp(1, height.max(1));
+        let params = LetterboxParams {
+            scale,
+            pad_x: (width - content_width) / 2,
+            pad_y: (height - content_height) / 2,
+            content_width,
+            content_height,
+        };
+
+        let content = image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
+            self.width,
+            self.height,
+            self.data.as_slice(),
+        )
+        .map(|img| {
+            image::imageops::resize(
+                &img,
+                content_width,
+                content_height,
+                image::imageops::FilterType::Triangle,
+            )
+            .into_raw()
+        })
+        .unwrap_or_else(|| self.resize(content_width, content_height).data);
+
+        let mut data = pad.repeat(width as usize * height as usize);
+        let row_bytes = content_width as usize * RGB_CHANNELS;
+        for (y, row) in content.chunks_exact(row_bytes).enumerate() {
+            let start = ((params.pad_y as usize + y) * width as usize + params.pad_x as usize)
+                * RGB_CHANNELS;
+            data[start..start + row_bytes].copy_from_slice(row);
+        }
+
+        let frame = VideoFrame {
+            data,
+            width,
+            height,
+            timestamp_ns: self.timestamp_ns,
+            sequence: self.sequence,
+        };
+        (frame, params)
+    }
+
+    /// Contrast-limited adaptive histogram equalization of the luminance
+    ///
+    /// The frame is split into `grid` (columns, rows) tiles, each equalized
+    /// with its histogram bins capped at `clip_limit` times the mean bin
+    /// count (1.0 leaves the frame as is, 2-4 is typical); pixels blend the
+    /// mappings of the nearest tiles. Colour frames keep their chroma: every
+    /// channel shifts by the change in luma. Malformed frames are returned
+    /// unchanged.
+    pub fn clahe(&self, clip_limit: f32, grid: (u32, u32)) -> VideoFrame {
+        let grayscale = self.is_grayscale();
+        if !grayscale && self.validate_rgb().is_err() {
+            return self.clone();
+        }
+        let (width, height) = (self.width as usize, self.height as usize);
+        let luma = if grayscale {
+            self.data.clone()
+        } else {
+            self.to_grayscale()
+        };
+
+        let cols = (grid.0 as usize).clamp(1, width);
+        let rows = (grid.1 as usize).clamp(1, height);
+        let mut luts = Vec::with_capacity(cols * rows);
+        for ty in 0..rows {
+            for tx in 0..cols {
+                let (x0, x1) = (tx * width / cols, (tx + 1) * width / cols);
+                let (y0, y1) = (ty * height / rows, (ty + 1) * height / rows);
+                let mut histogram = [0u32; 256];
+                for y in y0..y1 {
+                    for &v in &luma[y * width + x0..y * width + x1] {
+                        histogram[v as usize] += 1;
+                    }
+                }
+                luts.push(clipped_equalization(&mut histogram, clip_limit));
+            }
+        }
+
+        // Position of a pixel between tile centres along one axis
+        let locate = |pos: usize, len: usize, tiles: usize| {
+            let f = ((pos as f32 + 0.5) * tiles as f32 / len as f32 - 0.5).max(0.0);
+            let t0 = (f as usize).min(tiles - 1);
+            let t1 = (t0 + 1).min(tiles - 1);
+            (t0, t1, (f - t0 as f32).min(1.0))
+        };
+
+        let mut equalized = Vec::with_capacity(luma.len());
+        for y in 0..height {
+            let (ty0, ty1, ay) = locate(y, height, rows);
+            for x in 0..width {
+                let (tx0, tx1, ax) = locate(x, width, cols);
+                let v = luma[y * width + x] as usize;
+                let lerp = |a: u8, b: u8, t: f32| a as f32 + (b as f32 - a as f32) * t;
+                let top = lerp(luts[ty0 * cols + tx0][v], luts[ty0 * cols + tx1][v], ax);
+                let bottom = lerp(luts[ty1 * cols + tx0][v], luts[ty1 * cols + tx1][v], ax);
+                equalized.push((top + (bottom - top) * ay).round() as u8);
+            }
+        }
+
+        let data = if grayscale {
+            equalized
+        } else {
+            let mut data = self.data.clone();
+            for ((pixel, &old), &new) in data.chunks_mut(RGB_CHANNELS).zip(&luma).zip(&equalized) {
+                let delta = new as i16 - old as i16;
+                for c in pixel {
+                    *c = (*c as i16 + delta).clamp(0, 255) as u8;
+                }
+            }
+            data
+        };
+
+        VideoFrame {
+            data,
+            width: self.width,
+            height: self.height,
+            timestamp_ns: self.timestamp_ns,
+            sequence: self.sequence,
+        }
+    }
+}
+
+/// Equalization lookup table for one tile, clipping the histogram first
+fn clipped_equalization(histogram: &mut [u32; 256], clip_limit: f32) -> [u8; 256] {
+    let total: u32 = histogram.iter().sum();
+    let mut lut = [0u8; 256];
+    if total == 0 {
+        for (v, out) in lut.iter_mut().enumerate() {
+            *out = v as u8;
+        }
+        return lut;
+    }
+
+    // Spread the clipped excess evenly so the table stays monotonic
+    let limit = ((clip_limit.max(1.0) * total as f32 / 256.0) as u32).max(1);
+    let mut excess = 0;
+    for bin in histogram.iter_mut() {
+        if *bin > limit {
+            excess += *bin - limit;
+            *bin = limit;
+        }
+    }
+    let (share, remainder) = (excess / 256, (excess % 256) as usize);
+    for (i, bin) in histogram.iter_mut().enumerate() {
+        *bin += share + u32::from(i < remainder);
+    }
+
+    let mut cumulative = 0u32;
+    for (bin, out) in histogram.iter().zip(lut.iter_mut()) {
+        cumulative += bin;
+        *out = (cumulative as u64 * 255 / total as u64) as u8;
+    }
+    lut
+}
+
+/// Low-light enhancement applied to frames before inference
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClaheConfig {
+    /// Histogram bin cap, as a multiple of the mean bin count
+    pub clip_limit: f32,
+    /// Tiles across and down
+    pub grid: (u32, u32),
+}
+
+impl Default for ClaheConfig {
+    fn default() -> Self {
+        Self {
+            clip_limit: 2.0,
+            grid: (8, 8),
+        }
+    }
+}
+
+impl ClaheConfig {
+    /// Enhance `frame` with these settings
+    pub fn apply(&self, frame: &VideoFrame) -> VideoFrame {
+        frame.clahe(self.clip_limit, self.grid)
+    }
+}
+
+/// How [`VideoFrame::letterbox`] placed the source inside its output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LetterboxParams {
+    /// Output pixels per source pixel, the same on both axes
+    pub scale: f32,
+    /// Padding left of the content (pixels)
+    pub pad_x: u32,
+    /// Padding above the content (pixels)
+    pub pad_y: u32,
+    /// Size of the scaled content inside the output
+    pub content_width: u32,
+    pub content_height: u32,
+}
+
+impl LetterboxParams {
+    /// Map a point in the letterboxed frame to source coordinates
+    pub fn to_original_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (x - self.pad_x as f32) / self.scale,
+            (y - self.pad_y as f32) / self.scale,
+        )
+    }
+
+    /// Map an `[x, y, width, height]` box in the letterboxed frame to
+    /// source coordinates
+    pub fn to_original(&self, bbox: [f32; 4]) -> [f32; 4] {
+        let (x, y) = self.to_original_point(bbox[0], bbox[1]);
+        [x, y, bbox[2] / self.scale, bbox[3] / self.scale]
+    }
 }
 
 /// Decode MJPEG frame to RGB
@@ -145,3 +422,90 @@ pub fn decode_mjpeg(mjpeg_data: &[u8]) -> Result<VideoFrame, image::ImageError>
         sequence: 0,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rgb_correct_size() {
+        let frame = VideoFrame::new(vec![0; 4 * 2 * 3], 4, 2, 0, 0);
+        assert!(frame.validate_rgb().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rgb_undersized() {
+        let frame = VideoFrame::new(vec![0; 10], 4, 2, 0, 0);
+        let err = frame.validate_rgb().unwrap_err();
+        assert!(err.to_string().contains("needs 24 bytes, got 10"));
+
+        let ir = VideoFrame::new(vec![0; 8], 4, 2, 0, 0);
+        assert!(ir.validate_rgb().unwrap_err().to_string().contains("single-channel"));
+    }
+
+    #[test]
+    fn test_grayscale_expansion() {
+        let frame = VideoFrame::from_grayscale(&[10, 20, 30, 40], 2, 2, 5, 7).unwrap();
+        assert!(frame.validate_rgb().is_ok());
+        assert_eq!(frame.get_pixel(1, 1), Some([40, 40, 40]));
+        assert_eq!(frame.sequence, 7);
+
+        assert!(VideoFrame::from_grayscale(&[0; 3], 2, 2, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_letterbox_road_frame_to_square() {
+        let frame = VideoFrame::new(vec![200; 1920 * 1080 * 3], 1920, 1080, 0, 3);
+        let (boxed, params) = frame.letterbox(640, 640, [114, 114, 114]);
+
+        assert_eq!((boxed.width, boxed.height), (640, 640));
+        assert_eq!(boxed.data.len(), 640 * 640 * 3);
+        assert_eq!(boxed.sequence, 3);
+
+        // One scale for both axes: 1920x1080 -> 640x360, centred vertically
+        assert_eq!((params.content_width, params.content_height), (640, 360));
+        assert!((params.content_width as f32 / 1920.0 - params.scale).abs() < 1e-6);
+        assert!((params.content_height as f32 / 1080.0 - params.scale).abs() < 1e-6);
+        assert_eq!((params.pad_x, params.pad_y), (0, 140));
+
+        for y in 0..640 {
+            let expected = if (140..500).contains(&y) { 200 } else { 114 };
+            assert_eq!(boxed.get_pixel(0, y), Some([expected; 3]), "row {y}");
+            assert_eq!(boxed.get_pixel(639, y), Some([expected; 3]), "row {y}");
+        }
+
+        let mapped = params.to_original([320.0, 140.0, 100.0, 50.0]);
+        assert!((mapped[0] - 960.0).abs() < 1e-3 && mapped[1].abs() < 1e-3);
+        assert!((mapped[2] - 300.0).abs() < 1e-3 && (mapped[3] - 150.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_clahe_spreads_low_contrast_histogram() {
+        fn spread(values: &[u8]) -> (u8, u8, f32) {
+            let mean = values.iter().map(|&v| v as f32).sum::<f32>() / values.len() as f32;
+            let variance = values.iter().map(|&v| (v as f32 - mean).powi(2)).sum::<f32>()
+                / values.len() as f32;
+            let min = *values.iter().min().unwrap();
+            let max = *values.iter().max().unwrap();
+            (min, max, variance.sqrt())
+        }
+
+        // Dim IR frame: every pixel within 100..130
+        let (w, h) = (64u32, 64u32);
+        let gray: Vec<u8> = (0..w * h).map(|i| 100 + ((i % w + i / w) % 30) as u8).collect();
+        let frame = VideoFrame::new(gray, w, h, 0, 0);
+        let enhanced = frame.clahe(3.0, (4, 4));
+        assert!(enhanced.is_grayscale());
+
+        let (in_min, in_max, in_std) = spread(&frame.data);
+        let (out_min, out_max, out_std) = spread(&enhanced.data);
+        assert!(out_max - out_min > 2 * (in_max - in_min), "range {out_min}..{out_max}");
+        assert!(out_std > 2.0 * in_std, "std {in_std} -> {out_std}");
+
+        // Colour frames: equal channels stay equal (no tint)
+        let rgb = VideoFrame::from_grayscale(&frame.data, w, h, 0, 0).unwrap();
+        let enhanced_rgb = rgb.clahe(3.0, (4, 4));
+        let pixel = enhanced_rgb.get_pixel(10, 20).unwrap();
+        assert!(pixel[0] == pixel[1] && pixel[1] == pixel[2]);
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::ffi::CString;
 use std::os::raw::c_char;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::sync::mpsc;
 use tracing::{debug, error, warn};
@@ -105,6 +106,55 @@ impl From<CImuProcessed> for ImuData {
     }
 }
 
+/// Sensor offsets measured with the vehicle at rest and level
+///
+/// MEMS accelerometers read a few hundredths of a g off zero, and how the
+/// unit is mounted adds more; left in, the offset shows up in every
+/// braking and cornering reading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImuBias {
+    /// Accelerometer offsets (g), gravity excluded
+    pub accel_g: [f32; 3],
+    /// Gyro offsets (deg/s)
+    pub gyro_dps: [f32; 3],
+}
+
+impl ImuBias {
+    /// Estimate offsets from samples taken at rest, with gravity on +Z
+    pub fn estimate(samples: &[ImuData]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let n = samples.len() as f32;
+        let mean = |f: fn(&ImuData) -> f32| samples.iter().map(f).sum::<f32>() / n;
+        Some(Self {
+            accel_g: [
+                mean(|s| s.accel_x),
+                mean(|s| s.accel_y),
+                mean(|s| s.accel_z) - 1.0,
+            ],
+            gyro_dps: [mean(|s| s.gyro_x), mean(|s| s.gyro_y), mean(|s| s.gyro_z)],
+        })
+    }
+
+    /// Remove the offsets from a sample, recomputing its G-force magnitude
+    pub fn apply(&self, data: ImuData) -> ImuData {
+        let accel_x = data.accel_x - self.accel_g[0];
+        let accel_y = data.accel_y - self.accel_g[1];
+        let accel_z = data.accel_z - self.accel_g[2];
+        ImuData {
+            accel_x,
+            accel_y,
+            accel_z,
+            gyro_x: data.gyro_x - self.gyro_dps[0],
+            gyro_y: data.gyro_y - self.gyro_dps[1],
+            gyro_z: data.gyro_z - self.gyro_dps[2],
+            g_force: (accel_x * accel_x + accel_y * accel_y + accel_z * accel_z).sqrt(),
+            ..data
+        }
+    }
+}
+
 /// IMU configuration
 #[derive(Debug, Clone)]
 pub struct ImuConfig {
@@ -114,6 +164,8 @@ pub struct ImuConfig {
     pub address: u8,
     /// Sample rate in Hz
     pub sample_rate: u32,
+    /// Offsets removed from every sample, from the vehicle's calibration
+    pub bias: ImuBias,
 }
 
 impl Default for ImuConfig {
@@ -122,6 +174,7 @@ impl Default for ImuConfig {
             device: "/dev/i2c-1".to_string(),
             address: 0x68,
             sample_rate: 100,
+            bias: ImuBias::default(),
         }
     }
 }
@@ -129,6 +182,7 @@ impl Default for ImuConfig {
 /// IMU driver wrapper
 pub struct ImuDriver {
     _device: CString,
+    bias: ImuBias,
 }
 
 impl ImuDriver {
@@ -148,10 +202,13 @@ impl ImuDriver {
             return Err(ImuError::Open(format!("Init failed: {}", ret)));
         }
 
-        Ok(Self { _device: device })
+        Ok(Self {
+            _device: device,
+            bias: config.bias,
+        })
     }
 
-    /// Read processed IMU data
+    /// Read processed IMU data, with the configured bias removed
     pub fn read(&self) -> Result<ImuData, ImuError> {
         let mut data = CImuProcessed {
             accel_x_g: 0.0,
@@ -170,7 +227,7 @@ impl ImuDriver {
             return Err(ImuError::Read);
         }
 
-        Ok(ImuData::from(data))
+        Ok(self.bias.apply(ImuData::from(data)))
     }
 
     /// Check if initialized
@@ -6,12 +6,23 @@
 //! - Road dashcam (1080p @ 30fps) for ADAS
 //! - IMU sensor for crash detection
 
+pub mod exposure;
 pub mod ffi;
 pub mod frame;
 pub mod imu;
+pub mod mock;
+pub mod normalize;
+pub mod service;
+pub mod skip;
 
-pub use frame::{VideoFrame, PixelFormat};
-pub use imu::{ImuData, ImuService};
+pub use exposure::{AutoExposure, AutoExposureConfig, ExposureControl, ExposureSettings};
+pub use frame::{ClaheConfig, LetterboxParams, VideoFrame, PixelFormat};
+pub use imu::{ImuBias, ImuData, ImuService};
+pub use common_types::MockConfig;
+pub use mock::MockRng;
+pub use normalize::Normalization;
+pub use service::{CameraService, CameraServiceConfig, CameraStatus, DriverSource, FrameSource};
+pub use skip::{FrameSkip, FrameSkipper};
 
 use thiserror::Error;
 
@@ -35,6 +46,9 @@ pub enum CameraError {
     
     #[error("Camera not initialized")]
     NotInitialized,
+
+    #[error("Camera control failed: {0}")]
+    Control(String),
 }
 
 /// Camera type
@@ -46,6 +60,15 @@ pub enum CameraType {
     Road,
 }
 
+/// Frame delivery strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatencyMode {
+    /// Always hand out the newest frame, dropping older queued ones
+    LowLatency,
+    /// Hand out frames in capture order (smooth, but may lag)
+    Buffered,
+}
+
 /// Camera configuration
 #[derive(Debug, Clone)]
 pub struct CameraConfig {
@@ -61,6 +84,10 @@ pub struct CameraConfig {
     pub fps: u32,
     /// Enable IR mode (cabin only)
     pub enable_ir: bool,
+    /// Number of V4L2 capture buffers
+    pub buffer_count: u32,
+    /// Frame delivery strategy
+    pub latency_mode: LatencyMode,
 }
 
 impl Default for CameraConfig {
@@ -72,6 +99,8 @@ impl Default for CameraConfig {
             height: 480,
             fps: 15,
             enable_ir: true,
+            buffer_count: 2,
+            latency_mode: LatencyMode::LowLatency,
         }
     }
 }
@@ -86,6 +115,8 @@ impl CameraConfig {
             height: 480,
             fps: 15,
             enable_ir: true,
+            buffer_count: 2,
+            latency_mode: LatencyMode::LowLatency,
         }
     }
     
@@ -98,6 +129,8 @@ impl CameraConfig {
             height: 1080,
             fps: 30,
             enable_ir: false,
+            buffer_count: 4,
+            latency_mode: LatencyMode::Buffered,
         }
     }
 }
@@ -0,0 +1,84 @@
+//! Model input normalization
+//!
+//! Vision models expect pixels scaled the way they were during training.
+//! Each module's config names the mapping for its models, so a model
+//! trained with a different convention can be dropped in without code
+//! changes.
+
+use serde::{Deserialize, Serialize};
+
+/// ImageNet per-channel mean (RGB, 0-1 scale)
+pub const IMAGENET_MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+/// ImageNet per-channel standard deviation (RGB, 0-1 scale)
+pub const IMAGENET_STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+/// Mapping from 8-bit pixel values to model input
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Normalization {
+    /// `v / 255`
+    #[default]
+    ZeroToOne,
+    /// `v / 127.5 - 1`
+    MinusOneToOne,
+    /// `(v / 255 - mean) / std` with the ImageNet statistics
+    ImageNet,
+    /// `(v / 255 - mean) / std` with per-channel (RGB) statistics on the
+    /// 0-1 scale
+    Custom { mean: [f32; 3], std: [f32; 3] },
+}
+
+impl Normalization {
+    /// Normalize `value` for input channel `channel` (0 = R, 1 = G, 2 = B;
+    /// single-channel models use 0)
+    pub fn apply(&self, value: u8, channel: usize) -> f32 {
+        let v = f32::from(value);
+        match self {
+            Normalization::ZeroToOne => v / 255.0,
+            Normalization::MinusOneToOne => v / 127.5 - 1.0,
+            Normalization::ImageNet => {
+                let c = channel.min(2);
+                (v / 255.0 - IMAGENET_MEAN[c]) / IMAGENET_STD[c]
+            }
+            Normalization::Custom { mean, std } => {
+                let c = channel.min(2);
+                (v / 255.0 - mean[c]) / std[c]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes_on_known_pixel() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+        let pixel = [255u8, 128, 0];
+
+        let values = |n: Normalization| -> Vec<f32> {
+            pixel.iter().enumerate().map(|(c, &v)| n.apply(v, c)).collect()
+        };
+
+        let zero_one = values(Normalization::ZeroToOne);
+        assert!(close(zero_one[0], 1.0) && close(zero_one[1], 128.0 / 255.0) && close(zero_one[2], 0.0));
+
+        let signed = values(Normalization::MinusOneToOne);
+        assert!(close(signed[0], 1.0) && close(signed[1], 128.0 / 127.5 - 1.0) && close(signed[2], -1.0));
+
+        let imagenet = values(Normalization::ImageNet);
+        assert!(close(imagenet[0], (1.0 - 0.485) / 0.229));
+        assert!(close(imagenet[1], (128.0 / 255.0 - 0.456) / 0.224));
+        assert!(close(imagenet[2], -0.406 / 0.225));
+
+        // ArcFace-style (v - 127.5) / 128
+        let arcface = Normalization::Custom {
+            mean: [0.5; 3],
+            std: [128.0 / 255.0; 3],
+        };
+        let custom = values(arcface);
+        assert!(close(custom[0], 127.5 / 128.0));
+        assert!(close(custom[1], 0.5 / 128.0));
+        assert!(close(custom[2], -127.5 / 128.0));
+    }
+}
@@ -0,0 +1,297 @@
+//! Camera capture service with stall detection
+//!
+//! Runs a capture loop on a dedicated thread and watches for the stream
+//! going quiet (cable pulled, driver hang). A stalled camera is reported
+//! through a status channel and the underlying driver is re-opened
+//! periodically until frames resume.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, info, warn};
+
+use crate::ffi::CameraDriver;
+use crate::frame::VideoFrame;
+use crate::{CameraConfig, CameraError};
+
+/// A source of decoded frames that can be re-opened after a failure
+pub trait FrameSource: Send + 'static {
+    /// Wait up to `timeout` for the next frame
+    fn next_frame(&mut self, timeout: Duration) -> Option<VideoFrame>;
+
+    /// Tear down and re-open the underlying device
+    fn reinit(&mut self) -> Result<(), CameraError>;
+}
+
+/// [`FrameSource`] backed by the C++ V4L2 driver
+pub struct DriverSource {
+    config: CameraConfig,
+    driver: Option<CameraDriver>,
+}
+
+impl DriverSource {
+    /// Open and start the camera described by `config`
+    pub fn open(config: CameraConfig) -> Result<Self, CameraError> {
+        let driver = CameraDriver::new(&config)?;
+        driver.start()?;
+        Ok(Self {
+            config,
+            driver: Some(driver),
+        })
+    }
+}
+
+impl FrameSource for DriverSource {
+    fn next_frame(&mut self, timeout: Duration) -> Option<VideoFrame> {
+        let Some(driver) = &self.driver else {
+            std::thread::sleep(timeout);
+            return None;
+        };
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+        let frame = driver.read_frame(timeout_ms)?;
+        Some(VideoFrame::new(
+            frame.data().to_vec(),
+            frame.width(),
+            frame.height(),
+            frame.timestamp_ns(),
+            frame.sequence(),
+        ))
+    }
+
+    fn reinit(&mut self) -> Result<(), CameraError> {
+        // Shut the old instance down before opening the device again
+        self.driver = None;
+        let driver = CameraDriver::new(&self.config)?;
+        driver.start()?;
+        self.driver = Some(driver);
+        Ok(())
+    }
+}
+
+/// Health of a camera stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraStatus {
+    /// No frame received yet
+    Starting,
+    /// Frames are arriving
+    Streaming,
+    /// No frame within the stall timeout; re-init attempts are in progress
+    CameraStalled,
+}
+
+impl CameraStatus {
+    /// Check whether downstream analysis should treat the camera as offline
+    pub fn is_offline(&self) -> bool {
+        matches!(self, CameraStatus::CameraStalled)
+    }
+}
+
+/// Camera service configuration
+#[derive(Debug, Clone)]
+pub struct CameraServiceConfig {
+    /// Time without a frame after which the stream counts as stalled
+    pub stall_timeout: Duration,
+    /// Longest single wait for a frame
+    pub poll_interval: Duration,
+    /// Minimum time between re-init attempts while stalled
+    pub reinit_interval: Duration,
+    /// Frames buffered for the consumer; newer frames are dropped when full
+    pub channel_capacity: usize,
+}
+
+impl Default for CameraServiceConfig {
+    fn default() -> Self {
+        Self {
+            stall_timeout: Duration::from_secs(2),
+            poll_interval: Duration::from_millis(100),
+            reinit_interval: Duration::from_secs(5),
+            channel_capacity: 4,
+        }
+    }
+}
+
+/// Async camera service for tokio
+pub struct CameraService {
+    receiver: mpsc::Receiver<VideoFrame>,
+    status: watch::Receiver<CameraStatus>,
+    reinit_attempts: Arc<AtomicU32>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl CameraService {
+    /// Spawn the capture loop for `source`
+    pub fn spawn<S: FrameSource>(mut source: S, config: CameraServiceConfig) -> Self {
+        let (tx, rx) = mpsc::channel::<VideoFrame>(config.channel_capacity.max(1));
+        let (status_tx, status_rx) = watch::channel(CameraStatus::Starting);
+        let reinit_attempts = Arc::new(AtomicU32::new(0));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let attempts = Arc::clone(&reinit_attempts);
+        let stop = Arc::clone(&shutdown);
+
+        std::thread::spawn(move || {
+            let mut last_frame = Instant::now();
+            let mut last_reinit: Option<Instant> = None;
+
+            while !stop.load(Ordering::SeqCst) {
+                if let Some(frame) = source.next_frame(config.poll_interval) {
+                    last_frame = Instant::now();
+                    status_tx.send_if_modified(|status| {
+                        if *status == CameraStatus::CameraStalled {
+                            info!("Camera stream recovered");
+                        }
+                        let changed = *status != CameraStatus::Streaming;
+                        *status = CameraStatus::Streaming;
+                        changed
+                    });
+
+                    match tx.try_send(frame) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            debug!("Camera consumer behind, dropping frame");
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => {
+                            debug!("Camera receiver dropped");
+                            break;
+                        }
+                    }
+                    continue;
+                }
+
+                if last_frame.elapsed() < config.stall_timeout {
+                    continue;
+                }
+
+                status_tx.send_if_modified(|status| {
+                    if *status == CameraStatus::CameraStalled {
+                        return false;
+                    }
+                    warn!("No camera frame for {:?}, marking stalled", last_frame.elapsed());
+                    *status = CameraStatus::CameraStalled;
+                    true
+                });
+
+                if last_reinit.is_none_or(|t|t.elapsed() >= config.reinit_interval) {
+                    last_reinit = Some(Instant::now());
+                    let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                    match source.reinit() {
+                        Ok(()) => info!("Camera re-initialized (attempt {})", attempt),
+                        Err(e) => warn!("Camera re-init attempt {} failed: {}", attempt, e),
+                    }
+                }
+            }
+        });
+
+        Self {
+            receiver: rx,
+            status: status_rx,
+            reinit_attempts,
+            shutdown,
+        }
+    }
+
+    /// Receive the next frame
+    pub async fn next(&mut self) -> Option<VideoFrame> {
+        self.receiver.recv().await
+    }
+
+    /// Current stream status
+    pub fn status(&self) -> CameraStatus {
+        *self.status.borrow()
+    }
+
+    /// Subscribe to status changes
+    pub fn status_changes(&self) -> watch::Receiver<CameraStatus> {
+        self.status.clone()
+    }
+
+    /// Number of re-init attempts since the service started
+    pub fn reinit_attempts(&self) -> u32 {
+        self.reinit_attempts.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for CameraService {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Produces frames until `producing` is cleared
+    struct MockSource {
+        producing: Arc<AtomicBool>,
+        reinits: Arc<AtomicU32>,
+        sequence: u32,
+    }
+
+    impl FrameSource for MockSource {
+        fn next_frame(&mut self, timeout: Duration) -> Option<VideoFrame> {
+            if !self.producing.load(Ordering::SeqCst) {
+                std::thread::sleep(timeout);
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+            self.sequence += 1;
+            Some(VideoFrame::new(vec![0; 2 * 2 * 3], 2, 2, 0, self.sequence))
+        }
+
+        fn reinit(&mut self) -> Result<(), CameraError> {
+            self.reinits.fetch_add(1, Ordering::SeqCst);
+            Err(CameraError::Open("device unplugged".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stall_detected_and_reinit_attempted() {
+        let producing = Arc::new(AtomicBool::new(true));
+        let reinits = Arc::new(AtomicU32::new(0));
+        let source = MockSource {
+            producing: Arc::clone(&producing),
+            reinits: Arc::clone(&reinits),
+            sequence: 0,
+        };
+
+        let mut service = CameraService::spawn(
+            source,
+            CameraServiceConfig {
+                stall_timeout: Duration::from_millis(100),
+                poll_interval: Duration::from_millis(10),
+                reinit_interval: Duration::from_millis(50),
+                channel_capacity: 4,
+            },
+        );
+
+        assert!(service.next().await.is_some());
+        assert_eq!(service.status(), CameraStatus::Streaming);
+
+        // Camera goes quiet
+        producing.store(false, Ordering::SeqCst);
+        let mut changes = service.status_changes();
+        tokio::time::timeout(
+            Duration::from_secs(2),
+            changes.wait_for(|s| *s == CameraStatus::CameraStalled),
+        )
+        .await
+        .expect("stall not reported")
+        .unwrap();
+
+        assert!(service.status().is_offline());
+        // The service counts an attempt just before calling the source, so
+        // poll until both sides agree on at least one
+        let deadline = Instant::now() + Duration::from_secs(2);
+        loop {
+            let attempts = service.reinit_attempts();
+            if attempts >= 1 && attempts == reinits.load(Ordering::SeqCst) {
+                break;
+            }
+            assert!(Instant::now() < deadline, "re-init not attempted");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Frame skipping for vision modules
+//!
+//! On boards that cannot run every model at the camera rate, a module can
+//! run inference on a subset of frames and reuse its last result for the
+//! rest. The [`FrameSkipper`] makes the per-frame decision and tracks the
+//! rate inference actually runs at.
+
+use serde::{Deserialize, Serialize};
+
+/// Which frames a module runs inference on
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum FrameSkip {
+    /// Process every frame
+    #[default]
+    Off,
+    /// Process one frame in N, starting with the first
+    EveryNth(u32),
+    /// Process frames at up to this rate, based on frame timestamps
+    TargetFps(f32),
+}
+
+/// Weight of the newest interval in the effective-rate average
+const RATE_SMOOTHING: f64 = 0.2;
+
+/// Per-module frame selection state
+#[derive(Debug, Clone)]
+pub struct FrameSkipper {
+    policy: FrameSkip,
+    frames_seen: u64,
+    frames_processed: u64,
+    /// Timestamp from which the next frame is due under `TargetFps`
+    next_due_ns: Option<u64>,
+    last_processed_ns: Option<u64>,
+    /// Smoothed time between processed frames
+    avg_interval_ns: Option<f64>,
+}
+
+impl FrameSkipper {
+    /// Create a skipper applying `policy`
+    pub fn new(policy: FrameSkip) -> Self {
+        Self {
+            policy,
+            frames_seen: 0,
+            frames_processed: 0,
+            next_due_ns: None,
+            last_processed_ns: None,
+            avg_interval_ns: None,
+        }
+    }
+
+    /// Decide whether to run inference on the frame captured at `timestamp_ns`
+    pub fn should_process(&mut self, timestamp_ns: u64) -> bool {
+        let index = self.frames_seen;
+        self.frames_seen += 1;
+
+        let process = match self.policy {
+            FrameSkip::Off => true,
+            FrameSkip::EveryNth(n) => index.is_multiple_of(u64::from(n.max(1))),
+            FrameSkip::TargetFps(fps) if fps > 0.0 => {
+                let period = (1e9 / fps as f64) as u64;
+                // Accept frames slightly early so integer timestamps at an
+                // exact multiple of the camera rate are not pushed back a frame
+                let slack = period / 10;
+                match self.next_due_ns {
+                    Some(due) if timestamp_ns + slack < due => false,
+                    due => {
+                        // Schedule from the due time to hold the target rate,
+                        // but don't try to catch up after a gap
+                        let next = match due {
+                            Some(due) if timestamp_ns < due + period => due + period,
+                            _ => timestamp_ns + period,
+                        };
+                        self.next_due_ns = Some(next);
+                        true
+                    }
+                }
+            }
+            FrameSkip::TargetFps(_) => true,
+        };
+
+        if process {
+            self.record_processed(timestamp_ns);
+        }
+        process
+    }
+
+    fn record_processed(&mut self, timestamp_ns: u64) {
+        self.frames_processed += 1;
+        if let Some(last) = self.last_processed_ns {
+            let interval = timestamp_ns.saturating_sub(last) as f64;
+            self.avg_interval_ns = Some(match self.avg_interval_ns {
+                Some(avg) => avg + RATE_SMOOTHING * (interval - avg),
+                None => interval,
+            });
+        }
+        self.last_processed_ns = Some(timestamp_ns);
+    }
+
+    /// Rate inference is running at, once two frames have been processed
+    pub fn effective_fps(&self) -> Option<f32> {
+        self.avg_interval_ns
+            .filter(|avg| *avg > 0.0)
+            .map(|avg| (1e9 / avg) as f32)
+    }
+
+    /// Frames offered so far
+    pub fn frames_seen(&self) -> u64 {
+        self.frames_seen
+    }
+
+    /// Frames inference ran on so far
+    pub fn frames_processed(&self) -> u64 {
+        self.frames_processed
+    }
+
+    /// Active policy
+    pub fn policy(&self) -> FrameSkip {
+        self.policy
+    }
+}
+
+impl Default for FrameSkipper {
+    fn default() -> Self {
+        Self::new(FrameSkip::Off)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_fps_halves_camera_rate() {
+        let mut skipper = FrameSkipper::new(FrameSkip::TargetFps(15.0));
+        let frame_ns = 1_000_000_000 / 30;
+
+        let processed: Vec<bool> = (0..30).map(|i| skipper.should_process(i * frame_ns)).collect();
+        assert_eq!(processed.iter().filter(|p| **p).count(), 15);
+        assert!(processed.chunks(2).all(|pair| pair == [true, false]));
+
+        let fps = skipper.effective_fps().unwrap();
+        assert!((fps - 15.0).abs() < 0.1, "effective rate {fps}");
+    }
+}
@@ -5,16 +5,26 @@
 //! - Event prioritization
 //! - Video upload management
 //! - Driver roster sync
+//! - Risk-adaptive telemetry heartbeats
 
-use chrono::{DateTime, Utc};
-use event_fusion::{FusedEvent, Severity};
+pub mod telemetry;
+
+use alerting::{AlertQueue, QueuedAlert};
+use chrono::{DateTime, FixedOffset, NaiveTime, Utc};
+use common_types::{Severity, SharedClock, SystemClock};
+use event_fusion::FusedEvent;
 use rumqttc::{AsyncClient, Event, MqttOptions, QoS};
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use storage::{OutboxMessage, SequenceCounter, Storage};
 use thiserror::Error;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+pub use telemetry::{RiskBand, RiskIndicators, TelemetrySampler, TelemetrySampling};
+
 /// Cloud sync error types
 #[derive(Error, Debug)]
 pub enum CloudError {
@@ -26,9 +36,15 @@ pub enum CloudError {
     
     #[error("Bandwidth limit exceeded")]
     BandwidthLimit,
+
+    #[error("Message rate limit exceeded")]
+    RateLimited,
     
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("Outbox storage error: {0}")]
+    Storage(#[from] storage::StorageError),
 }
 
 /// Upload schedule
@@ -55,8 +71,25 @@ pub struct CloudConfig {
     pub vehicle_id: String,
     /// Daily upload quota (MB)
     pub daily_quota_mb: u32,
+    /// Fraction of the quota held back for critical evidence; routine
+    /// uploads stop once the rest is used
+    pub reserved_headroom: f32,
     /// Upload schedule
     pub schedule: UploadSchedule,
+    /// Vehicle's local offset from UTC, for the nightly window
+    pub utc_offset: FixedOffset,
+    /// Local time the nightly window opens (inclusive)
+    pub nightly_start: NaiveTime,
+    /// Local time the nightly window closes (exclusive); may be before
+    /// `nightly_start` for a window that crosses midnight
+    pub nightly_end: NaiveTime,
+    /// Sustained rate for non-critical messages; 0 disables the limit
+    pub messages_per_minute: u32,
+    /// Non-critical messages that may be sent back to back before the
+    /// sustained rate applies
+    pub message_burst: u32,
+    /// Heartbeat rate bands for `Normal` telemetry
+    pub telemetry: TelemetrySampling,
 }
 
 impl Default for CloudConfig {
@@ -66,16 +99,155 @@ impl Default for CloudConfig {
             broker_port: 1883,
             vehicle_id: "unknown".to_string(),
             daily_quota_mb: 500,
+            reserved_headroom: 0.1,
             schedule: UploadSchedule::Opportunistic,
+            utc_offset: FixedOffset::east_opt(0).expect("zero offset is valid"),
+            nightly_start: NaiveTime::from_hms_opt(2, 0, 0).expect("valid time"),
+            nightly_end: NaiveTime::from_hms_opt(6, 0, 0).expect("valid time"),
+            messages_per_minute: 120,
+            message_burst: 20,
+            telemetry: TelemetrySampling::default(),
+        }
+    }
+}
+
+/// Upload class for quota accounting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadPriority {
+    /// Telemetry and non-critical events, limited to the soft cap
+    Routine,
+    /// Evidence for critical events, may use the reserved headroom
+    Critical,
+}
+
+/// Bytes uploaded today against the daily quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaUsage {
+    /// Bytes used by routine uploads
+    pub routine_bytes: u64,
+    /// Bytes used by critical uploads
+    pub critical_bytes: u64,
+    /// Limit for routine uploads (quota minus reserved headroom)
+    pub soft_cap_bytes: u64,
+    /// Full daily quota
+    pub quota_bytes: u64,
+}
+
+impl QuotaUsage {
+    /// Total bytes uploaded today
+    pub fn used_bytes(&self) -> u64 {
+        self.routine_bytes + self.critical_bytes
+    }
+
+    /// Bytes still available to critical uploads
+    pub fn reserve_remaining_bytes(&self) -> u64 {
+        self.quota_bytes.saturating_sub(self.used_bytes())
+    }
+}
+
+/// Whether `time` falls in the half-open window `[start, end)`, which
+/// wraps past midnight when `end` is before `start`
+fn in_daily_window(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+/// Token bucket limiting the non-critical message rate
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    refilled_at: Option<SystemTime>,
+}
+
+impl TokenBucket {
+    fn new(messages_per_minute: u32, burst: u32) -> Self {
+        let capacity = f64::from(burst.max(1));
+        Self {
+            capacity,
+            per_second: f64::from(messages_per_minute) / 60.0,
+            tokens: capacity,
+            refilled_at: None,
+        }
+    }
+
+    /// Take a token if one is available at `now`
+    fn try_take(&mut self, now: SystemTime) -> bool {
+        if self.per_second <= 0.0 {
+            return true;
+        }
+        if let Some(last) = self.refilled_at {
+            // A clock step backwards refills nothing
+            let elapsed = now.duration_since(last).unwrap_or_default().as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        }
+        self.refilled_at = Some(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
         }
     }
 }
 
+/// Retry policy for store-and-forward delivery
+#[derive(Debug, Clone)]
+pub struct OutboxPolicy {
+    /// Delay after the first failed attempt
+    pub base_backoff: Duration,
+    /// Upper bound on the delay between attempts
+    pub max_backoff: Duration,
+    /// Failed attempts after which a message is dead-lettered
+    pub max_attempts: u32,
+    /// Messages delivered per flush
+    pub batch_size: usize,
+}
+
+impl Default for OutboxPolicy {
+    fn default() -> Self {
+        Self {
+            base_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(30 * 60),
+            max_attempts: 10,
+            batch_size: 50,
+        }
+    }
+}
+
+impl OutboxPolicy {
+    /// Exponential backoff after `attempts` failed deliveries
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.base_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+/// Result of one outbox flush
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlushStats {
+    pub sent: usize,
+    pub requeued: usize,
+    pub dead_lettered: usize,
+}
+
 /// Event message for cloud
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventMessage {
     pub message_type: String,
     pub vehicle_id: String,
+    /// Per-vehicle sequence number, strictly increasing across restarts;
+    /// gaps tell the backend which messages to request again
+    #[serde(default)]
+    pub sequence: u64,
     pub timestamp: DateTime<Utc>,
     pub driver_id: Option<String>,
     pub event: FusedEvent,
@@ -93,19 +265,58 @@ pub struct VideoReferences {
 pub struct CloudSync {
     config: CloudConfig,
     client: Option<AsyncClient>,
-    used_today_mb: AtomicU32,
+    routine_bytes: AtomicU64,
+    critical_bytes: AtomicU64,
+    /// Held across the quota check and the add in [`Self::reserve_upload`]
+    reservations: Mutex<()>,
+    outbox: Option<Arc<dyn Storage>>,
+    outbox_policy: OutboxPolicy,
+    clock: SharedClock,
+    sequence: Arc<SequenceCounter>,
+    rate_limit: Mutex<TokenBucket>,
+    telemetry: Mutex<TelemetrySampler>,
 }
 
 impl CloudSync {
     /// Create new cloud sync manager
     pub fn new(config: CloudConfig) -> Self {
+        let rate_limit = TokenBucket::new(config.messages_per_minute, config.message_burst);
+        let telemetry = TelemetrySampler::new(config.telemetry.clone());
         Self {
             config,
             client: None,
-            used_today_mb: AtomicU32::new(0),
+            routine_bytes: AtomicU64::new(0),
+            critical_bytes: AtomicU64::new(0),
+            reservations: Mutex::new(()),
+            outbox: None,
+            outbox_policy: OutboxPolicy::default(),
+            clock: SystemClock::shared(),
+            sequence: Arc::new(SequenceCounter::in_memory()),
+            rate_limit: Mutex::new(rate_limit),
+            telemetry: Mutex::new(telemetry),
         }
     }
 
+    /// Use `clock` for message timestamps and the upload window
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Number event messages from a persisted counter so numbering
+    /// survives restarts
+    pub fn with_sequence(mut self, counter: Arc<SequenceCounter>) -> Self {
+        self.sequence = counter;
+        self
+    }
+
+    /// Queue messages that can't be sent right away in the storage outbox
+    pub fn with_outbox(mut self, storage: Arc<dyn Storage>, policy: OutboxPolicy) -> Self {
+        self.outbox = Some(storage);
+        self.outbox_policy = policy;
+        self
+    }
+
     /// Connect to MQTT broker
     pub async fn connect(&mut self) -> Result<(), CloudError> {
         let mut options = MqttOptions::new(
@@ -148,14 +359,17 @@ impl CloudSync {
         if !self.should_upload(&event) {
             return Err(CloudError::BandwidthLimit);
         }
-
-        let client = self.client.as_ref()
-            .ok_or_else(|| CloudError::Connection("Not connected".to_string()))?;
+        let priority = if event.severity() == Some(Severity::Critical) {
+            UploadPriority::Critical
+        } else {
+            UploadPriority::Routine
+        };
 
         let message = EventMessage {
             message_type: "event".to_string(),
             vehicle_id: self.config.vehicle_id.clone(),
-            timestamp: Utc::now(),
+            sequence: self.sequence.next().await?,
+            timestamp: DateTime::<Utc>::from(self.clock.now()),
             driver_id,
             event,
             video_references: None,
@@ -165,27 +379,247 @@ impl CloudSync {
             .map_err(|e| CloudError::Serialization(e.to_string()))?;
 
         let topic = format!("vehicles/{}/events", self.config.vehicle_id);
-        
-        client.publish(&topic, QoS::AtLeastOnce, false, payload)
+        self.send_or_queue(topic, payload, priority).await
+    }
+
+    /// Publish a `Normal` heartbeat if one is due for the current risk
+    /// indicators, returning whether it was sent
+    ///
+    /// Call on every telemetry tick; the configured rate bands decide how
+    /// many ticks actually reach the backend.
+    pub async fn publish_heartbeat(
+        &self,
+        indicators: &RiskIndicators,
+        driver_id: Option<String>,
+    ) -> Result<bool, CloudError> {
+        let due = self
+            .telemetry
+            .lock()
+            .map_err(|e| CloudError::Publish(format!("telemetry sampler poisoned: {e}")))?
+            .update(indicators, self.clock.now());
+        if !due {
+            return Ok(false);
+        }
+        self.publish_event(FusedEvent::Normal, driver_id).await?;
+        Ok(true)
+    }
+
+    /// Publish a queued alert to the vehicle's alert topic
+    ///
+    /// Critical alerts may use the reserved quota headroom; others are
+    /// refused with [`CloudError::BandwidthLimit`] once the soft cap is hit.
+    /// Alerts that can't be sent, or routine ones over the message rate,
+    /// are kept in the outbox if one is set.
+    pub async fn publish_alert<T: Serialize>(&self, alert: &QueuedAlert<T>) -> Result<(), CloudError> {
+        let priority = if alert.severity == Severity::Critical {
+            UploadPriority::Critical
+        } else {
+            let usage = self.quota_usage();
+            if usage.used_bytes() >= usage.soft_cap_bytes {
+                return Err(CloudError::BandwidthLimit);
+            }
+            UploadPriority::Routine
+        };
+
+        let payload = serde_json::to_vec(alert)
+            .map_err(|e| CloudError::Serialization(e.to_string()))?;
+        let topic = format!("vehicles/{}/alerts", self.config.vehicle_id);
+        self.send_or_queue(topic, payload, priority).await
+    }
+
+    /// Publish now, or keep the message in the outbox for a later flush
+    ///
+    /// Routine messages over the configured rate are deferred without
+    /// being sent; critical ones are never rate limited. Without an
+    /// outbox, the failure is returned instead.
+    async fn send_or_queue(
+        &self,
+        topic: String,
+        payload: Vec<u8>,
+        priority: UploadPriority,
+    ) -> Result<(), CloudError> {
+        let result = if priority == UploadPriority::Routine && !self.take_rate_token()? {
+            Err(CloudError::RateLimited)
+        } else {
+            self.publish_raw(&topic, payload.clone(), priority).await
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) => match &self.outbox {
+                // Store-and-forward: keep the message for a later flush
+                Some(outbox) => {
+                    warn!("Publish to {} deferred ({}), queueing in outbox", topic, e);
+                    let message = OutboxMessage::new(topic, payload)
+                        .with_critical(priority == UploadPriority::Critical);
+                    outbox.enqueue_outbox(message).await?;
+                    Ok(())
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    fn take_rate_token(&self) -> Result<bool, CloudError> {
+        let mut bucket = self
+            .rate_limit
+            .lock()
+            .map_err(|e| CloudError::Publish(format!("rate limiter poisoned: {e}")))?;
+        Ok(bucket.try_take(self.clock.now()))
+    }
+
+    /// Upload alerts from `queue` as they arrive, most severe first
+    ///
+    /// Runs until the task is cancelled; without an outbox, alerts that fail
+    /// to publish are logged and dropped, as the queue has already
+    /// coalesced them.
+    pub async fn run_alert_uploader<T: Serialize>(&self, queue: &AlertQueue<T>) {
+        loop {
+            let alert = queue.next().await;
+            if let Err(e) = self.publish_alert(&alert).await {
+                warn!("Dropping {:?} alert {}: {}", alert.source, alert.key, e);
+            }
+        }
+    }
+
+    /// Deliver due outbox messages, backing off or dead-lettering failures
+    ///
+    /// Messages go out at the priority they were queued with, so critical
+    /// ones skip the message rate limit and may use the reserved quota.
+    pub async fn flush_outbox(&self) -> Result<FlushStats, CloudError> {
+        let mut stats = FlushStats::default();
+        let Some(outbox) = &self.outbox else {
+            return Ok(stats);
+        };
+
+        // Being offline is not the message's fault; don't burn attempts
+        if self.client.is_none() {
+            return Err(CloudError::Connection("Not connected".to_string()));
+        }
+
+        let mut throttled = false;
+        for message in outbox.peek_outbox(self.outbox_policy.batch_size).await? {
+            let priority = if message.critical {
+                UploadPriority::Critical
+            } else {
+                UploadPriority::Routine
+            };
+            // Deferred routine traffic drains at the same rate; the rest waits
+            if priority == UploadPriority::Routine && (throttled || !self.take_rate_token()?) {
+                if !throttled {
+                    debug!("Outbox flush of routine messages paused by message rate limit");
+                    throttled = true;
+                }
+                continue;
+            }
+            match self
+                .publish_raw(&message.topic, message.payload.clone(), priority)
+                .await
+            {
+                Ok(()) => {
+                    outbox.ack_outbox(message.id).await?;
+                    stats.sent += 1;
+                }
+                Err(e) if message.attempts + 1 >= self.outbox_policy.max_attempts => {
+                    error!(
+                        "Outbox message {} failed {} times, dead-lettering: {}",
+                        message.id,
+                        message.attempts + 1,
+                        e
+                    );
+                    outbox.requeue_outbox(message.id, Duration::ZERO).await?;
+                    outbox.dead_letter_outbox(message.id).await?;
+                    stats.dead_lettered += 1;
+                }
+                Err(e) => {
+                    let backoff = self.outbox_policy.backoff(message.attempts + 1);
+                    debug!("Outbox message {} failed ({}), retry in {:?}", message.id, e, backoff);
+                    outbox.requeue_outbox(message.id, backoff).await?;
+                    stats.requeued += 1;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Publish a serialized payload
+    async fn publish_raw(
+        &self,
+        topic: &str,
+        payload: Vec<u8>,
+        priority: UploadPriority,
+    ) -> Result<(), CloudError> {
+        let client = self.client.as_ref()
+            .ok_or_else(|| CloudError::Connection("Not connected".to_string()))?;
+
+        let size = payload.len() as u64;
+        client.publish(topic, QoS::AtLeastOnce, false, payload)
             .await
             .map_err(|e| CloudError::Publish(e.to_string()))?;
 
         // Track bandwidth usage
-        self.used_today_mb.fetch_add(1, Ordering::Relaxed); // Approximate
+        self.counter(priority).fetch_add(size, Ordering::Relaxed);
 
         Ok(())
     }
 
+    fn counter(&self, priority: UploadPriority) -> &AtomicU64 {
+        match priority {
+            UploadPriority::Routine => &self.routine_bytes,
+            UploadPriority::Critical => &self.critical_bytes,
+        }
+    }
+
+    /// Current usage against the daily quota
+    pub fn quota_usage(&self) -> QuotaUsage {
+        let quota_bytes = u64::from(self.config.daily_quota_mb) * 1024 * 1024;
+        let headroom = f64::from(self.config.reserved_headroom.clamp(0.0, 1.0));
+        QuotaUsage {
+            routine_bytes: self.routine_bytes.load(Ordering::Relaxed),
+            critical_bytes: self.critical_bytes.load(Ordering::Relaxed),
+            soft_cap_bytes: (quota_bytes as f64 * (1.0 - headroom)).round() as u64,
+            quota_bytes,
+        }
+    }
+
+    /// Claim quota for an upload of `size_bytes`, such as event video
+    ///
+    /// Routine uploads must fit under the soft cap; critical ones may use
+    /// the reserved headroom up to the full quota. On success the bytes are
+    /// counted as used.
+    pub fn reserve_upload(&self, size_bytes: u64, priority: UploadPriority) -> Result<(), CloudError> {
+        // Concurrent reservations must not both pass the check
+        let _reserving = self.reservations.lock().unwrap_or_else(|e| e.into_inner());
+        let usage = self.quota_usage();
+        let limit = match priority {
+            UploadPriority::Routine => usage.soft_cap_bytes,
+            UploadPriority::Critical => usage.quota_bytes,
+        };
+        if usage.used_bytes() + size_bytes > limit {
+            debug!(
+                "{:?} upload of {} bytes refused: {} of {} bytes used",
+                priority,
+                size_bytes,
+                usage.used_bytes(),
+                limit
+            );
+            return Err(CloudError::BandwidthLimit);
+        }
+        self.counter(priority).fetch_add(size_bytes, Ordering::Relaxed);
+        Ok(())
+    }
+
     /// Check if event should be uploaded
     fn should_upload(&self, event: &FusedEvent) -> bool {
-        // Critical events bypass quota
+        // Crashes bypass quota
         if matches!(event, FusedEvent::Crash { .. }) {
             return true;
         }
 
-        // Check quota
-        let used = self.used_today_mb.load(Ordering::Relaxed);
-        if used >= self.config.daily_quota_mb {
+        // Routine traffic stops at the soft cap, leaving the reserve
+        let usage = self.quota_usage();
+        if usage.used_bytes() >= usage.soft_cap_bytes {
             return false;
         }
 
@@ -199,12 +633,255 @@ impl CloudSync {
     }
 
     fn is_nightly_window(&self) -> bool {
-        let hour = Utc::now().format("%H").to_string().parse::<u32>().unwrap_or(12);
-        hour >= 2 && hour <= 6
+        let local = DateTime::<Utc>::from(self.clock.now())
+            .with_timezone(&self.config.utc_offset)
+            .time();
+        in_daily_window(local, self.config.nightly_start, self.config.nightly_end)
     }
 
     /// Reset daily quota (call at midnight)
     pub fn reset_daily_quota(&self) {
-        self.used_today_mb.store(0, Ordering::Relaxed);
+        self.routine_bytes.store(0, Ordering::Relaxed);
+        self.critical_bytes.store(0, Ordering::Relaxed);
+    }
+
+    /// Persist where message numbering stopped, so the next start
+    /// continues without a gap (call on a clean shutdown)
+    pub async fn shutdown(&self) -> Result<(), CloudError> {
+        self.sequence.close().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_types::MockClock;
+    use storage::Repository;
+
+    #[test]
+    fn test_outbox_backoff_is_exponential_and_capped() {
+        let policy = OutboxPolicy {
+            base_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(60),
+            ..Default::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(5));
+        assert_eq!(policy.backoff(2), Duration::from_secs(10));
+        assert_eq!(policy.backoff(4), Duration::from_secs(40));
+        assert_eq!(policy.backoff(5), Duration::from_secs(60));
+        assert_eq!(policy.backoff(40), Duration::from_secs(60));
+    }
+
+    fn sync_at(config: CloudConfig, utc: &str) -> CloudSync {
+        let wall: DateTime<Utc> = utc.parse().unwrap();
+        CloudSync::new(config).with_clock(MockClock::at(wall.into()).shared())
+    }
+
+    fn hm(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_nightly_window_is_half_open() {
+        let config = CloudConfig::default();
+        assert!(sync_at(config.clone(), "2024-03-01T02:00:00Z").is_nightly_window());
+        assert!(sync_at(config.clone(), "2024-03-01T05:59:59Z").is_nightly_window());
+        assert!(!sync_at(config.clone(), "2024-03-01T06:00:00Z").is_nightly_window());
+        assert!(!sync_at(config, "2024-03-01T06:30:00Z").is_nightly_window());
+    }
+
+    #[test]
+    fn test_nightly_window_crossing_midnight() {
+        let config = CloudConfig {
+            nightly_start: hm(22, 0),
+            nightly_end: hm(4, 0),
+            ..Default::default()
+        };
+        assert!(sync_at(config.clone(), "2024-03-01T23:15:00Z").is_nightly_window());
+        assert!(sync_at(config.clone(), "2024-03-01T00:00:00Z").is_nightly_window());
+        assert!(sync_at(config.clone(), "2024-03-01T03:59:00Z").is_nightly_window());
+        assert!(!sync_at(config.clone(), "2024-03-01T04:00:00Z").is_nightly_window());
+        assert!(!sync_at(config, "2024-03-01T12:00:00Z").is_nightly_window());
+    }
+
+    #[test]
+    fn test_nightly_window_uses_local_offset() {
+        // UTC+5:30: the 02:00-06:00 local window is 20:30-00:30 UTC
+        let config = CloudConfig {
+            utc_offset: FixedOffset::east_opt(5 * 3600 + 1800).unwrap(),
+            ..Default::default()
+        };
+        assert!(sync_at(config.clone(), "2024-03-01T20:30:00Z").is_nightly_window());
+        assert!(sync_at(config.clone(), "2024-03-01T23:00:00Z").is_nightly_window());
+        assert!(!sync_at(config.clone(), "2024-03-02T00:30:00Z").is_nightly_window());
+        // 03:00 UTC is 08:30 local
+        assert!(!sync_at(config, "2024-03-02T03:00:00Z").is_nightly_window());
+    }
+
+    #[test]
+    fn test_reserved_headroom_kept_for_critical_uploads() {
+        const MB: u64 = 1024 * 1024;
+        let sync = CloudSync::new(CloudConfig {
+            daily_quota_mb: 100,
+            reserved_headroom: 0.1,
+            schedule: UploadSchedule::Immediate,
+            ..Default::default()
+        });
+
+        // Routine telemetry fills up to the 90 MB soft cap
+        for _ in 0..9 {
+            sync.reserve_upload(10 * MB, UploadPriority::Routine).unwrap();
+        }
+        assert!(matches!(
+            sync.reserve_upload(MB, UploadPriority::Routine),
+            Err(CloudError::BandwidthLimit)
+        ));
+        assert!(!sync.should_upload(&FusedEvent::Normal));
+        // Only crashes skip the soft cap, not every critical event
+        assert!(!sync.should_upload(&FusedEvent::HardBraking {
+            severity: Severity::Critical,
+            decel_g: 0.9,
+            speed_before_kmh: 80.0,
+        }));
+        assert!(sync.should_upload(&FusedEvent::Crash {
+            severity: Severity::Critical,
+            g_force: 4.0,
+            airbag_deployed: true,
+        }));
+
+        // Crash video still fits in the reserve
+        sync.reserve_upload(8 * MB, UploadPriority::Critical).unwrap();
+        let usage = sync.quota_usage();
+        assert_eq!(usage.routine_bytes, 90 * MB);
+        assert_eq!(usage.critical_bytes, 8 * MB);
+        assert_eq!(usage.reserve_remaining_bytes(), 2 * MB);
+
+        // ...but not past the full quota
+        assert!(sync.reserve_upload(3 * MB, UploadPriority::Critical).is_err());
+
+        sync.reset_daily_quota();
+        assert!(sync.reserve_upload(MB, UploadPriority::Routine).is_ok());
+    }
+
+    #[test]
+    fn test_concurrent_reservations_stay_under_the_cap() {
+        const MB: u64 = 1024 * 1024;
+        let sync = CloudSync::new(CloudConfig {
+            daily_quota_mb: 100,
+            reserved_headroom: 0.1,
+            ..Default::default()
+        });
+
+        let granted = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..50)
+                            .filter(|_| sync.reserve_upload(MB, UploadPriority::Routine).is_ok())
+                            .count()
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).sum::<usize>()
+        });
+
+        assert_eq!(granted, 90);
+        assert_eq!(sync.quota_usage().routine_bytes, 90 * MB);
+    }
+
+    #[tokio::test]
+    async fn test_offline_publish_is_queued() {
+        let repo = Arc::new(Repository::new());
+        let sync = CloudSync::new(CloudConfig {
+            schedule: UploadSchedule::Immediate,
+            ..Default::default()
+        })
+        .with_outbox(repo.clone(), OutboxPolicy::default());
+
+        sync.publish_event(FusedEvent::Normal, None).await.unwrap();
+        assert_eq!(repo.outbox_len().await.unwrap(), 1);
+        let queued: EventMessage =
+            serde_json::from_slice(&repo.peek_outbox(1).await.unwrap()[0].payload).unwrap();
+        assert_eq!(queued.sequence, 1);
+
+        // Still offline: flush fails without consuming an attempt
+        assert!(matches!(sync.flush_outbox().await, Err(CloudError::Connection(_))));
+        assert_eq!(repo.peek_outbox(10).await.unwrap()[0].attempts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_excess_messages_deferred_to_outbox() {
+        let repo = Arc::new(Repository::new());
+        let clock = MockClock::at_unix_ms(1_700_000_000_000);
+        let mut sync = CloudSync::new(CloudConfig {
+            schedule: UploadSchedule::Immediate,
+            messages_per_minute: 6,
+            message_burst: 3,
+            ..Default::default()
+        })
+        .with_clock(clock.shared())
+        .with_outbox(repo.clone(), OutboxPolicy::default());
+        // Requests queue in the client without a broker while the event loop is held
+        let (client, _eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 10);
+        sync.client = Some(client);
+
+        // Five routine events at once: the burst goes out, the rest waits
+        for _ in 0..5 {
+            sync.publish_event(FusedEvent::Normal, None).await.unwrap();
+        }
+        assert_eq!(repo.outbox_len().await.unwrap(), 2);
+        let sent_bytes = sync.quota_usage().routine_bytes;
+        assert!(sent_bytes > 0);
+
+        // Out of tokens: a sixth is deferred too, without touching the quota
+        sync.publish_event(FusedEvent::Normal, None).await.unwrap();
+        assert_eq!(repo.outbox_len().await.unwrap(), 3);
+        assert_eq!(sync.quota_usage().routine_bytes, sent_bytes);
+
+        // 6/min refills one token every 10 s; the flush drains just that one
+        clock.advance(Duration::from_secs(10));
+        let stats = sync.flush_outbox().await.unwrap();
+        assert_eq!(stats.sent, 1);
+        assert_eq!(repo.outbox_len().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_queued_critical_message_keeps_its_priority() {
+        let repo = Arc::new(Repository::new());
+        let clock = MockClock::at_unix_ms(1_700_000_000_000);
+        let mut sync = CloudSync::new(CloudConfig {
+            schedule: UploadSchedule::Immediate,
+            messages_per_minute: 6,
+            message_burst: 1,
+            ..Default::default()
+        })
+        .with_clock(clock.shared())
+        .with_outbox(repo.clone(), OutboxPolicy::default());
+
+        // Offline: both are queued, and the routine one spends the only token
+        sync.publish_event(FusedEvent::Normal, None).await.unwrap();
+        let crash = FusedEvent::Crash {
+            severity: Severity::Critical,
+            g_force: 4.0,
+            airbag_deployed: true,
+        };
+        sync.publish_event(crash, None).await.unwrap();
+        let queued = repo.peek_outbox(10).await.unwrap();
+        assert_eq!(queued.iter().map(|m| m.critical).collect::<Vec<_>>(), [false, true]);
+
+        let (client, _eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 10);
+        sync.client = Some(client);
+
+        // The crash goes out past the throttled routine message, as critical
+        let stats = sync.flush_outbox().await.unwrap();
+        assert_eq!(stats.sent, 1);
+        let remaining = repo.peek_outbox(10).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert!(!remaining[0].critical);
+        let usage = sync.quota_usage();
+        assert!(usage.critical_bytes > 0);
+        assert_eq!(usage.routine_bytes, 0);
     }
 }
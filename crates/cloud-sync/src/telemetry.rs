@@ -0,0 +1,209 @@
+//! Adaptive Telemetry Sampling
+//!
+//! Backends want a periodic `Normal` heartbeat even when nothing happens,
+//! but at full rate it is mostly wasted quota. The heartbeat interval is
+//! chosen from rate bands instead: slow while every risk indicator is
+//! nominal, faster once one enters its elevated band, so an alert that
+//! fires later arrives with the minutes of context that led up to it.
+
+use std::time::{Duration, SystemTime};
+
+/// How risky current driving looks, from the telemetry indicators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskBand {
+    Nominal,
+    Elevated,
+    High,
+}
+
+/// Risk indicators sampled alongside the heartbeat
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RiskIndicators {
+    /// Current look-away duration from the DMS (ms)
+    pub distraction_ms: u64,
+    /// Coolant temperature (°C), if reported
+    pub coolant_temp_c: Option<f32>,
+}
+
+/// Heartbeat rate bands
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetrySampling {
+    /// Heartbeat interval while every indicator is nominal
+    pub nominal_interval: Duration,
+    /// Interval while any indicator is elevated
+    pub elevated_interval: Duration,
+    /// Interval while any indicator is high
+    pub high_interval: Duration,
+    /// Look-away time (ms) entering the elevated and high bands
+    pub distraction_bands_ms: (u64, u64),
+    /// Coolant temperature (°C) entering the elevated and high bands
+    pub coolant_bands_c: (f32, f32),
+    /// Coolant warming faster than this (°C/min) is elevated even below
+    /// the temperature bands
+    pub coolant_rise_c_per_min: f32,
+}
+
+impl Default for TelemetrySampling {
+    fn default() -> Self {
+        Self {
+            nominal_interval: Duration::from_secs(60),
+            elevated_interval: Duration::from_secs(10),
+            high_interval: Duration::from_secs(2),
+            // DMS alerts at 3 s of looking away
+            distraction_bands_ms: (1_000, 2_000),
+            // Rule-based fallback warns at 100 °C
+            coolant_bands_c: (95.0, 100.0),
+            coolant_rise_c_per_min: 3.0,
+        }
+    }
+}
+
+impl TelemetrySampling {
+    /// Heartbeat interval for `band`
+    pub fn interval(&self, band: RiskBand) -> Duration {
+        match band {
+            RiskBand::Nominal => self.nominal_interval,
+            RiskBand::Elevated => self.elevated_interval,
+            RiskBand::High => self.high_interval,
+        }
+    }
+}
+
+/// Decides when the next `Normal` heartbeat is due
+#[derive(Debug)]
+pub struct TelemetrySampler {
+    config: TelemetrySampling,
+    last_sent: Option<SystemTime>,
+    /// Previous coolant reading, for the warming rate
+    last_coolant: Option<(SystemTime, f32)>,
+    band: RiskBand,
+}
+
+impl TelemetrySampler {
+    pub fn new(config: TelemetrySampling) -> Self {
+        Self {
+            config,
+            last_sent: None,
+            last_coolant: None,
+            band: RiskBand::Nominal,
+        }
+    }
+
+    /// Band of the last update
+    pub fn band(&self) -> RiskBand {
+        self.band
+    }
+
+    /// Feed the current indicators; `true` when a heartbeat is due now
+    ///
+    /// The first call is always due. A rise in band takes effect at once,
+    /// so the interval shortens from the last heartbeat rather than after
+    /// the slower one runs out.
+    pub fn update(&mut self, indicators: &RiskIndicators, now: SystemTime) -> bool {
+        self.band = self.classify(indicators, now);
+        let due = self.last_sent.is_none_or(|last| {
+            now.duration_since(last).unwrap_or_default() >= self.config.interval(self.band)
+        });
+        if due {
+            self.last_sent = Some(now);
+        }
+        due
+    }
+
+    fn classify(&mut self, indicators: &RiskIndicators, now: SystemTime) -> RiskBand {
+        let (distraction_elevated, distraction_high) = self.config.distraction_bands_ms;
+        let distraction = if indicators.distraction_ms >= distraction_high {
+            RiskBand::High
+        } else if indicators.distraction_ms >= distraction_elevated {
+            RiskBand::Elevated
+        } else {
+            RiskBand::Nominal
+        };
+
+        let coolant = match indicators.coolant_temp_c {
+            Some(temp) => {
+                let (elevated, high) = self.config.coolant_bands_c;
+                let rising = self.last_coolant.is_some_and(|(at, previous)| {
+                    let minutes = now.duration_since(at).unwrap_or_default().as_secs_f32() / 60.0;
+                    minutes > 0.0 && (temp - previous) / minutes >= self.config.coolant_rise_c_per_min
+                });
+                // Rate over whole readings, not sub-second jitter
+                let next_reading = self.last_coolant.is_none_or(|(at, _)| {
+                    now.duration_since(at).unwrap_or_default() >= Duration::from_secs(1)
+                });
+                if next_reading {
+                    self.last_coolant = Some((now, temp));
+                }
+                if temp >= high {
+                    RiskBand::High
+                } else if temp >= elevated || rising {
+                    RiskBand::Elevated
+                } else {
+                    RiskBand::Nominal
+                }
+            }
+            None => RiskBand::Nominal,
+        };
+
+        distraction.max(coolant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_types::{Clock, MockClock};
+
+    /// Heartbeats sent over `span` when updated once a second
+    fn heartbeats(
+        sampler: &mut TelemetrySampler,
+        clock: &MockClock,
+        span: Duration,
+        indicators: impl Fn(u64) -> RiskIndicators,
+    ) -> usize {
+        (0..span.as_secs())
+            .filter(|&s| {
+                clock.advance(Duration::from_secs(1));
+                sampler.update(&indicators(s), clock.now())
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_heartbeat_rate_rises_with_risk_band() {
+        let clock = MockClock::default();
+        let mut sampler = TelemetrySampler::new(TelemetrySampling::default());
+        let two_minutes = Duration::from_secs(120);
+        let steady = |_| RiskIndicators {
+            distraction_ms: 200,
+            coolant_temp_c: Some(88.0),
+        };
+
+        // Nominal: the first update plus one a minute
+        assert_eq!(heartbeats(&mut sampler, &clock, two_minutes, steady), 2);
+        assert_eq!(sampler.band(), RiskBand::Nominal);
+
+        // Moderate distraction, well before the DMS alert
+        let distracted = |_| RiskIndicators {
+            distraction_ms: 1_200,
+            coolant_temp_c: Some(88.0),
+        };
+        assert_eq!(heartbeats(&mut sampler, &clock, two_minutes, distracted), 12);
+        assert_eq!(sampler.band(), RiskBand::Elevated);
+
+        // Coolant climbing 6 °C/min while still below the bands
+        let warming = |s| RiskIndicators {
+            distraction_ms: 0,
+            coolant_temp_c: Some(80.0 + s as f32 / 10.0),
+        };
+        assert_eq!(heartbeats(&mut sampler, &clock, Duration::from_secs(60), warming), 6);
+        assert_eq!(sampler.band(), RiskBand::Elevated);
+
+        let overheating = |_| RiskIndicators {
+            distraction_ms: 0,
+            coolant_temp_c: Some(101.0),
+        };
+        assert_eq!(heartbeats(&mut sampler, &clock, Duration::from_secs(20), overheating), 10);
+        assert_eq!(sampler.band(), RiskBand::High);
+    }
+}
@@ -0,0 +1,145 @@
+//! Checksummed file framing
+//!
+//! Snapshots live on embedded flash where a power cut can leave a file
+//! half-written. Each payload is framed as
+//!
+//! ```text
+//! magic [4] | payload length u64 LE | CRC32 u32 LE | payload
+//! ```
+//!
+//! and written to a temporary file that is renamed over the target, so a
+//! reader sees either the old file, the complete new one, or something
+//! [`read_verified`] rejects.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use thiserror::Error;
+
+/// Bytes before the payload
+pub const HEADER_LEN: usize = 4 + 8 + 4;
+
+/// Framed file could not be read back intact
+#[derive(Error, Debug)]
+pub enum IntegrityError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("unexpected file magic {found:?}, expected {expected:?}")]
+    BadMagic { expected: [u8; 4], found: Vec<u8> },
+
+    #[error("truncated: expected {expected} bytes, found {actual}")]
+    Truncated { expected: u64, actual: u64 },
+
+    #[error("checksum mismatch: header {expected:#010x}, payload {actual:#010x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+}
+
+impl IntegrityError {
+    /// Whether the file exists but its contents are damaged
+    pub fn is_corruption(&self) -> bool {
+        !matches!(self, IntegrityError::Io(_))
+    }
+}
+
+/// Prefix `payload` with the integrity header
+pub fn encode(magic: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(magic);
+    out.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    out.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Check the header and return the payload
+pub fn decode<'a>(magic: &[u8; 4], bytes: &'a [u8]) -> Result<&'a [u8], IntegrityError> {
+    if bytes.len() >= 4 && &bytes[..4] != magic {
+        return Err(IntegrityError::BadMagic {
+            expected: *magic,
+            found: bytes[..4].to_vec(),
+        });
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(IntegrityError::Truncated {
+            expected: HEADER_LEN as u64,
+            actual: bytes.len() as u64,
+        });
+    }
+
+    let len = u64::from_le_bytes(bytes[4..12].try_into().expect("8-byte slice"));
+    let crc = u32::from_le_bytes(bytes[12..16].try_into().expect("4-byte slice"));
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() as u64 != len {
+        return Err(IntegrityError::Truncated {
+            expected: len,
+            actual: payload.len() as u64,
+        });
+    }
+
+    let actual = crc32fast::hash(payload);
+    if actual != crc {
+        return Err(IntegrityError::ChecksumMismatch { expected: crc, actual });
+    }
+    Ok(payload)
+}
+
+/// Frame `payload` and atomically replace `path` with it
+pub fn write_atomic(path: impl AsRef<Path>, magic: &[u8; 4], payload: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(&encode(magic, payload))?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+/// Read a file written by [`write_atomic`] and verify it
+pub fn read_verified(path: impl AsRef<Path>, magic: &[u8; 4]) -> Result<Vec<u8>, IntegrityError> {
+    let bytes = fs::read(path)?;
+    decode(magic, &bytes).map(<[u8]>::to_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAGIC: &[u8; 4] = b"TEST";
+
+    #[test]
+    fn test_round_trip_and_damage() {
+        let framed = encode(MAGIC, b"hello flash");
+        assert_eq!(decode(MAGIC, &framed).unwrap(), b"hello flash");
+
+        let err = decode(MAGIC, &framed[..framed.len() - 3]).unwrap_err();
+        assert!(matches!(err, IntegrityError::Truncated { expected: 11, actual: 8 }));
+        assert!(err.is_corruption());
+
+        let mut flipped = framed.clone();
+        *flipped.last_mut().unwrap() ^= 0x01;
+        assert!(matches!(decode(MAGIC, &flipped), Err(IntegrityError::ChecksumMismatch { .. })));
+
+        assert!(matches!(decode(b"ELSE", &framed), Err(IntegrityError::BadMagic { .. })));
+    }
+
+    #[test]
+    fn test_truncated_file_detected() {
+        let path = std::env::temp_dir().join(format!("integrity-{}.bin", std::process::id()));
+        write_atomic(&path, MAGIC, &[7u8; 256]).unwrap();
+        assert_eq!(read_verified(&path, MAGIC).unwrap(), vec![7u8; 256]);
+
+        // Simulate a write cut short by power loss
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..100]).unwrap();
+        let err = read_verified(&path, MAGIC).unwrap_err();
+        assert!(err.is_corruption(), "{err}");
+
+        fs::remove_file(&path).unwrap();
+        assert!(!read_verified(&path, MAGIC).unwrap_err().is_corruption());
+    }
+}
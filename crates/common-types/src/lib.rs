@@ -0,0 +1,18 @@
+//! Shared Pipeline Types
+//!
+//! Small value types used by more than one subsystem, kept here so the
+//! crates agree on a single definition instead of converting between
+//! lookalikes, plus the checksummed file framing used for on-device
+//! persistence, the injectable clock used by time-dependent logic, the
+//! scaling shared by the sensor frame types and the seed for mock data.
+
+pub mod clock;
+pub mod integrity;
+mod mock;
+mod severity;
+pub mod units;
+
+pub use clock::{Clock, MockClock, SharedClock, SystemClock};
+pub use integrity::IntegrityError;
+pub use mock::MockConfig;
+pub use severity::{ParseSeverityError, Severity};
@@ -0,0 +1,33 @@
+//! Seeded Mock Data
+//!
+//! Mock OBD clients and vision models without a loaded model draw from a
+//! PRNG seeded here, so pipeline tests get varied but reproducible data.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+/// Mock mode configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MockConfig {
+    /// PRNG seed; the same seed reproduces the same mock sequence
+    pub seed: u64,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self { seed: 0x0BD2_2024 }
+    }
+}
+
+impl MockConfig {
+    /// Create a mock configuration with the given seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Build the PRNG for this seed
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
+}
@@ -0,0 +1,97 @@
+//! Alert and Event Severity
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Severity of an alert, fault or fused event, ordered `Low < Critical`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Severity {
+    /// Log only
+    Low,
+    /// Dashboard alert
+    Medium,
+    /// Audible warning
+    High,
+    /// Immediate attention
+    Critical,
+}
+
+/// String did not name a severity level
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown severity: {0}")]
+pub struct ParseSeverityError(pub String);
+
+impl Severity {
+    /// All levels, lowest first
+    pub const ALL: [Severity; 4] = [
+        Severity::Low,
+        Severity::Medium,
+        Severity::High,
+        Severity::Critical,
+    ];
+
+    /// Lowercase name as stored in the database and used by the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = ParseSeverityError;
+
+    /// Parse a level name, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Severity::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParseSeverityError(s.to_string()))
+    }
+}
+
+impl From<Severity> for &'static str {
+    fn from(severity: Severity) -> Self {
+        severity.as_str()
+    }
+}
+
+impl From<Severity> for String {
+    fn from(severity: Severity) -> Self {
+        severity.as_str().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_round_trip() {
+        for level in Severity::ALL {
+            assert_eq!(level.as_str().parse::<Severity>(), Ok(level));
+            assert_eq!(String::from(level), level.to_string());
+        }
+        assert_eq!("CRITICAL".parse::<Severity>(), Ok(Severity::Critical));
+        assert!("severe".parse::<Severity>().is_err());
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(Severity::Critical > Severity::High);
+        assert!(Severity::High > Severity::Medium);
+        assert!(Severity::Medium > Severity::Low);
+        assert_eq!(Severity::ALL.iter().max(), Some(&Severity::Critical));
+    }
+}
@@ -0,0 +1,42 @@
+//! Sensor Frame Scaling
+//!
+//! Sensor frames store fractional readings as scaled integers to stay
+//! compact. The scale factors live here so the OBD decoder that writes
+//! them and every frame type that reads them back agree.
+
+/// Stored MAF units per g/s
+pub const MAF_SCALE: f64 = 100.0;
+
+/// Stored fuel trim units per percent
+pub const FUEL_TRIM_SCALE: f64 = 100.0;
+
+/// Stored O2 sensor voltage units per volt (mV)
+pub const O2_VOLTAGE_SCALE: f64 = 1000.0;
+
+/// Mass air flow rate (g/s) from its stored value
+pub fn maf_g_s(stored: u16) -> f64 {
+    stored as f64 / MAF_SCALE
+}
+
+/// Fuel trim (%) from its stored value
+pub fn fuel_trim_pct(stored: i16) -> f64 {
+    stored as f64 / FUEL_TRIM_SCALE
+}
+
+/// Oxygen sensor voltage (V) from its stored value
+pub fn o2_voltage_v(stored: u16) -> f64 {
+    stored as f64 / O2_VOLTAGE_SCALE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_values_scale_back() {
+        assert!((maf_g_s(1234) - 12.34).abs() < 1e-9);
+        assert!((fuel_trim_pct(-525) + 5.25).abs() < 1e-9);
+        assert!((o2_voltage_v(450) - 0.45).abs() < 1e-9);
+        assert_eq!(maf_g_s((12.5 * MAF_SCALE) as u16), 12.5);
+    }
+}
@@ -9,5 +9,5 @@ mod validator;
 
 pub use error::ValidationError;
 pub use filter::MedianFilter;
-pub use normalizer::{Normalizer, NormalizationMethod};
+pub use normalizer::{AdaptiveAlpha, Normalizer, NormalizationMethod};
 pub use validator::{Validator, ValidationConfig, ValidationResult};
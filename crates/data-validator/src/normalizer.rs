@@ -13,6 +13,33 @@ pub enum NormalizationMethod {
     None,
 }
 
+/// Bounds and responsiveness for an error-driven smoothing factor
+///
+/// The effective alpha follows the Trigg-Leach tracking signal: the ratio
+/// of the smoothed prediction error to the smoothed absolute error. Noise
+/// around a steady level cancels out and drives alpha toward `min_alpha`;
+/// after a step every error has the same sign, the ratio approaches one
+/// and alpha rises toward `max_alpha` until the mean has caught up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveAlpha {
+    /// Smallest alpha, used while the signal is stable
+    pub min_alpha: f64,
+    /// Largest alpha, reached during a sustained regime change
+    pub max_alpha: f64,
+    /// Smoothing applied to the prediction error (0-1)
+    pub error_smoothing: f64,
+}
+
+impl Default for AdaptiveAlpha {
+    fn default() -> Self {
+        Self {
+            min_alpha: 0.02,
+            max_alpha: 0.5,
+            error_smoothing: 0.1,
+        }
+    }
+}
+
 /// Normalizer using Exponentially Weighted Moving Average
 pub struct Normalizer {
     /// Current mean estimate
@@ -21,6 +48,14 @@ pub struct Normalizer {
     variance: f64,
     /// EWMA smoothing factor (0-1, higher = more weight on recent)
     alpha: f64,
+    /// Alpha the normalizer was created with, restored on reset
+    base_alpha: f64,
+    /// Error-driven alpha bounds, if adaptive
+    adaptive: Option<AdaptiveAlpha>,
+    /// Smoothed signed prediction error
+    smoothed_error: f64,
+    /// Smoothed absolute prediction error
+    smoothed_abs_error: f64,
     /// Whether initialized with first value
     initialized: bool,
     /// Method to use
@@ -34,10 +69,15 @@ pub struct Normalizer {
 impl Normalizer {
     /// Create a new normalizer
     pub fn new(method: NormalizationMethod, alpha: f64) -> Self {
+        let alpha = alpha.clamp(0.0, 1.0);
         Self {
             mean: 0.0,
             variance: 1.0,
-            alpha: alpha.clamp(0.0, 1.0),
+            alpha,
+            base_alpha: alpha,
+            adaptive: None,
+            smoothed_error: 0.0,
+            smoothed_abs_error: 0.0,
             initialized: false,
             method,
             min: f64::MAX,
@@ -45,6 +85,20 @@ impl Normalizer {
         }
     }
 
+    /// Let alpha adapt to the prediction error within `config`'s bounds
+    pub fn with_adaptive_alpha(mut self, config: AdaptiveAlpha) -> Self {
+        let min_alpha = config.min_alpha.clamp(0.0, 1.0);
+        let config = AdaptiveAlpha {
+            min_alpha,
+            max_alpha: config.max_alpha.clamp(min_alpha, 1.0),
+            error_smoothing: config.error_smoothing.clamp(0.0, 1.0),
+        };
+        self.base_alpha = self.base_alpha.clamp(config.min_alpha, config.max_alpha);
+        self.alpha = self.base_alpha;
+        self.adaptive = Some(config);
+        self
+    }
+
     /// Normalize a value and update statistics
     pub fn normalize(&mut self, value: f64) -> f64 {
         // Update min/max
@@ -60,6 +114,7 @@ impl Normalizer {
 
         // Update EWMA mean
         let delta = value - self.mean;
+        self.adapt_alpha(delta);
         self.mean += self.alpha * delta;
 
         // Update EWMA variance
@@ -78,6 +133,28 @@ impl Normalizer {
         }
     }
 
+    /// Retune alpha from the error between `value` and the current mean
+    fn adapt_alpha(&mut self, error: f64) {
+        let Some(config) = self.adaptive else {
+            return;
+        };
+        let beta = config.error_smoothing;
+        self.smoothed_error = beta * error + (1.0 - beta) * self.smoothed_error;
+        self.smoothed_abs_error = beta * error.abs() + (1.0 - beta) * self.smoothed_abs_error;
+
+        let tracking = if self.smoothed_abs_error > f64::EPSILON {
+            (self.smoothed_error / self.smoothed_abs_error).abs()
+        } else {
+            0.0
+        };
+        self.alpha = tracking.clamp(config.min_alpha, config.max_alpha);
+    }
+
+    /// Smoothing factor applied to the latest value
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
     /// Get current mean
     pub fn mean(&self) -> f64 {
         self.mean
@@ -93,6 +170,9 @@ impl Normalizer {
         self.mean = 0.0;
         self.variance = 1.0;
         self.initialized = false;
+        self.alpha = self.base_alpha;
+        self.smoothed_error = 0.0;
+        self.smoothed_abs_error = 0.0;
         self.min = f64::MAX;
         self.max = f64::MIN;
     }
@@ -132,4 +212,34 @@ mod tests {
         let result = norm.normalize(50.0);
         assert!((result - 0.5).abs() < 0.1);
     }
+
+    #[test]
+    fn test_adaptive_alpha_rises_on_step_then_settles() {
+        let config = AdaptiveAlpha::default();
+        let mut norm = Normalizer::new(NormalizationMethod::ZScore, 0.1).with_adaptive_alpha(config);
+
+        // Coolant wobbling around 90 °C: errors alternate in sign
+        for i in 0..200 {
+            norm.normalize(if i % 2 == 0 { 89.5 } else { 90.5 });
+        }
+        let stable_alpha = norm.alpha();
+        assert!(stable_alpha < 0.1, "stable alpha {stable_alpha}");
+
+        // Thermostat failure: the level jumps to 105 °C
+        let mut peak_alpha: f64 = 0.0;
+        for i in 0..10 {
+            norm.normalize(if i % 2 == 0 { 104.5 } else { 105.5 });
+            peak_alpha = peak_alpha.max(norm.alpha());
+        }
+        assert!(peak_alpha > 0.4, "peak alpha {peak_alpha}");
+        assert!(peak_alpha <= config.max_alpha);
+
+        // Once the mean has caught up the noise dominates again
+        for i in 0..200 {
+            norm.normalize(if i % 2 == 0 { 104.5 } else { 105.5 });
+        }
+        assert!(norm.alpha() < 0.1, "settled alpha {}", norm.alpha());
+        assert!(norm.alpha() >= config.min_alpha);
+        assert!((norm.mean() - 105.0).abs() < 1.0);
+    }
 }
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use crate::detector::{FaceBbox, EyeState, HeadPose};
+use crate::fatigue::FatigueLevel;
 use crate::state::{DrowsinessLevel, DistractionType};
 
 /// DMS alert types
@@ -24,6 +25,12 @@ pub enum DmsAlert {
     
     /// Eye closure ratio too high (PERCLOS)
     HighPerclos,
+
+    /// Cabin camera stopped delivering frames
+    CameraOffline,
+
+    /// Fatigue rose to a higher level; raised once per escalation
+    Fatigue { level: FatigueLevel },
 }
 
 /// Complete DMS analysis result
@@ -40,12 +47,16 @@ pub struct DmsAnalysis {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub eye_state: Option<EyeState>,
     
-    /// Head pose (yaw, pitch, roll)
+    /// Head pose (yaw, pitch, roll), relative to the driver's calibrated neutral
     #[serde(skip_serializing_if = "Option::is_none")]
     pub head_pose: Option<HeadPose>,
     
     /// Current drowsiness level
     pub drowsiness_level: DrowsinessLevel,
+
+    /// Graduated fatigue level
+    #[serde(default)]
+    pub fatigue_level: FatigueLevel,
     
     /// Current distraction type (if any)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -53,9 +64,22 @@ pub struct DmsAnalysis {
     
     /// Active alerts
     pub alerts: Vec<DmsAlert>,
+
+    /// Models were skipped for this frame; detections are carried over
+    /// from the last processed frame
+    #[serde(default)]
+    pub skipped: bool,
 }
 
 impl DmsAnalysis {
+    /// Result to publish while the cabin camera is stalled
+    pub fn camera_offline() -> Self {
+        Self {
+            alerts: vec![DmsAlert::CameraOffline],
+            ..Default::default()
+        }
+    }
+
     /// Check if any alerts are active
     pub fn has_alerts(&self) -> bool {
         !self.alerts.is_empty()
@@ -1,7 +1,10 @@
 //! DMS configuration
 
+use camera_capture::{ClaheConfig, FrameSkip, MockConfig, Normalization};
 use serde::{Deserialize, Serialize};
 
+use crate::fatigue::FatigueConfig;
+
 /// DMS configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DmsConfig {
@@ -10,6 +13,9 @@ pub struct DmsConfig {
     
     /// Gaze away threshold for distraction alert (milliseconds)
     pub distraction_threshold_ms: u64,
+
+    /// Scaling of `distraction_threshold_ms` with vehicle speed
+    pub distraction_speed_curve: SpeedCurve,
     
     /// Gaze deviation threshold (degrees from center)
     pub gaze_threshold_degrees: f32,
@@ -22,11 +28,39 @@ pub struct DmsConfig {
     
     /// Enable head pose estimation
     pub enable_pose: bool,
+
+    /// Forward-facing frames averaged into the driver's neutral head pose
+    /// before gaze deviation is measured from it; 0 measures from the
+    /// camera axis
+    pub pose_calibration_frames: usize,
+
+    /// Neutral (yaw, pitch) saved for this vehicle's camera mount; used
+    /// until a calibration learns the current driver's
+    pub pose_neutral: Option<(f32, f32)>,
+
+    /// Fatigue score weights and level bands
+    pub fatigue: FatigueConfig,
+
+    /// Channels expected by the face model input (1 for IR models, 3 for RGB)
+    pub input_channels: usize,
+
+    /// Pixel scaling expected by the face model (BlazeFace: -1..1)
+    pub normalization: Normalization,
+
+    /// Contrast enhancement for dim IR frames, applied before inference
+    pub low_light: Option<ClaheConfig>,
+
+    /// Frames to run the models on; skipped frames reuse the last
+    /// observation so eye-closure and gaze timers keep running
+    pub frame_skip: FrameSkip,
     
     /// Model paths
     pub face_model_path: Option<String>,
     pub eye_model_path: Option<String>,
     pub pose_model_path: Option<String>,
+
+    /// Seed for synthetic results when a model is not configured
+    pub mock: MockConfig,
 }
 
 impl Default for DmsConfig {
@@ -34,14 +68,69 @@ impl Default for DmsConfig {
         Self {
             drowsiness_threshold_ms: 1500,
             distraction_threshold_ms: 3000,
+            distraction_speed_curve: SpeedCurve::default(),
             gaze_threshold_degrees: 30.0,
             face_confidence: 0.7,
             eye_confidence: 0.6,
             enable_pose: true,
+            pose_calibration_frames: 45,
+            pose_neutral: None,
+            fatigue: FatigueConfig::default(),
+            input_channels: 3,
+            normalization: Normalization::MinusOneToOne,
+            low_light: None,
+            frame_skip: FrameSkip::Off,
             face_model_path: None,
             eye_model_path: None,
             pose_model_path: None,
+            mock: MockConfig::default(),
+        }
+    }
+}
+
+/// Piecewise-linear factor over vehicle speed
+///
+/// Points are `(speed km/h, factor)`, ascending by speed; below the first
+/// and above the last point the end factors hold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedCurve {
+    pub points: Vec<(f32, f32)>,
+}
+
+impl Default for SpeedCurve {
+    /// Full look-away time in town, halved at motorway speed
+    fn default() -> Self {
+        Self {
+            points: vec![(10.0, 1.0), (50.0, 0.75), (100.0, 0.5)],
+        }
+    }
+}
+
+impl SpeedCurve {
+    /// Factor at `speed_kmh`; 1.0 for an empty curve
+    pub fn factor(&self, speed_kmh: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return 1.0;
+        };
+        if speed_kmh <= first.0 {
+            return first.1;
+        }
+        if speed_kmh >= last.0 {
+            return last.1;
         }
+        self.points
+            .windows(2)
+            .find(|w| speed_kmh <= w[1].0)
+            .map(|w| {
+                let (s0, f0) = w[0];
+                let (s1, f1) = w[1];
+                if s1 > s0 {
+                    f0 + (f1 - f0) * (speed_kmh - s0) / (s1 - s0)
+                } else {
+                    f1
+                }
+            })
+            .unwrap_or(last.1)
     }
 }
 
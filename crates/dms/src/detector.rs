@@ -1,6 +1,7 @@
 //! Face, eye, and pose detection models
 
 use camera_capture::frame::VideoFrame;
+use camera_capture::{MockConfig, MockRng, Normalization};
 use serde::{Deserialize, Serialize};
 use crate::{DmsConfig, DmsError};
 use ort::{Session, GraphOptimizationLevel};
@@ -59,10 +60,79 @@ pub struct HeadPose {
     pub roll: f32,
 }
 
+/// Face model input resolution (BlazeFace front)
+const FACE_INPUT_SIZE: u32 = 128;
+
+/// Check that a frame is packed grayscale or RGB24
+fn validate_frame(frame: &VideoFrame) -> Result<(), DmsError> {
+    if frame.is_grayscale() {
+        return Ok(());
+    }
+    frame.validate_rgb().map_err(|e| DmsError::ImageProcessing(e.to_string()))
+}
+
+/// Build a `[1, channels, size, size]` tensor scaled by `normalization`
+///
+/// Grayscale frames are used directly for single-channel models and
+/// replicated for 3-channel ones; RGB frames are reduced to luminance
+/// for single-channel models.
+pub fn preprocess(
+    frame: &VideoFrame,
+    size: u32,
+    channels: usize,
+    normalization: Normalization,
+) -> Result<Array4<f32>, DmsError> {
+    validate_frame(frame)?;
+    if channels != 1 && channels != 3 {
+        return Err(DmsError::ImageProcessing(format!(
+            "unsupported model input channel count {}",
+            channels
+        )));
+    }
+
+    let dim = size as usize;
+    let mut input_array = Array4::<f32>::zeros((1, channels, dim, dim));
+    if frame.is_grayscale() || channels == 1 {
+        let gray = if frame.is_grayscale() {
+            frame.data.clone()
+        } else {
+            frame.to_grayscale()
+        };
+        let img = image::GrayImage::from_raw(frame.width, frame.height, gray)
+            .ok_or_else(|| DmsError::ImageProcessing("Failed to create image buffer".into()))?;
+        let resized = image::imageops::resize(&img, size, size, image::imageops::FilterType::Triangle);
+
+        for (x, y, pixel) in resized.enumerate_pixels() {
+            for c in 0..channels {
+                input_array[[0, c, y as usize, x as usize]] = normalization.apply(pixel[0], c);
+            }
+        }
+    } else {
+        let img = image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
+            frame.width,
+            frame.height,
+            frame.data.as_slice(),
+        )
+        .ok_or_else(|| DmsError::ImageProcessing("Failed to create image buffer".into()))?;
+        let resized = image::imageops::resize(&img, size, size, image::imageops::FilterType::Triangle);
+
+        for (x, y, pixel) in resized.enumerate_pixels() {
+            for c in 0..3 {
+                input_array[[0, c, y as usize, x as usize]] = normalization.apply(pixel[c], c);
+            }
+        }
+    }
+
+    Ok(input_array)
+}
+
 /// Face detector using BlazeFace or similar
 pub struct FaceDetector {
     confidence_threshold: f32,
+    input_channels: usize,
+    normalization: Normalization,
     session: Option<Session>,
+    mock: MockRng,
 }
 
 impl FaceDetector {
@@ -97,33 +167,21 @@ impl FaceDetector {
 
         Ok(Self {
             confidence_threshold: config.face_confidence,
+            input_channels: config.input_channels,
+            normalization: config.normalization,
             session,
+            mock: MockRng::new(config.mock),
         })
     }
 
     /// Detect faces in frame
     pub fn detect(&self, frame: &VideoFrame) -> Result<Vec<FaceBbox>, DmsError> {
-         if let Some(session) = &self.session {
-             // 1. Preprocess: Resize to 128x128
-            let img = match image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
-                frame.width, 
-                frame.height, 
-                &frame.data
-            ) {
-                Some(i) => i,
-                None => return Err(DmsError::ImageProcessing("Failed to create image buffer".into())),
-            };
-
-            let resized = image::imageops::resize(&img, 128, 128, image::imageops::FilterType::Triangle);
+        validate_frame(frame)?;
 
-            // 2. Normalize and create tensor (1x3x128x128)
-            // BlazeFace usually expects -1..1 or 0..1 normalization
-            let mut input_array = Array4::<f32>::zeros((1, 3, 128, 128));
-            for (x, y, pixel) in resized.enumerate_pixels() {
-                input_array[[0, 0, y as usize, x as usize]] = (pixel[0] as f32 / 127.5) - 1.0;
-                input_array[[0, 1, y as usize, x as usize]] = (pixel[1] as f32 / 127.5) - 1.0;
-                input_array[[0, 2, y as usize, x as usize]] = (pixel[2] as f32 / 127.5) - 1.0;
-            }
+         if let Some(session) = &self.session {
+            // 1-2. Resize to 128x128 and normalize (BlazeFace: -1..1)
+            let input_array =
+                preprocess(frame, FACE_INPUT_SIZE, self.input_channels, self.normalization)?;
 
             // 3. Inference
             let outputs = session.run(ort::inputs![input_array].map_err(|e| DmsError::Inference(e.to_string()))?)
@@ -145,16 +203,19 @@ impl FaceDetector {
                 ]),
             }])
          } else {
-             // Mock
+             // Mock: a centered face drifting slightly between frames
+             let (w, h) = (frame.width as f32, frame.height as f32);
+             let dx = self.mock.jitter(0.0, 0.02) * w;
+             let dy = self.mock.jitter(0.0, 0.02) * h;
              let mock_face = FaceBbox {
-                x: frame.width as f32 * 0.3,
-                y: frame.height as f32 * 0.2,
-                width: frame.width as f32 * 0.4,
-                height: frame.height as f32 * 0.5,
-                confidence: 0.95,
+                x: w * 0.3 + dx,
+                y: h * 0.2 + dy,
+                width: w * 0.4,
+                height: h * 0.5,
+                confidence: self.mock.uniform(0.85, 0.99),
                 keypoints: Some(vec![
-                    (frame.width as f32 * 0.35, frame.height as f32 * 0.3), // L Eye
-                    (frame.width as f32 * 0.65, frame.height as f32 * 0.3), // R Eye
+                    (w * 0.35 + dx, h * 0.3 + dy), // L Eye
+                    (w * 0.65 + dx, h * 0.3 + dy), // R Eye
                 ]),
             };
             Ok(vec![mock_face])
@@ -166,6 +227,7 @@ impl FaceDetector {
 pub struct EyeDetector {
     confidence_threshold: f32,
     session: Option<Session>,
+    mock: MockRng,
 }
 
 impl EyeDetector {
@@ -200,11 +262,15 @@ impl EyeDetector {
         Ok(Self {
             confidence_threshold: config.eye_confidence,
             session,
+            // Offset the seed so eye and face streams aren't correlated
+            mock: MockRng::new(MockConfig::with_seed(config.mock.seed.wrapping_add(1))),
         })
     }
 
     /// Detect eye state within face region
     pub fn detect(&self, frame: &VideoFrame, face: &FaceBbox) -> Result<EyeState, DmsError> {
+        validate_frame(frame)?;
+
         if let Some(session) = &self.session {
             // Real implementation: 
             // 1. Crop eyes from face based on keypoints or bbox heuristic
@@ -213,14 +279,19 @@ impl EyeDetector {
             // TODO: Implement crop logic
             Ok(EyeState::default())
         } else {
-             // Fallback to heuristic
+             // Fallback to heuristic: mostly open eyes with occasional blinks
+             let openness = if self.mock.chance(0.05) {
+                 self.mock.uniform(0.0, 0.2)
+             } else {
+                 self.mock.uniform(0.6, 0.95)
+             };
              Ok(EyeState {
-                left_closed: false,
-                right_closed: false,
-                left_openness: 0.8,
-                right_openness: 0.8,
-                gaze_yaw: 0.0,
-                gaze_pitch: 0.0,
+                left_closed: openness < 0.2,
+                right_closed: openness < 0.2,
+                left_openness: openness,
+                right_openness: self.mock.jitter(openness, 0.03).clamp(0.0, 1.0),
+                gaze_yaw: self.mock.jitter(0.0, 5.0),
+                gaze_pitch: self.mock.jitter(0.0, 3.0),
             })
         }
     }
@@ -271,3 +342,49 @@ impl PoseEstimator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tensor_shape_follows_channel_count() {
+        let rgb = VideoFrame::new(vec![200; 64 * 48 * 3], 64, 48, 0, 0);
+        let ir = VideoFrame::new(vec![50; 64 * 48], 64, 48, 0, 0);
+
+        for frame in [&rgb, &ir] {
+            assert_eq!(preprocess(frame, 128, 1, Normalization::MinusOneToOne).unwrap().shape(), &[1, 1, 128, 128]);
+            assert_eq!(preprocess(frame, 128, 3, Normalization::MinusOneToOne).unwrap().shape(), &[1, 3, 128, 128]);
+        }
+
+        // Replicated IR channels are identical
+        let replicated = preprocess(&ir, 32, 3, Normalization::MinusOneToOne).unwrap();
+        let expected = 50.0 / 127.5 - 1.0;
+        assert!(replicated.iter().all(|&v| (v - expected).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_mock_detections_follow_seed() {
+        let frame = VideoFrame::new(vec![0; 64 * 48 * 3], 64, 48, 0, 0);
+        let run = |seed: u64| {
+            let config = DmsConfig {
+                mock: MockConfig::with_seed(seed),
+                ..Default::default()
+            };
+            let detector = FaceDetector::new(&config).unwrap();
+            (0..5)
+                .map(|_| detector.detect(&frame).unwrap()[0].x)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+    }
+
+    #[test]
+    fn test_rejects_malformed_frame() {
+        let frame = VideoFrame::new(vec![0; 10], 64, 48, 0, 0);
+        assert!(preprocess(&frame, 128, 1, Normalization::MinusOneToOne).is_err());
+        assert!(preprocess(&VideoFrame::new(vec![0; 12], 2, 2, 0, 0), 8, 2, Normalization::MinusOneToOne).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Graduated fatigue assessment
+//!
+//! Combines eye closure (PERCLOS), blink duration, yawning and head nods
+//! into one fatigue score and maps it onto levels loosely following the
+//! Karolinska Sleepiness Scale, so fleets can stage their response: a chime
+//! at [`FatigueLevel::Sleepy`], a pull-over warning at
+//! [`FatigueLevel::Extreme`].
+
+use serde::{Deserialize, Serialize};
+
+/// Fatigue level, roughly KSS 1-5 through 9
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub enum FatigueLevel {
+    /// KSS 1-5: alert to neither alert nor sleepy
+    #[default]
+    Alert,
+    /// KSS 6: some signs of sleepiness
+    SlightlySleepy,
+    /// KSS 7: sleepy, no effort to stay awake
+    Sleepy,
+    /// KSS 8: sleepy, some effort to stay awake
+    VerySleepy,
+    /// KSS 9: fighting sleep
+    Extreme,
+}
+
+impl FatigueLevel {
+    const ALL: [FatigueLevel; 5] = [
+        FatigueLevel::Alert,
+        FatigueLevel::SlightlySleepy,
+        FatigueLevel::Sleepy,
+        FatigueLevel::VerySleepy,
+        FatigueLevel::Extreme,
+    ];
+
+    /// Stage number, 0 (alert) to 4 (extreme)
+    pub fn stage(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Approximate Karolinska Sleepiness Scale value
+    pub fn kss(&self) -> u8 {
+        match self {
+            FatigueLevel::Alert => 5,
+            FatigueLevel::SlightlySleepy => 6,
+            FatigueLevel::Sleepy => 7,
+            FatigueLevel::VerySleepy => 8,
+            FatigueLevel::Extreme => 9,
+        }
+    }
+}
+
+/// Fatigue signs measured over the recent past
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FatigueIndicators {
+    /// Fraction of time the eyes were closed (0-1)
+    pub perclos: f32,
+    /// Mean duration of recent blinks (ms)
+    pub mean_blink_ms: f32,
+    /// Yawns per minute
+    pub yawns_per_min: f32,
+    /// Head nods per minute
+    pub nods_per_min: f32,
+}
+
+/// Weights and level bands for the fatigue score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FatigueConfig {
+    /// Score weight of PERCLOS
+    pub perclos_weight: f32,
+    /// Score weight of blink duration
+    pub blink_weight: f32,
+    /// Score weight of yawning
+    pub yawn_weight: f32,
+    /// Score weight of head nods
+    pub nod_weight: f32,
+    /// PERCLOS counted as fully fatigued
+    pub perclos_max: f32,
+    /// Blink duration of an alert driver (ms); longer blinks add to the score
+    pub blink_alert_ms: f32,
+    /// Blink duration counted as fully fatigued (ms)
+    pub blink_max_ms: f32,
+    /// Yawn rate counted as fully fatigued (per minute)
+    pub yawns_max_per_min: f32,
+    /// Nod rate counted as fully fatigued (per minute)
+    pub nods_max_per_min: f32,
+    /// Head pitch past which a dip counts as a nod (degrees)
+    pub nod_pitch_degrees: f32,
+    /// Score at which each level from `SlightlySleepy` to `Extreme` starts,
+    /// ascending, in `0.0..=1.0`
+    pub bands: [f32; 4],
+}
+
+impl Default for FatigueConfig {
+    fn default() -> Self {
+        Self {
+            perclos_weight: 0.4,
+            blink_weight: 0.25,
+            yawn_weight: 0.2,
+            nod_weight: 0.15,
+            perclos_max: 0.4,
+            blink_alert_ms: 150.0,
+            blink_max_ms: 500.0,
+            yawns_max_per_min: 3.0,
+            nods_max_per_min: 4.0,
+            nod_pitch_degrees: 20.0,
+            bands: [0.15, 0.3, 0.5, 0.7],
+        }
+    }
+}
+
+impl FatigueConfig {
+    /// Combined fatigue score, 0 (alert) to 1
+    pub fn score(&self, indicators: &FatigueIndicators) -> f32 {
+        let ratio = |value: f32, max: f32| {
+            if max > 0.0 {
+                (value / max).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+        let weighted = [
+            (self.perclos_weight, ratio(indicators.perclos, self.perclos_max)),
+            (
+                self.blink_weight,
+                ratio(
+                    indicators.mean_blink_ms - self.blink_alert_ms,
+                    self.blink_max_ms - self.blink_alert_ms,
+                ),
+            ),
+            (self.yawn_weight, ratio(indicators.yawns_per_min, self.yawns_max_per_min)),
+            (self.nod_weight, ratio(indicators.nods_per_min, self.nods_max_per_min)),
+        ];
+
+        let total_weight: f32 = weighted.iter().map(|(w, _)| w.max(0.0)).sum();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        weighted.iter().map(|(w, v)| w.max(0.0) * v).sum::<f32>() / total_weight
+    }
+
+    /// Level whose band contains the indicators' score
+    pub fn level(&self, indicators: &FatigueIndicators) -> FatigueLevel {
+        let score = self.score(indicators);
+        let reached = self.bands.iter().filter(|&&start| score >= start).count();
+        FatigueLevel::ALL[reached]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_climbs_through_bands() {
+        let config = FatigueConfig::default();
+        let stages = [
+            FatigueIndicators::default(),
+            FatigueIndicators { perclos: 0.12, mean_blink_ms: 220.0, ..Default::default() },
+            FatigueIndicators { perclos: 0.15, mean_blink_ms: 300.0, yawns_per_min: 1.0, nods_per_min: 0.0 },
+            FatigueIndicators { perclos: 0.25, mean_blink_ms: 380.0, yawns_per_min: 1.5, nods_per_min: 1.0 },
+            FatigueIndicators { perclos: 0.4, mean_blink_ms: 500.0, yawns_per_min: 2.0, nods_per_min: 3.0 },
+        ];
+
+        let levels: Vec<FatigueLevel> = stages.iter().map(|i| config.level(i)).collect();
+        assert_eq!(levels, FatigueLevel::ALL);
+        assert!(levels.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(levels.last().unwrap().kss(), 9);
+
+        // Moving the bands moves the levels
+        let strict = FatigueConfig {
+            bands: [0.05, 0.1, 0.15, 0.2],
+            ..Default::default()
+        };
+        assert_eq!(strict.level(&stages[2]), FatigueLevel::Extreme);
+    }
+}
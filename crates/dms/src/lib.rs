@@ -10,15 +10,19 @@
 pub mod analysis;
 pub mod config;
 pub mod detector;
+pub mod fatigue;
 pub mod state;
 
 pub use analysis::{DmsAnalysis, DmsAlert};
-pub use config::DmsConfig;
+pub use config::{DmsConfig, SpeedCurve};
 pub use detector::{FaceDetector, EyeDetector, PoseEstimator};
+pub use fatigue::{FatigueConfig, FatigueIndicators, FatigueLevel};
 pub use state::{DriverState, DrowsinessLevel, DistractionType};
 
 use camera_capture::frame::VideoFrame;
+use camera_capture::{CameraService, CameraStatus, FrameSkipper};
 use thiserror::Error;
+use tokio::sync::watch;
 
 /// DMS error types
 #[derive(Error, Debug)]
@@ -49,6 +53,36 @@ pub struct DmsModule {
     eye_detector: EyeDetector,
     pose_estimator: PoseEstimator,
     state: DriverState,
+    skipper: FrameSkipper,
+    /// Result of the last frame the models ran on
+    last: DmsAnalysis,
+    /// Latest vehicle speed, if known
+    speed_kmh: Option<f32>,
+    /// Neutral head pose of the current driver
+    pose_calibration: PoseCalibration,
+}
+
+/// Running average of forward-facing head poses for one driver
+///
+/// Seat height and camera placement put a driver's "straight ahead" a few
+/// degrees off the camera axis; measuring gaze from the learned neutral
+/// keeps the distraction threshold meaningful for every driver.
+#[derive(Debug, Clone, Default)]
+struct PoseCalibration {
+    yaw_sum: f32,
+    pitch_sum: f32,
+    samples: usize,
+    neutral: Option<(f32, f32)>,
+}
+
+impl PoseCalibration {
+    /// Start learning, measuring from `baseline` until done
+    fn seeded(baseline: Option<(f32, f32)>) -> Self {
+        Self {
+            neutral: baseline,
+            ..Default::default()
+        }
+    }
 }
 
 impl DmsModule {
@@ -59,24 +93,86 @@ impl DmsModule {
             eye_detector: EyeDetector::new(&config)?,
             pose_estimator: PoseEstimator::new(&config)?,
             state: DriverState::default(),
+            skipper: FrameSkipper::new(config.frame_skip),
+            last: DmsAnalysis::default(),
+            speed_kmh: None,
+            pose_calibration: PoseCalibration::seeded(config.pose_neutral),
             config,
         })
     }
 
+    /// Feed the current vehicle speed (km/h), used to tighten the
+    /// distraction timeout at speed
+    pub fn set_speed(&mut self, speed_kmh: f32) {
+        self.speed_kmh = Some(speed_kmh.max(0.0));
+    }
+
+    /// Look-away time allowed before a distraction alert at the current
+    /// speed; the configured base while speed is unknown
+    pub fn distraction_timeout_ms(&self) -> u64 {
+        let base = self.config.distraction_threshold_ms;
+        match self.speed_kmh {
+            Some(speed) => {
+                let factor = self.config.distraction_speed_curve.factor(speed).max(0.0);
+                (base as f64 * factor as f64).round() as u64
+            }
+            None => base,
+        }
+    }
+
     /// Analyze a single frame for driver state
     pub async fn analyze(&mut self, frame: &VideoFrame) -> Result<DmsAnalysis, DmsError> {
+        if !self.skipper.should_process(frame.timestamp_ns) {
+            return Ok(self.carry_forward());
+        }
+
+        let analysis = self.run_models(frame)?;
+        self.last = analysis.clone();
+        Ok(analysis)
+    }
+
+    /// Analyze the next frame from the cabin camera, or report it offline
+    ///
+    /// `status` comes from [`CameraService::status_changes`]. While the
+    /// camera is stalled and the service re-opens the driver no frames
+    /// arrive, so each stall yields one [`DmsAnalysis::camera_offline`] result
+    /// instead. `None` once the camera service has stopped.
+    pub async fn analyze_next(
+        &mut self,
+        camera: &mut CameraService,
+        status: &mut watch::Receiver<CameraStatus>,
+    ) -> Option<Result<DmsAnalysis, DmsError>> {
+        loop {
+            tokio::select! {
+                frame = camera.next() => {
+                    let frame = frame?;
+                    return Some(self.analyze(&frame).await);
+                }
+                Ok(()) = status.changed() => {
+                    if status.borrow_and_update().is_offline() {
+                        return Some(Ok(DmsAnalysis::camera_offline()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rate the models are actually running at, after frame skipping
+    pub fn processing_fps(&self) -> Option<f32> {
+        self.skipper.effective_fps()
+    }
+
+    fn run_models(&mut self, frame: &VideoFrame) -> Result<DmsAnalysis, DmsError> {
+        let enhanced = self.config.low_light.map(|clahe| clahe.apply(frame));
+        let frame = enhanced.as_ref().unwrap_or(frame);
+
         // Detect face
         let faces = self.face_detector.detect(frame)?;
         
         if faces.is_empty() {
-            self.state.face_absent_frames += 1;
             return Ok(DmsAnalysis {
                 face_detected: false,
-                alerts: if self.state.face_absent_frames > 30 {
-                    vec![DmsAlert::FaceNotVisible]
-                } else {
-                    vec![]
-                },
+                alerts: self.face_absent(),
                 ..Default::default()
             });
         }
@@ -87,8 +183,8 @@ impl DmsModule {
         // Detect eye state
         let eyes = self.eye_detector.detect(frame, face)?;
         
-        // Estimate head pose
-        let pose = self.pose_estimator.estimate(frame, face)?;
+        // Estimate head pose, relative to the driver's neutral
+        let pose = self.calibrated_pose(self.pose_estimator.estimate(frame, face)?);
 
         // Update state and detect alerts
         let alerts = self.update_state(&eyes, &pose);
@@ -99,26 +195,102 @@ impl DmsModule {
             eye_state: Some(eyes),
             head_pose: Some(pose),
             drowsiness_level: self.state.drowsiness_level,
+            fatigue_level: self.state.fatigue_level,
             distraction_type: self.state.distraction,
             alerts,
+            skipped: false,
         })
     }
 
+    /// Feed a raw pose to the calibration and return it relative to the
+    /// neutral pose once one has been learned
+    fn calibrated_pose(&mut self, mut pose: detector::HeadPose) -> detector::HeadPose {
+        let target = self.config.pose_calibration_frames;
+        let calibration = &mut self.pose_calibration;
+        if calibration.samples < target {
+            // Glances away during calibration would skew the neutral
+            let threshold = self.config.gaze_threshold_degrees;
+            if pose.yaw.abs() < threshold && pose.pitch.abs() < threshold {
+                calibration.yaw_sum += pose.yaw;
+                calibration.pitch_sum += pose.pitch;
+                calibration.samples += 1;
+            }
+            if calibration.samples >= target {
+                let n = calibration.samples as f32;
+                calibration.neutral = Some((calibration.yaw_sum / n, calibration.pitch_sum / n));
+            }
+        }
+
+        if let Some((yaw, pitch)) = calibration.neutral {
+            pose.yaw -= yaw;
+            pose.pitch -= pitch;
+        }
+        pose
+    }
+
+    /// Neutral (yaw, pitch) gaze is measured from: the current driver's
+    /// once learned, else the vehicle's saved baseline
+    pub fn pose_neutral(&self) -> Option<(f32, f32)> {
+        self.pose_calibration.neutral
+    }
+
+    /// Forget the neutral head pose and learn it again from the next
+    /// forward-facing frames, measuring from the vehicle's saved baseline
+    /// meanwhile
+    pub fn recalibrate_pose(&mut self) {
+        self.pose_calibration = PoseCalibration::seeded(self.config.pose_neutral);
+    }
+
+    /// Tracked driver state: timers and fatigue accumulators
+    pub fn driver_state(&self) -> &DriverState {
+        &self.state
+    }
+
+    /// Result for a skipped frame: hold the last observation and advance
+    /// the drowsiness and distraction timers with it
+    fn carry_forward(&mut self) -> DmsAnalysis {
+        let mut analysis = self.last.clone();
+        analysis.skipped = true;
+        analysis.alerts = match (&analysis.eye_state, &analysis.head_pose) {
+            (Some(eyes), Some(pose)) => self.update_state(eyes, pose),
+            _ => self.face_absent(),
+        };
+        analysis.drowsiness_level = self.state.drowsiness_level;
+        analysis.fatigue_level = self.state.fatigue_level;
+        analysis.distraction_type = self.state.distraction;
+        analysis
+    }
+
+    fn face_absent(&mut self) -> Vec<DmsAlert> {
+        self.state.face_absent_frames += 1;
+        if self.state.face_absent_frames > 30 {
+            vec![DmsAlert::FaceNotVisible]
+        } else {
+            vec![]
+        }
+    }
+
     fn update_state(
         &mut self,
         eyes: &detector::EyeState,
         pose: &detector::HeadPose,
     ) -> Vec<DmsAlert> {
         let mut alerts = Vec::new();
+        self.state.elapsed_ms += 33; // Assume ~30fps
+        self.state
+            .add_eye_sample((eyes.left_openness + eyes.right_openness) / 2.0);
 
         // Drowsiness detection (eyes closed >1.5s)
         if eyes.left_closed && eyes.right_closed {
-            self.state.eyes_closed_ms += 33; // Assume ~30fps
+            self.state.eyes_closed_ms += 33;
             if self.state.eyes_closed_ms > self.config.drowsiness_threshold_ms {
                 self.state.drowsiness_level = DrowsinessLevel::High;
                 alerts.push(DmsAlert::Drowsiness);
             }
         } else {
+            if self.state.eyes_closed_ms > 0 {
+                self.state.add_blink(self.state.eyes_closed_ms);
+            }
             self.state.eyes_closed_ms = 0;
             self.state.drowsiness_level = DrowsinessLevel::Normal;
         }
@@ -129,7 +301,7 @@ impl DmsModule {
 
         if !looking_forward {
             self.state.distraction_ms += 33;
-            if self.state.distraction_ms > self.config.distraction_threshold_ms {
+            if self.state.distraction_ms > self.distraction_timeout_ms() {
                 self.state.distraction = Some(DistractionType::LookingAway);
                 alerts.push(DmsAlert::Distraction);
             }
@@ -143,11 +315,145 @@ impl DmsModule {
             alerts.push(DmsAlert::HeadDown);
         }
 
+        // Graduated fatigue: count each dip of the head once, alert on escalation
+        let dipped = pose.pitch.abs() > self.config.fatigue.nod_pitch_degrees;
+        if dipped && !self.state.head_dipped {
+            self.state.add_nod();
+        }
+        self.state.head_dipped = dipped;
+
+        let level = self.config.fatigue.level(&self.state.fatigue_indicators());
+        if level > self.state.fatigue_level {
+            alerts.push(DmsAlert::Fatigue { level });
+        }
+        self.state.fatigue_level = level;
+
         alerts
     }
 
     /// Reset driver state (on driver change)
     pub fn reset_state(&mut self) {
         self.state = DriverState::default();
+        self.last = DmsAnalysis::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camera_capture::{CameraError, CameraServiceConfig, FrameSkip, FrameSource};
+    use std::time::Duration;
+
+    /// Delivers `remaining` blank frames, then goes quiet for good
+    struct FadingSource {
+        remaining: u32,
+        sequence: u32,
+    }
+
+    impl FrameSource for FadingSource {
+        fn next_frame(&mut self, timeout: Duration) -> Option<VideoFrame> {
+            if self.remaining == 0 {
+                std::thread::sleep(timeout);
+                return None;
+            }
+            self.remaining -= 1;
+            self.sequence += 1;
+            let timestamp_ns = u64::from(self.sequence) * 33_000_000;
+            Some(VideoFrame::new(vec![0; 64 * 48 * 3], 64, 48, timestamp_ns, self.sequence))
+        }
+
+        fn reinit(&mut self) -> Result<(), CameraError> {
+            Err(CameraError::Open("device unplugged".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_third_frame_runs_models() {
+        let mut dms = DmsModule::new(DmsConfig {
+            frame_skip: FrameSkip::EveryNth(3),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let frame_ns = 1_000_000_000 / 15;
+        let mut skipped = Vec::new();
+        for i in 0..9 {
+            let frame = VideoFrame::new(vec![0; 64 * 48 * 3], 64, 48, i * frame_ns, i as u32);
+            skipped.push(dms.analyze(&frame).await.unwrap().skipped);
+        }
+
+        assert_eq!(
+            skipped,
+            [false, true, true, false, true, true, false, true, true]
+        );
+        let fps = dms.processing_fps().unwrap();
+        assert!((fps - 5.0).abs() < 0.01, "processing at {fps} fps");
+    }
+
+    #[test]
+    fn test_distraction_timeout_shrinks_with_speed() {
+        let mut dms = DmsModule::new(DmsConfig::default()).unwrap();
+        assert_eq!(dms.distraction_timeout_ms(), 3000);
+
+        dms.set_speed(10.0);
+        let town = dms.distraction_timeout_ms();
+        dms.set_speed(75.0);
+        let rural = dms.distraction_timeout_ms();
+        dms.set_speed(110.0);
+        let motorway = dms.distraction_timeout_ms();
+
+        assert_eq!(town, 3000);
+        assert_eq!(rural, 1875);
+        assert_eq!(motorway, 1500);
+        assert!(motorway < rural && rural < town);
+    }
+
+    #[tokio::test]
+    async fn test_pose_neutral_learned_then_recalibrated() {
+        let mut dms = DmsModule::new(DmsConfig {
+            pose_calibration_frames: 5,
+            ..Default::default()
+        })
+        .unwrap();
+
+        for i in 0..5u64 {
+            assert!(dms.pose_neutral().is_none());
+            let frame = VideoFrame::new(vec![0; 64 * 48 * 3], 64, 48, i * 33_000_000, i as u32);
+            dms.analyze(&frame).await.unwrap();
+        }
+        assert_eq!(dms.pose_neutral(), Some((0.0, 0.0)));
+
+        dms.recalibrate_pose();
+        assert!(dms.pose_neutral().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stalled_camera_reported_offline() {
+        let mut module = DmsModule::new(Default::default()).unwrap();
+        let mut camera = CameraService::spawn(
+            FadingSource {
+                remaining: 2,
+                sequence: 0,
+            },
+            CameraServiceConfig {
+                stall_timeout: Duration::from_millis(100),
+                poll_interval: Duration::from_millis(10),
+                reinit_interval: Duration::from_millis(50),
+                channel_capacity: 4,
+            },
+        );
+        let mut status = camera.status_changes();
+
+        for _ in 0..2 {
+            let analysis = module.analyze_next(&mut camera, &mut status).await.unwrap().unwrap();
+            assert!(!analysis.alerts.iter().any(|a| matches!(a, DmsAlert::CameraOffline)));
+        }
+
+        let offline = tokio::time::timeout(Duration::from_secs(2), module.analyze_next(&mut camera, &mut status))
+            .await
+            .expect("stall not reported")
+            .unwrap()
+            .unwrap();
+        assert!(matches!(offline.alerts[..], [DmsAlert::CameraOffline]));
     }
 }
@@ -1,6 +1,15 @@
 //! Driver state tracking
 
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::fatigue::{FatigueIndicators, FatigueLevel};
+
+/// Blinks kept for the mean blink duration
+const BLINK_HISTORY: usize = 20;
+
+/// Window over which head nods are counted (ms)
+const NOD_WINDOW_MS: u64 = 60_000;
 
 /// Drowsiness level
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -45,6 +54,21 @@ pub struct DriverState {
     
     /// Eye openness ratio history (for PERCLOS)
     pub eye_openness_history: Vec<f32>,
+
+    /// Time observed since the last reset (ms)
+    pub elapsed_ms: u64,
+
+    /// Durations of the most recent eye closures (ms)
+    pub blink_durations_ms: VecDeque<u64>,
+
+    /// When recent head nods started, as `elapsed_ms`
+    pub nod_times_ms: VecDeque<u64>,
+
+    /// Head currently dipped past the nod threshold
+    pub head_dipped: bool,
+
+    /// Current graduated fatigue level
+    pub fatigue_level: FatigueLevel,
 }
 
 impl DriverState {
@@ -73,6 +97,42 @@ impl DriverState {
         }
     }
     
+    /// Record a completed eye closure
+    pub fn add_blink(&mut self, duration_ms: u64) {
+        self.blink_durations_ms.push_back(duration_ms);
+        if self.blink_durations_ms.len() > BLINK_HISTORY {
+            self.blink_durations_ms.pop_front();
+        }
+    }
+
+    /// Record the start of a head nod at the current time
+    pub fn add_nod(&mut self) {
+        self.nod_times_ms.push_back(self.elapsed_ms);
+        let cutoff = self.elapsed_ms.saturating_sub(NOD_WINDOW_MS);
+        while self.nod_times_ms.front().is_some_and(|&t| t < cutoff) {
+            self.nod_times_ms.pop_front();
+        }
+    }
+
+    /// Current fatigue signs
+    pub fn fatigue_indicators(&self) -> FatigueIndicators {
+        let mean_blink_ms = if self.blink_durations_ms.is_empty() {
+            0.0
+        } else {
+            self.blink_durations_ms.iter().sum::<u64>() as f32 / self.blink_durations_ms.len() as f32
+        };
+        let cutoff = self.elapsed_ms.saturating_sub(NOD_WINDOW_MS);
+        let nods = self.nod_times_ms.iter().filter(|&&t| t >= cutoff).count();
+
+        FatigueIndicators {
+            perclos: self.perclos(),
+            mean_blink_ms,
+            // yawn_count covers 10 minutes
+            yawns_per_min: self.yawn_count as f32 / 10.0,
+            nods_per_min: nods as f32,
+        }
+    }
+
     /// Reset state (on driver change)
     pub fn reset(&mut self) {
         *self = Self::default();
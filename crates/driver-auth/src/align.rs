@@ -0,0 +1,202 @@
+//! Landmark-based face alignment
+//!
+//! ArcFace was trained on faces warped so the eyes, nose and mouth corners
+//! land on fixed positions in a 112×112 crop. A similarity transform
+//! (rotation, uniform scale and translation) fitted from the detected
+//! landmarks to that template reproduces the training geometry; a plain
+//! center crop leaves head roll and face scale in the embedding.
+
+use image::{ImageBuffer, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+/// Side of the aligned recognition crop (px)
+pub const ALIGNED_SIZE: u32 = 112;
+
+/// Canonical ArcFace landmark positions in the 112×112 crop: left eye,
+/// right eye, nose tip, left mouth corner, right mouth corner
+pub const ARCFACE_TEMPLATE: [(f32, f32); 5] = [
+    (38.2946, 51.6963),
+    (73.5318, 51.5014),
+    (56.0252, 71.7366),
+    (41.5493, 92.3655),
+    (70.7299, 92.2041),
+];
+
+/// How the face is cropped before recognition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FaceAlignment {
+    /// Resize the whole frame; for models trained without alignment
+    #[default]
+    CenterCrop,
+    /// Warp onto [`ARCFACE_TEMPLATE`] using the detected landmarks
+    ///
+    /// Landmarks are not yet parsed from the detector output, so this
+    /// currently falls back to [`CenterCrop`](Self::CenterCrop) with a
+    /// warning.
+    Landmarks,
+}
+
+/// 2D similarity transform `p' = s·R·p + t`, stored as
+/// `x' = a·x - b·y + tx`, `y' = b·x + a·y + ty`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimilarityTransform {
+    pub a: f32,
+    pub b: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl SimilarityTransform {
+    /// Least-squares fit mapping `src` onto `dst`
+    ///
+    /// Returns `None` for fewer than two pairs, mismatched lengths, or
+    /// coincident source points.
+    pub fn estimate(src: &[(f32, f32)], dst: &[(f32, f32)]) -> Option<Self> {
+        if src.len() < 2 || src.len() != dst.len() {
+            return None;
+        }
+        let n = src.len() as f32;
+        let centroid = |points: &[(f32, f32)]| {
+            let (sx, sy) = points.iter().fold((0.0, 0.0), |(x, y), p| (x + p.0, y + p.1));
+            (sx / n, sy / n)
+        };
+        let (scx, scy) = centroid(src);
+        let (dcx, dcy) = centroid(dst);
+
+        let (mut dot, mut cross, mut norm) = (0.0, 0.0, 0.0);
+        for (&(sx, sy), &(dx, dy)) in src.iter().zip(dst) {
+            let (px, py) = (sx - scx, sy - scy);
+            let (qx, qy) = (dx - dcx, dy - dcy);
+            dot += px * qx + py * qy;
+            cross += px * qy - py * qx;
+            norm += px * px + py * py;
+        }
+        if norm <= f32::EPSILON {
+            return None;
+        }
+
+        let a = dot / norm;
+        let b = cross / norm;
+        Some(Self {
+            a,
+            b,
+            tx: dcx - (a * scx - b * scy),
+            ty: dcy - (b * scx + a * scy),
+        })
+    }
+
+    /// Map a point
+    pub fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (self.a * x - self.b * y + self.tx, self.b * x + self.a * y + self.ty)
+    }
+
+    /// Inverse transform, `None` if degenerate
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.a + self.b * self.b;
+        if det <= f32::EPSILON {
+            return None;
+        }
+        let (a, b) = (self.a / det, -self.b / det);
+        Some(Self {
+            a,
+            b,
+            tx: -(a * self.tx - b * self.ty),
+            ty: -(b * self.tx + a * self.ty),
+        })
+    }
+}
+
+/// Fit the transform from detected landmarks to the ArcFace template
+///
+/// Accepts the five ArcFace landmarks in template order, or the six
+/// BlazeFace keypoints (eyes, ears, nose, mouth center); ears are ignored
+/// and the mouth center is matched to the midpoint of the template's
+/// mouth corners.
+pub fn arcface_transform(landmarks: &[(f32, f32)]) -> Option<SimilarityTransform> {
+    match landmarks.len() {
+        5 => SimilarityTransform::estimate(landmarks, &ARCFACE_TEMPLATE),
+        6 => {
+            let [left_eye, right_eye, nose, mouth_left, mouth_right] = ARCFACE_TEMPLATE;
+            let mouth = (
+                (mouth_left.0 + mouth_right.0) / 2.0,
+                (mouth_left.1 + mouth_right.1) / 2.0,
+            );
+            let src = [landmarks[0], landmarks[1], landmarks[4], landmarks[5]];
+            SimilarityTransform::estimate(&src, &[left_eye, right_eye, nose, mouth])
+        }
+        _ => None,
+    }
+}
+
+/// Warp `image` into an aligned `ALIGNED_SIZE` square crop
+///
+/// Each output pixel is sampled bilinearly from the source position given
+/// by the inverse transform; pixels falling outside the frame are black.
+pub fn warp_aligned<C>(image: &ImageBuffer<Rgb<u8>, C>, transform: &SimilarityTransform) -> Option<RgbImage>
+where
+    C: Deref<Target = [u8]>,
+{
+    let inverse = transform.inverse()?;
+    let (width, height) = image.dimensions();
+
+    let sample = |x: f32, y: f32| -> Rgb<u8> {
+        if x < 0.0 || y < 0.0 || x > (width - 1) as f32 || y > (height - 1) as f32 {
+            return Rgb([0, 0, 0]);
+        }
+        let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let mut out = [0u8; 3];
+        for (c, value) in out.iter_mut().enumerate() {
+            let p = |px: u32, py: u32| f32::from(image.get_pixel(px, py)[c]);
+            let top = p(x0, y0) * (1.0 - fx) + p(x1, y0) * fx;
+            let bottom = p(x0, y1) * (1.0 - fx) + p(x1, y1) * fx;
+            *value = (top * (1.0 - fy) + bottom * fy).round() as u8;
+        }
+        Rgb(out)
+    };
+
+    Some(RgbImage::from_fn(ALIGNED_SIZE, ALIGNED_SIZE, |x, y| {
+        let (sx, sy) = inverse.apply((x as f32, y as f32));
+        sample(sx, sy)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_landmarks_map_onto_template() {
+        // The template rotated by 15°, scaled ×2.5 and shifted into a 640×480 frame
+        let (sin, cos) = 15f32.to_radians().sin_cos();
+        let detected: Vec<(f32, f32)> = ARCFACE_TEMPLATE
+            .iter()
+            .map(|&(x, y)| (2.5 * (cos * x - sin * y) + 300.0, 2.5 * (sin * x + cos * y) + 120.0))
+            .collect();
+
+        let transform = arcface_transform(&detected).expect("landmarks are well spread");
+        for (&point, &target) in detected.iter().zip(&ARCFACE_TEMPLATE) {
+            let (x, y) = transform.apply(point);
+            assert!(
+                (x - target.0).abs() < 0.05 && (y - target.1).abs() < 0.05,
+                "{point:?} mapped to ({x}, {y}), expected {target:?}"
+            );
+        }
+
+        // Round trip through the inverse used for warping
+        let inverse = transform.inverse().unwrap();
+        let (x, y) = inverse.apply(transform.apply(detected[2]));
+        assert!((x - detected[2].0).abs() < 0.01 && (y - detected[2].1).abs() < 0.01);
+
+        // BlazeFace keypoints (ears unused, mouth center) land near the template too
+        let mouth = ((detected[3].0 + detected[4].0) / 2.0, (detected[3].1 + detected[4].1) / 2.0);
+        let blazeface = [detected[0], detected[1], (0.0, 0.0), (0.0, 0.0), detected[2], mouth];
+        let transform = arcface_transform(&blazeface).unwrap();
+        let (x, y) = transform.apply(detected[0]);
+        assert!((x - ARCFACE_TEMPLATE[0].0).abs() < 0.05 && (y - ARCFACE_TEMPLATE[0].1).abs() < 0.05);
+
+        assert!(arcface_transform(&detected[..3]).is_none());
+    }
+}
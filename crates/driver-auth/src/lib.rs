@@ -7,14 +7,24 @@
 //! - Ignition lockout control
 
 use camera_capture::frame::VideoFrame;
+use camera_capture::{MockConfig, MockRng, Normalization};
 use chrono::{DateTime, Utc};
+use common_types::integrity::{read_verified, write_atomic};
+use common_types::IntegrityError;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use thiserror::Error;
 use uuid::Uuid;
 use ort::{Session, GraphOptimizationLevel};
 use ndarray::{Array4, Axis};
 use tracing::{info, warn, error};
 
+pub mod align;
+pub mod vote;
+
+pub use align::{FaceAlignment, SimilarityTransform};
+pub use vote::{AuthVote, VoteConfig};
+
 /// Authentication error types
 #[derive(Error, Debug)]
 pub enum AuthError {
@@ -41,6 +51,19 @@ pub enum AuthError {
 
     #[error("Image processing failed: {0}")]
     ImageProcessing(String),
+
+    #[error("Enrollment data corrupt: {0}")]
+    CorruptEnrollment(String),
+}
+
+impl From<IntegrityError> for AuthError {
+    fn from(e: IntegrityError) -> Self {
+        if e.is_corruption() {
+            AuthError::CorruptEnrollment(e.to_string())
+        } else {
+            AuthError::Database(e.to_string())
+        }
+    }
 }
 
 /// Driver information
@@ -54,6 +77,18 @@ pub struct Driver {
     pub certifications: Vec<String>,
 }
 
+/// File magic for saved enrollments
+const ENROLLMENT_MAGIC: &[u8; 4] = b"ENR1";
+
+/// ArcFace embedding dimension
+const EMBEDDING_DIM: usize = 512;
+
+/// ArcFace input scaling, `(v - 127.5) / 128`
+const ARCFACE_NORMALIZATION: Normalization = Normalization::Custom {
+    mean: [0.5; 3],
+    std: [128.0 / 255.0; 3],
+};
+
 /// Face embedding (512-dim vector for ArcFace)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FaceEmbedding {
@@ -69,6 +104,8 @@ pub enum AuthResult {
         driver: Driver,
         confidence: f32,
     },
+    /// Face matched a driver who has not yet won the frame vote
+    Pending { votes: usize, required: usize },
     /// Face detected but not recognized
     Unknown,
     /// No face detected
@@ -88,11 +125,45 @@ pub struct AuthModule {
     /// Current authenticated driver
     current_driver: Option<Driver>,
 
+    /// K-of-N vote gating changes to `current_driver`
+    vote: AuthVote,
+
     /// Face detection session (BlazeFace)
     det_session: Option<Session>,
     
     /// Face recognition session (ArcFace)
     rec_session: Option<Session>,
+
+    /// Pixel scaling expected by the recognition model
+    rec_normalization: Normalization,
+
+    /// Face crop applied before recognition
+    alignment: FaceAlignment,
+
+    /// Synthetic embeddings used when no models are loaded
+    mock: MockEmbeddings,
+}
+
+/// Mock embedding source: one seeded identity plus per-frame noise
+struct MockEmbeddings {
+    identity: Vec<f32>,
+    rng: MockRng,
+}
+
+impl MockEmbeddings {
+    fn new(config: MockConfig) -> Self {
+        let rng = MockRng::new(config);
+        let identity = (0..EMBEDDING_DIM).map(|_| rng.uniform(-1.0, 1.0)).collect();
+        Self { identity, rng }
+    }
+
+    fn sample(&self) -> FaceEmbedding {
+        let vector = self.identity.iter().map(|&v| self.rng.jitter(v, 0.1)).collect();
+        FaceEmbedding {
+            vector,
+            quality: self.rng.uniform(0.85, 0.99),
+        }
+    }
 }
 
 impl AuthModule {
@@ -133,11 +204,40 @@ impl AuthModule {
             drivers: Vec::new(),
             threshold,
             current_driver: None,
+            vote: AuthVote::new(VoteConfig::default()),
             det_session,
             rec_session,
+            rec_normalization: ARCFACE_NORMALIZATION,
+            alignment: FaceAlignment::default(),
+            mock: MockEmbeddings::new(MockConfig::default()),
         })
     }
 
+    /// Seed the synthetic embeddings used when no models are loaded
+    pub fn with_mock(mut self, config: MockConfig) -> Self {
+        self.mock = MockEmbeddings::new(config);
+        self
+    }
+
+    /// Use a recognition model trained with different pixel scaling
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.rec_normalization = normalization;
+        self
+    }
+
+    /// Require a different number of agreeing frames before the
+    /// authenticated driver changes
+    pub fn with_vote(mut self, config: VoteConfig) -> Self {
+        self.vote = AuthVote::new(config);
+        self
+    }
+
+    /// Choose how faces are cropped for the recognition model
+    pub fn with_alignment(mut self, alignment: FaceAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
     /// Enroll a new driver
     pub fn enroll(
         &mut self,
@@ -160,6 +260,31 @@ impl AuthModule {
         Ok(())
     }
 
+    /// Persist enrolled drivers and their embeddings to `path`
+    ///
+    /// The file is replaced atomically, so an interrupted save leaves the
+    /// previous enrollment intact.
+    pub fn save_enrollment(&self, path: impl AsRef<Path>) -> Result<(), AuthError> {
+        let payload = postcard::to_allocvec(&self.drivers)
+            .map_err(|e| AuthError::Database(e.to_string()))?;
+        write_atomic(path, ENROLLMENT_MAGIC, &payload).map_err(|e| AuthError::Database(e.to_string()))
+    }
+
+    /// Replace the enrolled drivers with those saved at `path`
+    ///
+    /// Returns the number of drivers loaded. A damaged file is rejected
+    /// with [`AuthError::CorruptEnrollment`] and the current enrollment is
+    /// kept.
+    pub fn load_enrollment(&mut self, path: impl AsRef<Path>) -> Result<usize, AuthError> {
+        let payload = read_verified(path, ENROLLMENT_MAGIC)?;
+        let drivers: Vec<(Driver, Vec<FaceEmbedding>)> = postcard::from_bytes(&payload)
+            .map_err(|e| AuthError::CorruptEnrollment(e.to_string()))?;
+
+        info!("Loaded {} enrolled drivers", drivers.len());
+        self.drivers = drivers;
+        Ok(self.drivers.len())
+    }
+
     /// Authenticate driver from frame
     pub fn authenticate(&mut self, frame: &VideoFrame) -> Result<AuthResult, AuthError> {
         let embedding = match self.extract_embedding(frame)? {
@@ -181,14 +306,23 @@ impl AuthModule {
             }
         }
 
-        match best_match {
-            Some((driver, confidence)) => {
+        // Only a driver who wins the vote becomes the authenticated driver
+        let best_match = best_match.map(|(driver, confidence)| (driver.clone(), confidence));
+        if self.vote.record(best_match.as_ref().map(|(driver, _)| driver.id)).is_some() {
+            if let Some((driver, _)) = &best_match {
+                info!("Driver {} authenticated", driver.name);
                 self.current_driver = Some(driver.clone());
-                Ok(AuthResult::Authenticated {
-                    driver: driver.clone(),
-                    confidence,
-                })
             }
+        }
+
+        match best_match {
+            Some((driver, confidence)) if self.vote.winner() == Some(driver.id) => {
+                Ok(AuthResult::Authenticated { driver, confidence })
+            }
+            Some((driver, _)) => Ok(AuthResult::Pending {
+                votes: self.vote.votes_for(driver.id),
+                required: self.vote.config().required,
+            }),
             None => Ok(AuthResult::Unknown),
         }
     }
@@ -201,16 +335,19 @@ impl AuthModule {
     /// Clear authentication
     pub fn logout(&mut self) {
         self.current_driver = None;
+        self.vote.reset();
     }
 
     /// Extract face embedding from frame
     fn extract_embedding(&self, frame: &VideoFrame) -> Result<Option<FaceEmbedding>, AuthError> {
+        frame.validate_rgb().map_err(|e| AuthError::ImageProcessing(e.to_string()))?;
+
         if let (Some(det_sess), Some(rec_sess)) = (&self.det_session, &self.rec_session) {
             // Real implementation pipeline
             
             // 1. Detect Face
             // Preprocess for BlazeFace (128x128)
-            let img = image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(frame.width, frame.height, &frame.data)
+            let img = image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(frame.width, frame.height, frame.data.as_slice())
                 .ok_or(AuthError::ImageProcessing("Failed to create image buffer".into()))?;
 
             let det_input = image::imageops::resize(&img, 128, 128, image::imageops::FilterType::Triangle);
@@ -220,17 +357,39 @@ impl AuthModule {
             // Assuming we found a face bbox.
             // TODO: Implement actual detection inference parsing here or share code.
             
+            // TODO: Parse the BlazeFace keypoints (pixel coordinates) along
+            // with the bbox; until then there are none to align with
+            let landmarks: Option<Vec<(f32, f32)>> = None;
+
             // 2. Crop & Align
-            // ArcFace input: 112x112
-            // For now, center crop 112x112 from original if no detection logic implemented here yet.
-            let rec_input = image::imageops::resize(&img, 112, 112, image::imageops::FilterType::Triangle);
+            // ArcFace input: 112x112, warped onto the landmark template when
+            // keypoints are available, otherwise a center crop
+            let transform = match self.alignment {
+                FaceAlignment::Landmarks => {
+                    let transform = landmarks.as_deref().and_then(align::arcface_transform);
+                    if transform.is_none() {
+                        warn!("No usable face landmarks, falling back to a center crop");
+                    }
+                    transform
+                }
+                FaceAlignment::CenterCrop => None,
+            };
+            let rec_input = match transform.and_then(|t| align::warp_aligned(&img, &t)) {
+                Some(aligned) => aligned,
+                None => image::imageops::resize(
+                    &img,
+                    align::ALIGNED_SIZE,
+                    align::ALIGNED_SIZE,
+                    image::imageops::FilterType::Triangle,
+                ),
+            };
             
-            // 3. Normalize (-1..1) for ArcFace
+            // 3. Normalize (ArcFace: (v - 127.5) / 128)
             let mut rec_array = Array4::<f32>::zeros((1, 3, 112, 112));
-             for (x, y, pixel) in rec_input.enumerate_pixels() {
-                rec_array[[0, 0, y as usize, x as usize]] = (pixel[0] as f32 - 127.5) / 128.0;
-                rec_array[[0, 1, y as usize, x as usize]] = (pixel[1] as f32 - 127.5) / 128.0;
-                rec_array[[0, 2, y as usize, x as usize]] = (pixel[2] as f32 - 127.5) / 128.0;
+            for (x, y, pixel) in rec_input.enumerate_pixels() {
+                for c in 0..3 {
+                    rec_array[[0, c, y as usize, x as usize]] = self.rec_normalization.apply(pixel[c], c);
+                }
             }
 
             // 4. Inference
@@ -249,11 +408,8 @@ impl AuthModule {
                 quality: 0.99,
             }))
         } else {
-             // Mock: return random embedding
-            Ok(Some(FaceEmbedding {
-                vector: vec![0.0; 512], // Updated to 512 for ArcFace
-                quality: 0.95,
-            }))
+             // Mock: the seeded identity with per-frame noise
+            Ok(Some(self.mock.sample()))
         }
     }
 
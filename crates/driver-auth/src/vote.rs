@@ -0,0 +1,131 @@
+//! Temporal voting over per-frame matches
+//!
+//! Per-frame recognition dithers when the similarity sits near the
+//! threshold, and a single lucky frame can match the wrong driver. The
+//! authenticated identity only changes once one driver has matched in
+//! enough of the most recent frames, so the ignition controller sees one
+//! clean transition instead of flicker.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+/// Voting parameters: `required` of the last `window` frames must match
+/// the same driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteConfig {
+    /// Frames considered (N)
+    pub window: usize,
+    /// Matching frames needed (K), at most `window`
+    pub required: usize,
+}
+
+impl Default for VoteConfig {
+    fn default() -> Self {
+        Self {
+            window: 5,
+            required: 3,
+        }
+    }
+}
+
+/// Sliding K-of-N vote on the driver identity
+#[derive(Debug, Clone)]
+pub struct AuthVote {
+    config: VoteConfig,
+    /// Per-frame matches, oldest first; `None` for frames matching no one
+    recent: VecDeque<Option<Uuid>>,
+    winner: Option<Uuid>,
+}
+
+impl AuthVote {
+    /// Create an empty vote
+    pub fn new(config: VoteConfig) -> Self {
+        let window = config.window.max(1);
+        Self {
+            config: VoteConfig {
+                window,
+                required: config.required.clamp(1, window),
+            },
+            recent: VecDeque::with_capacity(window),
+            winner: None,
+        }
+    }
+
+    /// Record one frame's match, returning the new winner if this frame
+    /// changed it
+    ///
+    /// Frames without a match count toward the window but never revoke
+    /// the current winner; only another driver winning the vote (or
+    /// [`Self::reset`]) does.
+    pub fn record(&mut self, candidate: Option<Uuid>) -> Option<Uuid> {
+        if self.recent.len() == self.config.window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(candidate);
+
+        let id = candidate?;
+        if Some(id) != self.winner && self.votes_for(id) >= self.config.required {
+            self.winner = Some(id);
+            return Some(id);
+        }
+        None
+    }
+
+    /// Frames in the window that matched `id`
+    pub fn votes_for(&self, id: Uuid) -> usize {
+        self.recent.iter().filter(|v| **v == Some(id)).count()
+    }
+
+    /// Driver currently holding the vote
+    pub fn winner(&self) -> Option<Uuid> {
+        self.winner
+    }
+
+    /// Active parameters, after clamping
+    pub fn config(&self) -> VoteConfig {
+        self.config
+    }
+
+    /// Forget all frames and the winner
+    pub fn reset(&mut self) {
+        self.recent.clear();
+        self.winner = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noisy_matches_settle_after_vote() {
+        let mut vote = AuthVote::new(VoteConfig { window: 5, required: 3 });
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+
+        // Confidence dithering around the threshold, with one misidentification
+        let frames = [Some(alice), None, Some(bob), Some(alice), None, Some(alice), Some(alice)];
+        let winners: Vec<Option<Uuid>> = frames
+            .iter()
+            .map(|&frame| {
+                vote.record(frame);
+                vote.winner()
+            })
+            .collect();
+
+        // Nothing until three Alice matches fall inside one window
+        assert_eq!(winners[..6], [None; 6]);
+        assert_eq!(winners[6], Some(alice));
+
+        // A stray Bob frame and dropouts don't flip the state
+        for frame in [Some(bob), None, Some(bob), None] {
+            assert_eq!(vote.record(frame), None);
+        }
+        assert_eq!(vote.winner(), Some(alice));
+
+        // Bob wins only with his own K of N
+        assert_eq!(vote.record(Some(bob)), Some(bob));
+        assert_eq!(vote.winner(), Some(bob));
+    }
+}
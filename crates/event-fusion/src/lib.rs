@@ -8,10 +8,21 @@
 //!
 //! Generates unified events for storage and alerting.
 
+mod reorder;
+pub mod session;
+pub mod speed;
+
+pub use session::{AttributedEvent, DriverSession};
+pub use speed::{SpeedReading, SpeedSource, SpeedSourceConfig, SpeedSourceKind};
+
 use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use common_types::{SharedClock, SystemClock};
+use reorder::ReorderBuffer;
+
 use dms::DmsAnalysis;
 use adas::AdasAnalysis;
 use camera_capture::imu::ImuData;
@@ -26,15 +37,25 @@ pub enum FusionError {
     TimestampMismatch,
 }
 
-/// Event severity
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub enum Severity {
-    Low,
-    Medium,
-    High,
-    Critical,
+/// Invalid fusion configuration
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("{field} must be {expected}, got {value}")]
+    OutOfRange {
+        field: &'static str,
+        value: f32,
+        expected: &'static str,
+    },
+
+    #[error("crash_g ({crash_g}) must exceed hard_brake_g ({hard_brake_g})")]
+    ThresholdOrder { hard_brake_g: f32, crash_g: f32 },
+
+    #[error("speed source priority must list at least one source")]
+    EmptySpeedPriority,
 }
 
+pub use common_types::Severity;
+
 /// Fused event types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum FusedEvent {
@@ -64,6 +85,7 @@ pub enum FusedEvent {
     /// Crash detected (high G-force + airbag)
     Crash {
         severity: Severity,
+        /// Peak G over the impact
         g_force: f32,
         airbag_deployed: bool,
     },
@@ -79,6 +101,28 @@ pub enum FusedEvent {
         current_kmh: u32,
         limit_kmh: u32,
     },
+
+    /// A different driver took over; events after this belong to `current`
+    DriverChanged {
+        previous: Option<String>,
+        current: Option<String>,
+    },
+}
+
+impl FusedEvent {
+    /// Severity carried by the event, if it has one
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            FusedEvent::HardBraking { severity, .. }
+            | FusedEvent::EmergencyBraking { severity, .. }
+            | FusedEvent::DrowsinessLaneDeparture { severity, .. }
+            | FusedEvent::Crash { severity, .. }
+            | FusedEvent::SustainedDistraction { severity, .. } => Some(*severity),
+            FusedEvent::Normal | FusedEvent::Speeding { .. } | FusedEvent::DriverChanged { .. } => {
+                None
+            }
+        }
+    }
 }
 
 /// OBD frame for fusion
@@ -91,9 +135,40 @@ pub struct ObdFrame {
     pub throttle: u8,
 }
 
-/// Sliding window for any data type
+/// Timestamped input for [`EventFusion::submit`]
+#[derive(Debug, Clone)]
+pub enum FusionInput {
+    Obd(ObdFrame),
+    /// DMS analysis of the cabin frame captured at `timestamp_ns`
+    Dms {
+        timestamp_ns: u64,
+        analysis: DmsAnalysis,
+    },
+    /// ADAS analysis of the road frame captured at `timestamp_ns`
+    Adas {
+        timestamp_ns: u64,
+        analysis: AdasAnalysis,
+    },
+    Imu(ImuData),
+}
+
+impl FusionInput {
+    /// Capture time of the underlying sample (ns)
+    pub fn timestamp_ns(&self) -> u64 {
+        match self {
+            Self::Obd(frame) => frame.timestamp_ns,
+            Self::Dms { timestamp_ns, .. } | Self::Adas { timestamp_ns, .. } => *timestamp_ns,
+            Self::Imu(data) => data.timestamp_ns,
+        }
+    }
+}
+
+/// Most inputs held for reordering at once (about 1 s of all sources)
+const REORDER_CAPACITY: usize = 256;
+
+/// Sliding window for any data type, stamped with arrival time
 struct SlidingWindow<T> {
-    data: VecDeque<T>,
+    data: VecDeque<(Instant, T)>,
     capacity: usize,
 }
 
@@ -105,20 +180,33 @@ impl<T> SlidingWindow<T> {
         }
     }
 
-    fn push(&mut self, item: T) {
+    fn push(&mut self, item: T, at: Instant) {
         if self.data.len() >= self.capacity {
             self.data.pop_front();
         }
-        self.data.push_back(item);
+        self.data.push_back((at, item));
     }
 
-    fn back(&self) -> Option<&T> {
-        self.data.back()
+    /// Newest item, unless it arrived more than `max_age` before `now`
+    fn fresh_back(&self, now: Instant, max_age: Duration) -> Option<&T> {
+        self.data
+            .back()
+            .filter(|(at, _)| now.duration_since(*at) <= max_age)
+            .map(|(_, item)| item)
     }
 
     #[allow(dead_code)]
     fn iter(&self) -> impl Iterator<Item = &T> {
-        self.data.iter()
+        self.data.iter().map(|(_, item)| item)
+    }
+
+    /// Items that arrived within `max_age` of `now`, newest first
+    fn fresh_rev(&self, now: Instant, max_age: Duration) -> impl Iterator<Item = &T> {
+        self.data
+            .iter()
+            .rev()
+            .take_while(move |(at, _)| now.duration_since(*at) <= max_age)
+            .map(|(_, item)| item)
     }
 }
 
@@ -141,6 +229,15 @@ pub struct EventFusion {
     
     /// Current driver ID
     driver_id: Option<String>,
+
+    /// Unified vehicle speed (OBD, GPS)
+    speed: SpeedSource,
+
+    /// Inputs waiting to enter the windows in timestamp order
+    reorder: ReorderBuffer<FusionInput>,
+
+    /// Time source for sample ageing
+    clock: SharedClock,
 }
 
 /// Fusion configuration
@@ -151,9 +248,28 @@ pub struct FusionConfig {
     
     /// G-force threshold for crash
     pub crash_g: f32,
+
+    /// How long the G-force must stay above `crash_g` (IMU time); 0 fires
+    /// on a single sample
+    pub crash_min_duration_ms: u64,
+
+    /// Impulse above `crash_g` (g·s) that fires a shorter multi-sample
+    /// impact, for hard hits briefer than `crash_min_duration_ms`
+    pub crash_min_impulse_g_s: f32,
     
     /// Speeding threshold (km/h over limit)
     pub speeding_threshold_kmh: u32,
+
+    /// Samples older than this are ignored, so a source that stops
+    /// reporting doesn't keep re-triggering its last event
+    pub max_sample_age: Duration,
+
+    /// Which speed readings to trust, and in what order
+    pub speed: SpeedSourceConfig,
+
+    /// How long (sample time) submitted inputs wait for older ones still
+    /// in flight; zero passes them straight to the windows
+    pub reorder_horizon: Duration,
 }
 
 impl Default for FusionConfig {
@@ -161,42 +277,161 @@ impl Default for FusionConfig {
         Self {
             hard_brake_g: 0.4,
             crash_g: 3.0,
+            crash_min_duration_ms: 20,
+            crash_min_impulse_g_s: 0.1,
             speeding_threshold_kmh: 10,
+            max_sample_age: Duration::from_secs(2),
+            speed: SpeedSourceConfig::default(),
+            reorder_horizon: Duration::ZERO,
         }
     }
 }
 
+impl FusionConfig {
+    /// Check ranges and invariants
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(self.hard_brake_g.is_finite() && self.hard_brake_g > 0.0) {
+            return Err(ConfigError::OutOfRange {
+                field: "hard_brake_g",
+                value: self.hard_brake_g,
+                expected: "a positive g-force",
+            });
+        }
+        if !self.crash_g.is_finite() || self.crash_g <= self.hard_brake_g {
+            return Err(ConfigError::ThresholdOrder {
+                hard_brake_g: self.hard_brake_g,
+                crash_g: self.crash_g,
+            });
+        }
+        if !(self.crash_min_impulse_g_s.is_finite() && self.crash_min_impulse_g_s > 0.0) {
+            return Err(ConfigError::OutOfRange {
+                field: "crash_min_impulse_g_s",
+                value: self.crash_min_impulse_g_s,
+                expected: "a positive impulse",
+            });
+        }
+        if self.max_sample_age.is_zero() {
+            return Err(ConfigError::OutOfRange {
+                field: "max_sample_age",
+                value: 0.0,
+                expected: "positive",
+            });
+        }
+        self.speed.validate()
+    }
+}
+
 impl EventFusion {
     /// Create new fusion engine
-    pub fn new(config: FusionConfig) -> Self {
-        Self {
+    pub fn new(config: FusionConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        let speed = SpeedSource::new(config.speed.clone())?;
+        let horizon_ns = config.reorder_horizon.as_nanos().min(u64::MAX as u128) as u64;
+        Ok(Self {
             obd_window: SlidingWindow::new(300),   // 60s @ 5Hz
             dms_window: SlidingWindow::new(150),   // 10s @ 15fps
             adas_window: SlidingWindow::new(60),   // 10s @ 6fps
             imu_window: SlidingWindow::new(1000),  // 10s @ 100Hz
             config,
             driver_id: None,
-        }
+            speed,
+            reorder: ReorderBuffer::new(horizon_ns, REORDER_CAPACITY),
+            clock: SystemClock::shared(),
+        })
+    }
+
+    /// Use `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.speed = self.speed.with_clock(clock.clone());
+        self.clock = clock;
+        self
     }
 
     /// Add OBD frame
     pub fn add_obd(&mut self, frame: ObdFrame) {
-        self.obd_window.push(frame);
+        self.submit(FusionInput::Obd(frame));
+    }
+
+    /// Add a GPS ground speed (km/h)
+    pub fn add_gps_speed(&mut self, speed_kmh: f32) {
+        self.speed.update_gps(speed_kmh);
     }
 
-    /// Add DMS analysis
+    /// Current vehicle speed from the preferred fresh source; feed this to
+    /// the DMS and ADAS stages rather than the raw OBD value
+    pub fn speed(&self) -> Option<SpeedReading> {
+        self.speed.current()
+    }
+
+    /// Add DMS analysis of unknown capture time
+    ///
+    /// It is stamped with the newest sample time seen so far, so it still
+    /// passes through the reorder buffer behind everything already received.
+    #[deprecated(note = "submit FusionInput::Dms with the cabin frame's capture time")]
     pub fn add_dms(&mut self, analysis: DmsAnalysis) {
-        self.dms_window.push(analysis);
+        let timestamp_ns = self.reorder.newest_ns();
+        self.submit(FusionInput::Dms { timestamp_ns, analysis });
     }
 
-    /// Add ADAS analysis
+    /// Add ADAS analysis of unknown capture time, stamped like
+    /// [`add_dms`](Self::add_dms)
+    #[deprecated(note = "submit FusionInput::Adas with the road frame's capture time")]
     pub fn add_adas(&mut self, analysis: AdasAnalysis) {
-        self.adas_window.push(analysis);
+        let timestamp_ns = self.reorder.newest_ns();
+        self.submit(FusionInput::Adas { timestamp_ns, analysis });
     }
 
     /// Add IMU data
     pub fn add_imu(&mut self, data: ImuData) {
-        self.imu_window.push(data);
+        self.submit(FusionInput::Imu(data));
+    }
+
+    /// Add a timestamped input through the reorder buffer
+    ///
+    /// Inputs reach the windows oldest first once the reorder horizon has
+    /// passed them; one older than an input already released is dropped
+    /// and counted in [`late_dropped`](Self::late_dropped).
+    pub fn submit(&mut self, input: FusionInput) {
+        if self.config.reorder_horizon.is_zero() {
+            self.admit(input);
+            return;
+        }
+        if !self.reorder.push(input.timestamp_ns(), input) {
+            tracing::debug!("Dropped fusion input behind the reorder horizon");
+        }
+        while let Some(ready) = self.reorder.pop_ready() {
+            self.admit(ready);
+        }
+    }
+
+    /// Release every held input, e.g. before fusing at shutdown
+    pub fn flush(&mut self) {
+        while let Some(input) = self.reorder.pop_oldest() {
+            self.admit(input);
+        }
+    }
+
+    /// Inputs dropped for arriving later than the reorder horizon
+    pub fn late_dropped(&self) -> u64 {
+        self.reorder.dropped_late()
+    }
+
+    /// Inputs held in the reorder buffer
+    pub fn pending_inputs(&self) -> usize {
+        self.reorder.len()
+    }
+
+    fn admit(&mut self, input: FusionInput) {
+        let now = self.clock.instant();
+        match input {
+            FusionInput::Obd(frame) => {
+                self.speed.update_obd(frame.speed_kmh as f32);
+                self.obd_window.push(frame, now);
+            }
+            FusionInput::Dms { analysis, .. } => self.dms_window.push(analysis, now),
+            FusionInput::Adas { analysis, .. } => self.adas_window.push(analysis, now),
+            FusionInput::Imu(data) => self.imu_window.push(data, now),
+        }
     }
 
     /// Set current driver
@@ -204,28 +439,38 @@ impl EventFusion {
         self.driver_id = driver_id;
     }
 
+    /// Current driver
+    pub fn driver(&self) -> Option<&str> {
+        self.driver_id.as_deref()
+    }
+
     /// Fuse events and return any detected incidents
     pub fn fuse(&self) -> Option<FusedEvent> {
+        let now = self.clock.instant();
+        let max_age = self.config.max_sample_age;
+        let imu_latest = self.imu_window.fresh_back(now, max_age);
+
         // Check for crash (highest priority)
-        if let Some(imu) = self.imu_window.back() {
-            if imu.g_force > self.config.crash_g {
-                return Some(FusedEvent::Crash {
-                    severity: Severity::Critical,
-                    g_force: imu.g_force,
-                    airbag_deployed: false,
-                });
-            }
+        if let Some(peak_g) = self.crash_peak(now, max_age) {
+            return Some(FusedEvent::Crash {
+                severity: Severity::Critical,
+                g_force: peak_g,
+                airbag_deployed: false,
+            });
         }
 
         // Check for hard braking
-        if let Some(imu) = self.imu_window.back() {
+        if let Some(imu) = imu_latest {
             if imu.accel_x.abs() > self.config.hard_brake_g {
-                if let Some(obd) = self.obd_window.back() {
+                if let Some(obd) = self.obd_window.fresh_back(now, max_age) {
                     if obd.brake_pedal > 80 {
                         return Some(FusedEvent::HardBraking {
                             severity: Severity::Medium,
                             decel_g: imu.accel_x.abs(),
-                            speed_before_kmh: obd.speed_kmh as f32,
+                            speed_before_kmh: self
+                                .speed
+                                .current()
+                                .map_or(obd.speed_kmh as f32, |s| s.speed_kmh),
                         });
                     }
                 }
@@ -233,7 +478,10 @@ impl EventFusion {
         }
 
         // Check for drowsiness + lane departure
-        if let (Some(dms), Some(adas)) = (self.dms_window.back(), self.adas_window.back()) {
+        if let (Some(dms), Some(adas)) = (
+            self.dms_window.fresh_back(now, max_age),
+            self.adas_window.fresh_back(now, max_age),
+        ) {
             if dms.drowsiness_level as u8 >= 2 && adas.lane_state.departing {
                 return Some(FusedEvent::DrowsinessLaneDeparture {
                     severity: Severity::High,
@@ -244,5 +492,213 @@ impl EventFusion {
 
         None
     }
+
+    /// Peak G of the newest fresh impact that qualifies as a crash
+    ///
+    /// An impact is a run of consecutive samples above `crash_g`. It counts
+    /// once it lasts `crash_min_duration_ms`, or spans several samples with
+    /// enough impulse above the threshold; a lone spike never does. The
+    /// peak is held while any sample of the run is still fresh.
+    fn crash_peak(&self, now: Instant, max_age: Duration) -> Option<f32> {
+        let crash_g = self.config.crash_g;
+        let min_duration_ns = self.config.crash_min_duration_ms.saturating_mul(1_000_000);
+
+        let mut samples = self.imu_window.fresh_rev(now, max_age);
+        loop {
+            // Next run back in time; newest first, so it starts at the back
+            let run: Vec<&ImuData> = samples
+                .by_ref()
+                .skip_while(|s| s.g_force <= crash_g)
+                .take_while(|s| s.g_force > crash_g)
+                .collect();
+            if run.is_empty() {
+                return None;
+            }
+
+            let duration_ns = run[0].timestamp_ns.saturating_sub(run[run.len() - 1].timestamp_ns);
+            let impulse_g_s: f32 = run
+                .windows(2)
+                .map(|pair| {
+                    let dt_s = pair[0].timestamp_ns.saturating_sub(pair[1].timestamp_ns) as f32 / 1e9;
+                    ((pair[0].g_force + pair[1].g_force) / 2.0 - crash_g) * dt_s
+                })
+                .sum();
+
+            if duration_ns >= min_duration_ns || impulse_g_s >= self.config.crash_min_impulse_g_s {
+                return run.iter().map(|s| s.g_force).reduce(f32::max);
+            }
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_types::MockClock;
+
+    #[test]
+    fn test_config_validation() {
+        assert!(FusionConfig::default().validate().is_ok());
+        assert!(EventFusion::new(FusionConfig::default()).is_ok());
+
+        let err = EventFusion::new(FusionConfig {
+            hard_brake_g: -0.4,
+            ..Default::default()
+        })
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "hard_brake_g must be a positive g-force, got -0.4");
+
+        let err = FusionConfig {
+            crash_g: 0.3,
+            ..Default::default()
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err, ConfigError::ThresholdOrder { hard_brake_g: 0.4, crash_g: 0.3 });
+
+        let err = FusionConfig {
+            max_sample_age: Duration::ZERO,
+            ..Default::default()
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err.to_string(), "max_sample_age must be positive, got 0");
+    }
+
+    fn imu_sample(g_force: f32, timestamp_ms: u64) -> ImuData {
+        ImuData {
+            accel_x: g_force,
+            accel_y: 0.0,
+            accel_z: 1.0,
+            gyro_x: 0.0,
+            gyro_y: 0.0,
+            gyro_z: 0.0,
+            temperature: 25.0,
+            g_force,
+            timestamp_ns: timestamp_ms * 1_000_000,
+        }
+    }
+
+    /// Millisecond timestamps of the IMU samples that entered the window
+    fn imu_order(fusion: &EventFusion) -> Vec<u64> {
+        fusion
+            .imu_window
+            .iter()
+            .map(|s| s.timestamp_ns / 1_000_000)
+            .collect()
+    }
+
+    #[test]
+    fn test_stale_imu_sample_stops_firing() {
+        let clock = MockClock::default();
+        let mut fusion = EventFusion::new(FusionConfig::default())
+            .unwrap()
+            .with_clock(clock.shared());
+
+        for t in [0, 10, 20] {
+            fusion.add_imu(imu_sample(4.5, t));
+        }
+        assert!(matches!(fusion.fuse(), Some(FusedEvent::Crash { .. })));
+
+        // IMU goes silent; the crash sample must not be reported forever
+        clock.advance(Duration::from_secs(2));
+        assert!(fusion.fuse().is_some());
+        clock.advance(Duration::from_millis(1));
+        assert!(fusion.fuse().is_none());
+    }
+
+    #[test]
+    fn test_crash_needs_sustained_impulse_and_reports_peak() {
+        let clock = MockClock::default();
+        let mut fusion = EventFusion::new(FusionConfig::default())
+            .unwrap()
+            .with_clock(clock.shared());
+
+        // 100 Hz IMU at 1 g with a single-sample glitch
+        for (t, g) in [(0, 1.0), (10, 9.0), (20, 1.0), (30, 1.0)] {
+            fusion.add_imu(imu_sample(g, t));
+        }
+        assert!(fusion.fuse().is_none());
+
+        // Impact lasting 40 ms, peaking at 7.5 g
+        for (t, g) in [(40, 3.5), (50, 6.0), (60, 7.5), (70, 5.0), (80, 3.2), (90, 1.1)] {
+            fusion.add_imu(imu_sample(g, t));
+        }
+        match fusion.fuse() {
+            Some(FusedEvent::Crash { g_force, severity, .. }) => {
+                assert_eq!(g_force, 7.5);
+                assert_eq!(severity, Severity::Critical);
+            }
+            other => panic!("expected crash, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_reorder_buffer_orders_inputs_and_drops_late_ones() {
+        let mut fusion = EventFusion::new(FusionConfig {
+            reorder_horizon: Duration::from_millis(50),
+            ..Default::default()
+        })
+        .unwrap();
+        for t in [0, 20, 10, 40, 30] {
+            fusion.submit(FusionInput::Imu(imu_sample(1.0, t)));
+        }
+        fusion.submit(FusionInput::Obd(ObdFrame {
+            timestamp_ns: 25_000_000,
+            rpm: 2_000,
+            speed_kmh: 50,
+            brake_pedal: 0,
+            throttle: 20,
+        }));
+        // Nothing is 50 ms behind the newest sample yet
+        assert!(imu_order(&fusion).is_empty());
+        assert_eq!(fusion.pending_inputs(), 6);
+
+        fusion.submit(FusionInput::Imu(imu_sample(1.0, 90)));
+        assert_eq!(imu_order(&fusion), [0, 10, 20, 30, 40]);
+        assert_eq!(fusion.speed().map(|s| s.speed_kmh), Some(50.0));
+
+        // Older than what already entered the windows
+        fusion.submit(FusionInput::Imu(imu_sample(1.0, 35)));
+        assert_eq!(fusion.late_dropped(), 1);
+
+        fusion.flush();
+        assert_eq!(imu_order(&fusion), [0, 10, 20, 30, 40, 90]);
+        assert_eq!(fusion.pending_inputs(), 0);
+    }
+
+    #[test]
+    fn test_reorder_buffer_orders_dms_among_imu() {
+        let mut fusion = EventFusion::new(FusionConfig {
+            reorder_horizon: Duration::from_millis(50),
+            ..Default::default()
+        })
+        .unwrap();
+        // The DMS result for a 30 ms frame lands before the 20 ms IMU sample
+        fusion.submit(FusionInput::Imu(imu_sample(1.0, 10)));
+        fusion.submit(FusionInput::Dms {
+            timestamp_ns: 30_000_000,
+            analysis: DmsAnalysis::default(),
+        });
+        fusion.submit(FusionInput::Imu(imu_sample(1.0, 20)));
+        fusion.submit(FusionInput::Imu(imu_sample(1.0, 70)));
+
+        // Older IMU samples enter first; the DMS result waits its turn
+        assert_eq!(imu_order(&fusion), [10, 20]);
+        assert_eq!(fusion.dms_window.iter().count(), 0);
+
+        fusion.submit(FusionInput::Imu(imu_sample(1.0, 85)));
+        assert_eq!(fusion.dms_window.iter().count(), 1);
+        assert_eq!(imu_order(&fusion), [10, 20]);
+        assert_eq!(fusion.pending_inputs(), 2);
+
+        // Untimestamped results queue behind everything received so far
+        #[allow(deprecated)]
+        fusion.add_dms(DmsAnalysis::default());
+        assert_eq!(fusion.dms_window.iter().count(), 1);
+        fusion.flush();
+        assert_eq!(imu_order(&fusion), [10, 20, 70, 85]);
+        assert_eq!(fusion.dms_window.iter().count(), 2);
+    }
+}
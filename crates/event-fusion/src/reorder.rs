@@ -0,0 +1,84 @@
+//! Timestamp reordering for fusion inputs
+//!
+//! Each source reaches fusion over its own channel with its own latency,
+//! so a 20 ms-old IMU sample can land after an OBD frame captured later.
+//! Items are held until nothing older can still be in flight, i.e. until
+//! the newest timestamp seen is a horizon past them, then released oldest
+//! first. Anything arriving behind what was already released is dropped.
+
+use std::collections::BTreeMap;
+
+/// Holds items briefly and releases them in timestamp order
+#[derive(Debug)]
+pub(crate) struct ReorderBuffer<T> {
+    horizon_ns: u64,
+    capacity: usize,
+    /// Keyed by (timestamp, arrival), so equal timestamps keep arrival order
+    held: BTreeMap<(u64, u64), T>,
+    arrivals: u64,
+    newest_ns: u64,
+    released_ns: Option<u64>,
+    dropped_late: u64,
+}
+
+impl<T> ReorderBuffer<T> {
+    /// Buffer holding at most `capacity` items for up to `horizon_ns`
+    pub(crate) fn new(horizon_ns: u64, capacity: usize) -> Self {
+        Self {
+            horizon_ns,
+            capacity: capacity.max(1),
+            held: BTreeMap::new(),
+            arrivals: 0,
+            newest_ns: 0,
+            released_ns: None,
+            dropped_late: 0,
+        }
+    }
+
+    /// Hold `item`; `false` if it is older than what was already released
+    /// and has been dropped
+    pub(crate) fn push(&mut self, timestamp_ns: u64, item: T) -> bool {
+        if self.released_ns.is_some_and(|released| timestamp_ns < released) {
+            self.dropped_late += 1;
+            return false;
+        }
+        self.newest_ns = self.newest_ns.max(timestamp_ns);
+        self.held.insert((timestamp_ns, self.arrivals), item);
+        self.arrivals += 1;
+        true
+    }
+
+    /// Oldest item once it is past the horizon, or early when the buffer
+    /// is over capacity
+    pub(crate) fn pop_ready(&mut self) -> Option<T> {
+        let &(timestamp_ns, _) = self.held.keys().next()?;
+        let ready = timestamp_ns.saturating_add(self.horizon_ns) <= self.newest_ns;
+        if !ready && self.held.len() <= self.capacity {
+            return None;
+        }
+        self.pop_oldest()
+    }
+
+    /// Oldest item regardless of the horizon
+    pub(crate) fn pop_oldest(&mut self) -> Option<T> {
+        let ((timestamp_ns, _), item) = self.held.pop_first()?;
+        self.released_ns = Some(timestamp_ns);
+        Some(item)
+    }
+
+    /// Newest timestamp pushed so far, 0 before the first
+    pub(crate) fn newest_ns(&self) -> u64 {
+        self.newest_ns
+    }
+
+    /// Items dropped for arriving too late
+    pub(crate) fn dropped_late(&self) -> u64 {
+        self.dropped_late
+    }
+
+    /// Items currently held
+    pub(crate) fn len(&self) -> usize {
+        self.held.len()
+    }
+}
+
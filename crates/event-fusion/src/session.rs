@@ -0,0 +1,218 @@
+//! Driver Sessions
+//!
+//! Fleet vehicles change hands mid-shift. Everything tracked for the
+//! outgoing driver (eye-closure and look-away timers, PERCLOS history,
+//! the learned neutral head pose) would otherwise bleed into the first
+//! minutes of the incoming driver's record, and their events would be
+//! attributed to the wrong person. [`DriverSession`] owns the DMS and
+//! fusion stages together so a hand-off updates both in one step.
+
+use std::time::UNIX_EPOCH;
+
+use common_types::{SharedClock, SystemClock};
+use dms::DmsModule;
+use uuid::Uuid;
+
+use crate::{EventFusion, FusedEvent, SpeedReading};
+
+/// A fused event tagged with the driver and segment it belongs to
+#[derive(Debug, Clone)]
+pub struct AttributedEvent {
+    /// Driver behind the wheel, `None` when unidentified
+    pub driver_id: Option<Uuid>,
+    /// Trip segment the event falls in; each hand-off starts a new one
+    pub segment: u32,
+    pub event: FusedEvent,
+}
+
+/// DMS and fusion state for whoever is currently driving
+pub struct DriverSession {
+    dms: DmsModule,
+    fusion: EventFusion,
+    driver: Option<Uuid>,
+    segment: u32,
+    segment_started_ms: u64,
+    clock: SharedClock,
+}
+
+impl DriverSession {
+    /// Start the first segment with an unidentified driver
+    pub fn new(dms: DmsModule, fusion: EventFusion) -> Self {
+        let clock = SystemClock::shared();
+        Self {
+            dms,
+            fusion,
+            driver: None,
+            segment: 0,
+            segment_started_ms: unix_ms(&clock),
+            clock,
+        }
+    }
+
+    /// Use `clock` for segment start times
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.segment_started_ms = unix_ms(&clock);
+        self.clock = clock;
+        self
+    }
+
+    /// Hand the vehicle to `new_driver`
+    ///
+    /// Clears the DMS timers and fatigue accumulators, relearns the
+    /// neutral head pose, points fusion at the new driver and opens a new
+    /// segment. The returned `DriverChanged` event is the first event of
+    /// that segment.
+    pub fn handoff(&mut self, new_driver: Option<Uuid>) -> AttributedEvent {
+        let previous = std::mem::replace(&mut self.driver, new_driver);
+
+        self.dms.reset_state();
+        self.dms.recalibrate_pose();
+        self.fusion.set_driver(new_driver.map(|id| id.to_string()));
+        self.segment += 1;
+        self.segment_started_ms = unix_ms(&self.clock);
+
+        tracing::info!(
+            "Driver hand-off {:?} -> {:?}, segment {}",
+            previous,
+            new_driver,
+            self.segment
+        );
+        self.attribute(FusedEvent::DriverChanged {
+            previous: previous.map(|id| id.to_string()),
+            current: new_driver.map(|id| id.to_string()),
+        })
+    }
+
+    /// Fuse the current windows, attributing the result to the driver
+    pub fn fuse(&self) -> Option<AttributedEvent> {
+        self.fusion.fuse().map(|event| self.attribute(event))
+    }
+
+    /// Pass the unified vehicle speed to the DMS stage
+    ///
+    /// Returns the reading so the caller can hand the same value to the
+    /// ADAS stage; `None` leaves the DMS with its last known speed.
+    pub fn sync_speed(&mut self) -> Option<SpeedReading> {
+        let reading = self.fusion.speed()?;
+        self.dms.set_speed(reading.speed_kmh);
+        Some(reading)
+    }
+
+    /// Current driver
+    pub fn driver(&self) -> Option<Uuid> {
+        self.driver
+    }
+
+    /// Current segment number, 0 before the first hand-off
+    pub fn segment(&self) -> u32 {
+        self.segment
+    }
+
+    /// When the current segment started (Unix ms)
+    pub fn segment_started_ms(&self) -> u64 {
+        self.segment_started_ms
+    }
+
+    /// DMS stage, for analyzing cabin frames
+    pub fn dms_mut(&mut self) -> &mut DmsModule {
+        &mut self.dms
+    }
+
+    /// DMS stage
+    pub fn dms(&self) -> &DmsModule {
+        &self.dms
+    }
+
+    /// Fusion stage, for feeding OBD, ADAS and IMU data
+    pub fn fusion_mut(&mut self) -> &mut EventFusion {
+        &mut self.fusion
+    }
+
+    fn attribute(&self, event: FusedEvent) -> AttributedEvent {
+        AttributedEvent {
+            driver_id: self.driver,
+            segment: self.segment,
+            event,
+        }
+    }
+}
+
+fn unix_ms(clock: &SharedClock) -> u64 {
+    clock
+        .now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camera_capture::imu::ImuData;
+    use camera_capture::VideoFrame;
+    use dms::DmsConfig;
+
+    use crate::FusionConfig;
+
+    fn imu_sample(g_force: f32, timestamp_ms: u64) -> ImuData {
+        ImuData {
+            accel_x: g_force,
+            accel_y: 0.0,
+            accel_z: 1.0,
+            gyro_x: 0.0,
+            gyro_y: 0.0,
+            gyro_z: 0.0,
+            temperature: 25.0,
+            g_force,
+            timestamp_ns: timestamp_ms * 1_000_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handoff_resets_driver_state_and_attribution() {
+        // A zero gaze threshold makes every frame count as looking away
+        let dms = DmsModule::new(DmsConfig {
+            gaze_threshold_degrees: 0.0,
+            ..Default::default()
+        })
+        .unwrap();
+        let fusion = EventFusion::new(FusionConfig::default()).unwrap();
+        let mut session = DriverSession::new(dms, fusion);
+
+        let first = Uuid::new_v4();
+        session.handoff(Some(first));
+        for i in 0..30u64 {
+            let frame = VideoFrame::new(vec![0; 64 * 48 * 3], 64, 48, i * 33_000_000, i as u32);
+            session.dms_mut().analyze(&frame).await.unwrap();
+        }
+        let state = session.dms().driver_state();
+        assert!(state.distraction_ms > 0);
+        assert!(!state.eye_openness_history.is_empty());
+
+        let second = Uuid::new_v4();
+        let changed = session.handoff(Some(second));
+        assert_eq!(changed.segment, 2);
+        match changed.event {
+            FusedEvent::DriverChanged { previous, current } => {
+                assert_eq!(previous, Some(first.to_string()));
+                assert_eq!(current, Some(second.to_string()));
+            }
+            other => panic!("expected driver change, got {other:?}"),
+        }
+
+        let state = session.dms().driver_state();
+        assert_eq!(state.distraction_ms, 0);
+        assert_eq!(state.eyes_closed_ms, 0);
+        assert!(state.eye_openness_history.is_empty());
+        assert!(state.blink_durations_ms.is_empty());
+        assert_eq!(session.fusion_mut().driver(), Some(second.to_string().as_str()));
+
+        for t in [0, 10, 20] {
+            session.fusion_mut().add_imu(imu_sample(4.5, t));
+        }
+        let crash = session.fuse().unwrap();
+        assert!(matches!(crash.event, FusedEvent::Crash { .. }));
+        assert_eq!(crash.driver_id, Some(second));
+        assert_eq!(crash.segment, 2);
+    }
+}
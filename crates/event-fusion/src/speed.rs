@@ -0,0 +1,218 @@
+//! Vehicle Speed Source
+//!
+//! OBD PID 0x0D is the most accurate speed the box sees, but it goes
+//! missing during ECU dropouts and some vehicles never report it. GPS
+//! speed is coarser and lags in tunnels, yet it keeps speed-dependent
+//! logic (distraction timeouts, headway, hard-braking reports) working
+//! when OBD is silent. [`SpeedSource`] keeps the latest reading from each
+//! and answers with the first fresh one in the configured priority.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use common_types::{SharedClock, SystemClock};
+
+use crate::ConfigError;
+
+/// Where a speed reading came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpeedSourceKind {
+    /// Vehicle speed PID
+    Obd,
+    /// GPS receiver ground speed
+    Gps,
+    /// Weighted blend of OBD and GPS, only while both are fresh
+    Fused,
+}
+
+/// Speed source selection
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeedSourceConfig {
+    /// Sources to try, most preferred first
+    pub priority: Vec<SpeedSourceKind>,
+    /// OBD speed older than this is stale
+    pub obd_max_age: Duration,
+    /// GPS speed older than this is stale
+    pub gps_max_age: Duration,
+    /// Share of the OBD reading in the fused estimate (0-1)
+    pub obd_weight: f32,
+}
+
+impl Default for SpeedSourceConfig {
+    fn default() -> Self {
+        Self {
+            priority: vec![SpeedSourceKind::Obd, SpeedSourceKind::Gps],
+            obd_max_age: Duration::from_secs(1),
+            gps_max_age: Duration::from_secs(2),
+            obd_weight: 0.7,
+        }
+    }
+}
+
+impl SpeedSourceConfig {
+    /// Check ranges and invariants
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.priority.is_empty() {
+            return Err(ConfigError::EmptySpeedPriority);
+        }
+        for (field, age) in [
+            ("obd_max_age_s", self.obd_max_age),
+            ("gps_max_age_s", self.gps_max_age),
+        ] {
+            if age.is_zero() {
+                return Err(ConfigError::OutOfRange {
+                    field,
+                    value: 0.0,
+                    expected: "positive",
+                });
+            }
+        }
+        if !(0.0..=1.0).contains(&self.obd_weight) {
+            return Err(ConfigError::OutOfRange {
+                field: "obd_weight",
+                value: self.obd_weight,
+                expected: "between 0 and 1",
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Unified vehicle speed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedReading {
+    pub speed_kmh: f32,
+    pub source: SpeedSourceKind,
+}
+
+/// Latest OBD and GPS speeds, resolved by priority
+pub struct SpeedSource {
+    config: SpeedSourceConfig,
+    obd: Option<(Instant, f32)>,
+    gps: Option<(Instant, f32)>,
+    clock: SharedClock,
+}
+
+impl SpeedSource {
+    /// Create a source with no readings yet
+    pub fn new(config: SpeedSourceConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            obd: None,
+            gps: None,
+            clock: SystemClock::shared(),
+        })
+    }
+
+    /// Use `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record an OBD vehicle speed
+    pub fn update_obd(&mut self, speed_kmh: f32) {
+        self.obd = Some((self.clock.instant(), speed_kmh.max(0.0)));
+    }
+
+    /// Record a GPS ground speed
+    pub fn update_gps(&mut self, speed_kmh: f32) {
+        self.gps = Some((self.clock.instant(), speed_kmh.max(0.0)));
+    }
+
+    /// Speed from the most preferred source that is fresh, `None` when
+    /// every configured source is stale or has never reported
+    pub fn current(&self) -> Option<SpeedReading> {
+        let now = self.clock.instant();
+        let fresh = |reading: Option<(Instant, f32)>, max_age: Duration| {
+            reading
+                .filter(|(at, _)| now.duration_since(*at) <= max_age)
+                .map(|(_, speed)| speed)
+        };
+        let obd = fresh(self.obd, self.config.obd_max_age);
+        let gps = fresh(self.gps, self.config.gps_max_age);
+
+        self.config.priority.iter().find_map(|&source| {
+            let speed_kmh = match source {
+                SpeedSourceKind::Obd => obd?,
+                SpeedSourceKind::Gps => gps?,
+                SpeedSourceKind::Fused => {
+                    let weight = self.config.obd_weight;
+                    obd? * weight + gps? * (1.0 - weight)
+                }
+            };
+            Some(SpeedReading { speed_kmh, source })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_types::MockClock;
+
+    #[test]
+    fn test_prefers_fresh_obd_and_falls_back_to_gps() {
+        let clock = MockClock::default();
+        let mut speed = SpeedSource::new(SpeedSourceConfig::default())
+            .unwrap()
+            .with_clock(clock.shared());
+        assert_eq!(speed.current(), None);
+
+        speed.update_obd(62.0);
+        speed.update_gps(60.0);
+        assert_eq!(
+            speed.current(),
+            Some(SpeedReading {
+                speed_kmh: 62.0,
+                source: SpeedSourceKind::Obd
+            })
+        );
+
+        // ECU dropout: OBD goes stale, GPS keeps reporting
+        clock.advance(Duration::from_millis(1_500));
+        speed.update_gps(58.0);
+        assert_eq!(
+            speed.current(),
+            Some(SpeedReading {
+                speed_kmh: 58.0,
+                source: SpeedSourceKind::Gps
+            })
+        );
+
+        // OBD back
+        speed.update_obd(57.0);
+        assert_eq!(speed.current().unwrap().source, SpeedSourceKind::Obd);
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(speed.current(), None);
+
+        // Fused first: blended while both are fresh, else the next choice
+        let mut fused = SpeedSource::new(SpeedSourceConfig {
+            priority: vec![SpeedSourceKind::Fused, SpeedSourceKind::Gps],
+            obd_weight: 0.5,
+            ..Default::default()
+        })
+        .unwrap()
+        .with_clock(clock.shared());
+        fused.update_obd(60.0);
+        fused.update_gps(50.0);
+        assert_eq!(
+            fused.current(),
+            Some(SpeedReading {
+                speed_kmh: 55.0,
+                source: SpeedSourceKind::Fused
+            })
+        );
+        clock.advance(Duration::from_millis(1_500));
+        assert_eq!(fused.current().unwrap().source, SpeedSourceKind::Gps);
+
+        assert!(SpeedSource::new(SpeedSourceConfig {
+            priority: Vec::new(),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -4,18 +4,7 @@ use feature_engine::FeatureVector;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
-/// Alert severity levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum Severity {
-    /// Low severity - log only
-    Low,
-    /// Medium severity - dashboard alert
-    Medium,
-    /// High severity - audible beep
-    High,
-    /// Critical - immediate attention
-    Critical,
-}
+pub use common_types::Severity;
 
 /// Fault types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -112,6 +101,8 @@ pub struct FallbackThresholds {
     pub rpm_std_dev_threshold: f64,
     /// Engine load threshold for high load warning (%)
     pub engine_load_threshold: f64,
+    /// O2 degradation score above which the sensor is reported (0.0-1.0)
+    pub o2_degradation_threshold: f64,
 }
 
 impl Default for FallbackThresholds {
@@ -121,6 +112,7 @@ impl Default for FallbackThresholds {
             coolant_critical: 110.0,
             rpm_std_dev_threshold: 500.0,
             engine_load_threshold: 90.0,
+            o2_degradation_threshold: 0.7,
         }
     }
 }
@@ -165,6 +157,14 @@ impl FallbackEngine {
             alerts.push(Alert::new(Fault::Misfire, Severity::Medium));
         }
 
+        // Check for a lazy or stuck O2 sensor
+        if let Some(score) = features.o2_degradation_score {
+            if score > self.thresholds.o2_degradation_threshold {
+                debug!("O2 sensor degraded: score = {:.2}", score);
+                alerts.push(Alert::new(Fault::O2SensorDegradation, Severity::Low));
+            }
+        }
+
         // Check rapid temperature rise
         if features.coolant_temp_rate > 2.0 {
             debug!("Rapid temp rise: {}°C/sample", features.coolant_temp_rate);
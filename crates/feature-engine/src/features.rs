@@ -1,14 +1,128 @@
 //! Feature Vector Assembly
 
-use crate::fft::FftAnalyzer;
+use crate::fft::{FftAnalyzer, Resampling};
+use crate::gaps::{GapFillMode, GapFiller};
+use crate::gear::{GearConfig, GearEstimator};
+use crate::o2::{O2Analyzer, O2Config};
 use crate::statistics::StatisticalFeatures;
+use crate::vehicle::VehicleProfile;
 use ring_buffer::{RingBuffer, SensorFrame};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
 use tracing::debug;
 
 /// Number of features in the vector (45 as per blueprint)
 pub const FEATURE_DIMENSION: usize = 45;
 
+/// Length of the primary statistics window (ms)
+pub const WINDOW_30S_MS: u64 = 30_000;
+
+/// Signals in the order their features appear in the vector
+const SIGNALS: [&str; 5] = ["rpm", "coolant_temp", "speed", "engine_load", "maf"];
+/// Per-signal statistics, in vector order
+const STATISTICS: [&str; 4] = ["mean", "std_dev", "skewness", "kurtosis"];
+/// Per-signal FFT band powers, in vector order
+const FFT_BANDS: [&str; 3] = ["power_low", "power_medium", "power_high"];
+/// Per-signal temporal features, in vector order
+const TEMPORAL: [&str; 2] = ["rate_of_change", "zero_crossings"];
+
+/// Names of the vector positions, `<signal>_<feature>`, in vector order
+fn layout_names() -> &'static [&'static str] {
+    static NAMES: OnceLock<Vec<&'static str>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        [&STATISTICS[..], &FFT_BANDS[..], &TEMPORAL[..]]
+            .iter()
+            .flat_map(|group| {
+                SIGNALS.iter().flat_map(move |signal| {
+                    group.iter().map(move |feature| {
+                        // Built once per process; the names live as long as the layout
+                        &*Box::leak(format!("{signal}_{feature}").into_boxed_str())
+                    })
+                })
+            })
+            .collect()
+    })
+}
+
+/// Feature extraction configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureConfig {
+    /// Sample rate of the incoming frames (Hz)
+    pub sample_rate: f64,
+    /// Minimum fill of the 30s window (0.0-1.0) before a feature vector is emitted
+    pub min_window_fill: f64,
+    /// How gaps in the 30s window are reconstructed before computing features
+    #[serde(default)]
+    pub gap_fill: GapFillMode,
+    /// Calibrated gear ratios; gear estimation is skipped when unset
+    #[serde(default)]
+    pub gear: Option<GearConfig>,
+    /// Engine constants for MAF-derived features
+    #[serde(default)]
+    pub vehicle: VehicleProfile,
+    /// Thresholds for the O2 degradation score
+    #[serde(default)]
+    pub o2: O2Config,
+    /// How jittered PID samples are put on the `sample_rate` grid before
+    /// computing band powers
+    #[serde(default)]
+    pub resampling: Resampling,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 5.0,
+            min_window_fill: 0.5,
+            gap_fill: GapFillMode::None,
+            gear: None,
+            vehicle: VehicleProfile::default(),
+            o2: O2Config::default(),
+            resampling: Resampling::default(),
+        }
+    }
+}
+
+impl FeatureConfig {
+    /// Number of frames the 30s window must hold before features are ready
+    pub fn min_window_frames(&self) -> usize {
+        let expected = self.sample_rate * (WINDOW_30S_MS as f64 / 1000.0);
+        (expected * self.min_window_fill.clamp(0.0, 1.0)).ceil().max(1.0) as usize
+    }
+
+    /// Name of each entry of [`FeatureVector::values`], in the same order
+    pub fn feature_names(&self) -> Vec<&'static str> {
+        layout_names().to_vec()
+    }
+
+    /// Stable hash of the feature layout this configuration produces
+    ///
+    /// Covers feature order, dimension, window and sample rate (which sets
+    /// the FFT band edges), so a model trained on one layout can detect it
+    /// is being fed another. Settings that don't change the layout, such
+    /// as gap filling, are left out.
+    pub fn schema_hash(&self) -> String {
+        let mut layout = format!(
+            "dim={};window_ms={};sample_rate={}",
+            FEATURE_DIMENSION, WINDOW_30S_MS, self.sample_rate
+        );
+        for (group, names) in [
+            ("stats", &STATISTICS[..]),
+            ("fft", &FFT_BANDS[..]),
+            ("temporal", &TEMPORAL[..]),
+        ] {
+            layout.push_str(&format!(";{}={}:{}", group, SIGNALS.join(","), names.join(",")));
+        }
+
+        // FNV-1a: unlike `DefaultHasher`, stable across Rust releases
+        let hash = layout.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        format!("{hash:016x}")
+    }
+}
+
 /// Feature vector for ML inference
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureVector {
@@ -26,6 +140,18 @@ pub struct FeatureVector {
     pub rpm_mean: f64,
     /// RPM std dev
     pub rpm_std_dev: f64,
+    /// Estimated gear at the end of the window, if gear ratios are configured
+    pub estimated_gear: Option<u8>,
+    /// Engine speed persistently too high for the engaged gear
+    pub clutch_slip: bool,
+    /// Mean fuel consumption over the 30s window (L/h)
+    pub fuel_rate_l_h: f64,
+    /// Worst O2 sensor degradation over the 30s window (0.0 healthy to 1.0
+    /// degraded), `None` when no narrowband sensor is reported
+    pub o2_degradation_score: Option<f64>,
+    /// Sample rate the FFT band powers were computed at (Hz): the
+    /// configured rate when resampling, else the measured one
+    pub fft_sample_rate_hz: f64,
 }
 
 impl Default for FeatureVector {
@@ -37,39 +163,205 @@ impl Default for FeatureVector {
             coolant_temp_rate: 0.0,
             rpm_mean: 0.0,
             rpm_std_dev: 0.0,
+            estimated_gear: None,
+            clutch_slip: false,
+            fuel_rate_l_h: 0.0,
+            o2_degradation_score: None,
+            fft_sample_rate_hz: 0.0,
         }
     }
 }
 
+impl FeatureVector {
+    /// Values keyed by feature name, for self-describing export
+    pub fn to_labeled_map(&self) -> BTreeMap<&'static str, f64> {
+        layout_names().iter().copied().zip(self.values.iter().copied()).collect()
+    }
+
+    /// CSV header matching [`Self::to_csv_row`]
+    pub fn csv_header() -> String {
+        let mut header = String::from("timestamp_ms");
+        for name in layout_names() {
+            header.push(',');
+            header.push_str(name);
+        }
+        header
+    }
+
+    /// One CSV row: the timestamp followed by the values in layout order
+    pub fn to_csv_row(&self) -> String {
+        let mut row = self.timestamp_ms.to_string();
+        for value in &self.values {
+            row.push(',');
+            row.push_str(&value.to_string());
+        }
+        row
+    }
+}
 
 /// Feature extractor that processes sensor frames
 pub struct FeatureExtractor {
     /// FFT analyzer
     fft_analyzer: FftAnalyzer,
-    /// Sample rate (Hz)
-    sample_rate: f64,
+    /// Configuration
+    config: FeatureConfig,
+    /// Gap detector/filler matching the configured cadence
+    gap_filler: GapFiller,
+    /// Gaps found in the most recent 30s window
+    last_gap_count: usize,
+    /// Gear classifier, when gear ratios are configured
+    gear_estimator: Option<GearEstimator>,
+    /// O2 sensor switching analysis
+    o2_analyzer: O2Analyzer,
+    /// Last vector extracted from a buffer, with the buffer state it came from
+    cache: Option<(BufferState, FeatureVector)>,
+    /// Full extractions performed (cache hits excluded)
+    computations: u64,
+}
+
+/// Windows read by [`FeatureExtractor::compute`] (ms)
+const WINDOWS_MS: [u64; 3] = [WINDOW_30S_MS, 60_000, 300_000];
+
+/// Identifies the contents of a ring buffer's windows between extractions
+///
+/// With nothing pushed, a window only changes as frames age out of its
+/// start, which moves the oldest timestamp still inside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BufferState {
+    total_written: usize,
+    newest_ms: Option<u64>,
+    window_oldest_ms: [Option<u64>; 3],
+}
+
+impl BufferState {
+    fn of(buffer: &RingBuffer) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let len = buffer.len();
+        let window_oldest_ms = WINDOWS_MS.map(|duration_ms| {
+            let cutoff = now.saturating_sub(duration_ms);
+            // Timestamps fall with age, so the frames inside the window are
+            // a prefix; bisect for its end rather than copying the buffer
+            let (mut inside, mut outside) = (0, len);
+            while inside < outside {
+                let mid = (inside + outside) / 2;
+                if buffer.timestamp_at(mid).is_some_and(|t| t >= cutoff) {
+                    inside = mid + 1;
+                } else {
+                    outside = mid;
+                }
+            }
+            inside.checked_sub(1).and_then(|age| buffer.timestamp_at(age))
+        });
+        Self {
+            total_written: buffer.total_written(),
+            newest_ms: buffer.timestamp_at(0),
+            window_oldest_ms,
+        }
+    }
 }
 
 impl FeatureExtractor {
     /// Create a new feature extractor
     pub fn new(sample_rate: f64) -> Self {
-        Self {
-            fft_analyzer: FftAnalyzer::new(sample_rate),
+        Self::with_config(FeatureConfig {
             sample_rate,
+            ..Default::default()
+        })
+    }
+
+    /// Create a feature extractor with explicit configuration
+    pub fn with_config(config: FeatureConfig) -> Self {
+        Self {
+            fft_analyzer: FftAnalyzer::new(config.sample_rate).with_resampling(config.resampling),
+            gap_filler: GapFiller::for_sample_rate(config.sample_rate, config.gap_fill),
+            last_gap_count: 0,
+            gear_estimator: config.gear.clone().map(GearEstimator::new),
+            o2_analyzer: O2Analyzer::new(config.o2.clone()),
+            cache: None,
+            computations: 0,
+            config,
         }
     }
 
+    /// Get the active configuration
+    pub fn config(&self) -> &FeatureConfig {
+        &self.config
+    }
+
+    /// Number of gaps detected in the last extracted 30s window
+    pub fn gap_count(&self) -> usize {
+        self.last_gap_count
+    }
+
+    /// Number of full extractions performed, not counting cache hits
+    pub fn computations(&self) -> u64 {
+        self.computations
+    }
+
+    /// Check whether the buffer holds enough recent frames to extract features
+    pub fn is_ready(&self, buffer: &RingBuffer) -> bool {
+        buffer.read_window(WINDOW_30S_MS).len() >= self.config.min_window_frames()
+    }
+
     /// Extract features from the ring buffer
-    pub fn extract(&mut self, buffer: &RingBuffer) -> FeatureVector {
+    ///
+    /// Returns `None` until the 30s window holds at least
+    /// [`FeatureConfig::min_window_frames`] frames, so statistics are never
+    /// computed over a handful of samples early in a trip. Callers should
+    /// skip inference for that cycle.
+    ///
+    /// If no frame has been pushed or aged out of a window since the
+    /// previous extraction, the previous vector is returned without
+    /// recomputing it.
+    pub fn extract(&mut self, buffer: &RingBuffer) -> Option<FeatureVector> {
+        let state = BufferState::of(buffer);
+        if let Some((cached_state, features)) = &self.cache {
+            if *cached_state == state {
+                return Some(features.clone());
+            }
+        }
+
+        let features = self.compute(buffer)?;
+        self.cache = Some((state, features.clone()));
+        Some(features)
+    }
+
+    /// Compute the feature vector over the buffer's current windows
+    fn compute(&mut self, buffer: &RingBuffer) -> Option<FeatureVector> {
         let timestamp_ms = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_millis() as u64)
             .unwrap_or(0);
 
         // Get frames for different windows
-        let frames_30s = buffer.read_window(30_000);
-        let frames_60s = buffer.read_window(60_000);
-        let frames_300s = buffer.read_window(300_000);
+        let frames_30s = buffer.read_window(WINDOW_30S_MS);
+        let required = self.config.min_window_frames();
+        if frames_30s.len() < required {
+            debug!(
+                "Feature window not ready: {}/{} frames",
+                frames_30s.len(),
+                required
+            );
+            return None;
+        }
+
+        // Readiness is judged on received frames; filling happens afterwards
+        let filled = self.gap_filler.fill(&frames_30s);
+        self.last_gap_count = filled.gap_count();
+        if filled.gap_count() > 0 {
+            debug!(
+                "{} gap(s) in 30s window, {} frame(s) synthesized",
+                filled.gap_count(),
+                filled.interpolated_count()
+            );
+        }
+        let frames_30s = filled.frames;
+
+        let frames_60s = buffer.read_window(WINDOWS_MS[1]);
+        let frames_300s = buffer.read_window(WINDOWS_MS[2]);
 
         debug!(
             "Extracting features: 30s={}, 60s={}, 300s={} frames",
@@ -125,27 +417,29 @@ impl FeatureExtractor {
         values[idx] = maf_stats_30s.kurtosis; idx += 1;
 
         // Frequency features (15 total: 3 bands × 5 signals)
-        let rpm_fft = self.fft_analyzer.analyze(&rpm_30s);
+        // Resampled on frame timestamps since the real PID rate varies
+        let ts_30s: Vec<u64> = frames_30s.iter().map(|f| f.timestamp_ms).collect();
+        let rpm_fft = self.fft_analyzer.analyze_timestamped(&ts_30s, &rpm_30s);
         values[idx] = rpm_fft.power_low; idx += 1;
         values[idx] = rpm_fft.power_medium; idx += 1;
         values[idx] = rpm_fft.power_high; idx += 1;
 
-        let coolant_fft = self.fft_analyzer.analyze(&coolant_30s);
+        let coolant_fft = self.fft_analyzer.analyze_timestamped(&ts_30s, &coolant_30s);
         values[idx] = coolant_fft.power_low; idx += 1;
         values[idx] = coolant_fft.power_medium; idx += 1;
         values[idx] = coolant_fft.power_high; idx += 1;
 
-        let speed_fft = self.fft_analyzer.analyze(&speed_30s);
+        let speed_fft = self.fft_analyzer.analyze_timestamped(&ts_30s, &speed_30s);
         values[idx] = speed_fft.power_low; idx += 1;
         values[idx] = speed_fft.power_medium; idx += 1;
         values[idx] = speed_fft.power_high; idx += 1;
 
-        let load_fft = self.fft_analyzer.analyze(&load_30s);
+        let load_fft = self.fft_analyzer.analyze_timestamped(&ts_30s, &load_30s);
         values[idx] = load_fft.power_low; idx += 1;
         values[idx] = load_fft.power_medium; idx += 1;
         values[idx] = load_fft.power_high; idx += 1;
 
-        let maf_fft = self.fft_analyzer.analyze(&maf_30s);
+        let maf_fft = self.fft_analyzer.analyze_timestamped(&ts_30s, &maf_30s);
         values[idx] = maf_fft.power_low; idx += 1;
         values[idx] = maf_fft.power_medium; idx += 1;
         values[idx] = maf_fft.power_high; idx += 1;
@@ -162,23 +456,43 @@ impl FeatureExtractor {
         values[idx] = maf_stats_30s.rate_of_change; idx += 1;
         values[idx] = maf_stats_30s.zero_crossings as f64;
 
-        FeatureVector {
+        let gear = self
+            .gear_estimator
+            .as_ref()
+            .map(|estimator| estimator.estimate(&frames_30s))
+            .unwrap_or_default();
+
+        // Diesels report no narrowband sensor; don't score a zero trace
+        let o2_degradation_score = if self.config.vehicle.has_lambda_pids() {
+            self.o2_analyzer.assess(&frames_30s).degradation_score()
+        } else {
+            None
+        };
+
+        self.computations += 1;
+        Some(FeatureVector {
             values,
             timestamp_ms,
             coolant_temp_mean_30s: coolant_stats_30s.mean,
             coolant_temp_rate: coolant_stats_30s.rate_of_change,
             rpm_mean: rpm_stats_30s.mean,
             rpm_std_dev: rpm_stats_30s.std_dev,
-        }
+            estimated_gear: gear.gear,
+            clutch_slip: gear.clutch_slip,
+            fuel_rate_l_h: self.config.vehicle.fuel_rate_l_per_h(maf_stats_30s.mean),
+            o2_degradation_score,
+            fft_sample_rate_hz: rpm_fft.effective_sample_rate,
+        })
     }
 
     /// Extract features from a slice of frames directly
-    pub fn extract_from_frames(&mut self, frames: &[SensorFrame]) -> FeatureVector {
+    pub fn extract_from_frames(&mut self, frames: &[SensorFrame]) -> Option<FeatureVector> {
         let buffer = RingBuffer::new(frames.len().max(1));
         for frame in frames {
             buffer.push(frame.clone());
         }
-        self.extract(&buffer)
+        // A throwaway buffer's state says nothing about the frames, so skip the cache
+        self.compute(&buffer)
     }
 }
 
@@ -186,13 +500,20 @@ impl FeatureExtractor {
 mod tests {
     use super::*;
 
+    fn now_ms() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
     #[test]
     fn test_feature_extraction() {
         let mut extractor = FeatureExtractor::new(5.0);
-        let buffer = RingBuffer::new(100);
+        let buffer = RingBuffer::new(200);
         
         // Add some test frames
-        for i in 0..50 {
+        for i in 0..100 {
             buffer.push(SensorFrame {
                 timestamp_ms: std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -207,11 +528,193 @@ mod tests {
             });
         }
         
-        let features = extractor.extract(&buffer);
+        let features = extractor.extract(&buffer).expect("window should be ready");
         
         // Check that features are populated
         assert!(features.rpm_mean > 0.0);
         assert!(features.coolant_temp_mean_30s > 0.0);
         assert_eq!(features.values.len(), FEATURE_DIMENSION);
     }
+
+    #[test]
+    fn test_extraction_waits_for_window_fill() {
+        let mut extractor = FeatureExtractor::with_config(FeatureConfig {
+            sample_rate: 5.0,
+            min_window_fill: 0.2, // 30 of 150 expected frames
+            ..Default::default()
+        });
+        assert_eq!(extractor.config().min_window_frames(), 30);
+
+        let buffer = RingBuffer::new(200);
+        let push = |n: usize| {
+            for _ in 0..n {
+                buffer.push(SensorFrame {
+                    timestamp_ms: now_ms(),
+                    rpm: 2000,
+                    coolant_temp: 85,
+                    ..Default::default()
+                });
+            }
+        };
+
+        push(3);
+        assert!(!extractor.is_ready(&buffer));
+        assert!(extractor.extract(&buffer).is_none());
+
+        push(26);
+        assert!(extractor.extract(&buffer).is_none());
+
+        push(1);
+        assert!(extractor.is_ready(&buffer));
+        let features = extractor.extract(&buffer).expect("window should be ready");
+        assert!((features.rpm_mean - 2000.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_gap_is_filled_before_extraction() {
+        let mut extractor = FeatureExtractor::with_config(FeatureConfig {
+            gap_fill: GapFillMode::Linear,
+            ..Default::default()
+        });
+
+        // 20s of 5 Hz data ending now, with a 4s dropout in the middle
+        let buffer = RingBuffer::new(200);
+        let end = now_ms();
+        for i in (0..100u64).rev() {
+            if (40..60).contains(&i) {
+                continue;
+            }
+            buffer.push(SensorFrame {
+                timestamp_ms: end - i * 200,
+                rpm: 2000,
+                ..Default::default()
+            });
+        }
+
+        let features = extractor.extract(&buffer).expect("window should be ready");
+        assert_eq!(extractor.gap_count(), 1);
+        assert!((features.rpm_mean - 2000.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_gear_estimate_in_feature_vector() {
+        let mut extractor = FeatureExtractor::with_config(FeatureConfig {
+            gear: Some(GearConfig::default()),
+            ..Default::default()
+        });
+
+        let buffer = RingBuffer::new(200);
+        let end = now_ms();
+        for i in (0..100u64).rev() {
+            buffer.push(SensorFrame {
+                timestamp_ms: end - i * 200,
+                rpm: 2500,
+                speed: 68, // 27.2 km/h per 1000 rpm: 4th gear
+                ..Default::default()
+            });
+        }
+
+        let features = extractor.extract(&buffer).expect("window should be ready");
+        assert_eq!(features.estimated_gear, Some(4));
+        assert!(!features.clutch_slip);
+    }
+
+    #[test]
+    fn test_extract_reuses_vector_until_new_frame() {
+        let mut extractor = FeatureExtractor::new(5.0);
+        let buffer = RingBuffer::new(200);
+        let end = now_ms();
+        for i in (0..100u64).rev() {
+            buffer.push(SensorFrame {
+                timestamp_ms: end - i * 200,
+                rpm: 2000,
+                coolant_temp: 85,
+                ..Default::default()
+            });
+        }
+
+        let first = extractor.extract(&buffer).expect("window should be ready");
+        assert_eq!(extractor.computations(), 1);
+
+        // Inference polling again before the next frame: served from cache
+        let second = extractor.extract(&buffer).expect("window should be ready");
+        assert_eq!(extractor.computations(), 1);
+        assert_eq!(second.values, first.values);
+        assert_eq!(second.timestamp_ms, first.timestamp_ms);
+
+        buffer.push(SensorFrame {
+            timestamp_ms: now_ms(),
+            rpm: 4000,
+            coolant_temp: 85,
+            ..Default::default()
+        });
+        let third = extractor.extract(&buffer).expect("window should be ready");
+        assert_eq!(extractor.computations(), 2);
+        assert!(third.rpm_mean > first.rpm_mean);
+    }
+
+    #[test]
+    fn test_cached_vector_dropped_once_frames_age_out() {
+        let mut extractor = FeatureExtractor::new(5.0);
+        let buffer = RingBuffer::new(200);
+        // Exactly enough frames, the oldest about to leave the 30s window
+        let required = extractor.config().min_window_frames() as u64;
+        let start = now_ms() - 29_800;
+        for i in 0..required {
+            buffer.push(SensorFrame {
+                timestamp_ms: start + i * 200,
+                rpm: 2000,
+                ..Default::default()
+            });
+        }
+        assert!(extractor.extract(&buffer).is_some());
+
+        // Nothing new pushed, but the window no longer holds enough frames
+        std::thread::sleep(std::time::Duration::from_millis(400));
+        assert!(!extractor.is_ready(&buffer));
+        assert!(extractor.extract(&buffer).is_none());
+    }
+
+    #[test]
+    fn test_feature_names_follow_vector_layout() {
+        let config = FeatureConfig::default();
+        let names = config.feature_names();
+
+        let mut extractor = FeatureExtractor::with_config(config);
+        let buffer = RingBuffer::new(200);
+        let end = now_ms();
+        for i in (0..100u64).rev() {
+            buffer.push(SensorFrame {
+                timestamp_ms: end - i * 200,
+                rpm: 2000 + (i as u16 % 7) * 50,
+                coolant_temp: 88,
+                speed: 50,
+                ..Default::default()
+            });
+        }
+        let features = extractor.extract(&buffer).expect("window should be ready");
+        assert_eq!(names.len(), features.values.len());
+        assert_eq!(names.len(), FEATURE_DIMENSION);
+
+        // Statistics, then FFT bands, then temporal features, each per signal
+        assert_eq!(names[0], "rpm_mean");
+        assert_eq!(names[4], "coolant_temp_mean");
+        assert_eq!(names[19], "maf_kurtosis");
+        assert_eq!(names[20], "rpm_power_low");
+        assert_eq!(names[34], "maf_power_high");
+        assert_eq!(names[35], "rpm_rate_of_change");
+        assert_eq!(names[44], "maf_zero_crossings");
+
+        // Labels land on the values the named convenience fields report
+        let labeled = features.to_labeled_map();
+        assert_eq!(labeled["rpm_mean"], features.rpm_mean);
+        assert_eq!(labeled["rpm_std_dev"], features.rpm_std_dev);
+        assert_eq!(labeled["coolant_temp_mean"], features.coolant_temp_mean_30s);
+        assert_eq!(labeled["coolant_temp_rate_of_change"], features.coolant_temp_rate);
+
+        let header = FeatureVector::csv_header();
+        let row = features.to_csv_row();
+        assert_eq!(header.split(',').count(), row.split(',').count());
+        assert!(header.starts_with("timestamp_ms,rpm_mean,rpm_std_dev,"));
+    }
 }
@@ -1,6 +1,24 @@
 //! FFT-based Frequency Analysis
 
 use rustfft::{FftPlanner, num_complex::Complex};
+use serde::{Deserialize, Serialize};
+
+/// How a timestamped signal is put on a uniform grid before the FFT
+///
+/// PIDs are polled best-effort, so real sample intervals jitter around
+/// the nominal rate. Without resampling that jitter smears energy across
+/// neighbouring bins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Resampling {
+    /// Use samples as received, at their measured mean rate
+    None,
+    /// Linear interpolation between neighbouring samples
+    #[default]
+    Linear,
+    /// Cubic Hermite interpolation, which attenuates components close to
+    /// Nyquist less than linear
+    Cubic,
+}
 
 /// Frequency band definitions (Hz)
 #[derive(Debug, Clone, Copy)]
@@ -31,6 +49,8 @@ pub struct FftAnalyzer {
     bands: FrequencyBands,
     /// Sampling frequency (Hz)
     sample_rate: f64,
+    /// Grid reconstruction for timestamped signals
+    resampling: Resampling,
 }
 
 /// Power spectral density in frequency bands
@@ -46,6 +66,8 @@ pub struct SpectralFeatures {
     pub dominant_frequency: f64,
     /// Total spectral power
     pub total_power: f64,
+    /// Sample rate the spectrum was computed at (Hz)
+    pub effective_sample_rate: f64,
 }
 
 impl FftAnalyzer {
@@ -55,9 +77,139 @@ impl FftAnalyzer {
             planner: FftPlanner::new(),
             bands: FrequencyBands::default(),
             sample_rate,
+            resampling: Resampling::default(),
+        }
+    }
+
+    /// Use `resampling` for timestamped signals
+    pub fn with_resampling(mut self, resampling: Resampling) -> Self {
+        self.resampling = resampling;
+        self
+    }
+
+    /// Get the analysis sample rate (Hz)
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Grid reconstruction applied to timestamped signals
+    pub fn resampling(&self) -> Resampling {
+        self.resampling
+    }
+
+    /// Resample an unevenly-timestamped signal onto a uniform grid
+    ///
+    /// Uses linear interpolation between neighbouring samples. Samples are
+    /// sorted by timestamp first; if fewer than two distinct timestamps are
+    /// present the values are returned unchanged.
+    pub fn resample(timestamps_ms: &[u64], values: &[f64], rate_hz: f64) -> Vec<f64> {
+        Self::resample_with(timestamps_ms, values, rate_hz, Resampling::Linear)
+    }
+
+    /// Resample onto a uniform grid with the given interpolation
+    ///
+    /// [`Resampling::None`] only sorts the samples by timestamp.
+    pub fn resample_with(
+        timestamps_ms: &[u64],
+        values: &[f64],
+        rate_hz: f64,
+        resampling: Resampling,
+    ) -> Vec<f64> {
+        let n = timestamps_ms.len().min(values.len());
+        if n < 2 || rate_hz <= 0.0 {
+            return values[..n].to_vec();
+        }
+
+        let mut samples: Vec<(u64, f64)> = timestamps_ms[..n]
+            .iter()
+            .copied()
+            .zip(values[..n].iter().copied())
+            .collect();
+        samples.sort_by_key(|&(t, _)| t);
+
+        let start = samples[0].0 as f64;
+        let end = samples[n - 1].0 as f64;
+        if end <= start || resampling == Resampling::None {
+            return samples.into_iter().map(|(_, v)| v).collect();
+        }
+
+        let step_ms = 1000.0 / rate_hz;
+        let count = ((end - start) / step_ms).floor() as usize + 1;
+        let mut out = Vec::with_capacity(count);
+        let mut j = 0;
+
+        for k in 0..count {
+            let t = start + k as f64 * step_ms;
+            while j + 2 < n && (samples[j + 1].0 as f64) < t {
+                j += 1;
+            }
+            let (t0, v0) = samples[j];
+            let (t1, v1) = samples[j + 1];
+            let (t0, t1) = (t0 as f64, t1 as f64);
+            if t1 <= t0 {
+                out.push(v1);
+                continue;
+            }
+            let frac = ((t - t0) / (t1 - t0)).clamp(0.0, 1.0);
+            if resampling == Resampling::Cubic {
+                let h = t1 - t0;
+                let m0 = Self::slope(&samples, j) * h;
+                let m1 = Self::slope(&samples, j + 1) * h;
+                let (f2, f3) = (frac * frac, frac * frac * frac);
+                out.push(
+                    (2.0 * f3 - 3.0 * f2 + 1.0) * v0
+                        + (f3 - 2.0 * f2 + frac) * m0
+                        + (-2.0 * f3 + 3.0 * f2) * v1
+                        + (f3 - f2) * m1,
+                );
+            } else {
+                out.push(v0 + (v1 - v0) * frac);
+            }
+        }
+
+        out
+    }
+
+    /// Finite-difference slope at sample `i` (per ms), one-sided at the ends
+    fn slope(samples: &[(u64, f64)], i: usize) -> f64 {
+        let lo = i.saturating_sub(1);
+        let hi = (i + 1).min(samples.len() - 1);
+        let dt = samples[hi].0 as f64 - samples[lo].0 as f64;
+        if dt <= 0.0 {
+            0.0
+        } else {
+            (samples[hi].1 - samples[lo].1) / dt
         }
     }
 
+    /// Mean sample rate of a timestamped signal (Hz), `None` without a
+    /// measurable span
+    pub fn measured_rate(timestamps_ms: &[u64]) -> Option<f64> {
+        let first = *timestamps_ms.iter().min()?;
+        let last = *timestamps_ms.iter().max()?;
+        if last <= first {
+            return None;
+        }
+        Some((timestamps_ms.len() - 1) as f64 * 1000.0 / (last - first) as f64)
+    }
+
+    /// Compute spectral features from a timestamped signal
+    ///
+    /// The signal is first resampled to the analyzer's sample rate so the
+    /// frequency axis stays correct when the real sampling rate varies
+    /// (e.g. while the scheduler is boosting a PID). With
+    /// [`Resampling::None`] the samples are used as received and the
+    /// spectrum is computed at their measured mean rate.
+    pub fn analyze_timestamped(&mut self, timestamps_ms: &[u64], signal: &[f64]) -> SpectralFeatures {
+        let resampled =
+            Self::resample_with(timestamps_ms, signal, self.sample_rate, self.resampling);
+        let rate = match self.resampling {
+            Resampling::None => Self::measured_rate(timestamps_ms).unwrap_or(self.sample_rate),
+            Resampling::Linear | Resampling::Cubic => self.sample_rate,
+        };
+        self.analyze_at(&resampled, rate)
+    }
+
     /// Apply Hamming window to reduce spectral leakage
     fn apply_hamming_window(signal: &mut [f64]) {
         let n = signal.len();
@@ -69,6 +221,11 @@ impl FftAnalyzer {
 
     /// Compute spectral features from a signal
     pub fn analyze(&mut self, signal: &[f64]) -> SpectralFeatures {
+        self.analyze_at(signal, self.sample_rate)
+    }
+
+    /// Compute spectral features from a signal sampled at `sample_rate`
+    fn analyze_at(&mut self, signal: &[f64], sample_rate: f64) -> SpectralFeatures {
         if signal.is_empty() {
             return SpectralFeatures::default();
         }
@@ -97,7 +254,7 @@ impl FftAnalyzer {
             .collect();
         
         // Frequency resolution
-        let freq_resolution = self.sample_rate / n as f64;
+        let freq_resolution = sample_rate / n as f64;
         
         // Compute band powers
         let mut power_low = 0.0;
@@ -132,6 +289,7 @@ impl FftAnalyzer {
             power_high,
             dominant_frequency,
             total_power,
+            effective_sample_rate: sample_rate,
         }
     }
 }
@@ -157,6 +315,65 @@ mod tests {
         assert!(features.power_low > features.power_high);
     }
 
+    #[test]
+    fn test_irregular_sampling_recovers_frequency() {
+        let mut analyzer = FftAnalyzer::new(10.0); // 10 Hz analysis rate
+
+        // 1 Hz sine sampled at jittered intervals averaging 100 ms
+        let intervals = [60u64, 140, 90, 110, 75, 125];
+        let mut timestamps = Vec::new();
+        let mut t = 0u64;
+        for i in 0..300 {
+            timestamps.push(t);
+            t += intervals[i % intervals.len()];
+        }
+        let signal: Vec<f64> = timestamps
+            .iter()
+            .map(|&t| (2.0 * std::f64::consts::PI * 1.0 * t as f64 / 1000.0).sin())
+            .collect();
+
+        let features = analyzer.analyze_timestamped(&timestamps, &signal);
+        assert!((features.dominant_frequency - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_jittered_2hz_signal_resampled() {
+        // 2 Hz sine polled nominally at 10 Hz with ±40 ms of jitter
+        let jitter = [0i64, 35, -20, 40, -35, 10, -40, 25];
+        let timestamps: Vec<u64> = (0..300i64)
+            .map(|i| (i * 100 + 40 + jitter[i as usize % jitter.len()]) as u64)
+            .collect();
+        let signal: Vec<f64> = timestamps
+            .iter()
+            .map(|&t| (2.0 * std::f64::consts::PI * 2.0 * t as f64 / 1000.0).sin())
+            .collect();
+
+        for resampling in [Resampling::Linear, Resampling::Cubic] {
+            let mut analyzer = FftAnalyzer::new(10.0).with_resampling(resampling);
+            let features = analyzer.analyze_timestamped(&timestamps, &signal);
+            assert!(
+                (features.dominant_frequency - 2.0).abs() < 0.1,
+                "{resampling:?}: {} Hz",
+                features.dominant_frequency
+            );
+            assert_eq!(features.effective_sample_rate, 10.0);
+        }
+
+        // Without resampling the measured mean rate is reported instead
+        let mut analyzer = FftAnalyzer::new(5.0).with_resampling(Resampling::None);
+        let features = analyzer.analyze_timestamped(&timestamps, &signal);
+        assert!((features.effective_sample_rate - 10.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_resample_uniform_grid() {
+        let resampled = FftAnalyzer::resample(&[0, 300, 1000], &[0.0, 3.0, 10.0], 10.0);
+        assert_eq!(resampled.len(), 11);
+        assert!((resampled[2] - 2.0).abs() < 1e-9);
+        assert!((resampled[5] - 5.0).abs() < 1e-9);
+        assert!((resampled[10] - 10.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_empty_signal() {
         let mut analyzer = FftAnalyzer::new(100.0);
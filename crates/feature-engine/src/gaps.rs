@@ -0,0 +1,237 @@
+//! Sensor Data Gap Detection and Filling
+//!
+//! Windowed statistics assume contiguous samples. When the OBD link drops
+//! briefly the window has holes; this module finds them and can rebuild a
+//! regular grid, flagging the synthesized samples.
+
+use ring_buffer::SensorFrame;
+use serde::{Deserialize, Serialize};
+
+/// An interval is a gap when it exceeds the expected interval by this factor
+pub const GAP_TOLERANCE: f64 = 2.0;
+
+/// How missing samples are reconstructed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GapFillMode {
+    /// Detect gaps only
+    #[default]
+    None,
+    /// Linearly interpolate between the samples around the gap
+    Linear,
+    /// Repeat the last sample before the gap
+    ForwardFill,
+}
+
+/// A run of missing samples between two received frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// Timestamp of the last frame before the gap
+    pub start_ms: u64,
+    /// Timestamp of the first frame after the gap
+    pub end_ms: u64,
+    /// Number of samples expected in between
+    pub missing: usize,
+}
+
+/// Frames on a regular grid, with gap-filled samples flagged
+#[derive(Debug, Clone, Default)]
+pub struct FilledWindow {
+    /// Received and synthesized frames in timestamp order
+    pub frames: Vec<SensorFrame>,
+    /// `true` where the frame at the same index was synthesized
+    pub interpolated: Vec<bool>,
+    /// Gaps detected in the input
+    pub gaps: Vec<Gap>,
+}
+
+impl FilledWindow {
+    /// Number of gaps detected
+    pub fn gap_count(&self) -> usize {
+        self.gaps.len()
+    }
+
+    /// Number of synthesized frames
+    pub fn interpolated_count(&self) -> usize {
+        self.interpolated.iter().filter(|&&i| i).count()
+    }
+}
+
+/// Gap detector/filler for a fixed sampling cadence
+#[derive(Debug, Clone)]
+pub struct GapFiller {
+    /// Expected time between samples (ms)
+    expected_interval_ms: u64,
+    /// Reconstruction strategy
+    mode: GapFillMode,
+}
+
+impl GapFiller {
+    /// Create a gap filler for the given cadence
+    pub fn new(expected_interval_ms: u64, mode: GapFillMode) -> Self {
+        Self {
+            expected_interval_ms: expected_interval_ms.max(1),
+            mode,
+        }
+    }
+
+    /// Create a gap filler for a sample rate in Hz
+    pub fn for_sample_rate(sample_rate: f64, mode: GapFillMode) -> Self {
+        let interval = if sample_rate > 0.0 { 1000.0 / sample_rate } else { 1000.0 };
+        Self::new(interval.round() as u64, mode)
+    }
+
+    /// Find gaps between consecutive frames
+    ///
+    /// Frames may be in any order; `RingBuffer::read_window` returns them
+    /// newest first.
+    pub fn detect(&self, frames: &[SensorFrame]) -> Vec<Gap> {
+        self.detect_sorted(&chronological(frames))
+    }
+
+    /// Detect gaps and, depending on the mode, fill them to the expected cadence
+    ///
+    /// The returned frames are always in chronological order.
+    pub fn fill(&self, frames: &[SensorFrame]) -> FilledWindow {
+        let frames = chronological(frames);
+        let gaps = self.detect_sorted(&frames);
+        if self.mode == GapFillMode::None || gaps.is_empty() {
+            return FilledWindow {
+                interpolated: vec![false; frames.len()],
+                frames,
+                gaps,
+            };
+        }
+
+        let mut out = Vec::with_capacity(frames.len() + gaps.iter().map(|g| g.missing).sum::<usize>());
+        let mut interpolated = Vec::with_capacity(out.capacity());
+        let mut gap_iter = gaps.iter().peekable();
+
+        for (i, frame) in frames.iter().enumerate() {
+            out.push(frame.clone());
+            interpolated.push(false);
+
+            // Gaps are in frame order, so only the next one can start here
+            let Some(next) = frames.get(i + 1) else { break };
+            let Some(gap) = gap_iter.next_if(|g| g.start_ms == frame.timestamp_ms && g.end_ms == next.timestamp_ms) else {
+                continue;
+            };
+
+            let span = (next.timestamp_ms - frame.timestamp_ms) as f64;
+            for k in 1..=gap.missing {
+                let t = frame.timestamp_ms + k as u64 * self.expected_interval_ms;
+                let synthesized = match self.mode {
+                    GapFillMode::Linear => {
+                        lerp_frame(frame, next, (t - frame.timestamp_ms) as f64 / span, t)
+                    }
+                    _ => SensorFrame { timestamp_ms: t, ..frame.clone() },
+                };
+                out.push(synthesized);
+                interpolated.push(true);
+            }
+        }
+
+        FilledWindow {
+            frames: out,
+            interpolated,
+            gaps,
+        }
+    }
+
+    fn detect_sorted(&self, frames: &[SensorFrame]) -> Vec<Gap> {
+        let threshold = self.expected_interval_ms as f64 * GAP_TOLERANCE;
+
+        frames
+            .windows(2)
+            .filter_map(|w| {
+                let dt = w[1].timestamp_ms - w[0].timestamp_ms;
+                if dt as f64 > threshold {
+                    Some(Gap {
+                        start_ms: w[0].timestamp_ms,
+                        end_ms: w[1].timestamp_ms,
+                        missing: self.missing_in(dt),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Samples expected strictly between two frames `dt` apart
+    fn missing_in(&self, dt: u64) -> usize {
+        // Round to the nearest grid step so small jitter doesn't add a sample
+        let steps = (dt as f64 / self.expected_interval_ms as f64).round() as usize;
+        steps.saturating_sub(1)
+    }
+}
+
+fn chronological(frames: &[SensorFrame]) -> Vec<SensorFrame> {
+    let mut sorted = frames.to_vec();
+    sorted.sort_by_key(|f| f.timestamp_ms);
+    sorted
+}
+
+fn lerp_frame(a: &SensorFrame, b: &SensorFrame, frac: f64, timestamp_ms: u64) -> SensorFrame {
+    let lerp = |x: f64, y: f64| x + (y - x) * frac;
+    SensorFrame {
+        timestamp_ms,
+        rpm: lerp(a.rpm as f64, b.rpm as f64).round() as u16,
+        speed: lerp(a.speed as f64, b.speed as f64).round() as u8,
+        coolant_temp: lerp(a.coolant_temp as f64, b.coolant_temp as f64).round() as i16,
+        engine_load: lerp(a.engine_load as f64, b.engine_load as f64).round() as u8,
+        maf: lerp(a.maf as f64, b.maf as f64).round() as u16,
+        fuel_trim_short: lerp(a.fuel_trim_short as f64, b.fuel_trim_short as f64).round() as i16,
+        fuel_trim_long: lerp(a.fuel_trim_long as f64, b.fuel_trim_long as f64).round() as i16,
+        o2_voltage: lerp(a.o2_voltage as f64, b.o2_voltage as f64).round() as u16,
+        o2_voltage_b2: lerp(a.o2_voltage_b2 as f64, b.o2_voltage_b2 as f64).round() as u16,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames_with_gap() -> Vec<SensorFrame> {
+        // 200 ms cadence, with samples 600..=1200 missing
+        [0u64, 200, 400, 1400, 1600]
+            .iter()
+            .map(|&t| SensorFrame {
+                timestamp_ms: t,
+                rpm: 1000 + t as u16,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_gap_detected() {
+        let filler = GapFiller::new(200, GapFillMode::None);
+        let gaps = filler.detect(&frames_with_gap());
+        assert_eq!(gaps, vec![Gap { start_ms: 400, end_ms: 1400, missing: 4 }]);
+
+        let window = filler.fill(&frames_with_gap());
+        assert_eq!(window.frames.len(), 5);
+        assert_eq!(window.gap_count(), 1);
+    }
+
+    #[test]
+    fn test_linear_fill_restores_cadence() {
+        let filler = GapFiller::for_sample_rate(5.0, GapFillMode::Linear);
+        let window = filler.fill(&frames_with_gap());
+
+        let timestamps: Vec<u64> = window.frames.iter().map(|f| f.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![0, 200, 400, 600, 800, 1000, 1200, 1400, 1600]);
+        assert_eq!(window.interpolated_count(), 4);
+        assert!(window.interpolated[3] && !window.interpolated[7]);
+        // rpm tracks the timestamp, so interpolation is exact
+        assert!(window.frames.iter().all(|f| f.rpm == 1000 + f.timestamp_ms as u16));
+    }
+
+    #[test]
+    fn test_forward_fill_repeats_last_sample() {
+        let filler = GapFiller::new(200, GapFillMode::ForwardFill);
+        let window = filler.fill(&frames_with_gap());
+        assert_eq!(window.frames[5].rpm, 1400);
+        assert_eq!(window.frames[5].timestamp_ms, 1000);
+    }
+}
@@ -0,0 +1,188 @@
+//! Gear Estimation
+//!
+//! With the clutch engaged, road speed is proportional to engine speed for
+//! each gear. Matching the observed speed/RPM ratio against calibrated gear
+//! ratios gives the current gear; a ratio that stays below the engaged
+//! gear while driving means the engine is revving without the wheels
+//! following, i.e. the clutch is slipping.
+
+use ring_buffer::SensorFrame;
+use serde::{Deserialize, Serialize};
+
+/// Gear estimation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GearConfig {
+    /// Road speed per 1000 rpm for each gear, first gear first (km/h)
+    pub ratios_kmh_per_krpm: Vec<f64>,
+    /// Relative deviation from a gear ratio still counted as that gear
+    pub tolerance: f64,
+    /// Below this speed the vehicle is treated as stationary (km/h)
+    pub min_speed_kmh: f64,
+    /// Below this engine speed no gear is inferred (rpm)
+    pub min_rpm: f64,
+    /// Consecutive low-ratio samples before clutch slip is reported
+    pub slip_min_samples: usize,
+}
+
+impl Default for GearConfig {
+    fn default() -> Self {
+        // Typical 6-speed manual passenger car
+        Self {
+            ratios_kmh_per_krpm: vec![7.5, 13.5, 20.0, 27.0, 34.0, 41.0],
+            tolerance: 0.08,
+            min_speed_kmh: 5.0,
+            min_rpm: 900.0,
+            slip_min_samples: 5,
+        }
+    }
+}
+
+/// Classification of a single sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GearReading {
+    /// Too slow, or engine near idle, to infer a gear
+    Stationary,
+    /// Ratio matches a configured gear (1-based)
+    Gear(u8),
+    /// Moving, but the ratio matches no gear (shifting, coasting or slipping)
+    Unmatched {
+        /// Observed speed per 1000 rpm (km/h)
+        ratio: f64,
+    },
+}
+
+/// Gear estimate over a window of frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GearEstimate {
+    /// Gear of the most recent sample, if one matched
+    pub gear: Option<u8>,
+    /// Engine speed persistently too high for the last engaged gear
+    pub clutch_slip: bool,
+}
+
+/// Speed/RPM ratio gear classifier
+#[derive(Debug, Clone)]
+pub struct GearEstimator {
+    config: GearConfig,
+}
+
+impl GearEstimator {
+    /// Create an estimator from calibrated gear ratios
+    pub fn new(config: GearConfig) -> Self {
+        Self { config }
+    }
+
+    /// Classify one sample
+    pub fn classify(&self, speed_kmh: f64, rpm: f64) -> GearReading {
+        if speed_kmh < self.config.min_speed_kmh || rpm < self.config.min_rpm {
+            return GearReading::Stationary;
+        }
+
+        let ratio = speed_kmh / (rpm / 1000.0);
+        let nearest = self
+            .config
+            .ratios_kmh_per_krpm
+            .iter()
+            .enumerate()
+            .map(|(i, &gear_ratio)| (i, (ratio - gear_ratio).abs() / gear_ratio))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        match nearest {
+            Some((i, deviation)) if deviation <= self.config.tolerance => GearReading::Gear(i as u8 + 1),
+            _ => GearReading::Unmatched { ratio },
+        }
+    }
+
+    /// Estimate gear and clutch slip from chronologically ordered frames
+    pub fn estimate(&self, frames: &[SensorFrame]) -> GearEstimate {
+        let mut engaged: Option<u8> = None;
+        let mut low_run = 0;
+        let mut clutch_slip = false;
+        let mut current = None;
+
+        for frame in frames {
+            let reading = self.classify(frame.speed_kmh(), frame.rpm());
+            current = None;
+            match reading {
+                GearReading::Gear(gear) => {
+                    engaged = Some(gear);
+                    current = Some(gear);
+                    low_run = 0;
+                    clutch_slip = false;
+                }
+                GearReading::Unmatched { ratio } => {
+                    let expected = engaged.map(|g| self.config.ratios_kmh_per_krpm[g as usize - 1]);
+                    // Slip revs the engine, lowering speed per rpm
+                    if expected.is_some_and(|e| ratio < e * (1.0 - self.config.tolerance)) {
+                        low_run += 1;
+                        clutch_slip = low_run >= self.config.slip_min_samples.max(1);
+                    } else {
+                        low_run = 0;
+                    }
+                }
+                GearReading::Stationary => {
+                    engaged = None;
+                    low_run = 0;
+                    clutch_slip = false;
+                }
+            }
+        }
+
+        GearEstimate { gear: current, clutch_slip }
+    }
+}
+
+impl Default for GearEstimator {
+    fn default() -> Self {
+        Self::new(GearConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(speed: u8, rpm: u16) -> SensorFrame {
+        SensorFrame {
+            speed,
+            rpm,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_gear_classification() {
+        let estimator = GearEstimator::default();
+        assert_eq!(estimator.classify(15.0, 2000.0), GearReading::Gear(1));
+        assert_eq!(estimator.classify(40.0, 2000.0), GearReading::Gear(3));
+        assert_eq!(estimator.classify(100.0, 2440.0), GearReading::Gear(6));
+        assert_eq!(estimator.classify(0.0, 800.0), GearReading::Stationary);
+        assert!(matches!(estimator.classify(48.0, 2000.0), GearReading::Unmatched { .. }));
+
+        let frames: Vec<_> = (0..10).map(|_| frame(68, 2500)).collect();
+        assert_eq!(
+            estimator.estimate(&frames),
+            GearEstimate { gear: Some(4), clutch_slip: false }
+        );
+    }
+
+    #[test]
+    fn test_clutch_slip_flagged() {
+        let estimator = GearEstimator::default();
+
+        // Cruising in 4th, then revs climb while speed holds
+        let mut frames: Vec<_> = (0..10).map(|_| frame(68, 2500)).collect();
+        frames.extend((0..6).map(|_| frame(68, 3000)));
+        let estimate = estimator.estimate(&frames);
+        assert_eq!(estimate.gear, None);
+        assert!(estimate.clutch_slip);
+
+        // A quick shift 4th -> 5th passes through no gear only briefly
+        let mut frames: Vec<_> = (0..10).map(|_| frame(68, 2500)).collect();
+        frames.push(frame(68, 3000));
+        frames.extend((0..5).map(|_| frame(68, 2000)));
+        let estimate = estimator.estimate(&frames);
+        assert_eq!(estimate.gear, Some(5));
+        assert!(!estimate.clutch_slip);
+    }
+}
@@ -4,8 +4,18 @@
 
 mod features;
 mod fft;
+mod gaps;
+mod gear;
+mod o2;
 mod statistics;
+mod trip;
+mod vehicle;
 
-pub use features::{FeatureVector, FeatureExtractor};
-pub use fft::FftAnalyzer;
+pub use features::{FeatureConfig, FeatureVector, FeatureExtractor};
+pub use fft::{FftAnalyzer, Resampling, SpectralFeatures};
+pub use gaps::{FilledWindow, Gap, GapFillMode, GapFiller};
+pub use gear::{GearConfig, GearEstimate, GearEstimator, GearReading};
+pub use o2::{O2Analyzer, O2Assessment, O2Config, O2SensorHealth};
 pub use statistics::StatisticalFeatures;
+pub use trip::{TripConfig, TripDetector, TripEvent};
+pub use vehicle::{EngineType, VehicleProfile};
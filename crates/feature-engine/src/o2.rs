@@ -0,0 +1,193 @@
+//! Narrowband O2 Sensor Health
+//!
+//! In closed loop the ECU dithers the mixture around stoichiometric, so a
+//! healthy narrowband sensor swings between ~0.1 V (lean) and ~0.9 V
+//! (rich) several times a second. An aged or contaminated sensor responds
+//! slowly: it switches less often and no longer reaches either rail.
+//! Switching frequency and swing over the window are folded into a
+//! degradation score per bank, 0.0 for a healthy sensor and 1.0 for one
+//! that has stopped switching.
+
+use ring_buffer::SensorFrame;
+use serde::{Deserialize, Serialize};
+
+/// O2 health thresholds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct O2Config {
+    /// Above this voltage the sensor reads rich (V)
+    pub rich_threshold_v: f64,
+    /// Below this voltage the sensor reads lean (V); the band between the
+    /// two thresholds keeps noise around 0.45 V from counting as switches
+    pub lean_threshold_v: f64,
+    /// Lean/rich cycles per second at or above which switching scores healthy
+    pub healthy_switching_hz: f64,
+    /// Peak-to-peak swing at or above which amplitude scores healthy (V)
+    pub healthy_amplitude_v: f64,
+}
+
+impl Default for O2Config {
+    fn default() -> Self {
+        Self {
+            rich_threshold_v: 0.6,
+            lean_threshold_v: 0.3,
+            healthy_switching_hz: 0.5,
+            healthy_amplitude_v: 0.6,
+        }
+    }
+}
+
+/// Health of one O2 sensor over a window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct O2SensorHealth {
+    /// Full lean/rich cycles per second
+    pub switching_hz: f64,
+    /// Peak-to-peak voltage (V)
+    pub amplitude_v: f64,
+    /// 0.0 healthy to 1.0 degraded, the worse of frequency and amplitude
+    pub degradation_score: f64,
+}
+
+/// Per-bank O2 health; a bank is `None` when its sensor is not reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct O2Assessment {
+    pub bank1: Option<O2SensorHealth>,
+    pub bank2: Option<O2SensorHealth>,
+}
+
+impl O2Assessment {
+    /// Score of the worse bank, `None` if neither bank reports
+    pub fn degradation_score(&self) -> Option<f64> {
+        [self.bank1, self.bank2]
+            .into_iter()
+            .flatten()
+            .map(|health| health.degradation_score)
+            .reduce(f64::max)
+    }
+}
+
+/// Scores O2 sensor switching behaviour
+pub struct O2Analyzer {
+    config: O2Config,
+}
+
+impl O2Analyzer {
+    /// Create an analyzer
+    pub fn new(config: O2Config) -> Self {
+        Self { config }
+    }
+
+    /// Assess both banks over a window of frames
+    pub fn assess(&self, frames: &[SensorFrame]) -> O2Assessment {
+        let timestamps: Vec<u64> = frames.iter().map(|f| f.timestamp_ms).collect();
+        let bank1: Vec<f64> = frames.iter().map(|f| f.o2_voltage_v()).collect();
+        let bank2: Vec<f64> = frames.iter().map(|f| f.o2_voltage_b2_v()).collect();
+        O2Assessment {
+            bank1: self.analyze(&timestamps, &bank1),
+            bank2: self.analyze(&timestamps, &bank2),
+        }
+    }
+
+    /// Score one sensor's voltage trace
+    ///
+    /// Returns `None` for fewer than two samples, a zero-length window, or
+    /// a trace that is zero throughout (the sensor is not reported). A
+    /// sensor stuck at any other voltage is reported, and scores degraded.
+    pub fn analyze(&self, timestamps_ms: &[u64], volts: &[f64]) -> Option<O2SensorHealth> {
+        let n = timestamps_ms.len().min(volts.len());
+        if n < 2 || volts[..n].iter().all(|&v| v == 0.0) {
+            return None;
+        }
+        let first = *timestamps_ms[..n].iter().min()?;
+        let last = *timestamps_ms[..n].iter().max()?;
+        if last <= first {
+            return None;
+        }
+        let duration_s = (last - first) as f64 / 1000.0;
+
+        // Count lean <-> rich transitions with hysteresis
+        let mut rich: Option<bool> = None;
+        let mut transitions = 0usize;
+        for &v in &volts[..n] {
+            let state = if v >= self.config.rich_threshold_v {
+                Some(true)
+            } else if v <= self.config.lean_threshold_v {
+                Some(false)
+            } else {
+                None
+            };
+            if let Some(state) = state {
+                if rich.is_some_and(|prev| prev != state) {
+                    transitions += 1;
+                }
+                rich = Some(state);
+            }
+        }
+
+        let min = volts[..n].iter().copied().fold(f64::INFINITY, f64::min);
+        let max = volts[..n].iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let switching_hz = transitions as f64 / 2.0 / duration_s;
+        let amplitude_v = max - min;
+
+        let shortfall = |value: f64, healthy: f64| {
+            if healthy <= 0.0 {
+                0.0
+            } else {
+                (1.0 - value / healthy).clamp(0.0, 1.0)
+            }
+        };
+        let degradation_score = shortfall(switching_hz, self.config.healthy_switching_hz)
+            .max(shortfall(amplitude_v, self.config.healthy_amplitude_v));
+
+        Some(O2SensorHealth {
+            switching_hz,
+            amplitude_v,
+            degradation_score,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 30 s at 5 Hz of a square-ish wave between 0.1 and 0.9 V
+    fn o2_frames(period_ms: u64, b2: bool) -> Vec<SensorFrame> {
+        (0..150u64)
+            .map(|i| {
+                let t = i * 200;
+                let rich = (t / (period_ms / 2)) % 2 == 1;
+                let mv = if rich { 850 } else { 120 };
+                SensorFrame {
+                    timestamp_ms: t,
+                    o2_voltage: mv,
+                    o2_voltage_b2: if b2 { mv } else { 0 },
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fast_switching_healthy_slow_degraded() {
+        let analyzer = O2Analyzer::new(O2Config::default());
+
+        // Healthy: a full cycle every second, inline engine
+        let healthy = analyzer.assess(&o2_frames(1_000, false));
+        let bank1 = healthy.bank1.unwrap();
+        assert!((bank1.switching_hz - 1.0).abs() < 0.1, "{}", bank1.switching_hz);
+        assert!(healthy.bank2.is_none());
+        assert_eq!(healthy.degradation_score(), Some(0.0));
+
+        // Lazy: one cycle every 10 s on both banks
+        let lazy = analyzer.assess(&o2_frames(10_000, true));
+        let score = lazy.degradation_score().unwrap();
+        assert!(score > 0.7, "{score}");
+        assert_eq!(lazy.bank1, lazy.bank2);
+
+        // A sensor stuck mid-range scores fully degraded
+        let stuck: Vec<f64> = vec![0.45; 150];
+        let timestamps: Vec<u64> = (0..150).map(|i| i * 200).collect();
+        let health = analyzer.analyze(&timestamps, &stuck).unwrap();
+        assert_eq!(health.degradation_score, 1.0);
+    }
+}
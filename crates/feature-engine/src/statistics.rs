@@ -103,27 +103,27 @@ impl StatisticalFeatures {
 
     /// Extract RPM values from sensor frames
     pub fn extract_rpm(frames: &[SensorFrame]) -> Vec<f64> {
-        frames.iter().map(|f| f.rpm as f64).collect()
+        frames.iter().map(|f| f.rpm()).collect()
     }
 
     /// Extract coolant temp values from sensor frames
     pub fn extract_coolant_temp(frames: &[SensorFrame]) -> Vec<f64> {
-        frames.iter().map(|f| f.coolant_temp as f64).collect()
+        frames.iter().map(|f| f.coolant_temp_c()).collect()
     }
 
     /// Extract speed values from sensor frames
     pub fn extract_speed(frames: &[SensorFrame]) -> Vec<f64> {
-        frames.iter().map(|f| f.speed as f64).collect()
+        frames.iter().map(|f| f.speed_kmh()).collect()
     }
 
     /// Extract engine load values from sensor frames
     pub fn extract_engine_load(frames: &[SensorFrame]) -> Vec<f64> {
-        frames.iter().map(|f| f.engine_load as f64).collect()
+        frames.iter().map(|f| f.engine_load_pct()).collect()
     }
 
     /// Extract MAF values from sensor frames
     pub fn extract_maf(frames: &[SensorFrame]) -> Vec<f64> {
-        frames.iter().map(|f| f.maf as f64 / 100.0).collect()
+        frames.iter().map(|f| f.maf_g_s()).collect()
     }
 }
 
@@ -0,0 +1,281 @@
+//! Trip Segmentation
+//!
+//! Detects trip boundaries from engine-on/off transitions in the sensor stream.
+
+use crate::vehicle::VehicleProfile;
+use ring_buffer::SensorFrame;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// Trip detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TripConfig {
+    /// RPM at or above which the engine is considered running
+    pub engine_on_rpm: u16,
+    /// Engine-off, zero-speed time after which the trip is closed (ms)
+    pub end_gap_ms: u64,
+    /// Engine constants for the trip's fuel estimate
+    #[serde(default)]
+    pub vehicle: VehicleProfile,
+}
+
+impl Default for TripConfig {
+    fn default() -> Self {
+        Self {
+            engine_on_rpm: 300,
+            end_gap_ms: 120_000, // 2 minutes
+            vehicle: VehicleProfile::default(),
+        }
+    }
+}
+
+/// Trip boundary event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TripEvent {
+    /// Engine started after being off
+    TripStart {
+        timestamp_ms: u64,
+    },
+    /// Engine has been off long enough to end the trip
+    TripEnd {
+        start_ms: u64,
+        /// Last frame with the engine running
+        end_ms: u64,
+        /// Distance integrated from vehicle speed (km)
+        distance_km: f64,
+        /// Fuel integrated from MAF using the vehicle profile (L)
+        fuel_l: f64,
+    },
+}
+
+/// Active trip state
+#[derive(Debug, Clone)]
+struct ActiveTrip {
+    start_ms: u64,
+    last_running_ms: u64,
+    last_frame_ms: u64,
+    last_speed_kmh: f64,
+    last_fuel_rate_l_h: f64,
+    distance_km: f64,
+    fuel_l: f64,
+}
+
+/// Trip boundary detector
+pub struct TripDetector {
+    /// Configuration
+    config: TripConfig,
+    /// Current trip, if the engine is (or recently was) running
+    active: Option<ActiveTrip>,
+}
+
+impl TripDetector {
+    /// Create a new trip detector
+    pub fn new(config: TripConfig) -> Self {
+        Self {
+            config,
+            active: None,
+        }
+    }
+
+    /// Feed the next frame, returning the boundary events it caused
+    ///
+    /// At most one event, except when a data gap closes a trip and the
+    /// frame after the gap opens the next: then the end comes first.
+    pub fn update(&mut self, frame: &SensorFrame) -> Vec<TripEvent> {
+        let now = frame.timestamp_ms;
+        let running = frame.rpm >= self.config.engine_on_rpm;
+
+        let Some(trip) = self.active.as_mut() else {
+            return self.start(frame, running).into_iter().collect();
+        };
+
+        // A long hole in the data (e.g. the unit was asleep) closes the trip;
+        // the frame after it starts the next one if the engine is running
+        if now.saturating_sub(trip.last_frame_ms) > self.config.end_gap_ms {
+            debug!("Trip closed by {}ms data gap", now - trip.last_frame_ms);
+            return self.finish().into_iter().chain(self.start(frame, running)).collect();
+        }
+
+        // Trapezoidal integration of speed and fuel rate over the frame interval
+        let dt_h = now.saturating_sub(trip.last_frame_ms) as f64 / 3_600_000.0;
+        let fuel_rate_l_h = self.config.vehicle.fuel_rate_l_per_h(frame.maf_g_s());
+        trip.distance_km += (trip.last_speed_kmh + frame.speed_kmh()) / 2.0 * dt_h;
+        trip.fuel_l += (trip.last_fuel_rate_l_h + fuel_rate_l_h) / 2.0 * dt_h;
+        trip.last_frame_ms = now;
+        trip.last_speed_kmh = frame.speed_kmh();
+        trip.last_fuel_rate_l_h = fuel_rate_l_h;
+
+        if running || frame.speed > 0 {
+            if running {
+                trip.last_running_ms = now;
+            }
+            return Vec::new();
+        }
+
+        if now.saturating_sub(trip.last_running_ms) >= self.config.end_gap_ms {
+            return self.finish().into_iter().collect();
+        }
+
+        Vec::new()
+    }
+
+    /// Open a trip at `frame` if the engine is running
+    fn start(&mut self, frame: &SensorFrame, running: bool) -> Option<TripEvent> {
+        if !running {
+            return None;
+        }
+        let now = frame.timestamp_ms;
+        info!("Trip started at {}", now);
+        self.active = Some(ActiveTrip {
+            start_ms: now,
+            last_running_ms: now,
+            last_frame_ms: now,
+            last_speed_kmh: frame.speed_kmh(),
+            last_fuel_rate_l_h: self.config.vehicle.fuel_rate_l_per_h(frame.maf_g_s()),
+            distance_km: 0.0,
+            fuel_l: 0.0,
+        });
+        Some(TripEvent::TripStart { timestamp_ms: now })
+    }
+
+    /// Close the active trip, if any (e.g. on shutdown)
+    pub fn finish(&mut self) -> Option<TripEvent> {
+        let trip = self.active.take()?;
+        info!(
+            "Trip ended: {} -> {} ({:.2} km, {:.2} L)",
+            trip.start_ms, trip.last_running_ms, trip.distance_km, trip.fuel_l
+        );
+        Some(TripEvent::TripEnd {
+            start_ms: trip.start_ms,
+            end_ms: trip.last_running_ms,
+            distance_km: trip.distance_km,
+            fuel_l: trip.fuel_l,
+        })
+    }
+
+    /// Check whether a trip is in progress
+    pub fn in_trip(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+impl Default for TripDetector {
+    fn default() -> Self {
+        Self::new(TripConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(timestamp_ms: u64, rpm: u16, speed: u8) -> SensorFrame {
+        SensorFrame {
+            timestamp_ms,
+            rpm,
+            speed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_single_trip_boundaries() {
+        let mut detector = TripDetector::new(TripConfig {
+            engine_on_rpm: 300,
+            end_gap_ms: 60_000,
+            ..Default::default()
+        });
+        let mut events = Vec::new();
+        let mut t = 0;
+
+        // Parked, engine off
+        for _ in 0..10 {
+            events.extend(detector.update(&frame(t, 0, 0)));
+            t += 1_000;
+        }
+        // Engine on, idling, then driving at 36 km/h for 100s
+        let start = t;
+        for _ in 0..10 {
+            events.extend(detector.update(&frame(t, 800, 0)));
+            t += 1_000;
+        }
+        for _ in 0..100 {
+            events.extend(detector.update(&frame(t, 2000, 36)));
+            t += 1_000;
+        }
+        // Stop and switch off
+        let last_running = t;
+        events.extend(detector.update(&frame(t, 800, 0)));
+        t += 1_000;
+        for _ in 0..90 {
+            events.extend(detector.update(&frame(t, 0, 0)));
+            t += 1_000;
+        }
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], TripEvent::TripStart { timestamp_ms: start });
+        match &events[1] {
+            TripEvent::TripEnd { start_ms, end_ms, distance_km, .. } => {
+                assert_eq!(*start_ms, start);
+                assert_eq!(*end_ms, last_running);
+                assert!((distance_km - 1.0).abs() < 0.02);
+            }
+            other => panic!("expected trip end, got {other:?}"),
+        }
+        assert!(!detector.in_trip());
+    }
+
+    #[test]
+    fn test_data_gap_closes_trip() {
+        let mut detector = TripDetector::new(TripConfig {
+            engine_on_rpm: 300,
+            end_gap_ms: 60_000,
+            ..Default::default()
+        });
+
+        assert!(matches!(detector.update(&frame(0, 900, 0))[..], [TripEvent::TripStart { .. }]));
+        // The frame after the gap ends the old trip and starts the next
+        assert!(matches!(
+            detector.update(&frame(600_000, 900, 0))[..],
+            [
+                TripEvent::TripEnd { end_ms: 0, .. },
+                TripEvent::TripStart { timestamp_ms: 600_000 }
+            ]
+        ));
+        assert!(detector.update(&frame(601_000, 900, 30)).is_empty());
+        assert!(detector.in_trip());
+    }
+
+    #[test]
+    fn test_gap_boundary_frame_opens_next_trip() {
+        let mut detector = TripDetector::new(TripConfig {
+            engine_on_rpm: 300,
+            end_gap_ms: 60_000,
+            ..Default::default()
+        });
+        detector.update(&frame(0, 900, 36));
+
+        // Exactly the gap limit is not a gap
+        assert!(detector.update(&frame(60_000, 900, 36)).is_empty());
+        // Just past it: the crossing frame is the new trip's first, so
+        // distance restarts from it
+        let events = detector.update(&frame(120_001, 900, 36));
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], TripEvent::TripEnd { start_ms: 0, end_ms: 60_000, .. }));
+        assert_eq!(events[1], TripEvent::TripStart { timestamp_ms: 120_001 });
+        assert!(detector.update(&frame(170_001, 0, 0)).is_empty());
+        match detector.finish() {
+            Some(TripEvent::TripEnd { start_ms, distance_km, .. }) => {
+                assert_eq!(start_ms, 120_001);
+                // 50 s decelerating from 36 km/h to 0: 0.25 km
+                assert!((distance_km - 0.25).abs() < 1e-9);
+            }
+            other => panic!("expected trip end, got {other:?}"),
+        }
+
+        // Engine off after the gap: the trip ends and none starts
+        detector.update(&frame(300_000, 900, 0));
+        assert!(matches!(detector.update(&frame(400_000, 0, 0))[..], [TripEvent::TripEnd { .. }]));
+        assert!(!detector.in_trip());
+    }
+}
@@ -0,0 +1,99 @@
+//! Vehicle Profile
+//!
+//! Engine constants needed to turn air flow into fuel flow. MAF measures
+//! intake air; dividing by the air/fuel ratio gives fuel mass and the fuel
+//! density turns that into volume. Gasoline engines run at stoichiometry,
+//! so this is close to exact; diesels run lean, so the diesel figure is an
+//! upper bound unless the ratio is calibrated for the engine.
+
+use serde::{Deserialize, Serialize};
+
+/// Combustion type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EngineType {
+    /// Spark ignition
+    #[default]
+    Gasoline,
+    /// Compression ignition
+    Diesel,
+}
+
+/// Engine-specific constants for fuel and load calculations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VehicleProfile {
+    /// Combustion type
+    pub engine_type: EngineType,
+    /// Engine displacement (litres)
+    pub displacement_l: f64,
+    /// Air/fuel mass ratio used to derive fuel flow from MAF
+    pub air_fuel_ratio: f64,
+    /// Fuel density (g/L)
+    pub fuel_density_g_per_l: f64,
+}
+
+impl VehicleProfile {
+    /// Typical 2.0 L gasoline engine
+    pub fn gasoline() -> Self {
+        Self {
+            engine_type: EngineType::Gasoline,
+            displacement_l: 2.0,
+            air_fuel_ratio: 14.7,
+            fuel_density_g_per_l: 745.0,
+        }
+    }
+
+    /// Typical 2.0 L diesel engine
+    pub fn diesel() -> Self {
+        Self {
+            engine_type: EngineType::Diesel,
+            displacement_l: 2.0,
+            air_fuel_ratio: 14.5,
+            fuel_density_g_per_l: 832.0,
+        }
+    }
+
+    /// Fuel consumption (L/h) at a mass air flow of `maf_g_s`
+    pub fn fuel_rate_l_per_h(&self, maf_g_s: f64) -> f64 {
+        if self.air_fuel_ratio <= 0.0 || self.fuel_density_g_per_l <= 0.0 {
+            return 0.0;
+        }
+        maf_g_s.max(0.0) / self.air_fuel_ratio / self.fuel_density_g_per_l * 3600.0
+    }
+
+    /// Whether the engine reports narrowband O2 voltage and fuel trims
+    /// (PIDs 06, 07 and 14); diesels generally do not
+    pub fn has_lambda_pids(&self) -> bool {
+        self.engine_type == EngineType::Gasoline
+    }
+}
+
+impl Default for VehicleProfile {
+    fn default() -> Self {
+        Self::gasoline()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuel_rate_gasoline_vs_diesel() {
+        let gasoline = VehicleProfile::gasoline();
+        let diesel = VehicleProfile::diesel();
+        let maf = 20.0;
+
+        // 20 g/s / 14.7 / 745 g/L * 3600 s/h
+        let gasoline_rate = gasoline.fuel_rate_l_per_h(maf);
+        assert!((gasoline_rate - 6.575).abs() < 0.01, "gasoline {gasoline_rate}");
+
+        let diesel_rate = diesel.fuel_rate_l_per_h(maf);
+        assert!((diesel_rate - 5.969).abs() < 0.01, "diesel {diesel_rate}");
+
+        // Same air, different fuel: the ratio follows the configured constants
+        let expected = (diesel.air_fuel_ratio * diesel.fuel_density_g_per_l)
+            / (gasoline.air_fuel_ratio * gasoline.fuel_density_g_per_l);
+        assert!((diesel_rate / gasoline_rate - 1.0 / expected).abs() < 1e-9);
+        assert!(!diesel.has_lambda_pids());
+    }
+}
@@ -1,11 +1,13 @@
 //! Inference Batcher
 
 use feature_engine::FeatureVector;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::time::{Duration, timeout};
 use tracing::{debug, info};
 
 use crate::engine::InferenceEngine;
+use crate::latency::LatencyHistogram;
 use crate::InferenceError;
 
 /// Inference batcher for batching multiple feature vectors
@@ -16,6 +18,8 @@ pub struct InferenceBatcher {
     batch_size: usize,
     /// Timeout for batch collection (ms)
     timeout_ms: u64,
+    /// Latency distribution of completed inferences
+    histogram: Arc<LatencyHistogram>,
 }
 
 impl InferenceBatcher {
@@ -26,9 +30,21 @@ impl InferenceBatcher {
             receiver,
             batch_size,
             timeout_ms,
+            histogram: Arc::new(LatencyHistogram::default()),
         }
     }
 
+    /// Record latencies into a shared histogram (e.g. one read by the API)
+    pub fn with_histogram(mut self, histogram: Arc<LatencyHistogram>) -> Self {
+        self.histogram = histogram;
+        self
+    }
+
+    /// Get the latency histogram
+    pub fn histogram(&self) -> Arc<LatencyHistogram> {
+        Arc::clone(&self.histogram)
+    }
+
     /// Create a channel pair for the batcher
     pub fn channel(batch_size: usize, timeout_ms: u64) -> (mpsc::Sender<FeatureVector>, Self) {
         let (tx, rx) = mpsc::channel(batch_size * 2);
@@ -68,6 +84,7 @@ impl InferenceBatcher {
             for features in &batch {
                 match engine.predict(features).await {
                     Ok(result) => {
+                        self.histogram.record(result.latency_ms);
                         debug!(
                             "Prediction: {:?} (conf={:.2}, latency={}ms)",
                             result.prediction.fault_type,
@@ -98,4 +115,22 @@ mod tests {
         // Send a feature vector
         tx.send(FeatureVector::default()).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_batcher_records_latency() {
+        let mut engine = InferenceEngine::mock();
+        engine.load().unwrap();
+
+        let histogram = Arc::new(LatencyHistogram::default());
+        let (tx, batcher) = InferenceBatcher::channel(4, 10);
+        let mut batcher = batcher.with_histogram(Arc::clone(&histogram));
+
+        for _ in 0..3 {
+            tx.send(FeatureVector::default()).await.unwrap();
+        }
+        drop(tx);
+
+        batcher.run(&engine).await.unwrap();
+        assert_eq!(histogram.snapshot().count, 3);
+    }
 }
@@ -1,8 +1,9 @@
 //! Inference Engine Implementation
 
 use crate::InferenceError;
-use feature_engine::FeatureVector;
+use feature_engine::{FeatureConfig, FeatureVector};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
 /// Fault type detected by the model
@@ -64,6 +65,39 @@ pub struct InferenceResult {
     pub used_fallback: bool,
 }
 
+/// Sidecar metadata shipped next to a model (`model.onnx` → `model.json`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelMetadata {
+    /// [`FeatureConfig::schema_hash`] of the layout the model was trained on
+    pub feature_schema_hash: String,
+}
+
+impl ModelMetadata {
+    /// Sidecar location for a model file
+    pub fn sidecar_path(model_path: &str) -> PathBuf {
+        Path::new(model_path).with_extension("json")
+    }
+
+    /// Read the sidecar for `model_path`, if one exists
+    pub fn load(model_path: &str) -> Result<Option<Self>, InferenceError> {
+        let path = Self::sidecar_path(model_path);
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(InferenceError::ModelLoadError(format!(
+                    "reading {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        serde_json::from_slice(&bytes).map(Some).map_err(|e| {
+            InferenceError::ModelLoadError(format!("parsing {}: {}", path.display(), e))
+        })
+    }
+}
+
 /// ONNX Inference Engine (mock implementation for development)
 pub struct InferenceEngine {
     /// Model path
@@ -72,6 +106,8 @@ pub struct InferenceEngine {
     loaded: bool,
     /// Enable mock mode (no actual model)
     mock_mode: bool,
+    /// Schema hash of the feature config that will feed the model
+    feature_schema_hash: String,
 }
 
 impl InferenceEngine {
@@ -83,6 +119,7 @@ impl InferenceEngine {
             model_path: model_path.to_string(),
             loaded: false,
             mock_mode: true, // Start in mock mode until real model exists
+            feature_schema_hash: FeatureConfig::default().schema_hash(),
         })
     }
 
@@ -93,11 +130,37 @@ impl InferenceEngine {
             model_path: "mock".to_string(),
             loaded: true,
             mock_mode: true,
+            feature_schema_hash: FeatureConfig::default().schema_hash(),
         }
     }
 
+    /// Check the model against the layout produced by `config` on load
+    /// (the default feature config otherwise)
+    pub fn with_feature_config(mut self, config: &FeatureConfig) -> Self {
+        self.feature_schema_hash = config.schema_hash();
+        self
+    }
+
     /// Load the ONNX model
+    ///
+    /// Refuses with [`InferenceError::FeatureSchemaMismatch`] when the
+    /// model's sidecar names a different feature layout than the active
+    /// one. Models without a sidecar load unchecked.
     pub fn load(&mut self) -> Result<(), InferenceError> {
+        match ModelMetadata::load(&self.model_path)? {
+            Some(metadata) if metadata.feature_schema_hash != self.feature_schema_hash => {
+                return Err(InferenceError::FeatureSchemaMismatch {
+                    model: metadata.feature_schema_hash,
+                    active: self.feature_schema_hash.clone(),
+                });
+            }
+            Some(_) => debug!("Feature schema {} matches model", self.feature_schema_hash),
+            None => warn!(
+                "No sidecar metadata for {}; feature layout not checked",
+                self.model_path
+            ),
+        }
+
         if self.mock_mode {
             debug!("Mock mode: skipping model load");
             self.loaded = true;
@@ -211,6 +274,44 @@ mod tests {
         assert_eq!(result.prediction.fault_type, FaultType::None);
     }
 
+    #[test]
+    fn test_load_rejects_mismatched_feature_schema() {
+        let dir = std::env::temp_dir().join(format!("inference-schema-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let model_path = dir.join("model.onnx");
+        let model_path = model_path.to_str().unwrap();
+
+        // Trained at 5 Hz; the vehicle now samples at 10 Hz
+        let trained = FeatureConfig::default();
+        let metadata = ModelMetadata {
+            feature_schema_hash: trained.schema_hash(),
+        };
+        std::fs::write(
+            ModelMetadata::sidecar_path(model_path),
+            serde_json::to_vec(&metadata).unwrap(),
+        )
+        .unwrap();
+        let active = FeatureConfig {
+            sample_rate: 10.0,
+            ..Default::default()
+        };
+
+        let mut engine = InferenceEngine::new(model_path).unwrap().with_feature_config(&active);
+        let err = engine.load().unwrap_err();
+        assert!(!engine.is_loaded());
+        let message = err.to_string();
+        assert!(message.contains(&trained.schema_hash()), "{message}");
+        assert!(message.contains(&active.schema_hash()), "{message}");
+        assert!(matches!(err, InferenceError::FeatureSchemaMismatch { .. }));
+
+        // The matching layout loads
+        let mut engine = InferenceEngine::new(model_path).unwrap().with_feature_config(&trained);
+        engine.load().unwrap();
+        assert!(engine.is_loaded());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[tokio::test]
     async fn test_overheating_detection() {
         let mut engine = InferenceEngine::mock();
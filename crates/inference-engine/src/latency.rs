@@ -0,0 +1,196 @@
+//! Inference Latency Histogram
+//!
+//! Bucketed latency distribution for tracking the real-time inference budget.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Histogram configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyHistogramConfig {
+    /// Upper bounds of each bucket (ms), ascending
+    pub buckets_ms: Vec<u64>,
+    /// Latency budget per inference (ms); anything above is a deadline violation
+    pub budget_ms: u64,
+}
+
+impl Default for LatencyHistogramConfig {
+    fn default() -> Self {
+        Self {
+            buckets_ms: vec![1, 2, 5, 10, 20, 50, 100, 200, 500, 1000],
+            budget_ms: 50,
+        }
+    }
+}
+
+/// Point-in-time view of the latency distribution
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencySnapshot {
+    /// Number of recorded inferences
+    pub count: u64,
+    /// Median latency (bucket upper bound, ms)
+    pub p50_ms: u64,
+    /// 95th percentile latency (bucket upper bound, ms)
+    pub p95_ms: u64,
+    /// 99th percentile latency (bucket upper bound, ms)
+    pub p99_ms: u64,
+    /// Largest latency observed (ms)
+    pub max_ms: u64,
+    /// Inferences that exceeded the budget
+    pub deadline_violations: u64,
+    /// Configured budget (ms)
+    pub budget_ms: u64,
+}
+
+#[derive(Debug)]
+struct HistogramState {
+    /// One count per bucket plus a trailing overflow bucket
+    counts: Vec<u64>,
+    total: u64,
+    max_ms: u64,
+    violations: u64,
+}
+
+/// Thread-safe latency histogram with fixed bucket bounds
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    buckets_ms: Vec<u64>,
+    budget_ms: u64,
+    state: Mutex<HistogramState>,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new(LatencyHistogramConfig::default())
+    }
+}
+
+impl LatencyHistogram {
+    /// Create a histogram from configuration
+    pub fn new(config: LatencyHistogramConfig) -> Self {
+        let mut buckets_ms = config.buckets_ms;
+        buckets_ms.sort_unstable();
+        buckets_ms.dedup();
+
+        let counts = vec![0; buckets_ms.len() + 1];
+        Self {
+            buckets_ms,
+            budget_ms: config.budget_ms,
+            state: Mutex::new(HistogramState {
+                counts,
+                total: 0,
+                max_ms: 0,
+                violations: 0,
+            }),
+        }
+    }
+
+    /// Record a single inference latency
+    pub fn record(&self, latency_ms: u64) {
+        let idx = self
+            .buckets_ms
+            .iter()
+            .position(|&bound| latency_ms <= bound)
+            .unwrap_or(self.buckets_ms.len());
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.counts[idx] += 1;
+        state.total += 1;
+        state.max_ms = state.max_ms.max(latency_ms);
+        if latency_ms > self.budget_ms {
+            state.violations += 1;
+        }
+    }
+
+    /// Get the configured budget (ms)
+    pub fn budget_ms(&self) -> u64 {
+        self.budget_ms
+    }
+
+    /// Compute percentiles and violation count
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        LatencySnapshot {
+            count: state.total,
+            p50_ms: self.percentile(&state, 0.50),
+            p95_ms: self.percentile(&state, 0.95),
+            p99_ms: self.percentile(&state, 0.99),
+            max_ms: state.max_ms,
+            deadline_violations: state.violations,
+            budget_ms: self.budget_ms,
+        }
+    }
+
+    /// Clear all recorded latencies
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.counts.iter_mut().for_each(|c| *c = 0);
+        state.total = 0;
+        state.max_ms = 0;
+        state.violations = 0;
+    }
+
+    /// Upper bound of the bucket holding the `q` quantile.
+    /// Samples in the overflow bucket report the observed maximum.
+    fn percentile(&self, state: &HistogramState, q: f64) -> u64 {
+        if state.total == 0 {
+            return 0;
+        }
+
+        let rank = ((q * state.total as f64).ceil() as u64).max(1);
+        let mut cumulative = 0;
+        for (idx, &count) in state.counts.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= rank {
+                return self.buckets_ms.get(idx).copied().unwrap_or(state.max_ms);
+            }
+        }
+        state.max_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_and_violations() {
+        let histogram = LatencyHistogram::new(LatencyHistogramConfig {
+            buckets_ms: vec![5, 10, 25, 50, 100, 250],
+            budget_ms: 50,
+        });
+
+        for _ in 0..90 {
+            histogram.record(4);
+        }
+        for _ in 0..8 {
+            histogram.record(40);
+        }
+        histogram.record(200);
+        histogram.record(200);
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count, 100);
+        assert_eq!(snapshot.p50_ms, 5);
+        assert_eq!(snapshot.p95_ms, 50);
+        assert_eq!(snapshot.p99_ms, 250);
+        assert_eq!(snapshot.max_ms, 200);
+        assert_eq!(snapshot.deadline_violations, 2);
+    }
+
+    #[test]
+    fn test_overflow_bucket_reports_max() {
+        let histogram = LatencyHistogram::new(LatencyHistogramConfig {
+            buckets_ms: vec![10],
+            budget_ms: 10,
+        });
+        histogram.record(1500);
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.p99_ms, 1500);
+        assert_eq!(snapshot.deadline_violations, 1);
+
+        histogram.reset();
+        assert_eq!(histogram.snapshot().count, 0);
+    }
+}
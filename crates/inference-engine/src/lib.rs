@@ -4,9 +4,11 @@
 
 mod batcher;
 mod engine;
+mod latency;
 
 pub use batcher::InferenceBatcher;
-pub use engine::{InferenceEngine, InferenceResult, Prediction};
+pub use engine::{FaultType, InferenceEngine, InferenceResult, ModelMetadata, Prediction};
+pub use latency::{LatencyHistogram, LatencyHistogramConfig, LatencySnapshot};
 
 use thiserror::Error;
 
@@ -21,4 +23,6 @@ pub enum InferenceError {
     InvalidInputShape { expected: String, actual: String },
     #[error("Inference timeout after {0}ms")]
     Timeout(u64),
+    #[error("Feature schema mismatch: model expects {model}, active feature config is {active}")]
+    FeatureSchemaMismatch { model: String, active: String },
 }
@@ -0,0 +1,5 @@
+//! End-to-End Pipeline Tests
+//!
+//! The tests under `tests/` assemble the pipeline from its mock
+//! implementations: OBD client → validator → ring buffer → features →
+//! inference → alerting → storage → cloud sync.
@@ -0,0 +1,102 @@
+//! Calibration survives a reboot
+//!
+//! Saves a calibration set for one vehicle, then builds fresh modules the
+//! way startup does: a new store over the same backend, configs filled
+//! from whatever it holds. The loaded values must be the ones driving
+//! object distance, IMU correction and head pose.
+
+use std::sync::Arc;
+
+use adas::{AdasConfig, CalibrationReference, CameraGeometry};
+use camera_capture::imu::ImuConfig;
+use camera_capture::{ImuBias, ImuData, VideoFrame};
+use dms::{DmsConfig, DmsModule};
+use feature_engine::{FeatureConfig, GearConfig};
+use storage::calibration::kind;
+use storage::{CalibrationStore, Repository, Storage};
+
+const VEHICLE: &str = "van-0042";
+
+fn imu_at(accel: [f32; 3], gyro_z: f32) -> ImuData {
+    let [accel_x, accel_y, accel_z] = accel;
+    ImuData {
+        accel_x,
+        accel_y,
+        accel_z,
+        gyro_x: 0.0,
+        gyro_y: 0.0,
+        gyro_z,
+        temperature: 25.0,
+        g_force: (accel_x * accel_x + accel_y * accel_y + accel_z * accel_z).sqrt(),
+        timestamp_ns: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_calibration_round_trip_drives_fresh_modules() {
+    let backend: Arc<dyn Storage> = Arc::new(Repository::new());
+
+    // First boot: calibrate and save
+    let geometry = CameraGeometry::default()
+        .calibrate(&[
+            CalibrationReference { ground_row: 900.0, distance_m: 5.0 },
+            CalibrationReference { ground_row: 700.0, distance_m: 12.0 },
+            CalibrationReference { ground_row: 620.0, distance_m: 25.0 },
+        ])
+        .unwrap();
+    let at_rest = [imu_at([0.03, -0.02, 1.05], 0.4), imu_at([0.05, -0.04, 1.03], 0.6)];
+    let bias = ImuBias::estimate(&at_rest).unwrap();
+    let gears = GearConfig {
+        ratios_kmh_per_krpm: vec![7.5, 13.0, 20.0, 27.0, 34.0],
+        ..Default::default()
+    };
+    {
+        let store = CalibrationStore::new(Arc::clone(&backend), VEHICLE);
+        store.save(kind::CAMERA_GEOMETRY, &geometry).await.unwrap();
+        store.save(kind::IMU_BIAS, &bias).await.unwrap();
+        store.save(kind::HEAD_POSE, &(6.0f32, -4.0f32)).await.unwrap();
+        store.save(kind::GEAR_RATIOS, &gears).await.unwrap();
+    }
+
+    // Next boot: only the backend carries over
+    let store = CalibrationStore::new(Arc::clone(&backend), VEHICLE);
+    let adas_config = AdasConfig {
+        camera_geometry: store.load(kind::CAMERA_GEOMETRY).await.unwrap().unwrap_or_default(),
+        ..Default::default()
+    };
+    let imu_config = ImuConfig {
+        bias: store.load(kind::IMU_BIAS).await.unwrap().unwrap_or_default(),
+        ..Default::default()
+    };
+    let dms_config = DmsConfig {
+        pose_neutral: store.load(kind::HEAD_POSE).await.unwrap(),
+        ..Default::default()
+    };
+    let feature_config = FeatureConfig {
+        gear: store.load(kind::GEAR_RATIOS).await.unwrap(),
+        ..Default::default()
+    };
+
+    // Distance: the calibrated geometry, not the factory default
+    let bbox = [900.0, 640.0, 120.0, 100.0];
+    let distance = adas_config.camera_geometry.distance_to_box(&bbox).unwrap();
+    assert_eq!(Some(distance), geometry.distance_to_box(&bbox));
+    assert_ne!(Some(distance), CameraGeometry::default().distance_to_box(&bbox));
+
+    // IMU: a stationary sample reads 1 g straight down with no rotation
+    let corrected = imu_config.bias.apply(imu_at([0.04, -0.03, 1.04], 0.5));
+    assert!((corrected.g_force - 1.0).abs() < 1e-4, "{}", corrected.g_force);
+    assert!(corrected.gyro_z.abs() < 1e-4);
+
+    // Head pose: measured from the saved baseline from the first frame
+    let mut dms = DmsModule::new(dms_config).unwrap();
+    assert_eq!(dms.pose_neutral(), Some((6.0, -4.0)));
+    let frame = VideoFrame::new(vec![0; 64 * 48 * 3], 64, 48, 0, 0);
+    let pose = dms.analyze(&frame).await.unwrap().head_pose.unwrap();
+    assert_eq!((pose.yaw, pose.pitch), (-6.0, 4.0));
+
+    assert_eq!(
+        feature_config.gear.unwrap().ratios_kmh_per_krpm,
+        gears.ratios_kmh_per_krpm
+    );
+}
@@ -0,0 +1,185 @@
+//! Overheating scenario through the whole pipeline
+//!
+//! Assembles the system the way the vehicle does, with mock implementations
+//! at the edges: the mock OBD client answers RPM, speed, load and MAF, while
+//! coolant replies are scripted ELM327 text climbing past the overheating
+//! threshold. Each cycle's frame is validated, buffered and logged; the
+//! features then go through mock inference, alerting, storage and an
+//! offline cloud sync, which must keep the alert in its outbox.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alerting::{AlertConfig, AlertManager, AlertQueue, AlertQueueConfig, AlertSource, QueuedAlert};
+use cloud_sync::{CloudConfig, CloudSync, OutboxPolicy, UploadSchedule};
+use common_types::{MockClock, Severity};
+use data_validator::Validator;
+use feature_engine::{FeatureConfig, FeatureExtractor};
+use inference_engine::{FaultType, InferenceEngine};
+use obd_protocol::{valid, ObdClient, SensorFrame};
+use obd_scheduler::FrameAssembler;
+use ring_buffer::RingBuffer;
+use storage::{OutboxStatus, PredictionRecord, Repository, SensorRecord};
+
+/// Polling period of one full PID cycle (5 Hz, the feature sample rate)
+const CYCLE: Duration = Duration::from_millis(200);
+/// Cycles driven through the pipeline (20 s of driving)
+const CYCLES: u32 = 100;
+/// PIDs answered by the mock client each cycle
+const MOCK_PIDS: [u8; 4] = [0x0C, 0x0D, 0x04, 0x10];
+
+/// Scripted coolant temperature for cycle `i`: warm engine heating from
+/// 100 °C to a plateau at 125 °C
+fn scripted_coolant_c(i: u32) -> i16 {
+    (100 + i as i16 / 2).min(125)
+}
+
+/// ELM327 reply to `0105` for a coolant temperature
+fn coolant_reply(temp_c: i16) -> String {
+    format!("41 05 {:02X}\r\r>", temp_c + 40)
+}
+
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+fn to_buffer_frame(frame: &SensorFrame) -> ring_buffer::SensorFrame {
+    ring_buffer::SensorFrame {
+        timestamp_ms: frame.timestamp_ms,
+        rpm: frame.rpm,
+        speed: frame.speed,
+        coolant_temp: frame.coolant_temp,
+        engine_load: frame.engine_load,
+        maf: frame.maf,
+        fuel_trim_short: frame.fuel_trim_short,
+        fuel_trim_long: frame.fuel_trim_long,
+        o2_voltage: frame.o2_voltage,
+        o2_voltage_b2: frame.o2_voltage_b2,
+    }
+}
+
+fn to_sensor_record(frame: &SensorFrame) -> SensorRecord {
+    SensorRecord {
+        timestamp_ms: frame.timestamp_ms as i64,
+        rpm: i32::from(frame.rpm),
+        speed: i32::from(frame.speed),
+        coolant_temp: i32::from(frame.coolant_temp),
+        engine_load: i32::from(frame.engine_load),
+        maf: f64::from(frame.maf) / 100.0,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_overheating_reaches_storage_alerting_and_outbox() {
+    // The feature window is taken relative to wall-clock now, so the
+    // scripted drive ends just before the test's present
+    let start = SystemTime::now() - CYCLE * CYCLES;
+    let clock = MockClock::at(start);
+
+    // Acquisition
+    let mut client = ObdClient::mock().with_clock(clock.shared());
+    client.initialize().await.unwrap();
+    let mut assembler = FrameAssembler::new();
+    let validator = Validator::default();
+    let buffer = RingBuffer::new(256);
+    let repo = Arc::new(Repository::new());
+
+    for i in 0..CYCLES {
+        for pid in MOCK_PIDS {
+            let response = client.query_pid(pid).await.unwrap();
+            assembler.ingest(&response).expect("responses arrive in order");
+        }
+        let coolant = ObdClient::parse_response(
+            0x05,
+            &coolant_reply(scripted_coolant_c(i)),
+            unix_ms(clock.shared().now()),
+        )
+        .unwrap();
+        let frame = assembler.ingest(&coolant).expect("responses arrive in order");
+
+        assert!(frame.is_valid(valid::RPM | valid::SPEED | valid::COOLANT_TEMP));
+        validator.validate_rpm(f64::from(frame.rpm)).unwrap();
+        validator.validate_speed(f64::from(frame.speed)).unwrap();
+        validator.validate_coolant_temp(f64::from(frame.coolant_temp)).unwrap();
+        validator.validate_engine_load(f64::from(frame.engine_load)).unwrap();
+        validator.validate_maf(f64::from(frame.maf) / 100.0).unwrap();
+
+        buffer.push(to_buffer_frame(&frame));
+        repo.insert_sensor(to_sensor_record(&frame)).await.unwrap();
+        clock.advance(CYCLE);
+    }
+    assert_eq!(assembler.out_of_order(), 0);
+
+    // Features and inference
+    let mut extractor = FeatureExtractor::with_config(FeatureConfig::default());
+    let features = extractor.extract(&buffer).expect("window holds 20 s of frames");
+    assert!(
+        features.coolant_temp_mean_30s > 105.0,
+        "coolant mean {}",
+        features.coolant_temp_mean_30s
+    );
+
+    let engine = InferenceEngine::mock();
+    let prediction = engine.predict(&features).await.unwrap().prediction;
+    assert_eq!(prediction.fault_type, FaultType::Overheating);
+
+    // Alerting
+    let mut manager = AlertManager::new(AlertConfig::default())
+        .unwrap()
+        .with_clock(clock.shared());
+    let alert = manager
+        .evaluate_prediction(&prediction)
+        .expect("overheating prediction fires an alert");
+    assert_eq!(alert.fault_type, FaultType::Overheating);
+    assert!(alert.severity >= Severity::Medium);
+    // A repeat within the cooldown is deduplicated
+    assert!(manager.evaluate_prediction(&prediction).is_none());
+
+    // Storage
+    let id = repo
+        .insert_prediction(PredictionRecord {
+            id: 0,
+            timestamp_ms: (unix_ms(clock.shared().now()) - CYCLE.as_millis() as u64) as i64,
+            fault_class: prediction.fault_type.as_str().to_string(),
+            confidence: prediction.confidence,
+            severity: alert.severity.as_str().to_string(),
+            sensor_snapshot: None,
+            acknowledged: false,
+        })
+        .await
+        .unwrap();
+    let (stored, context) = repo.get_prediction_with_context(id).await.unwrap();
+    assert_eq!(stored.fault_class, "engine_overheating");
+    assert_eq!(context.len(), CYCLES as usize);
+    assert_eq!(context.iter().map(|r| r.coolant_temp).max(), Some(125));
+
+    // Cloud sync, with the broker unreachable
+    let queue = AlertQueue::new(AlertQueueConfig::default()).with_clock(clock.shared());
+    queue
+        .try_push(QueuedAlert::new(
+            AlertSource::Inference,
+            prediction.fault_type.as_str(),
+            alert.severity,
+            alert,
+        ))
+        .unwrap();
+    let queued = queue.try_next().unwrap();
+
+    let cloud = CloudSync::new(CloudConfig {
+        vehicle_id: "test-vehicle".to_string(),
+        schedule: UploadSchedule::Immediate,
+        ..Default::default()
+    })
+    .with_clock(clock.shared())
+    .with_outbox(repo.clone(), OutboxPolicy::default());
+    cloud.publish_alert(&queued).await.unwrap();
+
+    let outbox = repo.peek_outbox(10).await.unwrap();
+    assert_eq!(outbox.len(), 1);
+    assert_eq!(outbox[0].topic, "vehicles/test-vehicle/alerts");
+    assert_eq!(outbox[0].status, OutboxStatus::Pending);
+    let payload = String::from_utf8(outbox[0].payload.clone()).unwrap();
+    assert!(payload.contains("\"key\":\"engine_overheating\""), "{payload}");
+    assert!(payload.contains("\"fault_type\":\"Overheating\""), "{payload}");
+}
@@ -0,0 +1,118 @@
+//! Trip review API
+//!
+//! Records two trips the way the pipeline does (open the trip, log sensor
+//! frames and fused events while it runs, close it), then reads them back
+//! through the router as the fleet dashboard would.
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use api::{create_router, AppState};
+use axum::body::{to_bytes, Body};
+use axum::extract::ConnectInfo;
+use axum::http::{Request, StatusCode};
+use serde_json::Value;
+use storage::{EventRecord, Repository, SensorRecord, Storage};
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+
+fn event(timestamp_ms: i64, kind: &str) -> EventRecord {
+    EventRecord {
+        id: 0,
+        timestamp_ms,
+        kind: kind.to_string(),
+        severity: Some("medium".to_string()),
+        payload: "{}".to_string(),
+        trip_id: None,
+    }
+}
+
+fn sensor(timestamp_ms: i64, rpm: i32, speed: i32) -> SensorRecord {
+    SensorRecord {
+        timestamp_ms,
+        rpm,
+        speed,
+        coolant_temp: 85,
+        ..Default::default()
+    }
+}
+
+async fn get(router: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let mut request = Request::get(uri).body(Body::empty()).unwrap();
+    // The rate limiter keys on the peer address; give every request its
+    // own so the test is not throttled
+    static PEER: AtomicU8 = AtomicU8::new(1);
+    let peer = PEER.fetch_add(1, Ordering::Relaxed);
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, peer], 40_000))));
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_trip_list_and_detail_aggregate_events_and_sensors() {
+    let storage = Arc::new(Repository::new());
+
+    let first = storage.start_trip(1_000).await.unwrap();
+    storage.insert_sensor(sensor(1_000, 1_500, 20)).await.unwrap();
+    storage.insert_sensor(sensor(2_000, 2_500, 60)).await.unwrap();
+    let braking = storage.insert_event(event(1_500, "hard_braking")).await.unwrap();
+    storage.insert_event(event(1_800, "hard_braking")).await.unwrap();
+    storage.insert_event(event(1_900, "distraction")).await.unwrap();
+    storage.end_trip(first, 2_000, 1.2).await.unwrap();
+
+    // Logged between trips: belongs to neither
+    storage.insert_event(event(5_000, "hard_braking")).await.unwrap();
+
+    let second = storage.start_trip(10_000).await.unwrap();
+    storage.insert_sensor(sensor(10_000, 900, 10)).await.unwrap();
+    let crash = storage.insert_event(event(10_500, "crash")).await.unwrap();
+
+    let state = AppState::new().with_storage(Arc::new(storage));
+    let router = create_router(Arc::new(RwLock::new(state)));
+
+    let (status, list) = get(&router, "/api/v1/trips").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["total"], 2);
+    let trips = list["data"].as_array().unwrap();
+    assert_eq!(trips.len(), 2);
+
+    // Newest first; the second trip is still open
+    assert_eq!(trips[0]["id"], second);
+    assert_eq!(trips[0]["end_ms"], Value::Null);
+    assert_eq!(trips[0]["event_count"], 1);
+    assert_eq!(trips[1]["id"], first);
+    assert_eq!(trips[1]["end_ms"], 2_000);
+    assert_eq!(trips[1]["distance_km"], 1.2);
+    assert_eq!(trips[1]["event_count"], 3);
+    assert_eq!(trips[1]["event_counts"]["hard_braking"], 2);
+    assert_eq!(trips[1]["event_counts"]["distraction"], 1);
+
+    let (_, page) = get(&router, "/api/v1/trips?offset=1&limit=1").await;
+    assert_eq!(page["data"].as_array().unwrap().len(), 1);
+    assert_eq!(page["data"][0]["id"], first);
+
+    let (status, detail) = get(&router, &format!("/api/v1/trips/{first}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(detail["id"], first);
+    let events = detail["events"].as_array().unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0]["id"], braking);
+    assert!(events.iter().all(|e| e["trip_id"] == first));
+    assert_eq!(detail["sensors"]["sample_count"], 2);
+    assert_eq!(detail["sensors"]["avg_speed_kmh"], 40.0);
+    assert_eq!(detail["sensors"]["max_rpm"], 2_500);
+
+    let (_, detail) = get(&router, &format!("/api/v1/trips/{second}")).await;
+    assert_eq!(detail["events"][0]["id"], crash);
+    assert_eq!(detail["sensors"]["avg_rpm"], 900.0);
+
+    for uri in ["/api/v1/trips?limit=0", "/api/v1/trips?limit=101", "/api/v1/trips?offset=-1"] {
+        assert_eq!(get(&router, uri).await.0, StatusCode::BAD_REQUEST, "{uri}");
+    }
+    assert_eq!(get(&router, "/api/v1/trips/99").await.0, StatusCode::NOT_FOUND);
+}
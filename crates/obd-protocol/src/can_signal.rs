@@ -0,0 +1,265 @@
+//! Raw CAN Signal Decoding
+//!
+//! Custom ECUs publish data on proprietary CAN IDs that no OBD PID covers.
+//! A [`CanSignalDecoder`] holds a DBC-style signal map (CAN ID to the
+//! signals packed in its payload) and turns raw frames from the driver
+//! into named, scaled values.
+//!
+//! Bit numbering follows DBC files: bit `n` is bit `n % 8` of byte
+//! `n / 8`, counting from the LSB. Little-endian (Intel) signals start at
+//! their least significant bit and run upwards; big-endian (Motorola)
+//! signals start at their most significant bit and run down through each
+//! byte before continuing at the top of the next.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ffi::CCanFrame;
+
+/// Byte order of a signal in the frame payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ByteOrder {
+    /// Intel; `start_bit` is the least significant bit
+    LittleEndian,
+    /// Motorola; `start_bit` is the most significant bit
+    BigEndian,
+}
+
+/// One signal packed into a CAN frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanSignal {
+    pub name: String,
+    pub start_bit: u8,
+    /// Width in bits (1-64)
+    pub length: u8,
+    /// Physical value is `raw * scale + offset`
+    pub scale: f64,
+    pub offset: f64,
+    pub byte_order: ByteOrder,
+    /// Raw value is two's complement
+    #[serde(default)]
+    pub signed: bool,
+}
+
+impl CanSignal {
+    /// Unsigned little-endian signal with unit scale
+    pub fn new(name: impl Into<String>, start_bit: u8, length: u8) -> Self {
+        Self {
+            name: name.into(),
+            start_bit,
+            length,
+            scale: 1.0,
+            offset: 0.0,
+            byte_order: ByteOrder::LittleEndian,
+            signed: false,
+        }
+    }
+
+    /// Set the scale and offset
+    pub fn with_scaling(mut self, scale: f64, offset: f64) -> Self {
+        self.scale = scale;
+        self.offset = offset;
+        self
+    }
+
+    /// Set the byte order
+    pub fn with_byte_order(mut self, byte_order: ByteOrder) -> Self {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// Treat the raw value as two's complement
+    pub fn signed(mut self) -> Self {
+        self.signed = true;
+        self
+    }
+
+    /// Frame bit positions of the signal, most significant first; `None`
+    /// if it does not fit in an 8-byte payload
+    fn bit_positions(&self) -> Option<Vec<u8>> {
+        let length = self.length as usize;
+        match self.byte_order {
+            ByteOrder::LittleEndian => {
+                let end = self.start_bit as usize + length;
+                (end <= 64).then(|| (self.start_bit..end as u8).rev().collect())
+            }
+            ByteOrder::BigEndian => {
+                let mut positions = Vec::with_capacity(length);
+                let mut bit = self.start_bit as usize;
+                for i in 0..length {
+                    if bit >= 64 {
+                        return None;
+                    }
+                    positions.push(bit as u8);
+                    if i + 1 < length {
+                        // Down through the byte, then the top of the next one
+                        bit = if bit.is_multiple_of(8) { bit + 15 } else { bit - 1 };
+                    }
+                }
+                Some(positions)
+            }
+        }
+    }
+
+    /// Decode from a payload, `None` if the signal runs past its end
+    pub fn decode(&self, data: &[u8]) -> Option<f64> {
+        let mut raw = 0u64;
+        for bit in self.bit_positions()? {
+            let byte = data.get(bit as usize / 8)?;
+            raw = (raw << 1) | ((byte >> (bit % 8)) & 1) as u64;
+        }
+        let value = if self.signed {
+            // Sign-extend from the signal width
+            let shift = 64 - self.length as u32;
+            ((raw << shift) as i64 >> shift) as f64
+        } else {
+            raw as f64
+        };
+        Some(value * self.scale + self.offset)
+    }
+}
+
+/// Invalid signal map entry
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("signal {name:?} on CAN ID {can_id:#05X} {reason}")]
+pub struct SignalMapError {
+    pub can_id: u32,
+    pub name: String,
+    pub reason: &'static str,
+}
+
+/// A decoded signal value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedSignal {
+    pub name: String,
+    pub value: f64,
+}
+
+/// Signals decoded from one raw frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanSignalFrame {
+    pub can_id: u32,
+    pub timestamp_ns: u64,
+    pub signals: Vec<DecodedSignal>,
+}
+
+/// Decodes raw CAN frames against a signal map
+#[derive(Debug, Clone, Default)]
+pub struct CanSignalDecoder {
+    messages: HashMap<u32, Vec<CanSignal>>,
+}
+
+impl CanSignalDecoder {
+    /// Decoder with an empty signal map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the signals carried by `can_id`, replacing any earlier entry
+    pub fn with_message(mut self, can_id: u32, signals: Vec<CanSignal>) -> Result<Self, SignalMapError> {
+        for signal in &signals {
+            let invalid = |reason| SignalMapError {
+                can_id,
+                name: signal.name.clone(),
+                reason,
+            };
+            if !(1..=64).contains(&signal.length) {
+                return Err(invalid("must be 1 to 64 bits long"));
+            }
+            if signal.bit_positions().is_none() {
+                return Err(invalid("runs past the 8-byte payload"));
+            }
+            if !(signal.scale.is_finite() && signal.offset.is_finite()) {
+                return Err(invalid("has a non-finite scale or offset"));
+            }
+        }
+        self.messages.insert(can_id, signals);
+        Ok(self)
+    }
+
+    /// Whether `can_id` is in the signal map
+    pub fn handles(&self, can_id: u32) -> bool {
+        self.messages.contains_key(&can_id)
+    }
+
+    /// Decode a frame, `None` for IDs not in the map
+    ///
+    /// Signals extending past the frame's DLC are left out.
+    pub fn decode(&self, frame: &CCanFrame) -> Option<CanSignalFrame> {
+        let signals = self.messages.get(&frame.can_id)?;
+        let data = &frame.data[..(frame.dlc as usize).min(frame.data.len())];
+        Some(CanSignalFrame {
+            can_id: frame.can_id,
+            timestamp_ns: frame.timestamp_ns,
+            signals: signals
+                .iter()
+                .filter_map(|signal| {
+                    Some(DecodedSignal {
+                        name: signal.name.clone(),
+                        value: signal.decode(data)?,
+                    })
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_against_signal_map() {
+        let decoder = CanSignalDecoder::new()
+            .with_message(
+                0x3A0,
+                vec![
+                    // Bytes 0-1, Intel: 0x1234 * 0.1
+                    CanSignal::new("pack_voltage_v", 0, 16).with_scaling(0.1, 0.0),
+                    // Upper nibble of byte 2
+                    CanSignal::new("gear", 20, 4),
+                    // Bytes 3-4, Motorola (MSB is bit 7 of byte 3): 0x0190 * 0.25 - 40
+                    CanSignal::new("oil_temp_c", 31, 16)
+                        .with_byte_order(ByteOrder::BigEndian)
+                        .with_scaling(0.25, -40.0),
+                    // Byte 5 as two's complement
+                    CanSignal::new("accel_mps2", 40, 8).signed().with_scaling(0.1, 0.0),
+                    // Past the DLC
+                    CanSignal::new("reserved", 56, 8),
+                ],
+            )
+            .unwrap();
+
+        let frame = CCanFrame {
+            can_id: 0x3A0,
+            dlc: 6,
+            data: [0x34, 0x12, 0x5F, 0x01, 0x90, 0xF6, 0xFF, 0xFF],
+            timestamp_ns: 7,
+        };
+        let decoded = decoder.decode(&frame).unwrap();
+        assert_eq!(decoded.timestamp_ns, 7);
+        let value = |name: &str| {
+            decoded
+                .signals
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| s.value)
+        };
+        assert!((value("pack_voltage_v").unwrap() - 466.0).abs() < 1e-9);
+        assert_eq!(value("gear"), Some(5.0));
+        assert_eq!(value("oil_temp_c"), Some(0x0190 as f64 * 0.25 - 40.0));
+        assert!((value("accel_mps2").unwrap() + 1.0).abs() < 1e-9);
+        assert_eq!(value("reserved"), None);
+
+        assert!(decoder
+            .decode(&CCanFrame { can_id: 0x7E8, ..frame })
+            .is_none());
+
+        let err = CanSignalDecoder::new()
+            .with_message(0x100, vec![CanSignal::new("wide", 60, 8)])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "signal \"wide\" on CAN ID 0x100 runs past the 8-byte payload");
+    }
+}
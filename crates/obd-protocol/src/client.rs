@@ -2,19 +2,59 @@
 //!
 //! Provides async serial communication with OBD-II adapters.
 
+use crate::dtc::Dtc;
+use crate::elm::{self, ElmLink};
 use crate::error::ObdError;
-use crate::pid::PidResponse;
+use crate::link::{LinkConfig, LinkMonitor, LinkState, IGNITION_PROBE_PID};
+use crate::pid::{Pid, PidResponse};
 use crate::protocol::ObdProtocol;
-use std::time::Duration;
+use crate::readiness::ReadinessStatus;
+use rand::rngs::StdRng;
+use common_types::{MockConfig, SharedClock, SystemClock};
+use rand::Rng;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_serial::SerialPortBuilderExt;
 use tracing::{debug, error, info, warn};
 
 /// Default timeout for OBD commands
 const DEFAULT_TIMEOUT_MS: u64 = 2000;
 
+/// `ATZ` reboots the adapter, which takes longer than a normal command
+const RESET_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The first query after `ATSP0` waits for the adapter to try each protocol
+const PROTOCOL_SEARCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// VIN reported by mock clients
+pub const MOCK_VIN: &str = "1HGCM82633A004352";
+
+/// Mode 09 info type for the VIN
+const VIN_INFO_TYPE: u8 = 0x02;
+
+/// Characters in a VIN
+const VIN_LEN: usize = 17;
+
+/// Service ID byte marking a negative response
+const NEGATIVE_RESPONSE_SID: u8 = 0x7F;
+
+/// Positive responses echo the request mode plus this offset
+const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+
+/// PIDs covered by each supported-PID bitmap (`0100`, `0120`, ...)
+const SUPPORTED_PID_RANGE: u8 = 0x20;
+
+/// Last PID that reports a supported-PID bitmap
+const LAST_SUPPORTED_PID_QUERY: u8 = 0xE0;
+
 /// OBD-II client for communicating with ELM327-compatible adapters
 pub struct ObdClient {
     /// Serial port device path (e.g., "/dev/ttyUSB0" or "COM3")
     device: String,
+    /// Serial baud rate
+    baud_rate: u32,
+    /// Open adapter connection, `None` until initialized and in mock mode
+    port: Option<ElmLink>,
     /// OBD protocol to use
     protocol: ObdProtocol,
     /// Command timeout
@@ -23,6 +63,18 @@ pub struct ObdClient {
     connected: bool,
     /// Mock mode for testing (uses simulated responses)
     mock_mode: bool,
+    /// Source of simulated values in mock mode
+    mock_rng: Option<StdRng>,
+    /// Stored codes reported in mock mode
+    mock_dtcs: Vec<Dtc>,
+    /// Whether mock readiness monitors have completed since the last clear
+    mock_monitors_complete: bool,
+    /// Whether the mock ECU answers (ignition on)
+    mock_ignition_on: bool,
+    /// Ignition inference from response history
+    link: LinkMonitor,
+    /// Time source for response timestamps and the silence window
+    clock: SharedClock,
 }
 
 impl ObdClient {
@@ -31,30 +83,83 @@ impl ObdClient {
     /// # Arguments
     /// * `device` - Serial port device path
     /// * `baud_rate` - Baud rate for serial communication
-    pub async fn new(device: &str, _baud_rate: u32) -> Result<Self, ObdError> {
+    pub async fn new(device: &str, baud_rate: u32) -> Result<Self, ObdError> {
         info!("Creating OBD client for device: {}", device);
 
+        let clock = SystemClock::shared();
         Ok(Self {
             device: device.to_string(),
+            baud_rate,
+            port: None,
             protocol: ObdProtocol::Auto,
             timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
             connected: false,
             mock_mode: false,
+            mock_rng: None,
+            mock_dtcs: Vec::new(),
+            mock_monitors_complete: true,
+            mock_ignition_on: true,
+            link: LinkMonitor::new(LinkConfig::default(), clock.instant()),
+            clock,
         })
     }
 
     /// Create a mock OBD client for testing (no hardware required)
     pub fn mock() -> Self {
-        info!("Creating mock OBD client for testing");
+        Self::mock_with_config(MockConfig::default())
+    }
+
+    /// Create a mock OBD client whose responses are driven by `config.seed`
+    pub fn mock_with_config(config: MockConfig) -> Self {
+        info!("Creating mock OBD client for testing (seed {})", config.seed);
+        let clock = SystemClock::shared();
         Self {
             device: "mock".to_string(),
+            baud_rate: 0,
+            port: None,
             protocol: ObdProtocol::Iso15765_4Can11bit500,
             timeout: Duration::from_millis(100),
             connected: true,
             mock_mode: true,
+            mock_rng: Some(config.rng()),
+            mock_dtcs: vec![Dtc::from_raw([0x03, 0x01]), Dtc::from_raw([0x04, 0x20])],
+            mock_monitors_complete: true,
+            mock_ignition_on: true,
+            link: LinkMonitor::new(LinkConfig::default(), clock.instant()),
+            clock,
         }
     }
 
+    /// Client for an adapter on an already-open byte stream, such as the
+    /// TCP socket of a Wi-Fi dongle; `device` only labels it in logs
+    pub async fn with_stream(
+        device: &str,
+        stream: impl AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    ) -> Result<Self, ObdError> {
+        let mut client = Self::new(device, 0).await?;
+        client.port = Some(ElmLink::new(Box::new(stream)));
+        Ok(client)
+    }
+
+    /// Use `clock` for timestamps and ignition inference
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.link = LinkMonitor::new(self.link.config(), clock.instant());
+        self.clock = clock;
+        self
+    }
+
+    /// Replace the ignition inference settings
+    pub fn with_link_config(mut self, config: LinkConfig) -> Self {
+        self.link = LinkMonitor::new(config, self.clock.instant());
+        self
+    }
+
+    /// Replace the codes a mock client reports as stored
+    pub fn with_mock_dtcs(mut self, dtcs: Vec<Dtc>) -> Self {
+        self.mock_dtcs = dtcs;
+        self
+    }
+
     /// Initialize the ELM327 adapter
     pub async fn initialize(&mut self) -> Result<(), ObdError> {
         if self.mock_mode {
@@ -65,46 +170,440 @@ impl ObdClient {
 
         info!("Initializing OBD adapter on {}", self.device);
 
-        // In real implementation, we would:
-        // 1. Send "ATZ" to reset
-        // 2. Send "ATE0" to disable echo
-        // 3. Send "ATL0" to disable linefeeds
-        // 4. Send "ATSP0" (or specific protocol) to set protocol
-        // 5. Send "0100" to test connection
+        let port = match self.port.take() {
+            Some(port) => port,
+            None => {
+                let stream = tokio_serial::new(&self.device, self.baud_rate)
+                    .open_native_async()
+                    .map_err(|e| ObdError::SerialError(format!("{}: {}", self.device, e)))?;
+                ElmLink::new(Box::new(stream))
+            }
+        };
+        self.port = Some(port);
+
+        let banner = self.at_command("ATZ", RESET_TIMEOUT).await?;
+        info!("Adapter identifies as {:?}", banner.trim());
+        for command in ["ATE0", "ATL0", self.protocol.to_elm_command()] {
+            self.expect_ok(command).await?;
+        }
+        if self.protocol == ObdProtocol::Auto {
+            self.detect_protocol().await;
+        }
 
         self.connected = true;
         info!("OBD adapter initialized successfully");
         Ok(())
     }
 
+    /// Have the adapter search for the vehicle's protocol and record the one
+    /// it settles on
+    ///
+    /// Issues `0100` to start the search and reads the result with
+    /// `ATDPN`. If the vehicle does not answer (ignition off) the protocol
+    /// stays [`ObdProtocol::Auto`] and the adapter searches again on the
+    /// next query.
+    async fn detect_protocol(&mut self) {
+        let Some(port) = self.port.as_mut() else {
+            return;
+        };
+        let probe = match port.command("0100", PROTOCOL_SEARCH_TIMEOUT).await {
+            Ok(raw) => elm::reply_bytes("0100", &raw),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = probe {
+            warn!("Protocol search failed, leaving it automatic: {}", e);
+            return;
+        }
+
+        match self.at_command("ATDPN", self.timeout).await {
+            Ok(reply) => match ObdProtocol::from_protocol_number(&reply) {
+                Some(protocol) => {
+                    info!("Adapter negotiated {:?}", protocol);
+                    self.protocol = protocol;
+                }
+                None => warn!("Unrecognized protocol number {:?}", reply),
+            },
+            Err(e) => warn!("Could not read the negotiated protocol: {}", e),
+        }
+    }
+
+    /// Send an AT command, returning the reply text
+    async fn at_command(&mut self, command: &str, timeout: Duration) -> Result<String, ObdError> {
+        let port = self.port.as_mut().ok_or(ObdError::AdapterNotResponding)?;
+        let raw = port.command(command, timeout).await?;
+        Ok(elm::reply_lines(command, &raw)?.join("\n"))
+    }
+
+    /// Send an AT command that answers `OK`
+    async fn expect_ok(&mut self, command: &str) -> Result<(), ObdError> {
+        let reply = self.at_command(command, self.timeout).await?;
+        if reply.lines().any(|l| l.eq_ignore_ascii_case("OK")) {
+            Ok(())
+        } else {
+            Err(ObdError::InvalidResponse(format!("{command}: {reply}")))
+        }
+    }
+
     /// Query a PID and return the decoded response
+    ///
+    /// While the link sleeps only [`IGNITION_PROBE_PID`] is sent; other
+    /// PIDs fail with [`ObdError::VehicleNotConnected`] without touching
+    /// the bus.
     pub async fn query_pid(&mut self, pid: u8) -> Result<PidResponse, ObdError> {
         if !self.connected {
             return Err(ObdError::AdapterNotResponding);
         }
+        if self.link.state() == LinkState::Sleeping && pid != IGNITION_PROBE_PID {
+            return Err(ObdError::VehicleNotConnected);
+        }
 
-        let timestamp_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as u64)
-            .unwrap_or(0);
+        let result = self.send_query(pid).await;
+        self.track_link(&result);
+        result
+    }
 
+    async fn send_query(&mut self, pid: u8) -> Result<PidResponse, ObdError> {
         if self.mock_mode {
+            if !self.mock_ignition_on {
+                // What the adapter reports when nothing answers on the bus
+                return Err(ObdError::PidNotSupported(pid));
+            }
+            let timestamp_ms = self.now_ms();
             return Ok(self.generate_mock_response(pid, timestamp_ms));
         }
 
         debug!("Querying PID {:02X}", pid);
+        let port = self.port.as_mut().ok_or(ObdError::AdapterNotResponding)?;
+        let raw = port.command(&format!("01{pid:02X}"), self.timeout).await?;
+        Self::parse_response(pid, &raw, self.now_ms())
+    }
+
+    /// Current time (Unix ms)
+    fn now_ms(&self) -> u64 {
+        self.clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
+    /// Update ignition inference from a query outcome
+    fn track_link<T>(&mut self, result: &Result<T, ObdError>) {
+        let now = self.clock.instant();
+        match result {
+            // A negative response still means the ECU is awake
+            Ok(_) | Err(ObdError::NegativeResponse { .. }) => {
+                if self.link.record_response(now) {
+                    info!("ECU responding again, resuming full polling");
+                }
+            }
+            Err(
+                ObdError::Timeout(_)
+                | ObdError::AdapterNotResponding
+                | ObdError::PidNotSupported(_)
+                | ObdError::VehicleNotConnected
+                | ObdError::SearchingProtocol,
+            ) => {
+                if self.link.record_silence(now) {
+                    warn!(
+                        "No ECU response for {:?}, assuming ignition off; probing every {:?}",
+                        self.link.config().silence_window,
+                        self.link.config().sleep_poll_interval
+                    );
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Parse a raw ELM327 reply to a Mode 01 request for `pid`
+    ///
+    /// Accepts reply text as read up to the `>` prompt, e.g. `"41 0C 1A F8\r\r>"`.
+    /// `NO DATA` is [`ObdError::PidNotSupported`]. Negative responses
+    /// (`7F <sid> <nrc>`) are surfaced as [`ObdError::NegativeResponse`]
+    /// with the decoded NRC.
+    pub fn parse_response(pid: u8, raw: &str, timestamp_ms: u64) -> Result<PidResponse, ObdError> {
+        let header = [crate::mode::CURRENT_DATA + POSITIVE_RESPONSE_OFFSET, pid];
+        Self::parse_pid_reply(&format!("01{pid:02X}"), &header, pid, raw, timestamp_ms)
+    }
+
+    /// Parse a raw ELM327 reply to a Mode 02 request for `pid` in freeze
+    /// frame 0, e.g. `"42 05 00 7B"`
+    pub fn parse_freeze_frame_response(pid: u8, raw: &str, timestamp_ms: u64) -> Result<PidResponse, ObdError> {
+        let header = [crate::mode::FREEZE_FRAME + POSITIVE_RESPONSE_OFFSET, pid, 0x00];
+        Self::parse_pid_reply(&format!("02{pid:02X}00"), &header, pid, raw, timestamp_ms)
+    }
+
+    /// Decode the data after `header` with the PID formulas
+    fn parse_pid_reply(
+        command: &str,
+        header: &[u8],
+        pid: u8,
+        raw: &str,
+        timestamp_ms: u64,
+    ) -> Result<PidResponse, ObdError> {
+        let text = raw.replace('>', "");
+        let Some(bytes) = elm::reply_bytes(command, &text)? else {
+            return Err(ObdError::PidNotSupported(pid));
+        };
+
+        match bytes.as_slice() {
+            [NEGATIVE_RESPONSE_SID, service, nrc, ..] => {
+                warn!("Negative response to service {:02X}: NRC {:02X}", service, nrc);
+                Err(ObdError::negative_response(*service, *nrc))
+            }
+            reply => match reply.strip_prefix(header) {
+                Some(data) => Ok(PidResponse::decode(pid, data.to_vec(), timestamp_ms)),
+                None => Err(ObdError::InvalidResponse(raw.trim().to_string())),
+            },
+        }
+    }
+
+    /// Read `pid` as stored in freeze frame 0 (Mode 02), the engine state
+    /// when the first DTC set
+    ///
+    /// Decoded with the same formulas as [`ObdClient::query_pid`]. PID 02
+    /// holds the DTC that stored the frame. Without a stored frame the ECU
+    /// answers `NO DATA`, reported as [`ObdError::PidNotSupported`].
+    pub async fn read_freeze_frame(&mut self, pid: u8) -> Result<PidResponse, ObdError> {
+        if !self.connected {
+            return Err(ObdError::VehicleNotConnected);
+        }
+
+        if self.mock_mode {
+            let timestamp_ms = self.now_ms();
+            return match self.mock_dtcs.first() {
+                None => Err(ObdError::PidNotSupported(pid)),
+                Some(dtc) if pid == 0x02 => Ok(PidResponse::decode(pid, dtc.raw.to_vec(), timestamp_ms)),
+                Some(_) => Ok(self.generate_mock_response(pid, timestamp_ms)),
+            };
+        }
+
+        debug!("Reading freeze frame PID {:02X}", pid);
+        let port = self.port.as_mut().ok_or(ObdError::AdapterNotResponding)?;
+        let raw = port.command(&format!("02{pid:02X}00"), self.timeout).await?;
+        Self::parse_freeze_frame_response(pid, &raw, self.now_ms())
+    }
+
+    /// Read stored diagnostic trouble codes (Mode 03)
+    pub async fn read_dtcs(&mut self) -> Result<Vec<Dtc>, ObdError> {
+        if !self.connected {
+            return Err(ObdError::VehicleNotConnected);
+        }
+
+        if self.mock_mode {
+            return Ok(self.mock_dtcs.clone());
+        }
+
+        debug!("Reading stored DTCs");
+        let port = self.port.as_mut().ok_or(ObdError::AdapterNotResponding)?;
+        let raw = port.command("03", self.timeout).await?;
+        Self::parse_dtc_response(&raw)
+    }
+
+    /// Parse a raw ELM327 reply to a Mode 03 request
+    ///
+    /// Each ECU answers with its own `43`-prefixed message. CAN replies
+    /// carry a code count after the `43` and span several frames beyond
+    /// two codes; legacy replies are always three codes, padded with
+    /// `00 00`. Codes reported by several ECUs are listed once.
+    pub fn parse_dtc_response(raw: &str) -> Result<Vec<Dtc>, ObdError> {
+        let text = raw.replace('>', "");
+        let Some(messages) = elm::reply_messages("03", &text)? else {
+            return Ok(Vec::new());
+        };
+
+        let mode = crate::mode::READ_DTC + POSITIVE_RESPONSE_OFFSET;
+        let mut dtcs: Vec<Dtc> = Vec::new();
+        for bytes in messages {
+            let invalid = || ObdError::InvalidResponse(raw.trim().to_string());
+            let codes = match bytes.as_slice() {
+                [NEGATIVE_RESPONSE_SID, service, nrc, ..] => {
+                    return Err(ObdError::negative_response(*service, *nrc));
+                }
+                // CAN: count byte, then exactly that many codes
+                [sid, count, codes @ ..] if *sid == mode && codes.len() == *count as usize * 2 => codes,
+                // Legacy: three code slots
+                [sid, codes @ ..] if *sid == mode && codes.len() == 6 => codes,
+                _ => return Err(invalid()),
+            };
+            for &pair in codes.as_chunks::<2>().0 {
+                let dtc = Dtc::from_raw(pair);
+                if pair != [0, 0] && !dtcs.contains(&dtc) {
+                    dtcs.push(dtc);
+                }
+            }
+        }
+        Ok(dtcs)
+    }
+
+    /// Read the vehicle identification number (Mode 09, info type 02)
+    pub async fn read_vin(&mut self) -> Result<String, ObdError> {
+        if !self.connected {
+            return Err(ObdError::VehicleNotConnected);
+        }
+
+        if self.mock_mode {
+            return Ok(MOCK_VIN.to_string());
+        }
+
+        debug!("Reading VIN");
+        let port = self.port.as_mut().ok_or(ObdError::AdapterNotResponding)?;
+        let raw = port.command("0902", self.timeout).await?;
+        Self::parse_vin_response(&raw)
+    }
+
+    /// Parse a raw ELM327 reply to `0902`
+    ///
+    /// CAN ECUs send the VIN as one multi-frame `49 02 01` message; legacy
+    /// ECUs send five `49 02 <n>` messages of four bytes each, the first
+    /// starting with three bytes of zero padding. The VIN must be 17
+    /// characters without I, O or Q.
+    pub fn parse_vin_response(raw: &str) -> Result<String, ObdError> {
+        let text = raw.replace('>', "");
+        let Some(messages) = elm::reply_messages("0902", &text)? else {
+            return Err(ObdError::PidNotSupported(VIN_INFO_TYPE));
+        };
+
+        let sid = crate::mode::VEHICLE_INFO + POSITIVE_RESPONSE_OFFSET;
+        let mut frames = Vec::new();
+        for bytes in &messages {
+            match bytes.as_slice() {
+                [NEGATIVE_RESPONSE_SID, service, nrc, ..] => {
+                    return Err(ObdError::negative_response(*service, *nrc));
+                }
+                [mode, info, seq, data @ ..] if *mode == sid && *info == VIN_INFO_TYPE => frames.push((*seq, data)),
+                _ => return Err(ObdError::InvalidResponse(raw.trim().to_string())),
+            }
+        }
+        // Legacy frames are numbered; a CAN reply is the single frame 01
+        frames.sort_by_key(|(seq, _)| *seq);
+        let vin: String = frames
+            .iter()
+            .flat_map(|(_, data)| data.iter())
+            .skip_while(|&&b| b == 0)
+            .map(|&b| b as char)
+            .collect();
+
+        let valid = vin.len() == VIN_LEN
+            && vin
+                .chars()
+                .all(|c| c.is_ascii_digit() || (c.is_ascii_uppercase() && !matches!(c, 'I' | 'O' | 'Q')));
+        if !valid {
+            return Err(ObdError::InvalidResponse(format!("VIN {vin:?}")));
+        }
+        Ok(vin)
+    }
+
+    /// Clear stored trouble codes and the MIL (Mode 04)
+    ///
+    /// Clearing also resets the emissions readiness monitors, so the
+    /// vehicle fails inspection until they have run again. SAE J1979 only
+    /// allows it key on, engine off (KOEO); many ECUs refuse with a
+    /// "conditions not correct" negative response while the engine runs.
+    /// Without a `44` acknowledgement within the command timeout this
+    /// fails with [`ObdError::AdapterNotResponding`].
+    pub async fn clear_dtcs(&mut self) -> Result<(), ObdError> {
+        if !self.connected {
+            return Err(ObdError::VehicleNotConnected);
+        }
 
-        // In real implementation, we would:
-        // 1. Format command: "01{PID:02X}\r"
-        // 2. Write to serial port
-        // 3. Read response until ">" prompt
-        // 4. Parse hex response bytes
-        // 5. Decode using PidResponse::decode()
+        if self.mock_mode {
+            info!("Mock mode: clearing {} stored DTCs", self.mock_dtcs.len());
+            self.mock_dtcs.clear();
+            self.mock_monitors_complete = false;
+            return Ok(());
+        }
 
-        Err(ObdError::AdapterNotResponding)
+        warn!("Clearing stored DTCs");
+        let port = self.port.as_mut().ok_or(ObdError::AdapterNotResponding)?;
+        let raw = match port.command("04", self.timeout).await {
+            Ok(raw) => raw,
+            Err(ObdError::Timeout(_)) => return Err(ObdError::AdapterNotResponding),
+            Err(e) => return Err(e),
+        };
+
+        let ack = crate::mode::CLEAR_DTC + POSITIVE_RESPONSE_OFFSET;
+        match elm::reply_bytes("04", &raw)?.as_deref() {
+            Some([sid, ..]) if *sid == ack => {
+                info!("Stored DTCs cleared");
+                Ok(())
+            }
+            Some([NEGATIVE_RESPONSE_SID, service, nrc, ..]) => Err(ObdError::negative_response(*service, *nrc)),
+            _ => Err(ObdError::AdapterNotResponding),
+        }
+    }
+
+    /// Read MIL state, DTC count and emissions readiness monitors (PID 01)
+    pub async fn read_readiness(&mut self) -> Result<ReadinessStatus, ObdError> {
+        if !self.connected {
+            return Err(ObdError::VehicleNotConnected);
+        }
+
+        if self.mock_mode {
+            return ReadinessStatus::decode(&self.mock_monitor_status());
+        }
+
+        let response = self.query_pid(Pid::MonitorStatus.as_hex()).await?;
+        ReadinessStatus::decode(&response.raw_bytes)
+    }
+
+    /// Mode 01 PIDs the vehicle supports, in ascending order
+    ///
+    /// Reads the bitmaps at PIDs 00, 20, 40, ... in turn. Bit 31 of a
+    /// bitmap is the PID after its own, and bit 0 (the next bitmap PID)
+    /// says whether the next range is worth querying. PID 00 must be
+    /// answered; a later range the ECU rejects ends the walk.
+    pub async fn supported_pids(&mut self) -> Result<Vec<u8>, ObdError> {
+        let mut supported = Vec::new();
+        let mut base = 0x00;
+        loop {
+            let response = match self.query_pid(base).await {
+                Ok(response) => response,
+                Err(ObdError::PidNotSupported(_)) if base > 0x00 => break,
+                Err(e) => return Err(e),
+            };
+            let Some(&bitmap) = response.raw_bytes.first_chunk::<4>() else {
+                return Err(ObdError::InvalidResponse(format!(
+                    "supported PIDs {base:02X}: {:02X?}",
+                    response.raw_bytes
+                )));
+            };
+            let bitmap = u32::from_be_bytes(bitmap);
+            supported.extend(
+                (1..=SUPPORTED_PID_RANGE)
+                    .filter(|offset| bitmap & (1 << (SUPPORTED_PID_RANGE - offset)) != 0)
+                    .map(|offset| base + offset),
+            );
+
+            let next_range = bitmap & 1 != 0;
+            if !next_range || base == LAST_SUPPORTED_PID_QUERY {
+                break;
+            }
+            base += SUPPORTED_PID_RANGE;
+        }
+        debug!("{} supported PIDs", supported.len());
+        Ok(supported)
+    }
+
+    /// PID 01 bytes for the mock: a spark-ignition engine with catalyst,
+    /// EVAP, O2 and EGR monitors, all incomplete after a clear
+    fn mock_monitor_status(&self) -> [u8; 4] {
+        let count = self.mock_dtcs.len().min(0x7F) as u8;
+        let mil = if count > 0 { 0x80 } else { 0x00 };
+        let supported = 0xE5;
+        let (common_incomplete, incomplete) = if self.mock_monitors_complete {
+            (0x00, 0x00)
+        } else {
+            (0x70, supported)
+        };
+        [mil | count, 0x07 | common_incomplete, supported, incomplete]
     }
 
     /// Set the OBD protocol
+    ///
+    /// With an adapter connected, [`ObdProtocol::Auto`] searches right away
+    /// and [`ObdClient::protocol`] then reports what was found.
     pub async fn set_protocol(&mut self, protocol: ObdProtocol) -> Result<(), ObdError> {
         info!("Setting OBD protocol to {:?}", protocol);
 
@@ -113,10 +612,13 @@ impl ObdClient {
             return Ok(());
         }
 
-        let _cmd = protocol.to_elm_command();
-        // In real implementation, send command to adapter
-
+        if self.port.is_some() {
+            self.expect_ok(protocol.to_elm_command()).await?;
+        }
         self.protocol = protocol;
+        if protocol == ObdProtocol::Auto && self.port.is_some() {
+            self.detect_protocol().await;
+        }
         Ok(())
     }
 
@@ -125,6 +627,21 @@ impl ObdClient {
         self.timeout = timeout;
     }
 
+    /// Whether the ECU is answering or the link is asleep
+    pub fn link_state(&self) -> LinkState {
+        self.link.state()
+    }
+
+    /// Ignition inference settings
+    pub fn link_config(&self) -> LinkConfig {
+        self.link.config()
+    }
+
+    /// Switch the mock ECU on or off, as turning the key would
+    pub fn set_mock_ignition(&mut self, on: bool) {
+        self.mock_ignition_on = on;
+    }
+
     /// Check if client is connected
     pub fn is_connected(&self) -> bool {
         self.connected
@@ -141,20 +658,20 @@ impl ObdClient {
             info!("Disconnecting OBD client");
             self.connected = false;
         }
+        self.port = None;
     }
 
     /// Generate a mock response for testing
-    fn generate_mock_response(&self, pid: u8, timestamp_ms: u64) -> PidResponse {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        // Use timestamp to generate pseudo-random but deterministic values
-        let mut hasher = DefaultHasher::new();
-        timestamp_ms.hash(&mut hasher);
-        pid.hash(&mut hasher);
-        let hash = hasher.finish();
+    fn generate_mock_response(&mut self, pid: u8, timestamp_ms: u64) -> PidResponse {
+        let hash: u64 = self.mock_rng.get_or_insert_with(|| MockConfig::default().rng()).gen();
 
         let raw_bytes = match pid {
+            // Supported PIDs 01-20
+            0x00 => vec![0xBE, 0x1F, 0xA8, 0x13],
+            // Supported PIDs 21-40: 23, 2F and the next range
+            0x20 => vec![0x20, 0x02, 0x00, 0x01],
+            // Supported PIDs 41-60: 42 and 46
+            0x40 => vec![0x44, 0x00, 0x00, 0x00],
             // RPM: 800-3500 RPM range
             0x0C => {
                 let rpm = 800 + (hash % 2700) as u16;
@@ -176,6 +693,22 @@ impl ObdClient {
             0x06 | 0x07 => vec![(115 + (hash % 26)) as u8], // -10% to +10%
             // O2 voltage: 0.1-0.9V
             0x14 => vec![(20 + (hash % 160)) as u8],
+            // Fuel level: 10-100%
+            0x2F => vec![(26 + (hash % 230)) as u8],
+            // Intake air temp: 15-60°C
+            0x0F => vec![(55 + (hash % 45)) as u8],
+            // Control module voltage: 13.5-14.5V while charging
+            0x42 => {
+                let mv = 13_500 + (hash % 1000) as u16;
+                vec![(mv >> 8) as u8, (mv & 0xFF) as u8]
+            }
+            // Ambient temp: 0-35°C
+            0x46 => vec![(40 + (hash % 35)) as u8],
+            // Fuel rail pressure: 3-12 MPa (stored as kPa / 10)
+            0x23 => {
+                let raw = 300 + (hash % 900) as u16;
+                vec![(raw >> 8) as u8, (raw & 0xFF) as u8]
+            }
             _ => vec![0],
         };
 
@@ -186,6 +719,42 @@ impl ObdClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// ELM327 on the far end of a pipe, answering each command from
+    /// `replies` and everything else with `?`
+    fn fake_adapter(replies: &[(&str, &str)]) -> tokio::io::DuplexStream {
+        let replies: HashMap<String, String> = replies
+            .iter()
+            .map(|(command, reply)| (command.to_string(), reply.to_string()))
+            .collect();
+        let (client, mut adapter) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            let mut command = Vec::new();
+            let mut byte = [0u8; 1];
+            while adapter.read(&mut byte).await.unwrap_or(0) == 1 {
+                if byte[0] != b'\r' {
+                    command.push(byte[0]);
+                    continue;
+                }
+                let command = String::from_utf8(std::mem::take(&mut command)).unwrap();
+                let reply = replies.get(&command).map_or("?", String::as_str);
+                if adapter.write_all(format!("{reply}\r\r>").as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+        client
+    }
+
+    /// Replies to the `initialize` sequence
+    const INIT: [(&str, &str); 4] = [
+        ("ATZ", "\r\rELM327 v1.5"),
+        ("ATE0", "ATE0\rOK"),
+        ("ATL0", "OK"),
+        ("ATSP0", "OK"),
+    ];
 
     #[tokio::test]
     async fn test_mock_client_creation() {
@@ -202,6 +771,279 @@ mod tests {
         assert!(response.value >= 800.0 && response.value <= 3500.0);
     }
 
+    #[tokio::test]
+    async fn test_mock_seed_reproducible() {
+        async fn sequence(seed: u64) -> Vec<f64> {
+            let mut client = ObdClient::mock_with_config(MockConfig::with_seed(seed));
+            let mut values = Vec::new();
+            for _ in 0..20 {
+                values.push(client.query_pid(0x0C).await.unwrap().value);
+            }
+            values
+        }
+
+        let a = sequence(7).await;
+        assert_eq!(a, sequence(7).await);
+        assert_ne!(a, sequence(8).await);
+        // Varied, not a constant
+        assert!(a.iter().any(|&v| v != a[0]));
+    }
+
+    #[tokio::test]
+    async fn test_mock_dtcs_read_and_clear() {
+        let mut client = ObdClient::mock().with_mock_dtcs(vec![Dtc::parse("P0171").unwrap()]);
+        let codes = client.read_dtcs().await.unwrap();
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].code, "P0171");
+
+        client.clear_dtcs().await.unwrap();
+        assert!(client.read_dtcs().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mock_readiness_resets_on_clear() {
+        let mut client = ObdClient::mock().with_mock_dtcs(vec![Dtc::parse("P0420").unwrap()]);
+        let status = client.read_readiness().await.unwrap();
+        assert!(status.mil_on);
+        assert_eq!(status.dtc_count, 1);
+        assert!(status.is_ready());
+
+        client.clear_dtcs().await.unwrap();
+        let status = client.read_readiness().await.unwrap();
+        assert!(!status.mil_on);
+        assert_eq!(status.dtc_count, 0);
+        assert!(!status.is_ready());
+    }
+
+    #[test]
+    fn test_parse_positive_response() {
+        let response = ObdClient::parse_response(0x0C, "41 0C 1A F8\r\r>", 0).unwrap();
+        assert_eq!(response.raw_bytes, vec![0x1A, 0xF8]);
+        assert!((response.value - 1726.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_parse_negative_response() {
+        let err = ObdClient::parse_response(0x0C, "7F 01 12\r>", 0).unwrap_err();
+        match err {
+            ObdError::NegativeResponse { service, nrc, meaning } => {
+                assert_eq!(service, 0x01);
+                assert_eq!(nrc, 0x12);
+                assert_eq!(meaning, "sub-function not supported");
+            }
+            other => panic!("expected negative response, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_dtcs_from_adapter() {
+        let codes = |dtcs: Vec<Dtc>| dtcs.into_iter().map(|d| d.code).collect::<Vec<_>>();
+
+        // CAN: count byte; a second ECU repeats one code
+        let can = ObdClient::parse_dtc_response("43 02 03 01 04 20\r43 01 03 01\r\r>").unwrap();
+        assert_eq!(codes(can), ["P0301", "P0420"]);
+        // Legacy: three slots per line, zero-padded
+        let legacy = ObdClient::parse_dtc_response("43 01 71 C1 00 00 00\r43 00 00 00 00 00 00\r").unwrap();
+        assert_eq!(codes(legacy), ["P0171", "U0100"]);
+        assert!(ObdClient::parse_dtc_response("43 00\r").unwrap().is_empty());
+        assert!(ObdClient::parse_dtc_response("NO DATA\r").unwrap().is_empty());
+        assert!(matches!(
+            ObdClient::parse_dtc_response("43 02 03 01\r"),
+            Err(ObdError::InvalidResponse(_))
+        ));
+
+        let mut replies = INIT.to_vec();
+        replies.push(("03", "43 01 01 71"));
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        assert_eq!(codes(client.read_dtcs().await.unwrap()), ["P0171"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_freeze_frame() {
+        let mut replies = INIT.to_vec();
+        replies.extend([("020500", "42 05 00 7B"), ("020C00", "NO DATA")]);
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        let coolant = client.read_freeze_frame(0x05).await.unwrap();
+        assert_eq!(coolant.value, 83.0);
+        assert!(matches!(client.read_freeze_frame(0x0C).await, Err(ObdError::PidNotSupported(0x0C))));
+
+        // A Mode 01 reply is not a freeze frame
+        assert!(matches!(
+            ObdClient::parse_freeze_frame_response(0x05, "41 05 7B\r", 0),
+            Err(ObdError::InvalidResponse(_))
+        ));
+
+        let mut mock = ObdClient::mock().with_mock_dtcs(vec![Dtc::parse("P0217").unwrap()]);
+        assert_eq!(mock.read_freeze_frame(0x02).await.unwrap().raw_bytes, [0x02, 0x17]);
+        let rpm = mock.read_freeze_frame(0x0C).await.unwrap().value;
+        assert!((800.0..=3500.0).contains(&rpm));
+        mock.clear_dtcs().await.unwrap();
+        assert!(mock.read_freeze_frame(0x0C).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_supported_pids_follow_range_chain() {
+        let mut mock = ObdClient::mock();
+        assert_eq!(
+            mock.supported_pids().await.unwrap(),
+            [
+                0x01, 0x03, 0x04, 0x05, 0x06, 0x07, 0x0C, 0x0D, 0x0E, 0x0F, 0x10, 0x11, 0x13, 0x15,
+                0x1C, 0x1F, 0x20, 0x23, 0x2F, 0x40, 0x42, 0x46,
+            ]
+        );
+
+        // 0120 announces 41-60 but the ECU rejects 0140
+        let mut replies = INIT.to_vec();
+        replies.extend([
+            ("0100", "41 00 80 00 00 01"),
+            ("0120", "41 20 00 00 00 03"),
+            ("0140", "NO DATA"),
+        ]);
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        assert_eq!(client.supported_pids().await.unwrap(), [0x01, 0x20, 0x3F, 0x40]);
+
+        let mut replies = INIT.to_vec();
+        replies.push(("0100", "41 00 80 00"));
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        assert!(matches!(client.supported_pids().await, Err(ObdError::InvalidResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_read_vin() {
+        assert_eq!(ObdClient::mock().read_vin().await.unwrap(), MOCK_VIN);
+
+        let can = "014\r0: 49 02 01 31 48 47\r1: 43 4D 38 32 36 33 33\r2: 41 30 30 34 33 35 32\r";
+        let mut replies = INIT.to_vec();
+        replies.push(("0902", can));
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        assert_eq!(client.read_vin().await.unwrap(), MOCK_VIN);
+
+        // Legacy, frames out of order
+        let legacy = "49 02 02 48 47 43 4D\r49 02 01 00 00 00 31\r49 02 03 38 32 36 33\r\
+                      49 02 04 33 41 30 30\r49 02 05 34 33 35 32\r";
+        assert_eq!(ObdClient::parse_vin_response(legacy).unwrap(), MOCK_VIN);
+
+        // Too short, and a forbidden letter
+        assert!(matches!(
+            ObdClient::parse_vin_response("49 02 01 31 48 47 43\r"),
+            Err(ObdError::InvalidResponse(_))
+        ));
+        assert!(ObdClient::parse_vin_response(&legacy.replace("48 47 43 4D", "48 49 43 4D")).is_err());
+        assert!(matches!(ObdClient::parse_vin_response("NO DATA\r"), Err(ObdError::PidNotSupported(0x02))));
+    }
+
+    #[tokio::test]
+    async fn test_clear_dtcs_needs_acknowledgement() {
+        let mut replies = INIT.to_vec();
+        replies.push(("04", "44"));
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        client.clear_dtcs().await.unwrap();
+
+        // Engine running
+        let mut replies = INIT.to_vec();
+        replies.push(("04", "7F 04 22"));
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        assert!(matches!(
+            client.clear_dtcs().await,
+            Err(ObdError::NegativeResponse { nrc: 0x22, .. })
+        ));
+
+        let mut replies = INIT.to_vec();
+        replies.push(("04", "NO DATA"));
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        assert!(matches!(client.clear_dtcs().await, Err(ObdError::AdapterNotResponding)));
+    }
+
+    #[test]
+    fn test_parse_no_data() {
+        let err = ObdClient::parse_response(0x5C, "SEARCHING...\rNO DATA\r>", 0).unwrap_err();
+        assert!(matches!(err, ObdError::PidNotSupported(0x5C)));
+    }
+
+    #[tokio::test]
+    async fn test_link_sleeps_on_silence_and_resumes() {
+        let clock = common_types::MockClock::default();
+        let mut client = ObdClient::mock().with_clock(clock.shared());
+        assert_eq!(client.link_state(), LinkState::Active);
+
+        client.set_mock_ignition(false);
+        assert!(client.query_pid(0x0C).await.is_err());
+        clock.advance(Duration::from_secs(3));
+        assert!(client.query_pid(0x0D).await.is_err());
+        assert_eq!(client.link_state(), LinkState::Active);
+
+        clock.advance(Duration::from_secs(3));
+        assert!(client.query_pid(0x0C).await.is_err());
+        assert_eq!(client.link_state(), LinkState::Sleeping);
+
+        // Only the probe goes out while asleep
+        assert!(matches!(
+            client.query_pid(0x0C).await,
+            Err(ObdError::VehicleNotConnected)
+        ));
+        assert!(client.query_pid(IGNITION_PROBE_PID).await.is_err());
+        assert_eq!(client.link_state(), LinkState::Sleeping);
+
+        client.set_mock_ignition(true);
+        client.query_pid(IGNITION_PROBE_PID).await.unwrap();
+        assert_eq!(client.link_state(), LinkState::Active);
+        assert!(client.query_pid(0x0C).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_serial_transport_queries_adapter() {
+        let mut replies = INIT.to_vec();
+        replies.extend([
+            ("010C", "410C1AF8"),
+            ("010D", "SEARCHING...\r41 0D 55"),
+            ("0105", "NO DATA"),
+            ("0110", "SEARCHING..."),
+        ]);
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        assert!(matches!(client.query_pid(0x0C).await, Err(ObdError::AdapterNotResponding)));
+
+        client.initialize().await.unwrap();
+        assert!(client.is_connected());
+        assert!((client.query_pid(0x0C).await.unwrap().value - 1726.0).abs() < 0.01);
+        assert_eq!(client.query_pid(0x0D).await.unwrap().value, 85.0);
+        assert!(matches!(client.query_pid(0x05).await, Err(ObdError::PidNotSupported(0x05))));
+        assert!(matches!(client.query_pid(0x10).await, Err(ObdError::SearchingProtocol)));
+        assert!(matches!(
+            client.query_pid(0x5C).await,
+            Err(ObdError::UnknownCommand(command)) if command == "015C"
+        ));
+
+        // An adapter that never resets fails initialization
+        let mut client = ObdClient::with_stream("test", fake_adapter(&[])).await.unwrap();
+        assert!(matches!(client.initialize().await, Err(ObdError::UnknownCommand(_))));
+        assert!(!client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_detects_protocol() {
+        let mut replies = INIT.to_vec();
+        replies.extend([("0100", "SEARCHING...\r41 00 BE 1F A8 13"), ("ATDPN", "A6")]);
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        assert_eq!(client.protocol(), ObdProtocol::Iso15765_4Can11bit500);
+        assert!(client.protocol().is_can());
+
+        // Ignition off: initialization still succeeds, still searching
+        let mut replies = INIT.to_vec();
+        replies.extend([("0100", "SEARCHING...\rUNABLE TO CONNECT"), ("ATDPN", "A0")]);
+        let mut client = ObdClient::with_stream("test", fake_adapter(&replies)).await.unwrap();
+        client.initialize().await.unwrap();
+        assert_eq!(client.protocol(), ObdProtocol::Auto);
+    }
+
     #[tokio::test]
     async fn test_mock_protocol_change() {
         let mut client = ObdClient::mock();
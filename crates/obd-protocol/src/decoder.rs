@@ -0,0 +1,213 @@
+//! PID Decoder Overrides
+//!
+//! Some manufacturers deviate from the SAE J1979 formulas or expose
+//! enhanced PIDs outside the standard set. A [`PidDecoderRegistry`] maps a
+//! PID number to a replacement decoder; PIDs without an override fall back
+//! to the standard formulas.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use thiserror::Error;
+
+use crate::pid::standard_value;
+
+/// Widest raw integer a [`PidDecoder::Linear`] can accumulate
+const MAX_LINEAR_BYTES: usize = 8;
+
+/// Decode function for a custom PID
+pub type DecodeFn = Arc<dyn Fn(&[u8]) -> Option<f64> + Send + Sync>;
+
+/// Decoder that cannot be registered
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DecoderError {
+    /// Linear decoder reading no bytes, or more than fit in a u64
+    #[error("linear decoder for PID {pid:02X} reads {bytes} bytes, expected 1 to {MAX_LINEAR_BYTES}")]
+    LinearWidth { pid: u8, bytes: usize },
+}
+
+/// How to turn the raw data bytes of a PID into a value
+#[derive(Clone)]
+pub enum PidDecoder {
+    /// `(A*256^(n-1) + ... + last) * scale + offset` over the first `bytes` bytes
+    Linear {
+        /// Number of big-endian data bytes that form the raw integer
+        bytes: usize,
+        /// Multiplier applied to the raw integer
+        scale: f64,
+        /// Added after scaling
+        offset: f64,
+    },
+    /// Arbitrary decode function; `None` means the response was malformed
+    Custom(DecodeFn),
+}
+
+impl PidDecoder {
+    /// Build a decoder from a closure
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&[u8]) -> Option<f64> + Send + Sync + 'static,
+    {
+        PidDecoder::Custom(Arc::new(f))
+    }
+
+    /// Check that `pid` can be decoded this way
+    ///
+    /// A linear decoder wider than 8 bytes would shift data out of the u64
+    /// it accumulates into.
+    pub fn validate(&self, pid: u8) -> Result<(), DecoderError> {
+        match self {
+            PidDecoder::Linear { bytes, .. } if !(1..=MAX_LINEAR_BYTES).contains(bytes) => {
+                Err(DecoderError::LinearWidth { pid, bytes: *bytes })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Decode raw bytes, returning `None` if there are too few
+    pub fn decode(&self, bytes: &[u8]) -> Option<f64> {
+        match self {
+            PidDecoder::Linear { bytes: n, scale, offset } => {
+                if *n == 0 || *n > MAX_LINEAR_BYTES || bytes.len() < *n {
+                    return None;
+                }
+                let raw = bytes[..*n].iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+                Some(raw as f64 * scale + offset)
+            }
+            PidDecoder::Custom(f) => f(bytes),
+        }
+    }
+}
+
+impl fmt::Debug for PidDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PidDecoder::Linear { bytes, scale, offset } => f
+                .debug_struct("Linear")
+                .field("bytes", bytes)
+                .field("scale", scale)
+                .field("offset", offset)
+                .finish(),
+            PidDecoder::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// PID → decoder overrides on top of the standard formulas
+#[derive(Debug, Clone, Default)]
+pub struct PidDecoderRegistry {
+    overrides: HashMap<u8, PidDecoder>,
+}
+
+static GLOBAL: OnceLock<RwLock<PidDecoderRegistry>> = OnceLock::new();
+
+fn global() -> &'static RwLock<PidDecoderRegistry> {
+    GLOBAL.get_or_init(|| RwLock::new(PidDecoderRegistry::default()))
+}
+
+impl PidDecoderRegistry {
+    /// Create a registry that only uses the standard formulas
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the decoder for `pid`, returning the previous override
+    pub fn register(
+        &mut self,
+        pid: u8,
+        decoder: PidDecoder,
+    ) -> Result<Option<PidDecoder>, DecoderError> {
+        decoder.validate(pid)?;
+        Ok(self.overrides.insert(pid, decoder))
+    }
+
+    /// Builder-style [`register`](Self::register)
+    pub fn with(mut self, pid: u8, decoder: PidDecoder) -> Result<Self, DecoderError> {
+        self.register(pid, decoder)?;
+        Ok(self)
+    }
+
+    /// Check whether `pid` has an override
+    pub fn is_overridden(&self, pid: u8) -> bool {
+        self.overrides.contains_key(&pid)
+    }
+
+    /// Decode using the override for `pid`, or the standard formula
+    ///
+    /// PIDs that neither know, and malformed responses, decode to 0.0 as
+    /// the standard decoder always has.
+    pub fn decode(&self, pid: u8, bytes: &[u8]) -> f64 {
+        match self.overrides.get(&pid) {
+            Some(decoder) => decoder.decode(bytes).unwrap_or(0.0),
+            None => standard_value(pid, bytes),
+        }
+    }
+
+    /// Make this the registry used by [`PidResponse::decode`](crate::PidResponse::decode)
+    ///
+    /// Intended to be called once at startup, before polling begins.
+    pub fn install(self) {
+        *global().write().unwrap_or_else(|e| e.into_inner()) = self;
+    }
+
+    /// Run `f` against the installed registry
+    pub(crate) fn with_installed<R>(f: impl FnOnce(&PidDecoderRegistry) -> R) -> R {
+        f(&global().read().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PidResponse;
+
+    #[test]
+    fn test_custom_decoder_replaces_zero_fallback() {
+        // Manufacturer-specific transmission temperature: A - 50 (°C)
+        let pid = 0xE1;
+        let standard = PidDecoderRegistry::new();
+        assert_eq!(PidResponse::decode_with(&standard, pid, vec![0x82], 0).value, 0.0);
+
+        let registry = PidDecoderRegistry::new()
+            .with(pid, PidDecoder::custom(|b| b.first().map(|&a| a as f64 - 50.0)))
+            .unwrap();
+        let response = PidResponse::decode_with(&registry, pid, vec![0x82], 0);
+        assert!((response.value - 80.0).abs() < 1e-9);
+
+        // Standard PIDs are untouched
+        let speed = PidResponse::decode_with(&registry, 0x0D, vec![0x55], 0);
+        assert!((speed.value - 85.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_linear_override_of_standard_pid() {
+        let registry = PidDecoderRegistry::new().with(
+            0x10,
+            PidDecoder::Linear { bytes: 2, scale: 0.05, offset: 0.0 },
+        )
+        .unwrap();
+        let response = PidResponse::decode_with(&registry, 0x10, vec![0x01, 0x00], 0);
+        assert!((response.value - 12.8).abs() < 1e-9);
+
+        // Too few bytes for the descriptor
+        assert_eq!(registry.decode(0x10, &[0x01]), 0.0);
+        assert!(!registry.is_overridden(0x0C));
+    }
+
+    #[test]
+    fn test_linear_wider_than_u64_rejected() {
+        let wide = PidDecoder::Linear { bytes: 9, scale: 1.0, offset: 0.0 };
+        let mut registry = PidDecoderRegistry::new();
+        assert_eq!(
+            registry.register(0x10, wide.clone()).unwrap_err(),
+            DecoderError::LinearWidth { pid: 0x10, bytes: 9 }
+        );
+        assert!(!registry.is_overridden(0x10));
+        // Built directly, it still refuses rather than overflowing
+        assert_eq!(wide.decode(&[0xFF; 9]), None);
+
+        let full = PidDecoder::Linear { bytes: 8, scale: 1.0, offset: 0.0 };
+        assert!(registry.register(0x10, full).is_ok());
+    }
+}
@@ -0,0 +1,115 @@
+//! Diagnostic Trouble Codes
+//!
+//! Two-byte DTC encoding per SAE J2012: the top two bits of the first byte
+//! select the system letter, the remaining 14 bits are four hex digits.
+
+use serde::{Deserialize, Serialize};
+
+/// A diagnostic trouble code, e.g. `P0301`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Dtc {
+    /// Five-character code (system letter plus four hex digits)
+    pub code: String,
+    /// Raw two-byte encoding
+    pub raw: [u8; 2],
+}
+
+impl Dtc {
+    /// Decode a DTC from its two-byte encoding
+    pub fn from_raw(raw: [u8; 2]) -> Self {
+        let system = match raw[0] >> 6 {
+            0 => 'P', // Powertrain
+            1 => 'C', // Chassis
+            2 => 'B', // Body
+            _ => 'U', // Network
+        };
+        let code = format!(
+            "{}{:X}{:X}{:02X}",
+            system,
+            (raw[0] >> 4) & 0x03,
+            raw[0] & 0x0F,
+            raw[1]
+        );
+        Self { code, raw }
+    }
+
+    /// Parse a textual code such as `P0301`
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.trim().to_ascii_uppercase();
+        let mut chars = code.chars();
+        let system: u8 = match chars.next()? {
+            'P' => 0,
+            'C' => 1,
+            'B' => 2,
+            'U' => 3,
+            _ => return None,
+        };
+        let digits = chars.as_str();
+        if digits.len() != 4 {
+            return None;
+        }
+        let value = u16::from_str_radix(digits, 16).ok()?;
+        // The first digit only has two bits
+        if value > 0x3FFF {
+            return None;
+        }
+        let raw = [(system << 6) | (value >> 8) as u8, (value & 0xFF) as u8];
+        Some(Self::from_raw(raw))
+    }
+
+    /// Human-readable description for common generic codes
+    pub fn description(&self) -> Option<&'static str> {
+        let description = match self.code.as_str() {
+            "P0101" => "Mass air flow circuit range/performance",
+            "P0115" => "Engine coolant temperature circuit malfunction",
+            "P0128" => "Coolant thermostat below regulating temperature",
+            "P0130" => "O2 sensor circuit malfunction (bank 1, sensor 1)",
+            "P0171" => "System too lean (bank 1)",
+            "P0172" => "System too rich (bank 1)",
+            "P0217" => "Engine overtemperature condition",
+            "P0300" => "Random/multiple cylinder misfire detected",
+            "P0301" => "Cylinder 1 misfire detected",
+            "P0302" => "Cylinder 2 misfire detected",
+            "P0303" => "Cylinder 3 misfire detected",
+            "P0304" => "Cylinder 4 misfire detected",
+            "P0420" => "Catalyst system efficiency below threshold (bank 1)",
+            "P0442" => "Evaporative emission system small leak detected",
+            "P0455" => "Evaporative emission system large leak detected",
+            "P0500" => "Vehicle speed sensor malfunction",
+            "P0562" => "System voltage low",
+            "U0100" => "Lost communication with ECM/PCM",
+            _ => return None,
+        };
+        Some(description)
+    }
+}
+
+impl std::fmt::Display for Dtc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_raw() {
+        assert_eq!(Dtc::from_raw([0x03, 0x01]).code, "P0301");
+        assert_eq!(Dtc::from_raw([0x44, 0x20]).code, "C0420");
+        assert_eq!(Dtc::from_raw([0xC1, 0x00]).code, "U0100");
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let dtc = Dtc::parse("p0420").unwrap();
+        assert_eq!(dtc.raw, [0x04, 0x20]);
+        assert_eq!(dtc.code, "P0420");
+        assert!(dtc.description().unwrap().contains("Catalyst"));
+
+        assert!(Dtc::parse("X0420").is_none());
+        assert!(Dtc::parse("P4420").is_none());
+        assert!(Dtc::parse("P04").is_none());
+    }
+}
@@ -0,0 +1,285 @@
+//! ELM327 Command Transport
+//!
+//! The adapter speaks a line protocol: each command ends in `\r`, and a
+//! reply is complete once the `>` prompt comes back. Besides hex data the
+//! reply may carry status text (`SEARCHING...`, `NO DATA`, `?`), which is
+//! turned into [`ObdError`] variants here so every mode handles it alike.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::debug;
+
+use crate::error::ObdError;
+use crate::protocol::{IsoTpProgress, IsoTpReassembler};
+
+/// Printed by the adapter when it is ready for the next command
+const PROMPT: u8 = b'>';
+
+/// Byte stream to an adapter: a serial port, or a TCP socket on Wi-Fi
+/// dongles
+pub(crate) trait AdapterIo: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> AdapterIo for T {}
+
+/// Command/response exchange with an ELM327
+pub(crate) struct ElmLink {
+    io: Box<dyn AdapterIo>,
+    /// A reply timed out and its prompt may still arrive
+    stale: bool,
+}
+
+impl ElmLink {
+    pub(crate) fn new(io: Box<dyn AdapterIo>) -> Self {
+        Self { io, stale: false }
+    }
+
+    /// Send `command` and return the reply text up to the prompt
+    pub(crate) async fn command(&mut self, command: &str, timeout: Duration) -> Result<String, ObdError> {
+        if self.stale {
+            // Swallow the late reply so it is not taken for this one
+            let late = tokio::time::timeout(timeout, self.read_to_prompt()).await;
+            self.stale = !matches!(late, Ok(Ok(_)));
+        }
+
+        debug!("ELM327 <- {}", command);
+        self.io.write_all(command.as_bytes()).await?;
+        self.io.write_all(b"\r").await?;
+        self.io.flush().await?;
+
+        match tokio::time::timeout(timeout, self.read_to_prompt()).await {
+            Ok(reply) => {
+                let reply = reply?;
+                debug!("ELM327 -> {:?}", reply);
+                Ok(reply)
+            }
+            Err(_) => {
+                self.stale = true;
+                Err(ObdError::Timeout(timeout.as_millis() as u64))
+            }
+        }
+    }
+
+    async fn read_to_prompt(&mut self) -> Result<String, ObdError> {
+        let mut reply = Vec::new();
+        let mut chunk = [0u8; 64];
+        loop {
+            let n = self.io.read(&mut chunk).await?;
+            if n == 0 {
+                return Err(ObdError::SerialError("adapter closed the connection".to_string()));
+            }
+            reply.extend_from_slice(&chunk[..n]);
+            if let Some(end) = reply.iter().position(|&b| b == PROMPT) {
+                reply.truncate(end);
+                return Ok(String::from_utf8_lossy(&reply).into_owned());
+            }
+        }
+    }
+}
+
+/// Reply lines with blanks, the command echo and `SEARCHING...` removed
+///
+/// Fails on the status replies that mean no data can follow.
+pub(crate) fn reply_lines<'a>(command: &str, raw: &'a str) -> Result<Vec<&'a str>, ObdError> {
+    let mut searching = false;
+    let mut lines = Vec::new();
+    for line in raw.split(['\r', '\n']).map(str::trim) {
+        if line.starts_with("SEARCHING") {
+            searching = true;
+        } else if !line.is_empty() && !line.eq_ignore_ascii_case(command) {
+            lines.push(line);
+        }
+    }
+
+    for line in &lines {
+        let upper = line.to_ascii_uppercase();
+        if upper == "?" {
+            return Err(ObdError::UnknownCommand(command.to_string()));
+        }
+        if upper.starts_with("UNABLE TO CONNECT") {
+            return Err(ObdError::VehicleNotConnected);
+        }
+        if ["CAN ERROR", "BUS ERROR", "BUS BUSY", "BUFFER FULL"]
+            .iter()
+            .any(|status| upper.starts_with(status))
+        {
+            return Err(ObdError::CanBusError(line.to_string()));
+        }
+    }
+    if lines.is_empty() && searching {
+        return Err(ObdError::SearchingProtocol);
+    }
+    Ok(lines)
+}
+
+/// Messages in a reply, `None` on `NO DATA`
+///
+/// Each line is a message, except that the adapter prints a CAN reply
+/// spanning several frames as a byte count (three hex digits) followed by
+/// numbered `0:`, `1:`, ... lines; those are joined into one message cut
+/// to the count. With headers on (`ATH1`) on 11-bit CAN, lines are raw
+/// ISO-TP frames prefixed by the sender ID and are reassembled per ECU.
+pub(crate) fn reply_messages(command: &str, raw: &str) -> Result<Option<Vec<Vec<u8>>>, ObdError> {
+    let lines = reply_lines(command, raw)?;
+    if lines.iter().any(|l| l.eq_ignore_ascii_case("NO DATA")) {
+        return Ok(None);
+    }
+    if lines.iter().all(|l| can_header(l).is_some()) {
+        return isotp_messages(&lines, raw).map(Some);
+    }
+
+    let invalid = || ObdError::InvalidResponse(raw.trim().to_string());
+    let mut messages = Vec::new();
+    // Declared length and bytes so far of a multi-frame message
+    let mut multi: Option<(usize, Vec<u8>)> = None;
+    let finish = |multi: Option<(usize, Vec<u8>)>| -> Result<Option<Vec<u8>>, ObdError> {
+        match multi {
+            Some((len, mut bytes)) if bytes.len() >= len => {
+                bytes.truncate(len);
+                Ok(Some(bytes))
+            }
+            Some(_) => Err(invalid()),
+            None => Ok(None),
+        }
+    };
+
+    for line in lines {
+        if let Some((index, data)) = line.split_once(':') {
+            let (_, bytes) = multi.as_mut().ok_or_else(invalid)?;
+            u8::from_str_radix(index.trim(), 16).map_err(|_| invalid())?;
+            bytes.extend(line_bytes(data).ok_or_else(invalid)?);
+        } else if line.len() == 3 && line.chars().all(|c| c.is_ascii_hexdigit()) {
+            let len = usize::from_str_radix(line, 16).map_err(|_| invalid())?;
+            messages.extend(finish(multi.replace((len, Vec::new())))?);
+        } else {
+            messages.extend(finish(multi.take())?);
+            messages.push(line_bytes(line).ok_or_else(invalid)?);
+        }
+    }
+    messages.extend(finish(multi)?);
+    Ok(Some(messages))
+}
+
+/// Sender ID and frame bytes of a line with an 11-bit CAN header, e.g.
+/// `7E8 10 14 49 02 01 31 48 47`
+fn can_header(line: &str) -> Option<(&str, &str)> {
+    let (id, frame) = line.split_once(' ')?;
+    (id.len() == 3 && id.chars().all(|c| c.is_ascii_hexdigit()) && !frame.trim().is_empty())
+        .then_some((id, frame))
+}
+
+/// Messages reassembled from the raw ISO-TP frames of a headers-on reply
+///
+/// The reply is only seen once the adapter prints its prompt, so there are
+/// no per-frame receive times and the N_Cr timeout is not checked here;
+/// the adapter times the frames itself and the command timeout bounds the
+/// whole reply.
+fn isotp_messages(lines: &[&str], raw: &str) -> Result<Vec<Vec<u8>>, ObdError> {
+    let now = Instant::now();
+    let mut senders: HashMap<&str, IsoTpReassembler> = HashMap::new();
+    let mut messages = Vec::new();
+    for line in lines {
+        let Some((id, frame)) = can_header(line) else {
+            continue;
+        };
+        let frame = line_bytes(frame).ok_or_else(|| ObdError::InvalidResponse(raw.trim().to_string()))?;
+        let isotp = senders.entry(id).or_insert_with(|| IsoTpReassembler::new(Duration::MAX));
+        // The adapter has already sent the flow control
+        if let IsoTpProgress::Complete(message) = isotp.push(&frame, now)? {
+            messages.push(message);
+        }
+    }
+    if let Some((id, _)) = senders.iter().find(|(_, isotp)| isotp.in_progress()) {
+        return Err(ObdError::Protocol(format!("reply from {id} ends mid-message")));
+    }
+    Ok(messages)
+}
+
+/// Data bytes of a reply, `None` on `NO DATA`
+///
+/// Spaces between bytes are optional (`ATS0`); messages from several ECUs
+/// are concatenated.
+pub(crate) fn reply_bytes(command: &str, raw: &str) -> Result<Option<Vec<u8>>, ObdError> {
+    Ok(reply_messages(command, raw)?.map(|messages| messages.concat()))
+}
+
+/// Hex bytes of one reply line, `None` if it is not hex
+pub(crate) fn line_bytes(line: &str) -> Option<Vec<u8>> {
+    let hex: Vec<u8> = line.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reply_status_text() {
+        assert_eq!(reply_bytes("010C", "410C1AF8\r\r").unwrap(), Some(vec![0x41, 0x0C, 0x1A, 0xF8]));
+        assert_eq!(
+            reply_bytes("010D", "010D\rSEARCHING...\r41 0D 55\r").unwrap(),
+            Some(vec![0x41, 0x0D, 0x55])
+        );
+        assert_eq!(reply_bytes("015C", "NO DATA\r").unwrap(), None);
+        assert!(matches!(
+            reply_bytes("AT@9", "?\r"),
+            Err(ObdError::UnknownCommand(command)) if command == "AT@9"
+        ));
+        assert!(matches!(reply_bytes("0100", "SEARCHING...\r"), Err(ObdError::SearchingProtocol)));
+        assert!(matches!(
+            reply_bytes("0100", "SEARCHING...\rUNABLE TO CONNECT\r"),
+            Err(ObdError::VehicleNotConnected)
+        ));
+        assert!(matches!(reply_bytes("010C", "CAN ERROR\r"), Err(ObdError::CanBusError(_))));
+        assert!(matches!(reply_bytes("010C", "41 0C 1\r"), Err(ObdError::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn test_multi_frame_reply_is_one_message() {
+        let raw = "00A\r0: 43 04 01 01 02 02\r1: 03 03 04 04 00 00 00\r43 01 05 05\r";
+        let messages = reply_messages("03", raw).unwrap().unwrap();
+        assert_eq!(
+            messages,
+            [
+                vec![0x43, 0x04, 0x01, 0x01, 0x02, 0x02, 0x03, 0x03, 0x04, 0x04],
+                vec![0x43, 0x01, 0x05, 0x05],
+            ]
+        );
+
+        // Fewer bytes than declared
+        assert!(matches!(
+            reply_messages("03", "00A\r0: 43 04 01 01 02 02\r"),
+            Err(ObdError::InvalidResponse(_))
+        ));
+        // Numbered line without a byte count
+        assert!(matches!(reply_messages("03", "0: 43 04\r"), Err(ObdError::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn test_headers_on_reply_is_reassembled_per_ecu() {
+        // Engine and transmission ECUs interleaving their DTC replies
+        let raw = "7E8 10 0A 43 04 01 01 02 02\r7E9 04 43 01 07 00\r7E8 21 03 03 04 04 00 00 00\r";
+        assert_eq!(
+            reply_messages("03", raw).unwrap().unwrap(),
+            [
+                vec![0x43, 0x01, 0x07, 0x00],
+                vec![0x43, 0x04, 0x01, 0x01, 0x02, 0x02, 0x03, 0x03, 0x04, 0x04],
+            ]
+        );
+
+        assert!(matches!(
+            reply_messages("03", "7E8 10 0A 43 04 01 01 02 02\r7E8 22 03 03 04 04\r"),
+            Err(ObdError::Protocol(_))
+        ));
+        assert!(matches!(
+            reply_messages("03", "7E8 10 0A 43 04 01 01 02 02\r"),
+            Err(ObdError::Protocol(_))
+        ));
+    }
+}
@@ -40,6 +40,66 @@ pub enum ObdError {
     /// Vehicle not connected
     #[error("Vehicle ignition is off or not connected")]
     VehicleNotConnected,
+
+    /// Adapter did not recognise the command (`?`)
+    #[error("Adapter rejected command {0:?}")]
+    UnknownCommand(String),
+
+    /// Malformed transport-layer frame sequence
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    /// Adapter was still searching for a bus protocol when it gave up
+    #[error("Adapter still searching for a vehicle protocol")]
+    SearchingProtocol,
+
+    /// ECU rejected the request with a negative response (`7F <sid> <nrc>`)
+    #[error("Negative response to service {service:02X}: NRC {nrc:02X} ({meaning})")]
+    NegativeResponse {
+        service: u8,
+        nrc: u8,
+        meaning: &'static str,
+    },
+}
+
+impl ObdError {
+    /// Build a negative-response error, decoding the NRC byte
+    pub fn negative_response(service: u8, nrc: u8) -> Self {
+        ObdError::NegativeResponse {
+            service,
+            nrc,
+            meaning: nrc_meaning(nrc),
+        }
+    }
+}
+
+/// Decode a standard negative response code (ISO 14229-1 / ISO 15765-4)
+pub fn nrc_meaning(nrc: u8) -> &'static str {
+    match nrc {
+        0x10 => "general reject",
+        0x11 => "service not supported",
+        0x12 => "sub-function not supported",
+        0x13 => "incorrect message length or invalid format",
+        0x14 => "response too long",
+        0x21 => "busy, repeat request",
+        0x22 => "conditions not correct",
+        0x24 => "request sequence error",
+        0x25 => "no response from sub-net component",
+        0x26 => "failure prevents execution of requested action",
+        0x31 => "request out of range",
+        0x33 => "security access denied",
+        0x35 => "invalid key",
+        0x36 => "exceeded number of attempts",
+        0x37 => "required time delay not expired",
+        0x70 => "upload/download not accepted",
+        0x71 => "transfer data suspended",
+        0x72 => "general programming failure",
+        0x73 => "wrong block sequence counter",
+        0x78 => "request correctly received, response pending",
+        0x7E => "sub-function not supported in active session",
+        0x7F => "service not supported in active session",
+        _ => "unknown negative response code",
+    }
 }
 
 impl From<std::io::Error> for ObdError {
@@ -12,6 +12,8 @@ use thiserror::Error;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
+use crate::can_signal::{CanSignalDecoder, CanSignalFrame};
+
 /// FFI type aliases matching C structures
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -351,16 +353,38 @@ impl Drop for CanDriver {
     }
 }
 
+/// Raw frames drained per polling pass, so a busy bus can't starve the
+/// sensor frames
+const RAW_FRAMES_PER_POLL: usize = 32;
+
 /// Async wrapper around CanDriver for use with Tokio
 pub struct AsyncCanDriver {
     receiver: mpsc::Receiver<CSensorFrame>,
+    signals: Option<mpsc::Receiver<CanSignalFrame>>,
     _shutdown: std::sync::Arc<AtomicBool>,
 }
 
 impl AsyncCanDriver {
     /// Spawn a new async CAN driver with a background polling thread
     pub fn spawn(config: DriverConfig) -> Result<Self, DriverError> {
+        Self::spawn_inner(config, None)
+    }
+
+    /// Spawn a driver that also decodes raw frames on the IDs in
+    /// `decoder`'s signal map, delivered by [`next_signals`](Self::next_signals)
+    pub fn spawn_with_signals(config: DriverConfig, decoder: CanSignalDecoder) -> Result<Self, DriverError> {
+        Self::spawn_inner(config, Some(decoder))
+    }
+
+    fn spawn_inner(config: DriverConfig, decoder: Option<CanSignalDecoder>) -> Result<Self, DriverError> {
         let (tx, rx) = mpsc::channel::<CSensorFrame>(1000);
+        let (signal_tx, signal_rx) = match decoder {
+            Some(_) => {
+                let (tx, rx) = mpsc::channel::<CanSignalFrame>(1000);
+                (Some(tx), Some(rx))
+            }
+            None => (None, None),
+        };
         let shutdown = std::sync::Arc::new(AtomicBool::new(false));
         let shutdown_clone = shutdown.clone();
 
@@ -375,6 +399,20 @@ impl AsyncCanDriver {
             };
 
             while !shutdown_clone.load(Ordering::SeqCst) {
+                if let (Some(decoder), Some(signal_tx)) = (&decoder, &signal_tx) {
+                    for _ in 0..RAW_FRAMES_PER_POLL {
+                        let Ok(Some(raw)) = driver.read_frame() else {
+                            break;
+                        };
+                        if let Some(decoded) = decoder.decode(&raw) {
+                            // A slow consumer loses signal frames, not sensor frames
+                            if signal_tx.try_send(decoded).is_err() {
+                                debug!("Signal channel full, dropping CAN ID {:#05X}", raw.can_id);
+                            }
+                        }
+                    }
+                }
+
                 match driver.read_sensor_frame() {
                     Ok(Some(frame)) => {
                         if tx.blocking_send(frame).is_err() {
@@ -396,6 +434,7 @@ impl AsyncCanDriver {
 
         Ok(Self {
             receiver: rx,
+            signals: signal_rx,
             _shutdown: shutdown,
         })
     }
@@ -404,6 +443,11 @@ impl AsyncCanDriver {
     pub async fn next_frame(&mut self) -> Option<CSensorFrame> {
         self.receiver.recv().await
     }
+
+    /// Receive the next decoded raw frame; `None` without a signal map
+    pub async fn next_signals(&mut self) -> Option<CanSignalFrame> {
+        self.signals.as_mut()?.recv().await
+    }
 }
 
 #[cfg(test)]
@@ -8,17 +8,31 @@
 //! The `ffi` module provides safe Rust bindings to the C++ CAN driver for
 //! low-latency hardware interaction.
 
+mod can_signal;
 mod client;
+mod decoder;
+mod dtc;
+mod elm;
 mod error;
 pub mod ffi;
+mod link;
 mod pid;
 mod protocol;
+mod readiness;
 
-pub use client::ObdClient;
-pub use error::ObdError;
+pub use can_signal::{
+    ByteOrder, CanSignal, CanSignalDecoder, CanSignalFrame, DecodedSignal, SignalMapError,
+};
+pub use client::{ObdClient, MOCK_VIN};
+pub use decoder::{DecodeFn, DecoderError, PidDecoder, PidDecoderRegistry};
+pub use dtc::Dtc;
+pub use error::{nrc_meaning, ObdError};
 pub use ffi::{AsyncCanDriver, CanDriver, CSensorFrame, DriverConfig, DriverError};
-pub use pid::{Pid, PidResponse, SensorFrame};
-pub use protocol::ObdProtocol;
+pub use link::{LinkConfig, LinkState, IGNITION_PROBE_PID};
+pub use common_types::MockConfig;
+pub use pid::{valid, Pid, PidResponse, SensorFrame};
+pub use protocol::{IsoTpProgress, IsoTpReassembler, ObdProtocol, ISOTP_FRAME_TIMEOUT};
+pub use readiness::{Monitor, MonitorState, ReadinessStatus};
 
 /// OBD-II mode constants
 pub mod mode {
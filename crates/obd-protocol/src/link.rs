@@ -0,0 +1,85 @@
+//! Ignition State Inference
+//!
+//! With the key off the ECU stops answering: every request times out or
+//! comes back `NO DATA`. Once nothing has answered for the silence window
+//! the link is considered asleep; only the `0100` probe is sent, at a slow
+//! rate, until the ECU responds again.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// PID polled while the link sleeps (`0100`, supported PIDs 01-20)
+pub const IGNITION_PROBE_PID: u8 = 0x00;
+
+/// Whether the ECU is answering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkState {
+    /// ECU responding; full scheduling
+    Active,
+    /// Ignition presumed off; only the probe is sent
+    Sleeping,
+}
+
+/// Ignition inference settings
+#[derive(Debug, Clone, Copy)]
+pub struct LinkConfig {
+    /// Time without any successful response before the link sleeps
+    pub silence_window: Duration,
+    /// Interval between `0100` probes while sleeping
+    pub sleep_poll_interval: Duration,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            silence_window: Duration::from_secs(5),
+            sleep_poll_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Tracks responses and decides the link state
+#[derive(Debug, Clone)]
+pub(crate) struct LinkMonitor {
+    config: LinkConfig,
+    state: LinkState,
+    last_response: Instant,
+}
+
+impl LinkMonitor {
+    pub(crate) fn new(config: LinkConfig, now: Instant) -> Self {
+        Self {
+            config,
+            state: LinkState::Active,
+            last_response: now,
+        }
+    }
+
+    pub(crate) fn config(&self) -> LinkConfig {
+        self.config
+    }
+
+    pub(crate) fn state(&self) -> LinkState {
+        self.state
+    }
+
+    /// Note a successful response; returns true if this woke the link
+    pub(crate) fn record_response(&mut self, now: Instant) -> bool {
+        self.last_response = now;
+        let woke = self.state == LinkState::Sleeping;
+        self.state = LinkState::Active;
+        woke
+    }
+
+    /// Note a request that went unanswered; returns true if the link
+    /// just fell asleep
+    pub(crate) fn record_silence(&mut self, now: Instant) -> bool {
+        if self.state == LinkState::Active
+            && now.duration_since(self.last_response) >= self.config.silence_window
+        {
+            self.state = LinkState::Sleeping;
+            return true;
+        }
+        false
+    }
+}
@@ -2,12 +2,17 @@
 //!
 //! Defines the standard OBD-II Parameter IDs (PIDs) and their decoding formulas.
 
+use common_types::units;
 use serde::{Deserialize, Serialize};
 
+use crate::decoder::PidDecoderRegistry;
+
 /// Standard OBD-II PIDs for Mode 01 (current data)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Pid {
+    /// Monitor status since DTCs cleared (0x01)
+    MonitorStatus = 0x01,
     /// Engine RPM (0x0C)
     Rpm = 0x0C,
     /// Vehicle speed (0x0D)
@@ -24,22 +29,66 @@ pub enum Pid {
     LongFuelTrim = 0x07,
     /// Oxygen sensor voltage bank 1, sensor 1 (0x14)
     O2Voltage = 0x14,
+    /// Oxygen sensor voltage bank 2, sensor 1 (0x18)
+    O2VoltageBank2 = 0x18,
     /// Intake manifold absolute pressure (0x0B)
     IntakeManifoldPressure = 0x0B,
     /// Throttle position (0x11)
     ThrottlePosition = 0x11,
+    /// Fuel tank level input (0x2F)
+    FuelLevel = 0x2F,
+    /// Intake air temperature (0x0F)
+    IntakeAirTemp = 0x0F,
+    /// Control module voltage (0x42)
+    ControlModuleVoltage = 0x42,
+    /// Ambient air temperature (0x46)
+    AmbientTemp = 0x46,
+    /// Fuel rail gauge pressure, direct injection (0x23)
+    FuelRailPressure = 0x23,
 }
 
 impl Pid {
+    /// All PIDs this crate can decode
+    pub const ALL: [Pid; 17] = [
+        Pid::MonitorStatus,
+        Pid::Rpm,
+        Pid::Speed,
+        Pid::CoolantTemp,
+        Pid::EngineLoad,
+        Pid::Maf,
+        Pid::ShortFuelTrim,
+        Pid::LongFuelTrim,
+        Pid::O2Voltage,
+        Pid::O2VoltageBank2,
+        Pid::IntakeManifoldPressure,
+        Pid::ThrottlePosition,
+        Pid::FuelLevel,
+        Pid::IntakeAirTemp,
+        Pid::ControlModuleVoltage,
+        Pid::AmbientTemp,
+        Pid::FuelRailPressure,
+    ];
+
     /// Get the PID hex value
     pub fn as_hex(&self) -> u8 {
         *self as u8
     }
 
+    /// Look up a known PID by its hex value
+    pub fn from_hex(value: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|pid| pid.as_hex() == value)
+    }
+
     /// Get the number of response bytes for this PID
     pub fn response_bytes(&self) -> usize {
         match self {
-            Pid::Rpm | Pid::Maf | Pid::O2Voltage => 2,
+            Pid::MonitorStatus => 4,
+            Pid::Rpm
+            | Pid::Maf
+            | Pid::O2Voltage
+            | Pid::O2VoltageBank2
+            | Pid::ControlModuleVoltage
+            | Pid::FuelRailPressure => 2,
             _ => 1,
         }
     }
@@ -49,6 +98,7 @@ impl Pid {
         match self {
             Pid::Rpm | Pid::Speed | Pid::CoolantTemp | Pid::EngineLoad => 10, // 5Hz
             Pid::Maf => 5, // 1Hz
+            Pid::FuelLevel | Pid::AmbientTemp => 1, // 0.2Hz
             _ => 2, // 0.5Hz
         }
     }
@@ -69,8 +119,22 @@ pub struct PidResponse {
 
 impl PidResponse {
     /// Create a new PID response by decoding raw bytes
+    ///
+    /// Uses the installed [`PidDecoderRegistry`], so make-specific
+    /// overrides registered at startup take precedence over the standard
+    /// formulas.
     pub fn decode(pid: u8, raw_bytes: Vec<u8>, timestamp_ms: u64) -> Self {
-        let value = Self::decode_value(pid, &raw_bytes);
+        PidDecoderRegistry::with_installed(|r| Self::decode_with(r, pid, raw_bytes, timestamp_ms))
+    }
+
+    /// Decode raw bytes with an explicit registry
+    pub fn decode_with(
+        registry: &PidDecoderRegistry,
+        pid: u8,
+        raw_bytes: Vec<u8>,
+        timestamp_ms: u64,
+    ) -> Self {
+        let value = registry.decode(pid, &raw_bytes);
         Self {
             pid,
             timestamp_ms,
@@ -78,49 +142,105 @@ impl PidResponse {
             raw_bytes,
         }
     }
+}
 
-    /// Decode the raw bytes to a value based on the PID formula
-    fn decode_value(pid: u8, bytes: &[u8]) -> f64 {
-        match pid {
-            // RPM: ((A*256)+B)/4
-            0x0C if bytes.len() >= 2 => {
-                ((bytes[0] as f64 * 256.0) + bytes[1] as f64) / 4.0
-            }
-            // Speed: A (km/h)
-            0x0D if !bytes.is_empty() => bytes[0] as f64,
-            // Coolant Temp: A - 40 (°C)
-            0x05 if !bytes.is_empty() => bytes[0] as f64 - 40.0,
-            // Engine Load: A * 100 / 255 (%)
-            0x04 if !bytes.is_empty() => bytes[0] as f64 * 100.0 / 255.0,
-            // MAF: ((A*256)+B) / 100 (g/s)
-            0x10 if bytes.len() >= 2 => {
-                ((bytes[0] as f64 * 256.0) + bytes[1] as f64) / 100.0
-            }
-            // Short/Long fuel trim: (A - 128) * 100 / 128 (%)
-            0x06 | 0x07 if !bytes.is_empty() => {
-                (bytes[0] as f64 - 128.0) * 100.0 / 128.0
-            }
-            // O2 Voltage: A / 200 (V)
-            0x14 if !bytes.is_empty() => bytes[0] as f64 / 200.0,
-            // Intake manifold pressure: A (kPa)
-            0x0B if !bytes.is_empty() => bytes[0] as f64,
-            // Throttle position: A * 100 / 255 (%)
-            0x11 if !bytes.is_empty() => bytes[0] as f64 * 100.0 / 255.0,
-            _ => 0.0,
+/// Decode the raw bytes to a value based on the standard PID formula
+pub(crate) fn standard_value(pid: u8, bytes: &[u8]) -> f64 {
+    match pid {
+        // Monitor status: bit-packed, see ReadinessStatus; the scalar
+        // value is the stored DTC count, A & 0x7F
+        0x01 if !bytes.is_empty() => (bytes[0] & 0x7F) as f64,
+        // RPM: ((A*256)+B)/4
+        0x0C if bytes.len() >= 2 => {
+            ((bytes[0] as f64 * 256.0) + bytes[1] as f64) / 4.0
+        }
+        // Speed: A (km/h)
+        0x0D if !bytes.is_empty() => bytes[0] as f64,
+        // Coolant Temp: A - 40 (°C)
+        0x05 if !bytes.is_empty() => bytes[0] as f64 - 40.0,
+        // Engine Load: A * 100 / 255 (%)
+        0x04 if !bytes.is_empty() => bytes[0] as f64 * 100.0 / 255.0,
+        // MAF: ((A*256)+B) / 100 (g/s)
+        0x10 if bytes.len() >= 2 => {
+            ((bytes[0] as f64 * 256.0) + bytes[1] as f64) / 100.0
         }
+        // Short/Long fuel trim: (A - 128) * 100 / 128 (%)
+        0x06 | 0x07 if !bytes.is_empty() => {
+            (bytes[0] as f64 - 128.0) * 100.0 / 128.0
+        }
+        // O2 Voltage: A / 200 (V)
+        0x14 | 0x18 if !bytes.is_empty() => bytes[0] as f64 / 200.0,
+        // Intake manifold pressure: A (kPa)
+        0x0B if !bytes.is_empty() => bytes[0] as f64,
+        // Throttle position: A * 100 / 255 (%)
+        0x11 if !bytes.is_empty() => bytes[0] as f64 * 100.0 / 255.0,
+        // Fuel level: A * 100 / 255 (%)
+        0x2F if !bytes.is_empty() => bytes[0] as f64 * 100.0 / 255.0,
+        // Intake air / ambient temp: A - 40 (°C)
+        0x0F | 0x46 if !bytes.is_empty() => bytes[0] as f64 - 40.0,
+        // Control module voltage: ((A*256)+B) / 1000 (V)
+        0x42 if bytes.len() >= 2 => {
+            ((bytes[0] as f64 * 256.0) + bytes[1] as f64) / 1000.0
+        }
+        // Fuel rail gauge pressure: ((A*256)+B) * 10 (kPa)
+        0x23 if bytes.len() >= 2 => {
+            ((bytes[0] as f64 * 256.0) + bytes[1] as f64) * 10.0
+        }
+        _ => 0.0,
     }
 }
 
+/// Bits of [`SensorFrame::valid_mask`]
+///
+/// The low byte matches `valid_mask` in the C driver's `c_sensor_frame_t`;
+/// fields only the Rust frame carries use the bits above it.
+pub mod valid {
+    /// Engine RPM
+    pub const RPM: u16 = 0x01;
+    /// Coolant temperature
+    pub const COOLANT_TEMP: u16 = 0x02;
+    /// Vehicle speed
+    pub const SPEED: u16 = 0x04;
+    /// Engine load
+    pub const ENGINE_LOAD: u16 = 0x08;
+    /// Mass air flow
+    pub const MAF: u16 = 0x10;
+    /// Throttle position (C frame only)
+    pub const THROTTLE_POS: u16 = 0x20;
+    /// Short-term fuel trim
+    pub const FUEL_TRIM_SHORT: u16 = 0x40;
+    /// Long-term fuel trim
+    pub const FUEL_TRIM_LONG: u16 = 0x80;
+    /// O2 sensor voltage
+    pub const O2_VOLTAGE: u16 = 0x100;
+    /// Bank 2 O2 sensor voltage
+    pub const O2_VOLTAGE_B2: u16 = 0x200;
+    /// Fuel tank level
+    pub const FUEL_LEVEL: u16 = 0x400;
+    /// Intake air temperature
+    pub const INTAKE_AIR_TEMP: u16 = 0x800;
+    /// Control module voltage
+    pub const CONTROL_MODULE_VOLTAGE: u16 = 0x1000;
+    /// Ambient air temperature
+    pub const AMBIENT_TEMP: u16 = 0x2000;
+    /// Fuel rail pressure
+    pub const FUEL_RAIL_PRESSURE: u16 = 0x4000;
+}
+
 /// A complete sensor frame containing all collected PIDs
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SensorFrame {
-    /// Timestamp (Unix ms)
+    /// Capture time of the newest field (Unix ms)
     pub timestamp_ms: u64,
+    /// Capture time of the oldest field (Unix ms); fields are read one PID
+    /// at a time, so a frame spans `capture_start_ms..=timestamp_ms`
+    #[serde(default)]
+    pub capture_start_ms: u64,
     /// Engine RPM
     pub rpm: u16,
     /// Vehicle speed (km/h)
     pub speed: u8,
-    /// Coolant temperature (°C, offset by +40 for storage as u8)
+    /// Coolant temperature (°C)
     pub coolant_temp: i16,
     /// Engine load (0-100%)
     pub engine_load: u8,
@@ -132,6 +252,28 @@ pub struct SensorFrame {
     pub fuel_trim_long: i16,
     /// O2 sensor voltage (V * 1000)
     pub o2_voltage: u16,
+    /// Bank 2 O2 sensor voltage (V * 1000); 0 on inline engines
+    #[serde(default)]
+    pub o2_voltage_b2: u16,
+    /// Fuel tank level (0-100%)
+    #[serde(default)]
+    pub fuel_level: f32,
+    /// Intake air temperature (°C)
+    #[serde(default)]
+    pub intake_air_temp: i16,
+    /// Control module voltage (V * 1000)
+    #[serde(default)]
+    pub control_module_voltage: u16,
+    /// Ambient air temperature (°C)
+    #[serde(default)]
+    pub ambient_temp: i16,
+    /// Fuel rail gauge pressure (kPa)
+    #[serde(default)]
+    pub fuel_rail_pressure: u32,
+    /// Fields populated from a response, as [`valid`] bits; unset fields
+    /// hold their default rather than a reading
+    #[serde(default)]
+    pub valid_mask: u16,
 }
 
 impl SensorFrame {
@@ -142,23 +284,175 @@ impl SensorFrame {
     pub fn new(timestamp_ms: u64) -> Self {
         Self {
             timestamp_ms,
+            capture_start_ms: timestamp_ms,
             ..Default::default()
         }
     }
 
-    /// Update a field from a PID response
-    pub fn update_from_response(&mut self, response: &PidResponse) {
-        match response.pid {
-            0x0C => self.rpm = response.value as u16,
-            0x0D => self.speed = response.value as u8,
-            0x05 => self.coolant_temp = response.value as i16,
-            0x04 => self.engine_load = response.value as u8,
-            0x10 => self.maf = (response.value * 100.0) as u16,
-            0x06 => self.fuel_trim_short = (response.value * 100.0) as i16,
-            0x07 => self.fuel_trim_long = (response.value * 100.0) as i16,
-            0x14 => self.o2_voltage = (response.value * 1000.0) as u16,
-            _ => {}
+    /// Build a frame from a batch of responses
+    ///
+    /// The frame spans the oldest to the newest response timestamp;
+    /// `valid_mask` records exactly which fields the batch supplied.
+    pub fn from_batch_response(responses: &[PidResponse]) -> Self {
+        let timestamp_ms = responses.iter().map(|r| r.timestamp_ms).max().unwrap_or(0);
+        let mut frame = Self::new(timestamp_ms);
+        frame.capture_start_ms = responses
+            .iter()
+            .map(|r| r.timestamp_ms)
+            .min()
+            .unwrap_or(timestamp_ms);
+        for response in responses {
+            frame.update_from_response(response);
         }
+        frame
+    }
+
+    /// Update a field from a PID response
+    ///
+    /// Returns the [`valid`] bit of the updated field, or 0 when the frame
+    /// does not carry the PID. Timestamps are left to the caller.
+    pub fn update_from_response(&mut self, response: &PidResponse) -> u16 {
+        let bit = match response.pid {
+            0x0C => {
+                self.rpm = response.value as u16;
+                valid::RPM
+            }
+            0x0D => {
+                self.speed = response.value as u8;
+                valid::SPEED
+            }
+            0x05 => {
+                self.coolant_temp = response.value as i16;
+                valid::COOLANT_TEMP
+            }
+            0x04 => {
+                self.engine_load = response.value as u8;
+                valid::ENGINE_LOAD
+            }
+            0x10 => {
+                self.maf = (response.value * units::MAF_SCALE) as u16;
+                valid::MAF
+            }
+            0x06 => {
+                self.fuel_trim_short = (response.value * units::FUEL_TRIM_SCALE) as i16;
+                valid::FUEL_TRIM_SHORT
+            }
+            0x07 => {
+                self.fuel_trim_long = (response.value * units::FUEL_TRIM_SCALE) as i16;
+                valid::FUEL_TRIM_LONG
+            }
+            0x14 => {
+                self.o2_voltage = (response.value * units::O2_VOLTAGE_SCALE) as u16;
+                valid::O2_VOLTAGE
+            }
+            0x18 => {
+                self.o2_voltage_b2 = (response.value * units::O2_VOLTAGE_SCALE) as u16;
+                valid::O2_VOLTAGE_B2
+            }
+            0x2F => {
+                self.fuel_level = response.value as f32;
+                valid::FUEL_LEVEL
+            }
+            0x0F => {
+                self.intake_air_temp = response.value as i16;
+                valid::INTAKE_AIR_TEMP
+            }
+            0x42 => {
+                self.control_module_voltage = (response.value * 1000.0).round() as u16;
+                valid::CONTROL_MODULE_VOLTAGE
+            }
+            0x46 => {
+                self.ambient_temp = response.value as i16;
+                valid::AMBIENT_TEMP
+            }
+            0x23 => {
+                self.fuel_rail_pressure = response.value as u32;
+                valid::FUEL_RAIL_PRESSURE
+            }
+            _ => 0,
+        };
+        self.valid_mask |= bit;
+        bit
+    }
+
+    /// Time between the oldest and newest field captures (ms)
+    pub fn capture_span_ms(&self) -> u64 {
+        self.timestamp_ms.saturating_sub(self.capture_start_ms)
+    }
+
+    /// Whether every field in `mask` was populated
+    pub fn is_valid(&self, mask: u16) -> bool {
+        self.valid_mask & mask == mask
+    }
+
+    /// Engine speed (rpm)
+    pub fn rpm(&self) -> f64 {
+        self.rpm as f64
+    }
+
+    /// Vehicle speed (km/h)
+    pub fn speed_kmh(&self) -> f64 {
+        self.speed as f64
+    }
+
+    /// Coolant temperature (°C)
+    pub fn coolant_temp_c(&self) -> f64 {
+        self.coolant_temp as f64
+    }
+
+    /// Calculated engine load (%)
+    pub fn engine_load_pct(&self) -> f64 {
+        self.engine_load as f64
+    }
+
+    /// Mass air flow rate (g/s)
+    pub fn maf_g_s(&self) -> f64 {
+        units::maf_g_s(self.maf)
+    }
+
+    /// Short-term fuel trim (%)
+    pub fn fuel_trim_short_pct(&self) -> f64 {
+        units::fuel_trim_pct(self.fuel_trim_short)
+    }
+
+    /// Long-term fuel trim (%)
+    pub fn fuel_trim_long_pct(&self) -> f64 {
+        units::fuel_trim_pct(self.fuel_trim_long)
+    }
+
+    /// Oxygen sensor voltage (V)
+    pub fn o2_voltage_v(&self) -> f64 {
+        units::o2_voltage_v(self.o2_voltage)
+    }
+
+    /// Bank 2 oxygen sensor voltage (V)
+    pub fn o2_voltage_b2_v(&self) -> f64 {
+        units::o2_voltage_v(self.o2_voltage_b2)
+    }
+
+    /// Fuel tank level (%)
+    pub fn fuel_level_pct(&self) -> f64 {
+        self.fuel_level as f64
+    }
+
+    /// Intake air temperature (°C)
+    pub fn intake_air_temp_c(&self) -> f64 {
+        self.intake_air_temp as f64
+    }
+
+    /// Control module voltage (V)
+    pub fn control_module_voltage_v(&self) -> f64 {
+        self.control_module_voltage as f64 / 1000.0
+    }
+
+    /// Ambient air temperature (°C)
+    pub fn ambient_temp_c(&self) -> f64 {
+        self.ambient_temp as f64
+    }
+
+    /// Fuel rail gauge pressure (kPa)
+    pub fn fuel_rail_pressure_kpa(&self) -> f64 {
+        self.fuel_rail_pressure as f64
     }
 }
 
@@ -166,6 +460,13 @@ impl SensorFrame {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pid_from_hex() {
+        assert_eq!(Pid::from_hex(0x0C), Some(Pid::Rpm));
+        assert_eq!(Pid::from_hex(0x11), Some(Pid::ThrottlePosition));
+        assert_eq!(Pid::from_hex(0xA6), None);
+    }
+
     #[test]
     fn test_rpm_decode() {
         // 1A 2B => ((0x1A * 256) + 0x2B) / 4 = (26*256 + 43) / 4 = 6699/4 = 1674.75
@@ -187,6 +488,91 @@ mod tests {
         assert!((response.value - 85.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_physical_unit_accessors() {
+        let frame = SensorFrame {
+            rpm: 2450,
+            speed: 88,
+            coolant_temp: -12,
+            engine_load: 47,
+            maf: 1234,
+            fuel_trim_short: -525,
+            fuel_trim_long: 310,
+            o2_voltage: 450,
+            ..Default::default()
+        };
+
+        assert_eq!(frame.rpm(), 2450.0);
+        assert_eq!(frame.speed_kmh(), 88.0);
+        assert_eq!(frame.coolant_temp_c(), -12.0);
+        assert_eq!(frame.engine_load_pct(), 47.0);
+        assert!((frame.maf_g_s() - 12.34).abs() < 1e-9);
+        assert!((frame.fuel_trim_short_pct() + 5.25).abs() < 1e-9);
+        assert!((frame.fuel_trim_long_pct() - 3.1).abs() < 1e-9);
+        assert!((frame.o2_voltage_v() - 0.45).abs() < 1e-9);
+
+        // Round-trip through the PID decoder
+        let mut frame = SensorFrame::new(0);
+        frame.update_from_response(&PidResponse::decode(0x10, vec![0x04, 0xD2], 0));
+        assert!((frame.maf_g_s() - 12.34).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_batch_response_valid_mask() {
+        let responses = [
+            PidResponse::decode(0x0C, vec![0x1A, 0x2B], 100),
+            PidResponse::decode(0x05, vec![0x73], 120),
+            PidResponse::decode(0x14, vec![0x5A], 140),
+            // Not carried by the frame
+            PidResponse::decode(0x0B, vec![0x40], 160),
+        ];
+        let frame = SensorFrame::from_batch_response(&responses);
+
+        assert_eq!(frame.valid_mask, valid::RPM | valid::COOLANT_TEMP | valid::O2_VOLTAGE);
+        assert_eq!(frame.timestamp_ms, 160);
+        assert_eq!(frame.capture_span_ms(), 60);
+        assert_eq!(frame.rpm, 1674);
+        assert_eq!(frame.coolant_temp, 75);
+        assert!(frame.is_valid(valid::RPM | valid::O2_VOLTAGE));
+        assert!(!frame.is_valid(valid::SPEED));
+        assert_eq!(SensorFrame::from_batch_response(&[]).valid_mask, 0);
+    }
+
+    #[test]
+    fn test_extended_pids_decode_into_frame() {
+        let responses = [
+            // 0xBF = 191 => 74.9%
+            PidResponse::decode(0x2F, vec![0xBF], 0),
+            // 0x41 = 65 => 25°C
+            PidResponse::decode(0x0F, vec![0x41], 0),
+            // 0x3782 = 14210 => 14.21 V
+            PidResponse::decode(0x42, vec![0x37, 0x82], 0),
+            // 0x32 = 50 => 10°C
+            PidResponse::decode(0x46, vec![0x32], 0),
+            // 0x01F4 = 500 => 5000 kPa
+            PidResponse::decode(0x23, vec![0x01, 0xF4], 0),
+        ];
+        assert!((responses[0].value - 74.9).abs() < 0.01);
+        assert!((responses[2].value - 14.21).abs() < 1e-9);
+        assert_eq!(responses[4].value, 5000.0);
+
+        let frame = SensorFrame::from_batch_response(&responses);
+        assert!(frame.is_valid(
+            valid::FUEL_LEVEL
+                | valid::INTAKE_AIR_TEMP
+                | valid::CONTROL_MODULE_VOLTAGE
+                | valid::AMBIENT_TEMP
+                | valid::FUEL_RAIL_PRESSURE
+        ));
+        assert!((frame.fuel_level_pct() - 74.9).abs() < 0.01);
+        assert_eq!(frame.intake_air_temp_c(), 25.0);
+        assert!((frame.control_module_voltage_v() - 14.21).abs() < 1e-9);
+        assert_eq!(frame.ambient_temp_c(), 10.0);
+        assert_eq!(frame.fuel_rail_pressure_kpa(), 5000.0);
+        assert_eq!(Pid::ControlModuleVoltage.response_bytes(), 2);
+        assert_eq!(Pid::from_hex(0x23), Some(Pid::FuelRailPressure));
+    }
+
     #[test]
     fn test_fuel_trim_decode() {
         // 0x80 = 128, so trim = (128-128)*100/128 = 0%
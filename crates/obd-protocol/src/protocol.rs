@@ -1,6 +1,13 @@
 //! OBD-II Protocol Definitions
+//!
+//! Also home to ISO 15765-2 (ISO-TP) reassembly: on CAN, replies longer
+//! than seven bytes (VINs, long DTC lists) arrive as a First Frame, a
+//! Flow Control from the receiver, then numbered Consecutive Frames.
 
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::error::ObdError;
 
 /// Supported OBD-II protocols
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -44,6 +51,28 @@ impl ObdProtocol {
         }
     }
 
+    /// Parse the adapter's `ATDPN` reply, e.g. `A6` (found by automatic
+    /// search) or `6` (set explicitly)
+    ///
+    /// `None` for `0` and `A0`, meaning nothing was negotiated yet, and for
+    /// user-defined protocols.
+    pub fn from_protocol_number(reply: &str) -> Option<Self> {
+        let reply = reply.trim();
+        let number = reply.strip_prefix(['A', 'a']).unwrap_or(reply);
+        match number {
+            "1" => Some(ObdProtocol::J1850Pwm),
+            "2" => Some(ObdProtocol::J1850Vpw),
+            "3" => Some(ObdProtocol::Iso9141_2),
+            "4" => Some(ObdProtocol::Iso14230_4Kwp),
+            "5" => Some(ObdProtocol::Iso14230_4KwpFast),
+            "6" => Some(ObdProtocol::Iso15765_4Can11bit500),
+            "7" => Some(ObdProtocol::Iso15765_4Can29bit500),
+            "8" => Some(ObdProtocol::Iso15765_4Can11bit250),
+            "9" => Some(ObdProtocol::Iso15765_4Can29bit250),
+            _ => None,
+        }
+    }
+
     /// Check if this is a CAN protocol
     pub fn is_can(&self) -> bool {
         matches!(
@@ -71,3 +100,186 @@ impl Default for ObdProtocol {
         ObdProtocol::Auto
     }
 }
+
+/// Longest wait for the next Consecutive Frame (N_Cr, ISO 15765-2)
+pub const ISOTP_FRAME_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Flow Control sent after a First Frame: clear to send, no block limit,
+/// no separation time
+const FLOW_CONTROL_CTS: [u8; 8] = [0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+/// Result of feeding one frame to an [`IsoTpReassembler`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IsoTpProgress {
+    /// A whole message
+    Complete(Vec<u8>),
+    /// A First Frame arrived; send this Flow Control frame to the ECU
+    SendFlowControl([u8; 8]),
+    /// Waiting for more Consecutive Frames
+    Pending,
+}
+
+#[derive(Debug)]
+struct PartialMessage {
+    len: usize,
+    data: Vec<u8>,
+    next_sequence: u8,
+    last_frame: Instant,
+}
+
+/// Reassembles ISO-TP messages from the CAN frames of one sender
+#[derive(Debug)]
+pub struct IsoTpReassembler {
+    frame_timeout: Duration,
+    partial: Option<PartialMessage>,
+}
+
+impl Default for IsoTpReassembler {
+    fn default() -> Self {
+        Self::new(ISOTP_FRAME_TIMEOUT)
+    }
+}
+
+impl IsoTpReassembler {
+    /// Reassembler giving up on a message after `frame_timeout` between
+    /// frames
+    pub fn new(frame_timeout: Duration) -> Self {
+        Self {
+            frame_timeout,
+            partial: None,
+        }
+    }
+
+    /// Whether a multi-frame message is part way through
+    pub fn in_progress(&self) -> bool {
+        self.partial.is_some()
+    }
+
+    /// Feed the data bytes of a received frame
+    ///
+    /// A Single or First Frame replaces any message in progress. Errors
+    /// discard the message in progress.
+    pub fn push(&mut self, frame: &[u8], now: Instant) -> Result<IsoTpProgress, ObdError> {
+        let result = self.accept(frame, now);
+        if result.is_err() {
+            self.partial = None;
+        }
+        result
+    }
+
+    fn accept(&mut self, frame: &[u8], now: Instant) -> Result<IsoTpProgress, ObdError> {
+        let protocol = |reason: String| Err(ObdError::Protocol(reason));
+        let Some(&pci) = frame.first() else {
+            return protocol("empty frame".to_string());
+        };
+
+        match pci >> 4 {
+            // Single Frame: length in the low nibble
+            0x0 => {
+                let len = (pci & 0x0F) as usize;
+                if len == 0 || frame.len() < 1 + len {
+                    return protocol(format!("single frame of {} bytes declares {len}", frame.len() - 1));
+                }
+                self.partial = None;
+                Ok(IsoTpProgress::Complete(frame[1..1 + len].to_vec()))
+            }
+            // First Frame: 12-bit length, then the first six bytes
+            0x1 => {
+                let Some(&low) = frame.get(1) else {
+                    return protocol("truncated first frame".to_string());
+                };
+                let len = ((pci as usize & 0x0F) << 8) | low as usize;
+                if len <= 7 {
+                    return protocol(format!("first frame declares only {len} bytes"));
+                }
+                let data = frame[2..].iter().copied().take(len).collect();
+                self.partial = Some(PartialMessage {
+                    len,
+                    data,
+                    next_sequence: 1,
+                    last_frame: now,
+                });
+                Ok(IsoTpProgress::SendFlowControl(FLOW_CONTROL_CTS))
+            }
+            // Consecutive Frame: 4-bit sequence number
+            0x2 => {
+                let Some(partial) = self.partial.as_mut() else {
+                    return protocol("consecutive frame without a first frame".to_string());
+                };
+                let waited = now.saturating_duration_since(partial.last_frame);
+                if waited > self.frame_timeout {
+                    return protocol(format!("consecutive frame after {} ms", waited.as_millis()));
+                }
+                let sequence = pci & 0x0F;
+                if sequence != partial.next_sequence {
+                    return protocol(format!(
+                        "expected consecutive frame {}, got {sequence}",
+                        partial.next_sequence
+                    ));
+                }
+
+                let remaining = partial.len - partial.data.len();
+                partial.data.extend(frame[1..].iter().take(remaining.min(7)));
+                partial.next_sequence = (sequence + 1) & 0x0F;
+                partial.last_frame = now;
+                if partial.data.len() < partial.len {
+                    return Ok(IsoTpProgress::Pending);
+                }
+                let message = self.partial.take().map(|p| p.data).unwrap_or_default();
+                Ok(IsoTpProgress::Complete(message))
+            }
+            _ => protocol(format!("unexpected frame type {pci:02X}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_protocol_number() {
+        assert_eq!(ObdProtocol::from_protocol_number("A6"), Some(ObdProtocol::Iso15765_4Can11bit500));
+        assert_eq!(ObdProtocol::from_protocol_number("3\r"), Some(ObdProtocol::Iso9141_2));
+        assert_eq!(ObdProtocol::from_protocol_number("A0"), None);
+        assert_eq!(ObdProtocol::from_protocol_number("AB"), None);
+        for protocol in [ObdProtocol::J1850Vpw, ObdProtocol::Iso15765_4Can29bit250] {
+            let number = &protocol.to_elm_command()[4..];
+            assert_eq!(ObdProtocol::from_protocol_number(number), Some(protocol));
+        }
+    }
+
+    #[test]
+    fn test_isotp_reassembles_vin() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut isotp = IsoTpReassembler::default();
+
+        assert_eq!(
+            isotp.push(&[0x03, 0x41, 0x0D, 0x55, 0x00, 0x00, 0x00, 0x00], at(0)).unwrap(),
+            IsoTpProgress::Complete(vec![0x41, 0x0D, 0x55])
+        );
+
+        let first = [0x10, 0x14, 0x49, 0x02, 0x01, 0x31, 0x48, 0x47];
+        assert_eq!(isotp.push(&first, at(0)).unwrap(), IsoTpProgress::SendFlowControl(FLOW_CONTROL_CTS));
+        let second = [0x21, 0x43, 0x4D, 0x38, 0x32, 0x36, 0x33, 0x33];
+        assert_eq!(isotp.push(&second, at(10)).unwrap(), IsoTpProgress::Pending);
+        let third = [0x22, 0x41, 0x30, 0x30, 0x34, 0x33, 0x35, 0x32];
+        let IsoTpProgress::Complete(message) = isotp.push(&third, at(20)).unwrap() else {
+            panic!("message should be complete");
+        };
+        assert_eq!(message.len(), 0x14);
+        assert_eq!(&message[3..], b"1HGCM82633A004352");
+        assert!(!isotp.in_progress());
+
+        // Out of sequence
+        isotp.push(&first, at(100)).unwrap();
+        assert!(matches!(isotp.push(&third, at(110)), Err(ObdError::Protocol(_))));
+        assert!(!isotp.in_progress());
+        assert!(matches!(isotp.push(&second, at(120)), Err(ObdError::Protocol(_))));
+
+        // Too slow
+        isotp.push(&first, at(200)).unwrap();
+        assert!(matches!(isotp.push(&second, at(1_300)), Err(ObdError::Protocol(_))));
+    }
+}
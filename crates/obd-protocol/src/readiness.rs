@@ -0,0 +1,203 @@
+//! Readiness Monitor Status (Mode 01 PID 01)
+//!
+//! The ECU runs self-tests ("monitors") on emission-related systems.
+//! Clearing DTCs resets them to incomplete, and inspection stations refuse
+//! a vehicle whose supported monitors haven't completed since. The 4-byte
+//! reply is laid out as (SAE J1979):
+//!
+//! ```text
+//! A: bit 7 MIL on, bits 0-6 stored DTC count
+//! B: bits 0-2 misfire/fuel/component supported, bit 3 compression ignition,
+//!    bits 4-6 misfire/fuel/component incomplete
+//! C: engine-specific monitors supported
+//! D: engine-specific monitors incomplete
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ObdError;
+
+/// An emissions self-test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Monitor {
+    // Common to all engines
+    Misfire,
+    FuelSystem,
+    Components,
+    // Spark ignition
+    Catalyst,
+    HeatedCatalyst,
+    EvaporativeSystem,
+    SecondaryAir,
+    AcRefrigerant,
+    OxygenSensor,
+    OxygenSensorHeater,
+    EgrSystem,
+    // Compression ignition
+    NmhcCatalyst,
+    NoxScr,
+    BoostPressure,
+    ExhaustGasSensor,
+    PmFilter,
+    EgrVvt,
+}
+
+/// Byte C/D bit order for spark-ignition engines; `None` marks reserved bits
+const SPARK_MONITORS: [Option<Monitor>; 8] = [
+    Some(Monitor::Catalyst),
+    Some(Monitor::HeatedCatalyst),
+    Some(Monitor::EvaporativeSystem),
+    Some(Monitor::SecondaryAir),
+    Some(Monitor::AcRefrigerant),
+    Some(Monitor::OxygenSensor),
+    Some(Monitor::OxygenSensorHeater),
+    Some(Monitor::EgrSystem),
+];
+
+/// Byte C/D bit order for compression-ignition engines
+const COMPRESSION_MONITORS: [Option<Monitor>; 8] = [
+    Some(Monitor::NmhcCatalyst),
+    Some(Monitor::NoxScr),
+    None,
+    Some(Monitor::BoostPressure),
+    None,
+    Some(Monitor::ExhaustGasSensor),
+    Some(Monitor::PmFilter),
+    Some(Monitor::EgrVvt),
+];
+
+/// State of one monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitorState {
+    pub monitor: Monitor,
+    /// The vehicle implements this monitor
+    pub supported: bool,
+    /// The test has run to completion since codes were last cleared
+    pub complete: bool,
+}
+
+/// Decoded readiness status
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadinessStatus {
+    /// Malfunction indicator lamp lit
+    pub mil_on: bool,
+    /// Number of stored emission-related DTCs
+    pub dtc_count: u8,
+    /// Diesel engine (selects the meaning of the engine-specific monitors)
+    pub compression_ignition: bool,
+    /// All monitors the layout defines, supported or not
+    pub monitors: Vec<MonitorState>,
+}
+
+impl ReadinessStatus {
+    /// Decode the data bytes of a PID 01 reply
+    pub fn decode(bytes: &[u8]) -> Result<Self, ObdError> {
+        let [a, b, c, d] = match bytes {
+            [a, b, c, d, ..] => [*a, *b, *c, *d],
+            _ => {
+                return Err(ObdError::InvalidResponse(format!(
+                    "monitor status needs 4 bytes, got {}",
+                    bytes.len()
+                )))
+            }
+        };
+
+        let compression_ignition = b & 0x08 != 0;
+        let common = [Monitor::Misfire, Monitor::FuelSystem, Monitor::Components];
+        let mut monitors: Vec<MonitorState> = common
+            .into_iter()
+            .enumerate()
+            .map(|(bit, monitor)| MonitorState {
+                monitor,
+                supported: b & (1 << bit) != 0,
+                complete: b & (1 << (bit + 4)) == 0,
+            })
+            .collect();
+
+        let layout = if compression_ignition {
+            COMPRESSION_MONITORS
+        } else {
+            SPARK_MONITORS
+        };
+        monitors.extend(layout.into_iter().enumerate().filter_map(|(bit, monitor)| {
+            Some(MonitorState {
+                monitor: monitor?,
+                supported: c & (1 << bit) != 0,
+                complete: d & (1 << bit) == 0,
+            })
+        }));
+
+        Ok(Self {
+            mil_on: a & 0x80 != 0,
+            dtc_count: a & 0x7F,
+            compression_ignition,
+            monitors,
+        })
+    }
+
+    /// State of a specific monitor, if the engine type defines it
+    pub fn monitor(&self, monitor: Monitor) -> Option<&MonitorState> {
+        self.monitors.iter().find(|m| m.monitor == monitor)
+    }
+
+    /// Supported monitors that have not completed
+    pub fn incomplete(&self) -> impl Iterator<Item = Monitor> + '_ {
+        self.monitors
+            .iter()
+            .filter(|m| m.supported && !m.complete)
+            .map(|m| m.monitor)
+    }
+
+    /// Whether every supported monitor has completed
+    pub fn is_ready(&self) -> bool {
+        self.incomplete().next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_spark_ignition_status() {
+        // MIL on with 3 codes; misfire/fuel/components supported, fuel
+        // incomplete; catalyst, EVAP, O2, O2 heater and EGR supported,
+        // EVAP and EGR incomplete
+        let status = ReadinessStatus::decode(&[0x83, 0x27, 0xE5, 0x84]).unwrap();
+
+        assert!(status.mil_on);
+        assert_eq!(status.dtc_count, 3);
+        assert!(!status.compression_ignition);
+
+        let fuel = status.monitor(Monitor::FuelSystem).unwrap();
+        assert!(fuel.supported && !fuel.complete);
+        let misfire = status.monitor(Monitor::Misfire).unwrap();
+        assert!(misfire.supported && misfire.complete);
+        let catalyst = status.monitor(Monitor::Catalyst).unwrap();
+        assert!(catalyst.supported && catalyst.complete);
+        assert!(!status.monitor(Monitor::SecondaryAir).unwrap().supported);
+        assert!(status.monitor(Monitor::PmFilter).is_none());
+
+        let incomplete: Vec<_> = status.incomplete().collect();
+        assert_eq!(
+            incomplete,
+            vec![Monitor::FuelSystem, Monitor::EvaporativeSystem, Monitor::EgrSystem]
+        );
+        assert!(!status.is_ready());
+    }
+
+    #[test]
+    fn test_decode_diesel_and_short_reply() {
+        let status = ReadinessStatus::decode(&[0x00, 0x0F, 0x43, 0x00]).unwrap();
+        assert!(status.compression_ignition);
+        assert!(!status.mil_on);
+        assert!(status.monitor(Monitor::PmFilter).unwrap().supported);
+        assert!(status.monitor(Monitor::Catalyst).is_none());
+        assert!(status.is_ready());
+
+        assert!(matches!(
+            ReadinessStatus::decode(&[0x00, 0x07]),
+            Err(ObdError::InvalidResponse(_))
+        ));
+    }
+}
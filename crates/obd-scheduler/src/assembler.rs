@@ -0,0 +1,189 @@
+//! Sensor Frame Assembly
+//!
+//! The scheduler reads one PID per query and re-emits the whole frame after
+//! each, so a frame mixes readings taken at different moments. The
+//! assembler remembers when each field was captured, stamps every frame with
+//! the window its fields span, and keeps the emitted stream in timestamp
+//! order for windowed features and replay.
+//!
+//! A field whose PID stops answering keeps its last value in the frame.
+//! Once it has gone several expected intervals without an update, its bit
+//! is cleared from the emitted `valid_mask` so consumers treat it as
+//! missing rather than fresh.
+
+use obd_protocol::{PidResponse, SensorFrame};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Default number of missed intervals before a field is stale
+pub const DEFAULT_STALE_AFTER_INTERVALS: f64 = 3.0;
+
+/// Builds frames from individual PID responses
+#[derive(Debug, Clone)]
+pub struct FrameAssembler {
+    frame: SensorFrame,
+    /// Capture time per `valid` bit position
+    field_times: [u64; 16],
+    /// PID that last wrote each `valid` bit position
+    field_pids: [u8; 16],
+    /// Expected polling interval per PID (ms)
+    expected_ms: HashMap<u8, u64>,
+    stale_after_intervals: f64,
+    last_emitted_ms: Option<u64>,
+    out_of_order: u64,
+}
+
+impl FrameAssembler {
+    /// Create an assembler with an empty frame
+    pub fn new() -> Self {
+        Self {
+            frame: SensorFrame::default(),
+            field_times: [0; 16],
+            field_pids: [0; 16],
+            expected_ms: HashMap::new(),
+            stale_after_intervals: DEFAULT_STALE_AFTER_INTERVALS,
+            last_emitted_ms: None,
+            out_of_order: 0,
+        }
+    }
+
+    /// Mark fields stale after `intervals` expected intervals without an update
+    pub fn with_stale_after(mut self, intervals: f64) -> Self {
+        self.stale_after_intervals = intervals;
+        self
+    }
+
+    /// Set how often `pid` is expected to update; PIDs without an interval
+    /// never go stale
+    pub fn set_expected_interval(&mut self, pid: u8, interval: Duration) {
+        self.expected_ms.insert(pid, interval.as_millis() as u64);
+    }
+
+    /// Fold in a response and return the frame to emit
+    ///
+    /// A response older than the last emitted frame would move time
+    /// backwards; it is dropped and counted instead.
+    pub fn ingest(&mut self, response: &PidResponse) -> Option<SensorFrame> {
+        if let Some(last) = self.last_emitted_ms {
+            if response.timestamp_ms < last {
+                self.out_of_order += 1;
+                warn!(
+                    "Dropping out-of-order PID {:02X} response ({} ms < {} ms)",
+                    response.pid, response.timestamp_ms, last
+                );
+                return None;
+            }
+        }
+
+        let now = response.timestamp_ms;
+        let bit = self.frame.update_from_response(response);
+        if bit != 0 {
+            let index = bit.trailing_zeros() as usize;
+            self.field_times[index] = now;
+            self.field_pids[index] = response.pid;
+        }
+
+        let stale = self.stale_mask(now);
+        let mut frame = self.frame.clone();
+        frame.valid_mask &= !stale;
+        frame.timestamp_ms = now;
+        frame.capture_start_ms = (0..16)
+            .filter(|i| frame.valid_mask & (1 << i) != 0)
+            .map(|i| self.field_times[i])
+            .min()
+            .unwrap_or(now);
+        self.last_emitted_ms = Some(now);
+
+        Some(frame)
+    }
+
+    /// Fields whose PID has missed too many expected updates by `now_ms`
+    fn stale_mask(&self, now_ms: u64) -> u16 {
+        let mut stale = 0;
+        for index in 0..16 {
+            let bit = 1u16 << index;
+            if self.frame.valid_mask & bit == 0 {
+                continue;
+            }
+            let Some(&interval) = self.expected_ms.get(&self.field_pids[index]) else {
+                continue;
+            };
+            let age = now_ms.saturating_sub(self.field_times[index]);
+            if age as f64 > interval as f64 * self.stale_after_intervals {
+                debug!(
+                    "PID {:02X} stale: no update for {} ms",
+                    self.field_pids[index], age
+                );
+                stale |= bit;
+            }
+        }
+        stale
+    }
+
+    /// Responses dropped for arriving out of order
+    pub fn out_of_order(&self) -> u64 {
+        self.out_of_order
+    }
+}
+
+impl Default for FrameAssembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use obd_protocol::valid;
+
+    #[test]
+    fn test_out_of_order_dropped_and_stream_monotonic() {
+        let mut assembler = FrameAssembler::new();
+        let responses = [
+            PidResponse::decode(0x0C, vec![0x1A, 0x2B], 100),
+            PidResponse::decode(0x0D, vec![0x55], 150),
+            // Delayed reply carrying an older capture time
+            PidResponse::decode(0x05, vec![0x73], 120),
+            PidResponse::decode(0x0C, vec![0x20, 0x00], 200),
+        ];
+
+        let emitted: Vec<SensorFrame> = responses.iter().filter_map(|r| assembler.ingest(r)).collect();
+        let timestamps: Vec<u64> = emitted.iter().map(|f| f.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![100, 150, 200]);
+        assert!(timestamps.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(assembler.out_of_order(), 1);
+
+        // Speed is now the oldest field; rpm was refreshed at 200
+        let last = emitted.last().unwrap();
+        assert_eq!(last.capture_start_ms, 150);
+        assert_eq!(last.capture_span_ms(), 50);
+        assert!(!last.is_valid(valid::COOLANT_TEMP));
+    }
+
+    #[test]
+    fn test_silent_pid_marked_stale() {
+        let mut assembler = FrameAssembler::new();
+        assembler.set_expected_interval(0x0C, Duration::from_millis(200));
+        assembler.set_expected_interval(0x0D, Duration::from_millis(200));
+
+        // Speed answers until 200 ms, then stops; rpm keeps coming
+        assembler.ingest(&PidResponse::decode(0x0D, vec![0x55], 200));
+        let mut frame = SensorFrame::default();
+        for t in (200..=1000).step_by(200) {
+            frame = assembler.ingest(&PidResponse::decode(0x0C, vec![0x1A, 0x2B], t)).unwrap();
+            assert!(frame.is_valid(valid::RPM));
+            // 3 x 200 ms allowed: fresh through 800 ms, stale after
+            assert_eq!(frame.is_valid(valid::SPEED), t <= 800, "at {t} ms");
+        }
+
+        // Stale fields drop out of the capture window; the value is kept
+        assert_eq!(frame.capture_start_ms, 1000);
+        assert_eq!(frame.speed, 85);
+
+        // An update makes it valid again
+        let frame = assembler.ingest(&PidResponse::decode(0x0D, vec![0x56], 1100)).unwrap();
+        assert!(frame.is_valid(valid::SPEED | valid::RPM));
+    }
+}
@@ -0,0 +1,215 @@
+//! Bounded Frame Channel
+//!
+//! `tokio::sync::mpsc` can only refuse the value being sent when it is
+//! full, which throws away the newest telemetry. This channel lets the
+//! producer choose which end to drop and counts what was lost.
+
+use obd_protocol::SensorFrame;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::sync::Notify;
+
+/// What to discard when the channel is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Evict the oldest queued frame to make room
+    #[default]
+    DropOldest,
+    /// Discard the frame being sent
+    DropNewest,
+}
+
+/// The receiving half has been dropped
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("frame receiver dropped")]
+pub struct ChannelClosed;
+
+struct Shared {
+    queue: Mutex<VecDeque<SensorFrame>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+    senders: AtomicUsize,
+    receiver_alive: AtomicBool,
+    notify: Notify,
+}
+
+impl Shared {
+    fn queue(&self) -> std::sync::MutexGuard<'_, VecDeque<SensorFrame>> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Create a bounded frame channel
+pub fn frame_channel(capacity: usize, policy: OverflowPolicy) -> (FrameSender, FrameReceiver) {
+    let capacity = capacity.max(1);
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        capacity,
+        policy,
+        dropped: AtomicU64::new(0),
+        senders: AtomicUsize::new(1),
+        receiver_alive: AtomicBool::new(true),
+        notify: Notify::new(),
+    });
+    (
+        FrameSender {
+            shared: Arc::clone(&shared),
+        },
+        FrameReceiver { shared },
+    )
+}
+
+/// Sending half of a [`frame_channel`]
+pub struct FrameSender {
+    shared: Arc<Shared>,
+}
+
+impl FrameSender {
+    /// Queue a frame without waiting, applying the overflow policy when full
+    pub fn send(&self, frame: SensorFrame) -> Result<(), ChannelClosed> {
+        if !self.shared.receiver_alive.load(Ordering::SeqCst) {
+            return Err(ChannelClosed);
+        }
+
+        {
+            let mut queue = self.shared.queue();
+            if queue.len() >= self.shared.capacity {
+                self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                match self.shared.policy {
+                    OverflowPolicy::DropOldest => {
+                        queue.pop_front();
+                    }
+                    OverflowPolicy::DropNewest => return Ok(()),
+                }
+            }
+            queue.push_back(frame);
+        }
+
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+
+    /// Frames discarded because the channel was full
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Clone for FrameSender {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::SeqCst);
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl Drop for FrameSender {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Wake the receiver so it can observe the close
+            self.shared.notify.notify_one();
+        }
+    }
+}
+
+/// Receiving half of a [`frame_channel`]
+pub struct FrameReceiver {
+    shared: Arc<Shared>,
+}
+
+impl FrameReceiver {
+    /// Wait for the next frame; `None` once all senders are gone and the queue is drained
+    pub async fn recv(&mut self) -> Option<SensorFrame> {
+        loop {
+            if let Some(frame) = self.try_recv() {
+                return Some(frame);
+            }
+            if self.shared.senders.load(Ordering::SeqCst) == 0 {
+                // A last sender may have queued a frame between the pop
+                // above and dropping; drain it before reporting closed
+                return self.try_recv();
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Take the next frame if one is queued
+    pub fn try_recv(&mut self) -> Option<SensorFrame> {
+        self.shared.queue().pop_front()
+    }
+
+    /// Number of queued frames
+    pub fn len(&self) -> usize {
+        self.shared.queue().len()
+    }
+
+    /// Check whether the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Frames discarded because the channel was full
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        self.shared.receiver_alive.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(tx: &FrameSender, count: u64) {
+        for t in 0..count {
+            tx.send(SensorFrame::new(t)).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_keeps_latest_frame() {
+        let (tx, mut rx) = frame_channel(3, OverflowPolicy::DropOldest);
+        fill(&tx, 5);
+
+        assert_eq!(rx.len(), 3);
+        assert_eq!(tx.dropped(), 2);
+        let timestamps: Vec<u64> = std::iter::from_fn(|| rx.try_recv()).map(|f| f.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![2, 3, 4]);
+
+        drop(tx);
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest_keeps_earliest_frames() {
+        let (tx, mut rx) = frame_channel(3, OverflowPolicy::DropNewest);
+        fill(&tx, 5);
+
+        assert_eq!(rx.dropped(), 2);
+        assert_eq!(rx.recv().await.unwrap().timestamp_ms, 0);
+
+        drop(rx);
+        assert_eq!(tx.send(SensorFrame::new(9)), Err(ChannelClosed));
+    }
+
+    #[tokio::test]
+    async fn test_frames_sent_before_close_are_received() {
+        let (tx, mut rx) = frame_channel(3, OverflowPolicy::DropOldest);
+        let producer = tokio::spawn(async move {
+            fill(&tx, 2);
+        });
+        producer.await.unwrap();
+
+        assert_eq!(rx.recv().await.unwrap().timestamp_ms, 0);
+        assert_eq!(rx.recv().await.unwrap().timestamp_ms, 1);
+        assert!(rx.recv().await.is_none());
+    }
+}
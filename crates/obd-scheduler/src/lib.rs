@@ -3,6 +3,13 @@
 //! Provides priority-based scheduling for OBD-II PID queries with
 //! adaptive rate boosting based on sensor thresholds.
 
+mod assembler;
+mod channel;
 mod scheduler;
 
-pub use scheduler::{PidScheduler, SchedulerConfig, ScheduledPid};
+pub use assembler::FrameAssembler;
+pub use channel::{frame_channel, ChannelClosed, FrameReceiver, FrameSender, OverflowPolicy};
+pub use scheduler::{
+    AdapterStatus, ConfigError, HealthSnapshot, PidScheduler, SchedulerConfig, SchedulerHealth,
+    ScheduledPid,
+};
@@ -1,25 +1,45 @@
 //! PID Scheduler Implementation
 
-use obd_protocol::{ObdClient, ObdError, Pid, SensorFrame};
+use crate::assembler::{FrameAssembler, DEFAULT_STALE_AFTER_INTERVALS};
+use crate::channel::{frame_channel, FrameReceiver, FrameSender, OverflowPolicy};
+use obd_protocol::{LinkState, ObdClient, ObdError, Pid, IGNITION_PROBE_PID};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
-use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
-use tracing::{debug, info, warn};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
 
 /// Configuration for the PID scheduler
 #[derive(Debug, Clone)]
 pub struct SchedulerConfig {
     /// Base sampling rate in Hz (default: 5.0)
     pub base_rate_hz: f64,
-    /// Maximum retry attempts before marking adapter unhealthy
+    /// Consecutive failed queries, across all PIDs, before the adapter is
+    /// marked unhealthy
     pub max_retries: u8,
-    /// Retry backoff base in milliseconds
+    /// Retry backoff base in milliseconds; doubles with each consecutive
+    /// failure of the same PID
     pub retry_backoff_ms: u64,
+    /// Upper bound on the retry backoff in milliseconds
+    pub max_backoff_ms: u64,
+    /// Random spread applied to each backoff, as a fraction in `0.0..1.0`,
+    /// so PIDs that failed together don't retry in lockstep
+    pub backoff_jitter: f64,
     /// Coolant temperature threshold for rate boost (°C)
     pub coolant_boost_threshold: f64,
     /// Boosted rate multiplier
     pub boost_multiplier: f64,
+    /// Frames buffered between the scheduler and its consumer
+    pub channel_capacity: usize,
+    /// Which frame to discard when the consumer falls behind
+    pub overflow_policy: OverflowPolicy,
+    /// Expected intervals a PID may go without an update before its field
+    /// is dropped from the frame's `valid_mask`
+    pub stale_after_intervals: f64,
 }
 
 impl Default for SchedulerConfig {
@@ -28,12 +48,92 @@ impl Default for SchedulerConfig {
             base_rate_hz: 5.0,
             max_retries: 3,
             retry_backoff_ms: 100,
+            max_backoff_ms: 5_000,
+            backoff_jitter: 0.2,
             coolant_boost_threshold: 95.0,
             boost_multiplier: 2.0,
+            channel_capacity: 64,
+            overflow_policy: OverflowPolicy::DropOldest,
+            stale_after_intervals: DEFAULT_STALE_AFTER_INTERVALS,
         }
     }
 }
 
+/// Invalid scheduler configuration
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("{field} must be {expected}, got {value}")]
+    OutOfRange {
+        field: &'static str,
+        value: f64,
+        expected: &'static str,
+    },
+}
+
+impl SchedulerConfig {
+    /// Check ranges and invariants
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        // A zero rate would make ScheduledPid::interval infinite
+        if !(self.base_rate_hz.is_finite() && self.base_rate_hz > 0.0) {
+            return Err(ConfigError::OutOfRange {
+                field: "base_rate_hz",
+                value: self.base_rate_hz,
+                expected: "a finite rate above 0 Hz",
+            });
+        }
+        if !(self.boost_multiplier.is_finite() && self.boost_multiplier >= 1.0) {
+            return Err(ConfigError::OutOfRange {
+                field: "boost_multiplier",
+                value: self.boost_multiplier,
+                expected: "a finite multiplier of at least 1.0",
+            });
+        }
+        if !self.coolant_boost_threshold.is_finite() {
+            return Err(ConfigError::OutOfRange {
+                field: "coolant_boost_threshold",
+                value: self.coolant_boost_threshold,
+                expected: "a finite temperature",
+            });
+        }
+        if self.max_retries == 0 {
+            return Err(ConfigError::OutOfRange {
+                field: "max_retries",
+                value: 0.0,
+                expected: "at least 1",
+            });
+        }
+        if self.max_backoff_ms < self.retry_backoff_ms {
+            return Err(ConfigError::OutOfRange {
+                field: "max_backoff_ms",
+                value: self.max_backoff_ms as f64,
+                expected: "at least retry_backoff_ms",
+            });
+        }
+        if !(0.0..1.0).contains(&self.backoff_jitter) {
+            return Err(ConfigError::OutOfRange {
+                field: "backoff_jitter",
+                value: self.backoff_jitter,
+                expected: "within 0.0..1.0",
+            });
+        }
+        if !(self.stale_after_intervals.is_finite() && self.stale_after_intervals >= 1.0) {
+            return Err(ConfigError::OutOfRange {
+                field: "stale_after_intervals",
+                value: self.stale_after_intervals,
+                expected: "a finite count of at least 1.0",
+            });
+        }
+        if self.channel_capacity == 0 {
+            return Err(ConfigError::OutOfRange {
+                field: "channel_capacity",
+                value: 0.0,
+                expected: "at least 1",
+            });
+        }
+        Ok(())
+    }
+}
+
 /// A scheduled PID with priority and timing info
 #[derive(Debug, Clone)]
 pub struct ScheduledPid {
@@ -70,6 +170,13 @@ impl ScheduledPid {
     pub fn schedule_next(&mut self) {
         self.next_query = Instant::now() + self.interval();
     }
+
+    /// Backoff before retrying after the current run of failures:
+    /// `base`, `2 * base`, `4 * base`, ... up to `max`
+    pub fn retry_delay(&self, base: Duration, max: Duration) -> Duration {
+        let doublings = u32::from(self.failures.saturating_sub(1)).min(31);
+        base.saturating_mul(1 << doublings).min(max)
+    }
 }
 
 impl Eq for ScheduledPid {}
@@ -95,6 +202,84 @@ impl PartialOrd for ScheduledPid {
     }
 }
 
+/// OBD adapter state as seen by the scheduler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdapterStatus {
+    /// Queries are being answered
+    Healthy,
+    /// `max_retries` consecutive queries failed
+    Unhealthy,
+}
+
+/// Adapter health shared between the scheduler and its observers
+///
+/// Updated by [`PidScheduler::run`] after every query; readers such as the
+/// API health endpoint take a [`snapshot`](Self::snapshot) without locking.
+#[derive(Debug)]
+pub struct SchedulerHealth {
+    healthy: AtomicBool,
+    consecutive_failures: AtomicU32,
+    /// Unix ms of the last answered query, 0 if none yet
+    last_success_ms: AtomicU64,
+}
+
+/// Point-in-time copy of [`SchedulerHealth`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthSnapshot {
+    /// Adapter status
+    pub status: AdapterStatus,
+    /// Failed queries since the last success of any PID
+    pub consecutive_failures: u32,
+    /// Unix ms of the last answered query
+    pub last_success_ms: Option<u64>,
+}
+
+impl SchedulerHealth {
+    fn new() -> Self {
+        Self {
+            healthy: AtomicBool::new(true),
+            consecutive_failures: AtomicU32::new(0),
+            last_success_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Current adapter status
+    pub fn status(&self) -> AdapterStatus {
+        if self.healthy.load(AtomicOrdering::Relaxed) {
+            AdapterStatus::Healthy
+        } else {
+            AdapterStatus::Unhealthy
+        }
+    }
+
+    /// Read all fields
+    pub fn snapshot(&self) -> HealthSnapshot {
+        let last = self.last_success_ms.load(AtomicOrdering::Relaxed);
+        HealthSnapshot {
+            status: self.status(),
+            consecutive_failures: self.consecutive_failures.load(AtomicOrdering::Relaxed),
+            last_success_ms: (last != 0).then_some(last),
+        }
+    }
+
+    /// Record an answered query; returns true if the adapter was unhealthy
+    fn record_success(&self, now_ms: u64) -> bool {
+        self.consecutive_failures.store(0, AtomicOrdering::Relaxed);
+        self.last_success_ms.store(now_ms, AtomicOrdering::Relaxed);
+        !self.healthy.swap(true, AtomicOrdering::Relaxed)
+    }
+
+    /// Record a failed query; returns true if this failure crossed
+    /// `threshold` and made the adapter unhealthy
+    fn record_failure(&self, threshold: u32) -> bool {
+        let failures = self
+            .consecutive_failures
+            .fetch_add(1, AtomicOrdering::Relaxed)
+            .saturating_add(1);
+        failures >= threshold && self.healthy.swap(false, AtomicOrdering::Relaxed)
+    }
+}
+
 /// PID Scheduler for managing OBD-II queries
 pub struct PidScheduler {
     /// Scheduled PIDs in priority queue
@@ -105,11 +290,18 @@ pub struct PidScheduler {
     running: bool,
     /// Last known coolant temperature
     last_coolant_temp: f64,
+    /// Adapter health, shared with observers
+    health: Arc<SchedulerHealth>,
+    /// Source of backoff jitter
+    rng: StdRng,
+    /// Frame built from the responses so far
+    assembler: FrameAssembler,
 }
 
 impl PidScheduler {
     /// Create a new PID scheduler with default PIDs
-    pub fn new(config: SchedulerConfig) -> Self {
+    pub fn new(config: SchedulerConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
         let mut queue = BinaryHeap::new();
         
         // Add critical PIDs at high rate (5Hz)
@@ -128,38 +320,74 @@ impl PidScheduler {
 
         info!("PID scheduler created with {} PIDs", queue.len());
 
-        Self {
+        let mut assembler = FrameAssembler::new().with_stale_after(config.stale_after_intervals);
+        for scheduled in &queue {
+            assembler.set_expected_interval(scheduled.pid.as_hex(), scheduled.interval());
+        }
+
+        Ok(Self {
             queue,
             config,
             running: false,
             last_coolant_temp: 0.0,
-        }
+            health: Arc::new(SchedulerHealth::new()),
+            rng: StdRng::seed_from_u64(jitter_seed()),
+            assembler,
+        })
     }
 
     /// Boost priority for a specific PID
     pub fn boost_priority(&mut self, pid: Pid, new_rate_hz: f64) {
+        if !(new_rate_hz.is_finite() && new_rate_hz > 0.0) {
+            warn!("Ignoring invalid rate {} Hz for PID {:02X}", new_rate_hz, pid.as_hex());
+            return;
+        }
         let items: Vec<_> = self.queue.drain().collect();
         for mut item in items {
             if item.pid == pid {
                 debug!("Boosting {} rate to {} Hz", pid as u8, new_rate_hz);
                 item.rate_hz = new_rate_hz;
+                self.assembler.set_expected_interval(pid.as_hex(), item.interval());
             }
             self.queue.push(item);
         }
     }
 
+    /// Drop scheduled PIDs the vehicle does not support, e.g. those missing
+    /// from [`ObdClient::supported_pids`]
+    pub fn retain_supported(&mut self, supported: &[u8]) {
+        let before = self.queue.len();
+        self.queue.retain(|item| supported.contains(&item.pid.as_hex()));
+        let dropped = before - self.queue.len();
+        if dropped > 0 {
+            info!("Dropped {} unsupported PIDs, {} left", dropped, self.queue.len());
+        }
+    }
+
+    /// Create a frame channel sized and configured for [`run`](Self::run)
+    pub fn frame_channel(&self) -> (FrameSender, FrameReceiver) {
+        frame_channel(self.config.channel_capacity, self.config.overflow_policy)
+    }
+
     /// Run the scheduler loop
     pub async fn run(
         &mut self,
         client: &mut ObdClient,
-        frame_tx: mpsc::Sender<SensorFrame>,
+        frame_tx: FrameSender,
     ) -> Result<(), ObdError> {
         info!("Starting PID scheduler");
         self.running = true;
 
-        let mut current_frame = SensorFrame::new(0);
-
         while self.running {
+            // Ignition off: probe slowly instead of failing every PID
+            if client.link_state() == LinkState::Sleeping {
+                tokio::time::sleep(client.link_config().sleep_poll_interval).await;
+                if client.query_pid(IGNITION_PROBE_PID).await.is_ok() {
+                    self.resume(Instant::now());
+                }
+                continue;
+            }
+
             // Get next PID to query
             if let Some(mut scheduled) = self.queue.pop() {
                 // Wait until it's time
@@ -171,9 +399,8 @@ impl PidScheduler {
                 // Query the PID
                 match client.query_pid(scheduled.pid.as_hex()).await {
                     Ok(response) => {
-                        scheduled.failures = 0;
-                        current_frame.update_from_response(&response);
-                        current_frame.timestamp_ms = response.timestamp_ms;
+                        self.record_success(&mut scheduled);
+                        let frame = self.assembler.ingest(&response);
 
                         // Check for adaptive rate boosting
                         if scheduled.pid == Pid::CoolantTemp {
@@ -181,26 +408,23 @@ impl PidScheduler {
                             if response.value > self.config.coolant_boost_threshold {
                                 warn!("Coolant temp {} > threshold, boosting rate", response.value);
                                 scheduled.rate_hz = self.config.base_rate_hz * self.config.boost_multiplier;
+                                self.assembler
+                                    .set_expected_interval(scheduled.pid.as_hex(), scheduled.interval());
                             }
                         }
 
-                        // Send frame (non-blocking)
-                        let _ = frame_tx.try_send(current_frame.clone());
+                        // Send frame (non-blocking, overflow per config)
+                        if let Some(frame) = frame {
+                            let _ = frame_tx.send(frame);
+                        }
                     }
                     Err(e) => {
-                        scheduled.failures += 1;
-                        warn!("PID {:02X} query failed (attempt {}): {}", 
-                            scheduled.pid.as_hex(), scheduled.failures, e);
-
-                        if scheduled.failures >= self.config.max_retries {
-                            warn!("Max retries reached for PID {:02X}", scheduled.pid.as_hex());
-                            // Still reschedule but with longer delay
-                        }
+                        let delay = self.record_failure(&mut scheduled, Instant::now());
+                        warn!("PID {:02X} query failed (attempt {}), retry in {:?}: {}",
+                            scheduled.pid.as_hex(), scheduled.failures, delay, e);
                     }
                 }
 
-                // Reschedule
-                scheduled.schedule_next();
                 self.queue.push(scheduled);
             }
         }
@@ -209,6 +433,67 @@ impl PidScheduler {
         Ok(())
     }
 
+    /// Make every PID due now with its retry backoff cleared, after the
+    /// link wakes up
+    fn resume(&mut self, now: Instant) {
+        let items: Vec<_> = self.queue.drain().collect();
+        for mut item in items {
+            item.failures = 0;
+            item.next_query = now;
+            self.queue.push(item);
+        }
+    }
+
+    /// Reset failure tracking after a successful query and schedule the
+    /// next one at the PID's normal rate
+    fn record_success(&mut self, scheduled: &mut ScheduledPid) {
+        scheduled.failures = 0;
+        if self.health.record_success(unix_ms()) {
+            info!("OBD adapter responding again");
+        }
+        scheduled.schedule_next();
+    }
+
+    /// Count a failed query and schedule its retry with backoff, returning
+    /// the delay chosen
+    fn record_failure(&mut self, scheduled: &mut ScheduledPid, now: Instant) -> Duration {
+        scheduled.failures = scheduled.failures.saturating_add(1);
+        if self.health.record_failure(u32::from(self.config.max_retries)) {
+            error!(
+                "OBD adapter unhealthy after {} consecutive failed queries",
+                self.config.max_retries
+            );
+        }
+
+        let delay = scheduled.retry_delay(
+            Duration::from_millis(self.config.retry_backoff_ms),
+            Duration::from_millis(self.config.max_backoff_ms),
+        );
+        let jitter = self.config.backoff_jitter;
+        let delay = if jitter > 0.0 {
+            delay.mul_f64(1.0 + self.rng.gen_range(-jitter..jitter))
+        } else {
+            delay
+        };
+        scheduled.next_query = now + delay;
+        delay
+    }
+
+    /// Responses dropped because their timestamp preceded an emitted frame
+    pub fn out_of_order_responses(&self) -> u64 {
+        self.assembler.out_of_order()
+    }
+
+    /// Current adapter health
+    pub fn adapter_status(&self) -> AdapterStatus {
+        self.health.status()
+    }
+
+    /// Shared health state, for reporting outside the scheduler task
+    pub fn health(&self) -> Arc<SchedulerHealth> {
+        Arc::clone(&self.health)
+    }
+
     /// Stop the scheduler
     pub fn stop(&mut self) {
         info!("Stopping PID scheduler");
@@ -226,16 +511,143 @@ impl PidScheduler {
     }
 }
 
+/// Seed for backoff jitter; only needs to differ between devices
+fn jitter_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_scheduler_creation() {
-        let scheduler = PidScheduler::new(SchedulerConfig::default());
+        let scheduler = PidScheduler::new(SchedulerConfig::default()).unwrap();
         assert_eq!(scheduler.pid_count(), 8);
     }
 
+    #[test]
+    fn test_retain_supported_prunes_queue() {
+        let mut scheduler = PidScheduler::new(SchedulerConfig::default()).unwrap();
+        // No MAF or bank 1 O2 sensor
+        scheduler.retain_supported(&[0x04, 0x05, 0x06, 0x07, 0x0C, 0x0D, 0x2F]);
+        assert_eq!(scheduler.pid_count(), 6);
+        assert!(scheduler.queue.iter().all(|item| !matches!(item.pid, Pid::Maf | Pid::O2Voltage)));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(SchedulerConfig::default().validate().is_ok());
+
+        let err = PidScheduler::new(SchedulerConfig {
+            base_rate_hz: 0.0,
+            ..Default::default()
+        })
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "base_rate_hz must be a finite rate above 0 Hz, got 0"
+        );
+
+        let err = SchedulerConfig {
+            boost_multiplier: 0.5,
+            ..Default::default()
+        }
+        .validate()
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::OutOfRange { field: "boost_multiplier", .. }));
+
+        assert!(SchedulerConfig {
+            max_retries: 0,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_retry_backoff_grows_exponentially() {
+        let mut scheduler = PidScheduler::new(SchedulerConfig {
+            retry_backoff_ms: 100,
+            max_backoff_ms: 1_000,
+            backoff_jitter: 0.0,
+            max_retries: 3,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut scheduled = ScheduledPid::new(Pid::Rpm, 5.0);
+        let now = Instant::now();
+
+        let delays: Vec<u128> = (0..6)
+            .map(|_| {
+                scheduler.record_failure(&mut scheduled, now);
+                (scheduled.next_query - now).as_millis()
+            })
+            .collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1_000, 1_000]);
+        assert_eq!(scheduler.adapter_status(), AdapterStatus::Unhealthy);
+
+        scheduler.record_success(&mut scheduled);
+        assert_eq!(scheduled.failures, 0);
+        assert_eq!(scheduler.adapter_status(), AdapterStatus::Healthy);
+    }
+
+    #[test]
+    fn test_health_flips_after_consecutive_failures() {
+        let mut scheduler = PidScheduler::new(SchedulerConfig {
+            max_retries: 3,
+            ..Default::default()
+        })
+        .unwrap();
+        let health = scheduler.health();
+        assert_eq!(health.snapshot().last_success_ms, None);
+
+        // Failures spread over several PIDs still count together
+        let now = Instant::now();
+        let mut pids: Vec<_> = [Pid::Rpm, Pid::Speed, Pid::Maf, Pid::CoolantTemp]
+            .into_iter()
+            .map(|pid| ScheduledPid::new(pid, 5.0))
+            .collect();
+        for scheduled in &mut pids[..2] {
+            scheduler.record_failure(scheduled, now);
+        }
+        assert_eq!(health.status(), AdapterStatus::Healthy);
+        scheduler.record_failure(&mut pids[2], now);
+        assert_eq!(health.status(), AdapterStatus::Unhealthy);
+        assert_eq!(health.snapshot().consecutive_failures, 3);
+
+        scheduler.record_success(&mut pids[3]);
+        let snapshot = health.snapshot();
+        assert_eq!(snapshot.status, AdapterStatus::Healthy);
+        assert_eq!(snapshot.consecutive_failures, 0);
+        assert!(snapshot.last_success_ms.is_some());
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounded() {
+        let mut scheduler = PidScheduler::new(SchedulerConfig::default()).unwrap();
+        let mut scheduled = ScheduledPid::new(Pid::Speed, 5.0);
+        let now = Instant::now();
+
+        // 100 ms base with ±20% spread
+        for _ in 0..20 {
+            scheduled.failures = 0;
+            let delay = scheduler.record_failure(&mut scheduled, now);
+            assert!((80..=120).contains(&delay.as_millis()), "{delay:?}");
+        }
+    }
+
     #[test]
     fn test_scheduled_pid_ordering() {
         let mut pid1 = ScheduledPid::new(Pid::Rpm, 5.0);
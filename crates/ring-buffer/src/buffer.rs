@@ -108,6 +108,17 @@ impl RingBuffer {
         frames
     }
 
+    /// Timestamp of the frame `age` places back from the most recent
+    /// (0 = most recent), without copying the frame
+    pub fn timestamp_at(&self, age: usize) -> Option<u64> {
+        if age >= self.len() {
+            return None;
+        }
+        let head = self.head.load(Ordering::Acquire);
+        let idx = (head + self.capacity - age - 1) % self.capacity;
+        Some(self.storage[idx].timestamp_ms)
+    }
+
     /// Read frames within a time window (duration in milliseconds)
     pub fn read_window(&self, duration_ms: u64) -> Vec<SensorFrame> {
         let now = std::time::SystemTime::now()
@@ -183,6 +194,21 @@ mod tests {
         assert!(frames[0].rpm >= 500); // Recent frames
     }
 
+    #[test]
+    fn test_timestamp_at_wraps() {
+        let buffer = RingBuffer::new(5);
+        for i in 0..7u64 {
+            buffer.push(SensorFrame {
+                timestamp_ms: i * 1000,
+                ..Default::default()
+            });
+        }
+
+        assert_eq!(buffer.timestamp_at(0), Some(6000));
+        assert_eq!(buffer.timestamp_at(3), Some(3000));
+        assert_eq!(buffer.timestamp_at(4), None);
+    }
+
     #[test]
     fn test_fill_ratio() {
         let buffer = RingBuffer::new(100);
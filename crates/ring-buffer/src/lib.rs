@@ -3,9 +3,12 @@
 //! Provides a high-performance SPSC ring buffer for sensor frame storage.
 
 mod buffer;
+mod snapshot;
 
 pub use buffer::RingBuffer;
+pub use snapshot::SnapshotError;
 
+use common_types::units;
 use serde::{Deserialize, Serialize};
 
 /// Sensor frame stored in the ring buffer (from obd-protocol, duplicated to avoid circular dep)
@@ -20,4 +23,79 @@ pub struct SensorFrame {
     pub fuel_trim_short: i16,
     pub fuel_trim_long: i16,
     pub o2_voltage: u16,
+    /// Bank 2, zero on inline engines
+    pub o2_voltage_b2: u16,
+}
+
+/// Physical-unit accessors; fields hold scaled integers (see
+/// [`common_types::units`])
+impl SensorFrame {
+    /// Engine speed (rpm)
+    pub fn rpm(&self) -> f64 {
+        self.rpm as f64
+    }
+
+    /// Vehicle speed (km/h)
+    pub fn speed_kmh(&self) -> f64 {
+        self.speed as f64
+    }
+
+    /// Coolant temperature (°C)
+    pub fn coolant_temp_c(&self) -> f64 {
+        self.coolant_temp as f64
+    }
+
+    /// Calculated engine load (%)
+    pub fn engine_load_pct(&self) -> f64 {
+        self.engine_load as f64
+    }
+
+    /// Mass air flow rate (g/s)
+    pub fn maf_g_s(&self) -> f64 {
+        units::maf_g_s(self.maf)
+    }
+
+    /// Short-term fuel trim (%)
+    pub fn fuel_trim_short_pct(&self) -> f64 {
+        units::fuel_trim_pct(self.fuel_trim_short)
+    }
+
+    /// Long-term fuel trim (%)
+    pub fn fuel_trim_long_pct(&self) -> f64 {
+        units::fuel_trim_pct(self.fuel_trim_long)
+    }
+
+    /// Oxygen sensor voltage (V)
+    pub fn o2_voltage_v(&self) -> f64 {
+        units::o2_voltage_v(self.o2_voltage)
+    }
+
+    /// Bank 2 oxygen sensor voltage (V)
+    pub fn o2_voltage_b2_v(&self) -> f64 {
+        units::o2_voltage_v(self.o2_voltage_b2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffered_frames_read_back_in_physical_units() {
+        let buffer = RingBuffer::new(4);
+        buffer.push(SensorFrame {
+            timestamp_ms: 1,
+            maf: 1234,
+            fuel_trim_long: 310,
+            o2_voltage_b2: 720,
+            ..Default::default()
+        });
+
+        let frames = buffer.read_last(1);
+        assert!((frames[0].maf_g_s() - 12.34).abs() < 1e-9);
+        assert!((frames[0].fuel_trim_long_pct() - 3.1).abs() < 1e-9);
+        assert!((frames[0].o2_voltage_b2_v() - 0.72).abs() < 1e-9);
+        // Inline engine
+        assert_eq!(SensorFrame::default().o2_voltage_b2_v(), 0.0);
+    }
 }
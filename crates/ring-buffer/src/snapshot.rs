@@ -0,0 +1,111 @@
+//! Buffer Snapshots
+//!
+//! Persists the buffered frames across restarts so the feature windows
+//! don't start cold after a reboot.
+
+use crate::{RingBuffer, SensorFrame};
+use common_types::integrity::{read_verified, write_atomic};
+use common_types::IntegrityError;
+use std::path::Path;
+use thiserror::Error;
+
+/// File magic for ring buffer snapshots; bumped whenever `SensorFrame`
+/// gains a field, since postcard frames carry no field names
+const SNAPSHOT_MAGIC: &[u8; 4] = b"RBS2";
+
+/// Snapshot save/restore errors
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Snapshot corrupt: {0}")]
+    Corrupt(IntegrityError),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] postcard::Error),
+}
+
+impl From<IntegrityError> for SnapshotError {
+    fn from(e: IntegrityError) -> Self {
+        match e {
+            IntegrityError::Io(io) => SnapshotError::Io(io),
+            other => SnapshotError::Corrupt(other),
+        }
+    }
+}
+
+impl RingBuffer {
+    /// Write the buffered frames to `path`, replacing it atomically
+    pub fn snapshot_to(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let mut frames = self.read_last(self.len());
+        frames.reverse(); // oldest first, in push order
+        let payload = postcard::to_allocvec(&frames)?;
+        write_atomic(path, SNAPSHOT_MAGIC, &payload)?;
+        Ok(())
+    }
+
+    /// Create a buffer of `capacity` holding the frames saved at `path`
+    ///
+    /// A damaged file is rejected as a whole; if the snapshot holds more
+    /// frames than fit, the oldest are dropped as they would be live.
+    pub fn restore_from(path: impl AsRef<Path>, capacity: usize) -> Result<Self, SnapshotError> {
+        let payload = read_verified(path, SNAPSHOT_MAGIC)?;
+        let frames: Vec<SensorFrame> = postcard::from_bytes(&payload)?;
+
+        let buffer = RingBuffer::new(capacity);
+        for frame in frames {
+            buffer.push(frame);
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ring-buffer-{}-{}.snap", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let buffer = RingBuffer::new(10);
+        for i in 0..5u64 {
+            buffer.push(SensorFrame {
+                timestamp_ms: i * 200,
+                rpm: 1000 + i as u16,
+                ..Default::default()
+            });
+        }
+
+        let path = snapshot_path("round-trip");
+        buffer.snapshot_to(&path).unwrap();
+        let restored = RingBuffer::restore_from(&path, 10).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.len(), 5);
+        assert_eq!(restored.read_last(1)[0].rpm, 1004);
+    }
+
+    #[test]
+    fn test_truncated_snapshot_is_corrupt() {
+        let buffer = RingBuffer::new(10);
+        for i in 0..8u64 {
+            buffer.push(SensorFrame {
+                timestamp_ms: i * 200,
+                ..Default::default()
+            });
+        }
+
+        let path = snapshot_path("truncated");
+        buffer.snapshot_to(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+
+        let result = RingBuffer::restore_from(&path, 10);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(SnapshotError::Corrupt(IntegrityError::Truncated { .. }))));
+    }
+}
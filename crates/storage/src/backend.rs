@@ -0,0 +1,659 @@
+//! Storage Backend Traits
+//!
+//! Call sites hold a `dyn Storage` rather than a concrete [`Repository`],
+//! so a deployment can swap in another database, a time-series store or a
+//! no-op sink without touching the pipeline or API code. The built-in
+//! in-memory and SQLite backends are both provided by [`Repository`].
+//!
+//! The sensor log and predictions have their own [`SensorStore`] and
+//! [`PredictionStore`] traits, so a downstream crate can put just those
+//! two in another database (e.g. Postgres on the cloud aggregator) while
+//! trips, events and the outbox stay on the local repository.
+//!
+//! Storage operations are async: on SQLite they run queries against a
+//! connection pool rather than locking a buffer.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    CalibrationRecord, EventRecord, OutboxMessage, Page, PredictionFilter, PredictionRecord, Repository,
+    SensorAggregates, SensorRecord, StorageError, TripRecord,
+};
+
+/// Sensor log persistence
+#[async_trait]
+pub trait SensorStore: Send + Sync {
+    /// Insert a sensor record
+    async fn insert_sensor(&self, record: SensorRecord) -> Result<(), StorageError>;
+
+    /// Insert several sensor records atomically
+    async fn insert_sensors_batch(&self, records: &[SensorRecord]) -> Result<(), StorageError>;
+
+    /// Most recent sensor records, newest first
+    async fn get_sensors(&self, limit: usize) -> Result<Vec<SensorRecord>, StorageError>;
+
+    /// A page of sensor records older than `before_timestamp_ms`, newest first
+    async fn get_sensors_paged(
+        &self,
+        before_timestamp_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Page<SensorRecord>, StorageError>;
+
+    /// Sensor records at or after `since_ms`
+    async fn get_sensors_since(&self, since_ms: i64) -> Result<Vec<SensorRecord>, StorageError>;
+
+    /// Min/max/mean of the core sensor fields at or after `since_ms`
+    async fn sensor_aggregates(&self, since_ms: i64) -> Result<SensorAggregates, StorageError>;
+
+    /// Delete sensor records older than `before_ms`, returning how many
+    async fn prune_sensors(&self, before_ms: i64) -> Result<usize, StorageError>;
+
+    /// Sensor records tagged with a trip
+    async fn get_trip_sensors(&self, trip_id: i64) -> Result<Vec<SensorRecord>, StorageError>;
+
+    /// Min/max/mean of the core sensor fields over the records tagged with a trip
+    async fn trip_sensor_aggregates(&self, trip_id: i64) -> Result<SensorAggregates, StorageError>;
+
+    /// Number of stored sensor records
+    async fn sensor_count(&self) -> Result<usize, StorageError>;
+}
+
+/// Prediction persistence
+#[async_trait]
+pub trait PredictionStore: Send + Sync {
+    /// Insert a prediction, returning its ID
+    async fn insert_prediction(&self, record: PredictionRecord) -> Result<i64, StorageError>;
+
+    /// Most recent predictions matching `filter`, newest first
+    async fn get_predictions(&self, filter: &PredictionFilter) -> Result<Vec<PredictionRecord>, StorageError>;
+
+    /// A page of predictions with IDs below `before_id`, newest first
+    async fn get_predictions_paged(
+        &self,
+        before_id: Option<i64>,
+        limit: usize,
+    ) -> Result<Page<PredictionRecord>, StorageError>;
+
+    /// Predictions in `start_ms..=end_ms`, optionally of one severity,
+    /// oldest first
+    async fn get_predictions_between(
+        &self,
+        start_ms: i64,
+        end_ms: i64,
+        severity: Option<&str>,
+    ) -> Result<Vec<PredictionRecord>, StorageError>;
+
+    /// A single prediction
+    async fn get_prediction(&self, id: i64) -> Result<PredictionRecord, StorageError>;
+
+    /// A prediction and the sensor records in its feature window
+    async fn get_prediction_with_context(
+        &self,
+        id: i64,
+    ) -> Result<(PredictionRecord, Vec<SensorRecord>), StorageError>;
+
+    /// Predictions at or after `since_ms` counted per severity
+    async fn prediction_counts_by_severity(
+        &self,
+        since_ms: i64,
+    ) -> Result<HashMap<String, usize>, StorageError>;
+
+    /// Mark a prediction as acknowledged
+    async fn acknowledge_prediction(&self, id: i64) -> Result<(), StorageError>;
+
+    /// Delete predictions older than `before_ms`, returning how many
+    async fn prune_predictions(&self, before_ms: i64) -> Result<usize, StorageError>;
+
+    /// Number of stored predictions
+    async fn prediction_count(&self) -> Result<usize, StorageError>;
+}
+
+/// Persistence operations used by the pipeline, API and cloud sync
+#[async_trait]
+pub trait Storage: SensorStore + PredictionStore {
+    /// Insert a fused event, returning its ID
+    async fn insert_event(&self, record: EventRecord) -> Result<i64, StorageError>;
+
+    /// Most recent events, newest first
+    async fn get_events(&self, limit: usize) -> Result<Vec<EventRecord>, StorageError>;
+
+    /// Delete events older than `before_ms`, returning how many
+    async fn prune_events(&self, before_ms: i64) -> Result<usize, StorageError>;
+
+    /// Insert or replace a vehicle's calibration of one kind
+    async fn save_calibration(&self, record: CalibrationRecord) -> Result<(), StorageError>;
+
+    /// A vehicle's calibration of one kind, if saved
+    async fn get_calibration(
+        &self,
+        vehicle_id: &str,
+        kind: &str,
+    ) -> Result<Option<CalibrationRecord>, StorageError>;
+
+    /// Every calibration saved for a vehicle
+    async fn get_calibrations(&self, vehicle_id: &str) -> Result<Vec<CalibrationRecord>, StorageError>;
+
+    /// Delete a vehicle's calibration of one kind, returning whether one
+    /// was saved
+    async fn delete_calibration(&self, vehicle_id: &str, kind: &str) -> Result<bool, StorageError>;
+
+    /// Open a trip, returning its ID; sensor records and events inserted
+    /// while it is open are tagged with it
+    async fn start_trip(&self, start_ms: i64) -> Result<i64, StorageError>;
+
+    /// Close a trip with its integrated distance
+    async fn end_trip(&self, id: i64, end_ms: i64, distance_km: f64) -> Result<(), StorageError>;
+
+    /// A page of trips, newest first, skipping the `offset` newest
+    async fn list_trips(&self, offset: usize, limit: usize) -> Result<Vec<TripRecord>, StorageError>;
+
+    /// Number of recorded trips
+    async fn trip_count(&self) -> Result<usize, StorageError>;
+
+    /// A single trip
+    async fn get_trip(&self, id: i64) -> Result<TripRecord, StorageError>;
+
+    /// Events tagged with a trip, oldest first
+    async fn get_trip_events(&self, trip_id: i64) -> Result<Vec<EventRecord>, StorageError>;
+
+    /// Event counts by kind for each of `trip_ids`, in one query
+    async fn trip_event_counts(
+        &self,
+        trip_ids: &[i64],
+    ) -> Result<HashMap<i64, HashMap<String, usize>>, StorageError>;
+
+    /// Queue a message for upload, returning its ID
+    async fn enqueue_outbox(&self, message: OutboxMessage) -> Result<i64, StorageError>;
+
+    /// Pending messages due for delivery, oldest first
+    async fn peek_outbox(&self, limit: usize) -> Result<Vec<OutboxMessage>, StorageError>;
+
+    /// Remove a delivered message
+    async fn ack_outbox(&self, id: i64) -> Result<(), StorageError>;
+
+    /// Record a failed attempt and hold the message back for `backoff`,
+    /// returning the attempt count
+    async fn requeue_outbox(&self, id: i64, backoff: Duration) -> Result<u32, StorageError>;
+
+    /// Stop retrying a message, keeping it as a dead letter
+    async fn dead_letter_outbox(&self, id: i64) -> Result<(), StorageError>;
+
+    /// Dead-lettered messages
+    async fn get_dead_letters(&self) -> Result<Vec<OutboxMessage>, StorageError>;
+
+    /// Delete dead letters created before `before_ms`, returning how many
+    async fn prune_dead_letters(&self, before_ms: i64) -> Result<usize, StorageError>;
+
+    /// Number of messages pending delivery
+    async fn outbox_len(&self) -> Result<usize, StorageError>;
+
+    /// Highest message sequence number reserved for a vehicle, 0 if none
+    async fn reserved_sequence(&self, vehicle_id: &str) -> Result<u64, StorageError>;
+
+    /// Record that sequence numbers up to `reserved` may be issued for a
+    /// vehicle
+    async fn reserve_sequence(&self, vehicle_id: &str, reserved: u64) -> Result<(), StorageError>;
+}
+
+#[async_trait]
+impl SensorStore for Repository {
+    async fn insert_sensor(&self, record: SensorRecord) -> Result<(), StorageError> {
+        Repository::insert_sensor(self, record).await
+    }
+
+    async fn insert_sensors_batch(&self, records: &[SensorRecord]) -> Result<(), StorageError> {
+        Repository::insert_sensors_batch(self, records).await
+    }
+
+    async fn get_sensors(&self, limit: usize) -> Result<Vec<SensorRecord>, StorageError> {
+        Repository::get_sensors(self, limit).await
+    }
+
+    async fn get_sensors_paged(
+        &self,
+        before_timestamp_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Page<SensorRecord>, StorageError> {
+        Repository::get_sensors_paged(self, before_timestamp_ms, limit).await
+    }
+
+    async fn get_sensors_since(&self, since_ms: i64) -> Result<Vec<SensorRecord>, StorageError> {
+        Repository::get_sensors_since(self, since_ms).await
+    }
+
+    async fn sensor_aggregates(&self, since_ms: i64) -> Result<SensorAggregates, StorageError> {
+        Repository::sensor_aggregates(self, since_ms).await
+    }
+
+    async fn prune_sensors(&self, before_ms: i64) -> Result<usize, StorageError> {
+        Repository::prune_sensors(self, before_ms).await
+    }
+
+    async fn get_trip_sensors(&self, trip_id: i64) -> Result<Vec<SensorRecord>, StorageError> {
+        Repository::get_trip_sensors(self, trip_id).await
+    }
+
+    async fn trip_sensor_aggregates(&self, trip_id: i64) -> Result<SensorAggregates, StorageError> {
+        Repository::trip_sensor_aggregates(self, trip_id).await
+    }
+
+    async fn sensor_count(&self) -> Result<usize, StorageError> {
+        Repository::sensor_count(self).await
+    }
+}
+
+#[async_trait]
+impl PredictionStore for Repository {
+    async fn insert_prediction(&self, record: PredictionRecord) -> Result<i64, StorageError> {
+        Repository::insert_prediction(self, record).await
+    }
+
+    async fn get_predictions(&self, filter: &PredictionFilter) -> Result<Vec<PredictionRecord>, StorageError> {
+        Repository::get_predictions(self, filter).await
+    }
+
+    async fn get_predictions_paged(
+        &self,
+        before_id: Option<i64>,
+        limit: usize,
+    ) -> Result<Page<PredictionRecord>, StorageError> {
+        Repository::get_predictions_paged(self, before_id, limit).await
+    }
+
+    async fn get_predictions_between(
+        &self,
+        start_ms: i64,
+        end_ms: i64,
+        severity: Option<&str>,
+    ) -> Result<Vec<PredictionRecord>, StorageError> {
+        Repository::get_predictions_between(self, start_ms, end_ms, severity).await
+    }
+
+    async fn get_prediction(&self, id: i64) -> Result<PredictionRecord, StorageError> {
+        Repository::get_prediction(self, id).await
+    }
+
+    async fn get_prediction_with_context(
+        &self,
+        id: i64,
+    ) -> Result<(PredictionRecord, Vec<SensorRecord>), StorageError> {
+        Repository::get_prediction_with_context(self, id).await
+    }
+
+    async fn prediction_counts_by_severity(
+        &self,
+        since_ms: i64,
+    ) -> Result<HashMap<String, usize>, StorageError> {
+        Repository::prediction_counts_by_severity(self, since_ms).await
+    }
+
+    async fn acknowledge_prediction(&self, id: i64) -> Result<(), StorageError> {
+        Repository::acknowledge_prediction(self, id).await
+    }
+
+    async fn prune_predictions(&self, before_ms: i64) -> Result<usize, StorageError> {
+        Repository::prune_predictions(self, before_ms).await
+    }
+
+    async fn prediction_count(&self) -> Result<usize, StorageError> {
+        Repository::prediction_count(self).await
+    }
+}
+
+#[async_trait]
+impl Storage for Repository {
+    async fn insert_event(&self, record: EventRecord) -> Result<i64, StorageError> {
+        Repository::insert_event(self, record).await
+    }
+
+    async fn get_events(&self, limit: usize) -> Result<Vec<EventRecord>, StorageError> {
+        Repository::get_events(self, limit).await
+    }
+
+    async fn prune_events(&self, before_ms: i64) -> Result<usize, StorageError> {
+        Repository::prune_events(self, before_ms).await
+    }
+
+    async fn save_calibration(&self, record: CalibrationRecord) -> Result<(), StorageError> {
+        Repository::save_calibration(self, record).await
+    }
+
+    async fn get_calibration(
+        &self,
+        vehicle_id: &str,
+        kind: &str,
+    ) -> Result<Option<CalibrationRecord>, StorageError> {
+        Repository::get_calibration(self, vehicle_id, kind).await
+    }
+
+    async fn get_calibrations(&self, vehicle_id: &str) -> Result<Vec<CalibrationRecord>, StorageError> {
+        Repository::get_calibrations(self, vehicle_id).await
+    }
+
+    async fn delete_calibration(&self, vehicle_id: &str, kind: &str) -> Result<bool, StorageError> {
+        Repository::delete_calibration(self, vehicle_id, kind).await
+    }
+
+    async fn start_trip(&self, start_ms: i64) -> Result<i64, StorageError> {
+        Repository::start_trip(self, start_ms).await
+    }
+
+    async fn end_trip(&self, id: i64, end_ms: i64, distance_km: f64) -> Result<(), StorageError> {
+        Repository::end_trip(self, id, end_ms, distance_km).await
+    }
+
+    async fn list_trips(&self, offset: usize, limit: usize) -> Result<Vec<TripRecord>, StorageError> {
+        Repository::list_trips(self, offset, limit).await
+    }
+
+    async fn trip_count(&self) -> Result<usize, StorageError> {
+        Repository::trip_count(self).await
+    }
+
+    async fn get_trip(&self, id: i64) -> Result<TripRecord, StorageError> {
+        Repository::get_trip(self, id).await
+    }
+
+    async fn get_trip_events(&self, trip_id: i64) -> Result<Vec<EventRecord>, StorageError> {
+        Repository::get_trip_events(self, trip_id).await
+    }
+
+    async fn trip_event_counts(
+        &self,
+        trip_ids: &[i64],
+    ) -> Result<HashMap<i64, HashMap<String, usize>>, StorageError> {
+        Repository::trip_event_counts(self, trip_ids).await
+    }
+
+    async fn enqueue_outbox(&self, message: OutboxMessage) -> Result<i64, StorageError> {
+        Repository::enqueue_outbox(self, message).await
+    }
+
+    async fn peek_outbox(&self, limit: usize) -> Result<Vec<OutboxMessage>, StorageError> {
+        Repository::peek_outbox(self, limit).await
+    }
+
+    async fn ack_outbox(&self, id: i64) -> Result<(), StorageError> {
+        Repository::ack_outbox(self, id).await
+    }
+
+    async fn requeue_outbox(&self, id: i64, backoff: Duration) -> Result<u32, StorageError> {
+        Repository::requeue_outbox(self, id, backoff).await
+    }
+
+    async fn dead_letter_outbox(&self, id: i64) -> Result<(), StorageError> {
+        Repository::dead_letter_outbox(self, id).await
+    }
+
+    async fn get_dead_letters(&self) -> Result<Vec<OutboxMessage>, StorageError> {
+        Repository::get_dead_letters(self).await
+    }
+
+    async fn prune_dead_letters(&self, before_ms: i64) -> Result<usize, StorageError> {
+        Repository::prune_dead_letters(self, before_ms).await
+    }
+
+    async fn outbox_len(&self) -> Result<usize, StorageError> {
+        Repository::outbox_len(self).await
+    }
+
+    async fn reserved_sequence(&self, vehicle_id: &str) -> Result<u64, StorageError> {
+        Repository::reserved_sequence(self, vehicle_id).await
+    }
+
+    async fn reserve_sequence(&self, vehicle_id: &str, reserved: u64) -> Result<(), StorageError> {
+        Repository::reserve_sequence(self, vehicle_id, reserved).await
+    }
+}
+
+/// A shared backend, e.g. one repository serving both the API and the
+/// cloud outbox
+#[async_trait]
+impl<S: SensorStore + ?Sized> SensorStore for Arc<S> {
+    async fn insert_sensor(&self, record: SensorRecord) -> Result<(), StorageError> {
+        (**self).insert_sensor(record).await
+    }
+
+    async fn insert_sensors_batch(&self, records: &[SensorRecord]) -> Result<(), StorageError> {
+        (**self).insert_sensors_batch(records).await
+    }
+
+    async fn get_sensors(&self, limit: usize) -> Result<Vec<SensorRecord>, StorageError> {
+        (**self).get_sensors(limit).await
+    }
+
+    async fn get_sensors_paged(
+        &self,
+        before_timestamp_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Page<SensorRecord>, StorageError> {
+        (**self).get_sensors_paged(before_timestamp_ms, limit).await
+    }
+
+    async fn get_sensors_since(&self, since_ms: i64) -> Result<Vec<SensorRecord>, StorageError> {
+        (**self).get_sensors_since(since_ms).await
+    }
+
+    async fn sensor_aggregates(&self, since_ms: i64) -> Result<SensorAggregates, StorageError> {
+        (**self).sensor_aggregates(since_ms).await
+    }
+
+    async fn prune_sensors(&self, before_ms: i64) -> Result<usize, StorageError> {
+        (**self).prune_sensors(before_ms).await
+    }
+
+    async fn get_trip_sensors(&self, trip_id: i64) -> Result<Vec<SensorRecord>, StorageError> {
+        (**self).get_trip_sensors(trip_id).await
+    }
+
+    async fn trip_sensor_aggregates(&self, trip_id: i64) -> Result<SensorAggregates, StorageError> {
+        (**self).trip_sensor_aggregates(trip_id).await
+    }
+
+    async fn sensor_count(&self) -> Result<usize, StorageError> {
+        (**self).sensor_count().await
+    }
+}
+
+#[async_trait]
+impl<S: PredictionStore + ?Sized> PredictionStore for Arc<S> {
+    async fn insert_prediction(&self, record: PredictionRecord) -> Result<i64, StorageError> {
+        (**self).insert_prediction(record).await
+    }
+
+    async fn get_predictions(&self, filter: &PredictionFilter) -> Result<Vec<PredictionRecord>, StorageError> {
+        (**self).get_predictions(filter).await
+    }
+
+    async fn get_predictions_paged(
+        &self,
+        before_id: Option<i64>,
+        limit: usize,
+    ) -> Result<Page<PredictionRecord>, StorageError> {
+        (**self).get_predictions_paged(before_id, limit).await
+    }
+
+    async fn get_predictions_between(
+        &self,
+        start_ms: i64,
+        end_ms: i64,
+        severity: Option<&str>,
+    ) -> Result<Vec<PredictionRecord>, StorageError> {
+        (**self).get_predictions_between(start_ms, end_ms, severity).await
+    }
+
+    async fn get_prediction(&self, id: i64) -> Result<PredictionRecord, StorageError> {
+        (**self).get_prediction(id).await
+    }
+
+    async fn get_prediction_with_context(
+        &self,
+        id: i64,
+    ) -> Result<(PredictionRecord, Vec<SensorRecord>), StorageError> {
+        (**self).get_prediction_with_context(id).await
+    }
+
+    async fn prediction_counts_by_severity(
+        &self,
+        since_ms: i64,
+    ) -> Result<HashMap<String, usize>, StorageError> {
+        (**self).prediction_counts_by_severity(since_ms).await
+    }
+
+    async fn acknowledge_prediction(&self, id: i64) -> Result<(), StorageError> {
+        (**self).acknowledge_prediction(id).await
+    }
+
+    async fn prune_predictions(&self, before_ms: i64) -> Result<usize, StorageError> {
+        (**self).prune_predictions(before_ms).await
+    }
+
+    async fn prediction_count(&self) -> Result<usize, StorageError> {
+        (**self).prediction_count().await
+    }
+}
+
+#[async_trait]
+impl<S: Storage + ?Sized> Storage for Arc<S> {
+    async fn insert_event(&self, record: EventRecord) -> Result<i64, StorageError> {
+        (**self).insert_event(record).await
+    }
+
+    async fn get_events(&self, limit: usize) -> Result<Vec<EventRecord>, StorageError> {
+        (**self).get_events(limit).await
+    }
+
+    async fn prune_events(&self, before_ms: i64) -> Result<usize, StorageError> {
+        (**self).prune_events(before_ms).await
+    }
+
+    async fn save_calibration(&self, record: CalibrationRecord) -> Result<(), StorageError> {
+        (**self).save_calibration(record).await
+    }
+
+    async fn get_calibration(
+        &self,
+        vehicle_id: &str,
+        kind: &str,
+    ) -> Result<Option<CalibrationRecord>, StorageError> {
+        (**self).get_calibration(vehicle_id, kind).await
+    }
+
+    async fn get_calibrations(&self, vehicle_id: &str) -> Result<Vec<CalibrationRecord>, StorageError> {
+        (**self).get_calibrations(vehicle_id).await
+    }
+
+    async fn delete_calibration(&self, vehicle_id: &str, kind: &str) -> Result<bool, StorageError> {
+        (**self).delete_calibration(vehicle_id, kind).await
+    }
+
+    async fn start_trip(&self, start_ms: i64) -> Result<i64, StorageError> {
+        (**self).start_trip(start_ms).await
+    }
+
+    async fn end_trip(&self, id: i64, end_ms: i64, distance_km: f64) -> Result<(), StorageError> {
+        (**self).end_trip(id, end_ms, distance_km).await
+    }
+
+    async fn list_trips(&self, offset: usize, limit: usize) -> Result<Vec<TripRecord>, StorageError> {
+        (**self).list_trips(offset, limit).await
+    }
+
+    async fn trip_count(&self) -> Result<usize, StorageError> {
+        (**self).trip_count().await
+    }
+
+    async fn get_trip(&self, id: i64) -> Result<TripRecord, StorageError> {
+        (**self).get_trip(id).await
+    }
+
+    async fn get_trip_events(&self, trip_id: i64) -> Result<Vec<EventRecord>, StorageError> {
+        (**self).get_trip_events(trip_id).await
+    }
+
+    async fn trip_event_counts(
+        &self,
+        trip_ids: &[i64],
+    ) -> Result<HashMap<i64, HashMap<String, usize>>, StorageError> {
+        (**self).trip_event_counts(trip_ids).await
+    }
+
+    async fn enqueue_outbox(&self, message: OutboxMessage) -> Result<i64, StorageError> {
+        (**self).enqueue_outbox(message).await
+    }
+
+    async fn peek_outbox(&self, limit: usize) -> Result<Vec<OutboxMessage>, StorageError> {
+        (**self).peek_outbox(limit).await
+    }
+
+    async fn ack_outbox(&self, id: i64) -> Result<(), StorageError> {
+        (**self).ack_outbox(id).await
+    }
+
+    async fn requeue_outbox(&self, id: i64, backoff: Duration) -> Result<u32, StorageError> {
+        (**self).requeue_outbox(id, backoff).await
+    }
+
+    async fn dead_letter_outbox(&self, id: i64) -> Result<(), StorageError> {
+        (**self).dead_letter_outbox(id).await
+    }
+
+    async fn get_dead_letters(&self) -> Result<Vec<OutboxMessage>, StorageError> {
+        (**self).get_dead_letters().await
+    }
+
+    async fn prune_dead_letters(&self, before_ms: i64) -> Result<usize, StorageError> {
+        (**self).prune_dead_letters(before_ms).await
+    }
+
+    async fn outbox_len(&self) -> Result<usize, StorageError> {
+        (**self).outbox_len().await
+    }
+
+    async fn reserved_sequence(&self, vehicle_id: &str) -> Result<u64, StorageError> {
+        (**self).reserved_sequence(vehicle_id).await
+    }
+
+    async fn reserve_sequence(&self, vehicle_id: &str, reserved: u64) -> Result<(), StorageError> {
+        (**self).reserve_sequence(vehicle_id, reserved).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_prune_through_trait_object() {
+        let storage: Box<dyn Storage> = Box::new(Arc::new(Repository::new()));
+
+        for timestamp_ms in [1_000, 2_000, 3_000] {
+            storage
+                .insert_sensor(SensorRecord { timestamp_ms, ..Default::default() })
+                .await
+                .unwrap();
+            storage
+                .insert_event(EventRecord {
+                    id: 0,
+                    timestamp_ms,
+                    kind: "hard_braking".to_string(),
+                    severity: Some("medium".to_string()),
+                    payload: "{}".to_string(),
+                    trip_id: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(storage.prune_sensors(2_000).await.unwrap(), 1);
+        assert_eq!(storage.prune_events(3_000).await.unwrap(), 2);
+        assert_eq!(storage.sensor_count().await.unwrap(), 2);
+        let events = storage.get_events(10).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, 3);
+    }
+}
@@ -0,0 +1,116 @@
+//! Per-Vehicle Calibration Store
+//!
+//! Camera geometry, IMU bias, the head-pose baseline and gear ratios are
+//! measured once per vehicle and must survive reboots. They are stored as
+//! JSON per vehicle and kind, so a crate can add a calibration kind
+//! without a schema change and an older build skips kinds it does not
+//! know.
+
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::info;
+
+use crate::{CalibrationRecord, Storage, StorageError};
+
+/// Calibration kinds used by the pipeline
+pub mod kind {
+    /// Road camera intrinsics and mounting (`adas::CameraGeometry`)
+    pub const CAMERA_GEOMETRY: &str = "camera_geometry";
+    /// Accelerometer and gyro offsets (`camera_capture::imu::ImuBias`)
+    pub const IMU_BIAS: &str = "imu_bias";
+    /// Neutral head pose as (yaw, pitch) degrees for the cabin camera mount
+    pub const HEAD_POSE: &str = "head_pose";
+    /// Gear ratios (`feature_engine::GearConfig`)
+    pub const GEAR_RATIOS: &str = "gear_ratios";
+}
+
+/// Typed access to one vehicle's calibrations
+pub struct CalibrationStore {
+    storage: Arc<dyn Storage>,
+    vehicle_id: String,
+}
+
+impl CalibrationStore {
+    /// Calibrations of `vehicle_id` kept in `storage`
+    pub fn new(storage: Arc<dyn Storage>, vehicle_id: impl Into<String>) -> Self {
+        Self {
+            storage,
+            vehicle_id: vehicle_id.into(),
+        }
+    }
+
+    /// Vehicle the calibrations belong to
+    pub fn vehicle_id(&self) -> &str {
+        &self.vehicle_id
+    }
+
+    /// Load a calibration, `None` if this vehicle has never saved one
+    pub async fn load<T: DeserializeOwned>(&self, kind: &str) -> Result<Option<T>, StorageError> {
+        let Some(record) = self.storage.get_calibration(&self.vehicle_id, kind).await? else {
+            return Ok(None);
+        };
+        serde_json::from_str(&record.payload).map(Some).map_err(|e| {
+            StorageError::SerializationError(format!("{kind} calibration: {e}"))
+        })
+    }
+
+    /// Save a calibration, replacing the previous one of the same kind
+    pub async fn save<T: Serialize>(&self, kind: &str, value: &T) -> Result<(), StorageError> {
+        let payload = serde_json::to_string(value)
+            .map_err(|e| StorageError::SerializationError(format!("{kind} calibration: {e}")))?;
+        self.storage.save_calibration(CalibrationRecord {
+            vehicle_id: self.vehicle_id.clone(),
+            kind: kind.to_string(),
+            payload,
+            updated_ms: now_ms(),
+        })
+        .await?;
+        info!("Saved {} calibration for vehicle {}", kind, self.vehicle_id);
+        Ok(())
+    }
+
+    /// Kinds saved for this vehicle
+    pub async fn kinds(&self) -> Result<Vec<String>, StorageError> {
+        Ok(self
+            .storage
+            .get_calibrations(&self.vehicle_id)
+            .await?
+            .into_iter()
+            .map(|record| record.kind)
+            .collect())
+    }
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Repository;
+
+    #[tokio::test]
+    async fn test_calibration_kept_per_vehicle() {
+        let repo = Arc::new(Repository::new());
+        let van = CalibrationStore::new(repo.clone(), "van-7");
+        let truck = CalibrationStore::new(repo.clone(), "truck-2");
+
+        van.save(kind::HEAD_POSE, &(4.5f32, -2.0f32)).await.unwrap();
+        van.save(kind::HEAD_POSE, &(5.0f32, -1.5f32)).await.unwrap();
+        van.save(kind::GEAR_RATIOS, &vec![8.0, 15.0, 24.0]).await.unwrap();
+
+        assert_eq!(van.load::<(f32, f32)>(kind::HEAD_POSE).await.unwrap(), Some((5.0, -1.5)));
+        assert_eq!(van.kinds().await.unwrap(), [kind::GEAR_RATIOS, kind::HEAD_POSE]);
+        assert_eq!(truck.load::<(f32, f32)>(kind::HEAD_POSE).await.unwrap(), None);
+
+        // A payload that no longer matches the type is an error, not a default
+        let err = van.load::<String>(kind::GEAR_RATIOS).await.unwrap_err();
+        assert!(matches!(err, StorageError::SerializationError(_)));
+    }
+}
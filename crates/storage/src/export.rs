@@ -0,0 +1,116 @@
+//! CSV Export
+//!
+//! Rows are written as RFC 4180 CSV: a header line, CRLF line endings, and
+//! fields quoted only when they hold a comma, quote or line break. Missing
+//! optional readings are empty fields. Every timestamp is written twice,
+//! as Unix ms for scripts and as ISO 8601 UTC for spreadsheets.
+
+use std::io::Write;
+
+use chrono::{DateTime, SecondsFormat};
+
+use crate::{PredictionRecord, SensorRecord};
+
+/// Records fetched per query while exporting from SQLite
+pub(crate) const EXPORT_CHUNK: i64 = 1_000;
+
+const SENSOR_HEADER: &[&str] = &[
+    "timestamp_ms",
+    "timestamp",
+    "rpm",
+    "speed",
+    "coolant_temp",
+    "engine_load",
+    "maf",
+    "fuel_trim_short",
+    "fuel_trim_long",
+    "trip_id",
+    "intake_temp",
+    "fuel_level",
+    "o2_lambda",
+    "throttle_pos",
+];
+
+const PREDICTION_HEADER: &[&str] = &[
+    "id",
+    "timestamp_ms",
+    "timestamp",
+    "fault_class",
+    "confidence",
+    "severity",
+    "snapshot_start_ms",
+    "snapshot_end_ms",
+    "acknowledged",
+];
+
+/// ISO 8601 UTC with milliseconds, empty if out of range
+fn iso8601(timestamp_ms: i64) -> String {
+    DateTime::from_timestamp_millis(timestamp_ms)
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Write one CSV line, quoting fields as needed
+fn write_row<W: Write + ?Sized>(writer: &mut W, fields: &[&str]) -> std::io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        if field.contains([',', '"', '\r', '\n']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")
+}
+
+pub(crate) fn write_sensor_header<W: Write + ?Sized>(writer: &mut W) -> std::io::Result<()> {
+    write_row(writer, SENSOR_HEADER)
+}
+
+pub(crate) fn write_sensor<W: Write + ?Sized>(writer: &mut W, record: &SensorRecord) -> std::io::Result<()> {
+    let fields = [
+        record.timestamp_ms.to_string(),
+        iso8601(record.timestamp_ms),
+        record.rpm.to_string(),
+        record.speed.to_string(),
+        record.coolant_temp.to_string(),
+        record.engine_load.to_string(),
+        record.maf.to_string(),
+        record.fuel_trim_short.to_string(),
+        record.fuel_trim_long.to_string(),
+        optional(record.trip_id),
+        optional(record.intake_temp),
+        optional(record.fuel_level),
+        optional(record.o2_lambda),
+        optional(record.throttle_pos),
+    ];
+    write_row(writer, &fields.each_ref().map(String::as_str))
+}
+
+pub(crate) fn write_prediction_header<W: Write + ?Sized>(writer: &mut W) -> std::io::Result<()> {
+    write_row(writer, PREDICTION_HEADER)
+}
+
+pub(crate) fn write_prediction<W: Write + ?Sized>(
+    writer: &mut W,
+    record: &PredictionRecord,
+) -> std::io::Result<()> {
+    let fields = [
+        record.id.to_string(),
+        record.timestamp_ms.to_string(),
+        iso8601(record.timestamp_ms),
+        record.fault_class.clone(),
+        record.confidence.to_string(),
+        record.severity.clone(),
+        optional(record.sensor_snapshot.map(|s| s.start_ms)),
+        optional(record.sensor_snapshot.map(|s| s.end_ms)),
+        record.acknowledged.to_string(),
+    ];
+    write_row(writer, &fields.each_ref().map(String::as_str))
+}
@@ -2,19 +2,99 @@
 //!
 //! Provides SQLite persistence with repository pattern.
 
+mod backend;
+pub mod calibration;
+mod export;
+mod recorder;
 mod repository;
+pub mod schema;
+mod sequence;
 
-pub use repository::{Repository, SensorRecord, PredictionRecord};
+pub use backend::{PredictionStore, SensorStore, Storage};
+pub use calibration::CalibrationStore;
+pub use recorder::{PredictionRecorder, PredictionStoreConfig, NO_FAULT_CLASS};
+pub use repository::{
+    CalibrationRecord, EventRecord, FieldStats, OutboxMessage, OutboxStatus, Page, PredictionFilter, PredictionRecord,
+    Repository, SensorAggregates, SensorRecord, SensorSnapshot, StorageTransaction, TripRecord,
+};
+pub use sequence::SequenceCounter;
 
 use thiserror::Error;
 
 /// Storage errors
 #[derive(Debug, Error)]
 pub enum StorageError {
+    /// Could not reach or open the database
+    #[error("Connection error: {0}")]
+    Connection(String),
+    /// A query was rejected or failed to execute
+    #[error("Query failed: {0}")]
+    Query(String),
+    /// A lock guarding in-memory state was poisoned by a panicking writer
+    #[error("Lock poisoned: {0}")]
+    LockPoisoned(String),
+    /// Schema migration failed
+    #[error("Migration failed: {0}")]
+    Migration(String),
+    /// A uniqueness, foreign-key, not-null or check constraint was violated
+    #[error("Constraint violation: {0}")]
+    Constraint(String),
+    /// Any other database error
     #[error("Database error: {0}")]
     DatabaseError(String),
     #[error("Record not found")]
     NotFound,
     #[error("Serialization error: {0}")]
     SerializationError(String),
+    /// Reading or writing a file or stream outside the database failed
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+impl StorageError {
+    /// Whether the operation may succeed if retried later
+    ///
+    /// Only connection-level failures are considered transient; constraint
+    /// violations and query errors indicate a programming or data error.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, StorageError::Connection(_))
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for StorageError {
+    fn from(err: std::sync::PoisonError<T>) -> Self {
+        StorageError::LockPoisoned(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(err: std::io::Error) -> Self {
+        StorageError::Io(err.to_string())
+    }
+}
+
+impl From<sqlx::Error> for StorageError {
+    fn from(err: sqlx::Error) -> Self {
+        use sqlx::error::ErrorKind;
+
+        match err {
+            sqlx::Error::Database(db_err) => match db_err.kind() {
+                ErrorKind::UniqueViolation
+                | ErrorKind::ForeignKeyViolation
+                | ErrorKind::NotNullViolation
+                | ErrorKind::CheckViolation => StorageError::Constraint(db_err.to_string()),
+                _ => StorageError::Query(db_err.to_string()),
+            },
+            sqlx::Error::Io(e) => StorageError::Connection(e.to_string()),
+            sqlx::Error::Tls(e) => StorageError::Connection(e.to_string()),
+            sqlx::Error::PoolTimedOut => StorageError::Connection("pool timed out".to_string()),
+            sqlx::Error::PoolClosed => StorageError::Connection("pool closed".to_string()),
+            sqlx::Error::Migrate(e) => StorageError::Migration(e.to_string()),
+            sqlx::Error::RowNotFound => StorageError::NotFound,
+            sqlx::Error::ColumnDecode { .. } | sqlx::Error::Decode(_) => {
+                StorageError::SerializationError(err.to_string())
+            }
+            other => StorageError::DatabaseError(other.to_string()),
+        }
+    }
 }
@@ -0,0 +1,130 @@
+//! Prediction Recording
+//!
+//! The model runs on every feature window, but most windows are normal
+//! driving or low-confidence guesses. Persisting all of them buries the
+//! incidents worth reviewing, so only actionable predictions are written;
+//! every inference is still counted.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{PredictionRecord, Storage, StorageError};
+
+/// Fault class the inference engine reports for normal operation
+pub const NO_FAULT_CLASS: &str = "none";
+
+/// Which predictions are persisted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictionStoreConfig {
+    /// Predictions below this confidence are only counted (0.0-1.0)
+    pub store_min_confidence: f64,
+    /// Skip predictions of [`NO_FAULT_CLASS`] regardless of confidence
+    pub store_only_faults: bool,
+}
+
+impl Default for PredictionStoreConfig {
+    fn default() -> Self {
+        Self {
+            store_min_confidence: 0.6,
+            store_only_faults: true,
+        }
+    }
+}
+
+/// Gates predictions before they reach the storage backend
+pub struct PredictionRecorder {
+    storage: Arc<dyn Storage>,
+    config: PredictionStoreConfig,
+    inferences: AtomicU64,
+    stored: AtomicU64,
+}
+
+impl PredictionRecorder {
+    /// Record predictions into `storage`
+    pub fn new(storage: Arc<dyn Storage>, config: PredictionStoreConfig) -> Self {
+        Self {
+            storage,
+            config,
+            inferences: AtomicU64::new(0),
+            stored: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether `record` would be persisted
+    pub fn admits(&self, record: &PredictionRecord) -> bool {
+        if self.config.store_only_faults && record.fault_class == NO_FAULT_CLASS {
+            return false;
+        }
+        record.confidence >= self.config.store_min_confidence
+    }
+
+    /// Count one inference and store its prediction if it is actionable,
+    /// returning the stored ID
+    pub async fn record(&self, record: PredictionRecord) -> Result<Option<i64>, StorageError> {
+        self.inferences.fetch_add(1, Ordering::Relaxed);
+        if !self.admits(&record) {
+            debug!(
+                "Not storing {} prediction at confidence {:.2}",
+                record.fault_class, record.confidence
+            );
+            return Ok(None);
+        }
+
+        let id = self.storage.insert_prediction(record).await?;
+        self.stored.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(id))
+    }
+
+    /// Inferences seen, stored or not
+    pub fn inference_count(&self) -> u64 {
+        self.inferences.load(Ordering::Relaxed)
+    }
+
+    /// Predictions written to storage
+    pub fn stored_count(&self) -> u64 {
+        self.stored.load(Ordering::Relaxed)
+    }
+
+    /// Active configuration
+    pub fn config(&self) -> &PredictionStoreConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PredictionFilter, Repository};
+
+    fn prediction(fault_class: &str, confidence: f64) -> PredictionRecord {
+        PredictionRecord {
+            id: 0,
+            timestamp_ms: 60_000,
+            fault_class: fault_class.to_string(),
+            confidence,
+            severity: "high".to_string(),
+            sensor_snapshot: None,
+            acknowledged: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_confident_faults_are_stored() {
+        let repo = Arc::new(Repository::new());
+        let recorder = PredictionRecorder::new(repo.clone(), PredictionStoreConfig::default());
+
+        assert_eq!(recorder.record(prediction(NO_FAULT_CLASS, 0.98)).await.unwrap(), None);
+        assert_eq!(recorder.record(prediction("engine_overheating", 0.35)).await.unwrap(), None);
+        let id = recorder.record(prediction("engine_overheating", 0.92)).await.unwrap();
+
+        assert!(id.is_some());
+        assert_eq!(recorder.inference_count(), 3);
+        assert_eq!(recorder.stored_count(), 1);
+        let stored = repo.get_predictions(&PredictionFilter::new(10)).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].confidence, 0.92);
+    }
+}
@@ -1,10 +1,19 @@
 //! Repository Implementation
 
-use crate::StorageError;
+use crate::{export, schema, StorageError};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
-use std::sync::Mutex;
-use tracing::{debug, info};
+use sqlx::query::Query;
+use sqlx::sqlite::{
+    SqliteArguments, SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow,
+    SqliteSynchronous,
+};
+use sqlx::{Row, Sqlite};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
 /// Sensor log record
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +26,96 @@ pub struct SensorRecord {
     pub maf: f64,
     pub fuel_trim_short: f64,
     pub fuel_trim_long: f64,
+    /// Trip this record belongs to (tagged at insert time if a trip is open)
+    #[serde(default)]
+    pub trip_id: Option<i64>,
+    /// Intake air temperature (°C), `None` if not reported
+    #[serde(default)]
+    pub intake_temp: Option<i32>,
+    /// Fuel tank level (%), `None` if not reported
+    #[serde(default)]
+    pub fuel_level: Option<f64>,
+    /// Wideband O2 equivalence ratio (lambda), `None` if not reported
+    #[serde(default)]
+    pub o2_lambda: Option<f64>,
+    /// Throttle position (%), `None` if not reported
+    #[serde(default)]
+    pub throttle_pos: Option<f64>,
+}
+
+/// Minimum, maximum and mean of one sensor field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldStats {
+    pub min: i32,
+    pub max: i32,
+    pub mean: f64,
+}
+
+/// Summary statistics over a window of sensor records; all zero when the
+/// window is empty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SensorAggregates {
+    pub count: usize,
+    pub rpm: FieldStats,
+    pub speed: FieldStats,
+    pub coolant_temp: FieldStats,
+    pub engine_load: FieldStats,
+}
+
+/// Running min/max/sum for one field
+#[derive(Default)]
+struct FieldAccumulator {
+    min: i32,
+    max: i32,
+    sum: i64,
+}
+
+impl FieldAccumulator {
+    fn add(&mut self, value: i32, first: bool) {
+        if first {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += value as i64;
+    }
+
+    fn finish(&self, count: usize) -> FieldStats {
+        FieldStats {
+            min: self.min,
+            max: self.max,
+            mean: self.sum as f64 / count as f64,
+        }
+    }
+}
+
+/// Default sensor window attached to a prediction (matches the 30s feature window)
+pub const DEFAULT_SNAPSHOT_WINDOW_MS: i64 = 30_000;
+
+/// Time range of the sensor records that produced a prediction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SensorSnapshot {
+    /// Start of the feature window (Unix ms, inclusive)
+    pub start_ms: i64,
+    /// End of the feature window (Unix ms, inclusive)
+    pub end_ms: i64,
+}
+
+impl SensorSnapshot {
+    /// Window of `window_ms` ending at `end_ms`
+    pub fn ending_at(end_ms: i64, window_ms: i64) -> Self {
+        Self {
+            start_ms: end_ms - window_ms,
+            end_ms,
+        }
+    }
+
+    /// Check if a timestamp falls inside the window
+    pub fn contains(&self, timestamp_ms: i64) -> bool {
+        timestamp_ms >= self.start_ms && timestamp_ms <= self.end_ms
+    }
 }
 
 /// Prediction record
@@ -27,11 +126,362 @@ pub struct PredictionRecord {
     pub fault_class: String,
     pub confidence: f64,
     pub severity: String,
+    /// Sensor window that produced this prediction (filled in at insert time if absent)
+    #[serde(default)]
+    pub sensor_snapshot: Option<SensorSnapshot>,
+    /// Whether someone has acknowledged the prediction on the dashboard
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+/// One page of a newest-first listing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next (older) page, `None` once history is exhausted
+    pub next_cursor: Option<i64>,
+}
+
+impl<T> Page<T> {
+    /// Page from up to `limit + 1` fetched items; the extra one only shows
+    /// that more remain and is not returned
+    fn from_overfetch(mut items: Vec<T>, limit: usize, cursor: impl Fn(&T) -> i64) -> Self {
+        let more = items.len() > limit;
+        items.truncate(limit);
+        let next_cursor = if more { items.last().map(cursor) } else { None };
+        Self { items, next_cursor }
+    }
+}
+
+/// Which predictions [`Repository::get_predictions`] returns; every
+/// criterion set must match, and unset or empty ones match anything
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictionFilter {
+    pub severity: Option<String>,
+    pub fault_class: Option<String>,
+    /// Leave out predictions already acknowledged
+    pub unacknowledged_only: bool,
+    /// Maximum number of predictions, newest first
+    pub limit: usize,
+}
+
+impl PredictionFilter {
+    /// Match every prediction, up to `limit`
+    pub fn new(limit: usize) -> Self {
+        Self {
+            severity: None,
+            fault_class: None,
+            unacknowledged_only: false,
+            limit,
+        }
+    }
+
+    /// Only predictions of `severity`
+    pub fn with_severity(mut self, severity: impl Into<String>) -> Self {
+        self.severity = Some(severity.into());
+        self
+    }
+
+    /// Only predictions of `fault_class`
+    pub fn with_fault_class(mut self, fault_class: impl Into<String>) -> Self {
+        self.fault_class = Some(fault_class.into());
+        self
+    }
+
+    /// Only predictions nobody has acknowledged
+    pub fn unacknowledged(mut self) -> Self {
+        self.unacknowledged_only = true;
+        self
+    }
+
+    fn severity(&self) -> Option<&str> {
+        self.severity.as_deref().filter(|s| !s.is_empty())
+    }
+
+    fn fault_class(&self) -> Option<&str> {
+        self.fault_class.as_deref().filter(|c| !c.is_empty())
+    }
+
+    fn matches(&self, prediction: &PredictionRecord) -> bool {
+        self.severity().is_none_or(|s| prediction.severity == s)
+            && self.fault_class().is_none_or(|c| prediction.fault_class == c)
+            && !(self.unacknowledged_only && prediction.acknowledged)
+    }
+}
+
+/// Trip record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TripRecord {
+    pub id: i64,
+    pub start_ms: i64,
+    /// End of the trip, `None` while the trip is in progress
+    pub end_ms: Option<i64>,
+    pub distance_km: f64,
+}
+
+/// Fused driving event record (hard braking, crash, distraction, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub id: i64,
+    pub timestamp_ms: i64,
+    /// Event type, e.g. `hard_braking`
+    pub kind: String,
+    /// Severity string, `None` for informational events
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// Event details as serialized JSON
+    pub payload: String,
+    /// Trip this event belongs to (tagged at insert time if a trip is open)
+    #[serde(default)]
+    pub trip_id: Option<i64>,
+}
+
+/// Per-vehicle calibration record, one per vehicle and kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationRecord {
+    pub vehicle_id: String,
+    /// What was calibrated, e.g. `camera_geometry`
+    pub kind: String,
+    /// Calibration values as serialized JSON
+    pub payload: String,
+    /// When the calibration was last saved (Unix ms)
+    pub updated_ms: i64,
+}
+
+/// Delivery state of an outbox message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboxStatus {
+    /// Waiting for (re)delivery
+    Pending,
+    /// Gave up after repeated failures; kept for inspection
+    DeadLetter,
+}
+
+/// Outbox record: a message queued for store-and-forward upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxMessage {
+    pub id: i64,
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub created_ms: i64,
+    /// Failed delivery attempts so far
+    pub attempts: u32,
+    /// Earliest time the message may be retried (Unix ms)
+    pub next_attempt_ms: i64,
+    /// Queued at critical upload priority rather than routine
+    pub critical: bool,
+    pub status: OutboxStatus,
 }
 
-/// Repository for data access (in-memory implementation for now)
+impl OutboxMessage {
+    /// New pending message, deliverable immediately
+    pub fn new(topic: impl Into<String>, payload: Vec<u8>) -> Self {
+        let now = now_ms();
+        Self {
+            id: 0,
+            topic: topic.into(),
+            payload,
+            created_ms: now,
+            attempts: 0,
+            next_attempt_ms: now,
+            critical: false,
+            status: OutboxStatus::Pending,
+        }
+    }
+
+    /// Mark the message as critical, or routine
+    pub fn with_critical(mut self, critical: bool) -> Self {
+        self.critical = critical;
+        self
+    }
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Columns read back into a [`SensorRecord`]
+const SENSOR_COLUMNS: &str = "timestamp_ms, rpm, speed, coolant_temp, engine_load, maf, \
+    fuel_trim_short, fuel_trim_long, trip_id, intake_temp, fuel_level, o2_lambda, throttle_pos";
+
+/// Columns read back into a [`PredictionRecord`]
+const PREDICTION_COLUMNS: &str =
+    "id, timestamp_ms, fault_class, confidence, severity, snapshot_start_ms, snapshot_end_ms, acknowledged";
+
+/// Columns read back into an [`EventRecord`]
+const EVENT_COLUMNS: &str = "id, timestamp_ms, kind, severity, payload, trip_id";
+
+/// INSERT for one sensor record, with every column bound
+fn insert_sensor_query(record: &SensorRecord) -> Query<'static, Sqlite, SqliteArguments<'static>> {
+    sqlx::query(
+        "INSERT INTO sensor_log (timestamp_ms, rpm, speed, coolant_temp, engine_load, maf,
+            fuel_trim_short, fuel_trim_long, trip_id, intake_temp, fuel_level, o2_lambda, throttle_pos)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(record.timestamp_ms)
+    .bind(record.rpm)
+    .bind(record.speed)
+    .bind(record.coolant_temp)
+    .bind(record.engine_load)
+    .bind(record.maf)
+    .bind(record.fuel_trim_short)
+    .bind(record.fuel_trim_long)
+    .bind(record.trip_id)
+    .bind(record.intake_temp)
+    .bind(record.fuel_level)
+    .bind(record.o2_lambda)
+    .bind(record.throttle_pos)
+}
+
+/// Check a prediction before insert and fill in its default snapshot
+fn prepare_prediction(record: &mut PredictionRecord) -> Result<(), StorageError> {
+    // Same bounds as the CHECK on predictions.confidence (migration 1), so
+    // the in-memory store rejects what SQLite would
+    if !(0.0..=1.0).contains(&record.confidence) {
+        return Err(StorageError::Constraint(format!(
+            "prediction confidence {} outside [0, 1]",
+            record.confidence
+        )));
+    }
+
+    if record.sensor_snapshot.is_none() {
+        record.sensor_snapshot = Some(SensorSnapshot::ending_at(
+            record.timestamp_ms,
+            DEFAULT_SNAPSHOT_WINDOW_MS,
+        ));
+    }
+    Ok(())
+}
+
+/// INSERT for one prediction; the ID is assigned by SQLite
+fn insert_prediction_query(record: &PredictionRecord) -> Query<'_, Sqlite, SqliteArguments<'_>> {
+    sqlx::query(
+        "INSERT INTO predictions (timestamp_ms, fault_class, confidence, severity,
+            snapshot_start_ms, snapshot_end_ms, acknowledged) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(record.timestamp_ms)
+    .bind(&record.fault_class)
+    .bind(record.confidence)
+    .bind(&record.severity)
+    .bind(record.sensor_snapshot.map(|s| s.start_ms))
+    .bind(record.sensor_snapshot.map(|s| s.end_ms))
+    .bind(record.acknowledged)
+}
+
+fn sensor_from_row(row: &SqliteRow) -> Result<SensorRecord, sqlx::Error> {
+    Ok(SensorRecord {
+        timestamp_ms: row.try_get("timestamp_ms")?,
+        rpm: row.try_get("rpm")?,
+        speed: row.try_get("speed")?,
+        coolant_temp: row.try_get("coolant_temp")?,
+        engine_load: row.try_get("engine_load")?,
+        maf: row.try_get("maf")?,
+        fuel_trim_short: row.try_get("fuel_trim_short")?,
+        fuel_trim_long: row.try_get("fuel_trim_long")?,
+        trip_id: row.try_get("trip_id")?,
+        intake_temp: row.try_get("intake_temp")?,
+        fuel_level: row.try_get("fuel_level")?,
+        o2_lambda: row.try_get("o2_lambda")?,
+        throttle_pos: row.try_get("throttle_pos")?,
+    })
+}
+
+fn outbox_from_row(row: &SqliteRow) -> Result<OutboxMessage, sqlx::Error> {
+    let dead_letter: bool = row.try_get("dead_letter")?;
+    Ok(OutboxMessage {
+        id: row.try_get("id")?,
+        topic: row.try_get("topic")?,
+        payload: row.try_get("payload")?,
+        created_ms: row.try_get("created_ms")?,
+        attempts: row.try_get("attempts")?,
+        next_attempt_ms: row.try_get("next_attempt_ms")?,
+        critical: row.try_get("critical")?,
+        status: if dead_letter {
+            OutboxStatus::DeadLetter
+        } else {
+            OutboxStatus::Pending
+        },
+    })
+}
+
+fn calibration_from_row(row: &SqliteRow) -> Result<CalibrationRecord, sqlx::Error> {
+    Ok(CalibrationRecord {
+        vehicle_id: row.try_get("vehicle_id")?,
+        kind: row.try_get("kind")?,
+        payload: row.try_get("payload")?,
+        updated_ms: row.try_get("updated_ms")?,
+    })
+}
+
+fn event_from_row(row: &SqliteRow) -> Result<EventRecord, sqlx::Error> {
+    Ok(EventRecord {
+        id: row.try_get("id")?,
+        timestamp_ms: row.try_get("timestamp_ms")?,
+        kind: row.try_get("kind")?,
+        severity: row.try_get("severity")?,
+        payload: row.try_get("payload")?,
+        trip_id: row.try_get("trip_id")?,
+    })
+}
+
+fn trip_from_row(row: &SqliteRow) -> Result<TripRecord, sqlx::Error> {
+    Ok(TripRecord {
+        id: row.try_get("id")?,
+        start_ms: row.try_get("start_ms")?,
+        end_ms: row.try_get("end_ms")?,
+        distance_km: row.try_get("distance_km")?,
+    })
+}
+
+fn prediction_from_row(row: &SqliteRow) -> Result<PredictionRecord, sqlx::Error> {
+    let start: Option<i64> = row.try_get("snapshot_start_ms")?;
+    let end: Option<i64> = row.try_get("snapshot_end_ms")?;
+    Ok(PredictionRecord {
+        id: row.try_get("id")?,
+        timestamp_ms: row.try_get("timestamp_ms")?,
+        fault_class: row.try_get("fault_class")?,
+        confidence: row.try_get("confidence")?,
+        severity: row.try_get("severity")?,
+        sensor_snapshot: start
+            .zip(end)
+            .map(|(start_ms, end_ms)| SensorSnapshot { start_ms, end_ms }),
+        acknowledged: row.try_get("acknowledged")?,
+    })
+}
+
+/// Insert into a sensor log kept in timestamp order, after any record from
+/// the same millisecond, so the log reads back like SQLite's
+/// `ORDER BY timestamp_ms, rowid`; in-order records are simply appended
+fn log_sensor(log: &mut VecDeque<SensorRecord>, record: SensorRecord) {
+    if log.back().is_none_or(|last| last.timestamp_ms <= record.timestamp_ms) {
+        log.push_back(record);
+    } else {
+        let at = log.partition_point(|r| r.timestamp_ms <= record.timestamp_ms);
+        log.insert(at, record);
+    }
+}
+
+/// Contents written by [`Repository::save_snapshot`]
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    sensors: VecDeque<SensorRecord>,
+    predictions: Vec<PredictionRecord>,
+}
+
+/// Repository for data access
+///
+/// Opened with [`Repository::with_sqlite`], sensor records, predictions,
+/// trips, events, calibrations and the outbox are persisted to SQLite;
+/// [`Repository::new`] keeps them in memory, for tests and diskless setups.
+/// On SQLite the trip in progress is the open row in `trips`, restored when
+/// the database is reopened.
 pub struct Repository {
-    /// Sensor records (in-memory)
+    /// SQLite pool, if opened on disk
+    db: Option<SqlitePool>,
+    /// Sensor records (in-memory), by timestamp then insertion
     sensor_log: Mutex<VecDeque<SensorRecord>>,
     /// Prediction records (in-memory)
     predictions: Mutex<Vec<PredictionRecord>>,
@@ -41,55 +491,169 @@ pub struct Repository {
     max_prediction_records: usize,
     /// Next prediction ID
     next_prediction_id: Mutex<i64>,
+    /// Trip records (in-memory)
+    trips: Mutex<Vec<TripRecord>>,
+    /// Trip currently in progress, cached from `trips` on SQLite
+    active_trip: Mutex<Option<i64>>,
+    /// Outbox messages awaiting upload (in-memory)
+    outbox: Mutex<Vec<OutboxMessage>>,
+    /// Next outbox message ID
+    next_outbox_id: Mutex<i64>,
+    /// Fused event records (in-memory)
+    events: Mutex<VecDeque<EventRecord>>,
+    /// Max event records
+    max_event_records: usize,
+    /// Next event ID
+    next_event_id: Mutex<i64>,
+    /// Calibrations by (vehicle ID, kind)
+    calibrations: Mutex<HashMap<(String, String), CalibrationRecord>>,
+    /// Reserved message sequence numbers by vehicle ID
+    sequences: Mutex<HashMap<String, u64>>,
 }
 
 impl Repository {
     /// Create a new in-memory repository
     pub fn new() -> Self {
         info!("Creating in-memory repository");
+        Self::with_pool(None)
+    }
+
+    fn with_pool(db: Option<SqlitePool>) -> Self {
         Self {
+            db,
             sensor_log: Mutex::new(VecDeque::with_capacity(10000)),
             predictions: Mutex::new(Vec::with_capacity(1000)),
             max_sensor_records: 100_000, // ~5.5 hours at 5Hz
             max_prediction_records: 10_000,
             next_prediction_id: Mutex::new(1),
+            trips: Mutex::new(Vec::new()),
+            active_trip: Mutex::new(None),
+            outbox: Mutex::new(Vec::new()),
+            next_outbox_id: Mutex::new(1),
+            events: Mutex::new(VecDeque::new()),
+            max_event_records: 10_000,
+            next_event_id: Mutex::new(1),
+            calibrations: Mutex::new(HashMap::new()),
+            sequences: Mutex::new(HashMap::new()),
         }
     }
 
-    /// Create a new repository with SQLite (placeholder)
-    pub async fn with_sqlite(_db_path: &str) -> Result<Self, StorageError> {
-        // In real implementation, we would use sqlx here:
-        // let pool = SqlitePool::connect(db_path).await?;
-        // Run migrations, setup WAL mode, etc.
-        
-        Ok(Self::new())
+    /// Open the SQLite database at `db_path`, creating it if missing
+    ///
+    /// The schema is migrated on connect. The database runs in WAL mode so
+    /// API reads do not stall the pipeline's inserts. The newest trip left
+    /// open by the previous run stays in progress; any older open trip is
+    /// closed at its last tagged sensor record.
+    pub async fn with_sqlite(db_path: &str) -> Result<Self, StorageError> {
+        let options = SqliteConnectOptions::new()
+            .filename(db_path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await
+            .map_err(|e| StorageError::Connection(format!("{db_path}: {e}")))?;
+
+        let mut conn = pool.acquire().await?;
+        schema::migrate(&mut conn).await?;
+        drop(conn);
+
+        let closed = sqlx::query(
+            "UPDATE trips SET end_ms = MAX(start_ms, COALESCE(
+                    (SELECT MAX(timestamp_ms) FROM sensor_log WHERE trip_id = trips.id), start_ms))
+                WHERE end_ms IS NULL AND id < (SELECT MAX(id) FROM trips WHERE end_ms IS NULL)",
+        )
+        .execute(&pool)
+        .await?
+        .rows_affected();
+        if closed > 0 {
+            warn!("Closed {} dangling trips", closed);
+        }
+        let active_trip: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM trips WHERE end_ms IS NULL ORDER BY id DESC LIMIT 1")
+                .fetch_optional(&pool)
+                .await?;
+        if let Some(id) = active_trip {
+            info!("Resuming trip {}", id);
+        }
+
+        info!("Opened SQLite repository at {}", db_path);
+        let repo = Self::with_pool(Some(pool));
+        *repo.active_trip.lock()? = active_trip;
+        Ok(repo)
     }
 
     /// Insert a sensor record
-    pub fn insert_sensor(&self, record: SensorRecord) -> Result<(), StorageError> {
-        let mut log = self.sensor_log.lock().map_err(|e| {
-            StorageError::DatabaseError(format!("Lock error: {}", e))
-        })?;
+    pub async fn insert_sensor(&self, mut record: SensorRecord) -> Result<(), StorageError> {
+        if record.trip_id.is_none() {
+            record.trip_id = *self.active_trip.lock()?;
+        }
+
+        if let Some(db) = &self.db {
+            insert_sensor_query(&record).execute(db).await?;
+            return Ok(());
+        }
+
+        let mut log = self.sensor_log.lock()?;
 
         // Enforce retention
         while log.len() >= self.max_sensor_records {
             log.pop_front();
         }
 
-        log.push_back(record);
+        log_sensor(&mut log, record);
+        Ok(())
+    }
+
+    /// Insert several sensor records at once; either all of them are
+    /// stored or, on error, none
+    ///
+    /// On SQLite the rows share one transaction rather than committing
+    /// each. In memory the log is locked once and trimmed after the batch.
+    pub async fn insert_sensors_batch(&self, records: &[SensorRecord]) -> Result<(), StorageError> {
+        let active_trip = *self.active_trip.lock()?;
+        let tagged = records.iter().map(|record| SensorRecord {
+            trip_id: record.trip_id.or(active_trip),
+            ..record.clone()
+        });
+
+        if let Some(db) = &self.db {
+            let mut tx = db.begin().await?;
+            for record in tagged {
+                insert_sensor_query(&record).execute(&mut *tx).await?;
+            }
+            tx.commit().await?;
+            debug!("Inserted batch of {} sensor records", records.len());
+            return Ok(());
+        }
+
+        let mut log = self.sensor_log.lock()?;
+        for record in tagged {
+            log_sensor(&mut log, record);
+        }
+
+        // Enforce retention
+        let excess = log.len().saturating_sub(self.max_sensor_records);
+        log.drain(..excess);
         Ok(())
     }
 
     /// Insert a prediction record
-    pub fn insert_prediction(&self, mut record: PredictionRecord) -> Result<i64, StorageError> {
-        let mut predictions = self.predictions.lock().map_err(|e| {
-            StorageError::DatabaseError(format!("Lock error: {}", e))
-        })?;
+    pub async fn insert_prediction(&self, mut record: PredictionRecord) -> Result<i64, StorageError> {
+        prepare_prediction(&mut record)?;
+
+        if let Some(db) = &self.db {
+            let id = insert_prediction_query(&record).execute(db).await?.last_insert_rowid();
+            debug!("Inserted prediction with ID {}", id);
+            return Ok(id);
+        }
+
+        let mut predictions = self.predictions.lock()?;
 
         // Get next ID
-        let mut id = self.next_prediction_id.lock().map_err(|e| {
-            StorageError::DatabaseError(format!("Lock error: {}", e))
-        })?;
+        let mut id = self.next_prediction_id.lock()?;
         
         record.id = *id;
         *id += 1;
@@ -107,131 +671,2288 @@ impl Repository {
     }
 
     /// Get recent sensor records
-    pub fn get_sensors(&self, limit: usize) -> Result<Vec<SensorRecord>, StorageError> {
-        let log = self.sensor_log.lock().map_err(|e| {
-            StorageError::DatabaseError(format!("Lock error: {}", e))
-        })?;
+    pub async fn get_sensors(&self, limit: usize) -> Result<Vec<SensorRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(&format!(
+                "SELECT {SENSOR_COLUMNS} FROM sensor_log ORDER BY timestamp_ms DESC, rowid DESC LIMIT ?"
+            ))
+            .bind(limit as i64)
+            .fetch_all(db)
+            .await?;
+            return Ok(rows.iter().map(sensor_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let log = self.sensor_log.lock()?;
 
         Ok(log.iter().rev().take(limit).cloned().collect())
     }
 
+    /// A page of sensor records, newest first, strictly older than
+    /// `before_timestamp_ms` (from the newest when `None`)
+    ///
+    /// The next cursor is the oldest timestamp on the page, so a page never
+    /// ends partway through a millisecond: records sharing the boundary
+    /// timestamp are all included, even past `limit`. Records inserted after
+    /// the first page never shift later ones.
+    pub async fn get_sensors_paged(
+        &self,
+        before_timestamp_ms: Option<i64>,
+        limit: usize,
+    ) -> Result<Page<SensorRecord>, StorageError> {
+        if limit == 0 {
+            return Ok(Page { items: Vec::new(), next_cursor: None });
+        }
+        let before = before_timestamp_ms.unwrap_or(i64::MAX);
+
+        let (items, more) = if let Some(db) = &self.db {
+            let rows = sqlx::query(&format!(
+                "SELECT {SENSOR_COLUMNS} FROM sensor_log WHERE timestamp_ms < ? AND timestamp_ms >= COALESCE(
+                        (SELECT timestamp_ms FROM sensor_log WHERE timestamp_ms < ?
+                            ORDER BY timestamp_ms DESC LIMIT 1 OFFSET ?), ?)
+                    ORDER BY timestamp_ms DESC, rowid DESC"
+            ))
+            .bind(before)
+            .bind(before)
+            .bind(limit as i64 - 1)
+            .bind(i64::MIN)
+            .fetch_all(db)
+            .await?;
+            let items: Vec<SensorRecord> = rows.iter().map(sensor_from_row).collect::<Result<_, _>>()?;
+            let more = match items.last() {
+                Some(oldest) => {
+                    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM sensor_log WHERE timestamp_ms < ?)")
+                        .bind(oldest.timestamp_ms)
+                        .fetch_one(db)
+                        .await?
+                }
+                None => false,
+            };
+            (items, more)
+        } else {
+            let log = self.sensor_log.lock()?;
+            // The log is in timestamp order, so the cursor and the start of
+            // the oldest millisecond on the page are found by bisection
+            let end = log.partition_point(|r| r.timestamp_ms < before);
+            let start = match end.checked_sub(limit) {
+                Some(start) => {
+                    let oldest = log[start].timestamp_ms;
+                    log.partition_point(|r| r.timestamp_ms < oldest)
+                }
+                None => 0,
+            };
+            (log.range(start..end).rev().cloned().collect(), start > 0)
+        };
+
+        let next_cursor = if more { items.last().map(|r| r.timestamp_ms) } else { None };
+        Ok(Page { items, next_cursor })
+    }
+
     /// Get sensor records since a timestamp
-    pub fn get_sensors_since(&self, since_ms: i64) -> Result<Vec<SensorRecord>, StorageError> {
-        let log = self.sensor_log.lock().map_err(|e| {
-            StorageError::DatabaseError(format!("Lock error: {}", e))
-        })?;
+    pub async fn get_sensors_since(&self, since_ms: i64) -> Result<Vec<SensorRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            return self.sensors_between(db, since_ms, i64::MAX).await;
+        }
 
-        Ok(log.iter().filter(|r| r.timestamp_ms >= since_ms).cloned().collect())
+        let log = self.sensor_log.lock()?;
+
+        Ok(log
+            .iter()
+            .filter(|r| r.timestamp_ms >= since_ms)
+            .cloned()
+            .collect())
     }
 
-    /// Get predictions with optional filters
-    pub fn get_predictions(
+    /// Sensor records with timestamps in `start_ms..=end_ms`, oldest first
+    async fn sensors_between(
         &self,
-        severity: Option<&str>,
-        limit: usize,
-    ) -> Result<Vec<PredictionRecord>, StorageError> {
-        let predictions = self.predictions.lock().map_err(|e| {
-            StorageError::DatabaseError(format!("Lock error: {}", e))
-        })?;
+        db: &SqlitePool,
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<SensorRecord>, StorageError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {SENSOR_COLUMNS} FROM sensor_log WHERE timestamp_ms BETWEEN ? AND ?
+                ORDER BY timestamp_ms, rowid"
+        ))
+        .bind(start_ms)
+        .bind(end_ms)
+        .fetch_all(db)
+        .await?;
+        Ok(rows.iter().map(sensor_from_row).collect::<Result<_, _>>()?)
+    }
+
+    /// Statistics for rpm, speed, coolant temperature and engine load over
+    /// the records at or after `since_ms`
+    pub async fn sensor_aggregates(&self, since_ms: i64) -> Result<SensorAggregates, StorageError> {
+        self.aggregate_sensors("timestamp_ms >= ?", since_ms, |r| r.timestamp_ms >= since_ms).await
+    }
+
+    /// Statistics for rpm, speed, coolant temperature and engine load over
+    /// the records tagged with a trip
+    pub async fn trip_sensor_aggregates(&self, trip_id: i64) -> Result<SensorAggregates, StorageError> {
+        self.aggregate_sensors("trip_id = ?", trip_id, |r| r.trip_id == Some(trip_id)).await
+    }
+
+    /// Aggregate the records matching `condition` (SQL, one `?` bound to
+    /// `value`) or, in memory, `matches`
+    async fn aggregate_sensors(
+        &self,
+        condition: &str,
+        value: i64,
+        matches: impl Fn(&SensorRecord) -> bool,
+    ) -> Result<SensorAggregates, StorageError> {
+        if let Some(db) = &self.db {
+            let row = sqlx::query(&format!(
+                "SELECT COUNT(*) AS count,
+                    MIN(rpm) AS rpm_min, MAX(rpm) AS rpm_max, AVG(rpm) AS rpm_mean,
+                    MIN(speed) AS speed_min, MAX(speed) AS speed_max, AVG(speed) AS speed_mean,
+                    MIN(coolant_temp) AS coolant_temp_min, MAX(coolant_temp) AS coolant_temp_max,
+                    AVG(coolant_temp) AS coolant_temp_mean,
+                    MIN(engine_load) AS engine_load_min, MAX(engine_load) AS engine_load_max,
+                    AVG(engine_load) AS engine_load_mean
+                FROM sensor_log WHERE {condition}"
+            ))
+            .bind(value)
+            .fetch_one(db)
+            .await?;
+
+            // MIN/MAX/AVG are NULL over an empty window
+            let stats = |field: &str| -> Result<FieldStats, sqlx::Error> {
+                Ok(FieldStats {
+                    min: row.try_get::<Option<i32>, _>(format!("{field}_min").as_str())?.unwrap_or(0),
+                    max: row.try_get::<Option<i32>, _>(format!("{field}_max").as_str())?.unwrap_or(0),
+                    mean: row.try_get::<Option<f64>, _>(format!("{field}_mean").as_str())?.unwrap_or(0.0),
+                })
+            };
+            return Ok(SensorAggregates {
+                count: row.try_get::<i64, _>("count")? as usize,
+                rpm: stats("rpm")?,
+                speed: stats("speed")?,
+                coolant_temp: stats("coolant_temp")?,
+                engine_load: stats("engine_load")?,
+            });
+        }
+
+        let log = self.sensor_log.lock()?;
+        let mut count = 0;
+        let mut fields: [FieldAccumulator; 4] = Default::default();
+        for record in log.iter().filter(|r| matches(r)) {
+            let values = [record.rpm, record.speed, record.coolant_temp, record.engine_load];
+            for (field, value) in fields.iter_mut().zip(values) {
+                field.add(value, count == 0);
+            }
+            count += 1;
+        }
+        if count == 0 {
+            return Ok(SensorAggregates::default());
+        }
+
+        let [rpm, speed, coolant_temp, engine_load] = fields.map(|f| f.finish(count));
+        Ok(SensorAggregates {
+            count,
+            rpm,
+            speed,
+            coolant_temp,
+            engine_load,
+        })
+    }
+
+    /// Get the predictions matching `filter`, newest first
+    pub async fn get_predictions(&self, filter: &PredictionFilter) -> Result<Vec<PredictionRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(&format!(
+                "SELECT {PREDICTION_COLUMNS} FROM predictions
+                    WHERE (?1 IS NULL OR severity = ?1) AND (?2 IS NULL OR fault_class = ?2)
+                        AND NOT (?3 AND acknowledged)
+                    ORDER BY id DESC LIMIT ?4"
+            ))
+            .bind(filter.severity())
+            .bind(filter.fault_class())
+            .bind(filter.unacknowledged_only)
+            .bind(filter.limit as i64)
+            .fetch_all(db)
+            .await?;
+            return Ok(rows.iter().map(prediction_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let predictions = self.predictions.lock()?;
 
         let filtered: Vec<_> = predictions
             .iter()
             .rev()
-            .filter(|p| severity.map_or(true, |s| p.severity == s))
-            .take(limit)
+            .filter(|p| filter.matches(p))
+            .take(filter.limit)
             .cloned()
             .collect();
 
         Ok(filtered)
     }
 
-    /// Get total sensor count
-    pub fn sensor_count(&self) -> usize {
-        self.sensor_log.lock().map(|l| l.len()).unwrap_or(0)
-    }
+    /// Number of predictions at or after `since_ms` per severity string;
+    /// severities with no predictions are absent
+    pub async fn prediction_counts_by_severity(
+        &self,
+        since_ms: i64,
+    ) -> Result<HashMap<String, usize>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows: Vec<(String, i64)> = sqlx::query_as(
+                "SELECT severity, COUNT(*) FROM predictions WHERE timestamp_ms >= ? GROUP BY severity",
+            )
+            .bind(since_ms)
+            .fetch_all(db)
+            .await?;
+            return Ok(rows.into_iter().map(|(severity, count)| (severity, count as usize)).collect());
+        }
 
-    /// Get total prediction count
-    pub fn prediction_count(&self) -> usize {
-        self.predictions.lock().map(|p| p.len()).unwrap_or(0)
+        let predictions = self.predictions.lock()?;
+        let mut counts = HashMap::new();
+        for prediction in predictions.iter().filter(|p| p.timestamp_ms >= since_ms) {
+            *counts.entry(prediction.severity.clone()).or_insert(0) += 1;
+        }
+        Ok(counts)
     }
 
-    /// Clear all data (for testing)
-    pub fn clear(&self) {
-        if let Ok(mut log) = self.sensor_log.lock() {
-            log.clear();
+    /// Mark a prediction as acknowledged
+    pub async fn acknowledge_prediction(&self, id: i64) -> Result<(), StorageError> {
+        if let Some(db) = &self.db {
+            let result = sqlx::query("UPDATE predictions SET acknowledged = 1 WHERE id = ?")
+                .bind(id)
+                .execute(db)
+                .await?;
+            if result.rows_affected() == 0 {
+                return Err(StorageError::NotFound);
+            }
+            return Ok(());
         }
-        if let Ok(mut preds) = self.predictions.lock() {
-            preds.clear();
+
+        let mut predictions = self.predictions.lock()?;
+        let prediction = predictions
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or(StorageError::NotFound)?;
+        prediction.acknowledged = true;
+        Ok(())
+    }
+
+    /// A page of predictions, newest first, with IDs below `before_id`
+    /// (from the newest when `None`)
+    pub async fn get_predictions_paged(
+        &self,
+        before_id: Option<i64>,
+        limit: usize,
+    ) -> Result<Page<PredictionRecord>, StorageError> {
+        let before = before_id.unwrap_or(i64::MAX);
+
+        let records = if let Some(db) = &self.db {
+            let rows = sqlx::query(&format!(
+                "SELECT {PREDICTION_COLUMNS} FROM predictions WHERE id < ? ORDER BY id DESC LIMIT ?"
+            ))
+            .bind(before)
+            .bind(limit as i64 + 1)
+            .fetch_all(db)
+            .await?;
+            rows.iter().map(prediction_from_row).collect::<Result<_, _>>()?
+        } else {
+            let predictions = self.predictions.lock()?;
+            predictions
+                .iter()
+                .rev()
+                .filter(|p| p.id < before)
+                .take(limit + 1)
+                .cloned()
+                .collect()
+        };
+
+        Ok(Page::from_overfetch(records, limit, |p| p.id))
+    }
+
+    /// Predictions with timestamps in `start_ms..=end_ms`, optionally of one
+    /// severity, oldest first for replay
+    ///
+    /// On SQLite the range is served by `idx_predictions_timestamp`, which
+    /// also yields the rows in timestamp order; ties fall back to insert order.
+    pub async fn get_predictions_between(
+        &self,
+        start_ms: i64,
+        end_ms: i64,
+        severity: Option<&str>,
+    ) -> Result<Vec<PredictionRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(&format!(
+                "SELECT {PREDICTION_COLUMNS} FROM predictions
+                    WHERE timestamp_ms BETWEEN ?1 AND ?2 AND (?3 IS NULL OR severity = ?3)
+                    ORDER BY timestamp_ms, id"
+            ))
+            .bind(start_ms)
+            .bind(end_ms)
+            .bind(severity)
+            .fetch_all(db)
+            .await?;
+            return Ok(rows.iter().map(prediction_from_row).collect::<Result<_, _>>()?);
         }
+
+        let predictions = self.predictions.lock()?;
+        let mut matching: Vec<PredictionRecord> = predictions
+            .iter()
+            .filter(|p| (start_ms..=end_ms).contains(&p.timestamp_ms))
+            .filter(|p| severity.is_none_or(|s| p.severity == s))
+            .cloned()
+            .collect();
+        matching.sort_by_key(|p| p.timestamp_ms);
+        Ok(matching)
     }
-}
 
-impl Default for Repository {
-    fn default() -> Self {
-        Self::new()
+    /// Get a single prediction
+    pub async fn get_prediction(&self, id: i64) -> Result<PredictionRecord, StorageError> {
+        if let Some(db) = &self.db {
+            let row = sqlx::query(&format!("SELECT {PREDICTION_COLUMNS} FROM predictions WHERE id = ?"))
+                .bind(id)
+                .fetch_optional(db)
+                .await?
+                .ok_or(StorageError::NotFound)?;
+            return Ok(prediction_from_row(&row)?);
+        }
+
+        let predictions = self.predictions.lock()?;
+        predictions
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or(StorageError::NotFound)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Get a prediction together with the sensor records in its feature window
+    pub async fn get_prediction_with_context(
+        &self,
+        id: i64,
+    ) -> Result<(PredictionRecord, Vec<SensorRecord>), StorageError> {
+        let prediction = self.get_prediction(id).await?;
 
-    #[test]
-    fn test_sensor_insert_and_retrieve() {
-        let repo = Repository::new();
-        
-        let record = SensorRecord {
-            timestamp_ms: 1234567890,
-            rpm: 3000,
-            speed: 60,
-            coolant_temp: 85,
-            engine_load: 50,
-            maf: 12.5,
-            fuel_trim_short: 2.0,
-            fuel_trim_long: 1.5,
-        };
-        
-        repo.insert_sensor(record.clone()).unwrap();
-        
-        let sensors = repo.get_sensors(10).unwrap();
-        assert_eq!(sensors.len(), 1);
-        assert_eq!(sensors[0].rpm, 3000);
+        let window = prediction.sensor_snapshot.unwrap_or_else(|| {
+            SensorSnapshot::ending_at(prediction.timestamp_ms, DEFAULT_SNAPSHOT_WINDOW_MS)
+        });
+
+        if let Some(db) = &self.db {
+            let sensors = self.sensors_between(db, window.start_ms, window.end_ms).await?;
+            return Ok((prediction, sensors));
+        }
+
+        let log = self.sensor_log.lock()?;
+        let sensors = log
+            .iter()
+            .filter(|r| window.contains(r.timestamp_ms))
+            .cloned()
+            .collect();
+
+        Ok((prediction, sensors))
     }
 
-    #[test]
-    fn test_prediction_insert() {
-        let repo = Repository::new();
-        
-        let record = PredictionRecord {
-            id: 0,
-            timestamp_ms: 1234567890,
-            fault_class: "overheating".to_string(),
-            confidence: 0.85,
-            severity: "high".to_string(),
-        };
-        
-        let id = repo.insert_prediction(record).unwrap();
-        assert_eq!(id, 1);
-        
-        let preds = repo.get_predictions(None, 10).unwrap();
-        assert_eq!(preds.len(), 1);
-        assert_eq!(preds[0].fault_class, "overheating");
+    /// Write the sensor records at or after `since_ms` to `writer` as CSV,
+    /// returning how many rows were written
+    ///
+    /// Records are copied out in chunks, so the export never holds the
+    /// whole log in memory, and the in-memory log is unlocked while a chunk
+    /// is written.
+    pub async fn export_sensors_csv(&self, mut writer: impl Write, since_ms: i64) -> Result<usize, StorageError> {
+        export::write_sensor_header(&mut writer)?;
+        let mut rows = 0;
+
+        if let Some(db) = &self.db {
+            let mut after_rowid = 0;
+            loop {
+                let chunk = sqlx::query(&format!(
+                    "SELECT rowid, {SENSOR_COLUMNS} FROM sensor_log WHERE timestamp_ms >= ? AND rowid > ?
+                        ORDER BY rowid LIMIT ?"
+                ))
+                .bind(since_ms)
+                .bind(after_rowid)
+                .bind(export::EXPORT_CHUNK)
+                .fetch_all(db)
+                .await?;
+                let Some(last) = chunk.last() else {
+                    break;
+                };
+                after_rowid = last.try_get("rowid")?;
+                for row in &chunk {
+                    export::write_sensor(&mut writer, &sensor_from_row(row)?)?;
+                }
+                rows += chunk.len();
+            }
+        } else {
+            // Last timestamp written and how many records sharing it were;
+            // positions in the log shift with inserts and retention
+            let mut after: Option<(i64, usize)> = None;
+            loop {
+                let chunk: Vec<SensorRecord> = {
+                    let log = self.sensor_log.lock()?;
+                    let start = match after {
+                        Some((timestamp_ms, written)) => {
+                            log.partition_point(|r| r.timestamp_ms < timestamp_ms) + written
+                        }
+                        None => log.partition_point(|r| r.timestamp_ms < since_ms),
+                    };
+                    log.range(start.min(log.len())..)
+                        .take(export::EXPORT_CHUNK as usize)
+                        .cloned()
+                        .collect()
+                };
+                let Some(last) = chunk.last() else {
+                    break;
+                };
+                let last_ms = last.timestamp_ms;
+                let sharing = chunk.iter().rev().take_while(|r| r.timestamp_ms == last_ms).count();
+                after = Some(match after {
+                    Some((timestamp_ms, written)) if timestamp_ms == last_ms => (last_ms, written + sharing),
+                    _ => (last_ms, sharing),
+                });
+                for record in &chunk {
+                    export::write_sensor(&mut writer, record)?;
+                }
+                rows += chunk.len();
+            }
+        }
+
+        writer.flush()?;
+        Ok(rows)
     }
 
-    #[test]
-    fn test_retention_limit() {
-        let mut repo = Repository::new();
-        repo.max_sensor_records = 5;
-        
-        for i in 0..10 {
-            repo.insert_sensor(SensorRecord {
-                timestamp_ms: i,
-                rpm: i as i32 * 100,
-                ..Default::default()
-            }).unwrap();
+    /// Write the predictions at or after `since_ms` to `writer` as CSV,
+    /// returning how many rows were written
+    ///
+    /// Chunked like [`Repository::export_sensors_csv`].
+    pub async fn export_predictions_csv(&self, mut writer: impl Write, since_ms: i64) -> Result<usize, StorageError> {
+        export::write_prediction_header(&mut writer)?;
+        let mut rows = 0;
+
+        if let Some(db) = &self.db {
+            let mut after_id = 0;
+            loop {
+                let chunk = sqlx::query(&format!(
+                    "SELECT {PREDICTION_COLUMNS} FROM predictions WHERE timestamp_ms >= ? AND id > ?
+                        ORDER BY id LIMIT ?"
+                ))
+                .bind(since_ms)
+                .bind(after_id)
+                .bind(export::EXPORT_CHUNK)
+                .fetch_all(db)
+                .await?;
+                let Some(last) = chunk.last() else {
+                    break;
+                };
+                after_id = last.try_get("id")?;
+                for row in &chunk {
+                    export::write_prediction(&mut writer, &prediction_from_row(row)?)?;
+                }
+                rows += chunk.len();
+            }
+        } else {
+            let mut after_id = 0;
+            loop {
+                let chunk: Vec<PredictionRecord> = {
+                    let predictions = self.predictions.lock()?;
+                    // IDs increase along the list
+                    let start = predictions.partition_point(|p| p.id <= after_id);
+                    predictions[start..]
+                        .iter()
+                        .filter(|p| p.timestamp_ms >= since_ms)
+                        .take(export::EXPORT_CHUNK as usize)
+                        .cloned()
+                        .collect()
+                };
+                let Some(last) = chunk.last() else {
+                    break;
+                };
+                after_id = last.id;
+                for record in &chunk {
+                    export::write_prediction(&mut writer, record)?;
+                }
+                rows += chunk.len();
+            }
         }
-        
-        assert_eq!(repo.sensor_count(), 5);
+
+        writer.flush()?;
+        Ok(rows)
+    }
+
+    /// Delete sensor records older than `before_ms`, returning how many
+    pub async fn prune_sensors(&self, before_ms: i64) -> Result<usize, StorageError> {
+        if let Some(db) = &self.db {
+            let result = sqlx::query("DELETE FROM sensor_log WHERE timestamp_ms < ?")
+                .bind(before_ms)
+                .execute(db)
+                .await?;
+            return Ok(result.rows_affected() as usize);
+        }
+
+        let mut log = self.sensor_log.lock()?;
+        let before = log.len();
+        log.retain(|r| r.timestamp_ms >= before_ms);
+        Ok(before - log.len())
+    }
+
+    /// Delete predictions older than `before_ms`, returning how many
+    pub async fn prune_predictions(&self, before_ms: i64) -> Result<usize, StorageError> {
+        if let Some(db) = &self.db {
+            let result = sqlx::query("DELETE FROM predictions WHERE timestamp_ms < ?")
+                .bind(before_ms)
+                .execute(db)
+                .await?;
+            return Ok(result.rows_affected() as usize);
+        }
+
+        let mut predictions = self.predictions.lock()?;
+        let before = predictions.len();
+        predictions.retain(|p| p.timestamp_ms >= before_ms);
+        Ok(before - predictions.len())
+    }
+
+    /// Run `f` with a transactional handle: its writes are committed if it
+    /// returns `Ok` and rolled back if it returns `Err`
+    ///
+    /// Returns [`StorageError::DatabaseError`] if the commit itself fails.
+    /// The in-memory backend has nothing to roll back, so there this is a
+    /// no-op wrapper and writes made before an `Err` are kept.
+    pub async fn transaction<F, T>(&self, f: F) -> Result<T, StorageError>
+    where
+        F: AsyncFnOnce(&mut StorageTransaction<'_>) -> Result<T, StorageError>,
+    {
+        let tx = match &self.db {
+            Some(db) => Some(db.begin().await?),
+            None => None,
+        };
+        let mut handle = StorageTransaction { repository: self, tx };
+
+        let result = f(&mut handle).await;
+        let Some(tx) = handle.tx else {
+            return result;
+        };
+        match result {
+            Ok(value) => {
+                tx.commit()
+                    .await
+                    .map_err(|e| StorageError::DatabaseError(format!("commit failed: {e}")))?;
+                Ok(value)
+            }
+            Err(e) => {
+                if let Err(rollback) = tx.rollback().await {
+                    warn!("Rollback failed: {}", rollback);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete the oldest sensor records and predictions beyond the retention
+    /// limits, returning how many rows went
+    ///
+    /// SQLite inserts do not trim as they go; this runs periodically from
+    /// [`Repository::spawn_retention`]. In memory the limits are enforced on
+    /// insert and this does nothing.
+    pub async fn enforce_retention(&self) -> Result<usize, StorageError> {
+        let Some(db) = &self.db else {
+            return Ok(0);
+        };
+
+        let sensors = sqlx::query(
+            "DELETE FROM sensor_log WHERE rowid IN (
+                SELECT rowid FROM sensor_log ORDER BY timestamp_ms DESC, rowid DESC LIMIT -1 OFFSET ?)",
+        )
+        .bind(self.max_sensor_records as i64)
+        .execute(db)
+        .await?
+        .rows_affected();
+        let predictions = sqlx::query(
+            "DELETE FROM predictions WHERE id IN (
+                SELECT id FROM predictions ORDER BY id DESC LIMIT -1 OFFSET ?)",
+        )
+        .bind(self.max_prediction_records as i64)
+        .execute(db)
+        .await?
+        .rows_affected();
+        let events = sqlx::query(
+            "DELETE FROM events WHERE id IN (
+                SELECT id FROM events ORDER BY id DESC LIMIT -1 OFFSET ?)",
+        )
+        .bind(self.max_event_records as i64)
+        .execute(db)
+        .await?
+        .rows_affected();
+
+        if sensors + predictions + events > 0 {
+            debug!(
+                "Retention removed {} sensor records, {} predictions and {} events",
+                sensors, predictions, events
+            );
+        }
+        Ok((sensors + predictions + events) as usize)
+    }
+
+    /// Run [`Repository::enforce_retention`] every `interval` until the
+    /// repository is dropped everywhere else
+    pub fn spawn_retention(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let repository = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(repository) = repository.upgrade() else {
+                    break;
+                };
+                if let Err(e) = repository.enforce_retention().await {
+                    warn!("Retention pass failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Insert a fused event, returning its ID
+    pub async fn insert_event(&self, mut record: EventRecord) -> Result<i64, StorageError> {
+        if record.trip_id.is_none() {
+            record.trip_id = *self.active_trip.lock()?;
+        }
+
+        if let Some(db) = &self.db {
+            let id = sqlx::query(
+                "INSERT INTO events (timestamp_ms, kind, severity, payload, trip_id) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(record.timestamp_ms)
+            .bind(&record.kind)
+            .bind(&record.severity)
+            .bind(&record.payload)
+            .bind(record.trip_id)
+            .execute(db)
+            .await?
+            .last_insert_rowid();
+            debug!("Inserted event with ID {}", id);
+            return Ok(id);
+        }
+
+        let mut events = self.events.lock()?;
+        let mut id = self.next_event_id.lock()?;
+
+        record.id = *id;
+        *id += 1;
+
+        // Enforce retention
+        while events.len() >= self.max_event_records {
+            events.pop_front();
+        }
+
+        let returned_id = record.id;
+        events.push_back(record);
+        debug!("Inserted event with ID {}", returned_id);
+
+        Ok(returned_id)
+    }
+
+    /// Get recent events, newest first
+    pub async fn get_events(&self, limit: usize) -> Result<Vec<EventRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(&format!("SELECT {EVENT_COLUMNS} FROM events ORDER BY id DESC LIMIT ?"))
+                .bind(limit as i64)
+                .fetch_all(db)
+                .await?;
+            return Ok(rows.iter().map(event_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let events = self.events.lock()?;
+
+        Ok(events.iter().rev().take(limit).cloned().collect())
+    }
+
+    /// Delete events older than `before_ms`, returning how many
+    pub async fn prune_events(&self, before_ms: i64) -> Result<usize, StorageError> {
+        if let Some(db) = &self.db {
+            let result = sqlx::query("DELETE FROM events WHERE timestamp_ms < ?")
+                .bind(before_ms)
+                .execute(db)
+                .await?;
+            return Ok(result.rows_affected() as usize);
+        }
+
+        let mut events = self.events.lock()?;
+        let before = events.len();
+        events.retain(|e| e.timestamp_ms >= before_ms);
+        Ok(before - events.len())
+    }
+
+    /// Insert or replace the calibration for the record's vehicle and kind
+    pub async fn save_calibration(&self, record: CalibrationRecord) -> Result<(), StorageError> {
+        if let Some(db) = &self.db {
+            sqlx::query(
+                "INSERT OR REPLACE INTO calibrations (vehicle_id, kind, payload, updated_ms) \
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(&record.vehicle_id)
+            .bind(&record.kind)
+            .bind(&record.payload)
+            .bind(record.updated_ms)
+            .execute(db)
+            .await?;
+            debug!("Saved {} calibration for vehicle {}", record.kind, record.vehicle_id);
+            return Ok(());
+        }
+
+        let mut calibrations = self.calibrations.lock()?;
+        debug!("Saved {} calibration for vehicle {}", record.kind, record.vehicle_id);
+        calibrations.insert((record.vehicle_id.clone(), record.kind.clone()), record);
+        Ok(())
+    }
+
+    /// Calibration of one kind for a vehicle
+    pub async fn get_calibration(
+        &self,
+        vehicle_id: &str,
+        kind: &str,
+    ) -> Result<Option<CalibrationRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let row = sqlx::query(
+                "SELECT vehicle_id, kind, payload, updated_ms FROM calibrations \
+                 WHERE vehicle_id = ? AND kind = ?",
+            )
+            .bind(vehicle_id)
+            .bind(kind)
+            .fetch_optional(db)
+            .await?;
+            return Ok(row.as_ref().map(calibration_from_row).transpose()?);
+        }
+
+        let calibrations = self.calibrations.lock()?;
+        Ok(calibrations
+            .get(&(vehicle_id.to_string(), kind.to_string()))
+            .cloned())
+    }
+
+    /// All calibrations stored for a vehicle, by kind
+    pub async fn get_calibrations(&self, vehicle_id: &str) -> Result<Vec<CalibrationRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(
+                "SELECT vehicle_id, kind, payload, updated_ms FROM calibrations \
+                 WHERE vehicle_id = ? ORDER BY kind",
+            )
+            .bind(vehicle_id)
+            .fetch_all(db)
+            .await?;
+            return Ok(rows.iter().map(calibration_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let calibrations = self.calibrations.lock()?;
+        let mut records: Vec<CalibrationRecord> = calibrations
+            .values()
+            .filter(|c| c.vehicle_id == vehicle_id)
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| a.kind.cmp(&b.kind));
+        Ok(records)
+    }
+
+    /// Delete a vehicle's calibration of one kind, returning whether one
+    /// was saved
+    pub async fn delete_calibration(&self, vehicle_id: &str, kind: &str) -> Result<bool, StorageError> {
+        if let Some(db) = &self.db {
+            let result = sqlx::query("DELETE FROM calibrations WHERE vehicle_id = ? AND kind = ?")
+                .bind(vehicle_id)
+                .bind(kind)
+                .execute(db)
+                .await?;
+            return Ok(result.rows_affected() > 0);
+        }
+
+        let mut calibrations = self.calibrations.lock()?;
+        Ok(calibrations
+            .remove(&(vehicle_id.to_string(), kind.to_string()))
+            .is_some())
+    }
+
+    /// Highest message sequence number reserved for a vehicle, 0 if none
+    pub async fn reserved_sequence(&self, vehicle_id: &str) -> Result<u64, StorageError> {
+        if let Some(db) = &self.db {
+            let reserved: Option<i64> =
+                sqlx::query_scalar("SELECT reserved FROM sequence_reservations WHERE vehicle_id = ?")
+                    .bind(vehicle_id)
+                    .fetch_optional(db)
+                    .await?;
+            return Ok(reserved.unwrap_or(0) as u64);
+        }
+
+        Ok(self.sequences.lock()?.get(vehicle_id).copied().unwrap_or(0))
+    }
+
+    /// Record that sequence numbers up to `reserved` may be issued for a
+    /// vehicle
+    pub async fn reserve_sequence(&self, vehicle_id: &str, reserved: u64) -> Result<(), StorageError> {
+        if let Some(db) = &self.db {
+            sqlx::query("INSERT OR REPLACE INTO sequence_reservations (vehicle_id, reserved) VALUES (?, ?)")
+                .bind(vehicle_id)
+                .bind(reserved as i64)
+                .execute(db)
+                .await?;
+            return Ok(());
+        }
+
+        self.sequences.lock()?.insert(vehicle_id.to_string(), reserved);
+        Ok(())
+    }
+
+    /// Open a new trip; subsequent sensor records are tagged with its ID
+    pub async fn start_trip(&self, start_ms: i64) -> Result<i64, StorageError> {
+        if let Some(db) = &self.db {
+            let id = sqlx::query("INSERT INTO trips (start_ms) VALUES (?)")
+                .bind(start_ms)
+                .execute(db)
+                .await?
+                .last_insert_rowid();
+            *self.active_trip.lock()? = Some(id);
+            debug!("Started trip {}", id);
+            return Ok(id);
+        }
+
+        let mut trips = self.trips.lock()?;
+        let mut active = self.active_trip.lock()?;
+
+        let id = trips.last().map(|t| t.id + 1).unwrap_or(1);
+        trips.push(TripRecord {
+            id,
+            start_ms,
+            end_ms: None,
+            distance_km: 0.0,
+        });
+        *active = Some(id);
+        debug!("Started trip {}", id);
+
+        Ok(id)
+    }
+
+    /// Close a trip
+    pub async fn end_trip(&self, id: i64, end_ms: i64, distance_km: f64) -> Result<(), StorageError> {
+        let start_ms = self.get_trip(id).await?.start_ms;
+        if end_ms < start_ms {
+            return Err(StorageError::Constraint(format!(
+                "trip {} ends at {} before it starts at {}",
+                id, end_ms, start_ms
+            )));
+        }
+
+        if let Some(db) = &self.db {
+            sqlx::query("UPDATE trips SET end_ms = ?, distance_km = ? WHERE id = ?")
+                .bind(end_ms)
+                .bind(distance_km)
+                .bind(id)
+                .execute(db)
+                .await?;
+        } else {
+            let mut trips = self.trips.lock()?;
+            let trip = trips
+                .iter_mut()
+                .find(|t| t.id == id)
+                .ok_or(StorageError::NotFound)?;
+            trip.end_ms = Some(end_ms);
+            trip.distance_km = distance_km;
+        }
+
+        let mut active = self.active_trip.lock()?;
+        if *active == Some(id) {
+            *active = None;
+        }
+        debug!("Ended trip {}", id);
+
+        Ok(())
+    }
+
+    /// Get the trip currently in progress
+    pub fn active_trip_id(&self) -> Option<i64> {
+        self.active_trip.lock().ok().and_then(|a| *a)
+    }
+
+    /// Get recent trips, newest first
+    pub async fn get_trips(&self, limit: usize) -> Result<Vec<TripRecord>, StorageError> {
+        self.list_trips(0, limit).await
+    }
+
+    /// A page of trips, newest first, skipping the `offset` newest
+    pub async fn list_trips(&self, offset: usize, limit: usize) -> Result<Vec<TripRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(
+                "SELECT id, start_ms, end_ms, distance_km FROM trips ORDER BY id DESC LIMIT ? OFFSET ?",
+            )
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(db)
+            .await?;
+            return Ok(rows.iter().map(trip_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let trips = self.trips.lock()?;
+
+        Ok(trips.iter().rev().skip(offset).take(limit).cloned().collect())
+    }
+
+    /// Number of recorded trips, including one in progress
+    pub async fn trip_count(&self) -> Result<usize, StorageError> {
+        if let Some(db) = &self.db {
+            return count_rows(db, "trips").await;
+        }
+
+        Ok(self.trips.lock()?.len())
+    }
+
+    /// Get a single trip
+    pub async fn get_trip(&self, id: i64) -> Result<TripRecord, StorageError> {
+        if let Some(db) = &self.db {
+            let row = sqlx::query("SELECT id, start_ms, end_ms, distance_km FROM trips WHERE id = ?")
+                .bind(id)
+                .fetch_optional(db)
+                .await?
+                .ok_or(StorageError::NotFound)?;
+            return Ok(trip_from_row(&row)?);
+        }
+
+        let trips = self.trips.lock()?;
+
+        trips
+            .iter()
+            .find(|t| t.id == id)
+            .cloned()
+            .ok_or(StorageError::NotFound)
+    }
+
+    /// Get the sensor records tagged with a trip
+    pub async fn get_trip_sensors(&self, trip_id: i64) -> Result<Vec<SensorRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(&format!(
+                "SELECT {SENSOR_COLUMNS} FROM sensor_log WHERE trip_id = ? ORDER BY timestamp_ms, rowid"
+            ))
+            .bind(trip_id)
+            .fetch_all(db)
+            .await?;
+            return Ok(rows.iter().map(sensor_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let log = self.sensor_log.lock()?;
+
+        Ok(log
+            .iter()
+            .filter(|r| r.trip_id == Some(trip_id))
+            .cloned()
+            .collect())
+    }
+
+    /// Event counts by kind for each of `trip_ids`; trips without events
+    /// are left out
+    pub async fn trip_event_counts(
+        &self,
+        trip_ids: &[i64],
+    ) -> Result<HashMap<i64, HashMap<String, usize>>, StorageError> {
+        let mut counts: HashMap<i64, HashMap<String, usize>> = HashMap::new();
+        if trip_ids.is_empty() {
+            return Ok(counts);
+        }
+
+        if let Some(db) = &self.db {
+            let placeholders = vec!["?"; trip_ids.len()].join(", ");
+            let sql = format!(
+                "SELECT trip_id, kind, COUNT(*) FROM events WHERE trip_id IN ({placeholders}) GROUP BY trip_id, kind"
+            );
+            let mut query = sqlx::query_as::<_, (i64, String, i64)>(&sql);
+            for id in trip_ids {
+                query = query.bind(id);
+            }
+            for (trip_id, kind, count) in query.fetch_all(db).await? {
+                counts.entry(trip_id).or_default().insert(kind, count as usize);
+            }
+            return Ok(counts);
+        }
+
+        let events = self.events.lock()?;
+        for event in events.iter() {
+            let Some(trip_id) = event.trip_id.filter(|id| trip_ids.contains(id)) else {
+                continue;
+            };
+            *counts.entry(trip_id).or_default().entry(event.kind.clone()).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+
+    /// Get the events tagged with a trip, oldest first
+    pub async fn get_trip_events(&self, trip_id: i64) -> Result<Vec<EventRecord>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(&format!("SELECT {EVENT_COLUMNS} FROM events WHERE trip_id = ? ORDER BY id"))
+                .bind(trip_id)
+                .fetch_all(db)
+                .await?;
+            return Ok(rows.iter().map(event_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let events = self.events.lock()?;
+
+        Ok(events.iter().filter(|e| e.trip_id == Some(trip_id)).cloned().collect())
+    }
+
+    /// Queue a message for upload, returning its ID
+    pub async fn enqueue_outbox(&self, mut message: OutboxMessage) -> Result<i64, StorageError> {
+        message.status = OutboxStatus::Pending;
+
+        if let Some(db) = &self.db {
+            let id = sqlx::query(
+                "INSERT INTO outbox (topic, payload, created_ms, attempts, next_attempt_ms, critical)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&message.topic)
+            .bind(&message.payload)
+            .bind(message.created_ms)
+            .bind(message.attempts)
+            .bind(message.next_attempt_ms)
+            .bind(message.critical)
+            .execute(db)
+            .await?
+            .last_insert_rowid();
+            debug!("Enqueued outbox message {}", id);
+            return Ok(id);
+        }
+
+        let mut outbox = self.outbox.lock()?;
+        let mut id = self.next_outbox_id.lock()?;
+
+        message.id = *id;
+        *id += 1;
+
+        let returned_id = message.id;
+        outbox.push(message);
+        debug!("Enqueued outbox message {}", returned_id);
+
+        Ok(returned_id)
+    }
+
+    /// Get pending messages that are due for delivery, oldest first
+    pub async fn peek_outbox(&self, limit: usize) -> Result<Vec<OutboxMessage>, StorageError> {
+        let now = now_ms();
+
+        if let Some(db) = &self.db {
+            let rows = sqlx::query(
+                "SELECT * FROM outbox WHERE dead_letter = 0 AND next_attempt_ms <= ? ORDER BY id LIMIT ?",
+            )
+            .bind(now)
+            .bind(limit as i64)
+            .fetch_all(db)
+            .await?;
+            return Ok(rows.iter().map(outbox_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let outbox = self.outbox.lock()?;
+
+        Ok(outbox
+            .iter()
+            .filter(|m| m.status == OutboxStatus::Pending && m.next_attempt_ms <= now)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    /// Remove a delivered message
+    pub async fn ack_outbox(&self, id: i64) -> Result<(), StorageError> {
+        if let Some(db) = &self.db {
+            let result = sqlx::query("DELETE FROM outbox WHERE id = ?").bind(id).execute(db).await?;
+            return match result.rows_affected() {
+                0 => Err(StorageError::NotFound),
+                _ => Ok(()),
+            };
+        }
+
+        let mut outbox = self.outbox.lock()?;
+        let idx = outbox
+            .iter()
+            .position(|m| m.id == id)
+            .ok_or(StorageError::NotFound)?;
+
+        outbox.remove(idx);
+        Ok(())
+    }
+
+    /// Record a failed attempt and hold the message back for `backoff`.
+    /// Returns the updated attempt count.
+    pub async fn requeue_outbox(&self, id: i64, backoff: std::time::Duration) -> Result<u32, StorageError> {
+        let next_attempt_ms = now_ms() + backoff.as_millis() as i64;
+
+        let attempts = if let Some(db) = &self.db {
+            sqlx::query_scalar::<_, u32>(
+                "UPDATE outbox SET attempts = attempts + 1, next_attempt_ms = ? WHERE id = ?
+                 RETURNING attempts",
+            )
+            .bind(next_attempt_ms)
+            .bind(id)
+            .fetch_optional(db)
+            .await?
+            .ok_or(StorageError::NotFound)?
+        } else {
+            let mut outbox = self.outbox.lock()?;
+            let message = outbox
+                .iter_mut()
+                .find(|m| m.id == id)
+                .ok_or(StorageError::NotFound)?;
+
+            message.attempts += 1;
+            message.next_attempt_ms = next_attempt_ms;
+            message.attempts
+        };
+        debug!(
+            "Requeued outbox message {} (attempt {}, retry in {:?})",
+            id, attempts, backoff
+        );
+
+        Ok(attempts)
+    }
+
+    /// Stop retrying a message, keeping it as a dead letter
+    pub async fn dead_letter_outbox(&self, id: i64) -> Result<(), StorageError> {
+        if let Some(db) = &self.db {
+            let result = sqlx::query("UPDATE outbox SET dead_letter = 1 WHERE id = ?")
+                .bind(id)
+                .execute(db)
+                .await?;
+            return match result.rows_affected() {
+                0 => Err(StorageError::NotFound),
+                _ => Ok(()),
+            };
+        }
+
+        let mut outbox = self.outbox.lock()?;
+        let message = outbox
+            .iter_mut()
+            .find(|m| m.id == id)
+            .ok_or(StorageError::NotFound)?;
+
+        message.status = OutboxStatus::DeadLetter;
+        Ok(())
+    }
+
+    /// Get dead-lettered messages
+    pub async fn get_dead_letters(&self) -> Result<Vec<OutboxMessage>, StorageError> {
+        if let Some(db) = &self.db {
+            let rows = sqlx::query("SELECT * FROM outbox WHERE dead_letter = 1 ORDER BY id")
+                .fetch_all(db)
+                .await?;
+            return Ok(rows.iter().map(outbox_from_row).collect::<Result<_, _>>()?);
+        }
+
+        let outbox = self.outbox.lock()?;
+
+        Ok(outbox
+            .iter()
+            .filter(|m| m.status == OutboxStatus::DeadLetter)
+            .cloned()
+            .collect())
+    }
+
+    /// Delete dead letters created before `before_ms`, returning how many;
+    /// pending messages are never pruned
+    pub async fn prune_dead_letters(&self, before_ms: i64) -> Result<usize, StorageError> {
+        if let Some(db) = &self.db {
+            let result = sqlx::query("DELETE FROM outbox WHERE dead_letter = 1 AND created_ms < ?")
+                .bind(before_ms)
+                .execute(db)
+                .await?;
+            return Ok(result.rows_affected() as usize);
+        }
+
+        let mut outbox = self.outbox.lock()?;
+        let before = outbox.len();
+        outbox.retain(|m| m.status == OutboxStatus::Pending || m.created_ms >= before_ms);
+        Ok(before - outbox.len())
+    }
+
+    /// Number of messages still pending delivery
+    pub async fn outbox_len(&self) -> Result<usize, StorageError> {
+        if let Some(db) = &self.db {
+            let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM outbox WHERE dead_letter = 0")
+                .fetch_one(db)
+                .await?;
+            return Ok(count as usize);
+        }
+
+        let outbox = self.outbox.lock()?;
+        Ok(outbox.iter().filter(|m| m.status == OutboxStatus::Pending).count())
+    }
+
+    /// Get total sensor count
+    pub async fn sensor_count(&self) -> Result<usize, StorageError> {
+        match &self.db {
+            Some(db) => count_rows(db, "sensor_log").await,
+            None => Ok(self.sensor_log.lock()?.len()),
+        }
+    }
+
+    /// Get total prediction count
+    pub async fn prediction_count(&self) -> Result<usize, StorageError> {
+        match &self.db {
+            Some(db) => count_rows(db, "predictions").await,
+            None => Ok(self.predictions.lock()?.len()),
+        }
+    }
+
+    /// Write the in-memory sensor log and predictions to `path` as JSON
+    ///
+    /// Meant for reproducing bug reports; SQLite tables are not included.
+    pub fn save_snapshot(&self, path: &str) -> Result<(), StorageError> {
+        let snapshot = Snapshot {
+            sensors: self.sensor_log.lock()?.clone(),
+            predictions: self.predictions.lock()?.clone(),
+        };
+        let json = serde_json::to_vec(&snapshot)
+            .map_err(|e| StorageError::SerializationError(format!("snapshot: {e}")))?;
+        std::fs::write(path, json)?;
+        info!(
+            "Saved snapshot of {} sensor records and {} predictions to {}",
+            snapshot.sensors.len(),
+            snapshot.predictions.len(),
+            path
+        );
+        Ok(())
+    }
+
+    /// Replace the in-memory sensor log and predictions with a snapshot
+    /// written by [`Repository::save_snapshot`]
+    ///
+    /// A file that does not parse leaves the repository untouched. Records
+    /// beyond the retention limits are dropped, oldest first.
+    pub fn load_snapshot(&self, path: &str) -> Result<(), StorageError> {
+        let json = std::fs::read(path)?;
+        let mut snapshot: Snapshot = serde_json::from_slice(&json)
+            .map_err(|e| StorageError::SerializationError(format!("snapshot {path}: {e}")))?;
+
+        let excess = snapshot.sensors.len().saturating_sub(self.max_sensor_records);
+        snapshot.sensors.drain(..excess);
+        let excess = snapshot.predictions.len().saturating_sub(self.max_prediction_records);
+        snapshot.predictions.drain(..excess);
+        let next_id = snapshot.predictions.iter().map(|p| p.id).max().unwrap_or(0) + 1;
+
+        info!(
+            "Loaded snapshot of {} sensor records and {} predictions from {}",
+            snapshot.sensors.len(),
+            snapshot.predictions.len(),
+            path
+        );
+        {
+            let mut log = self.sensor_log.lock()?;
+            log.clear();
+            for record in snapshot.sensors {
+                log_sensor(&mut log, record);
+            }
+        }
+        *self.predictions.lock()? = snapshot.predictions;
+        *self.next_prediction_id.lock()? = next_id;
+        Ok(())
+    }
+
+    /// Clear all in-memory data (for testing); SQLite tables are left alone
+    pub fn clear(&self) {
+        if let Ok(mut log) = self.sensor_log.lock() {
+            log.clear();
+        }
+        if let Ok(mut preds) = self.predictions.lock() {
+            preds.clear();
+        }
+        if let Ok(mut trips) = self.trips.lock() {
+            trips.clear();
+        }
+        if let Ok(mut active) = self.active_trip.lock() {
+            *active = None;
+        }
+        if let Ok(mut outbox) = self.outbox.lock() {
+            outbox.clear();
+        }
+        if let Ok(mut events) = self.events.lock() {
+            events.clear();
+        }
+        if let Ok(mut calibrations) = self.calibrations.lock() {
+            calibrations.clear();
+        }
+    }
+}
+
+/// Writes made inside [`Repository::transaction`]
+pub struct StorageTransaction<'r> {
+    repository: &'r Repository,
+    /// Open SQLite transaction, `None` in memory
+    tx: Option<sqlx::Transaction<'static, Sqlite>>,
+}
+
+impl StorageTransaction<'_> {
+    /// Insert a sensor record as part of the transaction
+    pub async fn insert_sensor(&mut self, mut record: SensorRecord) -> Result<(), StorageError> {
+        let Some(tx) = &mut self.tx else {
+            return self.repository.insert_sensor(record).await;
+        };
+        if record.trip_id.is_none() {
+            record.trip_id = *self.repository.active_trip.lock()?;
+        }
+        insert_sensor_query(&record).execute(&mut **tx).await?;
+        Ok(())
+    }
+
+    /// Insert a prediction as part of the transaction, returning its ID
+    pub async fn insert_prediction(&mut self, mut record: PredictionRecord) -> Result<i64, StorageError> {
+        let Some(tx) = &mut self.tx else {
+            return self.repository.insert_prediction(record).await;
+        };
+        prepare_prediction(&mut record)?;
+        Ok(insert_prediction_query(&record).execute(&mut **tx).await?.last_insert_rowid())
+    }
+}
+
+/// Row count of `table`
+async fn count_rows(db: &SqlitePool, table: &str) -> Result<usize, StorageError> {
+    let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+        .fetch_one(db)
+        .await?;
+    Ok(count as usize)
+}
+
+impl Default for Repository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh database path in the temp directory
+    fn temp_db(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("repository-{}-{}.db", name, std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        remove_db(&path);
+        path
+    }
+
+    fn remove_db(path: &str) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{path}{suffix}"));
+        }
+    }
+
+    fn prediction_at(timestamp_ms: i64, severity: &str) -> PredictionRecord {
+        PredictionRecord {
+            id: 0,
+            timestamp_ms,
+            fault_class: "engine_overheating".to_string(),
+            confidence: 0.9,
+            severity: severity.to_string(),
+            sensor_snapshot: None,
+            acknowledged: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sensor_insert_and_retrieve() {
+        let repo = Repository::new();
+        
+        let record = SensorRecord {
+            timestamp_ms: 1234567890,
+            rpm: 3000,
+            speed: 60,
+            coolant_temp: 85,
+            engine_load: 50,
+            maf: 12.5,
+            fuel_trim_short: 2.0,
+            fuel_trim_long: 1.5,
+            ..Default::default()
+        };
+        
+        repo.insert_sensor(record.clone()).await.unwrap();
+        
+        let sensors = repo.get_sensors(10).await.unwrap();
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors[0].rpm, 3000);
+    }
+
+    #[tokio::test]
+    async fn test_prediction_insert() {
+        let repo = Repository::new();
+        
+        let record = PredictionRecord {
+            id: 0,
+            timestamp_ms: 1234567890,
+            fault_class: "overheating".to_string(),
+            confidence: 0.85,
+            severity: "high".to_string(),
+            sensor_snapshot: None,
+            acknowledged: false,
+        };
+        
+        let id = repo.insert_prediction(record).await.unwrap();
+        assert_eq!(id, 1);
+        
+        let preds = repo.get_predictions(&PredictionFilter::new(10)).await.unwrap();
+        assert_eq!(preds.len(), 1);
+        assert_eq!(preds[0].fault_class, "overheating");
+    }
+
+    #[tokio::test]
+    async fn test_constraint_violation_is_specific() {
+        let repo = Repository::new();
+
+        let record = PredictionRecord {
+            id: 0,
+            timestamp_ms: 1234567890,
+            fault_class: "overheating".to_string(),
+            confidence: 1.5,
+            severity: "high".to_string(),
+            sensor_snapshot: None,
+            acknowledged: false,
+        };
+
+        let err = repo.insert_prediction(record).await.unwrap_err();
+        assert!(matches!(err, StorageError::Constraint(_)));
+        assert!(!matches!(err, StorageError::DatabaseError(_)));
+        assert!(!err.is_transient());
+        assert_eq!(repo.prediction_count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_counts_surface_database_errors() {
+        let path = temp_db("counts");
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+        repo.insert_sensor(SensorRecord::default()).await.unwrap();
+        assert_eq!(repo.sensor_count().await.unwrap(), 1);
+
+        // A dead connection is an error, not an empty table
+        repo.db.as_ref().unwrap().close().await;
+        assert!(matches!(repo.sensor_count().await, Err(StorageError::Connection(_))));
+        assert!(matches!(repo.prediction_count().await, Err(StorageError::Connection(_))));
+        assert!(matches!(repo.outbox_len().await, Err(StorageError::Connection(_))));
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_prediction_with_sensor_context() {
+        let repo = Repository::new();
+
+        // Sensor records every 10s from t=0 to t=100s
+        for i in 0..=10 {
+            repo.insert_sensor(SensorRecord {
+                timestamp_ms: i * 10_000,
+                rpm: i as i32 * 100,
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let id = repo.insert_prediction(PredictionRecord {
+            id: 0,
+            timestamp_ms: 60_000,
+            fault_class: "engine_overheating".to_string(),
+            confidence: 0.9,
+            severity: "critical".to_string(),
+            sensor_snapshot: None,
+            acknowledged: false,
+        }).await.unwrap();
+
+        let (prediction, sensors) = repo.get_prediction_with_context(id).await.unwrap();
+        assert_eq!(prediction.id, id);
+        assert_eq!(
+            prediction.sensor_snapshot,
+            Some(SensorSnapshot { start_ms: 30_000, end_ms: 60_000 })
+        );
+
+        let timestamps: Vec<i64> = sensors.iter().map(|r| r.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![30_000, 40_000, 50_000, 60_000]);
+
+        assert!(matches!(
+            repo.get_prediction_with_context(id + 1).await,
+            Err(StorageError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trip_tags_sensor_records() {
+        let repo = Repository::new();
+
+        repo.insert_sensor(SensorRecord { timestamp_ms: 0, ..Default::default() }).await.unwrap();
+
+        let trip_id = repo.start_trip(1_000).await.unwrap();
+        for i in 1..=3 {
+            repo.insert_sensor(SensorRecord { timestamp_ms: i * 1_000, ..Default::default() }).await.unwrap();
+        }
+        repo.end_trip(trip_id, 3_000, 0.5).await.unwrap();
+
+        repo.insert_sensor(SensorRecord { timestamp_ms: 10_000, ..Default::default() }).await.unwrap();
+
+        let trip = repo.get_trip(trip_id).await.unwrap();
+        assert_eq!(trip.end_ms, Some(3_000));
+        assert_eq!(repo.active_trip_id(), None);
+        assert_eq!(repo.get_trip_sensors(trip_id).await.unwrap().len(), 3);
+        assert!(matches!(repo.end_trip(trip_id + 1, 0, 0.0).await, Err(StorageError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_trip_aggregates_and_event_counts() {
+        let path = temp_db("trip-counts");
+        let event = |kind: &str| EventRecord {
+            id: 0,
+            timestamp_ms: 0,
+            kind: kind.to_string(),
+            severity: None,
+            payload: "{}".to_string(),
+            trip_id: None,
+        };
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            let first = repo.start_trip(1_000).await.unwrap();
+            for speed in [40, 60] {
+                repo.insert_sensor(SensorRecord { timestamp_ms: 1_000, speed, ..Default::default() }).await.unwrap();
+            }
+            repo.insert_event(event("hard_braking")).await.unwrap();
+            repo.insert_event(event("hard_braking")).await.unwrap();
+            repo.insert_event(event("distraction")).await.unwrap();
+            repo.end_trip(first, 2_000, 1.0).await.unwrap();
+            let second = repo.start_trip(3_000).await.unwrap();
+            repo.insert_sensor(SensorRecord { timestamp_ms: 3_000, speed: 100, ..Default::default() }).await.unwrap();
+
+            let aggregates = repo.trip_sensor_aggregates(first).await.unwrap();
+            assert_eq!(aggregates.count, 2);
+            assert_eq!((aggregates.speed.max, aggregates.speed.mean), (60, 50.0));
+            assert_eq!(repo.trip_sensor_aggregates(second + 1).await.unwrap(), SensorAggregates::default());
+
+            let counts = repo.trip_event_counts(&[first, second]).await.unwrap();
+            assert_eq!(counts[&first]["hard_braking"], 2);
+            assert_eq!(counts[&first]["distraction"], 1);
+            assert!(!counts.contains_key(&second));
+            assert!(repo.trip_event_counts(&[]).await.unwrap().is_empty());
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_trips_and_events_survive_reopen() {
+        let path = temp_db("trips");
+        let event = |timestamp_ms: i64, kind: &str| EventRecord {
+            id: 0,
+            timestamp_ms,
+            kind: kind.to_string(),
+            severity: Some("medium".to_string()),
+            payload: "{}".to_string(),
+            trip_id: None,
+        };
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+        let trip_id = repo.start_trip(1_000).await.unwrap();
+        repo.insert_event(event(1_500, "hard_braking")).await.unwrap();
+        repo.insert_event(event(1_800, "distraction")).await.unwrap();
+        assert!(matches!(repo.end_trip(trip_id, 500, 0.0).await, Err(StorageError::Constraint(_))));
+        repo.end_trip(trip_id, 2_000, 1.2).await.unwrap();
+        repo.insert_event(event(5_000, "hard_braking")).await.unwrap();
+        drop(repo);
+
+        let reopened = Repository::with_sqlite(&path).await.unwrap();
+        assert_eq!(reopened.trip_count().await.unwrap(), 1);
+        let trip = reopened.get_trip(trip_id).await.unwrap();
+        assert_eq!((trip.start_ms, trip.end_ms, trip.distance_km), (1_000, Some(2_000), 1.2));
+        let kinds: Vec<String> = reopened
+            .get_trip_events(trip_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.kind)
+            .collect();
+        assert_eq!(kinds, ["hard_braking", "distraction"]);
+        assert_eq!(reopened.get_events(10).await.unwrap()[0].trip_id, None);
+        assert_eq!(reopened.prune_events(2_000).await.unwrap(), 2);
+
+        // IDs continue after the stored trips
+        assert_eq!(reopened.start_trip(10_000).await.unwrap(), trip_id + 1);
+        assert_eq!(reopened.list_trips(0, 10).await.unwrap()[0].end_ms, None);
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_open_trip_resumes_after_reopen() {
+        let path = temp_db("open-trip");
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+        let trip_id = repo.start_trip(1_000).await.unwrap();
+        repo.insert_sensor(SensorRecord { timestamp_ms: 1_000, ..Default::default() }).await.unwrap();
+        drop(repo);
+
+        let reopened = Repository::with_sqlite(&path).await.unwrap();
+        assert_eq!(reopened.active_trip_id(), Some(trip_id));
+        reopened.insert_sensor(SensorRecord { timestamp_ms: 2_000, ..Default::default() }).await.unwrap();
+        assert_eq!(reopened.get_trip_sensors(trip_id).await.unwrap().len(), 2);
+
+        reopened.end_trip(trip_id, 2_000, 0.3).await.unwrap();
+        drop(reopened);
+        let closed = Repository::with_sqlite(&path).await.unwrap();
+        assert_eq!(closed.active_trip_id(), None);
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_dangling_trips_close_on_open() {
+        let path = temp_db("dangling-trips");
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+        let orphan = repo.start_trip(1_000).await.unwrap();
+        repo.insert_sensor(SensorRecord { timestamp_ms: 4_000, ..Default::default() }).await.unwrap();
+        let empty = repo.start_trip(5_000).await.unwrap();
+        let newest = repo.start_trip(8_000).await.unwrap();
+        drop(repo);
+
+        let reopened = Repository::with_sqlite(&path).await.unwrap();
+        assert_eq!(reopened.active_trip_id(), Some(newest));
+        assert_eq!(reopened.get_trip(orphan).await.unwrap().end_ms, Some(4_000));
+        assert_eq!(reopened.get_trip(empty).await.unwrap().end_ms, Some(5_000));
+        let open: Vec<i64> = reopened
+            .list_trips(0, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.end_ms.is_none())
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(open, [newest]);
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_outbox_enqueue_peek_ack() {
+        let path = temp_db("outbox");
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            let first = repo
+                .enqueue_outbox(OutboxMessage::new("vehicles/v1/events", b"a".to_vec()))
+                .await
+                .unwrap();
+            let second = repo
+                .enqueue_outbox(OutboxMessage::new("vehicles/v1/events", b"b".to_vec()))
+                .await
+                .unwrap();
+
+            let due = repo.peek_outbox(10).await.unwrap();
+            assert_eq!(due.iter().map(|m| m.id).collect::<Vec<_>>(), vec![first, second]);
+            assert_eq!(due[1].payload, b"b");
+            assert_eq!(repo.peek_outbox(1).await.unwrap().len(), 1);
+
+            repo.ack_outbox(first).await.unwrap();
+            assert_eq!(repo.outbox_len().await.unwrap(), 1);
+            assert!(matches!(repo.ack_outbox(first).await, Err(StorageError::NotFound)));
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_outbox_backoff_scheduling() {
+        let path = temp_db("outbox-backoff");
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            let id = repo.enqueue_outbox(OutboxMessage::new("t", vec![1])).await.unwrap();
+
+            // Held back while the backoff is running
+            let attempts = repo.requeue_outbox(id, std::time::Duration::from_secs(60)).await.unwrap();
+            assert_eq!(attempts, 1);
+            assert!(repo.peek_outbox(10).await.unwrap().is_empty());
+            assert_eq!(repo.outbox_len().await.unwrap(), 1);
+
+            // Due again once the backoff has elapsed
+            let attempts = repo.requeue_outbox(id, std::time::Duration::ZERO).await.unwrap();
+            assert_eq!(attempts, 2);
+            let due = repo.peek_outbox(10).await.unwrap();
+            assert_eq!(due[0].attempts, 2);
+
+            repo.dead_letter_outbox(id).await.unwrap();
+            assert!(repo.peek_outbox(10).await.unwrap().is_empty());
+            assert_eq!(repo.get_dead_letters().await.unwrap().len(), 1);
+            assert!(matches!(repo.requeue_outbox(id + 1, std::time::Duration::ZERO).await, Err(StorageError::NotFound)));
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_outbox_survives_reopen() {
+        let path = temp_db("outbox-reopen");
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+        let id = repo
+            .enqueue_outbox(OutboxMessage::new("vehicles/v1/alerts", vec![7, 8]).with_critical(true))
+            .await
+            .unwrap();
+        repo.requeue_outbox(id, std::time::Duration::ZERO).await.unwrap();
+        drop(repo);
+
+        // Power loss before the uplink came back
+        let reopened = Repository::with_sqlite(&path).await.unwrap();
+        let due = reopened.peek_outbox(10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].id, due[0].attempts), (id, 1));
+        assert_eq!(due[0].topic, "vehicles/v1/alerts");
+        assert_eq!(due[0].payload, [7, 8]);
+        assert!(due[0].critical);
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_calibrations_survive_reopen() {
+        let path = temp_db("calibrations");
+        let record = |kind: &str, payload: &str| CalibrationRecord {
+            vehicle_id: "van-7".to_string(),
+            kind: kind.to_string(),
+            payload: payload.to_string(),
+            updated_ms: 1_000,
+        };
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+        repo.save_calibration(record("head_pose", "[4.5,-2.0]")).await.unwrap();
+        repo.save_calibration(record("head_pose", "[5.0,-1.5]")).await.unwrap();
+        repo.save_calibration(record("gear_ratios", "[8.0,15.0]")).await.unwrap();
+        drop(repo);
+
+        let reopened = Repository::with_sqlite(&path).await.unwrap();
+        let pose = reopened.get_calibration("van-7", "head_pose").await.unwrap().unwrap();
+        assert_eq!(pose.payload, "[5.0,-1.5]");
+        let kinds: Vec<String> = reopened
+            .get_calibrations("van-7")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.kind)
+            .collect();
+        assert_eq!(kinds, ["gear_ratios", "head_pose"]);
+        assert_eq!(reopened.get_calibration("truck-2", "head_pose").await.unwrap(), None);
+
+        assert!(reopened.delete_calibration("van-7", "gear_ratios").await.unwrap());
+        assert!(!reopened.delete_calibration("van-7", "gear_ratios").await.unwrap());
+        assert_eq!(reopened.get_calibrations("van-7").await.unwrap().len(), 1);
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_retention_limit() {
+        let mut repo = Repository::new();
+        repo.max_sensor_records = 5;
+        
+        for i in 0..10 {
+            repo.insert_sensor(SensorRecord {
+                timestamp_ms: i,
+                rpm: i as i32 * 100,
+                ..Default::default()
+            }).await.unwrap();
+        }
+        
+        assert_eq!(repo.sensor_count().await.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_pages_are_stable_under_inserts() {
+        let path = temp_db("paged");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            for i in 1..=5 {
+                repo.insert_sensor(SensorRecord { timestamp_ms: i * 1_000, ..Default::default() })
+                    .await
+                    .unwrap();
+                repo.insert_prediction(prediction_at(i * 1_000, "high")).await.unwrap();
+            }
+
+            let first = repo.get_sensors_paged(None, 2).await.unwrap();
+            let timestamps: Vec<i64> = first.items.iter().map(|r| r.timestamp_ms).collect();
+            assert_eq!(timestamps, vec![5_000, 4_000]);
+            assert_eq!(first.next_cursor, Some(4_000));
+
+            // Newer records arriving between pages do not shift the next one
+            repo.insert_sensor(SensorRecord { timestamp_ms: 6_000, ..Default::default() })
+                .await
+                .unwrap();
+            let second = repo.get_sensors_paged(first.next_cursor, 2).await.unwrap();
+            let timestamps: Vec<i64> = second.items.iter().map(|r| r.timestamp_ms).collect();
+            assert_eq!(timestamps, vec![3_000, 2_000]);
+            let last = repo.get_sensors_paged(second.next_cursor, 2).await.unwrap();
+            assert_eq!(last.items.len(), 1);
+            assert_eq!(last.next_cursor, None);
+
+            let first = repo.get_predictions_paged(None, 3).await.unwrap();
+            let ids: Vec<i64> = first.items.iter().map(|p| p.id).collect();
+            assert_eq!(ids, vec![5, 4, 3]);
+            let rest = repo.get_predictions_paged(first.next_cursor, 3).await.unwrap();
+            let ids: Vec<i64> = rest.items.iter().map(|p| p.id).collect();
+            assert_eq!(ids, vec![2, 1]);
+            assert_eq!(rest.next_cursor, None);
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_pages_keep_a_millisecond_together() {
+        let path = temp_db("paged-ties");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            for (timestamp_ms, rpm) in [(1_000, 1), (2_000, 2), (2_000, 3), (2_000, 4), (3_000, 5)] {
+                repo.insert_sensor(SensorRecord { timestamp_ms, rpm, ..Default::default() })
+                    .await
+                    .unwrap();
+            }
+
+            // The page reaches into 2_000 ms, so it takes the whole millisecond
+            let first = repo.get_sensors_paged(None, 2).await.unwrap();
+            let rpms: Vec<i32> = first.items.iter().map(|r| r.rpm).collect();
+            assert_eq!(rpms, vec![5, 4, 3, 2]);
+            assert_eq!(first.next_cursor, Some(2_000));
+
+            let last = repo.get_sensors_paged(first.next_cursor, 2).await.unwrap();
+            let rpms: Vec<i32> = last.items.iter().map(|r| r.rpm).collect();
+            assert_eq!(rpms, vec![1]);
+            assert_eq!(last.next_cursor, None);
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_backfilled_sensors_page_in_timestamp_order() {
+        let path = temp_db("paged-backfill");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            repo.insert_sensor(SensorRecord { timestamp_ms: 3_000, ..Default::default() }).await.unwrap();
+            repo.insert_sensors_batch(&[
+                SensorRecord { timestamp_ms: 1_000, ..Default::default() },
+                SensorRecord { timestamp_ms: 4_000, ..Default::default() },
+                SensorRecord { timestamp_ms: 2_000, ..Default::default() },
+            ])
+            .await
+            .unwrap();
+
+            let mut timestamps = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = repo.get_sensors_paged(cursor, 1).await.unwrap();
+                timestamps.extend(page.items.iter().map(|r| r.timestamp_ms));
+                cursor = page.next_cursor;
+                if cursor.is_none() {
+                    break;
+                }
+            }
+            assert_eq!(timestamps, vec![4_000, 3_000, 2_000, 1_000]);
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_predictions_between_is_inclusive_and_oldest_first() {
+        let path = temp_db("between");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            // Inserted out of timestamp order
+            for (timestamp_ms, severity) in [
+                (3_000, "high"),
+                (1_000, "high"),
+                (2_000, "low"),
+                (4_000, "high"),
+                (5_000, "high"),
+            ] {
+                repo.insert_prediction(prediction_at(timestamp_ms, severity)).await.unwrap();
+            }
+
+            let window = repo.get_predictions_between(1_000, 4_000, None).await.unwrap();
+            let timestamps: Vec<i64> = window.iter().map(|p| p.timestamp_ms).collect();
+            assert_eq!(timestamps, vec![1_000, 2_000, 3_000, 4_000]);
+
+            let high = repo.get_predictions_between(2_000, 5_000, Some("high")).await.unwrap();
+            let timestamps: Vec<i64> = high.iter().map(|p| p.timestamp_ms).collect();
+            assert_eq!(timestamps, vec![3_000, 4_000, 5_000]);
+
+            assert!(repo.get_predictions_between(6_000, 9_000, None).await.unwrap().is_empty());
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_sensor_aggregates_over_window() {
+        let path = temp_db("aggregates");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            assert_eq!(repo.sensor_aggregates(0).await.unwrap(), SensorAggregates::default());
+
+            for (timestamp_ms, rpm, speed, coolant_temp, engine_load) in [
+                (1_000, 5_000, 120, 70, 90),
+                (2_000, 1_000, 30, 80, 20),
+                (3_000, 2_000, 50, 95, 40),
+                (4_000, 3_000, 70, 85, 60),
+            ] {
+                repo.insert_sensor(SensorRecord {
+                    timestamp_ms,
+                    rpm,
+                    speed,
+                    coolant_temp,
+                    engine_load,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            }
+
+            // The first record falls outside the window
+            let aggregates = repo.sensor_aggregates(2_000).await.unwrap();
+            assert_eq!(aggregates.count, 3);
+            assert_eq!(aggregates.rpm, FieldStats { min: 1_000, max: 3_000, mean: 2_000.0 });
+            assert_eq!(aggregates.speed, FieldStats { min: 30, max: 70, mean: 50.0 });
+            assert_eq!(aggregates.coolant_temp, FieldStats { min: 80, max: 95, mean: 260.0 / 3.0 });
+            assert_eq!(aggregates.engine_load, FieldStats { min: 20, max: 60, mean: 40.0 });
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_csv_export_is_rfc4180() {
+        let path = temp_db("export");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            for timestamp_ms in [1_000, 1_700_000_000_250] {
+                repo.insert_sensor(SensorRecord {
+                    timestamp_ms,
+                    rpm: 2_500,
+                    maf: 12.5,
+                    fuel_level: Some(40.5),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            }
+            repo.insert_prediction(PredictionRecord {
+                fault_class: "misfire, cyl \"2\"".to_string(),
+                ..prediction_at(1_700_000_000_250, "high")
+            })
+            .await
+            .unwrap();
+
+            let mut csv = Vec::new();
+            assert_eq!(repo.export_sensors_csv(&mut csv, 2_000).await.unwrap(), 1);
+            assert_eq!(
+                String::from_utf8(csv).unwrap(),
+                "timestamp_ms,timestamp,rpm,speed,coolant_temp,engine_load,maf,fuel_trim_short,\
+                 fuel_trim_long,trip_id,intake_temp,fuel_level,o2_lambda,throttle_pos\r\n\
+                 1700000000250,2023-11-14T22:13:20.250Z,2500,0,0,0,12.5,0,0,,,40.5,,\r\n"
+            );
+
+            let mut csv = Vec::new();
+            assert_eq!(repo.export_predictions_csv(&mut csv, 0).await.unwrap(), 1);
+            assert_eq!(
+                String::from_utf8(csv).unwrap(),
+                "id,timestamp_ms,timestamp,fault_class,confidence,severity,snapshot_start_ms,snapshot_end_ms,\
+                 acknowledged\r\n\
+                 1,1700000000250,2023-11-14T22:13:20.250Z,\"misfire, cyl \"\"2\"\"\",0.9,high,\
+                 1699999970250,1700000000250,false\r\n"
+            );
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_export_writes_unlocked_chunks() {
+        /// Counts lines, checking the logs are not locked while writing
+        struct Probe<'r> {
+            repo: &'r Repository,
+            lines: usize,
+        }
+
+        impl Write for Probe<'_> {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                assert!(self.repo.sensor_log.try_lock().is_ok());
+                assert!(self.repo.predictions.try_lock().is_ok());
+                self.lines += buf.iter().filter(|&&b| b == b'\n').count();
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let repo = Repository::new();
+        // Several chunks, with a millisecond shared across a chunk boundary
+        let total = export::EXPORT_CHUNK * 2 + 10;
+        let records: Vec<SensorRecord> = (0..total)
+            .map(|i| SensorRecord { timestamp_ms: i / 3, rpm: i as i32, ..Default::default() })
+            .collect();
+        repo.insert_sensors_batch(&records).await.unwrap();
+        for i in 0..total {
+            repo.insert_prediction(prediction_at(i, "low")).await.unwrap();
+        }
+
+        let mut probe = Probe { repo: &repo, lines: 0 };
+        assert_eq!(repo.export_sensors_csv(&mut probe, 0).await.unwrap(), total as usize);
+        assert_eq!(probe.lines, total as usize + 1);
+
+        let mut csv = Vec::new();
+        repo.export_sensors_csv(&mut csv, 0).await.unwrap();
+        let rpms: Vec<i32> = String::from_utf8(csv)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(2).unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(rpms, (0..total as i32).collect::<Vec<_>>());
+
+        let mut probe = Probe { repo: &repo, lines: 0 };
+        assert_eq!(repo.export_predictions_csv(&mut probe, 5).await.unwrap(), total as usize - 5);
+        assert_eq!(probe.lines, total as usize - 4);
+    }
+
+    #[tokio::test]
+    async fn test_sensor_batch_is_all_or_nothing() {
+        let mut repo = Repository::new();
+        repo.max_sensor_records = 4;
+        repo.start_trip(0).await.unwrap();
+        let batch: Vec<SensorRecord> = (0..6)
+            .map(|i| SensorRecord { timestamp_ms: i * 200, ..Default::default() })
+            .collect();
+        repo.insert_sensors_batch(&batch).await.unwrap();
+        let kept = repo.get_sensors_since(0).await.unwrap();
+        let timestamps: Vec<i64> = kept.iter().map(|r| r.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![400, 600, 800, 1_000]);
+        assert!(kept.iter().all(|r| r.trip_id == Some(1)));
+
+        let path = temp_db("batch");
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+        repo.insert_sensors_batch(&batch).await.unwrap();
+        assert_eq!(repo.sensor_count().await.unwrap(), 6);
+
+        // A row failing part-way through rolls back the ones before it
+        sqlx::query(
+            "CREATE TRIGGER reject_negative_rpm BEFORE INSERT ON sensor_log WHEN NEW.rpm < 0
+                BEGIN SELECT RAISE(ABORT, 'negative rpm'); END",
+        )
+        .execute(repo.db.as_ref().unwrap())
+        .await
+        .unwrap();
+        let mut bad = batch.clone();
+        bad[3].rpm = -1;
+        assert!(repo.insert_sensors_batch(&bad).await.is_err());
+        assert_eq!(repo.sensor_count().await.unwrap(), 6);
+
+        drop(repo);
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_acknowledgement_filters_and_survives_reopen() {
+        let path = temp_db("acknowledge");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            for timestamp_ms in [1_000, 2_000, 3_000] {
+                repo.insert_prediction(prediction_at(timestamp_ms, "high")).await.unwrap();
+            }
+            repo.acknowledge_prediction(2).await.unwrap();
+            assert!(matches!(repo.acknowledge_prediction(9).await, Err(StorageError::NotFound)));
+
+            let open: Vec<i64> = repo
+                .get_predictions(&PredictionFilter::new(10).unacknowledged())
+                .await
+                .unwrap()
+                .iter()
+                .map(|p| p.id)
+                .collect();
+            assert_eq!(open, vec![3, 1]);
+            assert_eq!(repo.get_predictions(&PredictionFilter::new(10)).await.unwrap().len(), 3);
+        }
+
+        let reopened = Repository::with_sqlite(&path).await.unwrap();
+        let (prediction, _) = reopened.get_prediction_with_context(2).await.unwrap();
+        assert!(prediction.acknowledged);
+
+        drop(reopened);
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commits_on_ok_and_rolls_back_on_err() {
+        let path = temp_db("transaction");
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+
+        let id = repo
+            .transaction(async |tx| {
+                tx.insert_sensor(SensorRecord { timestamp_ms: 60_000, ..Default::default() }).await?;
+                tx.insert_prediction(prediction_at(60_000, "high")).await
+            })
+            .await
+            .unwrap();
+        let (_, context) = repo.get_prediction_with_context(id).await.unwrap();
+        assert_eq!(context.len(), 1);
+
+        // A failing write undoes the ones before it
+        let err = repo
+            .transaction(async |tx| {
+                tx.insert_prediction(prediction_at(70_000, "high")).await?;
+                tx.insert_prediction(PredictionRecord { confidence: 2.0, ..prediction_at(70_000, "high") })
+                    .await
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::Constraint(_)));
+        assert_eq!(repo.prediction_count().await.unwrap(), 1);
+        assert_eq!(repo.sensor_count().await.unwrap(), 1);
+        drop(repo);
+        remove_db(&path);
+
+        // In memory earlier writes stay
+        let repo = Repository::new();
+        let result: Result<(), _> = repo
+            .transaction(async |tx| {
+                tx.insert_sensor(SensorRecord::default()).await?;
+                Err(StorageError::NotFound)
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(repo.sensor_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_prediction_hit_and_miss() {
+        let repo = Repository::new();
+        let id = repo.insert_prediction(prediction_at(1_000, "medium")).await.unwrap();
+
+        let prediction = repo.get_prediction(id).await.unwrap();
+        assert_eq!(prediction.id, id);
+        assert_eq!(prediction.severity, "medium");
+        assert!(matches!(repo.get_prediction(id + 1).await, Err(StorageError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_prediction_counts_by_severity() {
+        let path = temp_db("severity-counts");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            for (timestamp_ms, severity) in [
+                (500, "critical"),
+                (1_000, "critical"),
+                (2_000, "high"),
+                (3_000, "high"),
+                (4_000, "high"),
+            ] {
+                repo.insert_prediction(prediction_at(timestamp_ms, severity)).await.unwrap();
+            }
+
+            let counts = repo.prediction_counts_by_severity(1_000).await.unwrap();
+            assert_eq!(
+                counts,
+                HashMap::from([("critical".to_string(), 1), ("high".to_string(), 3)])
+            );
+            assert!(repo.prediction_counts_by_severity(5_000).await.unwrap().is_empty());
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_prediction_filter_combines_with_and() {
+        let path = temp_db("filter");
+
+        for repo in [Repository::new(), Repository::with_sqlite(&path).await.unwrap()] {
+            for (fault_class, severity) in [
+                ("engine_overheating", "high"),
+                ("engine_overheating", "low"),
+                ("battery_failure", "high"),
+                ("engine_overheating", "high"),
+            ] {
+                repo.insert_prediction(PredictionRecord {
+                    fault_class: fault_class.to_string(),
+                    ..prediction_at(1_000, severity)
+                })
+                .await
+                .unwrap();
+            }
+            let ids = |predictions: Vec<PredictionRecord>| -> Vec<i64> {
+                predictions.iter().map(|p| p.id).collect()
+            };
+
+            let overheating = PredictionFilter::new(10).with_fault_class("engine_overheating");
+            assert_eq!(ids(repo.get_predictions(&overheating).await.unwrap()), vec![4, 2, 1]);
+            let high_overheating = overheating.clone().with_severity("high");
+            assert_eq!(ids(repo.get_predictions(&high_overheating).await.unwrap()), vec![4, 1]);
+            let limited = PredictionFilter { limit: 1, ..high_overheating };
+            assert_eq!(ids(repo.get_predictions(&limited).await.unwrap()), vec![4]);
+
+            // Empty strings match everything
+            let empty = PredictionFilter::new(10).with_severity("").with_fault_class("");
+            assert_eq!(repo.get_predictions(&empty).await.unwrap().len(), 4);
+        }
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_persists_across_reopen() {
+        let path = temp_db("reopen");
+
+        {
+            let mut repo = Repository::with_sqlite(&path).await.unwrap();
+            let mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+                .fetch_one(repo.db.as_ref().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(mode, "wal");
+
+            for i in 0..10 {
+                repo.insert_sensor(SensorRecord {
+                    timestamp_ms: i * 10_000,
+                    rpm: i as i32 * 100,
+                    intake_temp: (i % 2 == 0).then_some(30),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            }
+            let id = repo
+                .insert_prediction(PredictionRecord {
+                    id: 0,
+                    timestamp_ms: 60_000,
+                    fault_class: "engine_overheating".to_string(),
+                    confidence: 0.9,
+                    severity: "critical".to_string(),
+                    sensor_snapshot: None,
+                    acknowledged: false,
+                })
+                .await
+                .unwrap();
+            assert_eq!(id, 1);
+
+            // Trimmed by the periodic pass, not on insert
+            repo.max_sensor_records = 6;
+            assert_eq!(repo.sensor_count().await.unwrap(), 10);
+            assert_eq!(repo.enforce_retention().await.unwrap(), 4);
+        }
+
+        let repo = Repository::with_sqlite(&path).await.unwrap();
+        assert_eq!(repo.sensor_count().await.unwrap(), 6);
+        let newest = repo.get_sensors(2).await.unwrap();
+        assert_eq!(newest[0].timestamp_ms, 90_000);
+        assert_eq!(newest[0].intake_temp, None);
+        assert_eq!(newest[1].intake_temp, Some(30));
+
+        let (prediction, sensors) = repo.get_prediction_with_context(1).await.unwrap();
+        assert_eq!(prediction.severity, "critical");
+        assert_eq!(
+            prediction.sensor_snapshot,
+            Some(SensorSnapshot { start_ms: 30_000, end_ms: 60_000 })
+        );
+        let timestamps: Vec<i64> = sensors.iter().map(|r| r.timestamp_ms).collect();
+        assert_eq!(timestamps, vec![40_000, 50_000, 60_000]);
+        assert!(matches!(repo.get_prediction_with_context(2).await, Err(StorageError::NotFound)));
+
+        drop(repo);
+        remove_db(&path);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let path = std::env::temp_dir().join(format!("repository-snapshot-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let repo = Repository::new();
+        for timestamp_ms in [1_000, 2_000] {
+            repo.insert_sensor(SensorRecord { timestamp_ms, rpm: 800, ..Default::default() }).await.unwrap();
+        }
+        let id = repo.insert_prediction(prediction_at(2_000, "high")).await.unwrap();
+        repo.acknowledge_prediction(id).await.unwrap();
+        repo.save_snapshot(path).unwrap();
+
+        let restored = Repository::new();
+        restored.load_snapshot(path).unwrap();
+        let sensors = restored.get_sensors(10).await.unwrap();
+        assert_eq!(sensors.iter().map(|r| r.timestamp_ms).collect::<Vec<_>>(), [2_000, 1_000]);
+        assert!(restored.get_prediction(id).await.unwrap().acknowledged);
+        // IDs continue after the restored predictions
+        assert_eq!(restored.insert_prediction(prediction_at(3_000, "low")).await.unwrap(), id + 1);
+
+        std::fs::write(path, b"{\"sensors\": [").unwrap();
+        assert!(matches!(restored.load_snapshot(path), Err(StorageError::SerializationError(_))));
+        assert_eq!(restored.prediction_count().await.unwrap(), 2);
+        std::fs::remove_file(path).unwrap();
+        assert!(matches!(restored.load_snapshot(path), Err(StorageError::Io(_))));
     }
 }
 
@@ -246,6 +2967,11 @@ impl Default for SensorRecord {
             maf: 0.0,
             fuel_trim_short: 0.0,
             fuel_trim_long: 0.0,
+            trip_id: None,
+            intake_temp: None,
+            fuel_level: None,
+            o2_lambda: None,
+            throttle_pos: None,
         }
     }
 }
@@ -0,0 +1,186 @@
+//! SQLite Schema Migrations
+//!
+//! Each entry in [`MIGRATIONS`] upgrades the schema by one version and is
+//! applied at most once, in its own transaction. The applied version lives
+//! in `schema_version`, so a database written by an older build opens and
+//! is brought forward in place. Databases created before versioning have
+//! no version table; the first migration only creates what is missing, so
+//! their existing rows are left untouched.
+//!
+//! Columns added after the first release are nullable with a `NULL`
+//! default: rows logged before a sensor was supported read back as
+//! missing, not as zero.
+
+use crate::StorageError;
+use sqlx::{Connection, SqliteConnection};
+use tracing::info;
+
+/// Schema migrations, in order; migration `i` upgrades to version `i + 1`
+pub const MIGRATIONS: &[&str] = &[
+    // 1: original sensor and prediction tables
+    "CREATE TABLE IF NOT EXISTS sensor_log (
+        timestamp_ms INTEGER NOT NULL,
+        rpm INTEGER NOT NULL,
+        speed INTEGER NOT NULL,
+        coolant_temp INTEGER NOT NULL,
+        engine_load INTEGER NOT NULL,
+        maf REAL NOT NULL,
+        fuel_trim_short REAL NOT NULL,
+        fuel_trim_long REAL NOT NULL,
+        trip_id INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS idx_sensor_log_timestamp ON sensor_log (timestamp_ms);
+    CREATE TABLE IF NOT EXISTS predictions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp_ms INTEGER NOT NULL,
+        fault_class TEXT NOT NULL,
+        confidence REAL NOT NULL CHECK (confidence >= 0.0 AND confidence <= 1.0),
+        severity TEXT NOT NULL,
+        snapshot_start_ms INTEGER,
+        snapshot_end_ms INTEGER
+    );",
+    // 2: intake temperature, fuel level, wideband O2 and throttle
+    "ALTER TABLE sensor_log ADD COLUMN intake_temp INTEGER DEFAULT NULL;
+    ALTER TABLE sensor_log ADD COLUMN fuel_level REAL DEFAULT NULL;
+    ALTER TABLE sensor_log ADD COLUMN o2_lambda REAL DEFAULT NULL;
+    ALTER TABLE sensor_log ADD COLUMN throttle_pos REAL DEFAULT NULL;",
+    // 3: fused driving events
+    "CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp_ms INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        severity TEXT,
+        payload TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events (timestamp_ms);",
+    // 4: per-vehicle calibrations
+    "CREATE TABLE IF NOT EXISTS calibrations (
+        vehicle_id TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        updated_ms INTEGER NOT NULL,
+        PRIMARY KEY (vehicle_id, kind)
+    );",
+    // 5: trips, and the trip each event happened on
+    "CREATE TABLE IF NOT EXISTS trips (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        start_ms INTEGER NOT NULL,
+        end_ms INTEGER CHECK (end_ms IS NULL OR end_ms >= start_ms),
+        distance_km REAL NOT NULL DEFAULT 0.0
+    );
+    CREATE INDEX IF NOT EXISTS idx_sensor_log_trip ON sensor_log (trip_id);
+    ALTER TABLE events ADD COLUMN trip_id INTEGER DEFAULT NULL;
+    CREATE INDEX IF NOT EXISTS idx_events_trip ON events (trip_id);",
+    // 6: time-range scans over predictions
+    "CREATE INDEX IF NOT EXISTS idx_predictions_timestamp ON predictions (timestamp_ms);",
+    // 7: prediction acknowledgement, which must survive a restart
+    "ALTER TABLE predictions ADD COLUMN acknowledged INTEGER NOT NULL DEFAULT 0;",
+    // 8: store-and-forward outbox, so queued uploads survive power loss
+    "CREATE TABLE IF NOT EXISTS outbox (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        topic TEXT NOT NULL,
+        payload BLOB NOT NULL,
+        created_ms INTEGER NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        next_attempt_ms INTEGER NOT NULL,
+        dead_letter INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX IF NOT EXISTS idx_outbox_due ON outbox (dead_letter, next_attempt_ms);",
+    // 9: upload priority of queued messages
+    "ALTER TABLE outbox ADD COLUMN critical INTEGER NOT NULL DEFAULT 0;",
+    // 10: highest message sequence number reserved per vehicle
+    "CREATE TABLE IF NOT EXISTS sequence_reservations (
+        vehicle_id TEXT PRIMARY KEY,
+        reserved INTEGER NOT NULL
+    );",
+];
+
+/// Schema version of this build
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Version recorded in the database, 0 if it predates versioning
+pub async fn schema_version(conn: &mut SqliteConnection) -> Result<u32, StorageError> {
+    sqlx::query("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)")
+        .execute(&mut *conn)
+        .await?;
+    let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_version")
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(version.unwrap_or(0) as u32)
+}
+
+/// Apply all pending migrations, returning the resulting version
+///
+/// A database from a newer build is rejected rather than written with a
+/// schema this build does not understand.
+pub async fn migrate(conn: &mut SqliteConnection) -> Result<u32, StorageError> {
+    let current = schema_version(conn).await?;
+    if current > SCHEMA_VERSION {
+        return Err(StorageError::Migration(format!(
+            "database schema version {current} is newer than supported version {SCHEMA_VERSION}"
+        )));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = index as u32 + 1;
+        let mut tx = conn.begin().await?;
+        sqlx::raw_sql(migration)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| StorageError::Migration(format!("version {version}: {e}")))?;
+        sqlx::query("INSERT INTO schema_version (version) VALUES (?)")
+            .bind(version as i64)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        info!("Migrated database schema to version {}", version);
+    }
+
+    Ok(SCHEMA_VERSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Row;
+
+    #[tokio::test]
+    async fn test_migrates_old_schema_preserving_rows() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+
+        // A database written before versioning and the new sensor columns
+        sqlx::raw_sql(MIGRATIONS[0]).execute(&mut conn).await.unwrap();
+        sqlx::query(
+            "INSERT INTO sensor_log (timestamp_ms, rpm, speed, coolant_temp, engine_load, maf,
+                fuel_trim_short, fuel_trim_long) VALUES (1000, 2500, 60, 90, 40, 12.5, 1.0, -2.0)",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        assert_eq!(schema_version(&mut conn).await.unwrap(), 0);
+        assert_eq!(migrate(&mut conn).await.unwrap(), SCHEMA_VERSION);
+        assert_eq!(schema_version(&mut conn).await.unwrap(), SCHEMA_VERSION);
+
+        let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info('sensor_log')")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        for column in ["intake_temp", "fuel_level", "o2_lambda", "throttle_pos"] {
+            assert!(columns.iter().any(|c| c == column), "missing column {column}");
+        }
+
+        // The old row survives; the new columns read back as missing
+        let row = sqlx::query("SELECT rpm, maf, intake_temp, throttle_pos FROM sensor_log")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(row.get::<i64, _>("rpm"), 2500);
+        assert_eq!(row.get::<f64, _>("maf"), 12.5);
+        assert_eq!(row.get::<Option<i64>, _>("intake_temp"), None);
+        assert_eq!(row.get::<Option<f64>, _>("throttle_pos"), None);
+
+        // Re-running is a no-op
+        assert_eq!(migrate(&mut conn).await.unwrap(), SCHEMA_VERSION);
+    }
+}
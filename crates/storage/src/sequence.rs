@@ -0,0 +1,170 @@
+//! Persistent Sequence Counter
+//!
+//! Numbers every message a vehicle sends so the backend can spot gaps and
+//! ask for a replay. Numbers are reserved in storage a block at a time and
+//! handed out from memory, so only one write in [`RESERVE_BLOCK`] reaches
+//! flash. [`SequenceCounter::close`] records the exact last number on a
+//! clean shutdown, so numbering continues without a gap. Only after a
+//! crash does it resume above the last reservation, skipping the unissued
+//! rest of the block; no number is ever issued twice.
+
+use crate::{Storage, StorageError};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+/// How many numbers one reservation covers
+const RESERVE_BLOCK: u64 = 1000;
+
+/// Numbers handed out and reserved so far
+#[derive(Debug)]
+struct Issued {
+    /// Last value handed out (0 before the first)
+    last: u64,
+    /// Highest value that may be handed out before reserving again
+    reserved: u64,
+}
+
+/// Monotonic per-vehicle message counter
+pub struct SequenceCounter {
+    /// Storage holding the reservation; `None` keeps the counter in memory
+    /// only
+    storage: Option<Arc<dyn Storage>>,
+    vehicle_id: String,
+    issued: Mutex<Issued>,
+}
+
+impl SequenceCounter {
+    /// Counter that is not persisted (tests, or vehicles without flash)
+    pub fn in_memory() -> Self {
+        Self {
+            storage: None,
+            vehicle_id: String::new(),
+            issued: Mutex::new(Issued {
+                last: 0,
+                reserved: u64::MAX,
+            }),
+        }
+    }
+
+    /// Open the counter for `vehicle_id` kept in `storage`, starting from
+    /// zero if nothing was reserved yet
+    pub async fn open(storage: Arc<dyn Storage>, vehicle_id: impl Into<String>) -> Result<Self, StorageError> {
+        let vehicle_id = vehicle_id.into();
+        let reserved = storage.reserved_sequence(&vehicle_id).await?;
+        info!("Sequence counter for {} resuming after {}", vehicle_id, reserved);
+
+        Ok(Self {
+            storage: Some(storage),
+            vehicle_id,
+            issued: Mutex::new(Issued {
+                last: reserved,
+                reserved,
+            }),
+        })
+    }
+
+    /// Issue the next sequence number, reserving a new block first when
+    /// the current one is used up
+    pub async fn next(&self) -> Result<u64, StorageError> {
+        let mut issued = self.issued.lock().await;
+        let next = issued.last + 1;
+        if next > issued.reserved {
+            if let Some(storage) = &self.storage {
+                let reserved = issued.last + RESERVE_BLOCK;
+                storage.reserve_sequence(&self.vehicle_id, reserved).await?;
+                debug!("Reserved sequence numbers up to {} for {}", reserved, self.vehicle_id);
+                issued.reserved = reserved;
+            }
+        }
+        issued.last = next;
+        Ok(next)
+    }
+
+    /// Last number issued, 0 if none
+    pub async fn last(&self) -> u64 {
+        self.issued.lock().await.last
+    }
+
+    /// Shrink the reservation to the last number issued, so the next start
+    /// continues right after it; call on a clean shutdown
+    ///
+    /// Issuing more numbers afterwards is fine, it just reserves a new block.
+    pub async fn close(&self) -> Result<(), StorageError> {
+        let mut issued = self.issued.lock().await;
+        if let Some(storage) = &self.storage {
+            storage.reserve_sequence(&self.vehicle_id, issued.last).await?;
+            debug!("Released sequence numbers above {} for {}", issued.last, self.vehicle_id);
+            issued.reserved = issued.last;
+        }
+        Ok(())
+    }
+}
+
+impl Default for SequenceCounter {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Repository;
+
+    #[tokio::test]
+    async fn test_sequence_survives_restart_without_reuse() {
+        let path = std::env::temp_dir().join(format!("sequence-{}.db", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let remove_db = || {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{path}{suffix}"));
+            }
+        };
+        remove_db();
+        let open = || async { Arc::new(Repository::with_sqlite(&path).await.unwrap()) };
+
+        let mut issued = Vec::new();
+        {
+            let repository = open().await;
+            let counter = SequenceCounter::open(repository.clone(), "van-7").await.unwrap();
+            for _ in 0..5 {
+                issued.push(counter.next().await.unwrap());
+            }
+            // One reservation covers the whole run
+            assert_eq!(repository.reserved_sequence("van-7").await.unwrap(), RESERVE_BLOCK);
+            assert_eq!(repository.reserved_sequence("truck-2").await.unwrap(), 0);
+            counter.close().await.unwrap();
+        }
+        // Clean restart: numbering continues without a gap
+        {
+            let repository = open().await;
+            let counter = SequenceCounter::open(repository, "van-7").await.unwrap();
+            assert_eq!(counter.last().await, 5);
+            for _ in 0..5 {
+                issued.push(counter.next().await.unwrap());
+            }
+            // Crash: the counter is dropped without closing
+        }
+        let repository = open().await;
+        let counter = SequenceCounter::open(repository.clone(), "van-7").await.unwrap();
+        issued.push(counter.next().await.unwrap());
+
+        assert!(issued.windows(2).all(|w| w[0] < w[1]), "{issued:?}");
+        assert_eq!(issued[..10], [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(issued[10], 5 + RESERVE_BLOCK + 1);
+        assert_eq!(repository.reserved_sequence("van-7").await.unwrap(), 5 + 2 * RESERVE_BLOCK);
+        drop(repository);
+        remove_db();
+    }
+
+    #[tokio::test]
+    async fn test_block_is_renewed_when_used_up() {
+        let repository = Arc::new(Repository::new());
+        let counter = SequenceCounter::open(repository.clone(), "van-7").await.unwrap();
+        for expected in 1..=RESERVE_BLOCK + 1 {
+            assert_eq!(counter.next().await.unwrap(), expected);
+        }
+        assert_eq!(repository.reserved_sequence("van-7").await.unwrap(), 2 * RESERVE_BLOCK);
+    }
+}
@@ -70,6 +70,8 @@ typedef enum {
     CAM_ERROR_BUFFER = -3,
     CAM_ERROR_STREAM = -4,
     CAM_ERROR_CAPTURE = -5,
+    CAM_ERROR_CONTROL = -6,
+    CAM_ERROR_INVALID_ARG = -7,
     CAM_ERROR_NOT_INITIALIZED = -10,
     CAM_ERROR_TIMEOUT = -11,
     CAM_ERROR_UNKNOWN = -99,
@@ -8,6 +8,7 @@
 
 #include "camera_capture.h"
 
+#include <algorithm>
 #include <cstring>
 #include <mutex>
 #include <thread>
@@ -318,6 +319,79 @@ public:
     }
 
     bool is_streaming() const { return streaming_; }
+
+    int set_ir(int enable) {
+        std::lock_guard<std::mutex> lock(mutex_);
+
+        if (!initialized_) {
+            return CAM_ERROR_NOT_INITIALIZED;
+        }
+
+#ifdef __linux__
+        // Same control init() uses: AWB off while the IR illuminator is lit
+        if (set_control(V4L2_CID_AUTO_WHITE_BALANCE, enable ? 0 : 1) < 0) {
+            set_error("Failed to switch IR mode");
+            return CAM_ERROR_CONTROL;
+        }
+#endif
+
+        config_.enable_ir = enable ? 1 : 0;
+        return CAM_OK;
+    }
+
+    int set_exposure(uint32_t exposure_us) {
+        std::lock_guard<std::mutex> lock(mutex_);
+
+        if (!initialized_) {
+            return CAM_ERROR_NOT_INITIALIZED;
+        }
+        if (exposure_us == 0) {
+            set_error("Exposure must be positive");
+            return CAM_ERROR_INVALID_ARG;
+        }
+
+#ifdef __linux__
+        // V4L2 absolute exposure is in 100 us units, honoured only in manual mode
+        int32_t units = std::max<uint32_t>(exposure_us / 100, 1);
+        if (set_control(V4L2_CID_EXPOSURE_AUTO, V4L2_EXPOSURE_MANUAL) < 0 ||
+            set_control(V4L2_CID_EXPOSURE_ABSOLUTE, units) < 0) {
+            set_error("Failed to set exposure");
+            return CAM_ERROR_CONTROL;
+        }
+#endif
+
+        return CAM_OK;
+    }
+
+    int set_gain(uint32_t gain_pct) {
+        std::lock_guard<std::mutex> lock(mutex_);
+
+        if (!initialized_) {
+            return CAM_ERROR_NOT_INITIALIZED;
+        }
+        if (gain_pct == 0) {
+            set_error("Gain must be positive");
+            return CAM_ERROR_INVALID_ARG;
+        }
+
+#ifdef __linux__
+        // Gain units are driver-specific; take the driver default as unity
+        v4l2_queryctrl query = {};
+        query.id = V4L2_CID_GAIN;
+        if (ioctl(fd_, VIDIOC_QUERYCTRL, &query) < 0) {
+            set_error("Camera has no gain control");
+            return CAM_ERROR_CONTROL;
+        }
+        int64_t value = static_cast<int64_t>(query.default_value) * gain_pct / 100;
+        value = std::clamp<int64_t>(value, query.minimum, query.maximum);
+        if (set_control(V4L2_CID_GAIN, static_cast<int32_t>(value)) < 0) {
+            set_error("Failed to set gain");
+            return CAM_ERROR_CONTROL;
+        }
+#endif
+
+        return CAM_OK;
+    }
     
     void set_error(const char* msg) {
         std::strncpy(last_error_, msg, sizeof(last_error_) - 1);
@@ -339,6 +413,13 @@ private:
     }
 
 #ifdef __linux__
+    int set_control(uint32_t id, int32_t value) {
+        v4l2_control ctrl = {};
+        ctrl.id = id;
+        ctrl.value = value;
+        return ioctl(fd_, VIDIOC_S_CTRL, &ctrl);
+    }
+
     void cleanup_buffers() {
         for (auto& buf : buffers_) {
             if (buf.data && buf.data != MAP_FAILED) {
@@ -411,4 +492,16 @@ const char* cabin_camera_last_error() {
     return get_cabin_camera()->get_error();
 }
 
+int cabin_camera_set_ir(int enable) {
+    return get_cabin_camera()->set_ir(enable);
+}
+
+int cabin_camera_set_exposure(uint32_t exposure_us) {
+    return get_cabin_camera()->set_exposure(exposure_us);
+}
+
+int cabin_camera_set_gain(uint32_t gain_pct) {
+    return get_cabin_camera()->set_gain(gain_pct);
+}
+
 } // extern "C"